@@ -15,7 +15,7 @@ use axum::{
 };
 use rustrtc::{
     PeerConnection, RtcConfiguration, SdpType, SessionDescription,
-    media::{MediaSample, MediaStreamTrack},
+    media::{AudioSampleFormat, MediaSample, MediaStreamTrack},
 };
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
@@ -83,6 +83,10 @@ async fn offer(Json(payload): Json<OfferRequest>) -> impl IntoResponse {
         },
     ];
     config.media_capabilities = Some(caps);
+    // With `--features opus`, Opus arrives decoded to S16 PCM.
+    config.depacketizer_strategy = rustrtc::config::DepacketizerStrategy {
+        factory: std::sync::Arc::new(rustrtc::media::CodecDepacketizerFactory),
+    };
 
     let pc = PeerConnection::new(config);
 
@@ -103,7 +107,7 @@ async fn offer(Json(payload): Json<OfferRequest>) -> impl IntoResponse {
                 let pc_clone = pc.clone();
                 tokio::spawn(async move {
                     info!("Starting audio recording loop");
-                    let mut file = File::create("output.ulaw").await.unwrap();
+                    let mut file = None;
                     let mut ice_state_rx = pc_clone.subscribe_ice_connection_state();
                     let mut packets_received = 0;
 
@@ -117,8 +121,19 @@ async fn offer(Json(payload): Json<OfferRequest>) -> impl IntoResponse {
                                             if packets_received == 1 {
                                                 info!("Received first audio packet: {} bytes, PT: {:?}", frame.data.len(), frame.payload_type);
                                             }
-                                            // Note: If this is OPUS, writing it directly to .ulaw file will result in noise,
-                                            // but at least we verify data reception.
+                                            // PCMU is written as received; decoded Opus as raw S16LE
+                                            // (play with `ffplay -f s16le -ar 48000 -ac <channels>`).
+                                            if file.is_none() {
+                                                let path = match frame.format {
+                                                    AudioSampleFormat::S16 => "output.pcm",
+                                                    AudioSampleFormat::Encoded => "output.ulaw",
+                                                };
+                                                info!("Writing {:?} audio to {}", frame.format, path);
+                                                file = Some(File::create(path).await.unwrap());
+                                            }
+                                            let Some(file) = file.as_mut() else {
+                                                break;
+                                            };
                                             if let Err(e) = file.write_all(&frame.data).await {
                                                 warn!("Failed to write audio: {}", e);
                                                 break;
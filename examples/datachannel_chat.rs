@@ -167,6 +167,7 @@ async fn offer(
                             info!("Data channel closed for client {}", my_id);
                             break;
                         }
+                        Some(DataChannelEvent::BufferedAmountLow) => {}
                     }
                 }
                 res = ice_state_rx.changed() => {
@@ -188,6 +188,7 @@ async fn handle_rustrtc_offer(payload: OfferRequest) -> Json<OfferResponse> {
                         info!("Data channel closed");
                         break;
                     }
+                    rustrtc::DataChannelEvent::BufferedAmountLow => {}
                 }
             }
         });
@@ -335,6 +336,9 @@ struct IvfHeader {
     timebase_denominator: u32,
 }
 
+/// Playback interval of the IVF file (30 fps).
+const FRAME_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 30);
+
 struct IvfSource {
     reader: IVFReader<BufReader<File>>,
     header: IvfHeader,
@@ -350,7 +354,7 @@ impl IvfSource {
         rtp_timestamp_offset: u32,
         last_timestamp_state: Arc<AtomicU32>,
     ) -> Self {
-        let mut interval = tokio::time::interval(Duration::from_millis(33));
+        let mut interval = tokio::time::interval(FRAME_INTERVAL);
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
         Self {
@@ -432,13 +436,16 @@ async fn start_video_playback(pc: PeerConnection, vp8_pt: u8) {
         let (sample_source, outgoing_track, _) = media::sample_track(MediaStreamKind::Video, 120);
 
         let ssrc = 5000 + transceiver.id() as u32;
+        let params = rustrtc::RtpCodecParameters {
+            payload_type: vp8_pt,
+            clock_rate: 90000,
+            channels: 0,
+        };
+        // A restarted file continues one frame after the last timestamp sent.
+        let frame_ticks = params.ts_from_duration(FRAME_INTERVAL);
         let sender = rustrtc::peer_connection::RtpSender::builder(outgoing_track.clone(), ssrc)
             .stream_id("stream".to_string())
-            .params(rustrtc::RtpCodecParameters {
-                payload_type: vp8_pt,
-                clock_rate: 90000,
-                channels: 0,
-            })
+            .params(params)
             .build();
 
         let mut rtcp_rx = sender.subscribe_rtcp();
@@ -496,21 +503,20 @@ async fn start_video_playback(pc: PeerConnection, vp8_pt: u8) {
                 let packetizer = Box::new(Packetizer::new(source, 1200, Box::new(Vp8Payloader)));
                 let sink = Arc::new(TrackMediaSink::new(Arc::new(sample_source.clone())));
 
-                let pump = spawn_media_pump(packetizer, sink).unwrap();
+                let mut pump = spawn_media_pump(packetizer, sink).unwrap();
 
                 tokio::select! {
-                    _ = pump => {
+                    _ = &mut pump => {
                         info!("Play done");
                         // Finished naturally
                         let last = last_rtp_timestamp.load(Ordering::SeqCst);
-                        rtp_timestamp_offset = last.wrapping_add(3000);
+                        rtp_timestamp_offset = last.wrapping_add(frame_ticks);
                     }
                     result = rtcp_rx.recv() => {
                         if let Ok(rustrtc::rtp::RtcpPacket::PictureLossIndication(_)) = result {
                             info!("Received PLI, restarting video to send keyframe");
                             let last = last_rtp_timestamp.load(Ordering::SeqCst);
-                            rtp_timestamp_offset = last.wrapping_add(3000);
-                            // Pump will be dropped and aborted when we loop
+                            rtp_timestamp_offset = last.wrapping_add(frame_ticks);
                         }
                     }
                     res = ice_state_rx_loop.changed() => {
@@ -518,14 +524,17 @@ async fn start_video_playback(pc: PeerConnection, vp8_pt: u8) {
                             let state = *ice_state_rx_loop.borrow();
                             if state == rustrtc::IceConnectionState::Disconnected || state == rustrtc::IceConnectionState::Failed || state == rustrtc::IceConnectionState::Closed {
                                 info!("Stopping playback due to connection state: {:?}", state);
+                                pump.stop();
                                 return;
                             }
                             // Ignore other state changes (e.g. Connected -> Completed)
                         } else {
+                            pump.stop();
                             return;
                         }
                     }
                 }
+                pump.stop();
             }
         });
     }
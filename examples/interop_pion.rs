@@ -93,6 +93,7 @@ async fn handle_offer(Json(payload): Json<OfferRequest>) -> impl IntoResponse {
                                     info!("DataChannel closed");
                                     break;
                                 }
+                                rustrtc::DataChannelEvent::BufferedAmountLow => {}
                             }
                         }
                     });
@@ -109,6 +110,10 @@ async fn handle_offer(Json(payload): Json<OfferRequest>) -> impl IntoResponse {
                         });
                     }
                 }
+                PeerConnectionEvent::RealtimeText(_)
+                | PeerConnectionEvent::IceCandidate(_)
+                | PeerConnectionEvent::EndOfCandidates
+                | PeerConnectionEvent::SctpConnected => {}
             }
         }
     });
@@ -101,7 +101,11 @@ a=mid:0
                             return;
                         }
                     }
-                    PeerConnectionEvent::DataChannel(_) => {
+                    PeerConnectionEvent::DataChannel(_)
+                    | PeerConnectionEvent::RealtimeText(_)
+                    | PeerConnectionEvent::IceCandidate(_)
+                    | PeerConnectionEvent::EndOfCandidates
+                    | PeerConnectionEvent::SctpConnected => {
                         // Not relevant for RTP mode
                     }
                 }
@@ -544,6 +544,10 @@ async fn setup_new_peer(peer: Arc<Peer>, room: Arc<Room>) {
                         handle_chat_datachannel(dc, peer_clone.clone(), room_clone.clone()).await;
                     }
                 }
+                PeerConnectionEvent::RealtimeText(_)
+                | PeerConnectionEvent::IceCandidate(_)
+                | PeerConnectionEvent::EndOfCandidates
+                | PeerConnectionEvent::SctpConnected => {}
             }
         }
 
@@ -148,25 +148,31 @@ async fn main() -> anyhow::Result<()> {
     let send_start = Instant::now();
     let max_duration = Duration::from_secs(10);
 
+    // Pace on the channel's send queue: above the high-water mark, wait for
+    // BufferedAmountLow instead of pushing more data.
+    let high_water = 1024 * 1024;
+    dc1.set_buffered_amount_low_threshold(high_water / 2);
+
     while sent_bytes < total_bytes {
         if send_start.elapsed() >= max_duration {
             println!("Time limit reached (10s)");
             break;
         }
 
-        // We need to handle backpressure or just blast it?
-        // SCTP should handle flow control, but if we fill the buffer too fast we might get errors or block.
-        // rustrtc's send might be async or blocking?
-        // Let's check the API.
+        if dc1.buffered_amount() > high_water {
+            while !matches!(
+                dc1.recv().await,
+                Some(rustrtc::DataChannelEvent::BufferedAmountLow) | None
+            ) {}
+        }
+
         match pc1.send_data(dc1.id, &data).await {
             Ok(_) => {
                 sent_bytes += data.len();
             }
             Err(e) => {
-                // If buffer is full, we might need to wait.
-                // For now, let's just print error and break or retry.
                 println!("Send error: {}", e);
-                tokio::time::sleep(Duration::from_millis(1)).await;
+                break;
             }
         }
     }
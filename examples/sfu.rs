@@ -0,0 +1,312 @@
+//! Minimal multi-party SFU over plain RTP.
+//!
+//! Every participant publishes one video track with its first offer. The SFU
+//! fans each publication out through a [`MediaRelay`] and adds one send-only
+//! m-line per subscriber, then re-offers to that subscriber. The SFU assigns
+//! the SSRC of every forwarded stream itself, so a subscriber sees one SSRC
+//! per remote participant regardless of what the publishers use. PLI/FIR from
+//! a subscriber is relayed back to the publisher.
+//!
+//! `main` runs the SFU with three in-process participants on loopback.
+//!
+//! Run with: cargo run --example sfu
+use rustrtc::media::MediaStreamTrack;
+use rustrtc::media::frame::{MediaKind, MediaSample, VideoFrame};
+use rustrtc::media::track::{MediaRelay, SampleStreamSource, sample_track};
+use rustrtc::peer_connection::RtpReceiver;
+use rustrtc::rtp::RtcpPacket;
+use rustrtc::{
+    MediaKind as SdpMediaKind, PeerConnection, RtcConfiguration, RtcError, RtcResult,
+    RtpCodecParameters, RtpSender, SessionDescription, TransceiverDirection, TransportMode,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, mpsc};
+use tracing::{info, warn};
+
+/// Frame rate of the demo's synthetic video.
+const FRAME_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 30);
+
+/// Codec every participant publishes (VP8).
+const VIDEO_PARAMS: RtpCodecParameters = RtpCodecParameters {
+    payload_type: 96,
+    clock_rate: 90000,
+    channels: 0,
+};
+
+fn rtp_config(ssrc_start: u32) -> RtcConfiguration {
+    RtcConfiguration {
+        transport_mode: TransportMode::Rtp,
+        bind_ip: Some("127.0.0.1".to_string()),
+        ssrc_start,
+        ..Default::default()
+    }
+}
+
+#[derive(Default)]
+struct Negotiation {
+    /// An offer was sent and its answer has not arrived yet.
+    offer_pending: bool,
+    /// Tracks changed while an offer was pending.
+    needs_offer: bool,
+}
+
+struct Participant {
+    id: u32,
+    pc: PeerConnection,
+    /// Offers from the SFU to this participant.
+    signal: mpsc::UnboundedSender<SessionDescription>,
+    publication: Option<MediaRelay>,
+    /// Next SSRC for a stream forwarded to this participant.
+    next_ssrc: AtomicU32,
+    negotiation: Mutex<Negotiation>,
+}
+
+impl Participant {
+    /// Forward `relay` to this participant on a new send-only m-line.
+    fn subscribe(&self, publisher: u32, relay: &MediaRelay) -> RtcResult<()> {
+        let track = relay.subscribe();
+        // Not `add_track`: it would reuse the participant's own publication
+        // m-line, which has no sender yet.
+        let transceiver = self
+            .pc
+            .add_transceiver(SdpMediaKind::Video, TransceiverDirection::SendOnly);
+        let ssrc = self.next_ssrc.fetch_add(1, Ordering::Relaxed);
+        let sender = RtpSender::builder(track.clone(), ssrc)
+            .stream_id(format!("participant-{}", publisher))
+            .params(VIDEO_PARAMS)
+            .build();
+        transceiver.set_sender(Some(sender.clone()));
+        info!(
+            "participant {} subscribed to {} as ssrc {}",
+            self.id,
+            publisher,
+            sender.ssrc()
+        );
+        let mut rtcp_rx = sender.subscribe_rtcp();
+        tokio::spawn(async move {
+            while let Ok(packet) = rtcp_rx.recv().await {
+                if matches!(
+                    packet,
+                    RtcpPacket::PictureLossIndication(_) | RtcpPacket::FullIntraRequest(_)
+                ) && let Err(e) = track.request_key_frame().await
+                {
+                    warn!("failed to relay key frame request to {}: {}", publisher, e);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn renegotiate(&self) -> RtcResult<()> {
+        let mut negotiation = self.negotiation.lock().await;
+        if negotiation.offer_pending {
+            negotiation.needs_offer = true;
+            return Ok(());
+        }
+        let offer = self.pc.create_offer().await?;
+        self.pc.set_local_description(offer.clone())?;
+        negotiation.offer_pending = true;
+        let _ = self.signal.send(offer);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Room {
+    next_id: u32,
+    participants: Vec<Arc<Participant>>,
+}
+
+pub struct Sfu {
+    room: Mutex<Room>,
+}
+
+impl Sfu {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            room: Mutex::new(Room::default()),
+        })
+    }
+
+    /// Answer a participant's first offer. Later offers from the SFU (for
+    /// new publications) are sent on `signal`; their answers go to
+    /// [`accept_answer`](Self::accept_answer).
+    pub async fn join(
+        &self,
+        offer: SessionDescription,
+        signal: mpsc::UnboundedSender<SessionDescription>,
+    ) -> RtcResult<(u32, SessionDescription)> {
+        let pc = PeerConnection::new(rtp_config(10000));
+        pc.set_remote_description(offer).await?;
+        let answer = pc.create_answer().await?;
+        pc.set_local_description(answer.clone())?;
+        let publication = pc
+            .get_transceivers()
+            .iter()
+            .find_map(|t| t.receiver())
+            .map(|receiver| MediaRelay::new(receiver.track()));
+
+        let mut room = self.room.lock().await;
+        let id = room.next_id;
+        room.next_id += 1;
+        let participant = Arc::new(Participant {
+            id,
+            pc,
+            signal,
+            publication,
+            next_ssrc: AtomicU32::new(0x5F00_0000 | (id << 8)),
+            negotiation: Mutex::new(Negotiation::default()),
+        });
+
+        for other in &room.participants {
+            if let Some(relay) = &other.publication {
+                participant.subscribe(other.id, relay)?;
+            }
+            if let Some(relay) = &participant.publication {
+                other.subscribe(id, relay)?;
+                other.renegotiate().await?;
+            }
+        }
+        if !room.participants.is_empty() {
+            participant.renegotiate().await?;
+        }
+        room.participants.push(participant);
+        info!("participant {} joined", id);
+        Ok((id, answer))
+    }
+
+    pub async fn accept_answer(&self, id: u32, answer: SessionDescription) -> RtcResult<()> {
+        let participant = self
+            .room
+            .lock()
+            .await
+            .participants
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| RtcError::Internal(format!("unknown participant {}", id)))?;
+        participant.pc.set_remote_description(answer).await?;
+        let again = {
+            let mut negotiation = participant.negotiation.lock().await;
+            negotiation.offer_pending = false;
+            std::mem::take(&mut negotiation.needs_offer)
+        };
+        if again {
+            participant.renegotiate().await?;
+        }
+        Ok(())
+    }
+}
+
+/// An RTP endpoint that publishes one video track and answers SFU offers.
+pub struct DemoPeer {
+    pub id: u32,
+    pub pc: PeerConnection,
+    pub sender: Arc<RtpSender>,
+    source: SampleStreamSource,
+}
+
+impl DemoPeer {
+    pub async fn join(sfu: &Arc<Sfu>, ssrc_start: u32) -> RtcResult<Self> {
+        let pc = PeerConnection::new(rtp_config(ssrc_start));
+        let (source, track, _) = sample_track(MediaKind::Video, 100);
+        let sender = pc.add_track(track, VIDEO_PARAMS)?;
+        let offer = pc.create_offer().await?;
+        pc.set_local_description(offer.clone())?;
+
+        let (signal_tx, mut signal_rx) = mpsc::unbounded_channel();
+        let (id, answer) = sfu.join(offer, signal_tx).await?;
+        pc.set_remote_description(answer).await?;
+
+        let (sfu, peer) = (sfu.clone(), pc.clone());
+        tokio::spawn(async move {
+            while let Some(offer) = signal_rx.recv().await {
+                let result = async {
+                    peer.set_remote_description(offer).await?;
+                    let answer = peer.create_answer().await?;
+                    peer.set_local_description(answer.clone())?;
+                    sfu.accept_answer(id, answer).await
+                };
+                if let Err(e) = result.await {
+                    warn!("participant {} failed to renegotiate: {}", id, e);
+                }
+            }
+        });
+
+        Ok(Self {
+            id,
+            pc,
+            sender,
+            source,
+        })
+    }
+
+    /// Send one single-packet frame whose payload is `tag` repeated.
+    pub fn send_frame(&self, tag: u8, index: u32) -> RtcResult<()> {
+        self.source
+            .send(MediaSample::Video(VideoFrame {
+                rtp_timestamp: VIDEO_PARAMS
+                    .ts_from_duration(FRAME_INTERVAL)
+                    .wrapping_mul(index),
+                is_last_packet: true,
+                data: vec![tag; 32].into(),
+                ..Default::default()
+            }))
+            .map_err(|e| RtcError::Internal(e.to_string()))
+    }
+
+    /// Receivers for the media forwarded from other participants.
+    pub fn forwarded_receivers(&self) -> Vec<Arc<RtpReceiver>> {
+        self.pc
+            .get_transceivers()
+            .iter()
+            .skip(1)
+            .filter_map(|t| t.receiver())
+            .collect()
+    }
+}
+
+#[tokio::main]
+async fn main() -> RtcResult<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter("warn,sfu=info")
+        .init();
+
+    let sfu = Sfu::new();
+    let mut peers = Vec::new();
+    for i in 0..3u32 {
+        peers.push(DemoPeer::join(&sfu, 1000 * (i + 1)).await?);
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    for index in 0..50 {
+        for peer in &peers {
+            peer.send_frame(peer.id as u8, index)?;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    for peer in &peers {
+        for receiver in peer.forwarded_receivers() {
+            let track = receiver.track();
+            let mut frames = 0;
+            let mut from = None;
+            while let Ok(Ok(MediaSample::Video(frame))) =
+                tokio::time::timeout(Duration::from_millis(100), track.recv()).await
+            {
+                frames += 1;
+                from = frame.data.first().copied();
+            }
+            println!(
+                "participant {} got {} frames from participant {:?} on ssrc {}",
+                peer.id,
+                frames,
+                from,
+                receiver.ssrc()
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,9 @@
+use crate::errors::{RtcError, RtcResult};
 use crate::media::depacketizer::{DefaultDepacketizerFactory, DepacketizerFactory};
 use crate::peer_connection::{RtpReceiverInterceptor, RtpSenderInterceptor};
+use crate::transports::ice::shared_udp::UdpMux;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
@@ -74,6 +77,54 @@ pub enum IceTcpPolicy {
     PassiveOnly,
 }
 
+/// ICE type preferences (RFC 8445 §5.1.2.1), the top byte of each local
+/// candidate's PRIORITY. Higher values sort first and win pair ordering and
+/// nomination; RFC 8445 limits them to 0..=126, and creating a description
+/// with a larger value fails with [`RtcError::InvalidConfiguration`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CandidateTypePreferences {
+    pub host: u8,
+    pub peer_reflexive: u8,
+    pub server_reflexive: u8,
+    pub relay: u8,
+}
+
+impl CandidateTypePreferences {
+    /// Highest type preference RFC 8445 §5.1.2.2 allows.
+    pub const MAX: u8 = 126;
+
+    /// Rejects preferences above [`Self::MAX`].
+    pub fn validate(&self) -> RtcResult<()> {
+        for (name, value) in [
+            ("host", self.host),
+            ("peer_reflexive", self.peer_reflexive),
+            ("server_reflexive", self.server_reflexive),
+            ("relay", self.relay),
+        ] {
+            if value > Self::MAX {
+                return Err(RtcError::InvalidConfiguration(format!(
+                    "{} type preference {} exceeds {}",
+                    name,
+                    value,
+                    Self::MAX
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for CandidateTypePreferences {
+    fn default() -> Self {
+        Self {
+            host: Self::MAX,
+            peer_reflexive: 110,
+            server_reflexive: 100,
+            relay: 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum BundlePolicy {
     #[default]
@@ -97,6 +148,21 @@ pub enum TransportMode {
     Rtp,
 }
 
+/// DTLS handshake role, as negotiated through `a=setup`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DtlsRole {
+    /// Initiate the handshake (`a=setup:active`).
+    Client,
+    /// Wait for the remote ClientHello (`a=setup:passive`).
+    Server,
+}
+
+impl DtlsRole {
+    pub fn is_client(&self) -> bool {
+        matches!(self, DtlsRole::Client)
+    }
+}
+
 /// Strategy for dropping packets when buffer is full.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum BufferDropStrategy {
@@ -209,6 +275,11 @@ pub struct VideoCapability {
     /// Default `None` preserves single-codec SDP; answers still accept remote RTX.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rtx_payload_type: Option<u8>,
+    /// FlexFEC-03 payload type. When set, SDP advertises
+    /// `a=rtpmap:<pt> flexfec-03/90000` and, once the remote agrees, repair
+    /// packets are sent on a separate SSRC and used to recover lost media.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flexfec_payload_type: Option<u8>,
 }
 
 impl Default for VideoCapability {
@@ -226,6 +297,7 @@ impl Default for VideoCapability {
                 "transport-cc".to_string(),
             ],
             rtx_payload_type: None,
+            flexfec_payload_type: None,
         }
     }
 }
@@ -239,6 +311,7 @@ impl VideoCapability {
             fmtp: Some("packetization-mode=1;profile-level-id=42e01f".to_string()),
             rtcp_fbs: vec!["nack pli".to_string(), "ccm fir".to_string()],
             rtx_payload_type: None,
+            flexfec_payload_type: None,
         }
     }
 
@@ -249,6 +322,14 @@ impl VideoCapability {
             ..Self::default()
         }
     }
+
+    /// VP8 protected by a FlexFEC-03 repair stream.
+    pub fn vp8_with_flexfec(flexfec_payload_type: u8) -> Self {
+        Self {
+            flexfec_payload_type: Some(flexfec_payload_type),
+            ..Self::default()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -356,6 +437,65 @@ impl Default for MediaCapabilities {
     }
 }
 
+impl MediaCapabilities {
+    /// Build a media section advertising these capabilities without a live
+    /// `PeerConnection`. Only codec lines are emitted; transport attributes
+    /// (ICE, DTLS, rtcp-mux, extmaps) are left to the caller.
+    pub fn to_media_section(
+        &self,
+        kind: crate::sdp::MediaKind,
+        mid: impl Into<String>,
+        direction: crate::sdp::Direction,
+    ) -> crate::sdp::MediaSection {
+        let mut section = crate::sdp::MediaSection::new(kind, mid);
+        section.direction = direction;
+        match kind {
+            crate::sdp::MediaKind::Audio => {
+                section.push_audio_capabilities(&self.audio_or_default())
+            }
+            crate::sdp::MediaKind::Video => {
+                section.push_video_capabilities(&self.video_or_default())
+            }
+            crate::sdp::MediaKind::Application => section.push_application_capability(
+                self.application.clone().unwrap_or_default().sctp_port,
+            ),
+            crate::sdp::MediaKind::Image => {
+                section.push_image_capabilities(&self.image_or_default())
+            }
+        }
+        section
+    }
+
+    /// Whether any video capability opts into FlexFEC.
+    pub(crate) fn flexfec_enabled(&self) -> bool {
+        self.video.iter().any(|c| c.flexfec_payload_type.is_some())
+    }
+
+    pub(crate) fn audio_or_default(&self) -> Vec<AudioCapability> {
+        if self.audio.is_empty() {
+            vec![AudioCapability::default()]
+        } else {
+            self.audio.clone()
+        }
+    }
+
+    pub(crate) fn video_or_default(&self) -> Vec<VideoCapability> {
+        if self.video.is_empty() {
+            vec![VideoCapability::default()]
+        } else {
+            self.video.clone()
+        }
+    }
+
+    pub(crate) fn image_or_default(&self) -> Vec<T38Capability> {
+        if self.image.is_empty() {
+            vec![T38Capability::default()]
+        } else {
+            self.image.clone()
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct DepacketizerStrategy {
     pub factory: Arc<dyn DepacketizerFactory>,
@@ -387,6 +527,18 @@ fn default_rtp_buffer_capacity() -> usize {
     100
 }
 
+fn default_sctp_sack_max_unacked() -> u32 {
+    2
+}
+
+fn default_nack_max_retries() -> u32 {
+    crate::peer_connection::DEFAULT_NACK_MAX_RETRIES
+}
+
+fn default_nack_retry_interval() -> std::time::Duration {
+    crate::peer_connection::DEFAULT_NACK_RETRY_INTERVAL
+}
+
 fn default_buffer_stats_log_interval() -> std::time::Duration {
     std::time::Duration::from_secs(10)
 }
@@ -403,6 +555,50 @@ pub enum SdpCompatibilityMode {
     LegacySip,
 }
 
+/// Media negotiation preset.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaProfile {
+    /// Negotiate according to the rest of the configuration (default).
+    #[default]
+    Standard,
+    /// Plain SIP gateway leg: `LegacySip` SDP (no BUNDLE, no `a=rtcp-mux`,
+    /// even when the remote offers it), exactly one codec per m-line and a
+    /// separate RTCP port. Under DTLS it advertises `a=setup:passive` unless
+    /// `dtls_role_override` says otherwise.
+    SipGateway,
+}
+
+/// How a receiver with no signaled SSRC treats RTP once it has adopted the
+/// SSRC of the first packet it was handed (RTP mode, `a=ssrc` absent).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsignaledSsrcPolicy {
+    /// Follow SSRC changes: any new SSRC keeps flowing to the same receiver
+    /// (default).
+    #[default]
+    Follow,
+    /// Keep the first SSRC. A different SSRC goes to a receiver that has not
+    /// adopted one yet (surfacing as a new track) or is dropped if none is
+    /// left.
+    Latch,
+}
+
+/// What a receiver does with RTP whose payload type is not in the negotiated
+/// payload map. Such packets are counted either way (see
+/// `RtpReceiver::unknown_payload_type_packets`), so a codec mismatch with the
+/// remote shows up without reading logs.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownPayloadTypePolicy {
+    /// Discard the packet (default).
+    #[default]
+    DropUnknownPt,
+    /// Deliver it with the receiver's default codec parameters and log the
+    /// first occurrence.
+    AcceptAndLog,
+}
+
 fn default_enable_upnp() -> bool {
     false
 }
@@ -425,6 +621,24 @@ pub struct RtcConfiguration {
     pub certificates: Vec<CertificateConfig>,
     pub transport_mode: TransportMode,
     pub nack_buffer_size: usize,
+    /// Times a missing packet is NACKed again after the first request
+    /// before the receiver gives up on it.
+    #[serde(default = "default_nack_max_retries")]
+    pub nack_max_retries: u32,
+    /// Minimum spacing between NACKs for the same packet, and between
+    /// retransmissions of it, standing in for one round trip.
+    #[serde(default = "default_nack_retry_interval")]
+    pub nack_retry_interval: std::time::Duration,
+    /// Target delay in milliseconds of the receive jitter buffer, which
+    /// reorders RTP packets by sequence number before depacketization.
+    /// Default: 0 — disabled, packets are depacketized in arrival order.
+    #[serde(default)]
+    pub jitter_buffer_ms: u32,
+    /// Target send rate in kbit/s of each RTP sender's pacer, which spaces
+    /// packets out instead of sending a frame as one burst. Default: None —
+    /// packets are sent as soon as they are produced.
+    #[serde(default)]
+    pub pacing_kbps: Option<u32>,
     pub media_capabilities: Option<MediaCapabilities>,
     /// Override the advertised IP address in SDP (for NAT traversal).
     /// When set, the `c=`, `o=`, and candidate addresses in the SDP will
@@ -452,6 +666,10 @@ pub struct RtcConfiguration {
     /// and reduce the probability of nomination failures under packet loss.
     pub nomination_timeout: std::time::Duration,
     pub ice_connection_timeout: std::time::Duration,
+    /// Overall deadline for ICE to leave the `Checking` state. If no candidate
+    /// pair has succeeded by then (e.g. only host candidates on disjoint
+    /// networks), the transport moves to `Failed`.
+    pub ice_checking_timeout: std::time::Duration,
     pub sctp_rto_initial: std::time::Duration,
     pub sctp_rto_min: std::time::Duration,
     pub sctp_rto_max: std::time::Duration,
@@ -462,9 +680,26 @@ pub struct RtcConfiguration {
     pub sctp_max_tsn_retransmits: u32,
     pub sctp_max_burst: usize,
     pub sctp_max_cwnd: usize,
+    /// Delayed SACK timer (RFC 4960 §6.2). Zero acknowledges every packet
+    /// carrying DATA immediately; RFC 4960 recommends 200ms and caps it at
+    /// 500ms.
+    #[serde(default)]
+    pub sctp_sack_delay: std::time::Duration,
+    /// Number of DATA-carrying packets after which a SACK is sent without
+    /// waiting for `sctp_sack_delay`.
+    #[serde(default = "default_sctp_sack_max_unacked")]
+    pub sctp_sack_max_unacked: u32,
     pub dtls_buffer_size: usize,
     pub rtp_start_port: Option<u16>,
     pub rtp_end_port: Option<u16>,
+    /// RTP/SRTP mode: bind the media socket of the given kind to exactly
+    /// this port instead of one from `rtp_start_port`..`rtp_end_port`, so
+    /// the `m=` line advertises a port known in advance. Negotiation fails
+    /// if the port cannot be bound. Under BUNDLE all sections share one
+    /// socket, which takes the port pinned for any of their kinds; pinning
+    /// bundled kinds to different ports is an error.
+    #[serde(default)]
+    pub fixed_media_ports: HashMap<crate::sdp::MediaKind, u16>,
     pub ice_gather_udp_hosts: bool,
     pub tcp_port_range_start: Option<u16>,
     pub tcp_port_range_end: Option<u16>,
@@ -496,10 +731,21 @@ pub struct RtcConfiguration {
     pub buffer_drop_strategy: BufferDropStrategy,
     #[serde(default = "default_buffer_stats_log_interval")]
     pub buffer_stats_log_interval: std::time::Duration,
+    /// Bytes of pooled storage each ICE read loop carves received packets
+    /// out of, so packets do not each need their own allocation. A packet
+    /// that is held on to (e.g. in a jitter buffer) pins its whole region,
+    /// so only enable this when receivers release packets promptly; 64 KiB
+    /// suits that case. Default: 0 (off, one allocation per packet).
+    #[serde(default)]
+    pub receive_buffer_pool_size: usize,
     /// Controls ICE TCP candidate support (RFC 6544).
     /// Default: Disabled — only UDP candidates are gathered and used.
     #[serde(default)]
     pub ice_tcp_policy: IceTcpPolicy,
+    /// Type preferences used to compute local candidate priorities, e.g. to
+    /// prefer relay over host. Default: the RFC 8445 recommended values.
+    #[serde(default)]
+    pub candidate_type_preferences: CandidateTypePreferences,
     /// Enable process-wide shared ICE UDP socket (single-port multiplexing).
     ///
     /// When `true`, multiple `PeerConnection`s share one `UdpSocket` bound to
@@ -515,9 +761,37 @@ pub struct RtcConfiguration {
     /// is enabled. All `PeerConnection`s sharing this port must agree on it.
     #[serde(default)]
     pub ice_udp_mux_port: Option<u16>,
+    /// Caller-owned shared UDP socket. When set, the PeerConnection gathers its
+    /// single host candidate on this socket (see [`UdpMux`]) and takes
+    /// precedence over `ice_udp_mux` / `ice_udp_mux_port`.
+    #[serde(skip, default)]
+    pub udp_mux: Option<UdpMux>,
     /// SDP generation compatibility mode.
     #[serde(default)]
     pub sdp_compatibility: SdpCompatibilityMode,
+    /// Negotiation preset layered over the other options.
+    #[serde(default)]
+    pub profile: MediaProfile,
+    /// Force the local DTLS role instead of deriving it from the remote
+    /// `a=setup`. Offers and answers advertise the matching `active` /
+    /// `passive` value, and a remote description taking the same role is
+    /// rejected. Default: None (offer `actpass`, follow the remote).
+    #[serde(default)]
+    pub dtls_role_override: Option<DtlsRole>,
+    /// Handling of unsignaled SSRCs after the first one is adopted.
+    #[serde(default)]
+    pub unsignaled_ssrc_policy: UnsignaledSsrcPolicy,
+    /// Handling of RTP carrying a payload type that was not negotiated.
+    #[serde(default)]
+    pub unknown_payload_type_policy: UnknownPayloadTypePolicy,
+    /// Forward RTCP packets the stack does not interpret (APP, XR, unknown
+    /// feedback formats) to `PeerConnection::subscribe_unknown_rtcp`, e.g.
+    /// for an SFU relaying them to the other leg. Default: false (dropped).
+    #[serde(default)]
+    pub forward_unknown_rtcp: bool,
+    /// Username for the SDP `o=` line. Default: None (`-`).
+    #[serde(default)]
+    pub sdp_origin_username: Option<String>,
     #[serde(skip, default)]
     pub label: Option<String>,
     #[serde(skip, default)]
@@ -540,6 +814,10 @@ impl Default for RtcConfiguration {
             certificates: Vec::new(),
             transport_mode: TransportMode::default(),
             nack_buffer_size: 200,
+            nack_max_retries: default_nack_max_retries(),
+            nack_retry_interval: default_nack_retry_interval(),
+            jitter_buffer_ms: 0,
+            pacing_kbps: None,
             media_capabilities: None,
             external_ip: None,
             external_port: None,
@@ -549,6 +827,7 @@ impl Default for RtcConfiguration {
             stun_timeout: std::time::Duration::from_secs(5),
             nomination_timeout: std::time::Duration::from_secs(10),
             ice_connection_timeout: std::time::Duration::from_secs(30),
+            ice_checking_timeout: std::time::Duration::from_secs(30),
             sctp_rto_initial: std::time::Duration::from_secs(3),
             sctp_rto_min: std::time::Duration::from_secs(1),
             sctp_rto_max: std::time::Duration::from_secs(60),
@@ -559,9 +838,12 @@ impl Default for RtcConfiguration {
             sctp_max_tsn_retransmits: 8,
             sctp_max_burst: 0,         // 0 = use default heuristic
             sctp_max_cwnd: 256 * 1024, // 256 KB
+            sctp_sack_delay: std::time::Duration::ZERO,
+            sctp_sack_max_unacked: default_sctp_sack_max_unacked(),
             dtls_buffer_size: 2048,
             rtp_start_port: None,
             rtp_end_port: None,
+            fixed_media_ports: HashMap::new(),
             ice_gather_udp_hosts: true,
             tcp_port_range_start: None,
             tcp_port_range_end: None,
@@ -576,10 +858,19 @@ impl Default for RtcConfiguration {
             rtp_buffer_capacity: default_rtp_buffer_capacity(),
             buffer_drop_strategy: BufferDropStrategy::default(),
             buffer_stats_log_interval: default_buffer_stats_log_interval(),
+            receive_buffer_pool_size: 0,
             ice_tcp_policy: IceTcpPolicy::default(),
+            candidate_type_preferences: CandidateTypePreferences::default(),
             ice_udp_mux: false,
             ice_udp_mux_port: None,
+            udp_mux: None,
             sdp_compatibility: SdpCompatibilityMode::default(),
+            profile: MediaProfile::default(),
+            dtls_role_override: None,
+            unsignaled_ssrc_policy: UnsignaledSsrcPolicy::default(),
+            unknown_payload_type_policy: UnknownPayloadTypePolicy::default(),
+            forward_unknown_rtcp: false,
+            sdp_origin_username: None,
             label: None,
             cname: None,
             recorder_interceptors: RecorderInterceptors::default(),
@@ -587,6 +878,57 @@ impl Default for RtcConfiguration {
     }
 }
 
+impl RtcConfiguration {
+    /// SDP compatibility in effect; the `SipGateway` profile implies
+    /// `LegacySip`.
+    pub fn effective_sdp_compatibility(&self) -> SdpCompatibilityMode {
+        match self.profile {
+            MediaProfile::SipGateway => SdpCompatibilityMode::LegacySip,
+            MediaProfile::Standard => self.sdp_compatibility.clone(),
+        }
+    }
+
+    /// Local DTLS role to enforce: `dtls_role_override`, or the server
+    /// (`a=setup:passive`) role under the `SipGateway` profile.
+    pub fn effective_dtls_role(&self) -> Option<DtlsRole> {
+        self.dtls_role_override.or(match self.profile {
+            MediaProfile::SipGateway => Some(DtlsRole::Server),
+            MediaProfile::Standard => None,
+        })
+    }
+
+    /// Port configured for `kind` in `fixed_media_ports`, if any.
+    pub fn fixed_media_port(&self, kind: crate::sdp::MediaKind) -> Option<u16> {
+        self.fixed_media_ports.get(&kind).copied()
+    }
+
+    /// Port for a transport BUNDLE shares between `kinds`: the one fixed
+    /// port configured for any of them. Kinds pinned to different ports
+    /// cannot share a socket.
+    pub(crate) fn fixed_bundle_port(
+        &self,
+        kinds: impl IntoIterator<Item = crate::sdp::MediaKind>,
+    ) -> RtcResult<Option<u16>> {
+        let mut pinned: Option<(crate::sdp::MediaKind, u16)> = None;
+        for kind in kinds {
+            let Some(port) = self.fixed_media_port(kind) else {
+                continue;
+            };
+            match pinned {
+                Some((first, first_port)) if first_port != port => {
+                    return Err(RtcError::InvalidConfiguration(format!(
+                        "BUNDLE shares one port, but {:?} is pinned to {} and {:?} to {}",
+                        first, first_port, kind, port
+                    )));
+                }
+                Some(_) => {}
+                None => pinned = Some((kind, port)),
+            }
+        }
+        Ok(pinned.map(|(_, port)| port))
+    }
+}
+
 pub struct RtcConfigurationBuilder {
     inner: RtcConfiguration,
 }
@@ -726,6 +1068,25 @@ impl RtcConfigurationBuilder {
         self
     }
 
+    /// NACK a missing packet at most `1 + max_retries` times, waiting at
+    /// least `interval` between requests for it.
+    pub fn nack_retries(mut self, max_retries: u32, interval: std::time::Duration) -> Self {
+        self.inner.nack_max_retries = max_retries;
+        self.inner.nack_retry_interval = interval;
+        self
+    }
+
+    /// Reorder received packets, waiting up to `ms` for a missing one.
+    pub fn jitter_buffer_ms(mut self, ms: u32) -> Self {
+        self.inner.jitter_buffer_ms = ms;
+        self
+    }
+
+    pub fn pacing_kbps(mut self, kbps: u32) -> Self {
+        self.inner.pacing_kbps = Some(kbps);
+        self
+    }
+
     pub fn stun_timeout(mut self, timeout: std::time::Duration) -> Self {
         self.inner.stun_timeout = timeout;
         self
@@ -742,6 +1103,11 @@ impl RtcConfigurationBuilder {
         self
     }
 
+    pub fn fixed_media_port(mut self, kind: crate::sdp::MediaKind, port: u16) -> Self {
+        self.inner.fixed_media_ports.insert(kind, port);
+        self
+    }
+
     pub fn ice_gather_udp_hosts(mut self, enable: bool) -> Self {
         self.inner.ice_gather_udp_hosts = enable;
         self
@@ -809,11 +1175,25 @@ impl RtcConfigurationBuilder {
         self
     }
 
+    /// Enable delayed SACK: acknowledge DATA after at most `delay` (capped at
+    /// 500ms) or once `max_unacked` packets are outstanding, whichever comes
+    /// first. Gaps and duplicates are still acknowledged immediately.
+    pub fn sctp_delayed_sack(mut self, delay: std::time::Duration, max_unacked: u32) -> Self {
+        self.inner.sctp_sack_delay = delay;
+        self.inner.sctp_sack_max_unacked = max_unacked;
+        self
+    }
+
     pub fn ice_connection_timeout(mut self, timeout: std::time::Duration) -> Self {
         self.inner.ice_connection_timeout = timeout;
         self
     }
 
+    pub fn ice_checking_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.inner.ice_checking_timeout = timeout;
+        self
+    }
+
     pub fn rtp_buffer_capacity(mut self, capacity: usize) -> Self {
         self.inner.rtp_buffer_capacity = capacity;
         self
@@ -829,11 +1209,21 @@ impl RtcConfigurationBuilder {
         self
     }
 
+    pub fn receive_buffer_pool_size(mut self, size: usize) -> Self {
+        self.inner.receive_buffer_pool_size = size;
+        self
+    }
+
     pub fn ice_tcp_policy(mut self, policy: IceTcpPolicy) -> Self {
         self.inner.ice_tcp_policy = policy;
         self
     }
 
+    pub fn candidate_type_preferences(mut self, preferences: CandidateTypePreferences) -> Self {
+        self.inner.candidate_type_preferences = preferences;
+        self
+    }
+
     /// Enable process-wide shared ICE UDP socket (single-port multiplexing).
     /// Requires `ice_udp_mux_port` to also be set.
     pub fn ice_udp_mux(mut self, enable: bool) -> Self {
@@ -847,11 +1237,47 @@ impl RtcConfigurationBuilder {
         self
     }
 
+    /// Share `mux` with other PeerConnections instead of binding own sockets.
+    pub fn udp_mux(mut self, mux: UdpMux) -> Self {
+        self.inner.udp_mux = Some(mux);
+        self
+    }
+
     pub fn sdp_compatibility(mut self, mode: SdpCompatibilityMode) -> Self {
         self.inner.sdp_compatibility = mode;
         self
     }
 
+    pub fn profile(mut self, profile: MediaProfile) -> Self {
+        self.inner.profile = profile;
+        self
+    }
+
+    pub fn dtls_role_override(mut self, role: DtlsRole) -> Self {
+        self.inner.dtls_role_override = Some(role);
+        self
+    }
+
+    pub fn unsignaled_ssrc_policy(mut self, policy: UnsignaledSsrcPolicy) -> Self {
+        self.inner.unsignaled_ssrc_policy = policy;
+        self
+    }
+
+    pub fn unknown_payload_type_policy(mut self, policy: UnknownPayloadTypePolicy) -> Self {
+        self.inner.unknown_payload_type_policy = policy;
+        self
+    }
+
+    pub fn forward_unknown_rtcp(mut self, forward: bool) -> Self {
+        self.inner.forward_unknown_rtcp = forward;
+        self
+    }
+
+    pub fn sdp_origin_username(mut self, username: String) -> Self {
+        self.inner.sdp_origin_username = Some(username);
+        self
+    }
+
     pub fn cname(mut self, cname: String) -> Self {
         self.inner.cname = Some(cname);
         self
@@ -897,6 +1323,7 @@ mod tests {
     fn test_rtc_configuration_defaults() {
         let config = RtcConfiguration::default();
         assert_eq!(config.ice_connection_timeout, Duration::from_secs(30));
+        assert_eq!(config.ice_checking_timeout, Duration::from_secs(30));
         assert_eq!(config.sctp_rto_initial, Duration::from_secs(3));
         assert_eq!(config.sctp_rto_min, Duration::from_secs(1));
         assert_eq!(config.sctp_rto_max, Duration::from_secs(60));
@@ -905,17 +1332,29 @@ mod tests {
         assert_eq!(config.sctp_max_heartbeat_failures, 4);
         assert_eq!(config.sctp_max_burst, 0);
         assert_eq!(config.sctp_max_cwnd, 256 * 1024);
+        assert_eq!(config.sctp_sack_delay, Duration::ZERO);
+        assert_eq!(config.sctp_sack_max_unacked, 2);
         assert_eq!(config.rtp_buffer_capacity, 100);
         assert_eq!(config.buffer_drop_strategy, BufferDropStrategy::DropNew);
         assert_eq!(config.buffer_stats_log_interval, Duration::from_secs(10));
+        assert_eq!(config.receive_buffer_pool_size, 0);
+        assert!(!config.forward_unknown_rtcp);
+        assert_eq!(
+            config.unknown_payload_type_policy,
+            UnknownPayloadTypePolicy::DropUnknownPt
+        );
+        assert_eq!(config.profile, MediaProfile::Standard);
+        assert!(config.fixed_media_ports.is_empty());
     }
 
     #[test]
     fn test_rtc_configuration_builder() {
         let config = RtcConfigurationBuilder::new()
             .stun_timeout(Duration::from_secs(10))
+            .forward_unknown_rtcp(true)
             .build();
         assert_eq!(config.stun_timeout, Duration::from_secs(10));
+        assert!(config.forward_unknown_rtcp);
         // Verify other defaults are still there
         assert_eq!(config.ice_connection_timeout, Duration::from_secs(30));
     }
@@ -926,10 +1365,12 @@ mod tests {
             .rtp_buffer_capacity(200)
             .buffer_drop_strategy(BufferDropStrategy::DropOldest)
             .buffer_stats_log_interval(Duration::from_secs(5))
+            .receive_buffer_pool_size(64 * 1024)
             .build();
         assert_eq!(config.rtp_buffer_capacity, 200);
         assert_eq!(config.buffer_drop_strategy, BufferDropStrategy::DropOldest);
         assert_eq!(config.buffer_stats_log_interval, Duration::from_secs(5));
+        assert_eq!(config.receive_buffer_pool_size, 64 * 1024);
     }
 
     #[test]
@@ -944,6 +1385,7 @@ mod tests {
             .sctp_max_heartbeat_failures(8)
             .sctp_max_burst(4)
             .sctp_max_cwnd(512 * 1024)
+            .sctp_delayed_sack(Duration::from_millis(200), 4)
             .ice_connection_timeout(Duration::from_secs(60))
             .build();
 
@@ -956,6 +1398,8 @@ mod tests {
         assert_eq!(config.sctp_max_heartbeat_failures, 8);
         assert_eq!(config.sctp_max_burst, 4);
         assert_eq!(config.sctp_max_cwnd, 512 * 1024);
+        assert_eq!(config.sctp_sack_delay, Duration::from_millis(200));
+        assert_eq!(config.sctp_sack_max_unacked, 4);
         assert_eq!(config.ice_connection_timeout, Duration::from_secs(60));
     }
 
@@ -1050,6 +1494,7 @@ mod tests {
             "ICE UDP mux should be disabled by default"
         );
         assert_eq!(config.ice_udp_mux_port, None);
+        assert!(config.udp_mux.is_none());
     }
 
     #[test]
@@ -1061,4 +1506,40 @@ mod tests {
         assert!(config.ice_udp_mux);
         assert_eq!(config.ice_udp_mux_port, Some(30500));
     }
+
+    #[test]
+    fn test_media_capabilities_to_audio_section() {
+        // Same capability set as the audio_saver example.
+        let caps = MediaCapabilities {
+            audio: vec![
+                AudioCapability::pcmu(),
+                AudioCapability {
+                    codec_name: "OPUS".to_string(),
+                    fmtp: Some("minptime=10;useinbandfec=1".to_string()),
+                    ..AudioCapability::opus()
+                },
+            ],
+            ..MediaCapabilities::default()
+        };
+
+        let section = caps.to_media_section(
+            crate::sdp::MediaKind::Audio,
+            "0",
+            crate::sdp::Direction::RecvOnly,
+        );
+        assert_eq!(section.mid, "0");
+        assert_eq!(section.direction, crate::sdp::Direction::RecvOnly);
+        assert_eq!(section.formats, vec!["0", "111"]);
+
+        let rtpmaps: Vec<_> = section
+            .attributes
+            .iter()
+            .filter(|a| a.key == "rtpmap")
+            .filter_map(|a| a.value.as_deref())
+            .collect();
+        assert_eq!(rtpmaps, vec!["0 PCMU/8000", "111 OPUS/48000/2"]);
+        assert!(section.attributes.iter().any(|a| {
+            a.key == "fmtp" && a.value.as_deref() == Some("111 minptime=10;useinbandfec=1")
+        }));
+    }
 }
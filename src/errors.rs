@@ -4,6 +4,7 @@ pub type RtcResult<T> = Result<T, RtcError>;
 pub type SdpResult<T> = Result<T, SdpError>;
 pub type RtpResult<T> = Result<T, RtpError>;
 pub type SrtpResult<T> = Result<T, SrtpError>;
+pub type TransportResult<T> = Result<T, TransportError>;
 
 #[derive(Debug, Error)]
 pub enum RtcError {
@@ -19,6 +20,28 @@ pub enum RtcError {
     Transport(String),
     #[error("internal error: {0}")]
     Internal(String),
+    /// The DTLS peer aborted the handshake or association with a fatal alert,
+    /// e.g. `fatal bad_certificate (42)`.
+    #[error("DTLS alert: {0}")]
+    DtlsAlert(String),
+    /// The DTLS peer certificate does not match the remote `a=fingerprint`.
+    #[error("DTLS fingerprint mismatch: {0}")]
+    FingerprintMismatch(String),
+    /// ICE exhausted its checklist or hit `ice_checking_timeout` without any
+    /// candidate pair succeeding.
+    #[error("ICE failed: no candidate pair succeeded")]
+    IceFailed,
+    /// No process-wide rustls `CryptoProvider` is installed. rustrtc's own
+    /// DTLS/SRTP never needs one, so this is only for applications reporting
+    /// their signalling TLS setup through [`RtcError`].
+    #[error(
+        "no rustls CryptoProvider installed; call rustls::crypto::CryptoProvider::install_default before creating TLS clients"
+    )]
+    CryptoProviderMissing,
+    /// A wait such as [`PeerConnection::wait_for_media`](crate::PeerConnection::wait_for_media)
+    /// ran out of time.
+    #[error("timed out waiting for {0}")]
+    Timeout(&'static str),
 }
 
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
@@ -29,6 +52,10 @@ pub enum SdpError {
     Unsupported(String),
     #[error("failed to parse SDP: {0}")]
     Parse(String),
+    /// A [`SessionDescriptionBuilder`](crate::sdp::SessionDescriptionBuilder)
+    /// was used inconsistently, e.g. a duplicate payload type or extmap id.
+    #[error("invalid session description: {0}")]
+    Invalid(String),
 }
 
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
@@ -53,10 +80,42 @@ pub enum SrtpError {
     PacketTooShort,
     #[error("SRTP authentication failed")]
     AuthenticationFailed,
+    #[error("SRTP packet replayed")]
+    ReplayDetected,
     #[error("SRTP internal error: {0}")]
     Internal(String),
 }
 
+/// Errors from sending on an ICE/RTP connection.
+#[derive(Debug, Error)]
+pub enum TransportError {
+    #[error("remote address not set")]
+    RemoteNotSet,
+    #[error("no selected socket")]
+    NoSocket,
+    /// The socket buffer is full; retry later or drop the packet.
+    #[error("send would block")]
+    WouldBlock,
+    #[error("socket error: {0}")]
+    SocketError(std::io::Error),
+}
+
+impl From<std::io::Error> for TransportError {
+    fn from(value: std::io::Error) -> Self {
+        if value.kind() == std::io::ErrorKind::WouldBlock {
+            TransportError::WouldBlock
+        } else {
+            TransportError::SocketError(value)
+        }
+    }
+}
+
+impl From<TransportError> for RtcError {
+    fn from(value: TransportError) -> Self {
+        RtcError::Transport(value.to_string())
+    }
+}
+
 impl From<RtpError> for SrtpError {
     fn from(value: RtpError) -> Self {
         SrtpError::Internal(value.to_string())
@@ -0,0 +1,572 @@
+//! FlexFEC-03 (draft-ietf-payload-flexible-fec-scheme-03) helpers.
+//!
+//! FlexFEC repair packets travel on their own SSRC and payload type, tied to
+//! the protected stream by `a=ssrc-group:FEC-FR <primary> <fec>` and announced
+//! as `a=rtpmap:<pt> flexfec-03/90000`. Only the flexible-mask, single-SSRC
+//! form is supported: each repair packet is the XOR of a run of media packets
+//! and restores exactly one of them when it alone is missing.
+//!
+//! Repair payload layout (the XOR covers everything after the 12-byte fixed
+//! RTP header of each protected packet):
+//!
+//! ```text
+//! |R|F|P|X|  CC   |M| PT recovery |        length recovery        |
+//! |                          TS recovery                          |
+//! |   SSRCCount   |                    reserved                   |
+//! |                             SSRC_i                            |
+//! |           SN base_i           |k|          Mask [0-14]        |
+//! |k|                   Mask [15-45] (optional)                   |
+//! |                     Mask [46-108] (optional)                  |
+//! ```
+
+use crate::rtp::{RtpHeader, RtpPacket};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::collections::{HashMap, VecDeque};
+
+/// Encoding name used in `a=rtpmap`.
+pub const FLEXFEC_CODEC_NAME: &str = "flexfec-03";
+/// FlexFEC always runs on the 90 kHz video clock.
+pub const FLEXFEC_CLOCK_RATE: u32 = 90_000;
+/// `a=fmtp` parameters advertised with the FlexFEC payload type (10 s, in µs).
+pub const FLEXFEC_FMTP: &str = "repair-window=10000000";
+/// Media packets covered by each repair packet the sender emits.
+pub const FLEXFEC_GROUP_SIZE: usize = 5;
+
+/// Highest sequence offset a mask can describe (15 + 31 + 63 bits).
+const MAX_PROTECTED_OFFSET: u16 = 108;
+/// Received media packets kept around to rebuild a lost neighbour.
+const MEDIA_HISTORY: usize = 512;
+/// Repair packets waiting for enough media to recover from.
+const MAX_PENDING_REPAIRS: usize = 64;
+const RTP_FIXED_HEADER_LEN: usize = 12;
+const FIXED_HEADER_LEN: usize = 16;
+
+/// Sender-side FlexFEC parameters negotiated for a media stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlexFecSenderConfig {
+    pub fec_ssrc: u32,
+    pub fec_payload_type: u8,
+}
+
+/// Build a repair packet protecting `protected`, which must share one SSRC
+/// and span at most 109 sequence numbers starting at the first packet.
+///
+/// Returns `None` for an empty run, mixed SSRCs or an out-of-range span.
+pub fn build_repair_packet(
+    protected: &[RtpPacket],
+    config: &FlexFecSenderConfig,
+    sequence_number: u16,
+) -> Option<RtpPacket> {
+    let first = protected.first()?;
+    let ssrc = first.header.ssrc;
+    let base = first.header.sequence_number;
+    let mut offsets = Vec::with_capacity(protected.len());
+    for packet in protected {
+        let offset = packet.header.sequence_number.wrapping_sub(base);
+        if packet.header.ssrc != ssrc || offset > MAX_PROTECTED_OFFSET {
+            return None;
+        }
+        offsets.push(offset);
+    }
+
+    let mut recovery = Recovery::default();
+    for packet in protected {
+        recovery.xor(&packet.marshal().ok()?);
+    }
+
+    let mut payload = BytesMut::with_capacity(FIXED_HEADER_LEN + 16 + recovery.body.len());
+    // R and F stay clear: flexible mask, retransmission bit unset.
+    payload.put_u8(recovery.b0 & 0x3F);
+    payload.put_u8(recovery.b1);
+    payload.put_u16(recovery.length);
+    payload.put_u32(recovery.timestamp);
+    payload.put_u32(1 << 24); // SSRCCount = 1, reserved = 0
+    payload.put_u32(ssrc);
+    payload.put_u16(base);
+    write_mask(&offsets, &mut payload);
+    payload.extend_from_slice(&recovery.body);
+
+    let header = RtpHeader::new(
+        config.fec_payload_type,
+        sequence_number,
+        protected.last()?.header.timestamp,
+        config.fec_ssrc,
+    );
+    Some(RtpPacket {
+        header,
+        payload: payload.freeze(),
+        padding_len: 0,
+    })
+}
+
+/// Groups outgoing media packets and emits one repair packet per group.
+#[derive(Debug)]
+pub struct FlexFecEncoder {
+    config: FlexFecSenderConfig,
+    group_size: usize,
+    group: Vec<RtpPacket>,
+    sequence_number: u16,
+}
+
+impl FlexFecEncoder {
+    pub fn new(config: FlexFecSenderConfig, group_size: usize) -> Self {
+        Self {
+            config,
+            group_size: group_size.max(1),
+            group: Vec::with_capacity(group_size),
+            sequence_number: crate::transports::ice::stun::random_u32() as u16,
+        }
+    }
+
+    pub fn config(&self) -> FlexFecSenderConfig {
+        self.config
+    }
+
+    /// Record a media packet as sent. Returns the repair packet once the
+    /// current group is full.
+    pub fn push(&mut self, packet: &RtpPacket) -> Option<RtpPacket> {
+        // A new SSRC or a sequence jump the mask cannot describe starts over.
+        if let Some(first) = self.group.first()
+            && (first.header.ssrc != packet.header.ssrc
+                || packet
+                    .header
+                    .sequence_number
+                    .wrapping_sub(first.header.sequence_number)
+                    > MAX_PROTECTED_OFFSET)
+        {
+            self.group.clear();
+        }
+        self.group.push(packet.clone());
+        if self.group.len() < self.group_size {
+            return None;
+        }
+        let repair = build_repair_packet(&self.group, &self.config, self.sequence_number);
+        self.group.clear();
+        if repair.is_some() {
+            self.sequence_number = self.sequence_number.wrapping_add(1);
+        }
+        repair
+    }
+}
+
+/// Receiver-side FlexFEC state: remembers recent media packets and pending
+/// repair packets, and rebuilds a media packet when exactly one of those a
+/// repair packet protects is missing.
+#[derive(Debug)]
+pub struct FlexFecDecoder {
+    payload_type: u8,
+    fec_ssrc: Option<u32>,
+    media: HashMap<(u32, u16), Vec<u8>>,
+    order: VecDeque<(u32, u16)>,
+    repairs: VecDeque<RepairPacket>,
+    recovered: u64,
+}
+
+#[derive(Debug)]
+struct RepairPacket {
+    ssrc: u32,
+    sequence_numbers: Vec<u16>,
+    header: [u8; 8],
+    body: Bytes,
+}
+
+impl FlexFecDecoder {
+    pub fn new(payload_type: u8, fec_ssrc: Option<u32>) -> Self {
+        Self {
+            payload_type,
+            fec_ssrc,
+            media: HashMap::new(),
+            order: VecDeque::new(),
+            repairs: VecDeque::new(),
+            recovered: 0,
+        }
+    }
+
+    pub fn payload_type(&self) -> u8 {
+        self.payload_type
+    }
+
+    pub fn fec_ssrc(&self) -> Option<u32> {
+        self.fec_ssrc
+    }
+
+    /// Media packets rebuilt from repair packets so far.
+    pub fn recovered_count(&self) -> u64 {
+        self.recovered
+    }
+
+    /// Whether `packet` is a FlexFEC repair packet rather than media.
+    pub fn is_repair(&self, packet: &RtpPacket) -> bool {
+        packet.header.payload_type == self.payload_type || self.fec_ssrc == Some(packet.header.ssrc)
+    }
+
+    /// Record a received media packet; returns any packets it made recoverable.
+    pub fn on_media(&mut self, packet: &RtpPacket) -> Vec<RtpPacket> {
+        let key = (packet.header.ssrc, packet.header.sequence_number);
+        if self.media.contains_key(&key) {
+            return Vec::new();
+        }
+        let Ok(raw) = packet.marshal() else {
+            return Vec::new();
+        };
+        self.remember(key, raw);
+        self.recover()
+    }
+
+    /// Record a received repair packet; returns any packets it recovered.
+    pub fn on_repair(&mut self, packet: &RtpPacket) -> Vec<RtpPacket> {
+        let Some(repair) = RepairPacket::parse(&packet.payload) else {
+            return Vec::new();
+        };
+        if self.repairs.len() == MAX_PENDING_REPAIRS {
+            self.repairs.pop_front();
+        }
+        self.repairs.push_back(repair);
+        self.recover()
+    }
+
+    fn remember(&mut self, key: (u32, u16), raw: Vec<u8>) {
+        if self.order.len() == MEDIA_HISTORY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.media.remove(&oldest);
+        }
+        self.order.push_back(key);
+        self.media.insert(key, raw);
+    }
+
+    fn recover(&mut self) -> Vec<RtpPacket> {
+        let mut recovered = Vec::new();
+        // Each recovery can complete another repair, so rescan until stable.
+        loop {
+            let mut progress = false;
+            let mut index = 0;
+            while index < self.repairs.len() {
+                let repair = &self.repairs[index];
+                let mut missing = repair
+                    .sequence_numbers
+                    .iter()
+                    .filter(|seq| !self.media.contains_key(&(repair.ssrc, **seq)));
+                match (missing.next().copied(), missing.next()) {
+                    (None, _) => {
+                        self.repairs.remove(index);
+                    }
+                    (Some(seq), None) => {
+                        let repair = self.repairs.remove(index).expect("index in range");
+                        if let Some((packet, raw)) = self.rebuild(&repair, seq) {
+                            self.remember((repair.ssrc, seq), raw);
+                            self.recovered += 1;
+                            recovered.push(packet);
+                            progress = true;
+                        }
+                    }
+                    _ => index += 1,
+                }
+            }
+            if !progress {
+                return recovered;
+            }
+        }
+    }
+
+    fn rebuild(&self, repair: &RepairPacket, missing: u16) -> Option<(RtpPacket, Vec<u8>)> {
+        let mut recovery = Recovery {
+            b0: repair.header[0],
+            b1: repair.header[1],
+            length: u16::from_be_bytes([repair.header[2], repair.header[3]]),
+            timestamp: u32::from_be_bytes([
+                repair.header[4],
+                repair.header[5],
+                repair.header[6],
+                repair.header[7],
+            ]),
+            body: repair.body.to_vec(),
+        };
+        for seq in &repair.sequence_numbers {
+            if *seq != missing {
+                recovery.xor(self.media.get(&(repair.ssrc, *seq))?);
+            }
+        }
+
+        let length = recovery.length as usize;
+        if recovery.body.len() < length {
+            return None;
+        }
+        let mut raw = Vec::with_capacity(RTP_FIXED_HEADER_LEN + length);
+        raw.push(0x80 | (recovery.b0 & 0x3F));
+        raw.push(recovery.b1);
+        raw.extend_from_slice(&missing.to_be_bytes());
+        raw.extend_from_slice(&recovery.timestamp.to_be_bytes());
+        raw.extend_from_slice(&repair.ssrc.to_be_bytes());
+        raw.extend_from_slice(&recovery.body[..length]);
+        let packet = RtpPacket::parse(&raw).ok()?;
+        Some((packet, raw))
+    }
+}
+
+impl RepairPacket {
+    fn parse(payload: &[u8]) -> Option<Self> {
+        if payload.len() < FIXED_HEADER_LEN + 8 {
+            return None;
+        }
+        // Retransmission (R) and fixed-offset (F) modes are not supported.
+        if payload[0] & 0xC0 != 0 || payload[8] != 1 {
+            return None;
+        }
+        let ssrc = u32::from_be_bytes([payload[12], payload[13], payload[14], payload[15]]);
+        let base = u16::from_be_bytes([payload[16], payload[17]]);
+        let (offsets, mask_len) = read_mask(&payload[18..])?;
+        let mut header = [0u8; 8];
+        header.copy_from_slice(&payload[..8]);
+        Some(Self {
+            ssrc,
+            sequence_numbers: offsets
+                .into_iter()
+                .map(|offset| base.wrapping_add(offset))
+                .collect(),
+            header,
+            body: Bytes::copy_from_slice(&payload[18 + mask_len..]),
+        })
+    }
+}
+
+/// Running XOR of the recovered header fields and the bytes after the fixed
+/// RTP header.
+#[derive(Debug, Default)]
+struct Recovery {
+    b0: u8,
+    b1: u8,
+    length: u16,
+    timestamp: u32,
+    body: Vec<u8>,
+}
+
+impl Recovery {
+    fn xor(&mut self, raw: &[u8]) {
+        self.b0 ^= raw[0];
+        self.b1 ^= raw[1];
+        self.timestamp ^= u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]);
+        let rest = &raw[RTP_FIXED_HEADER_LEN..];
+        self.length ^= rest.len() as u16;
+        if self.body.len() < rest.len() {
+            self.body.resize(rest.len(), 0);
+        }
+        for (out, byte) in self.body.iter_mut().zip(rest) {
+            *out ^= byte;
+        }
+    }
+}
+
+/// Write the k-bit terminated mask for `offsets`, using the shortest of the
+/// 15/46/109-bit forms that fits.
+fn write_mask(offsets: &[u16], out: &mut BytesMut) {
+    let (mut m15, mut m31, mut m63) = (0u16, 0u32, 0u64);
+    for &offset in offsets {
+        match offset {
+            0..=14 => m15 |= 1 << (14 - offset),
+            15..=45 => m31 |= 1 << (30 - (offset - 15)),
+            _ => m63 |= 1 << (62 - (offset - 46)),
+        }
+    }
+    if m31 == 0 && m63 == 0 {
+        out.put_u16(0x8000 | m15);
+    } else if m63 == 0 {
+        out.put_u16(m15);
+        out.put_u32(0x8000_0000 | m31);
+    } else {
+        out.put_u16(m15);
+        out.put_u32(m31);
+        out.put_u64(m63);
+    }
+}
+
+/// Read a k-bit terminated mask; returns the protected offsets and the number
+/// of mask bytes consumed.
+fn read_mask(data: &[u8]) -> Option<(Vec<u16>, usize)> {
+    let mut offsets = Vec::new();
+    let first = u16::from_be_bytes([*data.first()?, *data.get(1)?]);
+    offsets.extend((0..15).filter(|bit| first & (1 << (14 - bit)) != 0));
+    if first & 0x8000 != 0 {
+        return Some((offsets, 2));
+    }
+    let second = u32::from_be_bytes(data.get(2..6)?.try_into().ok()?);
+    offsets.extend(
+        (0..31)
+            .filter(|bit| second & (1 << (30 - bit)) != 0)
+            .map(|bit| bit + 15),
+    );
+    if second & 0x8000_0000 != 0 {
+        return Some((offsets, 6));
+    }
+    let third = u64::from_be_bytes(data.get(6..14)?.try_into().ok()?);
+    offsets.extend(
+        (0..63)
+            .filter(|bit| third & (1 << (62 - bit)) != 0)
+            .map(|bit| bit + 46),
+    );
+    Some((offsets, 14))
+}
+
+/// FlexFEC payload type announced by `a=rtpmap:<pt> flexfec-03/90000`.
+pub fn flexfec_payload_type(attrs: &[crate::sdp::Attribute]) -> Option<u8> {
+    attrs
+        .iter()
+        .filter(|a| a.key == "rtpmap")
+        .filter_map(|a| crate::sdp::RtpMap::parse(a.value.as_deref()?))
+        .find(|rtpmap| {
+            rtpmap
+                .encoding_name
+                .eq_ignore_ascii_case(FLEXFEC_CODEC_NAME)
+        })
+        .map(|rtpmap| rtpmap.payload_type)
+}
+
+/// Parse `a=ssrc-group:FEC-FR <primary> <fec>` into `(primary, fec)`.
+pub fn parse_fec_fr_group(attrs: &[crate::sdp::Attribute]) -> Option<(u32, u32)> {
+    attrs
+        .iter()
+        .filter(|a| a.key == "ssrc-group")
+        .filter_map(|a| a.value.as_deref())
+        .find_map(|val| {
+            let mut parts = val.split_whitespace();
+            if parts.next()? != "FEC-FR" {
+                return None;
+            }
+            Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+        })
+}
+
+/// Append the FlexFEC rtpmap/fmtp lines and the PT to `formats`.
+pub fn append_flexfec_to_section(
+    formats: &mut Vec<String>,
+    attributes: &mut Vec<crate::sdp::Attribute>,
+    fec_pt: u8,
+) {
+    let fec_pt_str = fec_pt.to_string();
+    if !formats.iter().any(|f| f == &fec_pt_str) {
+        formats.push(fec_pt_str);
+    }
+    let rtpmap = format!("{fec_pt} {FLEXFEC_CODEC_NAME}/{FLEXFEC_CLOCK_RATE}");
+    let already = attributes
+        .iter()
+        .any(|a| a.key == "rtpmap" && a.value.as_deref() == Some(rtpmap.as_str()));
+    if !already {
+        attributes.push(crate::sdp::Attribute::new("rtpmap", Some(rtpmap)));
+        attributes.push(crate::sdp::Attribute::new(
+            "fmtp",
+            Some(format!("{fec_pt} {FLEXFEC_FMTP}")),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn media(seq: u16, payload: &[u8]) -> RtpPacket {
+        let mut header = RtpHeader::new(96, seq, 3_000 * seq as u32, 0x1111);
+        header.marker = seq.is_multiple_of(2);
+        RtpPacket::new(header, payload.to_vec())
+    }
+
+    const CONFIG: FlexFecSenderConfig = FlexFecSenderConfig {
+        fec_ssrc: 0x2222,
+        fec_payload_type: 100,
+    };
+
+    #[test]
+    fn repair_packet_recovers_single_loss() {
+        let packets = vec![
+            media(65_534, &[1, 2, 3]),
+            media(65_535, &[4, 5, 6, 7, 8]),
+            media(0, &[9]),
+        ];
+        let repair = build_repair_packet(&packets, &CONFIG, 7).unwrap();
+        assert_eq!(repair.header.ssrc, 0x2222);
+        assert_eq!(repair.header.payload_type, 100);
+        assert_eq!(repair.header.sequence_number, 7);
+
+        let mut decoder = FlexFecDecoder::new(100, Some(0x2222));
+        assert!(decoder.is_repair(&repair));
+        assert!(!decoder.is_repair(&packets[0]));
+        assert!(decoder.on_media(&packets[0]).is_empty());
+        assert!(decoder.on_media(&packets[2]).is_empty());
+        let recovered = decoder.on_repair(&repair);
+        assert_eq!(recovered, vec![packets[1].clone()]);
+        assert_eq!(decoder.recovered_count(), 1);
+
+        // The recovered packet counts as received: a late copy is ignored.
+        assert!(decoder.on_media(&packets[1]).is_empty());
+    }
+
+    #[test]
+    fn repair_before_media_recovers_when_last_neighbour_arrives() {
+        let packets: Vec<_> = (10..13).map(|seq| media(seq, &[seq as u8; 4])).collect();
+        let repair = build_repair_packet(&packets, &CONFIG, 1).unwrap();
+        let mut decoder = FlexFecDecoder::new(100, None);
+        assert!(decoder.on_repair(&repair).is_empty());
+        assert!(decoder.on_media(&packets[0]).is_empty());
+        assert_eq!(decoder.on_media(&packets[2]), vec![packets[1].clone()]);
+    }
+
+    #[test]
+    fn two_losses_are_not_recoverable() {
+        let packets: Vec<_> = (0..4).map(|seq| media(seq, &[0xAB, seq as u8])).collect();
+        let repair = build_repair_packet(&packets, &CONFIG, 1).unwrap();
+        let mut decoder = FlexFecDecoder::new(100, None);
+        decoder.on_media(&packets[0]);
+        decoder.on_media(&packets[3]);
+        assert!(decoder.on_repair(&repair).is_empty());
+        assert_eq!(decoder.recovered_count(), 0);
+    }
+
+    #[test]
+    fn mask_round_trips_all_lengths() {
+        for offsets in [vec![0, 3, 14], vec![0, 15, 45], vec![0, 46, 108]] {
+            let mut buf = BytesMut::new();
+            write_mask(&offsets, &mut buf);
+            let (parsed, len) = read_mask(&buf).unwrap();
+            assert_eq!(parsed, offsets);
+            assert_eq!(len, buf.len());
+        }
+    }
+
+    #[test]
+    fn build_rejects_mixed_ssrc_and_wide_span() {
+        let mut other = media(1, &[0]);
+        other.header.ssrc = 0x3333;
+        assert!(build_repair_packet(&[media(0, &[0]), other], &CONFIG, 0).is_none());
+        assert!(build_repair_packet(&[media(0, &[0]), media(109, &[0])], &CONFIG, 0).is_none());
+        assert!(build_repair_packet(&[], &CONFIG, 0).is_none());
+    }
+
+    #[test]
+    fn encoder_emits_one_repair_per_group() {
+        let mut encoder = FlexFecEncoder::new(CONFIG, 3);
+        assert!(encoder.push(&media(1, &[1])).is_none());
+        assert!(encoder.push(&media(2, &[2])).is_none());
+        let first = encoder.push(&media(3, &[3])).unwrap();
+        assert!(encoder.push(&media(4, &[4])).is_none());
+        assert!(encoder.push(&media(5, &[5])).is_none());
+        let second = encoder.push(&media(6, &[6])).unwrap();
+        assert_eq!(
+            second.header.sequence_number,
+            first.header.sequence_number.wrapping_add(1)
+        );
+    }
+
+    #[test]
+    fn sdp_helpers_parse_rtpmap_and_fec_fr_group() {
+        let mut formats = vec!["96".to_string()];
+        let mut attrs = vec![crate::sdp::Attribute::new(
+            "ssrc-group",
+            Some("FEC-FR 1234 5678".into()),
+        )];
+        append_flexfec_to_section(&mut formats, &mut attrs, 100);
+        append_flexfec_to_section(&mut formats, &mut attrs, 100);
+        assert_eq!(formats, vec!["96", "100"]);
+        assert_eq!(attrs.iter().filter(|a| a.key == "rtpmap").count(), 1);
+        assert_eq!(flexfec_payload_type(&attrs), Some(100));
+        assert_eq!(parse_fec_fr_group(&attrs), Some((1234, 5678)));
+    }
+}
@@ -14,6 +14,7 @@
 
 pub mod config;
 pub mod errors;
+pub mod flexfec;
 pub mod media;
 pub mod peer_connection;
 pub mod rtp;
@@ -22,36 +23,43 @@ pub mod sdp;
 pub mod srtp;
 pub mod stats;
 pub mod stats_collector;
+pub mod t140;
 #[cfg(feature = "t38")]
 pub mod t38;
 pub mod transports;
+pub mod twcc;
 
 pub use config::{
-    ApplicationCapability, AudioCapability, BundlePolicy, CertificateConfig, IceCredentialType,
-    IceServer, IceTcpPolicy, IceTransportPolicy, MediaCapabilities, RecorderInterceptors,
-    RtcConfiguration, RtcConfigurationBuilder, RtcpMuxPolicy, SdpCompatibilityMode, T38Capability,
-    T38FaxRateManagement, T38UdpEC, TransportMode, VideoCapability,
+    ApplicationCapability, AudioCapability, BundlePolicy, CandidateTypePreferences,
+    CertificateConfig, DtlsRole, IceCredentialType, IceServer, IceTcpPolicy, IceTransportPolicy,
+    MediaCapabilities, MediaProfile, RecorderInterceptors, RtcConfiguration,
+    RtcConfigurationBuilder, RtcpMuxPolicy, SdpCompatibilityMode, T38Capability,
+    T38FaxRateManagement, T38UdpEC, TransportMode, UnknownPayloadTypePolicy, UnsignaledSsrcPolicy,
+    VideoCapability,
 };
-pub use errors::{RtcError, RtcResult, SdpError, SdpResult};
+pub use errors::{RtcError, RtcResult, SdpError, SdpResult, TransportError, TransportResult};
 pub use peer_connection::{
-    DisconnectReason, IceConnectionState, IceGatheringState, PeerConnection, PeerConnectionEvent,
-    PeerConnectionState, RtpCodecParameters, RtpReceiverInterceptor, RtpSender,
-    RtpSenderInterceptor, RtpTransceiver, SignalingState, TransceiverDirection,
+    DisconnectReason, IceConnectionState, IceGatheringState, PaddingProbe, PeerConnection,
+    PeerConnectionEvent, PeerConnectionState, RtpCodecParameters, RtpReceiverInterceptor,
+    RtpSender, RtpSenderInterceptor, RtpTransceiver, SignalingState, TransceiverDirection,
+    VideoSendLimits,
 };
 pub use sdp::{
-    AddressType, Attribute, Direction, MediaKind, MediaSection, NetworkType, Origin, SDES_MID_URI,
-    SdpType, SessionDescription, SessionSection, Timing, modify_sdp_direction,
-    parse_bundle_mid_info,
+    AddressType, Attribute, Direction, ImageAttribute, MediaKind, MediaSection, MediaSectionDiff,
+    NetworkType, Origin, RtpMap, SDES_MID_URI, SdpDiff, SdpType, SessionDescription,
+    SessionDescriptionBuilder, SessionSection, SimulcastLayer, SimulcastLayers, Timing,
+    modify_sdp_direction, parse_bundle_mid_info,
 };
 pub use srtp::{SrtpContext, SrtpDirection, SrtpKeyingMaterial, SrtpProfile, SrtpSession};
 pub use stats::{
     DynProvider, StatsEntry, StatsId, StatsKind, StatsProvider, StatsReport, gather_once,
 };
+pub use transports::ice::shared_udp::UdpMux;
 pub use transports::ice::{
     DEFAULT_LEASE_DURATION, DEFAULT_UPNP_DISCOVERY_TIMEOUT, IceCandidate, IceCandidatePair,
     IceCandidateType, IceGathererState, IceRole, IceTransport, IceTransportState,
     MAX_LEASE_DURATION, MIN_LEASE_DURATION, TcpType, UpnpPortMapper,
 };
-pub use transports::rtp::RtpRewriteBridgeParams;
+pub use transports::rtp::{RtpContinuity, RtpForwardSender, RtpRewriteBridgeParams};
 pub use transports::sctp::{DataChannelEvent, DataChannelState};
 pub use transports::udptl::{UdtlConfig, UdtlReceiveBuffer, UdtlTransport};
@@ -1,4 +1,6 @@
 use crate::media::MediaResult;
+#[cfg(feature = "opus")]
+use crate::media::frame::{AudioFrame, AudioSampleFormat};
 use crate::media::frame::{MediaKind, MediaSample, VideoFrame, VideoPixelFormat};
 use crate::rtp::RtpPacket;
 use bytes::Bytes;
@@ -37,14 +39,26 @@ impl Depacketizer for PassThroughDepacketizer {
 }
 
 /// H.264 Depacketizer (RFC 6184)
-/// Handles Single NAL Unit, STAP-A, and FU-A.
+/// Handles Single NAL Unit, STAP-A, and FU-A, reassembling the NAL units of
+/// one RTP timestamp into an Annex-B access unit emitted on the marker bit.
+/// Access units carrying an SPS or IDR slice are flagged as key frames.
 pub struct H264Depacketizer {
-    // Buffer for reassembling FU-A packets
-    fua_buffer: Vec<u8>,
-    // Expected sequence number for the next FU-A packet
+    // Annex-B access unit being reassembled
+    au_buffer: Vec<u8>,
+    // Sequence number of the last packet added to `au_buffer`
     last_seq: Option<u16>,
-    // Timestamp for the current frame being reassembled
+    // Newest sequence number seen, kept across access units so that an
+    // access unit whose first packets were lost is noticed
+    prev_seq: Option<u16>,
+    // Timestamp for the current access unit being reassembled
     current_timestamp: u32,
+    // Whether an FU-A fragmented NAL unit is open in `au_buffer`
+    fua_active: bool,
+    // Whether the current access unit holds an SPS or IDR slice
+    current_keyframe: bool,
+    // Whether remaining packets at `current_timestamp` are ignored because
+    // its access unit was already emitted or discarded
+    skip_timestamp: bool,
     // Counter of frames dropped due to depacketization errors (FU-A/STAP-A corruption)
     drop_count: Arc<AtomicU64>,
 }
@@ -56,11 +70,19 @@ impl Default for H264Depacketizer {
 }
 
 impl H264Depacketizer {
+    const START_CODE: [u8; 4] = [0, 0, 0, 1];
+    const NAL_IDR: u8 = 5;
+    const NAL_SPS: u8 = 7;
+
     pub fn new() -> Self {
         Self {
-            fua_buffer: Vec::new(),
+            au_buffer: Vec::new(),
             last_seq: None,
+            prev_seq: None,
             current_timestamp: 0,
+            fua_active: false,
+            current_keyframe: false,
+            skip_timestamp: false,
             drop_count: Arc::new(AtomicU64::new(0)),
         }
     }
@@ -69,6 +91,86 @@ impl H264Depacketizer {
     pub fn drop_counter(&self) -> Arc<AtomicU64> {
         self.drop_count.clone()
     }
+
+    fn reset(&mut self) {
+        self.au_buffer.clear();
+        self.last_seq = None;
+        self.fua_active = false;
+        self.current_keyframe = false;
+    }
+
+    /// Drop the current access unit and ignore the rest of its packets.
+    fn discard(&mut self) {
+        self.drop_count.fetch_add(1, Ordering::Relaxed);
+        self.reset();
+        self.skip_timestamp = true;
+    }
+
+    fn start_nal(&mut self, header: u8) {
+        let nal_type = header & 0x1F;
+        self.current_keyframe |= nal_type == Self::NAL_IDR || nal_type == Self::NAL_SPS;
+        self.au_buffer.extend_from_slice(&Self::START_CODE);
+        self.au_buffer.push(header);
+    }
+
+    /// Append the NAL units of one payload, or `Err` if it is malformed.
+    fn append_payload(&mut self, payload: &[u8]) -> Result<(), &'static str> {
+        let nal_type = payload[0] & 0x1F;
+        match nal_type {
+            // Single NAL unit (1-23)
+            1..=23 => {
+                if self.fua_active {
+                    return Err("FU-A fragment not terminated");
+                }
+                self.start_nal(payload[0]);
+                self.au_buffer.extend_from_slice(&payload[1..]);
+            }
+            // STAP-A (Single-Time Aggregation Packet type A)
+            24 => {
+                if self.fua_active {
+                    return Err("FU-A fragment not terminated");
+                }
+                let mut offset = 1; // Skip STAP-A header
+                while offset + 2 < payload.len() {
+                    let nal_len =
+                        u16::from_be_bytes([payload[offset], payload[offset + 1]]) as usize;
+                    offset += 2;
+                    if nal_len == 0 || offset + nal_len > payload.len() {
+                        return Err("STAP-A NAL length exceeds packet size");
+                    }
+                    self.start_nal(payload[offset]);
+                    self.au_buffer
+                        .extend_from_slice(&payload[offset + 1..offset + nal_len]);
+                    offset += nal_len;
+                }
+            }
+            // FU-A (Fragmentation Unit type A)
+            28 => {
+                let Some(&fu_header) = payload.get(1) else {
+                    return Err("FU-A header truncated");
+                };
+                let s_bit = (fu_header & 0x80) != 0;
+                let e_bit = (fu_header & 0x40) != 0;
+                if s_bit {
+                    if self.fua_active {
+                        return Err("FU-A fragment not terminated");
+                    }
+                    let nri = payload[0] & 0x60;
+                    self.start_nal(nri | (fu_header & 0x1F));
+                    self.fua_active = true;
+                } else if !self.fua_active {
+                    return Err("FU-A fragment without start");
+                }
+                self.au_buffer.extend_from_slice(&payload[2..]);
+                if e_bit {
+                    self.fua_active = false;
+                }
+            }
+            // STAP-B, MTAP and FU-B need interleaved mode, which is not negotiated
+            _ => return Err("unsupported NAL unit type"),
+        }
+        Ok(())
+    }
 }
 
 impl Depacketizer for H264Depacketizer {
@@ -83,169 +185,493 @@ impl Depacketizer for H264Depacketizer {
         addr: SocketAddr,
         kind: MediaKind,
     ) -> MediaResult<Vec<MediaSample>> {
-        if kind == MediaKind::Audio {
+        if kind == MediaKind::Audio || packet.payload.is_empty() {
+            // Empty payloads are keep-alive/padding and pass through
             return Ok(vec![MediaSample::from_rtp_packet(
                 packet, kind, clock_rate, addr,
             )]);
         }
 
-        let raw_packet = packet.clone();
-        let payload = packet.payload;
-        if payload.is_empty() {
-            // Treat empty payload as a keep-alive/padding frame and pass it through
+        let seq = packet.header.sequence_number;
+        let timestamp = packet.header.timestamp;
+        let contiguous = self.prev_seq.is_none_or(|prev| seq == prev.wrapping_add(1));
+        if self.prev_seq.is_none_or(|prev| {
+            let ahead = seq.wrapping_sub(prev);
+            ahead != 0 && ahead < 0x8000
+        }) {
+            self.prev_seq = Some(seq);
+        }
+        if timestamp != self.current_timestamp || (self.last_seq.is_none() && !self.skip_timestamp)
+        {
+            if self.last_seq.is_some() {
+                // Previous access unit never saw its marker packet
+                self.drop_count.fetch_add(1, Ordering::Relaxed);
+            }
+            self.reset();
+            self.skip_timestamp = false;
+            self.current_timestamp = timestamp;
+            if !contiguous {
+                tracing::warn!(
+                    "H.264 access unit {} lost its leading packets before seq {}",
+                    timestamp,
+                    seq
+                );
+                self.discard();
+                return Ok(vec![]);
+            }
+        } else if self.skip_timestamp {
+            return Ok(vec![]);
+        } else if let Some(last_seq) = self.last_seq
+            && seq != last_seq.wrapping_add(1)
+        {
+            tracing::warn!(
+                "H.264 access unit broken: expected seq {}, got {}",
+                last_seq.wrapping_add(1),
+                seq
+            );
+            self.discard();
+            return Ok(vec![]);
+        }
+
+        if let Err(e) = self.append_payload(&packet.payload) {
+            tracing::warn!("H.264 packet {} dropped: {}", seq, e);
+            self.discard();
+            return Ok(vec![]);
+        }
+        self.last_seq = Some(seq);
+
+        if !packet.header.marker {
+            return Ok(vec![]);
+        }
+        if self.fua_active {
+            tracing::warn!("H.264 marker packet {} inside FU-A fragment", seq);
+            self.discard();
+            return Ok(vec![]);
+        }
+        let data = Bytes::from(std::mem::take(&mut self.au_buffer));
+        let is_keyframe = self.current_keyframe;
+        self.reset();
+        self.skip_timestamp = true; // Late packets of this timestamp are ignored
+        Ok(vec![MediaSample::Video(VideoFrame {
+            rtp_timestamp: timestamp,
+            width: 0,
+            height: 0,
+            format: VideoPixelFormat::Unspecified,
+            rotation_deg: 0,
+            flip_horizontal: false,
+            is_last_packet: true,
+            is_keyframe,
+            frame_marking: None,
+            data,
+            header_extension: packet.header.extension.clone(),
+            csrcs: packet.header.csrcs.clone(),
+            sequence_number: Some(seq),
+            payload_type: Some(packet.header.payload_type),
+            source_addr: Some(addr),
+            raw_packet: Some(packet),
+            capture_time: None,
+        })])
+    }
+}
+
+/// VP8 Depacketizer (RFC 7741)
+/// Strips the payload descriptor and reassembles partitions into one frame
+/// per RTP timestamp, emitted on the marker bit with its key frame flag.
+pub struct Vp8Depacketizer {
+    // Frame being reassembled
+    frame_buffer: Vec<u8>,
+    // Sequence number of the last packet added to `frame_buffer`
+    last_seq: Option<u16>,
+    // Timestamp for the current frame being reassembled
+    current_timestamp: u32,
+    // Whether the current frame's payload header marks a key frame
+    current_keyframe: bool,
+    // Counter of frames dropped due to loss or malformed descriptors
+    drop_count: Arc<AtomicU64>,
+}
+
+impl Default for Vp8Depacketizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vp8Depacketizer {
+    pub fn new() -> Self {
+        Self {
+            frame_buffer: Vec::new(),
+            last_seq: None,
+            current_timestamp: 0,
+            current_keyframe: false,
+            drop_count: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns a shared reference to the atomic drop counter.
+    pub fn drop_counter(&self) -> Arc<AtomicU64> {
+        self.drop_count.clone()
+    }
+
+    /// Length of the payload descriptor (RFC 7741 §4.2), or `None` if the
+    /// payload is too short to hold it.
+    fn descriptor_len(payload: &[u8]) -> Option<usize> {
+        let first = *payload.first()?;
+        let mut len = 1;
+        if first & 0x80 != 0 {
+            // X: extension byte with I, L, T, K flags
+            let ext = *payload.get(1)?;
+            len += 1;
+            if ext & 0x80 != 0 {
+                // I: PictureID, 15 bits when M is set
+                let picture_id = *payload.get(len)?;
+                len += if picture_id & 0x80 != 0 { 2 } else { 1 };
+            }
+            if ext & 0x40 != 0 {
+                // L: TL0PICIDX
+                len += 1;
+            }
+            if ext & 0x30 != 0 {
+                // T or K: TID/Y/KEYIDX byte
+                len += 1;
+            }
+        }
+        (len < payload.len()).then_some(len)
+    }
+
+    fn reset(&mut self) {
+        self.frame_buffer.clear();
+        self.last_seq = None;
+    }
+}
+
+impl Depacketizer for Vp8Depacketizer {
+    fn drop_count(&self) -> u64 {
+        self.drop_count.load(Ordering::Relaxed)
+    }
+
+    fn push(
+        &mut self,
+        packet: RtpPacket,
+        clock_rate: u32,
+        addr: SocketAddr,
+        kind: MediaKind,
+    ) -> MediaResult<Vec<MediaSample>> {
+        if kind == MediaKind::Audio || packet.payload.is_empty() {
             return Ok(vec![MediaSample::from_rtp_packet(
-                raw_packet, kind, clock_rate, addr,
+                packet, kind, clock_rate, addr,
             )]);
         }
 
-        let header = payload[0];
-        let nal_type = header & 0x1F;
-        let mut samples = Vec::new();
-
-        let create_video_sample = |data: Bytes, timestamp: u32, is_last: bool, pkt: &RtpPacket| {
-            MediaSample::Video(VideoFrame {
-                rtp_timestamp: timestamp,
-                width: 0,
-                height: 0,
-                format: VideoPixelFormat::Unspecified,
-                rotation_deg: 0,
-                is_last_packet: is_last,
-                data,
-                header_extension: pkt.header.extension.clone(),
-                csrcs: pkt.header.csrcs.clone(),
-                sequence_number: Some(pkt.header.sequence_number),
-                payload_type: Some(pkt.header.payload_type),
-                source_addr: Some(addr),
-                raw_packet: Some(pkt.clone()),
-            })
+        let Some(descriptor_len) = Self::descriptor_len(&packet.payload) else {
+            tracing::warn!("VP8 payload descriptor truncated");
+            self.drop_count.fetch_add(1, Ordering::Relaxed);
+            self.reset();
+            return Ok(vec![]);
         };
+        let start_of_frame = packet.payload[0] & 0x17 == 0x10; // S=1, PID=0
+        let seq = packet.header.sequence_number;
 
-        match nal_type {
-            // STAP-A (Single-Time Aggregation Packet type A)
-            24 => {
-                let mut offset = 1; // Skip STAP-A header
-                let data = Bytes::from(payload);
-                let len = data.len();
-                let packet_marker = raw_packet.header.marker;
+        if start_of_frame {
+            if self.last_seq.is_some() {
+                // Previous frame never saw its marker packet
+                self.drop_count.fetch_add(1, Ordering::Relaxed);
+            }
+            self.frame_buffer.clear();
+            self.current_timestamp = packet.header.timestamp;
+            // Inverse key frame flag P in the VP8 payload header (RFC 7741 §4.3)
+            self.current_keyframe = packet.payload[descriptor_len] & 0x01 == 0;
+        } else {
+            let Some(last_seq) = self.last_seq else {
+                return Ok(vec![]); // Missing start
+            };
+            if seq != last_seq.wrapping_add(1) || packet.header.timestamp != self.current_timestamp
+            {
+                tracing::warn!(
+                    "VP8 frame broken: expected seq {}, got {}",
+                    last_seq.wrapping_add(1),
+                    seq
+                );
+                self.drop_count.fetch_add(1, Ordering::Relaxed);
+                self.reset();
+                return Ok(vec![]);
+            }
+        }
+        self.frame_buffer
+            .extend_from_slice(&packet.payload[descriptor_len..]);
+        self.last_seq = Some(seq);
 
-                while offset + 2 < len {
-                    let nal_len = u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
-                    offset += 2;
+        if !packet.header.marker {
+            return Ok(vec![]);
+        }
+        let data = Bytes::from(std::mem::take(&mut self.frame_buffer));
+        self.last_seq = None;
+        Ok(vec![MediaSample::Video(VideoFrame {
+            rtp_timestamp: self.current_timestamp,
+            width: 0,
+            height: 0,
+            format: VideoPixelFormat::Unspecified,
+            rotation_deg: 0,
+            flip_horizontal: false,
+            is_last_packet: true,
+            is_keyframe: self.current_keyframe,
+            frame_marking: None,
+            data,
+            header_extension: packet.header.extension.clone(),
+            csrcs: packet.header.csrcs.clone(),
+            sequence_number: Some(seq),
+            payload_type: Some(packet.header.payload_type),
+            source_addr: Some(addr),
+            raw_packet: Some(packet),
+            capture_time: None,
+        })])
+    }
+}
 
-                    if offset + nal_len > len {
-                        tracing::warn!("STAP-A NAL length exceeds packet size");
-                        self.drop_count.fetch_add(1, Ordering::Relaxed);
-                        break;
-                    }
+/// Opus decoder (RFC 7587): emits each packet as interleaved S16 PCM at the
+/// clock rate. Lost packets, detected from sequence gaps, are concealed with
+/// the decoder's PLC. Output has the negotiated channel count whatever a
+/// packet's ToC says, so a sender switching between mono and stereo frames
+/// does not change the PCM layout: such packets are decoded as coded and
+/// down- or upmixed.
+#[cfg(feature = "opus")]
+pub struct OpusDepacketizer {
+    channels: u16,
+    // Decoders for mono and stereo coded packets, with the sample rate each
+    // was created for. Only the negotiated layout's is normally used.
+    decoders: [Option<(u32, opus_rs::OpusDecoder)>; 2],
+    // Sequence number, timestamp and coded channels of the last decoded
+    // packet
+    last_seq: Option<u16>,
+    last_timestamp: u32,
+    last_coded_channels: u16,
+    // Samples per channel of the last decoded packet, used for concealment
+    last_frame_size: usize,
+    pcm: Vec<f32>,
+    // Counter of packets the decoder rejected
+    drop_count: Arc<AtomicU64>,
+}
 
-                    let nal_data = data.slice(offset..offset + nal_len);
-                    offset += nal_len;
+#[cfg(feature = "opus")]
+impl Default for OpusDepacketizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-                    let is_last = (offset == len) && packet_marker;
+#[cfg(feature = "opus")]
+impl OpusDepacketizer {
+    /// Longest run of lost packets that is concealed; longer gaps are
+    /// skipped rather than filled with synthesized audio.
+    const MAX_CONCEALED_PACKETS: u16 = 5;
 
-                    samples.push(create_video_sample(
-                        nal_data,
-                        raw_packet.header.timestamp,
-                        is_last,
-                        &raw_packet,
-                    ));
-                }
+    /// Mono output, the RFC 7587 default when `sprop-stereo` is absent.
+    pub fn new() -> Self {
+        Self::with_channels(1)
+    }
+
+    /// Output `channels` (1 or 2) interleaved channels.
+    pub fn with_channels(channels: u16) -> Self {
+        Self {
+            channels: channels.clamp(1, 2),
+            decoders: [None, None],
+            last_seq: None,
+            last_timestamp: 0,
+            last_coded_channels: 1,
+            last_frame_size: 0,
+            pcm: Vec::new(),
+            drop_count: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns a shared reference to the atomic drop counter.
+    pub fn drop_counter(&self) -> Arc<AtomicU64> {
+        self.drop_count.clone()
+    }
+
+    /// Output channels for a payload type with these `a=fmtp` parameters:
+    /// stereo only when the sender declared `sprop-stereo=1`.
+    pub fn from_fmtp(fmtp: Option<&str>) -> Self {
+        let stereo = fmtp.is_some_and(|fmtp| {
+            fmtp.split(';').any(|param| {
+                param
+                    .trim()
+                    .split_once('=')
+                    .is_some_and(|(k, v)| k.trim() == "sprop-stereo" && v.trim() == "1")
+            })
+        });
+        Self::with_channels(if stereo { 2 } else { 1 })
+    }
+
+    /// Decode `input` (empty to conceal `frame_size` lost samples), coded
+    /// with `coded_channels`, into S16 little-endian bytes in the negotiated
+    /// layout, returning them with the samples per channel.
+    fn decode(
+        &mut self,
+        input: &[u8],
+        frame_size: usize,
+        sample_rate: u32,
+        coded_channels: u16,
+    ) -> Result<(Bytes, usize), &'static str> {
+        let coded = coded_channels as usize;
+        let slot = &mut self.decoders[coded - 1];
+        if !matches!(slot, Some((rate, _)) if *rate == sample_rate) {
+            let decoder = opus_rs::OpusDecoder::new(sample_rate as i32, coded)?;
+            *slot = Some((sample_rate, decoder));
+        }
+        let Some((_, decoder)) = slot.as_mut() else {
+            return Err("Opus decoder unavailable");
+        };
+        self.pcm.resize(frame_size * coded, 0.0);
+        let samples = decoder.decode(input, frame_size, &mut self.pcm)?;
+        let channels = self.channels as usize;
+        let mut data = Vec::with_capacity(samples * channels * 2);
+        for frame in self.pcm[..samples * coded].chunks_exact(coded) {
+            // Stereo is averaged down to mono; mono is copied to both sides.
+            let mixed = [frame.iter().sum::<f32>() / coded as f32; 2];
+            let frame = if coded == channels {
+                frame
+            } else {
+                &mixed[..channels]
+            };
+            for sample in frame {
+                let value = (sample * 32768.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                data.extend_from_slice(&value.to_le_bytes());
             }
-            // FU-A (Fragmentation Unit type A)
-            28 => {
-                if payload.len() < 2 {
-                    return Ok(vec![]);
-                }
+        }
+        Ok((Bytes::from(data), samples))
+    }
 
-                let fu_header = payload[1];
-                let s_bit = (fu_header & 0x80) != 0;
-                let e_bit = (fu_header & 0x40) != 0;
-                let original_nal_type = fu_header & 0x1F;
+    fn pcm_sample(
+        data: Bytes,
+        timestamp: u32,
+        clock_rate: u32,
+        channels: u16,
+        packet: Option<RtpPacket>,
+        addr: SocketAddr,
+    ) -> MediaSample {
+        MediaSample::Audio(AudioFrame {
+            rtp_timestamp: timestamp,
+            clock_rate,
+            data,
+            format: AudioSampleFormat::S16,
+            channels,
+            sequence_number: packet.as_ref().map(|p| p.header.sequence_number),
+            payload_type: packet.as_ref().map(|p| p.header.payload_type),
+            marker: packet.as_ref().is_some_and(|p| p.header.marker),
+            header_extension: packet.as_ref().and_then(|p| p.header.extension.clone()),
+            source_addr: Some(addr),
+            raw_packet: packet,
+            capture_time: None,
+        })
+    }
+}
 
-                if s_bit {
-                    // Start
-                    let nri = header & 0x60;
-                    let reconstructed_header = nri | original_nal_type;
-
-                    self.fua_buffer.clear();
-                    self.fua_buffer.push(reconstructed_header);
-                    self.fua_buffer.extend_from_slice(&payload[2..]);
-
-                    self.current_timestamp = raw_packet.header.timestamp;
-                    self.last_seq = Some(raw_packet.header.sequence_number);
-                } else {
-                    // Continuation or End
-                    if let Some(last_seq) = self.last_seq {
-                        let expected = last_seq.wrapping_add(1);
-                        if raw_packet.header.sequence_number != expected {
-                            tracing::warn!(
-                                "FU-A Sequence mismatch: expected {}, got {}",
-                                expected,
-                                raw_packet.header.sequence_number
-                            );
-                            self.drop_count.fetch_add(1, Ordering::Relaxed);
-                            self.fua_buffer.clear();
-                            self.last_seq = None;
-                            return Ok(vec![]);
-                        }
-                    } else {
-                        return Ok(vec![]); // Missing start
-                    }
+#[cfg(feature = "opus")]
+impl Depacketizer for OpusDepacketizer {
+    fn drop_count(&self) -> u64 {
+        self.drop_count.load(Ordering::Relaxed)
+    }
 
-                    if raw_packet.header.timestamp != self.current_timestamp {
-                        tracing::warn!("FU-A timestamp mismatch inside frame");
-                        self.drop_count.fetch_add(1, Ordering::Relaxed);
-                        self.fua_buffer.clear();
-                        self.last_seq = None;
-                        return Ok(vec![]);
-                    }
+    fn push(
+        &mut self,
+        packet: RtpPacket,
+        clock_rate: u32,
+        addr: SocketAddr,
+        kind: MediaKind,
+    ) -> MediaResult<Vec<MediaSample>> {
+        if kind != MediaKind::Audio || packet.payload.is_empty() {
+            return Ok(vec![MediaSample::from_rtp_packet(
+                packet, kind, clock_rate, addr,
+            )]);
+        }
+        // Opus always runs at 48 kHz on the wire (RFC 7587 §4.1); other
+        // decoder rates are honoured if negotiated.
+        let sample_rate = if matches!(clock_rate, 8000 | 12000 | 16000 | 24000 | 48000) {
+            clock_rate
+        } else {
+            48000
+        };
+        let seq = packet.header.sequence_number;
+        let mut samples = Vec::new();
 
-                    self.fua_buffer.extend_from_slice(&payload[2..]);
-                    self.last_seq = Some(raw_packet.header.sequence_number);
-
-                    if e_bit {
-                        // End of fragment, emit frame
-                        let data = Bytes::from(self.fua_buffer.clone());
-                        samples.push(create_video_sample(
-                            data,
-                            self.current_timestamp,
-                            raw_packet.header.marker,
-                            &raw_packet,
-                        ));
-
-                        self.fua_buffer.clear();
-                        self.last_seq = None;
+        if let Some(last_seq) = self.last_seq {
+            let gap = seq.wrapping_sub(last_seq);
+            if gap == 0 || gap >= 0x8000 {
+                return Ok(vec![]); // Duplicate or late packet
+            }
+            if self.last_frame_size > 0 && gap - 1 <= Self::MAX_CONCEALED_PACKETS {
+                let channels = self.channels;
+                for lost in 1..gap {
+                    let timestamp = self
+                        .last_timestamp
+                        .wrapping_add((self.last_frame_size * lost as usize) as u32);
+                    let coded = self.last_coded_channels;
+                    match self.decode(&[], self.last_frame_size, sample_rate, coded) {
+                        Ok((data, _)) => samples.push(Self::pcm_sample(
+                            data, timestamp, clock_rate, channels, None, addr,
+                        )),
+                        Err(e) => {
+                            tracing::warn!("Opus concealment failed: {}", e);
+                            break;
+                        }
                     }
                 }
             }
-            // Single NAL unit (1-23)
-            1..=23 => {
-                let data = Bytes::from(payload);
-                samples.push(create_video_sample(
+        }
+
+        let channels = self.channels;
+        let coded = if packet.payload[0] & 0x04 != 0 { 2 } else { 1 };
+        // Up to 120 ms per packet (RFC 6716 §3.2.5).
+        let max_frame_size = sample_rate as usize * 120 / 1000;
+        self.last_seq = Some(seq);
+        self.last_timestamp = packet.header.timestamp;
+        match self.decode(&packet.payload, max_frame_size, sample_rate, coded) {
+            Ok((data, frame_size)) => {
+                self.last_frame_size = frame_size;
+                self.last_coded_channels = coded;
+                let timestamp = packet.header.timestamp;
+                samples.push(Self::pcm_sample(
                     data,
-                    raw_packet.header.timestamp,
-                    raw_packet.header.marker,
-                    &raw_packet,
+                    timestamp,
+                    clock_rate,
+                    channels,
+                    Some(packet),
+                    addr,
                 ));
             }
-            // Unknown or unsupported type headers (fallback)
-            _ => {
-                let data = Bytes::from(payload);
-                samples.push(create_video_sample(
-                    data,
-                    raw_packet.header.timestamp,
-                    raw_packet.header.marker,
-                    &raw_packet,
-                ));
+            Err(e) => {
+                tracing::warn!("Opus packet {} not decodable: {}", seq, e);
+                self.drop_count.fetch_add(1, Ordering::Relaxed);
             }
         }
-
         Ok(samples)
     }
 }
 
 pub trait DepacketizerFactory: std::fmt::Debug + Send + Sync {
     fn create(&self, kind: MediaKind) -> Box<dyn Depacketizer>;
+
+    /// Create a depacketizer for a negotiated codec, named as in `a=rtpmap`
+    /// (`VP8`, `H264`, `opus`, ...). The receiver calls this whenever the
+    /// incoming payload type maps to a different codec. Defaults to
+    /// [`create`](Self::create).
+    fn create_for_codec(&self, kind: MediaKind, codec: &str) -> Box<dyn Depacketizer> {
+        let _ = codec;
+        self.create(kind)
+    }
+
+    /// Like [`create_for_codec`](Self::create_for_codec), with the remote's
+    /// `a=fmtp` parameters for the payload type (e.g. Opus `sprop-stereo`).
+    /// Defaults to [`create_for_codec`](Self::create_for_codec).
+    fn create_for_format(
+        &self,
+        kind: MediaKind,
+        codec: &str,
+        fmtp: Option<&str>,
+    ) -> Box<dyn Depacketizer> {
+        let _ = fmtp;
+        self.create_for_codec(kind, codec)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -260,6 +686,45 @@ impl DepacketizerFactory for DefaultDepacketizerFactory {
     }
 }
 
+/// Selects the depacketizer from the negotiated codec: VP8 and H.264 video
+/// is reassembled into frames, Opus is decoded to PCM with the `opus`
+/// feature, and every other codec is passed through per packet.
+#[derive(Debug, Default)]
+pub struct CodecDepacketizerFactory;
+
+impl DepacketizerFactory for CodecDepacketizerFactory {
+    fn create(&self, _kind: MediaKind) -> Box<dyn Depacketizer> {
+        Box::new(PassThroughDepacketizer)
+    }
+
+    fn create_for_codec(&self, kind: MediaKind, codec: &str) -> Box<dyn Depacketizer> {
+        self.create_for_format(kind, codec, None)
+    }
+
+    fn create_for_format(
+        &self,
+        kind: MediaKind,
+        codec: &str,
+        fmtp: Option<&str>,
+    ) -> Box<dyn Depacketizer> {
+        #[cfg(not(feature = "opus"))]
+        let _ = fmtp;
+        if kind == MediaKind::Video {
+            if codec.eq_ignore_ascii_case("VP8") {
+                return Box::new(Vp8Depacketizer::new());
+            }
+            if codec.eq_ignore_ascii_case("H264") {
+                return Box::new(H264Depacketizer::new());
+            }
+        }
+        #[cfg(feature = "opus")]
+        if kind == MediaKind::Audio && codec.eq_ignore_ascii_case("opus") {
+            return Box::new(OpusDepacketizer::from_fmtp(fmtp));
+        }
+        Box::new(PassThroughDepacketizer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -293,9 +758,10 @@ mod tests {
         assert_eq!(frames.len(), 1);
         match &frames[0] {
             MediaSample::Video(v) => {
-                assert_eq!(v.data, Bytes::from(payload));
+                assert_eq!(v.data, Bytes::from([&[0, 0, 0, 1], &payload[..]].concat()));
                 assert_eq!(v.rtp_timestamp, 100);
                 assert!(v.is_last_packet);
+                assert!(v.is_keyframe);
             }
             _ => panic!("Expected Video sample"),
         }
@@ -322,22 +788,15 @@ mod tests {
             .push(packet, 90000, dummy_addr(), MediaKind::Video)
             .unwrap();
 
-        assert_eq!(frames.len(), 2);
-
+        // Both NAL units land in one Annex-B access unit.
+        assert_eq!(frames.len(), 1);
         match &frames[0] {
             MediaSample::Video(v) => {
-                assert_eq!(v.data, Bytes::from(nal1));
+                let expected = [&[0, 0, 0, 1], &nal1[..], &[0, 0, 0, 1], &nal2[..]].concat();
+                assert_eq!(v.data, Bytes::from(expected));
                 assert_eq!(v.rtp_timestamp, 200);
-                assert!(!v.is_last_packet); // Not last in packet
-            }
-            _ => panic!("Expected Video sample"),
-        }
-
-        match &frames[1] {
-            MediaSample::Video(v) => {
-                assert_eq!(v.data, Bytes::from(nal2));
-                assert_eq!(v.rtp_timestamp, 200);
-                assert!(v.is_last_packet); // Last in packet inherits marker
+                assert!(v.is_last_packet);
+                assert!(v.is_keyframe, "SPS marks a key frame");
             }
             _ => panic!("Expected Video sample"),
         }
@@ -369,7 +828,7 @@ mod tests {
             .unwrap();
         assert_eq!(frames3.len(), 1);
 
-        let expected_nal = vec![0x65, 0x01, 0x02, 0x03, 0x04];
+        let expected_nal = vec![0, 0, 0, 1, 0x65, 0x01, 0x02, 0x03, 0x04];
         match &frames3[0] {
             MediaSample::Video(v) => {
                 assert_eq!(v.data, Bytes::from(expected_nal));
@@ -434,6 +893,258 @@ mod tests {
         assert_eq!(res.len(), 1, "PassThrough should emit immediately");
     }
 
+    #[test]
+    fn test_vp8_reassembles_payloader_output() {
+        use crate::media::packetizer::{Payloader, Vp8Payloader};
+
+        let frame: Vec<u8> = (0..=255u8).cycle().take(2500).collect();
+        let payloads = Vp8Payloader.payload(1000, Bytes::from(frame.clone()));
+        assert_eq!(payloads.len(), 3);
+
+        let mut depacketizer = Vp8Depacketizer::new();
+        let mut samples = Vec::new();
+        for (i, payload) in payloads.iter().enumerate() {
+            let last = i == payloads.len() - 1;
+            let packet = create_packet(payload.to_vec(), 100 + i as u16, 9000, last);
+            samples.extend(
+                depacketizer
+                    .push(packet, 90000, dummy_addr(), MediaKind::Video)
+                    .unwrap(),
+            );
+        }
+        assert_eq!(samples.len(), 1, "one frame per marker packet");
+        match &samples[0] {
+            MediaSample::Video(v) => {
+                assert_eq!(v.data, Bytes::from(frame));
+                assert_eq!(v.rtp_timestamp, 9000);
+                assert!(v.is_last_packet);
+            }
+            _ => panic!("Expected Video sample"),
+        }
+        assert_eq!(depacketizer.drop_count(), 0);
+    }
+
+    #[test]
+    fn test_vp8_two_packet_frame_keyframe_flag() {
+        let mut depacketizer = Vp8Depacketizer::new();
+        let mut push = |payload: Vec<u8>, seq, timestamp, marker| {
+            depacketizer
+                .push(
+                    create_packet(payload, seq, timestamp, marker),
+                    90000,
+                    dummy_addr(),
+                    MediaKind::Video,
+                )
+                .unwrap()
+        };
+
+        // Key frame: P=0 in the first payload header byte.
+        assert!(push(vec![0x10, 0x50, 0x01], 10, 3000, false).is_empty());
+        match &push(vec![0x00, 0x02, 0x03], 11, 3000, true)[..] {
+            [MediaSample::Video(v)] => {
+                assert_eq!(v.data, Bytes::from_static(&[0x50, 0x01, 0x02, 0x03]));
+                assert!(v.is_keyframe);
+            }
+            other => panic!("Expected one Video sample, got {:?}", other),
+        }
+
+        // Interframe: P=1.
+        assert!(push(vec![0x10, 0x51], 12, 6000, false).is_empty());
+        match &push(vec![0x00, 0x04], 13, 6000, true)[..] {
+            [MediaSample::Video(v)] => assert!(!v.is_keyframe),
+            other => panic!("Expected one Video sample, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_vp8_extended_descriptor_and_loss() {
+        let mut depacketizer = Vp8Depacketizer::new();
+        // X=1, S=1; I=1 with 15-bit PictureID, L=1, T=1.
+        let first = vec![0x90, 0xE0, 0x80, 0x01, 0x05, 0x20, 0xAA, 0xBB];
+        let packet = create_packet(first, 1, 3000, false);
+        assert!(
+            depacketizer
+                .push(packet, 90000, dummy_addr(), MediaKind::Video)
+                .unwrap()
+                .is_empty()
+        );
+        // Sequence 2 is lost; the frame is dropped instead of emitted corrupt.
+        let last = vec![0x00, 0xCC];
+        let packet = create_packet(last, 3, 3000, true);
+        assert!(
+            depacketizer
+                .push(packet, 90000, dummy_addr(), MediaKind::Video)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(depacketizer.drop_count(), 1);
+
+        // The next complete frame still comes through.
+        let packet = create_packet(vec![0x10, 0xDD], 4, 6000, true);
+        let res = depacketizer
+            .push(packet, 90000, dummy_addr(), MediaKind::Video)
+            .unwrap();
+        match &res[..] {
+            [MediaSample::Video(v)] => assert_eq!(v.data, Bytes::from_static(&[0xDD])),
+            other => panic!("Expected one Video sample, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_codec_factory_selects_by_codec() {
+        let factory = CodecDepacketizerFactory;
+        let fu_a_start = || create_packet(vec![0x7C, 0x85, 0x01], 10, 100, false);
+
+        // H.264 buffers the FU-A start instead of passing it through.
+        let mut h264 = factory.create_for_codec(MediaKind::Video, "h264");
+        assert!(
+            h264.push(fu_a_start(), 90000, dummy_addr(), MediaKind::Video)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Unknown video codecs and audio pass packets through.
+        let mut vp9 = factory.create_for_codec(MediaKind::Video, "VP9");
+        assert_eq!(
+            vp9.push(fu_a_start(), 90000, dummy_addr(), MediaKind::Video)
+                .unwrap()
+                .len(),
+            1
+        );
+        #[cfg(not(feature = "opus"))]
+        {
+            let mut opus = factory.create_for_codec(MediaKind::Audio, "opus");
+            assert_eq!(
+                opus.push(fu_a_start(), 48000, dummy_addr(), MediaKind::Audio)
+                    .unwrap()
+                    .len(),
+                1
+            );
+        }
+    }
+
+    #[cfg(feature = "opus")]
+    fn opus_packets(channels: usize, count: u16) -> Vec<RtpPacket> {
+        let mut encoder =
+            opus_rs::OpusEncoder::new(48000, channels, opus_rs::Application::Audio).unwrap();
+        let pcm: Vec<f32> = (0..960 * channels)
+            .map(|i| ((i / channels) as f32 * 0.05).sin() * 0.5)
+            .collect();
+        (0..count)
+            .map(|i| {
+                let mut out = vec![0u8; 1500];
+                let len = encoder.encode(&pcm, 960, &mut out).unwrap();
+                out.truncate(len);
+                create_packet(out, 100 + i, 960 * i as u32, false)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "opus")]
+    #[test]
+    fn test_opus_decodes_to_pcm() {
+        for channels in [1u16, 2] {
+            let fmtp = (channels == 2).then_some("minptime=10;sprop-stereo=1");
+            let mut depacketizer =
+                CodecDepacketizerFactory.create_for_format(MediaKind::Audio, "opus", fmtp);
+            let packet = opus_packets(channels as usize, 1).remove(0);
+            assert_eq!(packet.payload[0] & 0x04 != 0, channels == 2);
+            let samples = depacketizer
+                .push(packet, 48000, dummy_addr(), MediaKind::Audio)
+                .unwrap();
+            match &samples[..] {
+                [MediaSample::Audio(a)] => {
+                    assert_eq!(a.format, AudioSampleFormat::S16);
+                    assert_eq!(a.channels, channels);
+                    assert_eq!(a.clock_rate, 48000);
+                    assert_eq!(a.samples_per_channel(), 960, "20 ms at 48 kHz");
+                    assert_eq!(a.data.len(), 960 * 2 * channels as usize);
+                }
+                other => panic!("Expected one Audio sample, got {:?}", other),
+            }
+        }
+    }
+
+    #[cfg(feature = "opus")]
+    #[test]
+    fn test_opus_channels_follow_negotiation_not_toc() {
+        // A mono-negotiated stream keeps mono output across a stereo packet,
+        // and a stereo one upmixes mono packets.
+        for (fmtp, negotiated, sent) in [(None, 1u16, 2usize), (Some("sprop-stereo=1"), 2, 1)] {
+            let mut depacketizer = OpusDepacketizer::from_fmtp(fmtp);
+            for packet in opus_packets(sent, 2) {
+                match &depacketizer
+                    .push(packet, 48000, dummy_addr(), MediaKind::Audio)
+                    .unwrap()[..]
+                {
+                    [MediaSample::Audio(a)] => {
+                        assert_eq!(a.channels, negotiated);
+                        assert_eq!(a.data.len(), 960 * 2 * negotiated as usize);
+                    }
+                    other => panic!("Expected one Audio sample, got {:?}", other),
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "opus")]
+    #[test]
+    fn test_opus_conceals_lost_packet() {
+        let mut depacketizer = OpusDepacketizer::new();
+        let mut packets = opus_packets(1, 3);
+        let third = packets.pop().unwrap();
+        let first = packets.remove(0);
+        assert_eq!(
+            depacketizer
+                .push(first, 48000, dummy_addr(), MediaKind::Audio)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Sequence 101 is lost: a concealed frame precedes the decoded one.
+        let samples = depacketizer
+            .push(third, 48000, dummy_addr(), MediaKind::Audio)
+            .unwrap();
+        let frames: Vec<_> = samples
+            .iter()
+            .map(|s| match s {
+                MediaSample::Audio(a) => a,
+                _ => panic!("Expected Audio sample"),
+            })
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].rtp_timestamp, 960);
+        assert_eq!(frames[0].sequence_number, None);
+        assert_eq!(frames[0].samples_per_channel(), 960);
+        assert_eq!(frames[1].rtp_timestamp, 1920);
+        assert_eq!(frames[1].sequence_number, Some(102));
+        assert_eq!(depacketizer.drop_count(), 0);
+    }
+
+    #[test]
+    fn test_access_unit_missing_leading_packets_is_dropped() {
+        let mut depacketizer = H264Depacketizer::new();
+        let mut push = |payload: Vec<u8>, seq, timestamp, marker| {
+            depacketizer
+                .push(
+                    create_packet(payload, seq, timestamp, marker),
+                    90000,
+                    dummy_addr(),
+                    MediaKind::Video,
+                )
+                .unwrap()
+        };
+
+        assert_eq!(push(vec![0x65, 0x01], 1, 3000, true).len(), 1);
+        // Seq 2, the first slice of the next access unit, is lost; what
+        // remains of that access unit would decode as a partial picture.
+        assert!(push(vec![0x41, 0x02], 3, 6000, true).is_empty());
+        // Continuity resumes with the following access unit.
+        assert_eq!(push(vec![0x41, 0x03], 4, 9000, true).len(), 1);
+        assert_eq!(depacketizer.drop_count(), 1);
+    }
+
     #[test]
     fn test_fu_a_loss() {
         let mut depacketizer = H264Depacketizer::new();
@@ -460,6 +1171,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_access_unit_across_packetization_modes() {
+        let mut depacketizer = H264Depacketizer::new();
+        let mut push = |payload: Vec<u8>, seq, timestamp, marker| {
+            depacketizer
+                .push(
+                    create_packet(payload, seq, timestamp, marker),
+                    90000,
+                    dummy_addr(),
+                    MediaKind::Video,
+                )
+                .unwrap()
+        };
+
+        // SPS + PPS in a STAP-A, then an IDR slice split into FU-A fragments.
+        let stap_a = vec![24, 0, 2, 0x67, 0x42, 0, 2, 0x68, 0xCE];
+        assert!(push(stap_a, 1, 3000, false).is_empty());
+        assert!(push(vec![0x7C, 0x85, 0xAA], 2, 3000, false).is_empty());
+        match &push(vec![0x7C, 0x45, 0xBB], 3, 3000, true)[..] {
+            [MediaSample::Video(v)] => {
+                let expected = [
+                    0, 0, 0, 1, 0x67, 0x42, 0, 0, 0, 1, 0x68, 0xCE, 0, 0, 0, 1, 0x65, 0xAA, 0xBB,
+                ];
+                assert_eq!(v.data, Bytes::copy_from_slice(&expected));
+                assert!(v.is_keyframe);
+                assert_eq!(v.sequence_number, Some(3));
+            }
+            other => panic!("Expected one Video sample, got {:?}", other),
+        }
+
+        // Two non-IDR slices as single NAL units form one delta frame.
+        assert!(push(vec![0x41, 0x01], 4, 6000, false).is_empty());
+        match &push(vec![0x41, 0x02], 5, 6000, true)[..] {
+            [MediaSample::Video(v)] => {
+                let expected = [0, 0, 0, 1, 0x41, 0x01, 0, 0, 0, 1, 0x41, 0x02];
+                assert_eq!(v.data, Bytes::copy_from_slice(&expected));
+                assert!(!v.is_keyframe);
+            }
+            other => panic!("Expected one Video sample, got {:?}", other),
+        }
+
+        // A lost marker packet drops the access unit once the next one
+        // starts, and that one too: the lost packet may have been its first.
+        assert!(push(vec![0x41, 0x03], 6, 9000, false).is_empty());
+        assert!(push(vec![0x41, 0x04], 8, 12000, true).is_empty());
+        assert_eq!(push(vec![0x41, 0x05], 9, 15000, true).len(), 1);
+        assert_eq!(depacketizer.drop_count(), 2);
+    }
+
     #[test]
     fn test_drop_count_fu_a_loss() {
         let mut depacketizer = H264Depacketizer::new();
@@ -494,13 +1254,13 @@ mod tests {
         );
 
         // Second loss should increment again
-        let packet4 = create_packet(vec![0x7C, 0x85, 0x03], 20, timestamp + 1, false);
+        let packet4 = create_packet(vec![0x7C, 0x85, 0x03], 13, timestamp + 1, false);
         let _ = depacketizer
             .push(packet4, 90000, dummy_addr(), MediaKind::Video)
             .unwrap();
         assert_eq!(depacketizer.drop_count(), 1, "FU-A start should not count");
 
-        let packet5 = create_packet(vec![0x7C, 0x45, 0x04], 22, timestamp + 1, true);
+        let packet5 = create_packet(vec![0x7C, 0x45, 0x04], 15, timestamp + 1, true);
         let _ = depacketizer
             .push(packet5, 90000, dummy_addr(), MediaKind::Video)
             .unwrap();
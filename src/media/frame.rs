@@ -1,8 +1,9 @@
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::time::SystemTime;
 
-use crate::rtp::{RtpHeader, RtpHeaderExtension, RtpPacket};
+use crate::rtp::{FrameMarking, RtpHeader, RtpHeaderExtension, RtpPacket};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum MediaKind {
@@ -20,11 +21,27 @@ pub enum VideoPixelFormat {
     Unspecified,
 }
 
+/// Sample encoding of [`AudioFrame::data`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum AudioSampleFormat {
+    /// Codec payload as carried in RTP (Opus, PCMU, ...).
+    #[default]
+    Encoded,
+    /// Interleaved signed 16-bit little-endian PCM.
+    S16,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AudioFrame {
     pub rtp_timestamp: u32,
     pub clock_rate: u32,
     pub data: Bytes,
+    /// Encoding of `data`. `S16` when a decoding depacketizer produced PCM.
+    #[serde(default)]
+    pub format: AudioSampleFormat,
+    /// Interleaved channels in `data` for PCM formats; 0 for encoded payloads.
+    #[serde(default)]
+    pub channels: u16,
     pub sequence_number: Option<u16>,
     pub payload_type: Option<u8>,
     pub marker: bool,
@@ -34,6 +51,10 @@ pub struct AudioFrame {
     pub source_addr: Option<SocketAddr>,
     #[serde(skip)]
     pub raw_packet: Option<RtpPacket>,
+    /// Sender wallclock time at capture. Derived from the RTCP Sender Report
+    /// NTP/RTP mapping on receive; `None` until the first SR arrives.
+    #[serde(skip)]
+    pub capture_time: Option<SystemTime>,
 }
 
 impl Default for AudioFrame {
@@ -42,16 +63,71 @@ impl Default for AudioFrame {
             rtp_timestamp: 0,
             clock_rate: 0,
             data: Bytes::new(),
+            format: AudioSampleFormat::Encoded,
+            channels: 0,
             sequence_number: None,
             payload_type: None,
             marker: false,
             header_extension: None,
             source_addr: None,
             raw_packet: None,
+            capture_time: None,
         }
     }
 }
 
+impl AudioFrame {
+    /// Samples per channel carried by a PCM frame; 0 for encoded payloads.
+    pub fn samples_per_channel(&self) -> usize {
+        match self.format {
+            AudioSampleFormat::Encoded => 0,
+            AudioSampleFormat::S16 => self.data.len() / 2 / usize::from(self.channels.max(1)),
+        }
+    }
+}
+
+/// A DTMF named event carried in a telephone-event payload (RFC 4733 §2.3).
+/// Inbound telephone-event packets reach the audio track as [`AudioFrame`]s
+/// with the negotiated telephone-event payload type; parse their `data`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TelephoneEvent {
+    /// Event code: 0-9 digits, 10 `*`, 11 `#`, 12-15 `A`-`D`.
+    pub event: u8,
+    /// Set on the final packets of the event.
+    pub end: bool,
+    /// Power level in -dBm0, 0 to 63.
+    pub volume: u8,
+    /// Duration so far, in RTP timestamp units.
+    pub duration: u16,
+}
+
+impl TelephoneEvent {
+    pub fn parse(payload: &[u8]) -> Option<Self> {
+        let &[event, flags, duration_hi, duration_lo, ..] = payload else {
+            return None;
+        };
+        Some(Self {
+            event,
+            end: flags & 0x80 != 0,
+            volume: flags & 0x3f,
+            duration: u16::from_be_bytes([duration_hi, duration_lo]),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let duration = self.duration.to_be_bytes();
+        let flags = if self.end { 0x80 } else { 0 } | (self.volume & 0x3f);
+        [self.event, flags, duration[0], duration[1]]
+    }
+
+    /// The DTMF key for `event`, if it is one of the 16 DTMF events.
+    pub fn digit(&self) -> Option<char> {
+        b"0123456789*#ABCD"
+            .get(usize::from(self.event))
+            .map(|&key| key as char)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct VideoFrame {
     pub rtp_timestamp: u32,
@@ -59,7 +135,18 @@ pub struct VideoFrame {
     pub height: u16,
     pub format: VideoPixelFormat,
     pub rotation_deg: u16,
+    #[serde(default)]
+    pub flip_horizontal: bool,
     pub is_last_packet: bool,
+    /// Set by depacketizers that reassemble whole frames when the frame can
+    /// be decoded on its own (VP8 key frame, H.264 IDR).
+    #[serde(default)]
+    pub is_keyframe: bool,
+    /// Frame marking extension. Parsed from incoming packets when the
+    /// extension is negotiated, and written on every packet of the frame
+    /// when sending.
+    #[serde(default)]
+    pub frame_marking: Option<FrameMarking>,
     pub data: Bytes,
     pub header_extension: Option<RtpHeaderExtension>,
     pub csrcs: Vec<u32>,
@@ -69,6 +156,10 @@ pub struct VideoFrame {
     pub source_addr: Option<SocketAddr>,
     #[serde(skip)]
     pub raw_packet: Option<RtpPacket>,
+    /// Sender wallclock time at capture. Derived from the RTCP Sender Report
+    /// NTP/RTP mapping on receive; `None` until the first SR arrives.
+    #[serde(skip)]
+    pub capture_time: Option<SystemTime>,
 }
 
 impl Default for VideoFrame {
@@ -79,7 +170,10 @@ impl Default for VideoFrame {
             height: 0,
             format: VideoPixelFormat::default(),
             rotation_deg: 0,
+            flip_horizontal: false,
             is_last_packet: false,
+            is_keyframe: false,
+            frame_marking: None,
             data: Bytes::new(),
             header_extension: None,
             csrcs: Vec::new(),
@@ -87,10 +181,32 @@ impl Default for VideoFrame {
             payload_type: None,
             source_addr: None,
             raw_packet: None,
+            capture_time: None,
         }
     }
 }
 
+impl VideoFrame {
+    /// Clockwise rotation in degrees (0, 90, 180 or 270) to apply before rendering.
+    pub fn rotation(&self) -> u16 {
+        self.rotation_deg
+    }
+
+    /// Apply a CVO byte from the `urn:3gpp:video-orientation` header extension
+    /// (3GPP TS 26.114): bits 0-1 carry the rotation, bit 2 the horizontal flip.
+    pub fn set_video_orientation(&mut self, cvo: u8) {
+        self.rotation_deg = (cvo & 0x03) as u16 * 90;
+        self.flip_horizontal = cvo & 0x04 != 0;
+    }
+
+    /// Encode `rotation_deg` and `flip_horizontal` as a CVO byte. Rotations that
+    /// are not a multiple of 90 degrees are rounded down.
+    pub fn video_orientation(&self) -> u8 {
+        let rotation = ((self.rotation_deg % 360) / 90) as u8;
+        rotation | if self.flip_horizontal { 0x04 } else { 0 }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum MediaSample {
     Audio(AudioFrame),
@@ -105,6 +221,28 @@ impl MediaSample {
         }
     }
 
+    /// RTP timestamp of the frame.
+    pub fn rtp_timestamp(&self) -> u32 {
+        match self {
+            MediaSample::Audio(f) => f.rtp_timestamp,
+            MediaSample::Video(f) => f.rtp_timestamp,
+        }
+    }
+
+    pub fn capture_time(&self) -> Option<SystemTime> {
+        match self {
+            MediaSample::Audio(f) => f.capture_time,
+            MediaSample::Video(f) => f.capture_time,
+        }
+    }
+
+    pub fn set_capture_time(&mut self, time: Option<SystemTime>) {
+        match self {
+            MediaSample::Audio(f) => f.capture_time = time,
+            MediaSample::Video(f) => f.capture_time = time,
+        }
+    }
+
     pub fn into_rtp_packet(
         self,
         ssrc: u32,
@@ -160,19 +298,22 @@ impl MediaSample {
         addr: SocketAddr,
     ) -> Self {
         let raw_packet = packet.clone();
-        let data = bytes::Bytes::from(packet.payload);
+        let data = packet.payload;
 
         match kind {
             MediaKind::Audio => MediaSample::Audio(AudioFrame {
                 rtp_timestamp: packet.header.timestamp,
                 clock_rate,
                 data,
+                format: AudioSampleFormat::Encoded,
+                channels: 0,
                 sequence_number: Some(packet.header.sequence_number),
                 payload_type: Some(packet.header.payload_type),
                 marker: packet.header.marker,
                 header_extension: packet.header.extension,
                 source_addr: Some(addr),
                 raw_packet: Some(raw_packet),
+                capture_time: None,
             }),
             MediaKind::Video => MediaSample::Video(VideoFrame {
                 rtp_timestamp: packet.header.timestamp,
@@ -180,7 +321,10 @@ impl MediaSample {
                 height: 0,
                 format: VideoPixelFormat::Unspecified,
                 rotation_deg: 0,
+                flip_horizontal: false,
                 is_last_packet: packet.header.marker,
+                is_keyframe: false,
+                frame_marking: None,
                 data,
                 header_extension: packet.header.extension,
                 csrcs: packet.header.csrcs,
@@ -188,6 +332,7 @@ impl MediaSample {
                 payload_type: Some(packet.header.payload_type),
                 source_addr: Some(addr),
                 raw_packet: Some(raw_packet),
+                capture_time: None,
             }),
         }
     }
@@ -7,14 +7,25 @@ pub mod pipeline;
 pub mod spsc;
 pub mod track;
 
-pub use depacketizer::{Depacketizer, H264Depacketizer, PassThroughDepacketizer};
+#[cfg(feature = "opus")]
+pub use depacketizer::OpusDepacketizer;
+pub use depacketizer::{
+    CodecDepacketizerFactory, Depacketizer, H264Depacketizer, PassThroughDepacketizer,
+    Vp8Depacketizer,
+};
 pub use error::{MediaError, MediaResult};
-pub use frame::{AudioFrame, MediaKind, MediaSample, VideoFrame, VideoPixelFormat};
+pub use frame::{
+    AudioFrame, AudioSampleFormat, MediaKind, MediaSample, TelephoneEvent, VideoFrame,
+    VideoPixelFormat,
+};
 pub use jitter_buffer::JitterBuffer;
+#[cfg(feature = "opus")]
+pub use packetizer::{OpusEncoderSettings, OpusPacketizer};
 pub use packetizer::{Packetizer, Payloader, SimplePayloader, Vp8Payloader};
 pub use pipeline::{
     ChannelMediaSink, ChannelMediaSource, DynMediaSink, DynMediaSource, MediaSink, MediaSource,
-    TrackMediaSink, TrackMediaSource, spawn_media_pump, track_from_source,
+    PacedSource, PumpHandle, TimestampNormalizer, TrackMediaSink, TrackMediaSource,
+    spawn_media_pump, track_from_source,
 };
 pub use spsc::SpscRing;
 pub use track::{
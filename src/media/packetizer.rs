@@ -1,8 +1,14 @@
+#[cfg(feature = "opus")]
+use std::collections::HashMap;
 use std::collections::VecDeque;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+#[cfg(feature = "opus")]
+use tokio::sync::watch;
 
+#[cfg(feature = "opus")]
+use crate::media::{AudioFrame, AudioSampleFormat};
 use crate::media::{DynMediaSource, MediaKind, MediaResult, MediaSample, MediaSource};
 
 /// Payloader splits a frame into RTP payloads
@@ -123,3 +129,225 @@ impl Payloader for SimplePayloader {
         payloads
     }
 }
+
+/// Opus encoder settings negotiated through the remote `a=fmtp`
+/// (RFC 7587 §6.1).
+#[cfg(feature = "opus")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpusEncoderSettings {
+    /// Code in-band FEC (`useinbandfec=1`), letting the receiver rebuild a
+    /// lost packet from the one after it.
+    pub inband_fec: bool,
+    /// Stop sending during silence (`usedtx=1`).
+    pub dtx: bool,
+    /// Constant bitrate (`cbr=1`).
+    pub cbr: bool,
+    /// Target bitrate, capped by `maxaveragebitrate`.
+    pub bitrate_bps: i32,
+    /// Expected loss in percent. In-band FEC is only coded when non-zero.
+    pub packet_loss_perc: i32,
+}
+
+#[cfg(feature = "opus")]
+impl Default for OpusEncoderSettings {
+    fn default() -> Self {
+        Self {
+            inband_fec: false,
+            dtx: false,
+            cbr: false,
+            bitrate_bps: 32000,
+            packet_loss_perc: 10,
+        }
+    }
+}
+
+#[cfg(feature = "opus")]
+impl OpusEncoderSettings {
+    /// Settings for the parameters of a negotiated `a=fmtp` line, as
+    /// returned by [`RtpSender::negotiated_fmtp`](crate::RtpSender::negotiated_fmtp).
+    pub fn from_fmtp(fmtp: &HashMap<String, String>) -> Self {
+        let flag = |name: &str| fmtp.get(name).is_some_and(|value| value.trim() == "1");
+        let mut settings = Self {
+            inband_fec: flag("useinbandfec"),
+            dtx: flag("usedtx"),
+            cbr: flag("cbr"),
+            ..Self::default()
+        };
+        if let Some(max) = fmtp
+            .get("maxaveragebitrate")
+            .and_then(|value| value.trim().parse::<i32>().ok())
+        {
+            settings.bitrate_bps = settings.bitrate_bps.min(max.clamp(6000, 510_000));
+        }
+        settings
+    }
+
+    fn apply(&self, encoder: &mut opus_rs::OpusEncoder) {
+        encoder.use_inband_fec = self.inband_fec;
+        encoder.use_cbr = self.cbr;
+        encoder.bitrate_bps = self.bitrate_bps;
+        encoder.packet_loss_perc = self.packet_loss_perc;
+    }
+}
+
+/// Opus encoder (RFC 7587): wraps a source of S16 PCM audio frames, each a
+/// valid Opus frame duration at the frame's clock rate, and emits one Opus
+/// payload per frame. Other samples pass through unchanged.
+///
+/// With DTX enabled, silence is sent normally for a short hangover and then
+/// only as a periodic comfort-noise update; the first frame after a
+/// suppressed stretch carries the marker bit (RFC 7587 §4.1).
+#[cfg(feature = "opus")]
+pub struct OpusPacketizer {
+    source: Box<DynMediaSource>,
+    fmtp: Option<watch::Receiver<HashMap<String, String>>>,
+    settings: OpusEncoderSettings,
+    // Encoder with the sample rate and channel count it was created for
+    encoder: Option<(u32, u16, opus_rs::OpusEncoder)>,
+    // Length of the current silence, and time since a frame was last sent
+    // during it, in milliseconds
+    silent_ms: u32,
+    since_sent_ms: u32,
+    suppressed: bool,
+    output: Vec<u8>,
+}
+
+#[cfg(feature = "opus")]
+impl OpusPacketizer {
+    /// Silence sent in full before DTX suppresses it (as libopus).
+    const DTX_HANGOVER_MS: u32 = 200;
+    /// Interval between comfort-noise updates while suppressed (as libopus).
+    const DTX_UPDATE_MS: u32 = 400;
+    /// Peak amplitude up to which a frame counts as silence.
+    const SILENCE_PEAK: u16 = 8;
+
+    pub fn new(source: Box<DynMediaSource>, settings: OpusEncoderSettings) -> Self {
+        Self {
+            source,
+            fmtp: None,
+            settings,
+            encoder: None,
+            silent_ms: 0,
+            since_sent_ms: 0,
+            suppressed: false,
+            output: vec![0; 1500],
+        }
+    }
+
+    /// Encode with the settings of a negotiated fmtp, following later
+    /// renegotiations, e.g. from
+    /// [`RtpSender::watch_negotiated_fmtp`](crate::RtpSender::watch_negotiated_fmtp).
+    pub fn with_negotiated_fmtp(
+        source: Box<DynMediaSource>,
+        mut fmtp: watch::Receiver<HashMap<String, String>>,
+    ) -> Self {
+        let settings = OpusEncoderSettings::from_fmtp(&fmtp.borrow_and_update());
+        let mut packetizer = Self::new(source, settings);
+        packetizer.fmtp = Some(fmtp);
+        packetizer
+    }
+
+    pub fn settings(&self) -> OpusEncoderSettings {
+        self.settings
+    }
+
+    fn refresh_settings(&mut self) {
+        let Some(fmtp) = self.fmtp.as_mut() else {
+            return;
+        };
+        if !fmtp.has_changed().unwrap_or(false) {
+            return;
+        }
+        self.settings = OpusEncoderSettings::from_fmtp(&fmtp.borrow_and_update());
+        if let Some((_, _, encoder)) = self.encoder.as_mut() {
+            self.settings.apply(encoder);
+        }
+        if !self.settings.dtx {
+            self.silent_ms = 0;
+        }
+    }
+
+    /// Encode a PCM frame, or `None` if it is dropped: DTX suppressed it or
+    /// the encoder rejected it.
+    fn encode(&mut self, mut frame: AudioFrame) -> Option<AudioFrame> {
+        let channels = frame.channels.max(1);
+        let sample_rate = frame.clock_rate;
+        if !matches!(&self.encoder, Some((r, c, _)) if *r == sample_rate && *c == channels) {
+            let mut encoder = match opus_rs::OpusEncoder::new(
+                sample_rate as i32,
+                channels as usize,
+                opus_rs::Application::Voip,
+            ) {
+                Ok(encoder) => encoder,
+                Err(e) => {
+                    tracing::warn!("Opus encoder unavailable: {}", e);
+                    return None;
+                }
+            };
+            self.settings.apply(&mut encoder);
+            self.encoder = Some((sample_rate, channels, encoder));
+        }
+        let (_, _, encoder) = self.encoder.as_mut()?;
+
+        let pcm: Vec<i16> = frame
+            .data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        let frame_size = frame.samples_per_channel();
+        let len = match encoder.encode_i16(&pcm, frame_size, &mut self.output) {
+            Ok(len) => len,
+            Err(e) => {
+                tracing::warn!("Opus frame of {} samples not encodable: {}", frame_size, e);
+                return None;
+            }
+        };
+
+        let duration_ms = (frame_size as u64 * 1000 / sample_rate.max(1) as u64) as u32;
+        let silent = pcm.iter().all(|s| s.unsigned_abs() <= Self::SILENCE_PEAK);
+        if self.settings.dtx && silent {
+            self.silent_ms = self.silent_ms.saturating_add(duration_ms);
+            self.since_sent_ms = self.since_sent_ms.saturating_add(duration_ms);
+            if self.silent_ms > Self::DTX_HANGOVER_MS && self.since_sent_ms < Self::DTX_UPDATE_MS {
+                self.suppressed = true;
+                return None;
+            }
+        } else {
+            self.silent_ms = 0;
+            frame.marker |= std::mem::take(&mut self.suppressed);
+        }
+        self.since_sent_ms = 0;
+
+        frame.data = Bytes::copy_from_slice(&self.output[..len]);
+        frame.format = AudioSampleFormat::Encoded;
+        frame.channels = 0;
+        Some(frame)
+    }
+}
+
+#[cfg(feature = "opus")]
+#[async_trait]
+impl MediaSource for OpusPacketizer {
+    fn id(&self) -> &str {
+        self.source.id()
+    }
+
+    fn kind(&self) -> MediaKind {
+        self.source.kind()
+    }
+
+    async fn next_sample(&mut self) -> MediaResult<MediaSample> {
+        loop {
+            let sample = self.source.next_sample().await?;
+            self.refresh_settings();
+            match sample {
+                MediaSample::Audio(frame) if frame.format == AudioSampleFormat::S16 => {
+                    if let Some(frame) = self.encode(frame) {
+                        return Ok(MediaSample::Audio(frame));
+                    }
+                }
+                other => return Ok(other),
+            }
+        }
+    }
+}
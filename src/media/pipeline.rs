@@ -3,13 +3,24 @@ use crate::{
     media::frame::{MediaKind, MediaSample},
     media::spsc::SpscRing,
     media::track::{MediaStreamTrack, SampleStreamSource, SampleStreamTrack, sample_track},
+    transports::ice::stun::random_u32,
 };
 use async_trait::async_trait;
-use std::sync::{
-    Arc,
-    atomic::{AtomicU64, Ordering},
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::Notify,
+    task::{JoinError, JoinHandle},
+    time::Instant,
 };
-use tokio::{sync::Notify, task::JoinHandle};
 
 #[async_trait]
 pub trait MediaSource: Send + Sync {
@@ -52,6 +63,65 @@ impl MediaSource for TrackMediaSource {
     }
 }
 
+/// Longest timestamp gap [`PacedSource`] waits out; a larger forward jump is
+/// treated as a source restart.
+const MAX_PACING_GAP: Duration = Duration::from_secs(10);
+
+/// Releases samples from a source that runs faster than real time (a file
+/// reader, a generator) at the cadence of their RTP timestamps.
+///
+/// The first sample is emitted at once and anchors the schedule; a timestamp
+/// that goes backwards or jumps forward by more than 10s re-anchors it. If
+/// the inner source falls behind, late samples are emitted immediately and
+/// the schedule is kept, so the stream catches up instead of drifting. Audio
+/// frames with a zero `clock_rate` are passed through unpaced.
+pub struct PacedSource {
+    inner: Box<DynMediaSource>,
+    /// Release time and RTP timestamp of the previous paced sample.
+    last: Option<(Instant, u32)>,
+}
+
+impl PacedSource {
+    pub fn new(inner: Box<DynMediaSource>) -> Self {
+        Self { inner, last: None }
+    }
+}
+
+#[async_trait]
+impl MediaSource for PacedSource {
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn kind(&self) -> MediaKind {
+        self.inner.kind()
+    }
+
+    async fn next_sample(&mut self) -> MediaResult<MediaSample> {
+        let sample = self.inner.next_sample().await?;
+        let clock_rate = match &sample {
+            MediaSample::Audio(f) => f.clock_rate,
+            MediaSample::Video(_) => 90_000,
+        };
+        if clock_rate == 0 {
+            return Ok(sample);
+        }
+
+        let timestamp = sample.rtp_timestamp();
+        let due = self
+            .last
+            .and_then(|(last_due, last_timestamp)| {
+                let delta = timestamp.wrapping_sub(last_timestamp);
+                let gap = Duration::from_nanos(delta as u64 * 1_000_000_000 / clock_rate as u64);
+                (delta < 0x8000_0000 && gap <= MAX_PACING_GAP).then_some(last_due + gap)
+            })
+            .unwrap_or_else(Instant::now);
+        tokio::time::sleep_until(due).await;
+        self.last = Some((due, timestamp));
+        Ok(sample)
+    }
+}
+
 pub struct ChannelMediaSink {
     kind: MediaKind,
     sender: SampleQueueSender,
@@ -100,6 +170,7 @@ fn sample_queue_channel(capacity: usize) -> (SampleQueueSender, SampleQueueRecei
 }
 
 impl SampleQueueSender {
+    #[allow(clippy::result_unit_err)]
     pub fn send(&self, sample: MediaSample) -> Result<(), ()> {
         if self.closed.load(std::sync::atomic::Ordering::Acquire) {
             return Err(());
@@ -283,10 +354,47 @@ impl MediaSink for TrackMediaSink {
 pub type DynMediaSource = dyn MediaSource + Send + Sync + 'static;
 pub type DynMediaSink = dyn MediaSink + Send + Sync + 'static;
 
+/// Handle to a task started by [`spawn_media_pump`].
+///
+/// Awaiting the handle yields the pump's result. [`PumpHandle::stop`] ends the
+/// pump at its next await point and releases the source and sink; the pump
+/// also ends on its own when the source reaches end of stream or the sink
+/// closes.
+#[derive(Debug)]
+pub struct PumpHandle {
+    stop: Arc<Notify>,
+    task: JoinHandle<MediaResult<()>>,
+}
+
+impl PumpHandle {
+    /// Ask the pump to stop. The pending result resolves to `Ok(())`.
+    pub fn stop(&self) {
+        self.stop.notify_one();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Cancel the task immediately; awaiting the handle yields a cancelled
+    /// `JoinError`.
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+}
+
+impl Future for PumpHandle {
+    type Output = Result<MediaResult<()>, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.task).poll(cx)
+    }
+}
+
 pub fn spawn_media_pump(
     mut source: Box<DynMediaSource>,
     sink: Arc<DynMediaSink>,
-) -> MediaResult<JoinHandle<MediaResult<()>>> {
+) -> MediaResult<PumpHandle> {
     if source.kind() != sink.kind() {
         return Err(MediaError::KindMismatch {
             expected: source.kind(),
@@ -294,23 +402,34 @@ pub fn spawn_media_pump(
         });
     }
 
-    Ok(tokio::spawn(async move {
+    let stop = Arc::new(Notify::new());
+    let stopped = stop.clone();
+    let task = tokio::spawn(async move {
         loop {
-            let sample = match source.next_sample().await {
-                Ok(sample) => sample,
-                Err(MediaError::EndOfStream) => return Ok(()),
-                Err(err) => return Err(err),
+            let sample = tokio::select! {
+                biased;
+                _ = stopped.notified() => return Ok(()),
+                sample = source.next_sample() => match sample {
+                    Ok(sample) => sample,
+                    Err(MediaError::EndOfStream) => return Ok(()),
+                    Err(err) => return Err(err),
+                },
             };
 
-            sink.consume(sample).await?;
+            tokio::select! {
+                biased;
+                _ = stopped.notified() => return Ok(()),
+                result = sink.consume(sample) => result?,
+            }
         }
-    }))
+    });
+    Ok(PumpHandle { stop, task })
 }
 
 pub fn track_from_source(
     source: Box<DynMediaSource>,
     capacity: usize,
-) -> MediaResult<(Arc<SampleStreamTrack>, JoinHandle<MediaResult<()>>)> {
+) -> MediaResult<(Arc<SampleStreamTrack>, PumpHandle)> {
     let kind = source.kind();
     let (sample_source, track, _feedback_rx) = sample_track(kind, capacity);
     let sink: Arc<DynMediaSink> = Arc::new(TrackMediaSink::new(Arc::new(sample_source)));
@@ -318,6 +437,120 @@ pub fn track_from_source(
     Ok((track, pump))
 }
 
+/// Forward jumps beyond this many seconds are treated as a source restart.
+const MAX_TIMESTAMP_JUMP_SECS: u32 = 10;
+/// Backward steps up to one second are reordering, not a restart.
+const MAX_TIMESTAMP_REORDER_SECS: u32 = 1;
+/// Frame rate assumed for the gap inserted after a restart before the frame
+/// interval is known.
+const DEFAULT_FRAME_RATE: u32 = 30;
+/// Clock rate until [`TimestampNormalizer::set_clock_rate`] is called.
+const DEFAULT_CLOCK_RATE: u32 = 90_000;
+
+/// Rebases sample timestamps onto one monotonic RTP clock that starts at a
+/// random offset.
+///
+/// When the input jumps far forward or backward (a source restart, or a
+/// different source with its own timestamp base), the output continues one
+/// frame interval after the last timestamp instead of following the jump.
+pub struct TimestampNormalizer {
+    offset: u32,
+    clock_rate: u32,
+    last_input: Option<u32>,
+    last_output: Option<u32>,
+    last_step: u32,
+}
+
+impl Default for TimestampNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimestampNormalizer {
+    pub fn new() -> Self {
+        Self::with_offset(random_u32())
+    }
+
+    pub fn with_offset(offset: u32) -> Self {
+        Self {
+            offset,
+            clock_rate: DEFAULT_CLOCK_RATE,
+            last_input: None,
+            last_output: None,
+            last_step: DEFAULT_CLOCK_RATE / DEFAULT_FRAME_RATE,
+        }
+    }
+
+    /// Scale the restart and reordering thresholds to the track's clock
+    /// rate. A change also forgets the frame interval learned at the old
+    /// rate.
+    pub fn set_clock_rate(&mut self, clock_rate: u32) {
+        let clock_rate = clock_rate.max(1);
+        if clock_rate != self.clock_rate {
+            self.clock_rate = clock_rate;
+            self.last_step = (clock_rate / DEFAULT_FRAME_RATE).max(1);
+        }
+    }
+
+    /// Treat the next timestamp as coming from a new source, regardless of
+    /// how close it is to the previous one.
+    pub fn restart(&mut self) {
+        self.last_input = None;
+    }
+
+    pub fn normalize(&mut self, timestamp: u32) -> u32 {
+        match self.last_input {
+            Some(last) => {
+                let delta = timestamp.wrapping_sub(last);
+                if delta < 0x8000_0000 {
+                    if delta > self.clock_rate.saturating_mul(MAX_TIMESTAMP_JUMP_SECS) {
+                        self.rebase(timestamp);
+                    } else if delta > 0 {
+                        self.last_step = delta;
+                    }
+                    self.last_input = Some(timestamp);
+                } else if delta.wrapping_neg()
+                    > self.clock_rate.saturating_mul(MAX_TIMESTAMP_REORDER_SECS)
+                {
+                    self.rebase(timestamp);
+                    self.last_input = Some(timestamp);
+                } else {
+                    // Reordered sample: keep the offset, don't move the clock back.
+                    return timestamp.wrapping_add(self.offset);
+                }
+            }
+            None => {
+                if self.last_output.is_some() {
+                    self.rebase(timestamp);
+                }
+                self.last_input = Some(timestamp);
+            }
+        }
+
+        let output = timestamp.wrapping_add(self.offset);
+        self.last_output = Some(output);
+        output
+    }
+
+    /// Rewrite the sample's `rtp_timestamp` in place.
+    pub fn normalize_sample(&mut self, sample: &mut MediaSample) {
+        let timestamp = match sample {
+            MediaSample::Audio(f) => &mut f.rtp_timestamp,
+            MediaSample::Video(f) => &mut f.rtp_timestamp,
+        };
+        *timestamp = self.normalize(*timestamp);
+    }
+
+    fn rebase(&mut self, timestamp: u32) {
+        if let Some(last_output) = self.last_output {
+            self.offset = last_output
+                .wrapping_add(self.last_step)
+                .wrapping_sub(timestamp);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
@@ -416,6 +649,35 @@ mod tests {
         assert!(matches!(err, MediaError::Closed));
     }
 
+    #[tokio::test]
+    async fn media_pump_stops_on_request() {
+        let (sender, channel_source) = ChannelMediaSource::channel(MediaKind::Audio, 4);
+        let source: Box<DynMediaSource> = Box::new(channel_source);
+        let (sink_impl, mut receiver) = ChannelMediaSink::channel(MediaKind::Audio, 4);
+        let sink: Arc<DynMediaSink> = Arc::new(sink_impl);
+        let pump = spawn_media_pump(source, sink).unwrap();
+
+        sender
+            .send(MediaSample::Audio(AudioFrame::default()))
+            .unwrap();
+        assert!(receiver.recv().await.is_some());
+
+        pump.stop();
+        tokio::time::timeout(std::time::Duration::from_secs(1), pump)
+            .await
+            .expect("pump should join after stop")
+            .unwrap()
+            .unwrap();
+
+        // The source was released with the task, so nothing pulls from it.
+        assert!(
+            sender
+                .send(MediaSample::Audio(AudioFrame::default()))
+                .is_err()
+        );
+        assert!(receiver.recv().await.is_none());
+    }
+
     #[tokio::test]
     async fn channel_source_with_pump_to_track_sink() {
         let (sender, channel_source) = ChannelMediaSource::channel(MediaKind::Audio, 1);
@@ -455,4 +717,87 @@ mod tests {
         drop(producer);
         pump.await.unwrap().unwrap();
     }
+
+    #[tokio::test]
+    async fn paced_source_releases_samples_at_timestamp_cadence() {
+        let (sender, channel_source) = ChannelMediaSource::channel(MediaKind::Audio, 8);
+        // 0, 20 and 40ms at 8kHz, all available immediately.
+        for timestamp in [0, 160, 320] {
+            sender
+                .send(MediaSample::Audio(AudioFrame {
+                    rtp_timestamp: timestamp,
+                    clock_rate: 8000,
+                    ..AudioFrame::default()
+                }))
+                .unwrap();
+        }
+        let mut paced = PacedSource::new(Box::new(channel_source));
+
+        let start = Instant::now();
+        let mut emitted = Vec::new();
+        for _ in 0..3 {
+            paced.next_sample().await.unwrap();
+            emitted.push(start.elapsed());
+        }
+
+        // Never early; the upper bounds only allow for a loaded scheduler.
+        let ms = Duration::from_millis;
+        assert!(emitted[0] < ms(20), "{emitted:?}");
+        assert!((ms(20)..ms(100)).contains(&emitted[1]), "{emitted:?}");
+        assert!((ms(40)..ms(120)).contains(&emitted[2]), "{emitted:?}");
+    }
+
+    #[tokio::test]
+    async fn timestamp_normalizer_keeps_concatenated_sources_monotonic() {
+        let mut normalizer = TimestampNormalizer::with_offset(u32::MAX - 2000);
+        let mut outputs = Vec::new();
+
+        // Second source restarts from a much lower base than the first.
+        for base in [3_000_000u32, 20] {
+            let (sender, mut source) = ChannelMediaSource::channel(MediaKind::Audio, 8);
+            for i in 0..5 {
+                sender
+                    .send(MediaSample::Audio(AudioFrame {
+                        rtp_timestamp: base + i * 960,
+                        ..AudioFrame::default()
+                    }))
+                    .unwrap();
+            }
+            drop(sender);
+            while let Ok(mut sample) = source.next_sample().await {
+                normalizer.normalize_sample(&mut sample);
+                if let MediaSample::Audio(f) = sample {
+                    outputs.push(f.rtp_timestamp);
+                }
+            }
+        }
+
+        assert_eq!(outputs.len(), 10);
+        for pair in outputs.windows(2) {
+            assert_eq!(pair[1].wrapping_sub(pair[0]), 960, "{:?}", outputs);
+        }
+
+        // A slightly late sample maps back without moving the clock.
+        let last = *outputs.last().unwrap();
+        assert_eq!(normalizer.normalize(20 + 3 * 960), last.wrapping_sub(960));
+        assert_eq!(normalizer.normalize(20 + 5 * 960), last.wrapping_add(960));
+    }
+
+    #[test]
+    fn timestamp_normalizer_thresholds_follow_clock_rate() {
+        // 2 s at 8 kHz is reordering at 90 kHz but a restart at 8 kHz.
+        let mut normalizer = TimestampNormalizer::with_offset(0);
+        normalizer.set_clock_rate(8000);
+        assert_eq!(normalizer.normalize(100_000), 100_000);
+        assert_eq!(normalizer.normalize(100_160), 100_160);
+        assert_eq!(normalizer.normalize(84_000), 100_320);
+
+        // 20 s at 8 kHz is below the 90 kHz jump threshold but a restart here;
+        // before any step is learned the gap is a 1/30 s frame at 8 kHz.
+        let mut normalizer = TimestampNormalizer::with_offset(0);
+        normalizer.set_clock_rate(8000);
+        assert_eq!(normalizer.normalize(1000), 1000);
+        assert_eq!(normalizer.normalize(161_000), 1000 + 8000 / 30);
+        assert_eq!(normalizer.normalize(161_160), 1000 + 8000 / 30 + 160);
+    }
 }
@@ -1,8 +1,9 @@
-use crate::media::depacketizer::{Depacketizer, DepacketizerFactory};
+use crate::media::depacketizer::{Depacketizer, DepacketizerFactory, PassThroughDepacketizer};
+use crate::media::pipeline::TimestampNormalizer;
 use crate::media::track::{MediaStreamTrack, SampleStreamSource, SampleStreamTrack, sample_track};
 use crate::rtp::{
-    FirRequest, FullIntraRequest, GenericNack, PictureLossIndication, RtcpPacket, RtpPacket,
-    SenderReport,
+    FirRequest, FullIntraRequest, GenericNack, PictureLossIndication, RTCP_SDES_CNAME, RtcpPacket,
+    RtpPacket, SdesChunk, SdesItem, SenderReport, SourceDescription,
 };
 use crate::stats::{StatsReport, gather_once};
 use crate::stats_collector::StatsCollector;
@@ -13,17 +14,20 @@ use crate::t38::t30::{T30FaxConfig, T30Session};
 use crate::transports::dtls::{self, DtlsTransport};
 use crate::transports::get_local_ip;
 use crate::transports::ice::stun::random_u32;
-use crate::transports::ice::{IceCandidate, IceGathererState, IceTransport, conn::IceConn};
-use crate::transports::rtp::{RtpRewriteBridgeParams, RtpTransport};
+use crate::transports::ice::{
+    IceCandidate, IceGathererState, IceTransport, LossInjection, conn::IceConn,
+};
+use crate::transports::rtp::{RtpForwardSender, RtpRewriteBridgeParams, RtpTransport};
 use crate::transports::sctp::SctpTransport;
 use crate::transports::udptl::UdtlTransport;
 use crate::{
-    Attribute, AudioCapability, Direction, MediaKind, MediaSection, Origin, RtcConfiguration,
-    RtcError, RtcResult, SdpType, SessionDescription, TransportMode, VideoCapability,
+    Attribute, AudioCapability, Direction, DtlsRole, MediaKind, MediaProfile, MediaSection, Origin,
+    RtcConfiguration, RtcError, RtcResult, SdpType, SessionDescription, TransportMode,
+    UnknownPayloadTypePolicy, VideoCapability,
 };
 use base64::prelude::*;
 use parking_lot::{Mutex, RwLock};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::net::IpAddr;
 use std::{
     sync::{
@@ -36,6 +40,7 @@ use tokio::sync::{Notify, broadcast, mpsc, watch};
 use tracing::{debug, info, trace, warn};
 
 use async_trait::async_trait;
+use futures::FutureExt;
 use futures::stream::{FuturesUnordered, StreamExt};
 use std::future::Future;
 use std::pin::Pin;
@@ -84,14 +89,24 @@ fn strip_rtx_from_section(section: &mut MediaSection) {
             rtx_pts.push(pt);
         }
     }
-    if rtx_pts.is_empty() {
+    remove_payload_types(section, &rtx_pts);
+}
+
+/// Remove the FlexFEC payload type that local config may have injected.
+fn strip_flexfec_from_section(section: &mut MediaSection) {
+    if let Some(fec_pt) = crate::flexfec::flexfec_payload_type(&section.attributes) {
+        remove_payload_types(section, &[fec_pt]);
+    }
+}
+
+/// Drop `pts` from the format list along with their `rtpmap`/`fmtp`/`rtcp-fb` lines.
+fn remove_payload_types(section: &mut MediaSection, pts: &[u8]) {
+    if pts.is_empty() {
         return;
     }
-    section.formats.retain(|f| {
-        f.parse::<u8>()
-            .map(|pt| !rtx_pts.contains(&pt))
-            .unwrap_or(true)
-    });
+    section
+        .formats
+        .retain(|f| f.parse::<u8>().map(|pt| !pts.contains(&pt)).unwrap_or(true));
     section.attributes.retain(|attr| {
         if !matches!(attr.key.as_str(), "rtpmap" | "fmtp" | "rtcp-fb") {
             return true;
@@ -105,7 +120,37 @@ fn strip_rtx_from_section(section: &mut MediaSection) {
         let Ok(pt) = pt_str.parse::<u8>() else {
             return true;
         };
-        !rtx_pts.contains(&pt)
+        !pts.contains(&pt)
+    });
+}
+
+/// Keep only the first media payload type of `section`, dropping every other
+/// format (including RTX and telephone-event) with its `rtpmap`/`fmtp`/
+/// `rtcp-fb` lines. Used by the `SipGateway` profile.
+fn retain_first_media_codec(section: &mut MediaSection) {
+    let Some(keep) = section
+        .formats
+        .iter()
+        .filter_map(|f| f.parse::<u8>().ok())
+        .find(|pt| PeerConnection::media_codec_name(section, *pt).is_some())
+    else {
+        return;
+    };
+    section
+        .formats
+        .retain(|f| f.parse::<u8>().is_ok_and(|pt| pt == keep));
+    section.attributes.retain(|attr| {
+        if !matches!(attr.key.as_str(), "rtpmap" | "fmtp" | "rtcp-fb") {
+            return true;
+        }
+        let Some(pt_str) = attr
+            .value
+            .as_deref()
+            .and_then(|v| v.split_whitespace().next())
+        else {
+            return true;
+        };
+        pt_str == "*" || pt_str.parse::<u8>().map_or(true, |pt| pt == keep)
     });
 }
 
@@ -148,6 +193,8 @@ pub trait RtpSenderInterceptor: Send + Sync {
     ) {
     }
     async fn on_rtcp_received(&self, _packet: &RtcpPacket, _transport: Arc<RtpTransport>) {}
+    /// Fires after the sender's periodic report compound (SR + SDES) is sent.
+    async fn on_rtcp_sent(&self, _packets: &[RtcpPacket]) {}
     fn as_nack_stats(self: Arc<Self>) -> Option<Arc<dyn NackStats>> {
         None
     }
@@ -170,6 +217,9 @@ pub trait RtpReceiverInterceptor: Send + Sync {
         None
     }
     async fn on_rtcp_received(&self, _packet: &RtcpPacket, _transport: Arc<RtpTransport>) {}
+    /// Fires with the receiver's negotiated payload types for `ssrc` when the
+    /// first packet of that SSRC arrives and again after every renegotiation.
+    fn on_payload_map(&self, _ssrc: u32, _payload_map: &HashMap<u8, RtpCodecParameters>) {}
     fn as_nack_stats(self: Arc<Self>) -> Option<Arc<dyn NackStats>> {
         None
     }
@@ -177,6 +227,8 @@ pub trait RtpReceiverInterceptor: Send + Sync {
 
 const RTP_RECEIVER_SAMPLE_CAPACITY: usize = 64;
 const RTP_RECEIVER_PACKET_CAPACITY: usize = 64;
+/// Packets a receive jitter buffer holds before releasing early.
+const RTP_RECEIVER_JITTER_CAPACITY: usize = 512;
 
 pub trait NackStats: Send + Sync {
     fn get_nack_count(&self) -> u64;
@@ -190,9 +242,20 @@ pub trait NackStats: Send + Sync {
     }
 }
 
+/// Default wait before the same sequence number is NACKed or retransmitted
+/// again, standing in for one round trip.
+pub const DEFAULT_NACK_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Default number of repeat NACKs for a sequence number after the first.
+pub const DEFAULT_NACK_MAX_RETRIES: u32 = 3;
+
 pub struct DefaultRtpSenderNackHandler {
     buffer: Mutex<VecDeque<RtpPacket>>,
     max_size: usize,
+    /// Last retransmission time per sequence number, so NACKs repeated
+    /// within `retransmit_interval` are answered only once.
+    retransmitted: Mutex<HashMap<u16, std::time::Instant>>,
+    retransmit_interval: std::time::Duration,
     pub nack_recv_count: AtomicU64,
     rtx_config: Mutex<Option<crate::rtx::RtxSenderConfig>>,
     /// Lock-free mirror of `rtx_config.rtx_ssrc` for the per-packet hot path.
@@ -229,9 +292,20 @@ impl RtpSenderInterceptor for DefaultRtpSenderBitrateHandler {
 
 impl DefaultRtpSenderNackHandler {
     pub fn new(max_size: usize) -> Self {
+        Self::with_retransmit_interval(max_size, DEFAULT_NACK_RETRY_INTERVAL)
+    }
+
+    /// Like [`new`](Self::new), ignoring NACKs for a packet that was already
+    /// retransmitted less than `retransmit_interval` ago.
+    pub fn with_retransmit_interval(
+        max_size: usize,
+        retransmit_interval: std::time::Duration,
+    ) -> Self {
         Self {
             buffer: Mutex::new(VecDeque::with_capacity(max_size)),
             max_size,
+            retransmitted: Mutex::new(HashMap::new()),
+            retransmit_interval,
             nack_recv_count: AtomicU64::new(0),
             rtx_config: Mutex::new(None),
             rtx_ssrc_fast: AtomicU32::new(0),
@@ -272,8 +346,12 @@ impl RtpSenderInterceptor for DefaultRtpSenderNackHandler {
         }
         let mut buffer = self.buffer.lock();
         buffer.push_back(packet.clone());
-        if buffer.len() > self.max_size {
-            buffer.pop_front();
+        if buffer.len() > self.max_size
+            && let Some(evicted) = buffer.pop_front()
+        {
+            self.retransmitted
+                .lock()
+                .remove(&evicted.header.sequence_number);
         }
     }
 
@@ -288,9 +366,19 @@ impl RtpSenderInterceptor for DefaultRtpSenderNackHandler {
 
             let to_resend = {
                 let buffer = self.buffer.lock();
+                let mut retransmitted = self.retransmitted.lock();
+                let now = std::time::Instant::now();
                 let mut packets = Vec::new();
                 for seq in &nack.lost_packets {
+                    if retransmitted
+                        .get(seq)
+                        .is_some_and(|at| now.duration_since(*at) < self.retransmit_interval)
+                    {
+                        trace!("NACK: ignoring duplicate NACK for seq={}", seq);
+                        continue;
+                    }
                     if let Some(packet) = buffer.iter().find(|p| p.header.sequence_number == *seq) {
+                        retransmitted.insert(*seq, now);
                         packets.push(packet.clone());
                     }
                 }
@@ -337,10 +425,27 @@ impl NackStats for DefaultRtpSenderNackHandler {
     }
 }
 
+/// A sequence number the receiver has NACKed and is still waiting for.
+struct MissingPacket {
+    retries: u32,
+    last_nack: std::time::Instant,
+}
+
+#[derive(Default)]
+struct ReceiverNackState {
+    initialized: bool,
+    last_ssrc: u32,
+    last_seq: u16,
+    missing: HashMap<u16, MissingPacket>,
+}
+
 pub struct DefaultRtpReceiverNackHandler {
-    last_seq: AtomicU16,
-    last_ssrc: AtomicU32,
-    initialized: std::sync::atomic::AtomicBool,
+    state: Mutex<ReceiverNackState>,
+    max_retries: u32,
+    retry_interval: std::time::Duration,
+    /// How far behind the newest sequence number a packet may be and still
+    /// be NACKed; older ones are past the sender's history.
+    max_depth: u16,
     pub nack_sent_count: AtomicU64,
     pub nack_recovered_count: AtomicU64,
 }
@@ -353,14 +458,47 @@ impl Default for DefaultRtpReceiverNackHandler {
 
 impl DefaultRtpReceiverNackHandler {
     pub fn new() -> Self {
+        // Depth matches the default `RtcConfiguration::nack_buffer_size`.
+        Self::with_limits(DEFAULT_NACK_MAX_RETRIES, DEFAULT_NACK_RETRY_INTERVAL, 200)
+    }
+
+    /// NACK each missing packet up to `1 + max_retries` times, at least
+    /// `retry_interval` apart, and only while it is within `max_depth`
+    /// packets of the newest one received.
+    pub fn with_limits(
+        max_retries: u32,
+        retry_interval: std::time::Duration,
+        max_depth: usize,
+    ) -> Self {
         Self {
-            last_seq: AtomicU16::new(0),
-            last_ssrc: AtomicU32::new(0),
-            initialized: std::sync::atomic::AtomicBool::new(false),
+            state: Mutex::new(ReceiverNackState::default()),
+            max_retries,
+            retry_interval,
+            max_depth: max_depth.clamp(1, 32767) as u16,
             nack_sent_count: AtomicU64::new(0),
             nack_recovered_count: AtomicU64::new(0),
         }
     }
+
+    /// Sequence numbers due for another NACK at `now`; packets that used up
+    /// their retries are given up on.
+    fn due_retries(&self, state: &mut ReceiverNackState, now: std::time::Instant) -> Vec<u16> {
+        let mut due = Vec::new();
+        state.missing.retain(|seq, missing| {
+            if now.duration_since(missing.last_nack) < self.retry_interval {
+                return true;
+            }
+            if missing.retries >= self.max_retries {
+                trace!("NACK: giving up on seq={}", seq);
+                return false;
+            }
+            missing.retries += 1;
+            missing.last_nack = now;
+            due.push(*seq);
+            true
+        });
+        due
+    }
 }
 
 #[async_trait]
@@ -373,58 +511,87 @@ impl RtpReceiverInterceptor for DefaultRtpReceiverNackHandler {
     ) -> Option<RtcpPacket> {
         let seq = packet.header.sequence_number;
         let ssrc = packet.header.ssrc;
+        let mut state = self.state.lock();
 
         // Check if SSRC changed - indicates stream switch
-        let last_ssrc = self.last_ssrc.load(Ordering::SeqCst);
-        if last_ssrc != 0 && last_ssrc != ssrc {
+        if state.initialized && state.last_ssrc != ssrc {
             debug!(
                 "NACK: SSRC changed from {} to {}, resetting state",
-                last_ssrc, ssrc
+                state.last_ssrc, ssrc
             );
-            self.last_ssrc.store(ssrc, Ordering::SeqCst);
-            self.last_seq.store(seq, Ordering::SeqCst);
+            state.last_ssrc = ssrc;
+            state.last_seq = seq;
+            state.missing.clear();
             return None; // Don't send NACK on stream switch
         }
 
-        if !self.initialized.swap(true, Ordering::SeqCst) {
-            self.last_ssrc.store(ssrc, Ordering::SeqCst);
-            self.last_seq.store(seq, Ordering::SeqCst);
+        if !state.initialized {
+            state.initialized = true;
+            state.last_ssrc = ssrc;
+            state.last_seq = seq;
             return None;
         }
 
-        let last = self.last_seq.load(Ordering::SeqCst);
+        let now = std::time::Instant::now();
+        let last = state.last_seq;
         let diff = seq.wrapping_sub(last);
+        let mut lost = Vec::new();
 
-        if diff > 1 && diff < 32768 {
-            let mut lost = Vec::new();
-            let mut s = last.wrapping_add(1);
+        if diff > 0 && diff < 32768 {
+            // Only packets still within `max_depth` of `seq` are worth asking for.
+            let first = if diff - 1 > self.max_depth {
+                seq.wrapping_sub(self.max_depth)
+            } else {
+                last.wrapping_add(1)
+            };
+            let mut s = first;
             while s != seq {
                 lost.push(s);
                 s = s.wrapping_add(1);
             }
-            trace!(
-                "NACK: detected gap from {} to {}, lost {} packets",
-                last,
-                seq,
-                lost.len()
-            );
-            self.nack_sent_count
-                .fetch_add(lost.len() as u64, Ordering::Relaxed);
-            self.last_seq.store(seq, Ordering::SeqCst);
-            return Some(RtcpPacket::GenericNack(GenericNack {
-                sender_ssrc: 0, // Will be filled by receiver
-                media_ssrc: packet.header.ssrc,
-                lost_packets: lost,
-            }));
-        }
-
-        if diff < 32768 {
-            self.last_seq.store(seq, Ordering::SeqCst);
+            if !lost.is_empty() {
+                trace!(
+                    "NACK: detected gap from {} to {}, lost {} packets",
+                    last,
+                    seq,
+                    lost.len()
+                );
+            }
+            state.last_seq = seq;
+            let max_depth = self.max_depth;
+            state
+                .missing
+                .retain(|missing, _| seq.wrapping_sub(*missing) <= max_depth);
         } else if diff > 32768 {
             trace!("NACK: received old packet seq={}, last={}", seq, last);
-            self.nack_recovered_count.fetch_add(1, Ordering::Relaxed);
+            if state.missing.remove(&seq).is_some() {
+                self.nack_recovered_count.fetch_add(1, Ordering::Relaxed);
+            }
         }
-        None
+
+        let mut nacked = self.due_retries(&mut state, now);
+        for s in &lost {
+            state.missing.insert(
+                *s,
+                MissingPacket {
+                    retries: 0,
+                    last_nack: now,
+                },
+            );
+        }
+        nacked.extend(lost);
+        if nacked.is_empty() {
+            return None;
+        }
+        // Oldest first, across the 16-bit wrap.
+        nacked.sort_by_key(|s| std::cmp::Reverse(seq.wrapping_sub(*s)));
+        self.nack_sent_count
+            .fetch_add(nacked.len() as u64, Ordering::Relaxed);
+        Some(RtcpPacket::GenericNack(GenericNack {
+            sender_ssrc: 0, // Will be filled by receiver
+            media_ssrc: ssrc,
+            lost_packets: nacked,
+        }))
     }
 
     fn as_nack_stats(self: Arc<Self>) -> Option<Arc<dyn NackStats>> {
@@ -458,15 +625,121 @@ enum LoopEvent {
         Option<(crate::rtp::RtpPacket, std::net::SocketAddr)>,
         Option<String>,
         mpsc::Receiver<(crate::rtp::RtpPacket, std::net::SocketAddr)>,
-        Box<dyn Depacketizer>,
+        ReceiveDepacketizer,
     ),
     Feedback(Option<crate::media::track::FeedbackEvent>, Option<String>),
+    /// The stream's jitter buffer has a packet due for playout.
+    JitterRelease(
+        Option<String>,
+        mpsc::Receiver<(crate::rtp::RtpPacket, std::net::SocketAddr)>,
+        ReceiveDepacketizer,
+    ),
+}
+
+/// Wait for the stream's next packet, or until its jitter buffer has a
+/// packet due for playout.
+fn next_packet_event(
+    rid: Option<String>,
+    mut packet_rx: mpsc::Receiver<(crate::rtp::RtpPacket, std::net::SocketAddr)>,
+    depacketizer: ReceiveDepacketizer,
+) -> Pin<Box<dyn Future<Output = LoopEvent> + Send>> {
+    Box::pin(async move {
+        let release = depacketizer.jitter.as_ref().and_then(|j| j.next_release());
+        let packet = match release {
+            Some(at) => match tokio::time::timeout_at(at.into(), packet_rx.recv()).await {
+                Ok(packet) => packet,
+                Err(_) => return LoopEvent::JitterRelease(rid, packet_rx, depacketizer),
+            },
+            None => packet_rx.recv().await,
+        };
+        LoopEvent::Packet(packet, rid, packet_rx, depacketizer)
+    })
+}
+
+/// Depacketizer of one receive stream together with the payload type and
+/// codec it was created for.
+struct ReceiveDepacketizer {
+    depacketizer: Box<dyn Depacketizer>,
+    codec: Option<String>,
+    payload_type: Option<u8>,
+    codecs_version: u32,
+    /// Reorders packets ahead of the depacketizer when a jitter buffer is
+    /// configured.
+    jitter: Option<crate::rtp::RtpJitterBuffer>,
+    /// The current payload type is telephone-event (RFC 4733); its packets
+    /// bypass the codec depacketizer and reach the track as-is.
+    telephone_event: bool,
+    /// SSRC and payload map version last handed to `on_payload_map`.
+    payload_map_announced: Option<(u32, u32)>,
+}
+
+impl ReceiveDepacketizer {
+    /// Next packet the jitter buffer has due at `now`.
+    fn release(
+        &mut self,
+        now: std::time::Instant,
+    ) -> Option<(crate::rtp::RtpPacket, std::net::SocketAddr)> {
+        self.jitter.as_mut()?.pop(now)
+    }
+
+    /// Switch to the negotiated codec's depacketizer when the payload type
+    /// (or its mapping) changed and now names a different codec.
+    fn select(
+        &mut self,
+        receiver: &RtpReceiver,
+        factory: &Arc<dyn DepacketizerFactory>,
+        payload_type: u8,
+        kind: crate::media::frame::MediaKind,
+    ) {
+        let version = receiver.payload_codecs_version.load(Ordering::Acquire);
+        if self.payload_type == Some(payload_type) && self.codecs_version == version {
+            return;
+        }
+        self.payload_type = Some(payload_type);
+        self.codecs_version = version;
+        let Some(codec) = receiver.payload_codecs.lock().get(&payload_type).cloned() else {
+            self.telephone_event = false;
+            return;
+        };
+        // DTMF interleaves with the audio codec; keep that codec's
+        // depacketizer (and decoder state) for the packets that follow.
+        self.telephone_event = codec.eq_ignore_ascii_case("telephone-event");
+        if self.telephone_event {
+            return;
+        }
+        if self
+            .codec
+            .as_deref()
+            .is_some_and(|current| current.eq_ignore_ascii_case(&codec))
+        {
+            return;
+        }
+        debug!(
+            "RTP receiver: payload type {} uses {}, switching depacketizer",
+            payload_type, codec
+        );
+        let fmtp = receiver.payload_fmtp.lock().get(&payload_type).cloned();
+        self.depacketizer = factory.create_for_format(kind, &codec, fmtp.as_deref());
+        self.codec = Some(codec);
+    }
 }
 
 #[derive(Clone)]
 pub enum PeerConnectionEvent {
     DataChannel(Arc<crate::transports::sctp::DataChannel>),
     Track(Arc<RtpTransceiver>),
+    /// Text received on a T.140 channel, in order. The peer's `t140` channel
+    /// is still reported as [`PeerConnectionEvent::DataChannel`], but its
+    /// messages arrive here instead of through its `recv()`.
+    RealtimeText(String),
+    /// A local candidate was gathered. Trickle it to the peer, which feeds it
+    /// to [`PeerConnection::add_ice_candidate`].
+    IceCandidate(IceCandidate),
+    /// Local gathering finished; the trickle counterpart of `a=end-of-candidates`.
+    EndOfCandidates,
+    /// The SCTP association is established, so negotiated data channels are
+    /// usable. Emitted before any channel reports `DataChannelEvent::Open`.
+    SctpConnected,
 }
 
 #[derive(Clone)]
@@ -498,11 +771,21 @@ struct PeerConnectionInner {
     rtp_media_transports: Mutex<HashMap<u64, Arc<RtpTransport>>>,
     sctp_transport: Mutex<Option<Arc<SctpTransport>>>,
     data_channels: Arc<Mutex<Vec<std::sync::Weak<crate::transports::sctp::DataChannel>>>>,
+    /// T.140 channel used for sending, opened on first use or adopted from
+    /// the peer, with its state as seen by the reader task.
+    realtime_text: Mutex<
+        Option<(
+            Arc<crate::transports::sctp::DataChannel>,
+            watch::Receiver<crate::transports::sctp::DataChannelState>,
+        )>,
+    >,
     event_tx: mpsc::UnboundedSender<PeerConnectionEvent>,
     event_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<PeerConnectionEvent>>,
     dtls_role: watch::Sender<Option<bool>>,
     _dtls_role_rx: watch::Receiver<Option<bool>>,
     stats_collector: Arc<StatsCollector>,
+    /// Uninterpreted RTCP, published when `config.forward_unknown_rtcp` is set.
+    unknown_rtcp_tx: broadcast::Sender<RtcpPacket>,
     ssrc_generator: AtomicU32,
     disconnect_reason: watch::Sender<Option<DisconnectReason>>,
     _disconnect_reason_rx: watch::Receiver<Option<DisconnectReason>>,
@@ -573,12 +856,14 @@ impl PeerConnection {
             watch::channel(IceConnectionState::New);
         let (ice_gathering_state_tx, ice_gathering_state_rx) =
             watch::channel(IceGatheringState::New);
-        let (dtls_role_tx, dtls_role_rx) = watch::channel(None);
+        let (dtls_role_tx, dtls_role_rx) =
+            watch::channel(config.effective_dtls_role().map(|role| role.is_client()));
 
         let ssrc_generator = AtomicU32::new(config.ssrc_start);
 
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         let (disconnect_reason_tx, disconnect_reason_rx) = watch::channel(None);
+        let (unknown_rtcp_tx, _) = broadcast::channel(100);
 
         let inner = PeerConnectionInner {
             config,
@@ -604,11 +889,13 @@ impl PeerConnection {
             rtp_media_transports: Mutex::new(HashMap::new()),
             sctp_transport: Mutex::new(None),
             data_channels: Arc::new(Mutex::new(Vec::new())),
+            realtime_text: Mutex::new(None),
             event_tx,
             event_rx: tokio::sync::Mutex::new(event_rx),
             dtls_role: dtls_role_tx,
             _dtls_role_rx: dtls_role_rx.clone(),
             stats_collector: Arc::new(StatsCollector::new()),
+            unknown_rtcp_tx,
             ssrc_generator,
             disconnect_reason: disconnect_reason_tx,
             _disconnect_reason_rx: disconnect_reason_rx,
@@ -716,6 +1003,37 @@ impl PeerConnection {
         total
     }
 
+    /// Bytes written to the network by every transport of this connection:
+    /// RTP, RTCP and DTLS (which carries data channels), headers included.
+    /// A cheap health metric that does not require `get_stats`.
+    pub fn total_bytes_sent(&self) -> u64 {
+        self.sum_ice_conn_counters(|conn| conn.tx_bytes.load(Ordering::Relaxed))
+    }
+
+    /// Bytes read from the network by every transport of this connection.
+    pub fn total_bytes_received(&self) -> u64 {
+        self.sum_ice_conn_counters(|conn| conn.rx_bytes.load(Ordering::Relaxed))
+    }
+
+    pub fn total_packets_sent(&self) -> u64 {
+        self.sum_ice_conn_counters(|conn| conn.tx_packets.load(Ordering::Relaxed))
+    }
+
+    pub fn total_packets_received(&self) -> u64 {
+        self.sum_ice_conn_counters(|conn| conn.rx_packets.load(Ordering::Relaxed))
+    }
+
+    fn sum_ice_conn_counters(&self, counter: impl Fn(&IceConn) -> u64) -> u64 {
+        let mut total = 0u64;
+        if let Some(transport) = self.inner.rtp_transport.lock().clone() {
+            total += counter(&transport.ice_conn());
+        }
+        for transport in self.inner.rtp_media_transports.lock().values() {
+            total += counter(&transport.ice_conn());
+        }
+        total
+    }
+
     pub async fn wait_for_rtp_transport_ready(
         &self,
         timeout: std::time::Duration,
@@ -733,6 +1051,57 @@ impl PeerConnection {
         ))
     }
 
+    /// Wait until `transceiver` has sent or received its first RTP packet.
+    /// Unlike [`wait_for_connected`](Self::wait_for_connected), which
+    /// resolves once ICE/DTLS is up, this means media is actually flowing.
+    pub async fn wait_for_media(
+        &self,
+        transceiver: &RtpTransceiver,
+        timeout: std::time::Duration,
+    ) -> RtcResult<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut peer_state = self.inner.peer_state.subscribe();
+        loop {
+            let mut sent = transceiver
+                .sender()
+                .map(|s| s.first_packet_sent.subscribe());
+            let mut received = transceiver
+                .receiver()
+                .map(|r| r.first_packet_received.subscribe());
+            if sent.as_mut().is_some_and(|rx| *rx.borrow_and_update())
+                || received.as_mut().is_some_and(|rx| *rx.borrow_and_update())
+            {
+                return Ok(());
+            }
+            let state = *peer_state.borrow_and_update();
+            if matches!(
+                state,
+                PeerConnectionState::Failed | PeerConnectionState::Closed
+            ) {
+                return Err(RtcError::InvalidState(format!(
+                    "peer connection {:?} before media flowed",
+                    state
+                )));
+            }
+            let changed = |rx: Option<watch::Receiver<bool>>| async move {
+                match rx {
+                    Some(mut rx) => {
+                        let _ = rx.changed().await;
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = changed(sent) => {}
+                _ = changed(received) => {}
+                _ = peer_state.changed() => {}
+                _ = tokio::time::sleep_until(deadline) => {
+                    return Err(RtcError::Timeout("media"));
+                }
+            }
+        }
+    }
+
     pub fn ice_transport(&self) -> IceTransport {
         self.inner.ice_transport.clone()
     }
@@ -757,7 +1126,8 @@ impl PeerConnection {
     ) {
         transceiver.set_rtp_transport(Arc::downgrade(&transport));
         let extmap = transceiver.get_extmap();
-        let _ = transceiver.update_extmap(extmap);
+        let directions = transceiver.extmap_directions();
+        let _ = transceiver.update_extmap_with_directions(extmap, directions);
 
         let sender_arc = transceiver.sender.lock().clone();
         let receiver_arc = transceiver.receiver.lock().clone();
@@ -778,6 +1148,20 @@ impl PeerConnection {
         }
     }
 
+    /// Reserve a fresh sender SSRC (plus default stream/track ids) so the next
+    /// local description can advertise the send stream before a track is added.
+    fn preallocate_sender_ssrc(&self, transceiver: &RtpTransceiver) -> u32 {
+        let rand_val = random_u32();
+        let ssrc = self
+            .inner
+            .ssrc_generator
+            .fetch_add(1 + rand_val, Ordering::Relaxed);
+        *transceiver.sender_ssrc.lock() = Some(ssrc);
+        *transceiver.sender_stream_id.lock() = Some("default".to_string());
+        *transceiver.sender_track_id.lock() = Some(format!("track-{}", transceiver.id()));
+        ssrc
+    }
+
     pub fn add_transceiver(
         &self,
         kind: MediaKind,
@@ -819,18 +1203,15 @@ impl PeerConnection {
         };
 
         if nack_enabled {
-            builder = builder.nack();
+            builder = builder.nack_with_limits(
+                self.inner.config.nack_max_retries,
+                self.inner.config.nack_retry_interval,
+                self.inner.config.nack_buffer_size,
+            );
         }
-        let receiver = builder.build();
+        let receiver = self.inner.configure_receiver(builder).build();
         if direction.sends() {
-            let rand_val = random_u32();
-            let ssrc = self
-                .inner
-                .ssrc_generator
-                .fetch_add(1 + rand_val, Ordering::Relaxed);
-            *transceiver.sender_ssrc.lock() = Some(ssrc);
-            *transceiver.sender_stream_id.lock() = Some("default".to_string());
-            *transceiver.sender_track_id.lock() = Some(format!("track-{}", transceiver.id()));
+            self.preallocate_sender_ssrc(&transceiver);
         }
         transceiver.set_receiver(Some(receiver));
 
@@ -919,9 +1300,16 @@ impl PeerConnection {
             }
         };
 
+        if let Some(kbps) = self.inner.config.pacing_kbps {
+            builder = builder.pacing_kbps(kbps);
+        }
+
         if nack_enabled {
             builder = builder
-                .nack(self.inner.config.nack_buffer_size)
+                .nack_with_interval(
+                    self.inner.config.nack_buffer_size,
+                    self.inner.config.nack_retry_interval,
+                )
                 .bitrate_controller();
         }
 
@@ -962,7 +1350,36 @@ impl PeerConnection {
         self.inner.transceivers.lock().clone()
     }
 
+    /// Restart ICE: local candidates are gathered again and the next offer
+    /// carries them with fresh ICE credentials. Once the peer answers,
+    /// connectivity checks run again to pick a new pair. The DTLS session is
+    /// not renegotiated; it moves to the new pair while media keeps flowing
+    /// on the current one in the meantime.
+    pub fn restart_ice(&self) -> RtcResult<()> {
+        if self.config().transport_mode != TransportMode::WebRtc {
+            return Err(RtcError::InvalidState(
+                "ICE restart requires WebRTC transport mode".into(),
+            ));
+        }
+        self.inner.restart_ice_transport();
+        Ok(())
+    }
+
     pub async fn create_offer(&self) -> RtcResult<SessionDescription> {
+        self.create_offer_until(std::future::pending()).await
+    }
+
+    /// Like [`create_offer`](Self::create_offer), but stops waiting for local
+    /// candidates once `stop_gathering` resolves and builds the offer from the
+    /// candidates gathered so far.
+    ///
+    /// Both variants are cancellation safe: if the returned future is dropped
+    /// mid-gather, socket setup still completes in the background and the
+    /// next `create_offer` reuses it.
+    pub async fn create_offer_until(
+        &self,
+        stop_gathering: impl Future<Output = ()>,
+    ) -> RtcResult<SessionDescription> {
         let state = &self.inner.signaling_state;
         if *state.borrow() != SignalingState::Stable {
             return Err(RtcError::InvalidState(format!(
@@ -983,7 +1400,7 @@ impl PeerConnection {
         }
         let desc = self
             .inner
-            .build_description(SdpType::Offer, |dir| dir)
+            .build_description(SdpType::Offer, |dir| dir, stop_gathering)
             .await?;
         if self.inner.config.transport_mode == TransportMode::Rtp && !Self::sdp_has_bundle(&desc) {
             for (media_index, (transceiver, _)) in self
@@ -1003,17 +1420,37 @@ impl PeerConnection {
     }
 
     pub async fn create_answer(&self) -> RtcResult<SessionDescription> {
-        let state = &self.inner.signaling_state;
-        if *state.borrow() != SignalingState::HaveRemoteOffer {
-            return Err(RtcError::InvalidState(
-                "create_answer requires remote offer".into(),
-            ));
+        self.create_answer_until(std::future::pending()).await
+    }
+
+    /// Answer counterpart of [`create_offer_until`](Self::create_offer_until).
+    pub async fn create_answer_until(
+        &self,
+        stop_gathering: impl Future<Output = ()>,
+    ) -> RtcResult<SessionDescription> {
+        let state = *self.inner.signaling_state.borrow();
+        if state != SignalingState::HaveRemoteOffer {
+            // Without a pending remote offer there is nothing to answer; an
+            // answer built from local state alone would be meaningless.
+            let missing = if self.inner.remote_description.lock().is_none() {
+                "no remote description has been set"
+            } else {
+                "the remote description is not a pending offer"
+            };
+            return Err(RtcError::InvalidState(format!(
+                "cannot create answer in state {:?}: {}; call set_remote_description with an offer first",
+                state, missing
+            )));
         }
         self.inner
             .ice_transport
             .set_role(crate::transports::ice::IceRole::Controlled);
         self.inner
-            .build_description(SdpType::Answer, |dir| dir.answer_direction())
+            .build_description(
+                SdpType::Answer,
+                |dir| dir.answer_direction(),
+                stop_gathering,
+            )
             .await
     }
 
@@ -1053,8 +1490,11 @@ impl PeerConnection {
                         if !payload_map.is_empty() {
                             let _ = t.update_payload_map(payload_map);
                         }
+                        t.update_codec_name(Self::extract_codec_name(section));
+                        t.update_payload_codecs(Self::extract_payload_codecs(section));
                         let extmap = Self::extract_extmap(section);
-                        let _ = t.update_extmap(extmap);
+                        let directions = Self::extract_extmap_directions(section);
+                        let _ = t.update_extmap_with_directions(extmap, directions);
                     }
                 }
             } else {
@@ -1142,8 +1582,49 @@ impl PeerConnection {
                 }
             }
         } else {
+            if self.config().transport_mode == TransportMode::Srtp
+                && matches!(desc.dtls_fingerprint(), Ok(Some(_)))
+                && !desc
+                    .media_sections
+                    .iter()
+                    .any(|m| !m.get_crypto_attributes().is_empty())
+            {
+                return Err(RtcError::InvalidConfiguration(
+                    "remote SDP negotiates DTLS-SRTP but the transport mode is SDES".into(),
+                ));
+            }
             None
         };
+        if remote_dtls_fingerprint.is_some()
+            && let Some(role) = self.config().effective_dtls_role()
+            && let Some(setup) = desc
+                .media_sections
+                .iter()
+                .flat_map(|m| &m.attributes)
+                .find(|attr| attr.key == "setup")
+                .and_then(|attr| attr.value.as_deref())
+        {
+            // A forced role only works against a peer that leaves us that
+            // role: both ends active (or both passive) never handshake.
+            let conflicts = match role {
+                DtlsRole::Client => setup == "active",
+                DtlsRole::Server => setup == "passive",
+            };
+            if conflicts {
+                return Err(RtcError::InvalidState(format!(
+                    "remote a=setup:{} conflicts with the forced DTLS {:?} role",
+                    setup, role
+                )));
+            }
+        }
+
+        // Only `trickle` changes our behavior. Renomination is not supported:
+        // we never advertise it, so the remote must stick to RFC 8445
+        // nomination even if it offers the option.
+        let ice_options = desc.ice_options();
+        if !ice_options.is_empty() {
+            debug!("remote ice-options: {}", ice_options.join(" "));
+        }
 
         // Check if this is a reinvite (not first negotiation)
         let is_reinvite = {
@@ -1270,6 +1751,7 @@ impl PeerConnection {
         let mut ufrag = None;
         let mut pwd = None;
         let mut candidates = Vec::new();
+        let mut end_of_candidates = false;
         let mut remote_addr = None;
 
         // Check session-level attributes for ICE credentials
@@ -1309,6 +1791,8 @@ impl PeerConnection {
                     && let Ok(c) = crate::transports::ice::IceCandidate::from_sdp(val)
                 {
                     candidates.push(c);
+                } else if attr.key == "end-of-candidates" {
+                    end_of_candidates = true;
                 }
             }
         }
@@ -1321,6 +1805,18 @@ impl PeerConnection {
                     ice_lite: false,
                     tie_breaker: 0,
                 };
+                // New remote credentials in an offer mean the peer restarted ICE;
+                // answer with fresh local credentials of our own.
+                let remote_restarted = desc.sdp_type == SdpType::Offer
+                    && self
+                        .inner
+                        .ice_transport
+                        .remote_parameters()
+                        .is_some_and(|current| params.is_restart_of(&current));
+                if remote_restarted && !self.inner.ice_transport.is_restarting() {
+                    debug!("Remote offer restarts ICE");
+                    self.inner.restart_ice_transport();
+                }
                 self.inner
                     .ice_transport
                     .start(params)
@@ -1329,6 +1825,9 @@ impl PeerConnection {
                 for candidate in candidates.iter().cloned() {
                     self.inner.ice_transport.add_remote_candidate(candidate);
                 }
+                if end_of_candidates {
+                    self.inner.ice_transport.add_remote_end_of_candidates();
+                }
             }
         } else if self.config().transport_mode == TransportMode::Rtp {
             // Direct RTP setup is deferred until media sections have been matched
@@ -1345,47 +1844,26 @@ impl PeerConnection {
         // Create transceivers for new media sections in Offer
         if desc.sdp_type == SdpType::Offer {
             let mut transceivers = self.inner.transceivers.lock();
-            for section in &desc.media_sections {
+            let matches = Self::match_transceivers_by_mid(&transceivers, &desc.media_sections);
+            for (section, found_transceiver) in desc.media_sections.iter().zip(matches) {
                 let mid = &section.mid;
-                let mut found_transceiver = None;
                 let mut newly_matched = false;
 
-                for t in transceivers.iter() {
-                    if let Some(t_mid) = t.mid()
-                        && t_mid == *mid
-                    {
-                        found_transceiver = Some(t.clone());
-                        break;
-                    }
-                }
-
-                if found_transceiver.is_none() {
-                    // Try to find a transceiver with no MID and same kind
-                    for t in transceivers.iter() {
-                        if t.mid().is_none() && t.kind() == section.kind {
-                            t.set_mid(mid.clone());
-                            found_transceiver = Some(t.clone());
-                            newly_matched = true;
-                            break;
-                        }
-                    }
-
-                    if found_transceiver.is_none()
-                        && mid.is_empty()
-                        && let Some(t) = transceivers.iter().find(|t| t.kind() == section.kind)
-                    {
-                        found_transceiver = Some(t.clone());
-                    }
+                if let Some(t) = &found_transceiver
+                    && t.mid().is_none()
+                {
+                    t.set_mid(mid.clone());
+                    newly_matched = true;
                 }
 
                 let mut ssrc = None;
-                let mut simulcast = None;
-                let mut rids = Vec::new();
                 let mut rid_ext_id = None;
                 let mut abs_send_time_ext_id = None;
                 let mut fid_group = None;
                 let mut rtx_ssrc = None;
                 let rtx_apt = crate::rtx::extract_rtx_apt_map_from_attrs(&section.attributes);
+                let fec_ssrc =
+                    crate::flexfec::parse_fec_fr_group(&section.attributes).map(|(_, fec)| fec);
 
                 // First pass: check for ssrc-group FID
                 for attr in &section.attributes {
@@ -1417,21 +1895,11 @@ impl PeerConnection {
                                 if parsed == primary {
                                     ssrc = Some(parsed);
                                 }
-                            } else if ssrc.is_none() {
-                                // No FID group, take the first one
+                            } else if ssrc.is_none() && Some(parsed) != fec_ssrc {
+                                // No FID group, take the first non-FEC one
                                 ssrc = Some(parsed);
                             }
                         }
-                    } else if attr.key == "simulcast"
-                        && let Some(val) = &attr.value
-                    {
-                        simulcast = crate::sdp::Simulcast::parse(val);
-                    } else if attr.key == "rid"
-                        && let Some(val) = &attr.value
-                    {
-                        if let Some(rid) = crate::sdp::Rid::parse(val) {
-                            rids.push(rid);
-                        }
                     } else if attr.key == "extmap"
                         && let Some(val) = &attr.value
                     {
@@ -1468,10 +1936,17 @@ impl PeerConnection {
                     if !payload_map.is_empty() {
                         let _ = t.update_payload_map(payload_map);
                     }
+                    t.update_codec_name(Self::extract_codec_name(section));
+                    t.update_payload_codecs(Self::extract_payload_codecs(section));
+                    t.update_remote_content(section);
+                    t.update_remote_fmtp(section);
                     let extmap = Self::extract_extmap(section);
-                    let _ = t.update_extmap(extmap);
+                    let directions = Self::extract_remote_extmap_directions(section);
+                    let _ = t.update_extmap_with_directions(extmap, directions);
+                    t.update_video_limits(Self::extract_video_limits(section));
                     let direction: TransceiverDirection = section.direction.into();
                     t.set_direction(direction);
+                    t.update_flexfec(section, self.inner.flexfec_enabled());
 
                     if let Some(ssrc_val) = ssrc
                         && let Some(rx) = t.receiver.lock().as_ref()
@@ -1483,14 +1958,6 @@ impl PeerConnection {
                         if !rtx_apt.is_empty() {
                             rx.set_rtx_apt_map(rtx_apt.clone());
                         }
-
-                        // Handle Simulcast
-                        if let Some(sim) = &simulcast {
-                            // For Offer, we look at 'send' direction (remote sends to us)
-                            for rid_id in &sim.send {
-                                let _ = rx.add_simulcast_track(rid_id.clone());
-                            }
-                        }
                     }
 
                     if newly_matched && ssrc.is_some() {
@@ -1508,6 +1975,10 @@ impl PeerConnection {
                     };
                     let t = Arc::new(RtpTransceiver::new(kind, direction));
                     t.set_mid(mid.clone());
+                    t.update_codec_name(Self::extract_codec_name(section));
+                    t.update_payload_codecs(Self::extract_payload_codecs(section));
+                    t.update_remote_content(section);
+                    t.update_remote_fmtp(section);
 
                     let receiver_ssrc = ssrc.unwrap_or(0);
 
@@ -1541,11 +2012,15 @@ impl PeerConnection {
 
                     if nack_enabled {
                         debug!("NACK: enabled for new receiver mid={}", mid);
-                        builder = builder.nack();
+                        builder = builder.nack_with_limits(
+                            self.inner.config.nack_max_retries,
+                            self.inner.config.nack_retry_interval,
+                            self.inner.config.nack_buffer_size,
+                        );
                     } else {
                         debug!("NACK: disabled for new receiver mid={}", mid);
                     }
-                    let receiver = builder.build();
+                    let receiver = self.inner.configure_receiver(builder).build();
                     if let Some(rtx) = rtx_ssrc {
                         receiver.set_rtx_ssrc(rtx);
                     }
@@ -1572,14 +2047,8 @@ impl PeerConnection {
                         }
                     }
 
-                    // Handle Simulcast for new transceiver
-                    if let Some(sim) = &simulcast {
-                        for rid_id in &sim.send {
-                            let _ = receiver.add_simulcast_track(rid_id.clone());
-                        }
-                    }
-
                     t.set_receiver(Some(receiver));
+                    t.update_flexfec(section, self.inner.flexfec_enabled());
 
                     transceivers.push(t.clone());
 
@@ -1601,10 +2070,18 @@ impl PeerConnection {
                 if !payload_map.is_empty() {
                     let _ = t.update_payload_map(payload_map);
                 }
+                t.update_codec_name(Self::extract_codec_name(section));
+                t.update_payload_codecs(Self::extract_payload_codecs(section));
+                t.restrict_sender_codec(&Self::extract_media_codecs(section));
+                t.update_remote_content(section);
+                t.update_remote_fmtp(section);
                 let extmap = Self::extract_extmap(section);
-                let _ = t.update_extmap(extmap);
+                let directions = Self::extract_remote_extmap_directions(section);
+                let _ = t.update_extmap_with_directions(extmap, directions);
+                t.update_video_limits(Self::extract_video_limits(section));
                 let direction: TransceiverDirection = section.direction.into();
                 t.set_direction(direction);
+                t.update_flexfec(section, self.inner.flexfec_enabled());
 
                 let mut ssrc = None;
                 for attr in &section.attributes {
@@ -1764,6 +2241,11 @@ impl PeerConnection {
             srtp_required,
             allow_ssrc_change,
         ));
+        if self.config().transport_mode == TransportMode::Rtp {
+            rtp_transport.set_early_rtp_buffering(true);
+            rtp_transport.set_unsignaled_ssrc_policy(self.config().unsignaled_ssrc_policy);
+        }
+        rtp_transport.set_forward_unknown_rtcp(self.config().forward_unknown_rtcp);
         {
             let mut rx = ice_conn.rtp_receiver.write();
             *rx = Some(Arc::downgrade(&rtp_transport)
@@ -1802,9 +2284,12 @@ impl PeerConnection {
                 self.inner.stats_collector.clone(),
             );
             let pair_monitor = Self::create_pair_monitor(pair_rx.clone(), ice_conn_monitor.clone());
-            return Ok(
-                self.spawn_transport_loops(vec![Box::pin(rtcp_loop), Box::pin(pair_monitor)])
-            );
+            let transport_cc_loop = Self::create_transport_cc_loop(rtp_transport.clone());
+            return Ok(self.spawn_transport_loops(vec![
+                Box::pin(rtcp_loop),
+                Box::pin(pair_monitor),
+                Box::pin(transport_cc_loop),
+            ]));
         }
 
         if self.config().transport_mode == TransportMode::Rtp {
@@ -1830,9 +2315,12 @@ impl PeerConnection {
                 self.attach_rtp_transport_to_transceiver(t, selected_transport);
             }
             let pair_monitor = Self::create_pair_monitor(pair_rx.clone(), ice_conn_monitor.clone());
-            return Ok(
-                self.spawn_transport_loops(vec![Box::pin(rtcp_loop), Box::pin(pair_monitor)])
-            );
+            let transport_cc_loop = Self::create_transport_cc_loop(rtp_transport.clone());
+            return Ok(self.spawn_transport_loops(vec![
+                Box::pin(rtcp_loop),
+                Box::pin(pair_monitor),
+                Box::pin(transport_cc_loop),
+            ]));
         }
 
         let remote_dtls_fingerprint = self.inner.remote_dtls_fingerprint.lock().clone();
@@ -1898,6 +2386,10 @@ impl PeerConnection {
                 is_client,
                 self.config(),
             );
+            let event_tx = self.inner.event_tx.clone();
+            sctp.on_connected(Box::new(move || {
+                let _ = event_tx.send(PeerConnectionEvent::SctpConnected);
+            }));
             *self.inner.sctp_transport.lock() = Some(sctp);
             sctp_runner = Box::pin(runner);
         } else {
@@ -1924,6 +2416,13 @@ impl PeerConnection {
         let dc_listener = async move {
             while let Some(dc) = dc_rx.recv().await {
                 if let Some(inner) = inner_weak_dc.upgrade() {
+                    if dc.protocol == crate::t140::T140_PROTOCOL {
+                        let state = inner.spawn_realtime_text_reader(dc.clone());
+                        inner
+                            .realtime_text
+                            .lock()
+                            .get_or_insert_with(|| (dc.clone(), state));
+                    }
                     let _ = inner.event_tx.send(PeerConnectionEvent::DataChannel(dc));
                 } else {
                     break;
@@ -1941,6 +2440,9 @@ impl PeerConnection {
             let state = state_rx.borrow().clone();
             match state {
                 crate::transports::dtls::DtlsState::Connected(_, profile_opt) => {
+                    // The nominated pair is now proven; only ICE may move the
+                    // remote address from here on.
+                    ice_conn_monitor.lock_remote();
                     self.setup_srtp(&dtls_clone, is_client, profile_opt, &rtp_transport_clone);
 
                     let rtcp_loop = Self::create_rtcp_loop(
@@ -1951,16 +2453,20 @@ impl PeerConnection {
 
                     let pair_monitor =
                         Self::create_pair_monitor(pair_rx.clone(), ice_conn_monitor.clone());
+                    let transport_cc_loop =
+                        Self::create_transport_cc_loop(rtp_transport_clone.clone());
 
                     return Ok(self.spawn_transport_loops(vec![
                         Box::pin(rtcp_loop),
                         sctp_runner,
                         dc_listener,
                         Box::pin(pair_monitor),
+                        Box::pin(transport_cc_loop),
                     ]));
                 }
                 crate::transports::dtls::DtlsState::Failed => {
-                    return Err(RtcError::Internal("DTLS handshake failed".into()));
+                    return Err(Self::dtls_failure(&dtls_clone)
+                        .unwrap_or_else(|| RtcError::Internal("DTLS handshake failed".into())));
                 }
                 crate::transports::dtls::DtlsState::Closed => {
                     return Err(RtcError::Internal(
@@ -1992,9 +2498,15 @@ impl PeerConnection {
                     // based on the final DtlsState set by the handshake.
                 }
                 _ = &mut sctp_runner => {
+                     if let Some(err) = Self::dtls_failure(&dtls_clone) {
+                         return Err(err);
+                     }
                      return Err(RtcError::Internal("SCTP runner stopped unexpectedly".into()));
                 }
                 _ = &mut dc_listener => {
+                     if let Some(err) = Self::dtls_failure(&dtls_clone) {
+                         return Err(err);
+                     }
                      debug!("DataChannel listener stopped unexpectedly");
                      return Err(RtcError::Internal("DataChannel listener stopped unexpectedly".into()));
                 }
@@ -2016,6 +2528,17 @@ impl PeerConnection {
         Ok(Box::pin(async {}) as Pin<Box<dyn Future<Output = ()> + Send>>)
     }
 
+    /// Why the handshake failed, when the peer certificate was rejected or a
+    /// fatal alert arrived. A failed handshake also stops the SCTP runner,
+    /// which may be noticed first.
+    fn dtls_failure(dtls: &DtlsTransport) -> Option<RtcError> {
+        if let Some(reason) = dtls.fingerprint_mismatch() {
+            return Some(RtcError::FingerprintMismatch(reason));
+        }
+        dtls.received_alert()
+            .map(|alert| RtcError::DtlsAlert(alert.to_string()))
+    }
+
     fn setup_sdes(&self, rtp_transport: &Arc<RtpTransport>) -> RtcResult<()> {
         let (tx_keying, rx_keying, profile) = {
             let remote_desc = self.inner.remote_description.lock();
@@ -2191,29 +2714,14 @@ impl PeerConnection {
     }
 
     fn sdp_has_bundle(desc: &SessionDescription) -> bool {
-        desc.session.attributes.iter().any(|attr| {
-            attr.key == "group"
-                && attr
-                    .value
-                    .as_deref()
-                    .is_some_and(|value| value.starts_with("BUNDLE"))
-        })
+        !desc.bundle_groups().is_empty()
     }
 
     fn bundle_tag_mid(desc: &SessionDescription) -> Option<String> {
-        desc.session
-            .attributes
-            .iter()
-            .find(|attr| attr.key == "group")
-            .and_then(|attr| attr.value.as_deref())
-            .and_then(|value| {
-                let mut parts = value.split_whitespace();
-                if parts.next() == Some("BUNDLE") {
-                    parts.next().map(ToString::to_string)
-                } else {
-                    None
-                }
-            })
+        desc.bundle_groups()
+            .into_iter()
+            .next()
+            .and_then(|group| group.into_iter().next())
     }
 
     fn remote_rtp_addr_from_section(
@@ -2235,6 +2743,53 @@ impl PeerConnection {
         None
     }
 
+    /// Pair each media section with a transceiver. Sections carrying `a=mid`
+    /// match the transceiver with that mid wherever the m-line now sits;
+    /// MID-less sections fall back to position, taking the next unused
+    /// transceiver of the same kind. A section with an unknown mid may still
+    /// claim a same-kind transceiver that has no mid yet.
+    fn match_transceivers_by_mid(
+        transceivers: &[Arc<RtpTransceiver>],
+        sections: &[MediaSection],
+    ) -> Vec<Option<Arc<RtpTransceiver>>> {
+        let mut used = vec![false; transceivers.len()];
+        let mut matched = vec![None; sections.len()];
+
+        for (slot, section) in matched.iter_mut().zip(sections) {
+            if section.mid.is_empty() {
+                continue;
+            }
+            if let Some(idx) = transceivers
+                .iter()
+                .position(|t| t.mid().as_deref() == Some(section.mid.as_str()))
+                && !used[idx]
+            {
+                used[idx] = true;
+                *slot = Some(idx);
+            }
+        }
+
+        for (slot, section) in matched.iter_mut().zip(sections) {
+            if slot.is_some() {
+                continue;
+            }
+            let idx = transceivers.iter().enumerate().position(|(idx, t)| {
+                !used[idx]
+                    && t.kind() == section.kind
+                    && (section.mid.is_empty() || t.mid().is_none())
+            });
+            if let Some(idx) = idx {
+                used[idx] = true;
+                *slot = Some(idx);
+            }
+        }
+
+        matched
+            .into_iter()
+            .map(|idx| idx.map(|idx| transceivers[idx].clone()))
+            .collect()
+    }
+
     fn matched_rtp_media_sections(
         &self,
         desc: &SessionDescription,
@@ -2332,6 +2887,11 @@ impl PeerConnection {
                 })
                 .or_else(|| matched.first());
 
+            // Every bundled section shares the primary's socket, so a port
+            // pinned for any of their kinds applies to it.
+            let fixed_port = self
+                .config()
+                .fixed_bundle_port(matched.iter().map(|(t, _)| t.kind()))?;
             if let Some((transceiver, section_idx)) = primary
                 && let Some(remote_addr) =
                     Self::remote_rtp_addr_from_section(desc, &desc.media_sections[*section_idx])
@@ -2340,6 +2900,7 @@ impl PeerConnection {
                     transceiver,
                     &desc.media_sections[*section_idx],
                     true,
+                    fixed_port,
                     remote_addr,
                     ufrag.as_ref(),
                     pwd.as_ref(),
@@ -2363,6 +2924,7 @@ impl PeerConnection {
                     transceiver,
                     &desc.media_sections[*section_idx],
                     media_index == 0,
+                    self.config().fixed_media_port(transceiver.kind()),
                     remote_addr,
                     ufrag.as_ref(),
                     pwd.as_ref(),
@@ -2380,6 +2942,7 @@ impl PeerConnection {
         transceiver: &Arc<RtpTransceiver>,
         section: &MediaSection,
         primary: bool,
+        fixed_port: Option<u16>,
         remote_addr: std::net::SocketAddr,
         ufrag: Option<&String>,
         pwd: Option<&String>,
@@ -2405,10 +2968,11 @@ impl PeerConnection {
             }
         }
 
-        let needs_rtcp_socket = !section.attributes.iter().any(|attr| attr.key == "rtcp-mux");
+        let needs_rtcp_socket = !section.attributes.iter().any(|attr| attr.key == "rtcp-mux")
+            || self.config().profile == MediaProfile::SipGateway;
         if ice_transport.local_candidates().is_empty() {
             ice_transport
-                .setup_direct_rtp_with_rtcp(remote_addr, needs_rtcp_socket)
+                .setup_direct_rtp_with_rtcp(remote_addr, needs_rtcp_socket, fixed_port)
                 .await
                 .map_err(|e| crate::RtcError::Internal(format!("RTP direct error: {}", e)))?;
         } else {
@@ -2505,6 +3069,9 @@ impl PeerConnection {
             false,
             self.config().enable_latching,
         ));
+        rtp_transport.set_early_rtp_buffering(true);
+        rtp_transport.set_unsignaled_ssrc_policy(self.config().unsignaled_ssrc_policy);
+        rtp_transport.set_forward_unknown_rtcp(self.config().forward_unknown_rtcp);
         ice_conn.set_rtp_receiver(rtp_transport.clone());
         ice_transport.set_data_receiver(ice_conn.clone()).await;
 
@@ -2622,12 +3189,18 @@ impl PeerConnection {
         Some(addr)
     }
 
+    /// Parse `a=rtcp:<port> [IN IP4|IP6 <addr>]` (RFC 3605). An omitted or
+    /// unspecified address (`0.0.0.0`, as WebRTC stacks emit) means the
+    /// media address.
     fn parse_rtcp_attribute(attr: &Attribute, fallback_ip: IpAddr) -> Option<std::net::SocketAddr> {
         let value = attr.value.as_deref()?;
         let mut parts = value.split_whitespace();
         let port = parts.next()?.parse::<u16>().ok()?;
         let ip = match (parts.next(), parts.next(), parts.next()) {
-            (Some("IN"), Some("IP4" | "IP6"), Some(host)) => host.parse().ok()?,
+            (Some("IN"), Some("IP4" | "IP6"), Some(host)) => {
+                let ip: IpAddr = host.parse().ok()?;
+                if ip.is_unspecified() { fallback_ip } else { ip }
+            }
             _ => fallback_ip,
         };
         Some(std::net::SocketAddr::new(ip, port))
@@ -2661,23 +3234,44 @@ impl PeerConnection {
                     }
 
                     stats_collector.process_rtcp(&packet);
+                    if let RtcpPacket::TransportWideCc(feedback) = &packet {
+                        rtp_transport.on_transport_cc_feedback(feedback);
+                    }
                     let Some(inner) = inner_weak.upgrade() else {
                         return;
                     };
+                    // The transport only passes these on when
+                    // `forward_unknown_rtcp` is set.
+                    if let RtcpPacket::Unknown(_) = &packet {
+                        let _ = inner.unknown_rtcp_tx.send(packet);
+                        continue;
+                    }
                     {
                         let transceivers = inner.transceivers.lock();
+                        if let RtcpPacket::SenderReport(sr) = &packet {
+                            for t in transceivers.iter() {
+                                if let Some(receiver) = &*t.receiver.lock()
+                                    && receiver.deliver_sender_report(sr)
+                                {
+                                    break;
+                                }
+                            }
+                        }
                         for t in transceivers.iter() {
                             if let Some(sender) = &*t.sender.lock() {
                                 let is_for_sender = match &packet {
-                                    RtcpPacket::PictureLossIndication(p) => {
-                                        if p.media_ssrc == sender.ssrc() {
-                                            trace!("Received PLI for SSRC: {}", p.media_ssrc);
-                                            true
-                                        } else {
-                                            false
-                                        }
+                                    RtcpPacket::PictureLossIndication(p)
+                                        if p.media_ssrc == sender.ssrc() =>
+                                    {
+                                        trace!("Received PLI for SSRC: {}", p.media_ssrc);
+                                        true
+                                    }
+                                    RtcpPacket::FullIntraRequest(f) => {
+                                        f.requests.iter().any(|r| r.ssrc == sender.ssrc())
                                     }
                                     RtcpPacket::GenericNack(n) => n.media_ssrc == sender.ssrc(),
+                                    // Transport-wide: every sender's estimator sees it.
+                                    RtcpPacket::TransportWideCc(_) => true,
                                     _ => false,
                                 };
 
@@ -2692,6 +3286,22 @@ impl PeerConnection {
         }
     }
 
+    /// Periodically report transport-cc arrivals back to the remote sender.
+    async fn create_transport_cc_loop(rtp_transport: Arc<RtpTransport>) {
+        let mut interval = tokio::time::interval(crate::twcc::FEEDBACK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Some(feedback) = rtp_transport.take_transport_cc_feedback()
+                && let Err(e) = rtp_transport
+                    .send_rtcp(&[RtcpPacket::TransportWideCc(feedback)])
+                    .await
+            {
+                trace!("Failed to send transport-cc feedback: {}", e);
+            }
+        }
+    }
+
     async fn create_pair_monitor(
         mut pair_rx: watch::Receiver<Option<crate::transports::ice::IceCandidatePair>>,
         ice_conn_monitor: Arc<IceConn>,
@@ -2730,6 +3340,14 @@ impl PeerConnection {
         self.inner.peer_state.subscribe()
     }
 
+    /// RTCP packets the stack does not interpret (APP, XR, unknown feedback),
+    /// as [`RtcpPacket::Unknown`]. Only published when
+    /// `RtcConfiguration::forward_unknown_rtcp` is enabled; otherwise they
+    /// are dropped.
+    pub fn subscribe_unknown_rtcp(&self) -> broadcast::Receiver<RtcpPacket> {
+        self.inner.unknown_rtcp_tx.subscribe()
+    }
+
     pub async fn wait_for_connected(&self) -> RtcResult<()> {
         let mut peer_state_rx = self.subscribe_peer_state();
         loop {
@@ -2737,7 +3355,21 @@ impl PeerConnection {
             if state == PeerConnectionState::Connected {
                 return Ok(());
             }
-            if state == PeerConnectionState::Failed || state == PeerConnectionState::Closed {
+            if state == PeerConnectionState::Failed {
+                match self.disconnect_reason() {
+                    Some(DisconnectReason::DtlsAlert(alert)) => {
+                        return Err(RtcError::DtlsAlert(alert));
+                    }
+                    Some(DisconnectReason::FingerprintMismatch(reason)) => {
+                        return Err(RtcError::FingerprintMismatch(reason));
+                    }
+                    Some(DisconnectReason::IceFailed) => {
+                        return Err(RtcError::IceFailed);
+                    }
+                    _ => {}
+                }
+            }
+            if state == PeerConnectionState::Failed || state == PeerConnectionState::Closed {
                 return Err(RtcError::Internal(format!(
                     "Peer connection failed or closed: {:?}",
                     state
@@ -2772,6 +3404,51 @@ impl PeerConnection {
         self.inner.local_description.lock().clone()
     }
 
+    /// The applied local description with its candidates refreshed from
+    /// everything gathered since, plus `a=end-of-candidates` once gathering
+    /// has completed.
+    ///
+    /// Trickle applications that answer before gathering finishes can use
+    /// this when they later need a full SDP. Outside WebRTC mode it is the
+    /// same as [`local_description`](Self::local_description).
+    pub fn current_local_description(&self) -> Option<SessionDescription> {
+        let mut desc = self.local_description()?;
+        if self.config().transport_mode != TransportMode::WebRtc {
+            return Some(desc);
+        }
+        let candidates: Vec<String> = self
+            .inner
+            .ice_transport
+            .local_candidates()
+            .iter()
+            .map(IceCandidate::to_sdp)
+            .collect();
+        let gather_complete = matches!(
+            self.inner.ice_transport.gather_state(),
+            IceGathererState::Complete
+        );
+        for section in desc
+            .media_sections
+            .iter_mut()
+            .filter(|s| s.attributes.iter().any(|a| a.key == "ice-ufrag"))
+        {
+            section
+                .attributes
+                .retain(|a| a.key != "candidate" && a.key != "end-of-candidates");
+            for candidate in &candidates {
+                section
+                    .attributes
+                    .push(Attribute::new("candidate", Some(candidate.clone())));
+            }
+            if gather_complete {
+                section
+                    .attributes
+                    .push(Attribute::new("end-of-candidates", None));
+            }
+        }
+        Some(desc)
+    }
+
     pub fn remote_description(&self) -> Option<SessionDescription> {
         self.inner.remote_description.lock().clone()
     }
@@ -2948,6 +3625,77 @@ impl PeerConnection {
         }
     }
 
+    /// Close a data channel with an SCTP stream reset (RFC 8831 §6.7).
+    ///
+    /// Messages already sent are still delivered. Both ends then emit
+    /// [`DataChannelEvent::Close`](crate::DataChannelEvent::Close); closing an
+    /// already closed channel does nothing.
+    pub async fn close_data_channel(&self, channel_id: u16) -> RtcResult<()> {
+        let transport = self.inner.sctp_transport.lock().clone();
+        if let Some(transport) = transport {
+            transport
+                .close_data_channel(channel_id)
+                .await
+                .map_err(|e| RtcError::Internal(format!("SCTP close failed: {}", e)))
+        } else {
+            Err(RtcError::InvalidState("SCTP not connected".into()))
+        }
+    }
+
+    /// Send real-time text (T.140) to the peer.
+    ///
+    /// The text travels as UTF-8 on a reliable, ordered RFC 8865 `t140` data
+    /// channel, and the peer surfaces it as
+    /// [`PeerConnectionEvent::RealtimeText`]. The channel is opened on first
+    /// use unless the peer already opened one, in which case the reply goes
+    /// back on that channel.
+    pub async fn send_realtime_text(&self, text: &str) -> RtcResult<()> {
+        let (dc, mut state) = self.realtime_text_channel()?;
+        let opened = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            state.wait_for(|s| *s != crate::transports::sctp::DataChannelState::Connecting),
+        )
+        .await;
+        if !matches!(opened, Ok(Ok(s)) if *s == crate::transports::sctp::DataChannelState::Open) {
+            return Err(RtcError::InvalidState(
+                "T.140 data channel is not open".into(),
+            ));
+        }
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.send_text(dc.id, text).await
+    }
+
+    fn realtime_text_channel(
+        &self,
+    ) -> RtcResult<(
+        Arc<crate::transports::sctp::DataChannel>,
+        watch::Receiver<crate::transports::sctp::DataChannelState>,
+    )> {
+        if let Some(channel) = self.inner.realtime_text.lock().as_ref() {
+            return Ok(channel.clone());
+        }
+        if self.inner.sctp_transport.lock().is_none() {
+            return Err(RtcError::InvalidState("SCTP not connected".into()));
+        }
+        let dc = self.create_data_channel(
+            crate::t140::T140_PROTOCOL,
+            Some(crate::transports::sctp::DataChannelConfig {
+                protocol: crate::t140::T140_PROTOCOL.to_string(),
+                ordered: true,
+                ..Default::default()
+            }),
+        )?;
+        let state = self.inner.spawn_realtime_text_reader(dc.clone());
+        Ok(self
+            .inner
+            .realtime_text
+            .lock()
+            .get_or_insert_with(|| (dc, state))
+            .clone())
+    }
+
     pub fn sctp_buffered_amount(&self) -> usize {
         let transport = self.inner.sctp_transport.lock().clone();
         if let Some(transport) = transport {
@@ -2957,12 +3705,158 @@ impl PeerConnection {
         }
     }
 
-    #[allow(clippy::cloned_ref_to_slice_refs)]
+    /// RTP stream, ICE candidate pair and transport statistics in one report.
     pub async fn get_stats(&self) -> RtcResult<StatsReport> {
-        // The `.clone()` is required for the `Arc<StatsCollector>` ->
-        // `Arc<dyn StatsProvider>` unsizing coercion into the slice; from_ref
-        // would not coerce.
-        gather_once(&[self.inner.stats_collector.clone()]).await
+        use crate::stats::{DynProvider, StatsKind};
+        use crate::transports::dtls::DtlsState;
+        use serde_json::json;
+        // RTP transports contribute the `SenderBwe` entry when transport-cc
+        // is negotiated; the ICE agent one entry per candidate pair and the
+        // DTLS transport the `transport` entry.
+        let mut providers: Vec<Arc<DynProvider>> = vec![
+            self.inner.stats_collector.clone(),
+            Arc::new(self.inner.ice_transport.clone()),
+        ];
+        let dtls = self.inner.dtls_transport.lock().clone();
+        if let Some(dtls) = dtls {
+            providers.push(dtls);
+        }
+        let mut conns = Vec::new();
+        if let Some(rtp) = self.inner.rtp_transport.lock().as_ref() {
+            providers.push(rtp.clone());
+            conns.push(rtp.ice_conn());
+        }
+        for rtp in self.inner.rtp_media_transports.lock().values() {
+            providers.push(rtp.clone());
+            conns.push(rtp.ice_conn());
+        }
+        let mut report = gather_once(&providers).await?;
+        if !report
+            .entries
+            .iter()
+            .any(|e| e.kind == StatsKind::Transport)
+        {
+            report.entries.push(DtlsState::New.stats_entry());
+        }
+
+        // The selected pair carries everything the connections sent.
+        let bytes_sent: u64 = conns
+            .iter()
+            .map(|conn| conn.tx_bytes.load(Ordering::Relaxed))
+            .sum();
+        let bytes_received: u64 = conns
+            .iter()
+            .map(|conn| conn.rx_bytes.load(Ordering::Relaxed))
+            .sum();
+        // Frame boundaries, retransmissions and padding are only known to
+        // the senders themselves.
+        let senders: HashMap<u32, Arc<RtpSender>> = self
+            .inner
+            .transceivers
+            .lock()
+            .iter()
+            .filter_map(|t| t.sender())
+            .map(|sender| (sender.ssrc(), sender))
+            .collect();
+
+        let mut selected_pair_id = None;
+        for entry in &mut report.entries {
+            if entry.kind == StatsKind::OutboundRtp
+                && let Some(sender) = entry
+                    .values
+                    .get("ssrc")
+                    .and_then(|ssrc| ssrc.as_u64())
+                    .and_then(|ssrc| senders.get(&(ssrc as u32)))
+            {
+                Self::add_sender_packetization_stats(entry, sender);
+            } else if entry.kind == StatsKind::IceCandidatePair
+                && entry.values.get("selected") == Some(&json!(true))
+            {
+                entry.values.insert("bytesSent".into(), json!(bytes_sent));
+                entry
+                    .values
+                    .insert("bytesReceived".into(), json!(bytes_received));
+                selected_pair_id = Some(entry.id.clone());
+            }
+        }
+        if let Some(transport) = report
+            .entries
+            .iter_mut()
+            .find(|e| e.kind == StatsKind::Transport)
+        {
+            let values = &mut transport.values;
+            values.insert("bytesSent".into(), json!(bytes_sent));
+            values.insert("bytesReceived".into(), json!(bytes_received));
+            if let Some(id) = selected_pair_id {
+                values.insert("selectedCandidatePairId".into(), json!(id));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Fill in what an outbound RTP entry can only learn from its sender:
+    /// frames as the packetizer delimited them, retransmissions and padding.
+    fn add_sender_packetization_stats(entry: &mut crate::stats::StatsEntry, sender: &RtpSender) {
+        use serde_json::json;
+        let frames = sender.frames_sent();
+        let retransmitted = sender
+            .nack_handler()
+            .map_or(0, |nack| nack.get_rtx_sent_count());
+        let padding = sender.padding_bytes_sent();
+        let values = &mut entry.values;
+        values.insert("framesSent".into(), json!(frames));
+        if frames > 0
+            && let Some(packets) = values.get("packetsSent").and_then(|v| v.as_u64())
+        {
+            let per_frame = packets as f64 / frames as f64;
+            values.insert("packetsPerFrame".into(), json!(per_frame));
+        }
+        values.insert("retransmittedPacketsSent".into(), json!(retransmitted));
+        values.insert("paddingBytesSent".into(), json!(padding));
+    }
+
+    /// The local DTLS role, once known from the remote description or
+    /// `RtcConfiguration::effective_dtls_role`.
+    pub fn dtls_role(&self) -> Option<DtlsRole> {
+        self.inner.dtls_role.borrow().map(|is_client| {
+            if is_client {
+                DtlsRole::Client
+            } else {
+                DtlsRole::Server
+            }
+        })
+    }
+
+    /// Derive `len` bytes of keying material from the DTLS session (RFC 5705).
+    ///
+    /// Both peers obtain identical output for the same `label`, `context` and
+    /// `len`. Fails until the DTLS handshake has completed.
+    pub fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        len: usize,
+    ) -> RtcResult<Vec<u8>> {
+        const RESERVED_LABELS: [&str; 4] = [
+            "client finished",
+            "server finished",
+            "master secret",
+            "key expansion",
+        ];
+        if RESERVED_LABELS.contains(&label) {
+            return Err(RtcError::InvalidConfiguration(format!(
+                "exporter label '{}' is reserved by TLS",
+                label
+            )));
+        }
+        let dtls = self
+            .inner
+            .dtls_transport
+            .lock()
+            .clone()
+            .ok_or_else(|| RtcError::InvalidState("DTLS transport not started".into()))?;
+        dtls.export_keying_material_with_context(label, context, len)
+            .map_err(|e| RtcError::InvalidState(format!("cannot export keying material: {}", e)))
     }
 
     /// Collect transport-level (UDP tx/rx) stats from all active IceConn instances.
@@ -3008,6 +3902,13 @@ impl PeerConnection {
         Ok(())
     }
 
+    /// The peer has trickled all of its candidates; lets ICE fail as soon as
+    /// every pair has failed instead of waiting for `ice_checking_timeout`.
+    pub fn add_end_of_candidates(&self) -> RtcResult<()> {
+        self.inner.ice_transport.add_remote_end_of_candidates();
+        Ok(())
+    }
+
     /// Handle reinvite - update RTP parameters without recreating tracks
     async fn handle_reinvite(&self, new_desc: &SessionDescription) -> RtcResult<()> {
         debug!("Handling reinvite: updating RTP parameters");
@@ -3015,21 +3916,9 @@ impl PeerConnection {
         let transceivers = self.inner.transceivers.lock().clone();
 
         // Extract RTP parameter changes for each media section
-        for section in &new_desc.media_sections {
-            // Find matching transceiver by mid
-            let transceiver = transceivers
-                .iter()
-                .find(|t| t.mid().as_ref() == Some(&section.mid))
-                .or_else(|| {
-                    if section.mid.is_empty() {
-                        // MID-less re-INVITE fallback: match by kind.
-                        transceivers.iter().find(|t| t.kind() == section.kind)
-                    } else {
-                        None
-                    }
-                });
-
-            if let Some(t) = transceiver {
+        let matches = Self::match_transceivers_by_mid(&transceivers, &new_desc.media_sections);
+        for (section, transceiver) in new_desc.media_sections.iter().zip(matches) {
+            if let Some(t) = &transceiver {
                 // Check SSRC change (indicates new track, not reinvite)
                 if let Some(receiver) = t.receiver() {
                     let new_ssrc = Self::extract_ssrc_from_section(section);
@@ -3066,10 +3955,16 @@ impl PeerConnection {
                     }
                     t.update_payload_map(payload_map)?;
                 }
+                t.update_codec_name(Self::extract_codec_name(section));
+                t.update_payload_codecs(Self::extract_payload_codecs(section));
+                t.update_remote_content(section);
+                t.update_remote_fmtp(section);
 
                 // Extract and update extension mapping
                 let extmap = Self::extract_extmap(section);
-                t.update_extmap(extmap)?;
+                let directions = Self::extract_remote_extmap_directions(section);
+                t.update_extmap_with_directions(extmap, directions)?;
+                t.update_video_limits(Self::extract_video_limits(section));
 
                 // Handle direction changes
                 let new_direction: TransceiverDirection = section.direction.into();
@@ -3080,7 +3975,8 @@ impl PeerConnection {
                         section.mid, old_direction, new_direction
                     );
                     t.set_direction(new_direction);
-                    Self::apply_direction_change(t, old_direction, new_direction).await?;
+                    self.apply_direction_change(t, old_direction, new_direction)
+                        .await?;
                 }
             }
         }
@@ -3124,40 +4020,108 @@ impl PeerConnection {
         Ok(())
     }
 
+    /// First payload type in m-line order that carries media, by encoding
+    /// name. Static payload types without an rtpmap use their RFC 3551 name.
+    fn extract_codec_name(section: &crate::MediaSection) -> Option<String> {
+        section.formats.iter().find_map(|format| {
+            let pt = format.parse::<u8>().ok()?;
+            Self::media_codec_name(section, pt).map(str::to_string)
+        })
+    }
+
+    /// Encoding name of every payload type that carries media, skipping the
+    /// same auxiliary formats as [`Self::extract_codec_name`] except
+    /// telephone-event, which the receiver delivers alongside the audio.
+    fn extract_payload_codecs(section: &crate::MediaSection) -> HashMap<u8, String> {
+        section
+            .formats
+            .iter()
+            .filter_map(|format| {
+                let pt = format.parse::<u8>().ok()?;
+                let codec = Self::media_codec_name(section, pt)
+                    .or_else(|| Self::telephone_event_pt(section, pt).then_some("telephone-event"));
+                Some((pt, codec?.to_string()))
+            })
+            .collect()
+    }
+
+    fn telephone_event_pt(section: &crate::MediaSection, pt: u8) -> bool {
+        section
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "rtpmap")
+            .filter_map(|attr| crate::sdp::RtpMap::parse(attr.value.as_deref()?))
+            .any(|rtpmap| {
+                rtpmap.payload_type == pt
+                    && rtpmap.encoding_name.eq_ignore_ascii_case("telephone-event")
+            })
+    }
+
+    fn media_codec_name(section: &crate::MediaSection, pt: u8) -> Option<&str> {
+        const AUXILIARY: [&str; 6] = [
+            "rtx",
+            "red",
+            "ulpfec",
+            "flexfec-03",
+            "telephone-event",
+            "CN",
+        ];
+        let name = section
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "rtpmap")
+            .filter_map(|attr| attr.value.as_deref())
+            .find_map(|val| {
+                let (attr_pt, encoding) = val.split_once(' ')?;
+                (attr_pt.parse::<u8>().ok()? == pt)
+                    .then(|| encoding.trim().split('/').next().unwrap_or_default())
+            })
+            .or(match pt {
+                0 => Some("PCMU"),
+                8 => Some("PCMA"),
+                9 => Some("G722"),
+                18 => Some("G729"),
+                _ => None,
+            })?;
+        let auxiliary = AUXILIARY.iter().any(|aux| name.eq_ignore_ascii_case(aux));
+        (!auxiliary).then_some(name)
+    }
+
+    /// Codec parameters of every payload type that carries media, in m-line
+    /// order, skipping the same auxiliary formats as
+    /// [`Self::extract_codec_name`].
+    fn extract_media_codecs(section: &crate::MediaSection) -> Vec<RtpCodecParameters> {
+        let payload_map = Self::extract_payload_map(section);
+        section
+            .formats
+            .iter()
+            .filter_map(|format| {
+                let pt = format.parse::<u8>().ok()?;
+                Self::media_codec_name(section, pt)?;
+                payload_map.get(&pt).cloned()
+            })
+            .collect()
+    }
+
     /// Extract payload type to codec parameters mapping from media section
     fn extract_payload_map(section: &crate::MediaSection) -> HashMap<u8, RtpCodecParameters> {
         let mut payload_map = HashMap::new();
 
-        // Parse rtpmap attributes: "96 opus/48000/2"
-        for attr in &section.attributes {
-            if attr.key == "rtpmap"
-                && let Some(val) = &attr.value
-            {
-                let parts: Vec<&str> = val.split_whitespace().collect();
-                if parts.len() >= 2
-                    && let Ok(pt) = parts[0].parse::<u8>()
-                {
-                    // Parse codec/rate/channels
-                    let codec_parts: Vec<&str> = parts[1].split('/').collect();
-                    if codec_parts.len() >= 2 {
-                        let clock_rate = codec_parts[1].parse().unwrap_or(90000);
-                        let channels = if codec_parts.len() >= 3 {
-                            codec_parts[2].parse().unwrap_or(0)
-                        } else {
-                            0
-                        };
-
-                        payload_map.insert(
-                            pt,
-                            RtpCodecParameters {
-                                payload_type: pt,
-                                clock_rate,
-                                channels,
-                            },
-                        );
-                    }
-                }
-            }
+        // Parse rtpmap attributes: "96 opus/48000/2". Malformed lines are skipped.
+        for rtpmap in section
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "rtpmap")
+            .filter_map(|attr| crate::sdp::RtpMap::parse(attr.value.as_deref()?))
+        {
+            payload_map.insert(
+                rtpmap.payload_type,
+                RtpCodecParameters {
+                    payload_type: rtpmap.payload_type,
+                    clock_rate: rtpmap.clock_rate,
+                    channels: rtpmap.channels.unwrap_or(0),
+                },
+            );
         }
         for format in &section.formats {
             if let Ok(pt) = format.parse::<u8>()
@@ -3211,16 +4175,75 @@ impl PeerConnection {
             {
                 let parts: Vec<&str> = val.split_whitespace().collect();
                 if parts.len() >= 2
-                    && let Ok(id) = parts[0].parse::<u8>()
+                    && let Some((id, _)) = Self::parse_extmap_id(parts[0])
                 {
                     extmap.insert(id, parts[1].to_string());
                 }
             }
         }
 
+        // The transport-wide sequence number is only useful with the matching
+        // feedback, so treat the extension as negotiated only alongside it.
+        if !section.has_rtcp_fb("transport-cc") {
+            extmap.retain(|_, uri| uri != crate::sdp::TRANSPORT_CC_URI);
+        }
+
         extmap
     }
 
+    /// Extract the direction suffixes ("1/recvonly ...") of extmap attributes
+    /// in a local description.
+    fn extract_extmap_directions(
+        section: &crate::MediaSection,
+    ) -> HashMap<u8, TransceiverDirection> {
+        section
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "extmap")
+            .filter_map(|attr| attr.value.as_deref()?.split_whitespace().next())
+            .filter_map(Self::parse_extmap_id)
+            .filter_map(|(id, direction)| Some((id, direction?)))
+            .collect()
+    }
+
+    /// Like [`extract_extmap_directions`](Self::extract_extmap_directions)
+    /// for a remote description. The remote writes directions from its own
+    /// side (RFC 8285 §7), so its `recvonly` means we only send.
+    fn extract_remote_extmap_directions(
+        section: &crate::MediaSection,
+    ) -> HashMap<u8, TransceiverDirection> {
+        Self::extract_extmap_directions(section)
+            .into_iter()
+            .map(|(id, direction)| (id, direction.answer_direction()))
+            .collect()
+    }
+
+    fn parse_extmap_id(value: &str) -> Option<(u8, Option<TransceiverDirection>)> {
+        let (id, direction) = match value.split_once('/') {
+            Some((id, direction)) => (id, Some(Direction::from_attribute(direction)?.into())),
+            None => (value, None),
+        };
+        Some((id.parse().ok()?, direction))
+    }
+
+    /// Limits the remote asked our video sender to respect: its `a=framerate`
+    /// and the `recv` part of the `a=imageattr` for the preferred payload type.
+    fn extract_video_limits(section: &crate::MediaSection) -> VideoSendLimits {
+        if section.kind != MediaKind::Video {
+            return VideoSendLimits::default();
+        }
+        let preferred_pt = section.formats.first().and_then(|f| f.parse::<u8>().ok());
+        let image_attrs = section.get_image_attributes();
+        let image_attr = image_attrs
+            .iter()
+            .find(|attr| attr.payload_type.is_some() && attr.payload_type == preferred_pt)
+            .or_else(|| image_attrs.iter().find(|attr| attr.payload_type.is_none()));
+        VideoSendLimits {
+            max_resolution: image_attr.and_then(|attr| attr.recv),
+            max_framerate: section.framerate(),
+        }
+    }
+
     /// Extract SSRC from media section
     fn extract_ssrc_from_section(section: &crate::MediaSection) -> Option<u32> {
         // Parse a=ssrc:<ssrc> <attribute>:<value>
@@ -3238,6 +4261,7 @@ impl PeerConnection {
 
     /// Apply direction change side effects
     async fn apply_direction_change(
+        &self,
         transceiver: &RtpTransceiver,
         old_direction: TransceiverDirection,
         new_direction: TransceiverDirection,
@@ -3264,10 +4288,28 @@ impl PeerConnection {
         if old_sends != new_sends {
             if new_sends {
                 debug!("Transceiver {} starting to send", transceiver.id());
-                // Resume sender if available
                 if let Some(sender) = transceiver.sender() {
-                    // In full implementation: sender.resume()
-                    trace!("Sender {} would resume", sender.ssrc());
+                    // Re-attach in case the sender was added while the
+                    // transceiver was not sending; set_transport is a no-op
+                    // when the send loop is already running on this transport.
+                    if let Some(transport) = transceiver
+                        .rtp_transport
+                        .lock()
+                        .as_ref()
+                        .and_then(|transport| transport.upgrade())
+                    {
+                        sender.set_transport(transport);
+                    }
+                } else if transceiver.sender_ssrc().is_none() {
+                    // A transceiver created recvonly from the remote offer has
+                    // never had a send stream; allocate one so the answer
+                    // signals it and add_track reuses it.
+                    let ssrc = self.preallocate_sender_ssrc(transceiver);
+                    debug!(
+                        "Transceiver {} allocated sender ssrc={} on unhold",
+                        transceiver.id(),
+                        ssrc
+                    );
                 }
             } else {
                 debug!("Transceiver {} stopping send", transceiver.id());
@@ -3335,8 +4377,23 @@ async fn run_gathering_loop(
     let mut rx = ice_transport.subscribe_gathering_state();
     let mut ice_state_rx = ice_transport.subscribe_state();
     let mut cand_rx = ice_transport.subscribe_candidates();
+    let trickle = |event: PeerConnectionEvent| {
+        if let Some(inner) = inner_weak.upgrade()
+            && inner.config.transport_mode == TransportMode::WebRtc
+        {
+            let _ = inner.event_tx.send(event);
+        }
+    };
     loop {
         let state = *rx.borrow_and_update();
+        if state == crate::transports::ice::IceGathererState::Complete {
+            // Candidates queued behind the state change go out before the
+            // gathering state reports Complete.
+            while let Ok(candidate) = cand_rx.try_recv() {
+                trickle(PeerConnectionEvent::IceCandidate(candidate));
+            }
+            trickle(PeerConnectionEvent::EndOfCandidates);
+        }
         if state == crate::transports::ice::IceGathererState::Complete
             && let Some(inner) = inner_weak.upgrade()
             && !update_local_description_on_gather(&inner, &ice_transport)
@@ -3362,7 +4419,15 @@ async fn run_gathering_loop(
             break;
         }
         if state == crate::transports::ice::IceGathererState::Complete {
-            break;
+            // Gathering only starts again after an ICE restart.
+            tokio::select! {
+                res = rx.changed() => {
+                    if res.is_err() { break; }
+                }
+                _ = ice_state_rx.wait_for(|s| matches!(s, crate::transports::ice::IceTransportState::Closed | crate::transports::ice::IceTransportState::Failed)) => break,
+            }
+            cand_rx = cand_rx.resubscribe();
+            continue;
         }
         tokio::select! {
             res = rx.changed() => {
@@ -3374,7 +4439,10 @@ async fn run_gathering_loop(
                     break;
                 }
             }
-            _ = cand_rx.recv() => {
+            res = cand_rx.recv() => {
+                if let Ok(candidate) = res {
+                    trickle(PeerConnectionEvent::IceCandidate(candidate));
+                }
                 if let Some(inner) = inner_weak.upgrade()
                     && inner.config.transport_mode == TransportMode::WebRtc
                 {
@@ -3405,18 +4473,7 @@ async fn run_rtp_direct_loop(
     loop {
         let ice_state = *ice_state_rx.borrow_and_update();
 
-        let pc_ice_state = match ice_state {
-            crate::transports::ice::IceTransportState::New => IceConnectionState::New,
-            crate::transports::ice::IceTransportState::Checking => IceConnectionState::Checking,
-            crate::transports::ice::IceTransportState::Connected => IceConnectionState::Connected,
-            crate::transports::ice::IceTransportState::Completed => IceConnectionState::Completed,
-            crate::transports::ice::IceTransportState::Failed => IceConnectionState::Failed,
-            crate::transports::ice::IceTransportState::Disconnected => {
-                IceConnectionState::Disconnected
-            }
-            crate::transports::ice::IceTransportState::Closed => IceConnectionState::Closed,
-        };
-        let _ = ice_connection_state_tx.send(pc_ice_state);
+        let _ = ice_connection_state_tx.send(ice_connection_state(ice_state));
 
         match ice_state {
             crate::transports::ice::IceTransportState::Connected
@@ -3475,18 +4532,7 @@ async fn run_ice_dtls_loop(
     loop {
         let ice_state = *ice_state_rx.borrow_and_update();
 
-        let pc_ice_state = match ice_state {
-            crate::transports::ice::IceTransportState::New => IceConnectionState::New,
-            crate::transports::ice::IceTransportState::Checking => IceConnectionState::Checking,
-            crate::transports::ice::IceTransportState::Connected => IceConnectionState::Connected,
-            crate::transports::ice::IceTransportState::Completed => IceConnectionState::Completed,
-            crate::transports::ice::IceTransportState::Failed => IceConnectionState::Failed,
-            crate::transports::ice::IceTransportState::Disconnected => {
-                IceConnectionState::Disconnected
-            }
-            crate::transports::ice::IceTransportState::Closed => IceConnectionState::Closed,
-        };
-        let _ = ice_connection_state_tx.send(pc_ice_state);
+        let _ = ice_connection_state_tx.send(ice_connection_state(ice_state));
         match ice_state {
             crate::transports::ice::IceTransportState::Connected
             | crate::transports::ice::IceTransportState::Completed => {
@@ -3722,9 +4768,16 @@ async fn handle_connected_state(
                 match pc_temp.start_dtls(is_client).await {
                     Err(e) => {
                         debug!("DTLS start failed: {}", e);
+                        let reason = match e {
+                            RtcError::DtlsAlert(alert) => DisconnectReason::DtlsAlert(alert),
+                            RtcError::FingerprintMismatch(reason) => {
+                                DisconnectReason::FingerprintMismatch(reason)
+                            }
+                            _ => DisconnectReason::DtlsFailed,
+                        };
                         let _ = inner.disconnect_reason.send_if_modified(|cur| {
                             if cur.is_none() {
-                                *cur = Some(DisconnectReason::DtlsFailed);
+                                *cur = Some(reason);
                                 true
                             } else {
                                 false
@@ -3755,6 +4808,9 @@ async fn handle_connected_state(
                                         if is_ice_disconnected(new_state) {
                                             return true;
                                         }
+                                        // An ICE restart passes through Checking while
+                                        // DTLS keeps running on the current pair.
+                                        forward_ice_connection_state(ice_connection_state_tx, new_state);
                                     }
                                     res = dtls_rx.changed() => {
                                         if res.is_ok() {
@@ -3762,7 +4818,10 @@ async fn handle_connected_state(
                                             if state == crate::transports::dtls::DtlsState::Closed || state == crate::transports::dtls::DtlsState::Failed {
                                                 debug!("DTLS closed/failed, disconnecting PC");
                                                 let reason = if state == crate::transports::dtls::DtlsState::Failed {
-                                                    DisconnectReason::DtlsFailed
+                                                    match inner.dtls_transport.lock().as_ref().and_then(|dtls| dtls.received_alert()) {
+                                                        Some(alert) => DisconnectReason::DtlsAlert(alert.to_string()),
+                                                        None => DisconnectReason::DtlsFailed,
+                                                    }
                                                 } else {
                                                     DisconnectReason::DtlsClosed
                                                 };
@@ -3793,6 +4852,9 @@ async fn handle_connected_state(
                                         if is_ice_disconnected(new_state) {
                                             return true;
                                         }
+                                        // An ICE restart passes through Checking while
+                                        // DTLS keeps running on the current pair.
+                                        forward_ice_connection_state(ice_connection_state_tx, new_state);
                                     }
                                 }
                             }
@@ -3823,6 +4885,34 @@ async fn handle_connected_state(
     }
 }
 
+fn ice_connection_state(state: crate::transports::ice::IceTransportState) -> IceConnectionState {
+    match state {
+        crate::transports::ice::IceTransportState::New => IceConnectionState::New,
+        crate::transports::ice::IceTransportState::Checking => IceConnectionState::Checking,
+        crate::transports::ice::IceTransportState::Connected => IceConnectionState::Connected,
+        crate::transports::ice::IceTransportState::Completed => IceConnectionState::Completed,
+        crate::transports::ice::IceTransportState::Failed => IceConnectionState::Failed,
+        crate::transports::ice::IceTransportState::Disconnected => IceConnectionState::Disconnected,
+        crate::transports::ice::IceTransportState::Closed => IceConnectionState::Closed,
+    }
+}
+
+/// Publish the connection state for `state`, notifying watchers only when it
+/// changes.
+fn forward_ice_connection_state(
+    tx: &watch::Sender<IceConnectionState>,
+    state: crate::transports::ice::IceTransportState,
+) {
+    let state = ice_connection_state(state);
+    tx.send_if_modified(|cur| {
+        if *cur == state {
+            return false;
+        }
+        *cur = state;
+        true
+    });
+}
+
 fn is_ice_disconnected(state: crate::transports::ice::IceTransportState) -> bool {
     matches!(
         state,
@@ -3833,6 +4923,49 @@ fn is_ice_disconnected(state: crate::transports::ice::IceTransportState) -> bool
 }
 
 impl PeerConnectionInner {
+    /// Restart the ICE transport and reflect the re-armed gathering right
+    /// away, so `wait_for_gathering_complete` does not return on the
+    /// previous generation's `Complete`.
+    fn restart_ice_transport(&self) {
+        self.ice_transport.restart();
+        let _ = self.ice_gathering_state.send(IceGatheringState::New);
+    }
+
+    /// Decode T.140 messages arriving on `dc` into `RealtimeText` events.
+    /// The returned receiver follows the channel's open and close events.
+    fn spawn_realtime_text_reader(
+        self: &Arc<Self>,
+        dc: Arc<crate::transports::sctp::DataChannel>,
+    ) -> watch::Receiver<crate::transports::sctp::DataChannelState> {
+        use crate::transports::sctp::{DataChannelEvent, DataChannelState};
+        let (state_tx, state_rx) =
+            watch::channel(DataChannelState::from(dc.state.load(Ordering::SeqCst)));
+        let inner_weak = Arc::downgrade(self);
+        let h = tokio::spawn(async move {
+            while let Some(event) = dc.recv().await {
+                match event {
+                    DataChannelEvent::Open => {
+                        let _ = state_tx.send(DataChannelState::Open);
+                    }
+                    DataChannelEvent::Close => break,
+                    DataChannelEvent::Message(data) => {
+                        let Some(inner) = inner_weak.upgrade() else {
+                            break;
+                        };
+                        let text = crate::t140::decode(&data);
+                        if !text.is_empty() {
+                            let _ = inner.event_tx.send(PeerConnectionEvent::RealtimeText(text));
+                        }
+                    }
+                    DataChannelEvent::BufferedAmountLow => {}
+                }
+            }
+            let _ = state_tx.send(DataChannelState::Closed);
+        });
+        self.track_task(h);
+        state_rx
+    }
+
     /// Track a spawned task so it can be aborted on close. Only meant for
     /// fire-and-forget tasks whose lifetime should be bounded by the connection.
     fn track_task(&self, handle: tokio::task::JoinHandle<()>) {
@@ -3869,10 +5002,39 @@ impl PeerConnectionInner {
         transport
     }
 
+    /// Whether the remote description advertises `a=ice-options:trickle`.
+    fn remote_supports_trickle(&self) -> bool {
+        self.remote_description
+            .lock()
+            .as_ref()
+            .is_some_and(|desc| desc.has_ice_option("trickle"))
+    }
+
+    /// Origin for the next local description. The session id is fixed by the
+    /// first local description and the version is one above the last applied
+    /// one (RFC 3264 §8), so SIP peers treat every re-offer as a change.
+    fn next_local_origin(&self) -> Origin {
+        let previous = self
+            .local_description
+            .lock()
+            .as_ref()
+            .map(|desc| desc.session.origin.clone());
+        let mut origin = previous.unwrap_or_else(default_origin);
+        origin.session_version += 1;
+        if let Some(username) = &self.config.sdp_origin_username {
+            origin.username = username.clone();
+        }
+        if let Some(ext_ip) = &self.config.external_ip {
+            origin.unicast_address = ext_ip.clone();
+        }
+        origin
+    }
+
     async fn build_description<F>(
         &self,
         sdp_type: SdpType,
         map_direction: F,
+        stop_gathering: impl Future<Output = ()>,
     ) -> RtcResult<SessionDescription>
     where
         F: Fn(TransceiverDirection) -> TransceiverDirection,
@@ -3887,7 +5049,11 @@ impl PeerConnectionInner {
             ));
         }
 
-        let mut remote_offered_bundle = false;
+        // Mids the remote offer bundled; the answer may only group these.
+        let mut remote_bundled_mids = std::collections::HashSet::new();
+        // Remote sections disabled with port 0 (and not `a=bundle-only`);
+        // the answer rejects them with port 0 as well (RFC 8843 §7.3.3).
+        let mut remote_rejected_mids = std::collections::HashSet::new();
 
         let ordered_transceivers = if sdp_type == SdpType::Answer {
             let remote_guard = self.remote_description.lock();
@@ -3895,14 +5061,17 @@ impl PeerConnectionInner {
                 RtcError::InvalidState("create_answer called without remote description".into())
             })?;
 
-            for attr in &remote.session.attributes {
-                if attr.key == "group"
-                    && let Some(val) = &attr.value
-                    && val.starts_with("BUNDLE")
-                {
-                    remote_offered_bundle = true;
-                }
-            }
+            remote_bundled_mids.extend(remote.bundle_groups().into_iter().flatten());
+            remote_rejected_mids.extend(
+                remote
+                    .media_sections
+                    .iter()
+                    .filter(|section| {
+                        section.port == 0
+                            && !section.attributes.iter().any(|a| a.key == "bundle-only")
+                    })
+                    .map(|section| section.mid.clone()),
+            );
 
             let mut ordered = Vec::new();
             let mut used_indices = std::collections::HashSet::new();
@@ -3943,7 +5112,8 @@ impl PeerConnectionInner {
                     used_indices.insert(idx);
                     ordered.push((
                         t,
-                        section.attributes.iter().any(|attr| attr.key == "rtcp-mux"),
+                        section.attributes.iter().any(|attr| attr.key == "rtcp-mux")
+                            && self.config.profile != MediaProfile::SipGateway,
                     ));
                 } else {
                     return Err(RtcError::Internal(format!(
@@ -3984,18 +5154,26 @@ impl PeerConnectionInner {
         // "Answer cannot remove m= section ... from already-established BUNDLE
         // group".  For offers we only group when there is more than one section
         // to stay compatible with plain-RTP/SIP peers.
-        let will_bundle = self.config.sdp_compatibility
+        let will_bundle = self.config.effective_sdp_compatibility()
             != crate::config::SdpCompatibilityMode::LegacySip
             && match sdp_type {
                 SdpType::Offer => ordered_transceivers.len() > 1,
-                SdpType::Answer => remote_offered_bundle,
+                SdpType::Answer => !remote_bundled_mids.is_empty(),
                 _ => false,
             };
+        let bundle_port = if will_bundle && mode == TransportMode::Rtp {
+            self.config
+                .fixed_bundle_port(ordered_transceivers.iter().map(|(t, _)| t.kind()))?
+        } else {
+            None
+        };
         let local_offers_rtcp_mux = self.config.rtcp_mux_policy
             == crate::config::RtcpMuxPolicy::Require
-            && self.config.sdp_compatibility != crate::config::SdpCompatibilityMode::LegacySip;
+            && self.config.effective_sdp_compatibility()
+                != crate::config::SdpCompatibilityMode::LegacySip;
 
         if mode != TransportMode::Rtp {
+            self.config.candidate_type_preferences.validate()?;
             self.ice_transport
                 .start_gathering()
                 .map_err(|err| RtcError::InvalidState(format!("ICE gathering failed: {err}")))?;
@@ -4009,18 +5187,42 @@ impl PeerConnectionInner {
                 let mut rx = self.ice_transport.subscribe_candidates();
                 let start = tokio::time::Instant::now();
                 let timeout_dur = tokio::time::Duration::from_millis(500);
+                let mut stop_gathering = std::pin::pin!(stop_gathering);
 
                 while candidates.is_empty() && start.elapsed() < timeout_dur {
-                    let _ = tokio::time::timeout(timeout_dur - start.elapsed(), rx.recv()).await;
+                    tokio::select! {
+                        biased;
+                        _ = &mut stop_gathering => break,
+                        _ = tokio::time::timeout(timeout_dur - start.elapsed(), rx.recv()) => {}
+                    }
                     candidates = self.ice_transport.local_candidates();
                 }
             }
-        }
-
-        let ice_params = self.ice_transport.local_parameters();
-        let ice_username = ice_params.username_fragment.clone();
-        let ice_password = ice_params.password.clone();
-        let candidate_lines: Vec<String> = self
+        } else if mode == TransportMode::WebRtc
+            && sdp_type == SdpType::Answer
+            && !self.remote_supports_trickle()
+        {
+            // A peer that did not advertise `a=ice-options:trickle` only
+            // learns our candidates from the answer, so finish gathering first.
+            let mut rx = self.ice_transport.subscribe_gathering_state();
+            let mut stop_gathering = std::pin::pin!(stop_gathering);
+            while *rx.borrow_and_update() != IceGathererState::Complete {
+                tokio::select! {
+                    biased;
+                    _ = &mut stop_gathering => break,
+                    changed = rx.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        let ice_params = self.ice_transport.local_parameters();
+        let ice_username = ice_params.username_fragment.clone();
+        let ice_password = ice_params.password.clone();
+        let candidate_lines: Vec<String> = self
             .ice_transport
             .local_candidates()
             .iter()
@@ -4031,11 +5233,7 @@ impl PeerConnectionInner {
             IceGathererState::Complete
         );
         let mut desc = SessionDescription::new(sdp_type);
-        desc.session.origin = default_origin();
-        if let Some(ext_ip) = &self.config.external_ip {
-            desc.session.origin.unicast_address = ext_ip.clone();
-        }
-        desc.session.origin.session_version += 1;
+        desc.session.origin = self.next_local_origin();
         if !desc
             .session
             .attributes
@@ -4162,7 +5360,14 @@ impl PeerConnectionInner {
                     };
                     if ice_transport.local_candidates().is_empty() {
                         ice_transport
-                            .setup_direct_rtp_offer_with_rtcp(needs_rtcp)
+                            .setup_direct_rtp_offer_with_rtcp(
+                                needs_rtcp,
+                                if will_bundle {
+                                    bundle_port
+                                } else {
+                                    self.config.fixed_media_port(transceiver.kind())
+                                },
+                            )
                             .await
                             .map_err(|err| {
                                 RtcError::Internal(format!("RTP socket bind failed: {err}"))
@@ -4230,6 +5435,25 @@ impl PeerConnectionInner {
             }
 
             self.populate_media_capabilities(&mut section, transceiver.kind(), sdp_type);
+            // Simulcast receive tracks follow the layers the answer accepts.
+            if sdp_type == SdpType::Answer
+                && let Some(layers) = section.simulcast_layers()
+                && let Some(receiver) = transceiver.receiver.lock().clone()
+            {
+                for layer in layers.recv {
+                    let _ = receiver.add_simulcast_track(layer.rid);
+                }
+            }
+            if let Some(content) = transceiver.content() {
+                section
+                    .attributes
+                    .push(Attribute::new("content", Some(content)));
+            }
+            if let Some(label) = transceiver.label() {
+                section
+                    .attributes
+                    .push(Attribute::new("label", Some(label)));
+            }
             if sdp_type == SdpType::Answer && !remote_offered_rtcp_mux {
                 section.attributes.retain(|attr| attr.key != "rtcp-mux");
             }
@@ -4280,6 +5504,27 @@ impl PeerConnectionInner {
                 None
             };
 
+            // Advertise a FlexFEC repair SSRC alongside the media SSRC; the sender
+            // only starts emitting repair packets once both sides negotiated it.
+            let sender_flexfec_ssrc = if direction.sends()
+                && transceiver.kind() == MediaKind::Video
+                && crate::flexfec::flexfec_payload_type(&section.attributes).is_some()
+            {
+                let fec_ssrc = {
+                    let mut slot = transceiver.sender_flexfec_ssrc.lock();
+                    if slot.is_none() {
+                        *slot = Some(self.ssrc_generator.fetch_add(1, Ordering::Relaxed));
+                    }
+                    *slot
+                };
+                if let Some(sender) = transceiver.sender.lock().as_ref() {
+                    transceiver.apply_flexfec(sender);
+                }
+                fec_ssrc
+            } else {
+                None
+            };
+
             if let Some(sender) = sender_info {
                 Self::attach_sender_attributes(
                     &mut section,
@@ -4289,6 +5534,7 @@ impl PeerConnectionInner {
                     sender.track_id(),
                     &mode,
                     sender_rtx_ssrc,
+                    sender_flexfec_ssrc,
                 );
             } else if direction.sends()
                 && let Some(ssrc) = *transceiver.sender_ssrc.lock()
@@ -4316,6 +5562,7 @@ impl PeerConnectionInner {
                     &track_id,
                     &mode,
                     sender_rtx_ssrc,
+                    sender_flexfec_ssrc,
                 );
             }
 
@@ -4341,21 +5588,35 @@ impl PeerConnectionInner {
                     .push(Attribute::new("crypto", Some(crypto_val)));
             }
 
+            if remote_rejected_mids.contains(&section.mid) {
+                section.port = 0;
+            }
+
             desc.media_sections.push(section);
         }
 
         if !desc.media_sections.is_empty() {
             if will_bundle {
-                let mids: Vec<String> = desc.media_sections.iter().map(|m| m.mid.clone()).collect();
-                let value = format!("BUNDLE {}", mids.join(" "));
-                desc.session
-                    .attributes
-                    .push(Attribute::new("group", Some(value)));
+                let mids: Vec<String> = desc
+                    .media_sections
+                    .iter()
+                    .filter(|m| m.port != 0)
+                    .filter(|m| sdp_type != SdpType::Answer || remote_bundled_mids.contains(&m.mid))
+                    .map(|m| m.mid.clone())
+                    .collect();
+                if !mids.is_empty() {
+                    let value = format!("BUNDLE {}", mids.join(" "));
+                    desc.session
+                        .attributes
+                        .push(Attribute::new("group", Some(value)));
+                }
             }
 
             // In LegacySip mode, omit a=mid entirely: legacy SIP endpoints confuse
             // a=mid without a matching a=group:BUNDLE.
-            if self.config.sdp_compatibility == crate::config::SdpCompatibilityMode::LegacySip {
+            if self.config.effective_sdp_compatibility()
+                == crate::config::SdpCompatibilityMode::LegacySip
+            {
                 for section in &mut desc.media_sections {
                     section.mid = String::new();
                 }
@@ -4384,6 +5645,7 @@ impl PeerConnectionInner {
         track_id: &str,
         mode: &TransportMode,
         rtx_ssrc: Option<u32>,
+        fec_ssrc: Option<u32>,
     ) {
         if *mode == TransportMode::WebRtc {
             section.attributes.push(Attribute::new(
@@ -4398,6 +5660,12 @@ impl PeerConnectionInner {
                 Some(format!("FID {} {}", ssrc, rtx)),
             ));
         }
+        if let Some(fec) = fec_ssrc {
+            section.attributes.push(Attribute::new(
+                "ssrc-group",
+                Some(format!("FEC-FR {} {}", ssrc, fec)),
+            ));
+        }
 
         section.attributes.push(Attribute::new(
             "ssrc",
@@ -4411,15 +5679,15 @@ impl PeerConnectionInner {
             ));
         }
 
-        if let Some(rtx) = rtx_ssrc {
+        for extra in rtx_ssrc.into_iter().chain(fec_ssrc) {
             section.attributes.push(Attribute::new(
                 "ssrc",
-                Some(format!("{} cname:{}", rtx, cname)),
+                Some(format!("{} cname:{}", extra, cname)),
             ));
             if *mode == TransportMode::WebRtc {
                 section.attributes.push(Attribute::new(
                     "ssrc",
-                    Some(format!("{} msid:{} {}", rtx, stream_id, track_id)),
+                    Some(format!("{} msid:{} {}", extra, stream_id, track_id)),
                 ));
             }
         }
@@ -4458,7 +5726,7 @@ impl PeerConnectionInner {
         sdp_type: SdpType,
     ) {
         section.apply_config(&self.config);
-        if let Some(caps) = self.reinvite_answer_audio_capabilities(&section.mid, kind, sdp_type) {
+        if let Some(caps) = self.answer_audio_capabilities(&section.mid, kind, sdp_type) {
             Self::apply_audio_capabilities(section, &caps);
         }
 
@@ -4467,11 +5735,23 @@ impl PeerConnectionInner {
         if sdp_type == SdpType::Answer && kind == MediaKind::Video {
             strip_rtx_from_section(section);
             self.merge_remote_rtx_into_answer(section);
+            // FlexFEC costs bandwidth, so only accept it when local config opts in.
+            strip_flexfec_from_section(section);
+            if self.flexfec_enabled() {
+                self.merge_remote_flexfec_into_answer(section);
+            }
+        }
+
+        if self.config.profile == MediaProfile::SipGateway
+            && matches!(kind, MediaKind::Audio | MediaKind::Video)
+        {
+            retain_first_media_codec(section);
         }
 
         // Add extmap for Video
         if kind == MediaKind::Video {
-            let (mut rid_id, mut repaired_rid_id) = self.get_remote_video_extmap_ids(&section.mid);
+            let (mut rid_id, mut repaired_rid_id) = self.get_remote_video_extmap_ids(section);
+            let remote_rid_negotiated = rid_id.is_some();
 
             if sdp_type == SdpType::Offer && self.config.transport_mode != TransportMode::Rtp {
                 // If not found in remote (new transceiver), use defaults
@@ -4484,11 +5764,46 @@ impl PeerConnectionInner {
             }
 
             section.add_video_extmaps(rid_id, repaired_rid_id);
+
+            // Accept every layer the remote sends; the receiver demuxes them
+            // by RID. Sending simulcast is not supported, so offered receive
+            // layers are declined.
+            if sdp_type == SdpType::Answer
+                && remote_rid_negotiated
+                && let Some(accepted) = self.accepted_simulcast_layers(section)
+            {
+                section.attributes.extend(accepted.to_attributes());
+            }
+
+            // Coordination of Video Orientation (CVO) so rotated mobile video can be rendered upright.
+            let mut cvo_id = self.get_remote_extmap_id(section, crate::sdp::VIDEO_ORIENTATION_URI);
+            if sdp_type == SdpType::Offer
+                && cvo_id.is_none()
+                && self.config.transport_mode != TransportMode::Rtp
+            {
+                cvo_id = self.allocate_extmap_id(section, crate::sdp::VIDEO_ORIENTATION_URI, 5);
+            }
+            if let Some(id) = cvo_id {
+                section.attributes.push(crate::sdp::Attribute::new(
+                    "extmap",
+                    Some(format!("{} {}", id, crate::sdp::VIDEO_ORIENTATION_URI)),
+                ));
+            }
+
+            // Frame marking is experimental: only accepted when the remote offers it.
+            if let Some(id) = self.get_remote_extmap_id(section, crate::sdp::FRAME_MARKING_URI) {
+                section.attributes.push(crate::sdp::Attribute::new(
+                    "extmap",
+                    Some(format!("{} {}", id, crate::sdp::FRAME_MARKING_URI)),
+                ));
+            }
+
+            self.negotiate_transport_cc(section, sdp_type);
         }
 
         // Add abs-send-time extmap
         let mut abs_send_time_id =
-            self.get_remote_extmap_id(&section.mid, crate::sdp::ABS_SEND_TIME_URI);
+            self.get_remote_extmap_id(section, crate::sdp::ABS_SEND_TIME_URI);
         if sdp_type == SdpType::Offer
             && abs_send_time_id.is_none()
             && self.config.transport_mode != TransportMode::Rtp
@@ -4505,8 +5820,10 @@ impl PeerConnectionInner {
         // Add sdes:mid extmap for BUNDLE support (RFC 8843).  Answers echo the
         // remote ID when offered; WebRTC offers use a default ID so bundled
         // audio/video can still be demuxed when payload types overlap.
-        if self.config.sdp_compatibility != crate::config::SdpCompatibilityMode::LegacySip {
-            let mut sdes_mid_id = self.get_remote_extmap_id(&section.mid, crate::sdp::SDES_MID_URI);
+        if self.config.effective_sdp_compatibility()
+            != crate::config::SdpCompatibilityMode::LegacySip
+        {
+            let mut sdes_mid_id = self.get_remote_extmap_id(section, crate::sdp::SDES_MID_URI);
             if sdp_type == SdpType::Offer
                 && sdes_mid_id.is_none()
                 && self.config.transport_mode != TransportMode::Rtp
@@ -4526,7 +5843,11 @@ impl PeerConnectionInner {
         // attributes, otherwise SIP/SDES peers (e.g. Twilio) reject the SDP.
         if self.config.transport_mode == TransportMode::WebRtc {
             let setup_value = match sdp_type {
-                SdpType::Offer => "actpass",
+                SdpType::Offer => match self.config.effective_dtls_role() {
+                    Some(DtlsRole::Client) => "active",
+                    Some(DtlsRole::Server) => "passive",
+                    None => "actpass",
+                },
                 SdpType::Answer => {
                     let role = *self.dtls_role.borrow();
                     match role {
@@ -4541,28 +5862,149 @@ impl PeerConnectionInner {
         }
     }
 
+    /// Offer transport-cc when the local codecs ask for it; answer it only
+    /// when the remote offered both the extension and the feedback, and drop
+    /// the `transport-cc` rtcp-fb lines otherwise.
+    fn negotiate_transport_cc(&self, section: &mut MediaSection, sdp_type: SdpType) {
+        if !section.has_rtcp_fb("transport-cc") {
+            return;
+        }
+        let mut transport_cc_id = self.get_remote_extmap_id(section, crate::sdp::TRANSPORT_CC_URI);
+        if sdp_type == SdpType::Offer
+            && transport_cc_id.is_none()
+            && self.config.transport_mode != TransportMode::Rtp
+        {
+            transport_cc_id = self.allocate_extmap_id(section, crate::sdp::TRANSPORT_CC_URI, 6);
+        }
+        if sdp_type == SdpType::Answer && !self.remote_has_rtcp_fb(section, "transport-cc") {
+            transport_cc_id = None;
+        }
+        match transport_cc_id {
+            Some(id) => section.attributes.push(crate::sdp::Attribute::new(
+                "extmap",
+                Some(format!("{} {}", id, crate::sdp::TRANSPORT_CC_URI)),
+            )),
+            None if sdp_type == SdpType::Answer => section.attributes.retain(|attr| {
+                attr.key != "rtcp-fb"
+                    || !attr
+                        .value
+                        .as_deref()
+                        .is_some_and(|v| v.ends_with(" transport-cc"))
+            }),
+            None => {}
+        }
+    }
+
     fn audio_capability_matches(local: &AudioCapability, remote: &AudioCapability) -> bool {
+        // Opus always signals `/2` in rtpmap; mono vs stereo is an fmtp
+        // preference (RFC 7587 §7), so the channel count is not compared.
         local.codec_name.eq_ignore_ascii_case(&remote.codec_name)
             && local.clock_rate == remote.clock_rate
-            && local.channels == remote.channels
+            && (local.channels == remote.channels || Self::is_opus(local))
+    }
+
+    fn is_opus(cap: &AudioCapability) -> bool {
+        cap.codec_name.eq_ignore_ascii_case("opus")
+    }
+
+    /// Answer Opus fmtp parameters from the local ones and the offer's (RFC
+    /// 7587 §6.1). `stereo` and `sprop-stereo` describe our own side and are
+    /// kept as configured; `useinbandfec`, `usedtx` and `cbr` stay enabled
+    /// only when both sides enable them; the receive limits `maxplaybackrate`
+    /// and `maxaveragebitrate` take the lower value and `minptime` the higher.
+    /// Parameters we do not configure are never added. Returns `Err` naming
+    /// the parameter when either side carries a value outside its defined
+    /// range, which rejects the codec.
+    fn negotiate_opus_fmtp(
+        local: &AudioCapability,
+        remote: Option<&str>,
+    ) -> Result<Option<String>, String> {
+        const FLAGS: [&str; 5] = ["stereo", "sprop-stereo", "useinbandfec", "usedtx", "cbr"];
+        const SHARED_FLAGS: [&str; 3] = ["useinbandfec", "usedtx", "cbr"];
+        const RATE_RANGES: [(&str, u32, u32); 3] = [
+            ("maxplaybackrate", 8000, 48000),
+            ("sprop-maxcapturerate", 8000, 48000),
+            ("maxaveragebitrate", 6000, 510000),
+        ];
+        const RECEIVE_LIMITS: [&str; 2] = ["maxplaybackrate", "maxaveragebitrate"];
+
+        let validate = |params: &HashMap<String, String>| -> Result<(), String> {
+            for flag in FLAGS {
+                if params.get(flag).is_some_and(|v| v != "0" && v != "1") {
+                    return Err(flag.to_string());
+                }
+            }
+            for (key, min, max) in RATE_RANGES {
+                if let Some(value) = params.get(key)
+                    && !value.parse::<u32>().is_ok_and(|v| (min..=max).contains(&v))
+                {
+                    return Err(key.to_string());
+                }
+            }
+            Ok(())
+        };
+
+        // Keep the local parameter order so the answer reads like our offer would.
+        let mut local_params: Vec<(String, String)> = local
+            .fmtp
+            .as_deref()
+            .unwrap_or_default()
+            .split(';')
+            .filter_map(|param| param.trim().split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        let local_map: HashMap<String, String> = local_params.iter().cloned().collect();
+        let remote_map = remote.map(crate::sdp::parse_fmtp).unwrap_or_default();
+        validate(&local_map)?;
+        validate(&remote_map)?;
+
+        // A capability declared with a single channel only ever accepts mono.
+        if local.channels == 1 && !local_map.contains_key("stereo") {
+            local_params.push(("stereo".to_string(), "0".to_string()));
+        }
+
+        for (key, value) in local_params.iter_mut() {
+            if key == "stereo" && local.channels == 1 {
+                *value = "0".to_string();
+            } else if SHARED_FLAGS.contains(&key.as_str()) {
+                let both =
+                    value == "1" && remote_map.get(key.as_str()).map(String::as_str) == Some("1");
+                *value = if both { "1" } else { "0" }.to_string();
+            } else if key == "minptime" {
+                if let (Ok(ours), Some(Ok(theirs))) = (
+                    value.parse::<u32>(),
+                    remote_map.get(key.as_str()).map(|v| v.parse::<u32>()),
+                ) {
+                    *value = ours.max(theirs).to_string();
+                }
+            } else if RECEIVE_LIMITS.contains(&key.as_str())
+                && let Some(theirs) = remote_map.get(key.as_str())
+                && let (Ok(ours), Ok(theirs)) = (value.parse::<u32>(), theirs.parse::<u32>())
+            {
+                *value = ours.min(theirs).to_string();
+            }
+        }
+
+        if local_params.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            local_params
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join(";"),
+        ))
     }
 
     fn configured_audio_capabilities(config: &RtcConfiguration) -> Vec<AudioCapability> {
-        let default_caps = AudioCapability::default();
-        config
-            .media_capabilities
-            .as_ref()
-            .map(|caps| {
-                if caps.audio.is_empty() {
-                    vec![default_caps.clone()]
-                } else {
-                    caps.audio.clone()
-                }
-            })
-            .unwrap_or_else(|| vec![default_caps])
+        config.media_capabilities.as_ref().map_or_else(
+            || vec![AudioCapability::default()],
+            |caps| caps.audio_or_default(),
+        )
     }
 
-    fn reinvite_answer_audio_capabilities(
+    fn answer_audio_capabilities(
         &self,
         mid: &str,
         kind: MediaKind,
@@ -4572,10 +6014,6 @@ impl PeerConnectionInner {
             return None;
         }
 
-        if self.local_description.lock().is_none() {
-            return None;
-        }
-
         let remote = self.remote_description.lock();
         let remote_desc = remote.as_ref()?;
         let remote_section = remote_desc
@@ -4594,70 +6032,89 @@ impl PeerConnectionInner {
         if caps.is_empty() { None } else { Some(caps) }
     }
 
+    /// Whether an offered audio format is self-consistent: telephone-event
+    /// and CN must run at the clock rate of an offered media codec (RFC 4733
+    /// §2.1, RFC 3389 §4), and the telephone-event fmtp must be an event list.
+    fn audio_capability_consistent(cap: &AudioCapability, offered: &[AudioCapability]) -> bool {
+        let is_telephone_event = cap.codec_name.eq_ignore_ascii_case("telephone-event");
+        if !is_telephone_event && !cap.codec_name.eq_ignore_ascii_case("CN") {
+            return true;
+        }
+        let rate_matches = offered.iter().any(|other| {
+            !other.codec_name.eq_ignore_ascii_case("telephone-event")
+                && !other.codec_name.eq_ignore_ascii_case("CN")
+                && other.clock_rate == cap.clock_rate
+        });
+        let fmtp_valid = !is_telephone_event
+            || cap.fmtp.as_deref().is_none_or(|events| {
+                events.split(',').all(|range| {
+                    let (start, end) = range.trim().split_once('-').unwrap_or((range, range));
+                    matches!(
+                        (start.trim().parse::<u8>(), end.trim().parse::<u8>()),
+                        (Ok(start), Ok(end)) if start <= end
+                    )
+                })
+            });
+        rate_matches && fmtp_valid
+    }
+
     fn derive_answer_audio_capabilities(
         remote_section: &MediaSection,
         local_caps: &[AudioCapability],
     ) -> Vec<AudioCapability> {
-        remote_section
-            .to_audio_capabilities()
-            .into_iter()
+        let offered = remote_section.to_audio_capabilities();
+        offered
+            .iter()
+            .filter(|remote_cap| {
+                let consistent = Self::audio_capability_consistent(remote_cap, &offered);
+                if !consistent {
+                    warn!(
+                        "Excluding offered {}/{} (pt {}): clock rate or fmtp does not match the offered audio codecs",
+                        remote_cap.codec_name, remote_cap.clock_rate, remote_cap.payload_type
+                    );
+                }
+                consistent
+            })
+            .cloned()
             .filter_map(|remote_cap| {
-                local_caps
+                let local_cap = local_caps
                     .iter()
-                    .find(|local_cap| Self::audio_capability_matches(local_cap, &remote_cap))
-                    .map(|local_cap| {
-                        let mut cap = local_cap.clone();
-                        cap.payload_type = remote_cap.payload_type;
-                        cap.codec_name = remote_cap.codec_name.clone();
-                        cap.clock_rate = remote_cap.clock_rate;
-                        cap.channels = remote_cap.channels;
-                        if remote_cap
-                            .codec_name
-                            .eq_ignore_ascii_case("telephone-event")
-                        {
-                            cap.fmtp = remote_cap.fmtp.clone().or(cap.fmtp);
+                    .find(|local_cap| Self::audio_capability_matches(local_cap, &remote_cap))?;
+                let mut cap = local_cap.clone();
+                cap.payload_type = remote_cap.payload_type;
+                cap.codec_name = remote_cap.codec_name.clone();
+                cap.clock_rate = remote_cap.clock_rate;
+                cap.channels = remote_cap.channels;
+                if remote_cap
+                    .codec_name
+                    .eq_ignore_ascii_case("telephone-event")
+                {
+                    cap.fmtp = remote_cap.fmtp.clone().or(cap.fmtp);
+                } else if Self::is_opus(&remote_cap) {
+                    match Self::negotiate_opus_fmtp(local_cap, remote_cap.fmtp.as_deref()) {
+                        Ok(fmtp) => cap.fmtp = fmtp,
+                        Err(param) => {
+                            warn!(
+                                "Excluding offered {}/{} (pt {}): incompatible fmtp parameter {}",
+                                remote_cap.codec_name,
+                                remote_cap.clock_rate,
+                                remote_cap.payload_type,
+                                param
+                            );
+                            return None;
                         }
-                        cap
-                    })
+                    }
+                }
+                Some(cap)
             })
             .collect()
     }
 
     fn apply_audio_capabilities(section: &mut MediaSection, caps: &[AudioCapability]) {
-        section.formats = caps.iter().map(|c| c.payload_type.to_string()).collect();
         section
             .attributes
             .retain(|attr| attr.key != "rtpmap" && attr.key != "fmtp" && attr.key != "rtcp-fb");
-
-        for audio in caps {
-            let rtpmap_value = if audio.channels == 1 {
-                format!(
-                    "{} {}/{}",
-                    audio.payload_type, audio.codec_name, audio.clock_rate
-                )
-            } else {
-                format!(
-                    "{} {}/{}/{}",
-                    audio.payload_type, audio.codec_name, audio.clock_rate, audio.channels
-                )
-            };
-
-            section
-                .attributes
-                .push(Attribute::new("rtpmap", Some(rtpmap_value)));
-            if let Some(fmtp) = &audio.fmtp {
-                section.attributes.push(Attribute::new(
-                    "fmtp",
-                    Some(format!("{} {}", audio.payload_type, fmtp)),
-                ));
-            }
-            for fb in &audio.rtcp_fbs {
-                section.attributes.push(Attribute::new(
-                    "rtcp-fb",
-                    Some(format!("{} {}", audio.payload_type, fb)),
-                ));
-            }
-        }
+        section.push_audio_capabilities(caps);
     }
 
     /// Echo remote-offered RTX payload types into a local answer when the
@@ -4711,33 +6168,167 @@ impl PeerConnectionInner {
         }
     }
 
-    fn get_remote_video_extmap_ids(&self, mid: &str) -> (Option<String>, Option<String>) {
+    fn merge_remote_flexfec_into_answer(&self, section: &mut MediaSection) {
+        let remote = self.remote_description.lock();
+        let fec_pt = remote.as_ref().and_then(|desc| {
+            desc.media_sections
+                .iter()
+                .find(|s| s.mid == section.mid)
+                .and_then(|s| crate::flexfec::flexfec_payload_type(&s.attributes))
+        });
+        if let Some(fec_pt) = fec_pt {
+            crate::flexfec::append_flexfec_to_section(
+                &mut section.formats,
+                &mut section.attributes,
+                fec_pt,
+            );
+        }
+    }
+
+    /// Apply the receive-side configuration to a receiver: the unknown
+    /// payload type policy and the jitter buffer, if any.
+    fn configure_receiver(&self, builder: RtpReceiverBuilder) -> RtpReceiverBuilder {
+        let builder = builder.unknown_payload_type_policy(self.config.unknown_payload_type_policy);
+        match self.config.jitter_buffer_ms {
+            0 => builder,
+            ms => builder.jitter_buffer(std::time::Duration::from_millis(ms.into())),
+        }
+    }
+
+    fn flexfec_enabled(&self) -> bool {
+        self.config
+            .media_capabilities
+            .as_ref()
+            .is_some_and(|caps| caps.flexfec_enabled())
+    }
+
+    /// The remote's send layers for `section`, as our receive layers. A
+    /// `pt=` restriction keeps only answered payload types; a layer left
+    /// with none is dropped (RFC 8851 §4).
+    fn accepted_simulcast_layers(
+        &self,
+        section: &MediaSection,
+    ) -> Option<crate::sdp::SimulcastLayers> {
+        let offered = self
+            .with_remote_section(section, |remote| remote.simulcast_layers())
+            .flatten()?;
+        let recv: Vec<_> = offered
+            .send
+            .into_iter()
+            .filter_map(|mut layer| {
+                for (key, value) in &mut layer.restrictions {
+                    if key == "pt" {
+                        let pts: Vec<&str> = value
+                            .split(',')
+                            .filter(|pt| section.formats.iter().any(|f| f == pt))
+                            .collect();
+                        if pts.is_empty() {
+                            return None;
+                        }
+                        *value = pts.join(",");
+                    }
+                }
+                Some(layer)
+            })
+            .collect();
+        (!recv.is_empty()).then(|| crate::sdp::SimulcastLayers {
+            send: Vec::new(),
+            recv,
+        })
+    }
+
+    fn get_remote_video_extmap_ids(
+        &self,
+        section: &MediaSection,
+    ) -> (Option<String>, Option<String>) {
         let rid_id =
-            self.get_remote_extmap_id(mid, "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id");
+            self.get_remote_extmap_id(section, "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id");
         let repaired_rid_id = self.get_remote_extmap_id(
-            mid,
+            section,
             "urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id",
         );
         (rid_id, repaired_rid_id)
     }
 
-    fn get_remote_extmap_id(&self, mid: &str, uri: &str) -> Option<String> {
-        let remote = self.remote_description.lock();
-        if let Some(desc) = &*remote {
-            let remote_section = desc.media_sections.iter().find(|s| s.mid == mid)?;
-            for attr in &remote_section.attributes {
-                if attr.key != "extmap" {
-                    continue;
-                }
-                let val = attr.value.as_ref()?;
-                if val.contains(uri)
-                    && let Some(id_str) = val.split_whitespace().next()
-                {
-                    return Some(id_str.to_string());
+    /// Id the remote assigned to `uri` on the section matching `section`.
+    /// Answers must echo these ids verbatim (RFC 8285 §6), so the lookup falls
+    /// back to a mid-less remote section of the same kind for legacy SIP offers.
+    /// Id for a header extension we offer that the remote has not mapped on
+    /// this m-line. BUNDLE shares one id space (RFC 8843 §9.2), so an id the
+    /// remote gave `uri` on another m-line is reused, and ids it gave other
+    /// extensions anywhere are avoided, as are this m-line's ids and the
+    /// abs-send-time and sdes:mid defaults added after it. `preferred` is
+    /// taken when free, otherwise the lowest free one-byte id; `None` when
+    /// none is left.
+    fn allocate_extmap_id(
+        &self,
+        section: &MediaSection,
+        uri: &str,
+        preferred: u8,
+    ) -> Option<String> {
+        const LATER_DEFAULT_IDS: [u8; 2] = [3, 4];
+        let extmaps = |section: &MediaSection| -> Vec<(u8, String)> {
+            section
+                .attributes
+                .iter()
+                .filter(|attr| attr.key == "extmap")
+                .filter_map(|attr| {
+                    let mut parts = attr.value.as_deref()?.split_whitespace();
+                    let (id, _) = PeerConnection::parse_extmap_id(parts.next()?)?;
+                    Some((id, parts.next()?.to_string()))
+                })
+                .collect()
+        };
+        let mut taken: HashSet<u8> = extmaps(section).into_iter().map(|(id, _)| id).collect();
+        let mut bundled = None;
+        if let Some(remote) = self.remote_description.lock().as_ref() {
+            for (id, remote_uri) in remote.media_sections.iter().flat_map(extmaps) {
+                if remote_uri == uri {
+                    bundled.get_or_insert(id);
+                } else {
+                    taken.insert(id);
                 }
             }
         }
-        None
+        if let Some(id) = bundled.filter(|id| !taken.contains(id)) {
+            return Some(id.to_string());
+        }
+        taken.extend(LATER_DEFAULT_IDS);
+        std::iter::once(preferred)
+            .chain(1..=14)
+            .find(|id| !taken.contains(id))
+            .map(|id| id.to_string())
+    }
+
+    fn get_remote_extmap_id(&self, section: &MediaSection, uri: &str) -> Option<String> {
+        self.with_remote_section(section, |remote| remote.get_extmap_id(uri))?
+            .map(|id| id.to_string())
+    }
+
+    fn remote_has_rtcp_fb(&self, section: &MediaSection, fb: &str) -> bool {
+        self.with_remote_section(section, |remote| remote.has_rtcp_fb(fb))
+            .unwrap_or(false)
+    }
+
+    /// Run `f` on the remote m-line matching `section` (by mid, or by kind
+    /// when the remote has no mids).
+    fn with_remote_section<R>(
+        &self,
+        section: &MediaSection,
+        f: impl FnOnce(&MediaSection) -> R,
+    ) -> Option<R> {
+        let remote = self.remote_description.lock();
+        let desc = remote.as_ref()?;
+        let remote_section = desc
+            .media_sections
+            .iter()
+            .find(|s| s.mid == section.mid)
+            .or_else(|| {
+                desc.media_sections
+                    .iter()
+                    .find(|s| s.mid.is_empty() && s.kind == section.kind)
+            })?;
+        Some(f(remote_section))
     }
 
     fn close_with_reason(&self, reason: DisconnectReason) {
@@ -4820,21 +6411,18 @@ impl PeerConnectionInner {
             }
 
             // Send RTCP BYE
-            let transceivers = self.transceivers.lock();
-            let mut ssrcs = Vec::new();
-            for t in transceivers.iter() {
-                if let Some(sender) = t.sender() {
-                    ssrcs.push(sender.ssrc());
-                }
-            }
-            if !ssrcs.is_empty() {
-                let bye = crate::rtp::RtcpPacket::Goodbye(crate::rtp::Goodbye {
-                    sources: ssrcs,
-                    reason: Some("PeerConnection closed".to_string()),
-                });
+            let senders: Vec<_> = self
+                .transceivers
+                .lock()
+                .iter()
+                .filter_map(|t| t.sender())
+                .collect();
+            if let Some(compound) =
+                RtpSender::build_bye_compound(&senders, "PeerConnection closed", SystemTime::now())
+            {
                 let transport_clone = transport.clone();
                 tokio::spawn(async move {
-                    let _ = transport_clone.send_rtcp(&[bye]).await;
+                    let _ = transport_clone.send_rtcp(&compound).await;
                 });
             }
         }
@@ -4864,6 +6452,7 @@ impl PeerConnectionInner {
             dtls.close();
         }
 
+        self.ice_transport.cancel_gathering();
         self.ice_transport.stop();
         let extra_ice = self
             .rtp_media_ice_transports
@@ -4872,6 +6461,7 @@ impl PeerConnectionInner {
             .map(|(_, transport)| transport)
             .collect::<Vec<_>>();
         for transport in extra_ice {
+            transport.cancel_gathering();
             transport.stop();
         }
     }
@@ -4926,6 +6516,10 @@ pub enum DisconnectReason {
     DtlsFailed,
     /// DTLS transport closed
     DtlsClosed,
+    /// Remote DTLS peer sent a fatal alert (e.g. `fatal bad_certificate (42)`)
+    DtlsAlert(String),
+    /// Remote DTLS certificate did not match the SDP fingerprint
+    FingerprintMismatch(String),
     /// SCTP association closed due to heartbeat timeout
     /// (peer not responding to heartbeats)
     SctpHeartbeatTimeout,
@@ -4951,6 +6545,10 @@ impl std::fmt::Display for DisconnectReason {
             DisconnectReason::IceDisconnected => write!(f, "ICE disconnected"),
             DisconnectReason::DtlsFailed => write!(f, "DTLS failed"),
             DisconnectReason::DtlsClosed => write!(f, "DTLS closed"),
+            DisconnectReason::DtlsAlert(alert) => write!(f, "DTLS alert: {}", alert),
+            DisconnectReason::FingerprintMismatch(reason) => {
+                write!(f, "DTLS fingerprint mismatch: {}", reason)
+            }
             DisconnectReason::SctpHeartbeatTimeout => {
                 write!(f, "SCTP heartbeat timeout (peer unresponsive)")
             }
@@ -5018,6 +6616,13 @@ impl TransceiverDirection {
             TransceiverDirection::SendRecv | TransceiverDirection::SendOnly
         )
     }
+
+    pub fn receives(self) -> bool {
+        matches!(
+            self,
+            TransceiverDirection::SendRecv | TransceiverDirection::RecvOnly
+        )
+    }
 }
 
 impl From<TransceiverDirection> for Direction {
@@ -5061,6 +6666,27 @@ impl Default for RtpCodecParameters {
     }
 }
 
+impl RtpCodecParameters {
+    /// Convert a wall-clock duration into RTP timestamp units at this codec's clock rate,
+    /// rounded to the nearest tick.
+    ///
+    /// The result wraps like RTP timestamps do, so it can be added to a previous
+    /// timestamp with `wrapping_add`.
+    pub fn ts_from_duration(&self, duration: std::time::Duration) -> u32 {
+        ((duration.as_nanos() * self.clock_rate as u128 + 500_000_000) / 1_000_000_000) as u32
+    }
+
+    /// Convert an RTP timestamp delta into a wall-clock duration at this codec's clock rate.
+    ///
+    /// Returns `Duration::ZERO` when the clock rate is unset.
+    pub fn duration_from_ts_delta(&self, delta: u32) -> std::time::Duration {
+        if self.clock_rate == 0 {
+            return std::time::Duration::ZERO;
+        }
+        std::time::Duration::from_nanos(delta as u64 * 1_000_000_000 / self.clock_rate as u64)
+    }
+}
+
 pub struct RtpTransceiver {
     id: u64,
     kind: MediaKind,
@@ -5073,13 +6699,36 @@ pub struct RtpTransceiver {
     sender_ssrc: Mutex<Option<u32>>,
     sender_rtx_ssrc: Mutex<Option<u32>>,
     sender_rtx_payload_type: Mutex<Option<u8>>,
+    sender_flexfec_ssrc: Mutex<Option<u32>>,
+    /// FlexFEC payload type once both descriptions carry `flexfec-03`.
+    flexfec_payload_type: Mutex<Option<u8>>,
     sender_stream_id: Mutex<Option<String>>,
     sender_track_id: Mutex<Option<String>>,
     payload_map: Arc<RwLock<HashMap<u8, RtpCodecParameters>>>,
     extmap: Arc<RwLock<HashMap<u8, String>>>,
+    /// Direction suffix of each `a=extmap:<id>/<direction>` line. IDs without
+    /// a suffix are absent and apply to both directions.
+    extmap_directions: RwLock<HashMap<u8, TransceiverDirection>>,
     /// Deferred sdes:mid configuration: stored here when update_extmap() is called
     /// but the sender has not been created yet.  Applied in set_sender().
     pending_sdes_mid: Mutex<Option<(u8, Arc<str>)>>,
+    /// Negotiated video limits, kept so a sender attached later inherits them.
+    video_limits: Mutex<VideoSendLimits>,
+    /// Negotiated primary codec name, kept so a receiver attached later
+    /// inherits it.
+    codec_name: Mutex<Option<String>>,
+    /// Negotiated encoding name per media payload type, likewise handed to
+    /// the receiver to pick its depacketizer.
+    payload_codecs: Mutex<HashMap<u8, String>>,
+    /// Local `a=content` / `a=label` values written into our descriptions.
+    content: Mutex<Option<String>>,
+    label: Mutex<Option<String>>,
+    /// `a=content` / `a=label` values from the remote description.
+    remote_content: Mutex<Option<String>>,
+    remote_label: Mutex<Option<String>>,
+    /// Remote `a=fmtp` parameters per payload type, kept so a sender
+    /// attached later inherits them.
+    remote_fmtp: Mutex<HashMap<u8, String>>,
 }
 
 impl RtpTransceiver {
@@ -5096,11 +6745,22 @@ impl RtpTransceiver {
             sender_ssrc: Mutex::new(None),
             sender_rtx_ssrc: Mutex::new(None),
             sender_rtx_payload_type: Mutex::new(None),
+            sender_flexfec_ssrc: Mutex::new(None),
+            flexfec_payload_type: Mutex::new(None),
             sender_stream_id: Mutex::new(None),
             sender_track_id: Mutex::new(None),
             payload_map: Arc::new(RwLock::new(HashMap::new())),
             extmap: Arc::new(RwLock::new(HashMap::new())),
+            extmap_directions: RwLock::new(HashMap::new()),
             pending_sdes_mid: Mutex::new(None),
+            video_limits: Mutex::new(VideoSendLimits::default()),
+            codec_name: Mutex::new(None),
+            payload_codecs: Mutex::new(HashMap::new()),
+            content: Mutex::new(None),
+            label: Mutex::new(None),
+            remote_content: Mutex::new(None),
+            remote_label: Mutex::new(None),
+            remote_fmtp: Mutex::new(HashMap::new()),
         }
     }
 
@@ -5126,6 +6786,45 @@ impl RtpTransceiver {
         *self.sender_rtx_ssrc.lock()
     }
 
+    pub fn sender_flexfec_ssrc(&self) -> Option<u32> {
+        *self.sender_flexfec_ssrc.lock()
+    }
+
+    /// Whether FlexFEC was negotiated: repair packets are sent for our media
+    /// and incoming repair packets are used to recover lost packets.
+    pub fn flexfec_active(&self) -> bool {
+        self.flexfec_payload_type.lock().is_some()
+    }
+
+    /// Record the FlexFEC outcome of a remote description and hand it to the
+    /// receiver and sender. `enabled` reflects the local configuration.
+    fn update_flexfec(&self, section: &MediaSection, enabled: bool) {
+        let fec_pt = crate::flexfec::flexfec_payload_type(&section.attributes).filter(|_| enabled);
+        *self.flexfec_payload_type.lock() = fec_pt;
+        if let Some(receiver) = self.receiver() {
+            let fec_ssrc =
+                crate::flexfec::parse_fec_fr_group(&section.attributes).map(|(_, fec)| fec);
+            receiver.set_flexfec(fec_pt, fec_ssrc);
+        }
+        if let Some(sender) = self.sender() {
+            self.apply_flexfec(&sender);
+        }
+    }
+
+    fn apply_flexfec(&self, sender: &RtpSender) {
+        let config = match (
+            *self.sender_flexfec_ssrc.lock(),
+            *self.flexfec_payload_type.lock(),
+        ) {
+            (Some(fec_ssrc), Some(fec_payload_type)) => Some(crate::flexfec::FlexFecSenderConfig {
+                fec_ssrc,
+                fec_payload_type,
+            }),
+            _ => None,
+        };
+        sender.set_flexfec(config);
+    }
+
     pub fn sender_stream_id(&self) -> Option<String> {
         self.sender_stream_id.lock().clone()
     }
@@ -5204,6 +6903,7 @@ impl RtpTransceiver {
                     rtx_payload_type: rtx_pt,
                 }));
             }
+            self.apply_flexfec(s);
 
             // Apply any negotiated sdes:mid configuration to replacement senders too.
             let pending_sdes_mid = self.pending_sdes_mid.lock().take();
@@ -5211,33 +6911,92 @@ impl RtpTransceiver {
                 s.set_sdes_mid(id, mid_val);
             } else {
                 let mid_value = self.mid.lock().clone();
-                let sdes_mid_id = self
-                    .extmap
-                    .read()
-                    .iter()
-                    .find(|(_, uri)| uri.as_str() == crate::sdp::SDES_MID_URI)
-                    .map(|(id, _)| *id);
+                let sdes_mid_id = self.extension_id(true, |uri| uri == crate::sdp::SDES_MID_URI);
                 if let (Some(id), Some(mid)) = (sdes_mid_id, mid_value) {
                     s.set_sdes_mid(id, Arc::from(mid.as_str()));
                 }
             }
+            s.set_video_orientation_extension_id(
+                self.extension_id(true, |uri| uri == crate::sdp::VIDEO_ORIENTATION_URI),
+            );
+            s.set_frame_marking_extension_id(
+                self.extension_id(true, |uri| uri == crate::sdp::FRAME_MARKING_URI),
+            );
+            s.set_video_limits(*self.video_limits.lock());
+            s.set_remote_fmtp(self.remote_fmtp.lock().clone());
         }
         *self.sender.lock() = sender;
     }
 
-    /// Set the RTP transport reference. Called by start_dtls when transport is established.
-    pub fn set_rtp_transport(&self, transport: Weak<RtpTransport>) {
-        *self.rtp_transport.lock() = Some(transport);
-    }
+    /// Raw RTP sender on this transceiver's negotiated transport and SSRC, for
+    /// forwarding packets whose sequence numbers and timestamps the application
+    /// controls. Fails until the transport is up or when nothing is sent here.
+    pub fn forward_sender(&self, payload_type: u8) -> RtcResult<RtpForwardSender> {
+        let transport = self
+            .rtp_transport
+            .lock()
+            .as_ref()
+            .and_then(Weak::upgrade)
+            .ok_or_else(|| RtcError::InvalidState("RTP transport is not ready".into()))?;
+        let ssrc = self
+            .sender_ssrc()
+            .ok_or_else(|| RtcError::InvalidState("transceiver has no send SSRC".into()))?;
+        Ok(RtpForwardSender::new(transport, ssrc, payload_type))
+    }
+
+    /// Set the RTP transport reference. Called by start_dtls when transport is established.
+    pub fn set_rtp_transport(&self, transport: Weak<RtpTransport>) {
+        *self.rtp_transport.lock() = Some(transport);
+    }
 
     pub fn receiver(&self) -> Option<Arc<RtpReceiver>> {
         self.receiver.lock().clone()
     }
 
     pub fn set_receiver(&self, receiver: Option<Arc<RtpReceiver>>) {
+        if let Some(r) = &receiver {
+            r.set_video_orientation_extension_id(self.video_orientation_extension_id());
+            r.set_frame_marking_extension_id(self.frame_marking_extension_id());
+            r.set_rid_extension_id(self.rid_extension_id());
+            r.set_codec_name(self.codec_name.lock().clone());
+            r.set_payload_codecs(self.payload_codecs.lock().clone());
+            r.set_payload_fmtp(self.remote_fmtp.lock().clone());
+        }
         *self.receiver.lock() = receiver;
     }
 
+    fn video_orientation_extension_id(&self) -> Option<u8> {
+        self.extension_id(false, |uri| uri == crate::sdp::VIDEO_ORIENTATION_URI)
+    }
+
+    fn frame_marking_extension_id(&self) -> Option<u8> {
+        self.extension_id(false, |uri| uri == crate::sdp::FRAME_MARKING_URI)
+    }
+
+    fn rid_extension_id(&self) -> Option<u8> {
+        self.extension_id(false, |uri| uri == crate::sdp::RID_URI)
+    }
+
+    /// ID of the first negotiated extension matching `matches` that applies
+    /// to sending (or receiving) per its extmap direction.
+    fn extension_id(&self, sending: bool, matches: impl Fn(&str) -> bool) -> Option<u8> {
+        let directions = self.extmap_directions.read();
+        self.extmap
+            .read()
+            .iter()
+            .filter(|(id, _)| {
+                directions.get(id).is_none_or(|direction| {
+                    if sending {
+                        direction.sends()
+                    } else {
+                        direction.receives()
+                    }
+                })
+            })
+            .find(|(_, uri)| matches(uri))
+            .map(|(id, _)| *id)
+    }
+
     /// Update payload type mapping for reinvite scenarios
     pub fn update_payload_map(&self, new_map: HashMap<u8, RtpCodecParameters>) -> RtcResult<()> {
         let mut payload_map = self.payload_map.write();
@@ -5252,7 +7011,11 @@ impl RtpTransceiver {
             }
         }
 
+        if let Some(receiver) = self.receiver() {
+            receiver.payload_map_replaced(&payload_map, &new_map);
+        }
         *payload_map = new_map.clone();
+        drop(payload_map);
 
         // Update PT listeners in transport for fallback routing
         if let Some(receiver) = self.receiver()
@@ -5268,6 +7031,18 @@ impl RtpTransceiver {
 
     /// Update RTP header extension mapping for reinvite scenarios
     pub fn update_extmap(&self, new_extmap: HashMap<u8, String>) -> RtcResult<()> {
+        self.update_extmap_with_directions(new_extmap, HashMap::new())
+    }
+
+    /// Like [`update_extmap`](Self::update_extmap), restricting IDs listed in
+    /// `directions` to sending or receiving only. Send-side extensions
+    /// (abs-send-time, sdes:mid, video orientation) are only written when
+    /// their ID applies to sending.
+    pub fn update_extmap_with_directions(
+        &self,
+        new_extmap: HashMap<u8, String>,
+        directions: HashMap<u8, TransceiverDirection>,
+    ) -> RtcResult<()> {
         let mut extmap = self.extmap.write();
 
         // Log changes
@@ -5278,47 +7053,52 @@ impl RtpTransceiver {
         }
 
         *extmap = new_extmap;
+        *self.extmap_directions.write() = directions;
+        drop(extmap);
 
         // Update transport extension IDs if available
         if let Some(weak_transport) = self.rtp_transport.lock().as_ref()
             && let Some(transport) = weak_transport.upgrade()
         {
-            let id = extmap
-                .iter()
-                .find(|(_, uri)| uri.as_str() == crate::sdp::ABS_SEND_TIME_URI)
-                .map(|(id, _)| *id);
-            transport.set_abs_send_time_extension_id(id);
-
-            let id = extmap
-                .iter()
-                .find(|(_, uri)| uri.contains("rtp-stream-id"))
-                .map(|(id, _)| *id);
-            transport.set_rid_extension_id(id);
+            transport.set_abs_send_time_extension_id(
+                self.extension_id(true, |uri| uri == crate::sdp::ABS_SEND_TIME_URI),
+            );
+            transport.set_rid_extension_id(
+                self.extension_id(false, |uri| uri.contains("rtp-stream-id")),
+            );
+            transport.set_sdes_mid_extension_id(
+                self.extension_id(false, |uri| uri == crate::sdp::SDES_MID_URI),
+            );
+            // Transport-wide under BUNDLE: an m-line without transport-cc
+            // must not switch it off for the others.
+            let transport_cc_id =
+                self.extension_id(true, |uri| uri == crate::sdp::TRANSPORT_CC_URI);
+            if transport_cc_id.is_some() {
+                transport.set_transport_cc_extension_id(transport_cc_id);
+            }
+        }
 
-            let id = extmap
-                .iter()
-                .find(|(_, uri)| uri.as_str() == crate::sdp::SDES_MID_URI)
-                .map(|(id, _)| *id);
-            transport.set_sdes_mid_extension_id(id);
+        if let Some(receiver) = self.receiver.lock().as_ref() {
+            receiver.set_video_orientation_extension_id(self.video_orientation_extension_id());
+            receiver.set_frame_marking_extension_id(self.frame_marking_extension_id());
+            receiver.set_rid_extension_id(self.rid_extension_id());
         }
 
         // Propagate sdes:mid to the sender so it auto-injects the extension on every outgoing packet
+        let mid_value = self.mid.lock().clone();
+        let sdes_mid_id = self.extension_id(true, |uri| uri == crate::sdp::SDES_MID_URI);
         if let Some(sender_arc) = self.sender.lock().as_ref() {
-            let mid_value = self.mid.lock().clone();
-            let sdes_mid_id = extmap
-                .iter()
-                .find(|(_, uri)| uri.as_str() == crate::sdp::SDES_MID_URI)
-                .map(|(id, _)| *id);
+            sender_arc.set_video_orientation_extension_id(
+                self.extension_id(true, |uri| uri == crate::sdp::VIDEO_ORIENTATION_URI),
+            );
+            sender_arc.set_frame_marking_extension_id(
+                self.extension_id(true, |uri| uri == crate::sdp::FRAME_MARKING_URI),
+            );
             if let (Some(id), Some(mid)) = (sdes_mid_id, mid_value) {
                 sender_arc.set_sdes_mid(id, Arc::from(mid.as_str()));
             }
         } else {
             // Sender not yet created — defer sdes:mid so set_sender() can apply it.
-            let mid_value = self.mid.lock().clone();
-            let sdes_mid_id = extmap
-                .iter()
-                .find(|(_, uri)| uri.as_str() == crate::sdp::SDES_MID_URI)
-                .map(|(id, _)| *id);
             if let (Some(id), Some(mid)) = (sdes_mid_id, mid_value) {
                 *self.pending_sdes_mid.lock() = Some((id, Arc::from(mid.as_str())));
             }
@@ -5336,6 +7116,309 @@ impl RtpTransceiver {
     pub fn get_extmap(&self) -> HashMap<u8, String> {
         self.extmap.read().clone()
     }
+
+    pub fn video_limits(&self) -> VideoSendLimits {
+        *self.video_limits.lock()
+    }
+
+    /// Set the RFC 4796 `a=content` value (e.g. `slides` for screen-share,
+    /// `main` for camera) advertised for this m-line. `None` omits it.
+    pub fn set_content(&self, content: Option<String>) {
+        *self.content.lock() = content;
+    }
+
+    pub fn content(&self) -> Option<String> {
+        self.content.lock().clone()
+    }
+
+    /// Set the RFC 4574 `a=label` value advertised for this m-line.
+    pub fn set_label(&self, label: Option<String>) {
+        *self.label.lock() = label;
+    }
+
+    pub fn label(&self) -> Option<String> {
+        self.label.lock().clone()
+    }
+
+    /// `a=content` of the matching m-line in the remote description.
+    pub fn remote_content(&self) -> Option<String> {
+        self.remote_content.lock().clone()
+    }
+
+    /// `a=label` of the matching m-line in the remote description.
+    pub fn remote_label(&self) -> Option<String> {
+        self.remote_label.lock().clone()
+    }
+
+    fn update_remote_content(&self, section: &MediaSection) {
+        *self.remote_content.lock() = section.content().map(str::to_string);
+        *self.remote_label.lock() = section.label().map(str::to_string);
+    }
+
+    fn update_remote_fmtp(&self, section: &MediaSection) {
+        let fmtp: HashMap<u8, String> = section
+            .formats
+            .iter()
+            .filter_map(|format| {
+                let pt = format.parse::<u8>().ok()?;
+                Some((pt, section.fmtp(pt)?.to_string()))
+            })
+            .collect();
+        *self.remote_fmtp.lock() = fmtp.clone();
+        if let Some(receiver) = self.receiver.lock().as_ref() {
+            receiver.set_payload_fmtp(fmtp.clone());
+        }
+        if let Some(sender) = self.sender.lock().as_ref() {
+            sender.set_remote_fmtp(fmtp);
+        }
+    }
+
+    fn update_codec_name(&self, name: Option<String>) {
+        if name.is_none() {
+            return;
+        }
+        *self.codec_name.lock() = name.clone();
+        if let Some(receiver) = self.receiver.lock().as_ref() {
+            receiver.set_codec_name(name);
+        }
+    }
+
+    /// Keep the sender on a codec the remote accepted. When an answer drops
+    /// the payload type the sender was using, switch to the first media codec
+    /// of the answer so nothing is sent with an unnegotiated payload type.
+    fn restrict_sender_codec(&self, accepted: &[RtpCodecParameters]) {
+        let Some(sender) = self.sender.lock().clone() else {
+            return;
+        };
+        let current = sender.params().payload_type;
+        let Some(first) = accepted.first() else {
+            return;
+        };
+        if accepted.iter().any(|codec| codec.payload_type == current) {
+            return;
+        }
+        debug!(
+            "Answer does not accept sender payload type {}; sending payload type {}",
+            current, first.payload_type
+        );
+        sender.set_params(first.clone());
+    }
+
+    fn update_payload_codecs(&self, codecs: HashMap<u8, String>) {
+        if codecs.is_empty() {
+            return;
+        }
+        *self.payload_codecs.lock() = codecs.clone();
+        if let Some(receiver) = self.receiver.lock().as_ref() {
+            receiver.set_payload_codecs(codecs);
+        }
+    }
+
+    /// Update the negotiated video resolution/framerate limits and pass them
+    /// on to the sender.
+    pub fn update_video_limits(&self, limits: VideoSendLimits) {
+        *self.video_limits.lock() = limits;
+        if let Some(sender) = self.sender.lock().as_ref() {
+            sender.set_video_limits(limits);
+        }
+    }
+
+    /// Direction each negotiated extension ID applies to. IDs whose
+    /// `a=extmap` line had no direction suffix are reported as `SendRecv`.
+    pub fn extmap_directions(&self) -> HashMap<u8, TransceiverDirection> {
+        let directions = self.extmap_directions.read();
+        self.extmap
+            .read()
+            .keys()
+            .map(|id| {
+                let direction = directions
+                    .get(id)
+                    .copied()
+                    .unwrap_or(TransceiverDirection::SendRecv);
+                (*id, direction)
+            })
+            .collect()
+    }
+}
+
+/// Upper bounds for outgoing video, taken from the remote `a=imageattr` and
+/// `a=framerate`. Encoded frames cannot be rescaled here, so the sender drops
+/// frames that exceed them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VideoSendLimits {
+    /// Maximum `(width, height)`. Frames reporting a zero size are not checked.
+    pub max_resolution: Option<(u16, u16)>,
+    pub max_framerate: Option<f32>,
+}
+
+/// Padding-only RTP emitted for bandwidth probing when no real media is
+/// flowing (e.g. on a recvonly line). Each probe carries the P bit, an empty
+/// payload and `padding_size` padding octets (RFC 3550 §5.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddingProbe {
+    pub packets_per_second: u32,
+    /// Padding octets per packet, including the trailing count octet.
+    pub padding_size: u8,
+}
+
+impl PaddingProbe {
+    pub fn new(packets_per_second: u32) -> Self {
+        Self {
+            packets_per_second,
+            padding_size: 255,
+        }
+    }
+}
+
+/// Per-send-loop state enforcing [`VideoSendLimits`]. Decisions are made
+/// once per frame (RTP timestamp) so every packet of a frame is kept or
+/// dropped together.
+#[derive(Default)]
+struct VideoFrameGate {
+    last_sent_ts: Option<u32>,
+    last_dropped_ts: Option<u32>,
+}
+
+impl VideoFrameGate {
+    fn admit(
+        &mut self,
+        frame: &crate::media::VideoFrame,
+        limits: VideoSendLimits,
+        clock_rate: u32,
+    ) -> bool {
+        let ts = frame.rtp_timestamp;
+        if self.last_sent_ts == Some(ts) {
+            return true;
+        }
+        if self.last_dropped_ts == Some(ts) {
+            return false;
+        }
+
+        let too_large = limits
+            .max_resolution
+            .is_some_and(|(width, height)| frame.width > width || frame.height > height);
+        let too_soon = match (limits.max_framerate, self.last_sent_ts) {
+            (Some(fps), Some(last)) => {
+                (ts.wrapping_sub(last) as f64) < clock_rate as f64 / fps as f64
+            }
+            _ => false,
+        };
+
+        if too_large || too_soon {
+            self.last_dropped_ts = Some(ts);
+            false
+        } else {
+            self.last_sent_ts = Some(ts);
+            true
+        }
+    }
+}
+
+/// Samples the send pacer holds before it drops the oldest frame.
+const PACER_MAX_QUEUE: usize = 512;
+/// RTP fixed header, charged with each payload against the pacing rate.
+const RTP_HEADER_LEN: usize = 12;
+
+/// Token bucket spacing a sender's packets at a target bitrate.
+///
+/// Samples wait in a bounded queue. On overflow the oldest frame is dropped
+/// whole, up to its marker packet, so what goes out keeps its frame
+/// boundaries.
+struct SendPacer {
+    kbps: Arc<AtomicU32>,
+    queue: VecDeque<crate::media::MediaSample>,
+    /// Earliest time the next packet may leave.
+    next_send: tokio::time::Instant,
+    /// Error the track ended with while samples were still queued.
+    ended: Option<crate::media::error::MediaError>,
+}
+
+impl SendPacer {
+    fn new(kbps: Arc<AtomicU32>) -> Self {
+        Self {
+            kbps,
+            queue: VecDeque::new(),
+            next_send: tokio::time::Instant::now(),
+            ended: None,
+        }
+    }
+
+    /// Next sample to send, once the pacing rate allows. Queued samples
+    /// survive cancellation, so this can be polled in `select!`.
+    async fn next_sample(
+        &mut self,
+        track: &Arc<dyn MediaStreamTrack>,
+    ) -> crate::media::MediaResult<crate::media::MediaSample> {
+        loop {
+            let kbps = self.kbps.load(Ordering::Relaxed);
+            if self.queue.is_empty() {
+                if let Some(e) = self.ended.take() {
+                    return Err(e);
+                }
+                if kbps == 0 {
+                    return track.recv().await;
+                }
+            }
+            let now = tokio::time::Instant::now();
+            if kbps == 0 || (!self.queue.is_empty() && now >= self.next_send) {
+                let sample = self.queue.pop_front().expect("queue is not empty");
+                if kbps > 0 {
+                    let bits = ((Self::payload_len(&sample) + RTP_HEADER_LEN) * 8) as u64;
+                    let spacing = std::time::Duration::from_micros(bits * 1000 / kbps as u64);
+                    self.next_send = self.next_send.max(now) + spacing;
+                }
+                return Ok(sample);
+            }
+
+            tokio::select! {
+                res = track.recv(), if self.ended.is_none() => match res {
+                    Ok(sample) => self.push(sample),
+                    Err(e) if self.queue.is_empty() => return Err(e),
+                    Err(crate::media::error::MediaError::Lagged) => {}
+                    Err(e) => self.ended = Some(e),
+                },
+                _ = tokio::time::sleep_until(self.next_send), if !self.queue.is_empty() => {}
+            }
+        }
+    }
+
+    fn push(&mut self, sample: crate::media::MediaSample) {
+        if self.queue.len() >= PACER_MAX_QUEUE
+            && let Some(front) = self.queue.front()
+        {
+            let timestamp = front.rtp_timestamp();
+            let mut dropped = 0;
+            while self
+                .queue
+                .front()
+                .is_some_and(|old| old.rtp_timestamp() == timestamp)
+            {
+                let Some(old) = self.queue.pop_front() else {
+                    break;
+                };
+                dropped += 1;
+                if Self::ends_frame(&old) {
+                    break;
+                }
+            }
+            trace!("RtpSender: pacer queue full, dropped {} packets", dropped);
+        }
+        self.queue.push_back(sample);
+    }
+
+    fn payload_len(sample: &crate::media::MediaSample) -> usize {
+        match sample {
+            crate::media::MediaSample::Audio(f) => f.data.len(),
+            crate::media::MediaSample::Video(f) => f.data.len(),
+        }
+    }
+
+    fn ends_frame(sample: &crate::media::MediaSample) -> bool {
+        match sample {
+            crate::media::MediaSample::Audio(_) => true,
+            crate::media::MediaSample::Video(f) => f.is_last_packet,
+        }
+    }
 }
 
 pub struct RtpSender {
@@ -5349,13 +7432,37 @@ pub struct RtpSender {
     rtcp_tx: broadcast::Sender<RtcpPacket>,
     stop_tx: Arc<tokio::sync::Notify>,
     next_sequence_number: Arc<AtomicU16>,
-    packets_sent: Arc<AtomicU32>,
+    packets_sent: Arc<AtomicU64>,
+    /// Flips to `true` with the first packet put on the wire.
+    first_packet_sent: Arc<watch::Sender<bool>>,
     octets_sent: Arc<AtomicU32>,
+    padding_bytes_sent: Arc<AtomicU64>,
+    /// Frames completed on the wire; a video frame ends at the packet the
+    /// packetizer marked as its last.
+    frames_sent: Arc<AtomicU64>,
     last_rtp_timestamp: Arc<AtomicU32>,
     interceptors: Vec<Arc<dyn RtpSenderInterceptor + Send + Sync>>,
     /// sdes:mid extension to inject: (extension header ID, mid value).
     /// Set automatically by update_extmap() when negotiation contains sdes:mid.
     sdes_mid: Arc<Mutex<Option<(u8, Arc<str>)>>>,
+    /// Negotiated `urn:3gpp:video-orientation` extension ID. When set, the
+    /// rotation/flip of each video frame is written on its last packet.
+    video_orientation_ext_id: Arc<Mutex<Option<u8>>>,
+    /// Negotiated frame marking extension ID. When set, each video frame's
+    /// `frame_marking` is written on its packets.
+    frame_marking_ext_id: Arc<Mutex<Option<u8>>>,
+    video_limits: Arc<Mutex<VideoSendLimits>>,
+    /// `a=fmtp` parameters per payload type from the remote description.
+    remote_fmtp: Mutex<HashMap<u8, String>>,
+    /// Parsed parameters for the current payload type; receivers are woken
+    /// when a renegotiation changes them.
+    negotiated_fmtp_tx: watch::Sender<HashMap<String, String>>,
+    padding_probe_tx: watch::Sender<Option<PaddingProbe>>,
+    /// Pacing rate in kbit/s; 0 sends packets as they are produced.
+    pacing_kbps: Arc<AtomicU32>,
+    loss_injection: Arc<LossInjection>,
+    /// Negotiated FlexFEC repair stream; `None` sends media unprotected.
+    flexfec: Arc<Mutex<Option<crate::flexfec::FlexFecSenderConfig>>>,
     transport_generation: Arc<AtomicU64>,
     transport_change_tx: watch::Sender<u64>,
 }
@@ -5367,6 +7474,8 @@ pub struct RtpSenderBuilder {
     params: RtpCodecParameters,
     interceptors: Vec<Arc<dyn RtpSenderInterceptor + Send + Sync>>,
     cname: Option<String>,
+    padding_probe: Option<PaddingProbe>,
+    pacing_kbps: Option<u32>,
 }
 
 impl RtpSenderBuilder {
@@ -5378,6 +7487,8 @@ impl RtpSenderBuilder {
             params: RtpCodecParameters::default(),
             interceptors: Vec::new(),
             cname: None,
+            padding_probe: None,
+            pacing_kbps: None,
         }
     }
 
@@ -5397,6 +7508,19 @@ impl RtpSenderBuilder {
         self
     }
 
+    /// Like [`nack`](Self::nack), answering repeated NACKs for a packet at
+    /// most once per `retransmit_interval`.
+    pub fn nack_with_interval(
+        mut self,
+        buffer_size: usize,
+        retransmit_interval: std::time::Duration,
+    ) -> Self {
+        self.interceptors.push(Arc::new(
+            DefaultRtpSenderNackHandler::with_retransmit_interval(buffer_size, retransmit_interval),
+        ));
+        self
+    }
+
     pub fn bitrate_controller(mut self) -> Self {
         self.interceptors
             .push(Arc::new(DefaultRtpSenderBitrateHandler::new()));
@@ -5413,15 +7537,28 @@ impl RtpSenderBuilder {
         self
     }
 
+    pub fn padding_probe(mut self, probe: PaddingProbe) -> Self {
+        self.padding_probe = Some(probe);
+        self
+    }
+
+    pub fn pacing_kbps(mut self, kbps: u32) -> Self {
+        self.pacing_kbps = Some(kbps);
+        self
+    }
+
     pub fn build(self) -> Arc<RtpSender> {
-        Arc::new(RtpSender::new_internal(
+        let sender = RtpSender::new_internal(
             self.track,
             self.ssrc,
             self.stream_id,
             self.params,
             self.interceptors,
             self.cname,
-        ))
+        );
+        sender.set_padding_probe(self.padding_probe);
+        sender.set_pacing_kbps(self.pacing_kbps);
+        Arc::new(sender)
     }
 }
 
@@ -5455,6 +7592,8 @@ impl RtpSender {
             Arc::<str>::from(cname_override.unwrap_or_else(|| format!("rustrtc-cname-{ssrc}")));
         let (rtcp_tx, _) = broadcast::channel(100);
         let (transport_change_tx, _) = watch::channel(0);
+        let (padding_probe_tx, _) = watch::channel(None);
+        let (negotiated_fmtp_tx, _) = watch::channel(HashMap::new());
 
         Self {
             track,
@@ -5467,11 +7606,23 @@ impl RtpSender {
             rtcp_tx,
             stop_tx: Arc::new(tokio::sync::Notify::new()),
             next_sequence_number: Arc::new(AtomicU16::new(random_u32() as u16)),
-            packets_sent: Arc::new(AtomicU32::new(0)),
+            packets_sent: Arc::new(AtomicU64::new(0)),
+            first_packet_sent: Arc::new(watch::Sender::new(false)),
             octets_sent: Arc::new(AtomicU32::new(0)),
+            padding_bytes_sent: Arc::new(AtomicU64::new(0)),
+            frames_sent: Arc::new(AtomicU64::new(0)),
             last_rtp_timestamp: Arc::new(AtomicU32::new(0)),
             interceptors,
             sdes_mid: Arc::new(Mutex::new(None)),
+            video_orientation_ext_id: Arc::new(Mutex::new(None)),
+            frame_marking_ext_id: Arc::new(Mutex::new(None)),
+            video_limits: Arc::new(Mutex::new(VideoSendLimits::default())),
+            remote_fmtp: Mutex::new(HashMap::new()),
+            negotiated_fmtp_tx,
+            padding_probe_tx,
+            pacing_kbps: Arc::new(AtomicU32::new(0)),
+            loss_injection: Arc::new(LossInjection::default()),
+            flexfec: Arc::new(Mutex::new(None)),
             transport_generation: Arc::new(AtomicU64::new(0)),
             transport_change_tx,
         }
@@ -5481,6 +7632,21 @@ impl RtpSender {
         self.ssrc
     }
 
+    /// RTP packets (media and padding probes) put on the wire so far.
+    pub fn packets_sent(&self) -> u64 {
+        self.packets_sent.load(Ordering::Relaxed)
+    }
+
+    /// Padding octets put on the wire so far, e.g. by padding probes.
+    pub fn padding_bytes_sent(&self) -> u64 {
+        self.padding_bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// Frames sent so far. Each audio sample is one frame.
+    pub fn frames_sent(&self) -> u64 {
+        self.frames_sent.load(Ordering::Relaxed)
+    }
+
     pub fn cname(&self) -> &str {
         &self.cname
     }
@@ -5497,6 +7663,83 @@ impl RtpSender {
         *self.sdes_mid.lock() = Some((ext_id, mid));
     }
 
+    pub fn set_video_orientation_extension_id(&self, ext_id: Option<u8>) {
+        *self.video_orientation_ext_id.lock() = ext_id;
+    }
+
+    pub fn set_frame_marking_extension_id(&self, ext_id: Option<u8>) {
+        *self.frame_marking_ext_id.lock() = ext_id;
+    }
+
+    pub fn video_limits(&self) -> VideoSendLimits {
+        *self.video_limits.lock()
+    }
+
+    pub fn set_video_limits(&self, limits: VideoSendLimits) {
+        *self.video_limits.lock() = limits;
+    }
+
+    /// Format parameters the remote side negotiated for this sender's payload
+    /// type (e.g. Opus `maxaveragebitrate` or H264 `profile-level-id`), for
+    /// configuring the encoder. Empty until a remote description carrying
+    /// `a=fmtp` for that payload type is applied.
+    pub fn negotiated_fmtp(&self) -> HashMap<String, String> {
+        self.negotiated_fmtp_tx.borrow().clone()
+    }
+
+    /// Watch [`negotiated_fmtp`](Self::negotiated_fmtp). The receiver is
+    /// marked changed only when a (re)negotiation alters the parameters, e.g.
+    /// a re-INVITE toggling Opus `usedtx`, so the encoder can be reconfigured
+    /// in place.
+    pub fn watch_negotiated_fmtp(&self) -> watch::Receiver<HashMap<String, String>> {
+        self.negotiated_fmtp_tx.subscribe()
+    }
+
+    pub(crate) fn set_remote_fmtp(&self, fmtp: HashMap<u8, String>) {
+        *self.remote_fmtp.lock() = fmtp;
+        self.refresh_negotiated_fmtp();
+    }
+
+    fn refresh_negotiated_fmtp(&self) {
+        let payload_type = self.params.lock().payload_type;
+        let fmtp = self
+            .remote_fmtp
+            .lock()
+            .get(&payload_type)
+            .map(|fmtp| crate::sdp::parse_fmtp(fmtp))
+            .unwrap_or_default();
+        self.negotiated_fmtp_tx.send_if_modified(|current| {
+            if *current == fmtp {
+                return false;
+            }
+            *current = fmtp;
+            true
+        });
+    }
+
+    pub fn padding_probe(&self) -> Option<PaddingProbe> {
+        *self.padding_probe_tx.borrow()
+    }
+
+    /// Start, retune or (with `None`) stop padding-only probe packets. Takes
+    /// effect immediately on a running send loop.
+    pub fn set_padding_probe(&self, probe: Option<PaddingProbe>) {
+        self.padding_probe_tx.send_replace(probe);
+    }
+
+    /// Space packets out at `kbps` kbit/s, or send them as they are
+    /// produced with `None`. Takes effect on the running send loop.
+    pub fn set_pacing_kbps(&self, kbps: Option<u32>) {
+        self.pacing_kbps.store(kbps.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Silently discard `ratio` (0.0–1.0) of outgoing media packets after
+    /// they pass the interceptors, so NACK retransmission can recover them.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn set_loss_injection(&self, ratio: f64) {
+        self.loss_injection.set_ratio(ratio);
+    }
+
     pub fn subscribe_rtcp(&self) -> broadcast::Receiver<RtcpPacket> {
         self.rtcp_tx.subscribe()
     }
@@ -5511,6 +7754,7 @@ impl RtpSender {
 
     pub fn set_params(&self, params: RtpCodecParameters) {
         *self.params.lock() = params;
+        self.refresh_negotiated_fmtp();
     }
 
     pub fn interceptors(&self) -> &[Arc<dyn RtpSenderInterceptor + Send + Sync>] {
@@ -5546,6 +7790,16 @@ impl RtpSender {
         false
     }
 
+    /// Emit a FlexFEC repair packet after every `FLEXFEC_GROUP_SIZE` media
+    /// packets. Pass `None` to stop.
+    pub fn set_flexfec(&self, config: Option<crate::flexfec::FlexFecSenderConfig>) {
+        *self.flexfec.lock() = config;
+    }
+
+    pub fn flexfec(&self) -> Option<crate::flexfec::FlexFecSenderConfig> {
+        *self.flexfec.lock()
+    }
+
     pub fn set_transport(&self, transport: Arc<RtpTransport>) {
         {
             let track_id = self.track_id.clone();
@@ -5571,9 +7825,11 @@ impl RtpSender {
         let _ = self.transport_change_tx.send(generation);
 
         *self.transport.lock() = Some(transport.clone());
+        transport.adopt_rtcp_sender_ssrc(self.ssrc);
         let track_id = self.track_id.clone();
         let track = self.track.clone();
         let ssrc = self.ssrc;
+        let cname = self.cname.clone();
         info!(
             "RtpSender: spawning send loop track_id={} ssrc={}",
             track_id, ssrc
@@ -5584,17 +7840,29 @@ impl RtpSender {
         let transport_generation = self.transport_generation.clone();
         let next_seq = self.next_sequence_number.clone();
         let packets_sent = self.packets_sent.clone();
+        let first_packet_sent = self.first_packet_sent.clone();
         let octets_sent = self.octets_sent.clone();
+        let padding_bytes_sent = self.padding_bytes_sent.clone();
+        let frames_sent = self.frames_sent.clone();
         let last_rtp_timestamp = self.last_rtp_timestamp.clone();
         let interceptors = self.interceptors.clone();
         let sdes_mid = self.sdes_mid.clone();
+        let video_orientation_ext_id = self.video_orientation_ext_id.clone();
+        let frame_marking_ext_id = self.frame_marking_ext_id.clone();
+        let video_limits = self.video_limits.clone();
+        let mut padding_probe_rx = self.padding_probe_tx.subscribe();
+        let mut pacer = SendPacer::new(self.pacing_kbps.clone());
+        let loss_injection = self.loss_injection.clone();
+        let flexfec = self.flexfec.clone();
         let mut rtcp_rx = self.rtcp_tx.subscribe();
 
         tokio::spawn(async move {
             let mut sequence_number = next_seq.load(Ordering::SeqCst);
+            let mut last_fir_seq: Option<u8> = None;
             let mut logged_first_sample = false;
-            let mut last_source_ts: Option<u32> = None;
-            let mut timestamp_offset = random_u32(); // Start with random offset
+            let mut timestamps = TimestampNormalizer::new();
+            let mut video_gate = VideoFrameGate::default();
+            let mut fec_encoder: Option<crate::flexfec::FlexFecEncoder> = None;
             // Delay the first SR so the initial RTP burst is not immediately followed by RTCP
             // on the same 5-tuple, which can confuse consumers that are expecting RTP first.
             let mut rtcp_interval = tokio::time::interval_at(
@@ -5602,6 +7870,8 @@ impl RtpSender {
                 std::time::Duration::from_secs(3),
             );
             rtcp_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut probe_interval =
+                Self::padding_probe_interval(*padding_probe_rx.borrow_and_update());
             let notified = stop_rx.notified();
             tokio::pin!(notified);
 
@@ -5622,11 +7892,53 @@ impl RtpSender {
                             Err(_) => break,
                         }
                     }
+                    changed = padding_probe_rx.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        probe_interval =
+                            Self::padding_probe_interval(*padding_probe_rx.borrow_and_update());
+                    }
+                    _ = async { probe_interval.as_mut().unwrap().tick().await }, if probe_interval.is_some() => {
+                        if transport_generation.load(Ordering::SeqCst) != generation {
+                            break;
+                        }
+                        let Some(probe) = *padding_probe_rx.borrow() else {
+                            continue;
+                        };
+                        // Same numbering as a media packet so probes and media
+                        // share one gap-free sequence space.
+                        sequence_number = sequence_number.wrapping_add(1);
+                        next_seq.store(sequence_number, Ordering::SeqCst);
+                        let mut header = crate::rtp::RtpHeader::new(
+                            params_lock.lock().payload_type,
+                            next_seq.fetch_add(1, Ordering::Relaxed),
+                            last_rtp_timestamp.load(Ordering::Relaxed),
+                            ssrc,
+                        );
+                        if let Some((id, ref mid)) = *sdes_mid.lock() {
+                            let _ = header.set_extension(id, mid.as_bytes());
+                        }
+                        let mut packet = RtpPacket::new(header, Vec::new());
+                        packet.padding_len = probe.padding_size.max(1);
+                        let padding_len = u64::from(packet.padding_len);
+
+                        if let Err(e) = transport.send_rtp(packet).await {
+                            trace!("Failed to send padding probe: {}", e);
+                        } else {
+                            // Padding is not payload, so only the packet count moves.
+                            if packets_sent.fetch_add(1, Ordering::Relaxed) == 0 {
+                                first_packet_sent.send_replace(true);
+                            }
+                            padding_bytes_sent.fetch_add(padding_len, Ordering::Relaxed);
+                        }
+                    }
                     _ = rtcp_interval.tick(), if packets_sent.load(Ordering::Relaxed) > 0 => {
                         if transport_generation.load(Ordering::SeqCst) != generation {
                             break;
                         }
-                        let packet_count = packets_sent.load(Ordering::Relaxed);
+                        // The SR packet count is a wrapping 32-bit field (RFC 3550 §6.4.1).
+                        let packet_count = packets_sent.load(Ordering::Relaxed) as u32;
 
                         let octet_count = octets_sent.load(Ordering::Relaxed);
                         let rtp_timestamp = last_rtp_timestamp.load(Ordering::Relaxed);
@@ -5637,12 +7949,14 @@ impl RtpSender {
                             octet_count,
                             SystemTime::now(),
                         );
+                        let compound = Self::build_report_compound(report, &cname);
 
-                        if let Err(e) = transport
-                            .send_rtcp(&[RtcpPacket::SenderReport(report)])
-                            .await
-                        {
+                        if let Err(e) = transport.send_rtcp(&compound).await {
                             trace!("Failed to send Sender Report: {}", e);
+                        } else {
+                            for interceptor in &interceptors {
+                                interceptor.on_rtcp_sent(&compound).await;
+                            }
                         }
                     }
                     rtcp = rtcp_rx.recv() => {
@@ -5650,12 +7964,33 @@ impl RtpSender {
                             break;
                         }
                         if let Ok(packet) = rtcp {
+                            let wants_key_frame = match &packet {
+                                RtcpPacket::PictureLossIndication(pli) => pli.media_ssrc == ssrc,
+                                // RFC 5104 §4.3.1.2: a repeated sequence number
+                                // retransmits a request that was already seen.
+                                RtcpPacket::FullIntraRequest(fir) => fir
+                                    .requests
+                                    .iter()
+                                    .find(|r| r.ssrc == ssrc)
+                                    .is_some_and(|r| {
+                                        last_fir_seq.replace(r.sequence_number)
+                                            != Some(r.sequence_number)
+                                    }),
+                                _ => false,
+                            };
+                            // Hand the request to whoever produces the track. A full
+                            // feedback queue already holds a pending request.
+                            if wants_key_frame
+                                && let Some(Err(e)) = track.request_key_frame().now_or_never()
+                            {
+                                trace!("RtpSender: key frame request not forwarded: {}", e);
+                            }
                             for interceptor in &interceptors {
                                 interceptor.on_rtcp_received(&packet, transport.clone()).await;
                             }
                         }
                     }
-                    res = track.recv() => {
+                    res = pacer.next_sample(&track) => {
                         if transport_generation.load(Ordering::SeqCst) != generation {
                             break;
                         }
@@ -5668,17 +8003,34 @@ impl RtpSender {
                                         ssrc, track_id
                                     );
                                 }
-                                let payload_type = {
+                                let (payload_type, clock_rate) = {
                                     let p = params_lock.lock();
-                                    p.payload_type
+                                    (p.payload_type, p.clock_rate)
                                 };
 
+                                if let crate::media::MediaSample::Video(f) = &sample
+                                    && !video_gate.admit(f, *video_limits.lock(), clock_rate.max(1))
+                                {
+                                    trace!(
+                                        "RtpSender: dropping {}x{} frame ts={} over negotiated limits",
+                                        f.width, f.height, f.rtp_timestamp
+                                    );
+                                    continue;
+                                }
+
                                 // Check if application provided sequence_number (indicates app wants control)
                                 let app_controlled = match &sample {
                                     crate::media::MediaSample::Audio(f) => f.sequence_number.is_some(),
                                     crate::media::MediaSample::Video(f) => f.sequence_number.is_some(),
                                 };
 
+                                let (video_orientation, frame_marking, ends_frame) = match &sample {
+                                    crate::media::MediaSample::Video(f) => {
+                                        (Some(f.video_orientation()), f.frame_marking, f.is_last_packet)
+                                    }
+                                    crate::media::MediaSample::Audio(_) => (None, None, true),
+                                };
+
                                 // Always rewrite sequence numbers to ensure continuity on the wire
                                 match &mut sample {
                                     crate::media::MediaSample::Audio(f) => f.sequence_number = None,
@@ -5695,40 +8047,11 @@ impl RtpSender {
                                 next_seq.store(sequence_number, Ordering::SeqCst);
 
                                 if !app_controlled {
-                                    // Application doesn't control seq/ts, use rustrtc's logic
-                                    // Timestamp rewriting
-                                    let src_ts = packet.header.timestamp;
-                                    if let Some(last_src) = last_source_ts {
-                                        let delta = src_ts.wrapping_sub(last_src);
-                                        // Check if src_ts is newer (delta < 2^31)
-                                        if delta < 0x80000000 {
-                                            // If delta is very large (e.g. > 10 seconds), assume source switch/reset
-                                            // 10 seconds * 90000 = 900,000.
-                                            if delta > 900_000 {
-                                                // Discontinuity detected.
-                                                // We want the new timestamp to continue from where we left off.
-                                                // But we don't track last_out_ts explicitly here, we rely on offset.
-                                                // last_out_ts was (last_src + old_offset).
-                                                // new_out_ts should be (last_out_ts + small_delta).
-                                                // Let's assume small_delta = 3000 (1/30s at 90khz) or just 1 to be safe.
-                                                // new_out_ts = last_src + old_offset + 3000.
-                                                // new_out_ts = src_ts + new_offset.
-                                                // => new_offset = last_src + old_offset + 3000 - src_ts.
-                                                timestamp_offset = last_src.wrapping_add(timestamp_offset).wrapping_add(3000).wrapping_sub(src_ts);
-                                            }
-                                            last_source_ts = Some(src_ts);
-                                        }
-                                        // If src_ts is older (delta >= 2^31), it's an out-of-order packet.
-                                        // We use the existing offset and do NOT update last_source_ts.
-                                    } else {
-                                        // First packet, establish offset
-                                        // We want out_ts = src_ts + offset.
-                                        // We initialized offset to random.
-                                        // So out_ts will be random. Correct.
-                                        last_source_ts = Some(src_ts);
-                                    }
-
-                                    packet.header.timestamp = src_ts.wrapping_add(timestamp_offset);
+                                    // Application doesn't control seq/ts, use rustrtc's logic.
+                                    // Rebase onto one clock so source switches don't jump.
+                                    timestamps.set_clock_rate(clock_rate);
+                                    packet.header.timestamp =
+                                        timestamps.normalize(packet.header.timestamp);
 
                                     // Rewrite sequence number
                                     packet.header.sequence_number = next_seq.fetch_add(1, Ordering::Relaxed);
@@ -5747,10 +8070,53 @@ impl RtpSender {
                                     let _ = packet.header.set_extension(id, mid.as_bytes());
                                 }
 
+                                // CVO is only required on the last packet of each frame (3GPP TS 26.114).
+                                if packet.header.marker
+                                    && let Some(cvo) = video_orientation
+                                    && let Some(id) = *video_orientation_ext_id.lock()
+                                {
+                                    let _ = packet.header.set_extension(id, &[cvo]);
+                                }
+
+                                if let Some(marking) = frame_marking
+                                    && let Some(id) = *frame_marking_ext_id.lock()
+                                {
+                                    let _ = packet.header.set_extension(id, &marking.to_bytes());
+                                }
+
                                 let payload_len = packet.payload.len() as u32;
                                 let packet_timestamp = packet.header.timestamp;
 
-                                if let Err(e) = transport.send_rtp(packet).await {
+                                // Protect the packet as it goes on the wire, including ones
+                                // the loss injection drops, so the receiver can rebuild them.
+                                let fec_config = *flexfec.lock();
+                                let repair = match fec_config {
+                                    Some(config) => {
+                                        if fec_encoder.as_ref().is_none_or(|e| e.config() != config) {
+                                            fec_encoder = Some(crate::flexfec::FlexFecEncoder::new(
+                                                config,
+                                                crate::flexfec::FLEXFEC_GROUP_SIZE,
+                                            ));
+                                        }
+                                        fec_encoder.as_mut().and_then(|e| e.push(&packet))
+                                    }
+                                    None => {
+                                        fec_encoder = None;
+                                        None
+                                    }
+                                };
+
+                                let sent = if loss_injection.should_drop() {
+                                    Ok(0)
+                                } else {
+                                    transport.send_rtp(packet).await
+                                };
+                                if let Some(repair) = repair
+                                    && let Err(e) = transport.send_rtp(repair).await
+                                {
+                                    trace!("Failed to send FlexFEC repair packet: {}", e);
+                                }
+                                if let Err(e) = sent {
                                     let n = packets_sent.load(Ordering::Relaxed);
                                     if n < 5 {
                                         warn!("RtpSender: failed to send RTP (ssrc={}): {}", ssrc, e);
@@ -5760,12 +8126,16 @@ impl RtpSender {
                                 } else {
                                     let n = packets_sent.fetch_add(1, Ordering::Relaxed) + 1;
                                     if n == 1 {
+                                        first_packet_sent.send_replace(true);
                                         info!(
                                             "RtpSender: first RTP packet sent on wire ssrc={} track_id={}",
                                             ssrc, track_id
                                         );
                                     }
                                     octets_sent.fetch_add(payload_len, Ordering::Relaxed);
+                                    if ends_frame {
+                                        frames_sent.fetch_add(1, Ordering::Relaxed);
+                                    }
                                     last_rtp_timestamp.store(packet_timestamp, Ordering::Relaxed);
                                 }
                             }
@@ -5781,6 +8151,16 @@ impl RtpSender {
         });
     }
 
+    fn padding_probe_interval(probe: Option<PaddingProbe>) -> Option<tokio::time::Interval> {
+        let rate = probe?.packets_per_second;
+        if rate == 0 {
+            return None;
+        }
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1) / rate);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        Some(interval)
+    }
+
     fn build_sender_report(
         sender_ssrc: u32,
         rtp_timestamp: u32,
@@ -5802,6 +8182,61 @@ impl RtpSender {
             report_blocks: Vec::new(),
         }
     }
+
+    /// RFC 3550 §6.1: every compound RTCP packet starts with an SR/RR and
+    /// carries an SDES with the CNAME of the sending source.
+    fn build_report_compound(report: SenderReport, cname: &str) -> Vec<RtcpPacket> {
+        let sdes = Self::build_cname_sdes(report.sender_ssrc, cname);
+        vec![RtcpPacket::SenderReport(report), sdes]
+    }
+
+    fn build_cname_sdes(ssrc: u32, cname: &str) -> RtcpPacket {
+        RtcpPacket::SourceDescription(SourceDescription {
+            chunks: vec![SdesChunk {
+                ssrc,
+                items: vec![SdesItem {
+                    ty: RTCP_SDES_CNAME,
+                    text: cname.to_string(),
+                }],
+            }],
+        })
+    }
+
+    /// BYE for every sender as the last packet of a compound (RFC 3550
+    /// §6.1, §6.6): the first sender's SR (an RR if it never sent) and
+    /// CNAME come first. `None` without senders.
+    fn build_bye_compound(
+        senders: &[Arc<RtpSender>],
+        reason: &str,
+        now: SystemTime,
+    ) -> Option<Vec<RtcpPacket>> {
+        let first = senders.first()?;
+        let packet_count = first.packets_sent.load(Ordering::Relaxed) as u32;
+        let mut compound = if packet_count > 0 {
+            let report = Self::build_sender_report(
+                first.ssrc,
+                first.last_rtp_timestamp.load(Ordering::Relaxed),
+                packet_count,
+                first.octets_sent.load(Ordering::Relaxed),
+                now,
+            );
+            Self::build_report_compound(report, &first.cname)
+        } else {
+            let report = crate::rtp::ReceiverReport {
+                sender_ssrc: first.ssrc,
+                report_blocks: Vec::new(),
+            };
+            vec![
+                RtcpPacket::ReceiverReport(report),
+                Self::build_cname_sdes(first.ssrc, &first.cname),
+            ]
+        };
+        compound.push(RtcpPacket::Goodbye(crate::rtp::Goodbye {
+            sources: senders.iter().map(|sender| sender.ssrc).collect(),
+            reason: Some(reason.to_string()),
+        }));
+        Some(compound)
+    }
 }
 
 impl RtpSender {
@@ -5833,6 +8268,8 @@ pub struct RtpReceiver {
     rtx_ssrc: Mutex<Option<u32>>,
     /// RTX payload type → primary payload type (from SDP `a=fmtp:<rtx> apt=<primary>`).
     rtx_apt: Mutex<HashMap<u8, u8>>,
+    /// FlexFEC recovery state when a repair stream was negotiated.
+    flexfec: Mutex<Option<crate::flexfec::FlexFecDecoder>>,
     fir_seq: AtomicU8,
     feedback_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<crate::media::track::FeedbackEvent>>>,
     simulcast_tracks: Mutex<
@@ -5851,11 +8288,58 @@ pub struct RtpReceiver {
     track_ready_event_tx: Mutex<Option<mpsc::UnboundedSender<PeerConnectionEvent>>>,
     track_ready_transceiver: Mutex<Option<Weak<RtpTransceiver>>>,
     track_event_sent: AtomicBool,
-    /// Lock-free clock-rate cache keyed by payload type. The mapping is static
-    /// after SDP negotiation, so the per-packet receive path can skip the
-    /// `payload_map` RwLock + `params` Mutex on every RTP packet.
+    /// RTP packets handed to the run loop, including RTX and simulcast layers.
+    packets_received: AtomicU64,
+    /// Flips to `true` with the first packet received.
+    first_packet_received: watch::Sender<bool>,
+    /// Set by [`pause`](RtpReceiver::pause): packets are still received but
+    /// not depacketized or delivered to the tracks.
+    paused: AtomicBool,
+    /// Lock-free clock-rate cache keyed by payload type. The mapping only
+    /// changes on renegotiation (which clears the cache), so the per-packet
+    /// receive path can skip the `payload_map` RwLock + `params` Mutex.
     clock_rate_cache_pt: AtomicU8,
     clock_rate_cache: AtomicU32,
+    /// Bumped whenever renegotiation replaces `payload_map`.
+    payload_map_version: AtomicU32,
+    /// Payload types dropped from the payload map by renegotiation. Late
+    /// packets still carrying one of them are discarded rather than
+    /// depacketized with the fallback codec parameters.
+    retired_payload_types: Mutex<HashSet<u8>>,
+    /// Negotiated `urn:3gpp:video-orientation` extension ID (0 = not negotiated).
+    video_orientation_ext_id: AtomicU8,
+    /// Last CVO byte received. Senders only repeat it on key frames or when it
+    /// changes, so it is carried over to every following frame.
+    video_orientation: AtomicU8,
+    /// Negotiated frame marking extension ID (0 = not negotiated).
+    frame_marking_ext_id: AtomicU8,
+    /// Negotiated `sdes:rtp-stream-id` extension ID (0 = not negotiated).
+    rid_ext_id: AtomicU8,
+    /// Simulcast layer forwarded to the main track (`None` = all layers).
+    selected_rid: Mutex<Option<String>>,
+    /// RID learned per SSRC. Senders stop tagging a layer once it is
+    /// established, so later packets are matched by SSRC.
+    layer_rids: Mutex<HashMap<u32, String>>,
+    /// Primary codec negotiated for this m-line, e.g. `VP8` or `opus`.
+    codec_name: Mutex<Option<String>>,
+    /// Encoding name per media payload type; the run loop swaps
+    /// depacketizers when incoming packets move to a different codec.
+    payload_codecs: Mutex<HashMap<u8, String>>,
+    /// Remote `a=fmtp` parameters per payload type, handed to the
+    /// depacketizer factory (e.g. Opus `sprop-stereo`).
+    payload_fmtp: Mutex<HashMap<u8, String>>,
+    /// Bumped on every `payload_codecs` or `payload_fmtp` update so a
+    /// payload type that was remapped by renegotiation is looked up again.
+    payload_codecs_version: AtomicU32,
+    /// Latest RTCP SR mapping per media SSRC: (RTP timestamp, sender wallclock).
+    sender_reports: Mutex<HashMap<u32, (u32, SystemTime)>>,
+    /// Target reorder delay ahead of depacketization (`None` = disabled).
+    jitter_buffer_delay: Option<std::time::Duration>,
+    /// Timestamp jump that starts a new talkspurt in the jitter buffer.
+    jitter_buffer_max_gap: std::time::Duration,
+    unknown_payload_type_policy: UnknownPayloadTypePolicy,
+    /// RTP packets whose payload type was not negotiated on this m-line.
+    unknown_payload_type_packets: AtomicU64,
     pub depacketizer_factory: Arc<dyn DepacketizerFactory>,
 }
 
@@ -5865,6 +8349,9 @@ pub struct RtpReceiverBuilder {
     interceptors: Vec<Arc<dyn RtpReceiverInterceptor>>,
     depacketizer_factory: Option<Arc<dyn DepacketizerFactory>>,
     payload_map: Arc<RwLock<HashMap<u8, RtpCodecParameters>>>,
+    jitter_buffer_delay: Option<std::time::Duration>,
+    jitter_buffer_max_gap: std::time::Duration,
+    unknown_payload_type_policy: UnknownPayloadTypePolicy,
 }
 
 impl RtpReceiverBuilder {
@@ -5875,6 +8362,9 @@ impl RtpReceiverBuilder {
             interceptors: Vec::new(),
             depacketizer_factory: None,
             payload_map: Arc::new(RwLock::new(HashMap::new())),
+            jitter_buffer_delay: None,
+            jitter_buffer_max_gap: crate::rtp::DEFAULT_MAX_TIMESTAMP_GAP,
+            unknown_payload_type_policy: UnknownPayloadTypePolicy::default(),
         }
     }
 
@@ -5897,11 +8387,50 @@ impl RtpReceiverBuilder {
         self
     }
 
+    /// Like [`nack`](Self::nack) with the limits of
+    /// [`DefaultRtpReceiverNackHandler::with_limits`].
+    pub fn nack_with_limits(
+        mut self,
+        max_retries: u32,
+        retry_interval: std::time::Duration,
+        max_depth: usize,
+    ) -> Self {
+        self.interceptors
+            .push(Arc::new(DefaultRtpReceiverNackHandler::with_limits(
+                max_retries,
+                retry_interval,
+                max_depth,
+            )));
+        self
+    }
+
     pub fn interceptor(mut self, interceptor: Arc<dyn RtpReceiverInterceptor>) -> Self {
         self.interceptors.push(interceptor);
         self
     }
 
+    /// Reorder incoming packets by sequence number before depacketizing,
+    /// waiting up to `target_delay` for a missing packet. See
+    /// [`RtpJitterBuffer`](crate::rtp::RtpJitterBuffer).
+    pub fn jitter_buffer(mut self, target_delay: std::time::Duration) -> Self {
+        self.jitter_buffer_delay = Some(target_delay);
+        self
+    }
+
+    /// Timestamp jump after which the jitter buffer starts a new talkspurt
+    /// instead of waiting for missing packets, e.g. longer for codecs whose
+    /// senders pause for long stretches. See
+    /// [`RtpJitterBuffer::set_max_timestamp_gap`](crate::rtp::RtpJitterBuffer::set_max_timestamp_gap).
+    pub fn jitter_buffer_max_timestamp_gap(mut self, gap: std::time::Duration) -> Self {
+        self.jitter_buffer_max_gap = gap;
+        self
+    }
+
+    pub fn unknown_payload_type_policy(mut self, policy: UnknownPayloadTypePolicy) -> Self {
+        self.unknown_payload_type_policy = policy;
+        self
+    }
+
     pub fn build(self) -> Arc<RtpReceiver> {
         let media_kind = match self.kind {
             MediaKind::Audio => crate::media::frame::MediaKind::Audio,
@@ -5935,6 +8464,7 @@ impl RtpReceiverBuilder {
             rtcp_feedback_ssrc: Mutex::new(None),
             rtx_ssrc: Mutex::new(None),
             rtx_apt: Mutex::new(HashMap::new()),
+            flexfec: Mutex::new(None),
             fir_seq: AtomicU8::new(0),
             feedback_rx: Arc::new(tokio::sync::Mutex::new(feedback_rx)),
             simulcast_tracks: Mutex::new(HashMap::new()),
@@ -5943,8 +8473,28 @@ impl RtpReceiverBuilder {
             track_ready_event_tx: Mutex::new(None),
             track_ready_transceiver: Mutex::new(None),
             track_event_sent: AtomicBool::new(false),
+            packets_received: AtomicU64::new(0),
+            first_packet_received: watch::Sender::new(false),
+            paused: AtomicBool::new(false),
             clock_rate_cache_pt: AtomicU8::new(u8::MAX),
             clock_rate_cache: AtomicU32::new(0),
+            payload_map_version: AtomicU32::new(0),
+            retired_payload_types: Mutex::new(HashSet::new()),
+            video_orientation_ext_id: AtomicU8::new(0),
+            video_orientation: AtomicU8::new(0),
+            frame_marking_ext_id: AtomicU8::new(0),
+            rid_ext_id: AtomicU8::new(0),
+            selected_rid: Mutex::new(None),
+            layer_rids: Mutex::new(HashMap::new()),
+            codec_name: Mutex::new(None),
+            payload_codecs: Mutex::new(HashMap::new()),
+            payload_fmtp: Mutex::new(HashMap::new()),
+            payload_codecs_version: AtomicU32::new(0),
+            sender_reports: Mutex::new(HashMap::new()),
+            jitter_buffer_delay: self.jitter_buffer_delay,
+            jitter_buffer_max_gap: self.jitter_buffer_max_gap,
+            unknown_payload_type_policy: self.unknown_payload_type_policy,
+            unknown_payload_type_packets: AtomicU64::new(0),
             depacketizer_factory: self.depacketizer_factory.unwrap_or_else(|| {
                 Arc::new(crate::media::depacketizer::DefaultDepacketizerFactory)
             }),
@@ -5990,6 +8540,7 @@ impl RtpReceiver {
             rtcp_feedback_ssrc: Mutex::new(None),
             rtx_ssrc: Mutex::new(None),
             rtx_apt: Mutex::new(HashMap::new()),
+            flexfec: Mutex::new(None),
             fir_seq: AtomicU8::new(0),
             feedback_rx: Arc::new(tokio::sync::Mutex::new(feedback_rx)),
             simulcast_tracks: Mutex::new(HashMap::new()),
@@ -5998,13 +8549,37 @@ impl RtpReceiver {
             track_ready_event_tx: Mutex::new(None),
             track_ready_transceiver: Mutex::new(None),
             track_event_sent: AtomicBool::new(false),
+            packets_received: AtomicU64::new(0),
+            first_packet_received: watch::Sender::new(false),
+            paused: AtomicBool::new(false),
             clock_rate_cache_pt: AtomicU8::new(u8::MAX),
             clock_rate_cache: AtomicU32::new(0),
+            payload_map_version: AtomicU32::new(0),
+            retired_payload_types: Mutex::new(HashSet::new()),
+            video_orientation_ext_id: AtomicU8::new(0),
+            video_orientation: AtomicU8::new(0),
+            frame_marking_ext_id: AtomicU8::new(0),
+            rid_ext_id: AtomicU8::new(0),
+            selected_rid: Mutex::new(None),
+            layer_rids: Mutex::new(HashMap::new()),
+            codec_name: Mutex::new(None),
+            payload_codecs: Mutex::new(HashMap::new()),
+            payload_fmtp: Mutex::new(HashMap::new()),
+            payload_codecs_version: AtomicU32::new(0),
+            sender_reports: Mutex::new(HashMap::new()),
+            jitter_buffer_delay: None,
+            jitter_buffer_max_gap: crate::rtp::DEFAULT_MAX_TIMESTAMP_GAP,
+            unknown_payload_type_policy: UnknownPayloadTypePolicy::default(),
+            unknown_payload_type_packets: AtomicU64::new(0),
             depacketizer_factory: Arc::new(crate::media::depacketizer::DefaultDepacketizerFactory),
         }
     }
 
     pub fn add_simulcast_track(self: &Arc<Self>, rid: String) -> Arc<SampleStreamTrack> {
+        // Renegotiation keeps the tracks of layers that are already set up.
+        if let Some((_, track, _, _)) = self.simulcast_tracks.lock().get(&rid) {
+            return track.clone();
+        }
         let (source, track, feedback_rx) =
             sample_track(self.track.kind(), RTP_RECEIVER_SAMPLE_CAPACITY);
         let source = Arc::new(source);
@@ -6041,6 +8616,32 @@ impl RtpReceiver {
         self.track.clone()
     }
 
+    /// Media kind of the received track, known as soon as the m-line is
+    /// negotiated.
+    pub fn kind(&self) -> crate::media::frame::MediaKind {
+        self.track.kind()
+    }
+
+    /// Encoding name of the negotiated primary codec (`a=rtpmap`), ignoring
+    /// RTX, RED, FEC and DTMF payload types. `None` until negotiated.
+    pub fn codec_name(&self) -> Option<String> {
+        self.codec_name.lock().clone()
+    }
+
+    fn set_codec_name(&self, name: Option<String>) {
+        *self.codec_name.lock() = name;
+    }
+
+    fn set_payload_codecs(&self, codecs: HashMap<u8, String>) {
+        *self.payload_codecs.lock() = codecs;
+        self.payload_codecs_version.fetch_add(1, Ordering::Release);
+    }
+
+    fn set_payload_fmtp(&self, fmtp: HashMap<u8, String>) {
+        *self.payload_fmtp.lock() = fmtp;
+        self.payload_codecs_version.fetch_add(1, Ordering::Release);
+    }
+
     pub fn nack_handler(&self) -> Option<Arc<dyn NackStats>> {
         for i in &self.interceptors {
             if let Some(stats) = i.clone().as_nack_stats() {
@@ -6068,6 +8669,50 @@ impl RtpReceiver {
         *self.ssrc.lock()
     }
 
+    /// RTP packets received on this m-line so far.
+    pub fn packets_received(&self) -> u64 {
+        self.packets_received.load(Ordering::Relaxed)
+    }
+
+    /// Stop delivering media to the track and its simulcast layers, without
+    /// renegotiating. Packets are still received and reported on in RTCP,
+    /// so the SSRC and receive state are kept.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Deliver media again after [`pause`](Self::pause). Video receivers
+    /// request a key frame, as the frames skipped while paused are gone.
+    pub async fn resume(&self) {
+        if !self.paused.swap(false, Ordering::Relaxed)
+            || self.track.kind() != crate::media::frame::MediaKind::Video
+        {
+            return;
+        }
+        let layers: Vec<_> = self
+            .simulcast_tracks
+            .lock()
+            .values()
+            .map(|(_, track, _, _)| track.clone())
+            .collect();
+        for track in std::iter::once(self.track.clone()).chain(layers) {
+            if let Err(e) = track.request_key_frame().await {
+                debug!("Key frame request on resume failed: {}", e);
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// RTP packets received with a payload type that was not negotiated,
+    /// whether dropped or accepted under the configured
+    /// [`UnknownPayloadTypePolicy`].
+    pub fn unknown_payload_type_packets(&self) -> u64 {
+        self.unknown_payload_type_packets.load(Ordering::Relaxed)
+    }
+
     pub fn packet_tx(&self) -> Option<mpsc::Sender<(crate::rtp::RtpPacket, std::net::SocketAddr)>> {
         self.packet_tx.lock().clone()
     }
@@ -6081,34 +8726,242 @@ impl RtpReceiver {
             .unwrap_or_else(|| self.params.lock().clone())
     }
 
+    /// Whether `payload_type` may reach the track under `unknown_payload_type_policy`.
+    fn accepts_payload_type(&self, payload_type: u8) -> bool {
+        {
+            let payload_map = self.payload_map.read();
+            if payload_map.is_empty() || payload_map.contains_key(&payload_type) {
+                return true;
+            }
+        }
+        // Retired payload types were negotiated once; `deliver` discards them.
+        if self.params.lock().payload_type == payload_type
+            || self.retired_payload_types.lock().contains(&payload_type)
+        {
+            return true;
+        }
+
+        let accept = self.unknown_payload_type_policy == UnknownPayloadTypePolicy::AcceptAndLog;
+        if self
+            .unknown_payload_type_packets
+            .fetch_add(1, Ordering::Relaxed)
+            == 0
+        {
+            warn!(
+                "RTP receiver: payload type {} was not negotiated, {}",
+                payload_type,
+                if accept { "accepting" } else { "dropping" }
+            );
+        } else {
+            trace!("RTP receiver: unnegotiated payload type {}", payload_type);
+        }
+        accept
+    }
+
     /// Lock-free clock-rate lookup for the per-packet receive path. The
-    /// payload-type → clock-rate mapping is immutable after SDP negotiation,
-    /// so a couple of atomic loads replace a RwLock + Mutex acquisition on
-    /// every RTP packet. Misses populate the cache from the slow path.
-    fn clock_rate_for_payload_type(&self, payload_type: u8) -> u32 {
+    /// payload-type → clock-rate mapping only changes on renegotiation, so a
+    /// couple of atomic loads replace a RwLock + Mutex acquisition on every
+    /// RTP packet. Misses populate the cache from the slow path.
+    ///
+    /// Returns `None` for a payload type that renegotiation removed from the
+    /// payload map, e.g. a packet still in flight when a re-INVITE switched PT.
+    fn clock_rate_for_payload_type(&self, payload_type: u8) -> Option<u32> {
         let cached_pt = self.clock_rate_cache_pt.load(Ordering::Relaxed);
         if cached_pt == payload_type {
             let cached = self.clock_rate_cache.load(Ordering::Relaxed);
             if cached != 0 {
-                return cached;
+                return Some(cached);
             }
         }
-        let rate = self
-            .payload_map
-            .read()
-            .get(&payload_type)
-            .map(|p| p.clock_rate)
-            .unwrap_or_else(|| self.params.lock().clock_rate);
+        // Fill the cache while holding the read lock so a concurrent
+        // `update_payload_map` (which invalidates after writing) always wins.
+        let payload_map = self.payload_map.read();
+        let rate = match payload_map.get(&payload_type) {
+            Some(params) => params.clock_rate,
+            None if self.retired_payload_types.lock().contains(&payload_type) => return None,
+            None => self.params.lock().clock_rate,
+        };
         self.clock_rate_cache_pt
             .store(payload_type, Ordering::Relaxed);
         self.clock_rate_cache.store(rate, Ordering::Relaxed);
-        rate
+        Some(rate)
+    }
+
+    /// Record the payload types a renegotiation removed and forget the cached
+    /// clock rate. Called with the payload map write lock held.
+    fn payload_map_replaced(
+        &self,
+        old: &HashMap<u8, RtpCodecParameters>,
+        new: &HashMap<u8, RtpCodecParameters>,
+    ) {
+        let mut retired = self.retired_payload_types.lock();
+        retired.extend(old.keys().copied());
+        retired.retain(|pt| !new.contains_key(pt));
+        self.clock_rate_cache.store(0, Ordering::Relaxed);
+        self.payload_map_version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Hand the payload map to the interceptors for a newly seen SSRC, or
+    /// again once renegotiation replaced it.
+    fn announce_payload_map(&self, announced: &mut Option<(u32, u32)>, ssrc: u32) {
+        let version = self.payload_map_version.load(Ordering::Acquire);
+        if *announced == Some((ssrc, version)) {
+            return;
+        }
+        *announced = Some((ssrc, version));
+        let payload_map = self.payload_map.read();
+        for interceptor in &self.interceptors {
+            interceptor.on_payload_map(ssrc, &payload_map);
+        }
     }
 
     pub fn rtx_ssrc(&self) -> Option<u32> {
         *self.rtx_ssrc.lock()
     }
 
+    pub fn set_video_orientation_extension_id(&self, ext_id: Option<u8>) {
+        self.video_orientation_ext_id
+            .store(ext_id.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn set_frame_marking_extension_id(&self, ext_id: Option<u8>) {
+        self.frame_marking_ext_id
+            .store(ext_id.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn set_rid_extension_id(&self, ext_id: Option<u8>) {
+        self.rid_ext_id
+            .store(ext_id.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Forward only the simulcast layer tagged `rid` to the main track.
+    /// Packets are matched by their `sdes:rtp-stream-id` extension, or by the
+    /// SSRC an earlier tagged packet tied to a RID.
+    pub fn select_rid(&self, rid: impl Into<String>) {
+        *self.selected_rid.lock() = Some(rid.into());
+    }
+
+    /// Stop filtering: every layer reaches the main track again.
+    pub fn clear_selected_rid(&self) {
+        *self.selected_rid.lock() = None;
+    }
+
+    pub fn selected_rid(&self) -> Option<String> {
+        self.selected_rid.lock().clone()
+    }
+
+    /// RIDs seen on inbound packets so far, sorted.
+    pub fn available_rids(&self) -> Vec<String> {
+        let mut rids: Vec<String> = self.layer_rids.lock().values().cloned().collect();
+        rids.sort();
+        rids.dedup();
+        rids
+    }
+
+    /// RID of the layer `packet` belongs to, learning the SSRC mapping from
+    /// tagged packets.
+    fn layer_rid(&self, packet: &crate::rtp::RtpPacket) -> Option<String> {
+        let ext_id = self.rid_ext_id.load(Ordering::Relaxed);
+        let mut layer_rids = self.layer_rids.lock();
+        if ext_id != 0
+            && let Some(rid) = packet.header.get_extension(ext_id)
+            && let Ok(rid) = std::str::from_utf8(&rid)
+            && !rid.is_empty()
+        {
+            if layer_rids.get(&packet.header.ssrc).map(String::as_str) != Some(rid) {
+                layer_rids.insert(packet.header.ssrc, rid.to_string());
+            }
+            return Some(rid.to_string());
+        }
+        layer_rids.get(&packet.header.ssrc).cloned()
+    }
+
+    /// Whether a packet on the main track passes the RID selection.
+    fn accepts_layer(&self, packet: &crate::rtp::RtpPacket) -> bool {
+        let rid = self.layer_rid(packet);
+        match self.selected_rid.lock().as_deref() {
+            Some(selected) => rid.as_deref() == Some(selected),
+            None => true,
+        }
+    }
+
+    /// Stamp the rotation/flip signalled via CVO onto depacketized video frames.
+    fn apply_video_orientation(&self, samples: &mut [crate::media::MediaSample]) {
+        let ext_id = self.video_orientation_ext_id.load(Ordering::Relaxed);
+        if ext_id == 0 {
+            return;
+        }
+        for sample in samples {
+            if let crate::media::MediaSample::Video(frame) = sample {
+                if let Some(ext) = &frame.header_extension
+                    && let Some(cvo) = ext.get(ext_id)
+                    && let Some(&byte) = cvo.first()
+                {
+                    self.video_orientation.store(byte, Ordering::Relaxed);
+                }
+                frame.set_video_orientation(self.video_orientation.load(Ordering::Relaxed));
+            }
+        }
+    }
+
+    /// Expose the frame marking extension of incoming video packets on the
+    /// frames built from them.
+    fn apply_frame_marking(&self, samples: &mut [crate::media::MediaSample]) {
+        let ext_id = self.frame_marking_ext_id.load(Ordering::Relaxed);
+        if ext_id == 0 {
+            return;
+        }
+        for sample in samples {
+            if let crate::media::MediaSample::Video(frame) = sample {
+                frame.frame_marking = frame
+                    .header_extension
+                    .as_ref()
+                    .and_then(|ext| ext.get(ext_id))
+                    .and_then(|data| crate::rtp::FrameMarking::parse(&data));
+            }
+        }
+    }
+
+    /// Record the NTP/RTP mapping of a Sender Report for one of this
+    /// receiver's streams. Returns `false` if the SR is for another SSRC.
+    fn deliver_sender_report(&self, sr: &SenderReport) -> bool {
+        let ssrc = sr.sender_ssrc;
+        if ssrc == 0 || (*self.ssrc.lock() != ssrc && !self.layer_rids.lock().contains_key(&ssrc)) {
+            return false;
+        }
+        self.sender_reports
+            .lock()
+            .insert(ssrc, (sr.rtp_timestamp, sr.ntp_time()));
+        true
+    }
+
+    /// Stamp sender capture time onto depacketized frames using the last SR
+    /// received for `ssrc`. Frames before the first SR keep `None`.
+    fn apply_capture_time(
+        &self,
+        ssrc: u32,
+        clock_rate: u32,
+        samples: &mut [crate::media::MediaSample],
+    ) {
+        if clock_rate == 0 {
+            return;
+        }
+        let Some((sr_rtp, sr_time)) = self.sender_reports.lock().get(&ssrc).copied() else {
+            return;
+        };
+        for sample in samples {
+            let ticks = sample.rtp_timestamp().wrapping_sub(sr_rtp) as i32;
+            let offset =
+                std::time::Duration::from_secs_f64(ticks.unsigned_abs() as f64 / clock_rate as f64);
+            let capture_time = if ticks >= 0 {
+                sr_time.checked_add(offset)
+            } else {
+                sr_time.checked_sub(offset)
+            };
+            sample.set_capture_time(capture_time);
+        }
+    }
+
     pub fn set_ssrc(&self, ssrc: u32) {
         *self.ssrc.lock() = ssrc;
         let transport = self.transport.lock().clone();
@@ -6177,6 +9030,61 @@ impl RtpReceiver {
         }
     }
 
+    /// Enable FlexFEC recovery for repair packets on `payload_type` (and, when
+    /// announced by `a=ssrc-group:FEC-FR`, on `fec_ssrc`). `None` disables it.
+    pub fn set_flexfec(&self, payload_type: Option<u8>, fec_ssrc: Option<u32>) {
+        let Some(payload_type) = payload_type else {
+            *self.flexfec.lock() = None;
+            return;
+        };
+        {
+            let mut flexfec = self.flexfec.lock();
+            let unchanged = flexfec.as_ref().is_some_and(|decoder| {
+                decoder.payload_type() == payload_type && decoder.fec_ssrc() == fec_ssrc
+            });
+            if !unchanged {
+                *flexfec = Some(crate::flexfec::FlexFecDecoder::new(payload_type, fec_ssrc));
+            }
+        }
+        let transport = self.transport.lock().clone();
+        let packet_tx = self.packet_tx.lock().clone();
+        if let Some(fec_ssrc) = fec_ssrc
+            && let Some(transport) = transport
+            && let Some(tx) = packet_tx
+        {
+            transport.register_listener_sync(fec_ssrc, tx);
+        }
+    }
+
+    pub fn flexfec_active(&self) -> bool {
+        self.flexfec.lock().is_some()
+    }
+
+    /// Media packets rebuilt from FlexFEC repair packets.
+    pub fn flexfec_recovered_packets(&self) -> u64 {
+        self.flexfec
+            .lock()
+            .as_ref()
+            .map_or(0, |decoder| decoder.recovered_count())
+    }
+
+    /// Feed `packet` to the FlexFEC decoder. Returns the packet to keep
+    /// processing (`None` for a repair packet) and any media packets it made
+    /// recoverable.
+    fn recover_flexfec(&self, packet: RtpPacket) -> (Option<RtpPacket>, Vec<RtpPacket>) {
+        let mut flexfec = self.flexfec.lock();
+        let Some(decoder) = flexfec.as_mut() else {
+            return (Some(packet), Vec::new());
+        };
+        if decoder.is_repair(&packet) {
+            let recovered = decoder.on_repair(&packet);
+            (None, recovered)
+        } else {
+            let recovered = decoder.on_media(&packet);
+            (Some(packet), recovered)
+        }
+    }
+
     /// If `packet` is an RTX retransmission for this receiver, unwrap it to the
     /// primary media packet. Returns `None` when the packet is RTX but cannot be
     /// safely restored (unknown primary SSRC, unrecognized payload type, or
@@ -6308,6 +9216,9 @@ impl RtpReceiver {
         if let Some(rtx_ssrc) = *self.rtx_ssrc.lock() {
             transport.register_listener_sync(rtx_ssrc, tx.clone());
         }
+        if let Some(fec_ssrc) = self.flexfec.lock().as_ref().and_then(|d| d.fec_ssrc()) {
+            transport.register_listener_sync(fec_ssrc, tx.clone());
+        }
         debug!(
             transport_id = format_args!("{:p}", Arc::as_ptr(&transport)),
             transceiver_id = route_transceiver.as_ref().map(|t| t.id()),
@@ -6351,15 +9262,83 @@ impl RtpReceiver {
         });
     }
 
+    /// Depacketize one packet and hand the samples to `source`, and to the
+    /// main track as well when `rid` is the selected simulcast layer.
+    fn deliver(
+        &self,
+        depacketizer: &mut ReceiveDepacketizer,
+        factory: &Arc<dyn DepacketizerFactory>,
+        source: &SampleStreamSource,
+        rid: &Option<String>,
+        packet: crate::rtp::RtpPacket,
+        addr: std::net::SocketAddr,
+    ) {
+        if self.paused.load(Ordering::Relaxed) {
+            return;
+        }
+        let payload_type = packet.header.payload_type;
+        let clock_rate = self.clock_rate_for_payload_type(payload_type);
+        if clock_rate.is_none() {
+            trace!(
+                "Dropping RTP packet with retired payload type {}",
+                payload_type
+            );
+        }
+
+        depacketizer.select(self, factory, payload_type, source.kind());
+        // Track depacketizer drop count changes
+        let prev_drop = depacketizer.depacketizer.drop_count();
+        let media_ssrc = packet.header.ssrc;
+        // Fix: Use Depacketizer to handle frames correctly
+        let pushed = clock_rate.map(|clock_rate| {
+            if depacketizer.telephone_event {
+                PassThroughDepacketizer.push(packet, clock_rate, addr, source.kind())
+            } else {
+                depacketizer
+                    .depacketizer
+                    .push(packet, clock_rate, addr, source.kind())
+            }
+        });
+        if let Some(clock_rate) = clock_rate
+            && let Some(Ok(mut samples)) = pushed
+        {
+            if depacketizer.depacketizer.drop_count() > prev_drop {
+                source.increment_drop_count();
+            }
+            self.apply_video_orientation(&mut samples);
+            self.apply_frame_marking(&mut samples);
+            self.apply_capture_time(media_ssrc, clock_rate, &mut samples);
+            // The selected layer's own track also feeds the main track.
+            if let Some(layer) = rid
+                && self.selected_rid.lock().as_ref() == Some(layer)
+                && let Err(e) = self.source.send_many(samples.clone())
+            {
+                tracing::warn!("Failed to send media sample batch: {}", e);
+            }
+            if let Err(e) = source.send_many(samples) {
+                tracing::warn!("Failed to send media sample batch: {}", e);
+            }
+        }
+    }
+
     async fn run_loop(
         weak_self: Weak<Self>,
         mut cmd_rx: mpsc::UnboundedReceiver<ReceiverCommand>,
         initial_tracks: Vec<ReceiverCommand>,
     ) {
-        let depacketizer_factory = if let Some(receiver) = weak_self.upgrade() {
-            receiver.depacketizer_factory.clone()
+        // Jitter buffer target delay and maximum timestamp gap, if enabled.
+        let (depacketizer_factory, jitter_config) = if let Some(receiver) = weak_self.upgrade() {
+            (
+                receiver.depacketizer_factory.clone(),
+                receiver
+                    .jitter_buffer_delay
+                    .map(|delay| (delay, receiver.jitter_buffer_max_gap)),
+            )
         } else {
-            Arc::new(crate::media::depacketizer::DefaultDepacketizerFactory)
+            (
+                Arc::new(crate::media::depacketizer::DefaultDepacketizerFactory) as _,
+                None,
+            )
         };
 
         let mut futures = FuturesUnordered::new();
@@ -6377,6 +9356,7 @@ impl RtpReceiver {
                 ),
             >,
             depacketizer_factory: &Arc<dyn DepacketizerFactory>,
+            jitter_config: Option<(std::time::Duration, std::time::Duration)>,
         ) {
             let ReceiverCommand::AddTrack {
                 rid,
@@ -6391,15 +9371,23 @@ impl RtpReceiver {
                 (source.clone(), simulcast_ssrc, feedback_rx.clone()),
             );
 
-            let rid_clone = rid.clone();
-            // Initialize depacketizer
-            let depacketizer = depacketizer_factory.create(source.kind());
-
-            futures.push(Box::pin(async move {
-                let mut rx = packet_rx;
-                let packet = rx.recv().await;
-                LoopEvent::Packet(packet, rid_clone, rx, depacketizer)
-            }));
+            // Initialize depacketizer; replaced by a codec-specific one once
+            // the first packet's payload type is known.
+            let depacketizer = ReceiveDepacketizer {
+                depacketizer: depacketizer_factory.create(source.kind()),
+                codec: None,
+                payload_type: None,
+                codecs_version: 0,
+                jitter: jitter_config.map(|(delay, max_gap)| {
+                    let mut jitter =
+                        crate::rtp::RtpJitterBuffer::new(delay, RTP_RECEIVER_JITTER_CAPACITY);
+                    jitter.set_max_timestamp_gap(max_gap);
+                    jitter
+                }),
+                telephone_event: false,
+                payload_map_announced: None,
+            };
+            futures.push(next_packet_event(rid.clone(), packet_rx, depacketizer));
 
             let rid_clone = rid.clone();
             futures.push(Box::pin(async move {
@@ -6412,14 +9400,20 @@ impl RtpReceiver {
         }
 
         for cmd in initial_tracks {
-            handle_add_track(cmd, &mut futures, &mut tracks, &depacketizer_factory);
+            handle_add_track(
+                cmd,
+                &mut futures,
+                &mut tracks,
+                &depacketizer_factory,
+                jitter_config,
+            );
         }
 
         loop {
             tokio::select! {
                 cmd = cmd_rx.recv() => {
                     match cmd {
-                        Some(cmd) => handle_add_track(cmd, &mut futures, &mut tracks, &depacketizer_factory),
+                        Some(cmd) => handle_add_track(cmd, &mut futures, &mut tracks, &depacketizer_factory, jitter_config),
                         None => break,
                     }
                 }
@@ -6433,21 +9427,51 @@ impl RtpReceiver {
                                     let Some(this) = weak_self.upgrade() else {
                                         break;
                                     };
+                                    if this.packets_received.fetch_add(1, Ordering::Relaxed) == 0 {
+                                        this.first_packet_received.send_replace(true);
+                                    }
                                     let Some(packet) = this.maybe_unwrap_rtx(packet) else {
                                         // Dropped truncated/unrestorable RTX — keep listening.
-                                        let rid_clone = rid.clone();
-                                        futures.push(Box::pin(async move {
-                                            let mut rx = packet_rx;
-                                            let packet = rx.recv().await;
-                                            LoopEvent::Packet(packet, rid_clone, rx, depacketizer)
-                                        }));
+                                        futures.push(next_packet_event(rid.clone(), packet_rx, depacketizer));
+                                        continue;
+                                    };
+
+                                    // Recovered packets re-enter the loop through the main
+                                    // track's channel like any other arrival.
+                                    let packet = if rid.is_none() {
+                                        let (packet, recovered) = this.recover_flexfec(packet);
+                                        if !recovered.is_empty()
+                                            && let Some(tx) = this.packet_tx()
+                                        {
+                                            for recovered in recovered {
+                                                let _ = tx.try_send((recovered, addr));
+                                            }
+                                        }
+                                        packet
+                                    } else {
+                                        Some(packet)
+                                    };
+                                    let Some(packet) = packet else {
+                                        futures.push(next_packet_event(rid.clone(), packet_rx, depacketizer));
                                         continue;
                                     };
 
-                                    if rid.is_some() {
+                                    if rid.is_none() && !this.accepts_layer(&packet) {
+                                        futures.push(next_packet_event(rid.clone(), packet_rx, depacketizer));
+                                        continue;
+                                    }
+                                    if !this.accepts_payload_type(packet.header.payload_type) {
+                                        futures.push(next_packet_event(rid.clone(), packet_rx, depacketizer));
+                                        continue;
+                                    }
+
+                                    if let Some(layer) = &rid {
                                         let mut s = simulcast_ssrc.lock();
                                         if s.is_none() {
                                             *s = Some(packet.header.ssrc);
+                                            this.layer_rids
+                                                .lock()
+                                                .insert(packet.header.ssrc, layer.clone());
                                         }
                                     } else {
                                         // Main track: latch the primary SSRC. `maybe_unwrap_rtx`
@@ -6495,6 +9519,10 @@ impl RtpReceiver {
                                         .as_ref()
                                         .map(|t| t.local_addr())
                                         .unwrap_or(std::net::SocketAddr::from(([0, 0, 0, 0], 0)));
+                                    this.announce_payload_map(
+                                        &mut depacketizer.payload_map_announced,
+                                        packet.header.ssrc,
+                                    );
                                     for interceptor in &this.interceptors {
                                         if let Some(mut rtcp_packet) =
                                             interceptor
@@ -6520,30 +9548,57 @@ impl RtpReceiver {
                                         }
                                     }
 
-                                    let clock_rate =
-                                        this.clock_rate_for_payload_type(packet.header.payload_type);
-
-                                    // Track depacketizer drop count changes
-                                    let prev_drop = depacketizer.drop_count();
-                                    // Fix: Use Depacketizer to handle frames correctly
-                                    if let Ok(samples) =
-                                        depacketizer.push(packet, clock_rate, addr, source.kind())
-                                    {
-                                        if depacketizer.drop_count() > prev_drop {
-                                            source.increment_drop_count();
-                                        }
-                                        if let Err(e) = source.send_many(samples) {
-                                            tracing::warn!("Failed to send media sample batch: {}", e);
+                                    let now = std::time::Instant::now();
+                                    match depacketizer.jitter.as_mut() {
+                                        Some(jitter) => {
+                                            if let Some(rate) = this
+                                                .clock_rate_for_payload_type(packet.header.payload_type)
+                                            {
+                                                jitter.set_clock_rate(rate);
+                                            }
+                                            jitter.push(packet, addr, now);
                                         }
+                                        None => this.deliver(
+                                            &mut depacketizer,
+                                            &depacketizer_factory,
+                                            source,
+                                            &rid,
+                                            packet,
+                                            addr,
+                                        ),
+                                    }
+                                    while let Some((packet, addr)) = depacketizer.release(now) {
+                                        this.deliver(
+                                            &mut depacketizer,
+                                            &depacketizer_factory,
+                                            source,
+                                            &rid,
+                                            packet,
+                                            addr,
+                                        );
                                     }
 
-                                    let rid_clone = rid.clone();
-                                    futures.push(Box::pin(async move {
-                                        let mut rx = packet_rx;
-                                        let packet = rx.recv().await;
-                                        LoopEvent::Packet(packet, rid_clone, rx, depacketizer)
-                                    }));
+                                    futures.push(next_packet_event(rid.clone(), packet_rx, depacketizer));
+                                }
+                            }
+                            LoopEvent::JitterRelease(rid, packet_rx, mut depacketizer) => {
+                                let Some(this) = weak_self.upgrade() else {
+                                    break;
+                                };
+                                if let Some((source, _, _)) = tracks.get(&rid) {
+                                    let now = std::time::Instant::now();
+                                    while let Some((packet, addr)) = depacketizer.release(now) {
+                                        this.deliver(
+                                            &mut depacketizer,
+                                            &depacketizer_factory,
+                                            source,
+                                            &rid,
+                                            packet,
+                                            addr,
+                                        );
+                                    }
                                 }
+                                futures.push(next_packet_event(rid, packet_rx, depacketizer));
                             }
                             LoopEvent::Feedback(event_opt, rid) => {
                                 if let Some(event) = event_opt
@@ -6673,6 +9728,36 @@ mod tests {
     const SCTP_FORMAT: &str = "webrtc-datachannel";
     const SCTP_PORT: u16 = 5000;
 
+    #[test]
+    fn rtp_codec_parameters_timestamp_conversion() {
+        use std::time::Duration;
+
+        let opus = RtpCodecParameters {
+            payload_type: AUDIO_PAYLOAD_TYPE,
+            clock_rate: 48000,
+            channels: 2,
+        };
+        assert_eq!(opus.ts_from_duration(Duration::from_millis(20)), 960);
+        assert_eq!(opus.ts_from_duration(Duration::from_secs(1)), 48000);
+        assert_eq!(opus.duration_from_ts_delta(960), Duration::from_millis(20));
+
+        let video = RtpCodecParameters::default();
+        assert_eq!(video.clock_rate, 90000);
+        assert_eq!(video.ts_from_duration(Duration::from_secs(1) / 30), 3000);
+        assert_eq!(video.ts_from_duration(Duration::from_secs(10)), 900_000);
+        assert_eq!(
+            video.duration_from_ts_delta(3000),
+            Duration::from_nanos(33_333_333)
+        );
+        assert_eq!(video.duration_from_ts_delta(90000), Duration::from_secs(1));
+
+        let unset = RtpCodecParameters {
+            clock_rate: 0,
+            ..Default::default()
+        };
+        assert_eq!(unset.duration_from_ts_delta(1000), Duration::ZERO);
+    }
+
     #[tokio::test]
     async fn repro_pair_monitor_must_not_override_latched_rtp_remote() {
         use crate::transports::PacketReceiver;
@@ -6788,8 +9873,108 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn offer_includes_video_capabilities() {
-        let pc = PeerConnection::new(RtcConfiguration::default());
+    async fn gathered_candidates_are_trickled_before_gathering_completes() {
+        // An unreachable STUN server keeps gathering open until stun_timeout.
+        let pc = PeerConnection::new(RtcConfiguration {
+            ice_servers: vec![crate::IceServer::new(vec![
+                "stun:192.0.2.1:3478".to_string(),
+            ])],
+            stun_timeout: std::time::Duration::from_millis(500),
+            ..Default::default()
+        });
+        pc.add_transceiver(MediaKind::Audio, TransceiverDirection::SendRecv);
+
+        // The offer is available right away and advertises trickle support.
+        let offer = pc.create_offer().await.unwrap();
+        let attrs = &offer.media_sections[0].attributes;
+        assert!(
+            attrs
+                .iter()
+                .any(|a| a.key == "ice-options" && a.value.as_deref() == Some("trickle"))
+        );
+
+        // Host candidates go out while the STUN probe is still pending.
+        let gathering = pc.subscribe_ice_gathering_state();
+        let mut trickled = Vec::new();
+        loop {
+            let event = tokio::time::timeout(std::time::Duration::from_secs(2), pc.recv())
+                .await
+                .expect("a host candidate should be trickled");
+            if let Some(PeerConnectionEvent::IceCandidate(candidate)) = event {
+                assert_ne!(*gathering.borrow(), IceGatheringState::Complete);
+                trickled.push(candidate);
+                break;
+            }
+        }
+
+        // Every candidate event is queued before the gathering state flips to
+        // Complete, so all of them are already waiting once it has.
+        pc.wait_for_gathering_complete().await;
+        loop {
+            let event = tokio::time::timeout(std::time::Duration::from_millis(100), pc.recv())
+                .await
+                .expect("end-of-candidates should already be queued");
+            match event {
+                Some(PeerConnectionEvent::IceCandidate(candidate)) => trickled.push(candidate),
+                Some(PeerConnectionEvent::EndOfCandidates) => break,
+                Some(_) => {}
+                None => panic!("event channel closed"),
+            }
+        }
+
+        assert!(!trickled.is_empty());
+        assert_eq!(trickled, pc.ice_transport().local_candidates());
+    }
+
+    #[tokio::test]
+    async fn answer_waits_for_gathering_only_without_remote_trickle() {
+        // An unreachable STUN server keeps gathering open until stun_timeout.
+        let slow_gathering = || RtcConfiguration {
+            ice_servers: vec![crate::IceServer::new(vec![
+                "stun:192.0.2.1:3478".to_string(),
+            ])],
+            stun_timeout: std::time::Duration::from_millis(500),
+            ..Default::default()
+        };
+        let offerer = PeerConnection::new(RtcConfiguration::default());
+        offerer.add_transceiver(MediaKind::Audio, TransceiverDirection::SendRecv);
+        let offer = offerer.create_offer().await.unwrap();
+        let offer = SessionDescription::parse(SdpType::Offer, &offer.to_sdp_string()).unwrap();
+        assert_eq!(offer.ice_options(), vec!["trickle".to_string()]);
+
+        let trickle = PeerConnection::new(slow_gathering());
+        trickle.set_remote_description(offer.clone()).await.unwrap();
+        let answer = trickle.create_answer().await.unwrap();
+        assert_ne!(
+            *trickle.subscribe_ice_gathering_state().borrow(),
+            IceGatheringState::Complete
+        );
+        assert!(
+            !answer.media_sections[0]
+                .attributes
+                .iter()
+                .any(|a| a.key == "end-of-candidates")
+        );
+
+        let mut legacy_offer = offer;
+        for section in &mut legacy_offer.media_sections {
+            section.attributes.retain(|a| a.key != "ice-options");
+        }
+        let legacy = PeerConnection::new(slow_gathering());
+        legacy.set_remote_description(legacy_offer).await.unwrap();
+        let answer = legacy.create_answer().await.unwrap();
+        assert_eq!(
+            *legacy.subscribe_ice_gathering_state().borrow(),
+            IceGatheringState::Complete
+        );
+        let attrs = &answer.media_sections[0].attributes;
+        assert!(attrs.iter().any(|a| a.key == "end-of-candidates"));
+        assert!(attrs.iter().any(|a| a.key == "candidate"));
+    }
+
+    #[tokio::test]
+    async fn offer_includes_video_capabilities() {
+        let pc = PeerConnection::new(RtcConfiguration::default());
         pc.add_transceiver(MediaKind::Video, TransceiverDirection::SendRecv);
         let offer = pc.create_offer().await.unwrap();
         let section = &offer.media_sections[0];
@@ -6839,12 +10024,12 @@ mod tests {
                        o=- 123456 0 IN IP4 127.0.0.1\r\n\
                        s=-\r\n\
                        t=0 0\r\n\
-                       a=extmap:3 urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id\r\n\
                        a=fingerprint:sha-256 AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99\r\n\
                        a=setup:passive\r\n\
                        c=IN IP4 127.0.0.1\r\n\
                        m=video 9 RTP/SAVPF 96\r\n\
                        a=rtpmap:96 VP8/90000\r\n\
+                       a=extmap:3 urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id\r\n\
                        a=rid:hi send\r\n\
                        a=rid:mid send\r\n\
                        a=rid:lo send\r\n\
@@ -6852,6 +10037,7 @@ mod tests {
 
         let desc = SessionDescription::parse(SdpType::Offer, sdp_str).unwrap();
         pc.set_remote_description(desc).await.unwrap();
+        pc.create_answer().await.unwrap();
 
         let transceivers = pc.inner.transceivers.lock();
         assert_eq!(transceivers.len(), 1);
@@ -6866,6 +10052,113 @@ mod tests {
         assert_eq!(simulcast_tracks.len(), 3);
     }
 
+    #[tokio::test]
+    async fn answer_echoes_accepted_simulcast_layers() {
+        use crate::{SdpType, SessionDescription};
+        let pc = PeerConnection::new(RtcConfiguration::default());
+
+        let offer = "v=0\r\n\
+                     o=- 123456 0 IN IP4 127.0.0.1\r\n\
+                     s=-\r\n\
+                     t=0 0\r\n\
+                     a=fingerprint:sha-256 AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99\r\n\
+                     a=setup:actpass\r\n\
+                     c=IN IP4 127.0.0.1\r\n\
+                     m=video 9 UDP/TLS/RTP/SAVPF 96 98\r\n\
+                     a=mid:0\r\n\
+                     a=rtpmap:96 VP8/90000\r\n\
+                     a=rtpmap:98 AV1/90000\r\n\
+                     a=extmap:3 urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id\r\n\
+                     a=rid:low send max-width=320\r\n\
+                     a=rid:mid send\r\n\
+                     a=rid:high send pt=98\r\n\
+                     a=simulcast:send low;mid;high\r\n\
+                     a=sendonly\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, offer).unwrap();
+        pc.set_remote_description(desc).await.unwrap();
+        let answer = pc.create_answer().await.unwrap();
+
+        // `high` is restricted to a payload type the answer does not carry.
+        let layers = answer.media_sections[0].simulcast_layers().unwrap();
+        assert!(layers.send.is_empty());
+        let rids: Vec<&str> = layers.recv.iter().map(|l| l.rid.as_str()).collect();
+        assert_eq!(rids, vec!["low", "mid"]);
+        assert_eq!(
+            layers.recv[0].restrictions,
+            vec![("max-width".to_string(), "320".to_string())]
+        );
+
+        let receiver = pc.get_transceivers()[0].receiver().unwrap();
+        let mut tracks = receiver.get_simulcast_rids();
+        tracks.sort();
+        assert_eq!(tracks, vec!["low", "mid"]);
+    }
+
+    #[tokio::test]
+    async fn test_select_rid_filters_main_track() {
+        use crate::media::MediaStreamTrack;
+        use crate::transports::PacketReceiver;
+
+        const RID_EXT_ID: u8 = 4;
+        let (_socket_tx, socket_rx) = tokio::sync::watch::channel(None);
+        let remote: std::net::SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let conn = IceConn::new(socket_rx, remote, None);
+        let transport = Arc::new(RtpTransport::new(conn, false));
+
+        let payload_map = HashMap::from([(
+            0,
+            RtpCodecParameters {
+                payload_type: 0,
+                clock_rate: 8000,
+                channels: 1,
+            },
+        )]);
+        let receiver = RtpReceiverBuilder::new(MediaKind::Audio, 0)
+            .payload_map(Arc::new(RwLock::new(payload_map)))
+            .build();
+        receiver.set_rid_extension_id(Some(RID_EXT_ID));
+        receiver.set_transport(transport.clone(), None, None);
+        receiver.select_rid("hi");
+        let track = receiver.track();
+
+        // Both layers tag their first packets with a RID, then rely on the SSRC.
+        let mut buf = Vec::new();
+        for seq in 0..6u16 {
+            for (ssrc, rid, fill) in [(0x2222, "lo", 0xBB), (0x1111, "hi", 0xAA)] {
+                let mut header = crate::rtp::RtpHeader::new(0, seq, seq as u32 * 160, ssrc);
+                if seq < 2 {
+                    header.set_extension(RID_EXT_ID, rid.as_bytes()).unwrap();
+                }
+                let packet = crate::rtp::RtpPacket::new(header, vec![fill; 160]);
+                transport
+                    .receive(
+                        bytes::Bytes::from(packet.marshal().unwrap()),
+                        remote,
+                        &mut buf,
+                    )
+                    .await;
+            }
+        }
+
+        for _ in 0..6 {
+            let sample = tokio::time::timeout(std::time::Duration::from_secs(1), track.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let crate::media::MediaSample::Audio(frame) = sample else {
+                panic!("expected audio");
+            };
+            assert!(frame.data.iter().all(|&b| b == 0xAA), "only the hi layer");
+        }
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), track.recv())
+                .await
+                .is_err()
+        );
+        assert_eq!(receiver.available_rids(), vec!["hi", "lo"]);
+        assert_eq!(receiver.selected_rid().as_deref(), Some("hi"));
+    }
+
     #[tokio::test]
     async fn test_rtcp_mux_detection() {
         use crate::{SdpType, SessionDescription, TransportMode};
@@ -7159,6 +10452,20 @@ mod tests {
         assert_eq!(pc.signaling_state(), SignalingState::Stable);
     }
 
+    #[tokio::test]
+    async fn create_answer_on_fresh_pc_names_missing_remote_description() {
+        let pc = PeerConnection::new(RtcConfiguration::default());
+        match pc.create_answer().await {
+            Err(RtcError::InvalidState(msg)) => {
+                assert!(msg.contains("no remote description"), "{msg}");
+                assert!(msg.contains("set_remote_description"), "{msg}");
+            }
+            other => panic!("expected InvalidState, got {other:?}"),
+        }
+        assert_eq!(pc.signaling_state(), SignalingState::Stable);
+        assert!(pc.local_description().is_none());
+    }
+
     #[tokio::test]
     async fn remote_answer_without_local_offer_is_error() {
         let pc = PeerConnection::new(RtcConfiguration::default());
@@ -7257,6 +10564,57 @@ mod tests {
         assert!(section.attributes.iter().any(|a| a.key == "crypto"));
     }
 
+    #[tokio::test]
+    async fn dropped_create_offer_does_not_break_next_offer() {
+        use crate::TransportMode;
+        use futures::FutureExt;
+
+        for mode in [TransportMode::Rtp, TransportMode::Srtp] {
+            let mut config = RtcConfiguration::default();
+            config.transport_mode = mode.clone();
+            config.bind_ip = Some("127.0.0.1".to_string());
+            let pc = PeerConnection::new(config);
+            pc.add_transceiver(MediaKind::Audio, TransceiverDirection::SendRecv);
+
+            // Poll once, then drop the future mid-gather.
+            assert!(pc.create_offer().now_or_never().is_none_or(|r| r.is_ok()));
+
+            let offer = tokio::time::timeout(std::time::Duration::from_secs(2), pc.create_offer())
+                .await
+                .expect("create_offer after a cancelled one should not hang")
+                .unwrap();
+            if mode == TransportMode::Rtp {
+                let rtp_candidates: Vec<_> = pc
+                    .inner
+                    .ice_transport
+                    .local_candidates()
+                    .into_iter()
+                    .filter(|c| c.component == 1)
+                    .collect();
+                assert_eq!(rtp_candidates.len(), 1, "no second socket is bound");
+                assert_eq!(
+                    offer.media_sections[0].port,
+                    rtp_candidates[0].address.port()
+                );
+            }
+            pc.set_local_description(offer).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn create_offer_until_stops_waiting_for_candidates() {
+        use crate::TransportMode;
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Srtp;
+        let pc = PeerConnection::new(config);
+        pc.add_transceiver(MediaKind::Audio, TransceiverDirection::SendRecv);
+
+        let start = std::time::Instant::now();
+        let offer = pc.create_offer_until(std::future::ready(())).await.unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_millis(400));
+        assert_eq!(offer.media_sections.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_ssrc_parsing_with_fid_group() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -7415,6 +10773,85 @@ a=ssrc-group:FID 12345 67890\r\n";
         assert!(t.sender_rtx_ssrc().is_some());
     }
 
+    #[tokio::test]
+    async fn nack_retransmits_on_the_fid_rtx_ssrc() {
+        use crate::config::{MediaCapabilities, VideoCapability};
+        use crate::media::frame::VideoFrame;
+        use tokio::net::UdpSocket;
+
+        let mut config = RtcConfiguration::default();
+        config.media_capabilities = Some(MediaCapabilities {
+            audio: vec![],
+            video: vec![VideoCapability::vp8_with_rtx(97)],
+            application: None,
+            image: vec![],
+        });
+        let pc = PeerConnection::new(config);
+        let (source, track, _) = sample_track(crate::media::frame::MediaKind::Video, 8);
+        let params = RtpCodecParameters {
+            payload_type: 96,
+            clock_rate: 90000,
+            channels: 0,
+        };
+        let sender = pc.add_track(track, params).unwrap();
+
+        let offer = pc.create_offer().await.unwrap();
+        let fid = offer.media_sections[0]
+            .attributes
+            .iter()
+            .find_map(|a| {
+                a.value
+                    .as_deref()
+                    .filter(|_| a.key == "ssrc-group")?
+                    .strip_prefix("FID ")
+            })
+            .expect("send offer must include a=ssrc-group:FID");
+        let rtx_ssrc = pc.get_transceivers()[0].sender_rtx_ssrc().unwrap();
+        assert_eq!(fid, format!("{} {}", sender.ssrc(), rtx_ssrc));
+
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_socket_tx, socket_rx) = tokio::sync::watch::channel(Some(
+            crate::transports::ice::IceSocketWrapper::Udp(Arc::new(local)),
+        ));
+        let ice_conn = IceConn::new(socket_rx, remote.local_addr().unwrap(), None);
+        sender.set_transport(Arc::new(RtpTransport::new(ice_conn, false)));
+
+        let mut buf = [0u8; 1500];
+        let mut recv = async || {
+            let n = tokio::time::timeout(std::time::Duration::from_secs(1), remote.recv(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            RtpPacket::parse(&buf[..n]).unwrap()
+        };
+
+        source
+            .send_video(VideoFrame {
+                rtp_timestamp: 3000,
+                data: bytes::Bytes::from_static(&[0x10, 0xAA]),
+                is_last_packet: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let original = recv().await;
+        assert_eq!(original.header.ssrc, sender.ssrc());
+
+        sender.deliver_rtcp(RtcpPacket::GenericNack(GenericNack {
+            sender_ssrc: 0,
+            media_ssrc: sender.ssrc(),
+            lost_packets: vec![original.header.sequence_number],
+        }));
+        let retransmission = recv().await;
+        assert_eq!(retransmission.header.ssrc, rtx_ssrc);
+        assert_eq!(retransmission.header.payload_type, 97);
+        assert_eq!(
+            &retransmission.payload[..2],
+            &original.header.sequence_number.to_be_bytes(),
+            "RTX payload starts with the original sequence number"
+        );
+    }
+
     #[tokio::test]
     async fn answer_echoes_remote_rtx_when_offered() {
         let pc = PeerConnection::new(RtcConfiguration::default());
@@ -7456,6 +10893,62 @@ a=ice-pwd:testpassword12345678901\r\n";
         );
     }
 
+    #[tokio::test]
+    async fn receiver_reports_kind_and_codec_before_first_packet() {
+        let pc = PeerConnection::new(RtcConfiguration::default());
+
+        // RTX is listed first; the primary codec is still VP8.
+        let offer_sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+t=0 0\r\n\
+m=video 9 UDP/TLS/RTP/SAVPF 97 96\r\n\
+c=IN IP4 127.0.0.1\r\n\
+a=mid:0\r\n\
+a=sendonly\r\n\
+a=rtpmap:97 rtx/90000\r\n\
+a=fmtp:97 apt=96\r\n\
+a=rtpmap:96 VP8/90000\r\n\
+a=fingerprint:sha-256 AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99\r\n\
+a=setup:actpass\r\n\
+a=ice-ufrag:test\r\n\
+a=ice-pwd:testpassword12345678901\r\n";
+
+        let offer =
+            crate::sdp::SessionDescription::parse(crate::sdp::SdpType::Offer, offer_sdp).unwrap();
+        pc.set_remote_description(offer).await.unwrap();
+
+        let receiver = pc.get_transceivers()[0].receiver().unwrap();
+        assert_eq!(receiver.kind(), crate::media::frame::MediaKind::Video);
+        assert_eq!(receiver.codec_name().as_deref(), Some("VP8"));
+    }
+
+    #[tokio::test]
+    async fn content_and_label_round_trip() {
+        let pc1 = PeerConnection::new(RtcConfiguration::default());
+        let pc2 = PeerConnection::new(RtcConfiguration::default());
+        let t1 = pc1.add_transceiver(MediaKind::Video, TransceiverDirection::SendOnly);
+        t1.set_content(Some("slides".to_string()));
+        t1.set_label(Some("screen".to_string()));
+
+        let offer = pc1.create_offer().await.unwrap();
+        let section = &offer.media_sections[0];
+        assert_eq!(section.content(), Some("slides"));
+        assert_eq!(section.label(), Some("screen"));
+
+        let parsed = crate::sdp::SessionDescription::parse(
+            crate::sdp::SdpType::Offer,
+            &offer.to_sdp_string(),
+        )
+        .unwrap();
+        pc2.set_remote_description(parsed).await.unwrap();
+
+        let t2 = &pc2.get_transceivers()[0];
+        assert_eq!(t2.remote_content().as_deref(), Some("slides"));
+        assert_eq!(t2.remote_label().as_deref(), Some("screen"));
+        assert_eq!(t2.content(), None);
+    }
+
     #[tokio::test]
     async fn answer_does_not_echo_rtx_when_remote_omits_it() {
         use crate::config::{MediaCapabilities, VideoCapability};
@@ -7606,6 +11099,112 @@ a=ssrc:67890 cname:foo\r\n";
         );
     }
 
+    fn flexfec_config(enabled: bool) -> RtcConfiguration {
+        use crate::config::{MediaCapabilities, VideoCapability};
+
+        let video = if enabled {
+            VideoCapability::vp8_with_flexfec(100)
+        } else {
+            VideoCapability::default()
+        };
+        let mut config = RtcConfiguration::default();
+        config.media_capabilities = Some(MediaCapabilities {
+            audio: vec![],
+            video: vec![video],
+            application: None,
+            image: vec![],
+        });
+        config
+    }
+
+    fn has_flexfec_rtpmap(desc: &SessionDescription) -> bool {
+        desc.media_sections[0]
+            .attributes
+            .iter()
+            .any(|a| a.key == "rtpmap" && a.value.as_deref() == Some("100 flexfec-03/90000"))
+    }
+
+    #[tokio::test]
+    async fn flexfec_negotiation_recovers_single_lost_packet() {
+        let offerer = PeerConnection::new(flexfec_config(true));
+        let (_source, track, _) =
+            crate::media::track::sample_track(crate::media::frame::MediaKind::Video, 8);
+        let params = RtpCodecParameters {
+            payload_type: 96,
+            clock_rate: 90000,
+            channels: 0,
+        };
+        offerer.add_track(track, params).unwrap();
+
+        let offer = offerer.create_offer().await.unwrap();
+        assert!(has_flexfec_rtpmap(&offer));
+        let (primary_ssrc, fec_ssrc) =
+            crate::flexfec::parse_fec_fr_group(&offer.media_sections[0].attributes)
+                .expect("send offer must include a=ssrc-group:FEC-FR");
+        offerer.set_local_description(offer.clone()).unwrap();
+
+        let answerer = PeerConnection::new(flexfec_config(true));
+        answerer.set_remote_description(offer).await.unwrap();
+        let answer = answerer.create_answer().await.unwrap();
+        assert!(has_flexfec_rtpmap(&answer));
+        answerer.set_local_description(answer.clone()).unwrap();
+        offerer.set_remote_description(answer).await.unwrap();
+
+        let sending = &offerer.get_transceivers()[0];
+        assert!(sending.flexfec_active());
+        let fec_config = crate::flexfec::FlexFecSenderConfig {
+            fec_ssrc,
+            fec_payload_type: 100,
+        };
+        assert_eq!(sending.sender().unwrap().flexfec(), Some(fec_config));
+
+        let receiving = &answerer.get_transceivers()[0];
+        assert!(receiving.flexfec_active());
+        let receiver = receiving.receiver().unwrap();
+        assert!(receiver.flexfec_active());
+
+        // Five packets share one repair packet; the third never arrives.
+        let packets: Vec<_> = (1..=5u16)
+            .map(|seq| {
+                let header = crate::rtp::RtpHeader::new(96, seq, 3000 * seq as u32, primary_ssrc);
+                crate::rtp::RtpPacket::new(header, vec![seq as u8; 10])
+            })
+            .collect();
+        let mut encoder =
+            crate::flexfec::FlexFecEncoder::new(fec_config, crate::flexfec::FLEXFEC_GROUP_SIZE);
+        let repair = packets.iter().find_map(|p| encoder.push(p)).unwrap();
+
+        for packet in packets.iter().filter(|p| p.header.sequence_number != 3) {
+            let (kept, recovered) = receiver.recover_flexfec(packet.clone());
+            assert_eq!(kept.as_ref(), Some(packet));
+            assert!(recovered.is_empty());
+        }
+        let (kept, recovered) = receiver.recover_flexfec(repair);
+        assert!(
+            kept.is_none(),
+            "repair packets must not reach the depacketizer"
+        );
+        assert_eq!(recovered, vec![packets[2].clone()]);
+        assert_eq!(receiver.flexfec_recovered_packets(), 1);
+    }
+
+    #[tokio::test]
+    async fn answer_omits_flexfec_unless_enabled_locally() {
+        let offerer = PeerConnection::new(flexfec_config(true));
+        offerer.add_transceiver(MediaKind::Video, TransceiverDirection::SendRecv);
+        let offer = offerer.create_offer().await.unwrap();
+        offerer.set_local_description(offer.clone()).unwrap();
+
+        let answerer = PeerConnection::new(flexfec_config(false));
+        answerer.set_remote_description(offer).await.unwrap();
+        let answer = answerer.create_answer().await.unwrap();
+        assert!(!has_flexfec_rtpmap(&answer));
+        assert!(!answerer.get_transceivers()[0].flexfec_active());
+
+        offerer.set_remote_description(answer).await.unwrap();
+        assert!(!offerer.get_transceivers()[0].flexfec_active());
+    }
+
     #[test]
     fn nack_handler_rtx_stats_default_zero() {
         let handler = DefaultRtpSenderNackHandler::new(16);
@@ -7830,18 +11429,159 @@ a=sendrecv\r\n";
     }
 
     #[tokio::test]
-    async fn test_receiver_nack_handler() {
-        use crate::rtp::RtpHeader;
-        let handler = DefaultRtpReceiverNackHandler::new();
-        let mut header = RtpHeader::new(96, 100, 0, 1234);
-        let packet1 = RtpPacket::new(header.clone(), vec![1, 2, 3]);
+    async fn create_answer_reuses_offered_extmap_ids() {
+        use crate::TransportMode;
+        // Mid-less SIP offer with a non-default abs-send-time id, a direction
+        // suffix on sdes:mid and an extension we do not support.
+        let remote_offer = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
+t=0 0\r\n\
+m=audio 4000 RTP/AVP 0\r\n\
+a=rtpmap:0 PCMU/8000\r\n\
+a=extmap:7 http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time\r\n\
+a=extmap:9/sendrecv urn:ietf:params:rtp-hdrext:sdes:mid\r\n\
+a=extmap:1 urn:ietf:params:rtp-hdrext:ssrc-audio-level\r\n\
+a=sendrecv\r\n";
 
-        // First packet initializes
-        let dummy = "0.0.0.0:0".parse().unwrap();
-        assert!(handler.on_packet_received(&packet1, dummy, dummy).await.is_none());
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        let pc = PeerConnection::new(config);
+        let offer = SessionDescription::parse(SdpType::Offer, remote_offer).expect("parse offer");
+        pc.set_remote_description(offer).await.expect("set remote");
 
-        // Consecutive packet
-        header.sequence_number = 101;
+        let answer = pc.create_answer().await.unwrap();
+        let extmaps: Vec<_> = answer.media_sections[0]
+            .attributes
+            .iter()
+            .filter(|a| a.key == "extmap")
+            .filter_map(|a| a.value.as_deref())
+            .collect();
+        assert_eq!(
+            extmaps,
+            vec![
+                "7 http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time",
+                "9 urn:ietf:params:rtp-hdrext:sdes:mid",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn transport_cc_negotiated_only_with_feedback() {
+        let transport_cc_extmaps = |desc: &SessionDescription| -> Vec<String> {
+            desc.media_sections[0]
+                .attributes
+                .iter()
+                .filter(|a| a.key == "extmap")
+                .filter_map(|a| a.value.clone())
+                .filter(|v| v.ends_with(crate::sdp::TRANSPORT_CC_URI))
+                .collect()
+        };
+
+        let pc1 = PeerConnection::new(RtcConfiguration::default());
+        pc1.add_transceiver(MediaKind::Video, TransceiverDirection::SendRecv);
+        let offer = pc1.create_offer().await.unwrap();
+        assert_eq!(
+            transport_cc_extmaps(&offer),
+            vec![format!("6 {}", crate::sdp::TRANSPORT_CC_URI)]
+        );
+        assert!(offer.media_sections[0].has_rtcp_fb("transport-cc"));
+
+        // A wildcard rtcp-fb is enough; the remote's extension id is echoed.
+        let offer_with = |fb: &str| {
+            format!(
+                "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+t=0 0\r\n\
+m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+c=IN IP4 127.0.0.1\r\n\
+a=mid:0\r\n\
+a=sendrecv\r\n\
+a=rtpmap:96 VP8/90000\r\n\
+a=rtcp-fb:* {fb}\r\n\
+a=extmap:3 {}\r\n\
+a=fingerprint:sha-256 AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99\r\n\
+a=setup:actpass\r\n",
+                crate::sdp::TRANSPORT_CC_URI
+            )
+        };
+
+        let pc2 = PeerConnection::new(RtcConfiguration::default());
+        let offer = SessionDescription::parse(SdpType::Offer, &offer_with("transport-cc")).unwrap();
+        pc2.set_remote_description(offer).await.unwrap();
+        let answer = pc2.create_answer().await.unwrap();
+        assert_eq!(
+            transport_cc_extmaps(&answer),
+            vec![format!("3 {}", crate::sdp::TRANSPORT_CC_URI)]
+        );
+        assert!(answer.media_sections[0].has_rtcp_fb("transport-cc"));
+
+        // The extension alone, without the feedback, is declined.
+        let pc3 = PeerConnection::new(RtcConfiguration::default());
+        let offer = SessionDescription::parse(SdpType::Offer, &offer_with("nack")).unwrap();
+        pc3.set_remote_description(offer).await.unwrap();
+        let answer = pc3.create_answer().await.unwrap();
+        assert!(transport_cc_extmaps(&answer).is_empty());
+        assert!(!answer.media_sections[0].has_rtcp_fb("transport-cc"));
+    }
+
+    #[tokio::test]
+    async fn offered_video_extmap_ids_avoid_remote_ids() {
+        // The remote already mapped the CVO and transport-cc defaults (5, 6)
+        // to other extensions, and transport-cc to 11, on its audio m-line.
+        let remote_offer = format!(
+            "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+t=0 0\r\n\
+a=group:BUNDLE 0\r\n\
+m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
+c=IN IP4 127.0.0.1\r\n\
+a=mid:0\r\n\
+a=sendrecv\r\n\
+a=rtpmap:111 opus/48000/2\r\n\
+a=extmap:5 urn:ietf:params:rtp-hdrext:ssrc-audio-level\r\n\
+a=extmap:6 urn:ietf:params:rtp-hdrext:toffset\r\n\
+a=extmap:11 {}\r\n\
+a=fingerprint:sha-256 AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99\r\n\
+a=setup:actpass\r\n",
+            crate::sdp::TRANSPORT_CC_URI
+        );
+        let pc = PeerConnection::new(RtcConfiguration::default());
+        let offer = SessionDescription::parse(SdpType::Offer, &remote_offer).unwrap();
+        pc.set_remote_description(offer).await.unwrap();
+        let answer = pc.create_answer().await.unwrap();
+        pc.set_local_description(answer).unwrap();
+
+        pc.add_transceiver(MediaKind::Video, TransceiverDirection::SendRecv);
+        let offer = pc.create_offer().await.unwrap();
+        let video = offer
+            .media_sections
+            .iter()
+            .find(|s| s.kind == MediaKind::Video)
+            .unwrap();
+        assert_eq!(
+            video.get_extmap_id(crate::sdp::VIDEO_ORIENTATION_URI),
+            Some(7)
+        );
+        assert_eq!(video.get_extmap_id(crate::sdp::TRANSPORT_CC_URI), Some(11));
+    }
+
+    #[tokio::test]
+    async fn test_receiver_nack_handler() {
+        use crate::rtp::RtpHeader;
+        let handler = DefaultRtpReceiverNackHandler::new();
+        let mut header = RtpHeader::new(96, 100, 0, 1234);
+        let packet1 = RtpPacket::new(header.clone(), vec![1, 2, 3]);
+
+        // First packet initializes
+        let dummy = "0.0.0.0:0".parse().unwrap();
+        assert!(handler.on_packet_received(&packet1, dummy, dummy).await.is_none());
+
+        // Consecutive packet
+        header.sequence_number = 101;
         let packet2 = RtpPacket::new(header.clone(), vec![4, 5, 6]);
         assert!(handler.on_packet_received(&packet2, dummy, dummy).await.is_none());
 
@@ -7873,6 +11613,94 @@ a=sendrecv\r\n";
         }
     }
 
+    #[tokio::test]
+    async fn receiver_nack_retries_across_wraparound_within_depth() {
+        use crate::rtp::RtpHeader;
+        let handler = DefaultRtpReceiverNackHandler::with_limits(1, std::time::Duration::ZERO, 10);
+        let dummy = "0.0.0.0:0".parse().unwrap();
+        let receive = async |seq: u16| {
+            let packet = RtpPacket::new(RtpHeader::new(96, seq, 0, 1234), vec![0]);
+            match handler.on_packet_received(&packet, dummy, dummy).await {
+                Some(RtcpPacket::GenericNack(nack)) => nack.lost_packets,
+                Some(other) => panic!("unexpected RTCP {other:?}"),
+                None => Vec::new(),
+            }
+        };
+
+        assert!(receive(65534).await.is_empty());
+        // Gap across the 16-bit wrap, oldest first.
+        assert_eq!(receive(1).await, vec![65535, 0]);
+        // One retry each, then the receiver gives up.
+        assert_eq!(receive(2).await, vec![65535, 0]);
+        assert!(receive(3).await.is_empty());
+
+        // Only the last `max_depth` packets of a long gap are requested.
+        assert_eq!(receive(100).await, (90..100).collect::<Vec<u16>>());
+        let retried = receive(95).await;
+        assert_eq!(retried.len(), 9);
+        assert!(!retried.contains(&95));
+        assert_eq!(handler.get_recovered_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn receiver_nack_waits_retry_interval_before_repeating() {
+        use crate::rtp::RtpHeader;
+        let handler = DefaultRtpReceiverNackHandler::new();
+        let dummy = "0.0.0.0:0".parse().unwrap();
+        for seq in [1u16, 3] {
+            let packet = RtpPacket::new(RtpHeader::new(96, seq, 0, 1234), vec![0]);
+            handler.on_packet_received(&packet, dummy, dummy).await;
+        }
+        let packet = RtpPacket::new(RtpHeader::new(96, 4, 0, 1234), vec![0]);
+        let nack = handler.on_packet_received(&packet, dummy, dummy).await;
+        assert!(nack.is_none());
+        assert_eq!(handler.get_nack_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn sender_nack_ignores_duplicate_nack_within_interval() {
+        use crate::rtp::RtpHeader;
+        use crate::transports::ice::IceSocketWrapper;
+        use crate::transports::ice::conn::IceConn;
+        use crate::transports::rtp::RtpTransport;
+        use tokio::net::UdpSocket;
+
+        let sink = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_tx, rx) = tokio::sync::watch::channel(Some(IceSocketWrapper::Udp(Arc::new(socket))));
+        let conn = IceConn::new(rx, sink.local_addr().unwrap(), None);
+        let transport = Arc::new(RtpTransport::new(conn, false));
+
+        let handler = DefaultRtpSenderNackHandler::with_retransmit_interval(
+            10,
+            std::time::Duration::from_secs(60),
+        );
+        handler.set_rtx(Some(crate::rtx::RtxSenderConfig {
+            rtx_ssrc: 5678,
+            rtx_payload_type: 97,
+        }));
+        let dummy = "0.0.0.0:0".parse().unwrap();
+        for seq in 100..103 {
+            let packet = RtpPacket::new(RtpHeader::new(96, seq, 0, 1234), vec![0]);
+            handler.on_packet_sent(&packet, dummy, dummy).await;
+        }
+
+        let nack = |lost_packets: Vec<u16>| {
+            RtcpPacket::GenericNack(GenericNack {
+                sender_ssrc: 0,
+                media_ssrc: 1234,
+                lost_packets,
+            })
+        };
+        handler
+            .on_rtcp_received(&nack(vec![101]), transport.clone())
+            .await;
+        handler
+            .on_rtcp_received(&nack(vec![101, 102]), transport)
+            .await;
+        assert_eq!(handler.get_rtx_sent_count(), 2);
+    }
+
     #[tokio::test]
     async fn test_sender_nack_handler() {
         use crate::rtp::RtpHeader;
@@ -8073,32 +11901,641 @@ a=mid:0
             "127.0.0.1:0".parse().unwrap(),
             None,
         );
-        let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
-        receiver.set_transport(transport, None, None);
+        let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
+        receiver.set_transport(transport, None, None);
+
+        let packet_tx = receiver.packet_tx().unwrap();
+        let packet = RtpPacket::new(
+            crate::rtp::RtpHeader::new(8, 1, 160, 0x1234_5678),
+            vec![0x55, 0x66],
+        );
+        packet_tx
+            .send((packet, "127.0.0.1:5004".parse().unwrap()))
+            .await
+            .unwrap();
+
+        let sample =
+            tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+                .await
+                .unwrap()
+                .unwrap();
+
+        match sample {
+            crate::media::MediaSample::Audio(frame) => {
+                assert_eq!(frame.clock_rate, 8000);
+                assert_eq!(frame.payload_type, Some(8));
+            }
+            other => panic!("expected audio sample, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn receiver_pause_stops_delivery_until_resumed() {
+        use crate::media::MediaStreamTrack;
+        use crate::media::depacketizer::{
+            Depacketizer, DepacketizerFactory, PassThroughDepacketizer,
+        };
+
+        #[derive(Debug)]
+        struct MockFactory;
+
+        impl DepacketizerFactory for MockFactory {
+            fn create(&self, _kind: crate::media::frame::MediaKind) -> Box<dyn Depacketizer> {
+                Box::new(PassThroughDepacketizer)
+            }
+        }
+
+        let receiver = RtpReceiverBuilder::new(MediaKind::Audio, 0x1234_5678)
+            .depacketizer_factory(Arc::new(MockFactory))
+            .build();
+        let (_socket_tx, socket_rx) =
+            tokio::sync::watch::channel::<Option<crate::transports::ice::IceSocketWrapper>>(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_rx,
+            "127.0.0.1:0".parse().unwrap(),
+            None,
+        );
+        let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
+        receiver.set_transport(transport, None, None);
+
+        let packet_tx = receiver.packet_tx().unwrap();
+        let send = |seq: u16| {
+            let packet = RtpPacket::new(
+                crate::rtp::RtpHeader::new(111, seq, seq as u32 * 960, 0x1234_5678),
+                vec![seq as u8],
+            );
+            packet_tx.send((packet, "127.0.0.1:5004".parse().unwrap()))
+        };
+        let track = receiver.track();
+        let recv = || tokio::time::timeout(std::time::Duration::from_millis(300), track.recv());
+
+        send(1).await.unwrap();
+        assert_eq!(recv().await.unwrap().unwrap().rtp_timestamp(), 960);
+
+        receiver.pause();
+        assert!(receiver.is_paused());
+        send(2).await.unwrap();
+        assert!(recv().await.is_err(), "paused receiver delivered a sample");
+
+        receiver.resume().await;
+        assert!(!receiver.is_paused());
+        send(3).await.unwrap();
+        assert_eq!(recv().await.unwrap().unwrap().rtp_timestamp(), 3 * 960);
+        assert_eq!(receiver.ssrc(), 0x1234_5678);
+        assert_eq!(receiver.packets_received(), 3);
+    }
+
+    #[tokio::test]
+    async fn receiver_handles_unnegotiated_payload_type_per_policy() {
+        use crate::media::MediaStreamTrack;
+        use crate::media::depacketizer::{
+            Depacketizer, DepacketizerFactory, PassThroughDepacketizer,
+        };
+
+        #[derive(Debug)]
+        struct MockFactory;
+
+        impl DepacketizerFactory for MockFactory {
+            fn create(&self, _kind: crate::media::frame::MediaKind) -> Box<dyn Depacketizer> {
+                Box::new(PassThroughDepacketizer)
+            }
+        }
+
+        for (policy, first_delivered_pt) in [
+            (UnknownPayloadTypePolicy::DropUnknownPt, 8),
+            (UnknownPayloadTypePolicy::AcceptAndLog, 99),
+        ] {
+            let transceiver = Arc::new(RtpTransceiver::new_for_test(
+                MediaKind::Audio,
+                TransceiverDirection::RecvOnly,
+            ));
+            let receiver = RtpReceiverBuilder::new(MediaKind::Audio, 1234)
+                .payload_map(transceiver.payload_map.clone())
+                .depacketizer_factory(Arc::new(MockFactory))
+                .unknown_payload_type_policy(policy)
+                .build();
+            transceiver.set_receiver(Some(receiver.clone()));
+            transceiver
+                .update_payload_map(HashMap::from([(
+                    8,
+                    RtpCodecParameters {
+                        payload_type: 8,
+                        clock_rate: 8000,
+                        channels: 1,
+                    },
+                )]))
+                .unwrap();
+
+            let (_socket_tx, socket_rx) = tokio::sync::watch::channel::<
+                Option<crate::transports::ice::IceSocketWrapper>,
+            >(None);
+            let ice_conn = crate::transports::ice::conn::IceConn::new(
+                socket_rx,
+                "127.0.0.1:0".parse().unwrap(),
+                None,
+            );
+            let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
+            receiver.set_transport(transport, None, None);
+
+            // PT 99 was never negotiated; PT 8 follows it on the same stream.
+            let packet_tx = receiver.packet_tx().unwrap();
+            for (seq, pt) in [(1, 99), (2, 8)] {
+                let packet = RtpPacket::new(
+                    crate::rtp::RtpHeader::new(pt, seq, 160 * seq as u32, 0x1234_5678),
+                    vec![0x55, 0x66],
+                );
+                packet_tx
+                    .send((packet, "127.0.0.1:5004".parse().unwrap()))
+                    .await
+                    .unwrap();
+            }
+
+            let sample =
+                tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+            match sample {
+                crate::media::MediaSample::Audio(frame) => {
+                    assert_eq!(frame.payload_type, Some(first_delivered_pt), "{policy:?}");
+                }
+                other => panic!("expected audio sample, got {:?}", other),
+            }
+            assert_eq!(receiver.unknown_payload_type_packets(), 1, "{policy:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn receiver_jitter_buffer_emits_shuffled_packets_in_order() {
+        use crate::media::MediaStreamTrack;
+        use crate::media::depacketizer::{
+            Depacketizer, DepacketizerFactory, PassThroughDepacketizer,
+        };
+
+        #[derive(Debug)]
+        struct MockFactory;
+
+        impl DepacketizerFactory for MockFactory {
+            fn create(&self, _kind: crate::media::frame::MediaKind) -> Box<dyn Depacketizer> {
+                Box::new(PassThroughDepacketizer)
+            }
+        }
+
+        let transceiver = Arc::new(RtpTransceiver::new_for_test(
+            MediaKind::Audio,
+            TransceiverDirection::RecvOnly,
+        ));
+        let receiver = RtpReceiverBuilder::new(MediaKind::Audio, 1234)
+            .payload_map(transceiver.payload_map.clone())
+            .depacketizer_factory(Arc::new(MockFactory))
+            .jitter_buffer(std::time::Duration::from_millis(50))
+            .build();
+        transceiver.set_receiver(Some(receiver.clone()));
+        let mut payload_map = HashMap::new();
+        payload_map.insert(
+            0,
+            RtpCodecParameters {
+                payload_type: 0,
+                clock_rate: 8000,
+                channels: 1,
+            },
+        );
+        transceiver.update_payload_map(payload_map).unwrap();
+
+        let (_socket_tx, socket_rx) =
+            tokio::sync::watch::channel::<Option<crate::transports::ice::IceSocketWrapper>>(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_rx,
+            "127.0.0.1:0".parse().unwrap(),
+            None,
+        );
+        let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
+        receiver.set_transport(transport, None, None);
+
+        // Crosses the sequence number wrap as well.
+        let packet_tx = receiver.packet_tx().unwrap();
+        let shuffled = [65533u16, 65535, 1, 65534, 0, 3, 2, 4];
+        for seq in shuffled {
+            let header =
+                crate::rtp::RtpHeader::new(0, seq, seq.wrapping_add(10) as u32 * 160, 1234);
+            let packet = RtpPacket::new(header, vec![seq as u8; 160]);
+            packet_tx
+                .send((packet, "127.0.0.1:5004".parse().unwrap()))
+                .await
+                .unwrap();
+        }
+
+        let mut received = Vec::new();
+        for _ in 0..shuffled.len() {
+            let sample =
+                tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+            match sample {
+                crate::media::MediaSample::Audio(frame) => {
+                    received.push(frame.sequence_number.unwrap())
+                }
+                other => panic!("expected audio sample, got {:?}", other),
+            }
+        }
+        assert_eq!(received, vec![65533, 65534, 65535, 0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn receiver_reports_cvo_rotation_on_video_frames() {
+        use crate::media::MediaStreamTrack;
+        use crate::media::depacketizer::{
+            Depacketizer, DepacketizerFactory, PassThroughDepacketizer,
+        };
+
+        #[derive(Debug)]
+        struct MockFactory;
+
+        impl DepacketizerFactory for MockFactory {
+            fn create(&self, _kind: crate::media::frame::MediaKind) -> Box<dyn Depacketizer> {
+                Box::new(PassThroughDepacketizer)
+            }
+        }
+
+        let transceiver = Arc::new(RtpTransceiver::new_for_test(
+            MediaKind::Video,
+            TransceiverDirection::RecvOnly,
+        ));
+        let receiver = RtpReceiverBuilder::new(MediaKind::Video, 1234)
+            .payload_map(transceiver.payload_map.clone())
+            .depacketizer_factory(Arc::new(MockFactory))
+            .build();
+        transceiver.set_receiver(Some(receiver.clone()));
+
+        let mut extmap = HashMap::new();
+        extmap.insert(7, crate::sdp::VIDEO_ORIENTATION_URI.to_string());
+        transceiver.update_extmap(extmap).unwrap();
+
+        let (_socket_tx, socket_rx) =
+            tokio::sync::watch::channel::<Option<crate::transports::ice::IceSocketWrapper>>(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_rx,
+            "127.0.0.1:0".parse().unwrap(),
+            None,
+        );
+        let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
+        receiver.set_transport(transport, None, None);
+
+        let mut header = crate::rtp::RtpHeader::new(VIDEO_PAYLOAD_TYPE, 1, 3000, 0x1234_5678);
+        header.marker = true;
+        // CVO byte: rotation 90° (R=1), horizontal flip (F=1).
+        header.set_extension(7, &[0x05]).unwrap();
+        let packet_tx = receiver.packet_tx().unwrap();
+        packet_tx
+            .send((
+                RtpPacket::new(header, vec![0x01]),
+                "127.0.0.1:5004".parse().unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        // A following frame without the extension keeps the last signalled orientation.
+        let header = crate::rtp::RtpHeader::new(VIDEO_PAYLOAD_TYPE, 2, 6000, 0x1234_5678);
+        packet_tx
+            .send((
+                RtpPacket::new(header, vec![0x02]),
+                "127.0.0.1:5004".parse().unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let sample =
+                tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+            match sample {
+                crate::media::MediaSample::Video(frame) => {
+                    assert_eq!(frame.rotation(), 90);
+                    assert!(frame.flip_horizontal);
+                    assert_eq!(frame.video_orientation(), 0x05);
+                }
+                other => panic!("expected video sample, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn frame_marking_is_written_by_sender_and_exposed_by_receiver() {
+        use crate::media::MediaStreamTrack;
+        use crate::media::frame::VideoFrame;
+        use crate::rtp::FrameMarking;
+        use tokio::net::UdpSocket;
+
+        let mut extmap = HashMap::new();
+        extmap.insert(4, crate::sdp::FRAME_MARKING_URI.to_string());
+
+        // Sender: frame marking set by the application goes out on the wire.
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_socket_tx, socket_rx) = tokio::sync::watch::channel(Some(
+            crate::transports::ice::IceSocketWrapper::Udp(Arc::new(local)),
+        ));
+        let ice_conn = IceConn::new(socket_rx, remote.local_addr().unwrap(), None);
+        let send_transceiver =
+            RtpTransceiver::new_for_test(MediaKind::Video, TransceiverDirection::SendOnly);
+        let (source, track, _) = sample_track(crate::media::frame::MediaKind::Video, 8);
+        let sender = RtpSender::builder(track, 42)
+            .params(RtpCodecParameters {
+                payload_type: VIDEO_PAYLOAD_TYPE,
+                clock_rate: 90000,
+                channels: 0,
+            })
+            .build();
+        send_transceiver.set_sender(Some(sender.clone()));
+        send_transceiver.update_extmap(extmap.clone()).unwrap();
+        sender.set_transport(Arc::new(RtpTransport::new(ice_conn, false)));
+
+        let markings = [
+            FrameMarking {
+                start_of_frame: true,
+                end_of_frame: true,
+                independent: true,
+                base_layer_sync: true,
+                tl0_pic_idx: 9,
+                ..Default::default()
+            },
+            FrameMarking {
+                start_of_frame: true,
+                discardable: true,
+                temporal_id: 2,
+                spatial_id: 1,
+                tl0_pic_idx: 9,
+                ..Default::default()
+            },
+        ];
+        for (i, marking) in markings.iter().enumerate() {
+            source
+                .send_video(VideoFrame {
+                    rtp_timestamp: i as u32 * 3000,
+                    frame_marking: Some(*marking),
+                    data: bytes::Bytes::from(vec![i as u8; 4]),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut wire = Vec::new();
+        let mut buf = [0u8; 1500];
+        for _ in &markings {
+            let n = tokio::time::timeout(std::time::Duration::from_secs(1), remote.recv(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            wire.push(RtpPacket::parse(&buf[..n]).unwrap());
+        }
+
+        // Receiver: the same packets surface their marking on the frames.
+        let recv_transceiver = Arc::new(RtpTransceiver::new_for_test(
+            MediaKind::Video,
+            TransceiverDirection::RecvOnly,
+        ));
+        let receiver = RtpReceiverBuilder::new(MediaKind::Video, 1234)
+            .payload_map(recv_transceiver.payload_map.clone())
+            .build();
+        recv_transceiver.set_receiver(Some(receiver.clone()));
+        recv_transceiver.update_extmap(extmap).unwrap();
+        let (_socket_tx, socket_rx) =
+            tokio::sync::watch::channel::<Option<crate::transports::ice::IceSocketWrapper>>(None);
+        let ice_conn = IceConn::new(socket_rx, "127.0.0.1:0".parse().unwrap(), None);
+        receiver.set_transport(Arc::new(RtpTransport::new(ice_conn, false)), None, None);
+
+        let packet_tx = receiver.packet_tx().unwrap();
+        for packet in wire {
+            packet_tx
+                .send((packet, "127.0.0.1:5004".parse().unwrap()))
+                .await
+                .unwrap();
+        }
+        for expected in markings {
+            let sample =
+                tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+            match sample {
+                crate::media::MediaSample::Video(frame) => {
+                    assert_eq!(frame.frame_marking, Some(expected));
+                }
+                other => panic!("expected video sample, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ice_conn_loss_injection_drops_about_the_configured_ratio() {
+        use tokio::net::UdpSocket;
+
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_socket_tx, socket_rx) = tokio::sync::watch::channel(Some(
+            crate::transports::ice::IceSocketWrapper::Udp(Arc::new(local)),
+        ));
+        let ice_conn = IceConn::new(socket_rx, remote.local_addr().unwrap(), None);
+        ice_conn.set_loss_injection(0.5);
+
+        let mut received = 0;
+        let mut buf = [0u8; 64];
+        // Drain in batches so the loopback socket buffer never overflows.
+        for batch in 0..20u16 {
+            for i in 0..50 {
+                ice_conn
+                    .send(&(batch * 50 + i).to_be_bytes())
+                    .await
+                    .unwrap();
+            }
+            while let Ok(Ok(_)) =
+                tokio::time::timeout(std::time::Duration::from_millis(50), remote.recv(&mut buf))
+                    .await
+            {
+                received += 1;
+            }
+        }
+        assert!(
+            (350..=650).contains(&received),
+            "expected about half of 1000 packets, got {}",
+            received
+        );
+    }
+
+    #[tokio::test]
+    async fn sender_loss_injection_is_recovered_by_nack() {
+        use crate::media::frame::AudioFrame;
+        use std::collections::HashSet;
+        use tokio::net::UdpSocket;
+
+        const FRAMES: u16 = 200;
+
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_socket_tx, socket_rx) = tokio::sync::watch::channel(Some(
+            crate::transports::ice::IceSocketWrapper::Udp(Arc::new(local)),
+        ));
+        let ice_conn = IceConn::new(socket_rx, remote.local_addr().unwrap(), None);
+        let (source, track, _) = sample_track(crate::media::frame::MediaKind::Audio, 256);
+        let sender = RtpSender::builder(track, 42)
+            .params(RtpCodecParameters {
+                payload_type: 0,
+                clock_rate: 8000,
+                channels: 1,
+            })
+            .nack(512)
+            .build();
+        sender.set_loss_injection(0.5);
+        sender.set_transport(Arc::new(RtpTransport::new(ice_conn, false)));
+
+        // Each payload carries its frame index, so the wire sequence number
+        // of a lost frame can be derived from any delivered one.
+        let mut delivered = HashSet::new();
+        let mut base_seq = None;
+        let mut buf = [0u8; 1500];
+        let mut drain = async |delivered: &mut HashSet<u16>, base_seq: &mut Option<u16>| {
+            while let Ok(Ok(n)) =
+                tokio::time::timeout(std::time::Duration::from_millis(200), remote.recv(&mut buf))
+                    .await
+            {
+                let packet = RtpPacket::parse(&buf[..n]).unwrap();
+                let index = u16::from_be_bytes([packet.payload[0], packet.payload[1]]);
+                base_seq.get_or_insert(packet.header.sequence_number.wrapping_sub(index));
+                delivered.insert(index);
+            }
+        };
+
+        for i in 0..FRAMES {
+            source
+                .send_audio(AudioFrame {
+                    rtp_timestamp: i as u32 * 160,
+                    clock_rate: 8000,
+                    data: bytes::Bytes::copy_from_slice(&i.to_be_bytes()),
+                    ..Default::default()
+                })
+                .unwrap();
+            if i % 20 == 19 {
+                tokio::task::yield_now().await;
+            }
+        }
+        drain(&mut delivered, &mut base_seq).await;
+        let without_nack = delivered.len();
+        assert!(
+            (50..=150).contains(&without_nack),
+            "expected about half of {} packets, got {}",
+            FRAMES,
+            without_nack
+        );
 
-        let packet_tx = receiver.packet_tx().unwrap();
-        let packet = RtpPacket::new(
-            crate::rtp::RtpHeader::new(8, 1, 160, 0x1234_5678),
-            vec![0x55, 0x66],
+        let base_seq = base_seq.unwrap();
+        let lost_packets = (0..FRAMES)
+            .filter(|i| !delivered.contains(i))
+            .map(|i| base_seq.wrapping_add(i))
+            .collect();
+        sender.deliver_rtcp(RtcpPacket::GenericNack(GenericNack {
+            sender_ssrc: 0,
+            media_ssrc: sender.ssrc(),
+            lost_packets,
+        }));
+        let mut base_seq = Some(base_seq);
+        drain(&mut delivered, &mut base_seq).await;
+        assert!(
+            delivered.len() > without_nack && delivered.len() as u16 >= FRAMES * 95 / 100,
+            "NACK should recover the injected loss: {} -> {} of {}",
+            without_nack,
+            delivered.len(),
+            FRAMES
         );
-        packet_tx
-            .send((packet, "127.0.0.1:5004".parse().unwrap()))
-            .await
-            .unwrap();
+    }
 
-        let sample =
-            tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+    #[tokio::test]
+    async fn pacer_spaces_packets_at_configured_rate() {
+        use crate::media::frame::VideoFrame;
+        use tokio::net::UdpSocket;
+
+        const PACKETS: usize = 20;
+        // 988 payload + 12 header bytes = 8000 bits, 10ms apart at 800 kbit/s.
+        const PAYLOAD: usize = 988;
+
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_socket_tx, socket_rx) = tokio::sync::watch::channel(Some(
+            crate::transports::ice::IceSocketWrapper::Udp(Arc::new(local)),
+        ));
+        let ice_conn = IceConn::new(socket_rx, remote.local_addr().unwrap(), None);
+        let (source, track, _) = sample_track(crate::media::frame::MediaKind::Video, 256);
+        let sender = RtpSender::builder(track, 42)
+            .params(RtpCodecParameters {
+                payload_type: 96,
+                clock_rate: 90000,
+                channels: 0,
+            })
+            .pacing_kbps(800)
+            .build();
+        sender.set_transport(Arc::new(RtpTransport::new(ice_conn, false)));
+
+        // Two frames of ten packets, all queued at once.
+        for i in 0..PACKETS {
+            source
+                .send_video(VideoFrame {
+                    rtp_timestamp: (i / 10) as u32 * 3000,
+                    data: bytes::Bytes::from(vec![i as u8; PAYLOAD]),
+                    is_last_packet: i % 10 == 9,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        let mut buf = [0u8; 1500];
+        let mut arrivals = Vec::new();
+        let mut markers = Vec::new();
+        while arrivals.len() < PACKETS {
+            let n = tokio::time::timeout(std::time::Duration::from_secs(2), remote.recv(&mut buf))
                 .await
-                .unwrap()
+                .expect("paced packet")
                 .unwrap();
+            arrivals.push(std::time::Instant::now());
+            markers.push(RtpPacket::parse(&buf[..n]).unwrap().header.marker);
+        }
 
-        match sample {
-            crate::media::MediaSample::Audio(frame) => {
-                assert_eq!(frame.clock_rate, 8000);
-                assert_eq!(frame.payload_type, Some(8));
-            }
-            other => panic!("expected audio sample, got {:?}", other),
+        // The pacer never sends early, so the lower bound is firm; the upper
+        // one only guards against pacing far too slowly on a loaded machine.
+        let spacing = (arrivals[PACKETS - 1] - arrivals[0]) / (PACKETS as u32 - 1);
+        assert!(
+            spacing >= std::time::Duration::from_millis(9)
+                && spacing <= std::time::Duration::from_millis(40),
+            "mean spacing {:?}, expected about 10ms",
+            spacing
+        );
+        // Frame ends keep their marker; the transport also marks the very
+        // first packet it sends.
+        let marked: Vec<usize> = (1..PACKETS).filter(|&i| markers[i]).collect();
+        assert_eq!(marked, vec![9, 19]);
+    }
+
+    #[test]
+    fn pacer_overflow_drops_oldest_whole_frame() {
+        use crate::media::MediaSample;
+        use crate::media::frame::VideoFrame;
+
+        let mut pacer = SendPacer::new(Arc::new(AtomicU32::new(100)));
+        let packet = |frame: usize, last: bool| {
+            MediaSample::Video(VideoFrame {
+                rtp_timestamp: frame as u32 * 3000,
+                is_last_packet: last,
+                ..Default::default()
+            })
+        };
+        // Frames of four packets fill the queue exactly.
+        for i in 0..PACER_MAX_QUEUE {
+            pacer.push(packet(i / 4, i % 4 == 3));
         }
+        pacer.push(packet(PACER_MAX_QUEUE / 4, false));
+
+        assert_eq!(pacer.queue.len(), PACER_MAX_QUEUE - 3);
+        assert_eq!(pacer.queue.front().unwrap().rtp_timestamp(), 3000);
     }
 
     #[tokio::test]
@@ -8182,6 +12619,184 @@ a=mid:0
         }
     }
 
+    #[tokio::test]
+    async fn receiver_switches_depacketizer_when_reinvite_changes_codec() {
+        use crate::media::{CodecDepacketizerFactory, MediaStreamTrack};
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        config.depacketizer_strategy.factory = Arc::new(CodecDepacketizerFactory);
+
+        let pc = PeerConnection::new(config);
+        let transceiver = pc.add_transceiver(MediaKind::Video, TransceiverDirection::RecvOnly);
+
+        let offer = |version: u32, pt: u8, codec: &str| {
+            let sdp = format!(
+                "v=0\r\no=- 12345 {version} IN IP4 192.168.1.100\r\ns=-\r\n\
+                 c=IN IP4 192.168.1.100\r\nt=0 0\r\nm=video 9000 RTP/AVP {pt}\r\n\
+                 a=rtpmap:{pt} {codec}/90000\r\na=sendonly\r\na=mid:0\r\n"
+            );
+            SessionDescription::parse(SdpType::Offer, &sdp).unwrap()
+        };
+        pc.set_remote_description(offer(1, 96, "VP8"))
+            .await
+            .unwrap();
+        let answer = pc.create_answer().await.unwrap();
+        pc.set_local_description(answer).unwrap();
+
+        let receiver = transceiver.receiver().unwrap();
+        assert_eq!(receiver.codec_name().as_deref(), Some("VP8"));
+        let (_socket_tx, socket_rx) =
+            tokio::sync::watch::channel::<Option<crate::transports::ice::IceSocketWrapper>>(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_rx,
+            "127.0.0.1:0".parse().unwrap(),
+            None,
+        );
+        let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
+        receiver.set_transport(transport, None, None);
+        tokio::task::yield_now().await;
+
+        let packet_tx = receiver.packet_tx().unwrap();
+        let send = |pt: u8, seq: u16, ts: u32, marker: bool, payload: &[u8]| {
+            let mut header = crate::rtp::RtpHeader::new(pt, seq, ts, 0x2233_4455);
+            header.marker = marker;
+            let packet = RtpPacket::new(header, payload.to_vec());
+            let packet_tx = packet_tx.clone();
+            async move {
+                packet_tx
+                    .send((packet, "127.0.0.1:5004".parse().unwrap()))
+                    .await
+                    .unwrap();
+            }
+        };
+        let next_frame = || async {
+            match tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+                .await
+                .unwrap()
+                .unwrap()
+            {
+                crate::media::MediaSample::Video(frame) => frame,
+                other => panic!("expected video sample, got {:?}", other),
+            }
+        };
+
+        // VP8: two packets, each with a one-byte payload descriptor (S bit on
+        // the first), come out as one frame with the descriptors stripped.
+        send(96, 1, 3000, false, &[0x10, 0xAA, 0xBB]).await;
+        send(96, 2, 3000, true, &[0x00, 0xCC]).await;
+        let frame = next_frame().await;
+        assert_eq!(frame.data.as_ref(), &[0xAA, 0xBB, 0xCC]);
+        assert_eq!(frame.payload_type, Some(96));
+
+        // Re-offer with H.264 on a new payload type: FU-A fragments are now
+        // reassembled into an Annex-B access unit.
+        pc.set_remote_description(offer(2, 102, "H264"))
+            .await
+            .unwrap();
+        let answer = pc.create_answer().await.unwrap();
+        pc.set_local_description(answer).unwrap();
+        assert_eq!(receiver.codec_name().as_deref(), Some("H264"));
+
+        send(102, 3, 6000, false, &[0x7C, 0x85, 0x01]).await;
+        send(102, 4, 6000, true, &[0x7C, 0x45, 0x02]).await;
+        let frame = next_frame().await;
+        assert_eq!(frame.data.as_ref(), &[0, 0, 0, 1, 0x65, 0x01, 0x02]);
+        assert_eq!(frame.payload_type, Some(102));
+    }
+
+    #[tokio::test]
+    async fn receiver_stamps_capture_time_from_sender_report() {
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+
+        let pc = PeerConnection::new(config);
+        let transceiver = pc.add_transceiver(MediaKind::Audio, TransceiverDirection::RecvOnly);
+        let sdp = "v=0\r\no=- 12345 1 IN IP4 192.168.1.100\r\ns=-\r\n\
+                   c=IN IP4 192.168.1.100\r\nt=0 0\r\nm=audio 9000 RTP/AVP 0\r\n\
+                   a=rtpmap:0 PCMU/8000\r\na=sendonly\r\na=mid:0\r\n";
+        pc.set_remote_description(SessionDescription::parse(SdpType::Offer, sdp).unwrap())
+            .await
+            .unwrap();
+        let answer = pc.create_answer().await.unwrap();
+        pc.set_local_description(answer).unwrap();
+
+        let receiver = transceiver.receiver().unwrap();
+        let (_socket_tx, socket_rx) =
+            tokio::sync::watch::channel::<Option<crate::transports::ice::IceSocketWrapper>>(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_rx,
+            "127.0.0.1:0".parse().unwrap(),
+            None,
+        );
+        let transport = Arc::new(crate::transports::rtp::RtpTransport::new(ice_conn, false));
+        receiver.set_transport(transport, None, None);
+        tokio::task::yield_now().await;
+
+        const SSRC: u32 = 0x0102_0304;
+        let packet_tx = receiver.packet_tx().unwrap();
+        let mut seq = 0u16;
+        let mut next_frame = async |ts: u32| {
+            seq += 1;
+            let packet = RtpPacket::new(
+                crate::rtp::RtpHeader::new(0, seq, ts, SSRC),
+                vec![0xFF; 160],
+            );
+            packet_tx
+                .send((packet, "127.0.0.1:5004".parse().unwrap()))
+                .await
+                .unwrap();
+            match tokio::time::timeout(std::time::Duration::from_secs(1), receiver.track().recv())
+                .await
+                .unwrap()
+                .unwrap()
+            {
+                crate::media::MediaSample::Audio(frame) => frame,
+                other => panic!("expected audio sample, got {:?}", other),
+            }
+        };
+
+        // No SR yet: the sender clock is unknown.
+        let frame = next_frame(1_000).await;
+        assert_eq!(frame.capture_time, None);
+
+        let sr = |ssrc: u32| SenderReport {
+            sender_ssrc: ssrc,
+            ntp_most: 3_900_000_000,
+            ntp_least: 1 << 31,
+            rtp_timestamp: 1_000,
+            packet_count: 1,
+            octet_count: 160,
+            report_blocks: Vec::new(),
+        };
+        assert!(!receiver.deliver_sender_report(&sr(0xDEAD_BEEF)));
+        assert!(receiver.deliver_sender_report(&sr(SSRC)));
+        let sr_time = sr(SSRC).ntp_time();
+        assert_eq!(
+            sr_time.duration_since(UNIX_EPOCH).unwrap(),
+            std::time::Duration::from_millis(1_691_011_200_500)
+        );
+
+        // 20 ms frames follow the RTP clock from the SR anchor, including one
+        // captured before the SR was sent.
+        let mut captured = Vec::new();
+        for ts in [1_160, 1_320, 840] {
+            captured.push(next_frame(ts).await.capture_time.unwrap());
+        }
+        assert_eq!(
+            captured[0].duration_since(sr_time).unwrap(),
+            std::time::Duration::from_millis(20)
+        );
+        assert_eq!(
+            captured[1].duration_since(captured[0]).unwrap(),
+            std::time::Duration::from_millis(20)
+        );
+        assert_eq!(
+            sr_time.duration_since(captured[2]).unwrap(),
+            std::time::Duration::from_millis(20)
+        );
+    }
+
     // ===== RTP mode ICE-skip verification tests =====
 
     #[tokio::test]
@@ -8533,6 +13148,77 @@ a=mid:0
         assert_eq!(pair.remote.address.port(), 6000);
     }
 
+    #[tokio::test]
+    async fn offerer_sender_switches_to_codec_accepted_by_answer() {
+        use crate::TransportMode;
+        use crate::config::MediaCapabilities;
+        use crate::media::frame::AudioFrame;
+        use tokio::net::UdpSocket;
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        config.bind_ip = Some("127.0.0.1".to_string());
+        config.media_capabilities = Some(MediaCapabilities {
+            audio: vec![AudioCapability::opus(), AudioCapability::pcmu()],
+            video: vec![],
+            application: None,
+            image: vec![],
+        });
+        let pc = PeerConnection::new(config);
+
+        let (source, track, _) = sample_track(crate::media::frame::MediaKind::Audio, 8);
+        let sender = pc
+            .add_track(
+                track,
+                RtpCodecParameters {
+                    payload_type: 111,
+                    clock_rate: 48000,
+                    channels: 2,
+                },
+            )
+            .unwrap();
+
+        let offer = pc.create_offer().await.unwrap();
+        assert_eq!(
+            offer.first_audio_section().unwrap().formats[..2],
+            ["111".to_string(), "0".to_string()]
+        );
+        pc.set_local_description(offer).unwrap();
+
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let remote_sdp = format!(
+            "v=0\r\n\
+             o=- 1 1 IN IP4 127.0.0.1\r\n\
+             s=-\r\n\
+             t=0 0\r\n\
+             c=IN IP4 127.0.0.1\r\n\
+             m=audio {} RTP/AVP 0\r\n\
+             a=rtpmap:0 PCMU/8000\r\n\
+             a=sendrecv\r\n",
+            remote.local_addr().unwrap().port()
+        );
+        let answer = SessionDescription::parse(SdpType::Answer, &remote_sdp).unwrap();
+        pc.set_remote_description(answer).await.unwrap();
+
+        assert_eq!(sender.params().payload_type, 0);
+        assert_eq!(sender.params().clock_rate, 8000);
+
+        source
+            .send_audio(AudioFrame {
+                clock_rate: 8000,
+                data: bytes::Bytes::from_static(&[0xff; 160]),
+                ..Default::default()
+            })
+            .unwrap();
+        let mut buf = [0u8; 1500];
+        let n = tokio::time::timeout(std::time::Duration::from_secs(2), remote.recv(&mut buf))
+            .await
+            .expect("offerer should send media after the answer")
+            .unwrap();
+        let packet = RtpPacket::parse(&buf[..n]).unwrap();
+        assert_eq!(packet.header.payload_type, 0);
+    }
+
     #[tokio::test]
     async fn rtp_mode_answerer_connects_on_set_remote() {
         use crate::TransportMode;
@@ -8707,17 +13393,167 @@ a=mid:0
         .await
         .unwrap();
 
-        let rtp_transport = pc.inner.rtp_transport.lock().clone().unwrap();
-        let ice_conn = rtp_transport.ice_conn();
-        let rtcp_addr = *ice_conn.remote_rtcp_addr.read();
-        assert!(
-            rtcp_addr.is_some(),
-            "Explicit a=rtcp must produce a separate RTCP addr"
+        let rtp_transport = pc.inner.rtp_transport.lock().clone().unwrap();
+        let ice_conn = rtp_transport.ice_conn();
+        let rtcp_addr = *ice_conn.remote_rtcp_addr.read();
+        assert!(
+            rtcp_addr.is_some(),
+            "Explicit a=rtcp must produce a separate RTCP addr"
+        );
+        assert_eq!(
+            rtcp_addr.unwrap().port(),
+            9000,
+            "RTCP port should honor explicit a=rtcp"
+        );
+    }
+
+    #[tokio::test]
+    async fn rtp_mode_rtcp_explicit_address_receives_rtcp() {
+        use crate::TransportMode;
+        use tokio::net::UdpSocket;
+
+        let rtp_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let rtcp_socket = UdpSocket::bind("127.0.0.2:9001").await.unwrap();
+        let rtp_addr = rtp_socket.local_addr().unwrap();
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        let pc = PeerConnection::new(config);
+
+        let remote_sdp = format!(
+            "v=0\r\n\
+             o=- 1 1 IN IP4 127.0.0.1\r\n\
+             s=-\r\n\
+             t=0 0\r\n\
+             c=IN IP4 127.0.0.1\r\n\
+             m=audio {} RTP/AVP 0\r\n\
+             a=rtcp:9001 IN IP4 127.0.0.2\r\n\
+             a=rtpmap:0 PCMU/8000\r\n\
+             a=sendrecv\r\n",
+            rtp_addr.port()
+        );
+        let desc = SessionDescription::parse(SdpType::Offer, &remote_sdp).unwrap();
+        pc.set_remote_description(desc).await.unwrap();
+        pc.wait_for_rtp_transport_ready(std::time::Duration::from_secs(2))
+            .await
+            .unwrap();
+
+        let rtp_transport = pc.inner.rtp_transport.lock().clone().unwrap();
+        assert_eq!(*rtp_transport.ice_conn().remote_addr.read(), rtp_addr);
+        assert_eq!(
+            *rtp_transport.ice_conn().remote_rtcp_addr.read(),
+            Some("127.0.0.2:9001".parse().unwrap())
+        );
+
+        rtp_transport
+            .send_rtp(RtpPacket::new(
+                crate::rtp::RtpHeader::new(0, 1, 160, 1234),
+                vec![0xff; 160],
+            ))
+            .await
+            .unwrap();
+        rtp_transport
+            .send_rtcp(&[RtcpPacket::PictureLossIndication(PictureLossIndication {
+                sender_ssrc: 1234,
+                media_ssrc: 5678,
+            })])
+            .await
+            .unwrap();
+
+        let mut buf = [0u8; 1500];
+        let timeout = std::time::Duration::from_secs(2);
+        let n = tokio::time::timeout(timeout, rtp_socket.recv(&mut buf))
+            .await
+            .expect("RTP should reach the c= address")
+            .unwrap();
+        assert_eq!(
+            buf[1] & 0x7f,
+            0,
+            "RTP socket should receive the PCMU packet"
+        );
+        assert!(n > 12);
+
+        let n = tokio::time::timeout(timeout, rtcp_socket.recv(&mut buf))
+            .await
+            .expect("RTCP should reach the a=rtcp address")
+            .unwrap();
+        let packets = crate::rtp::parse_rtcp_packets(&buf[..n], None).unwrap();
+        assert!(matches!(
+            packets.as_slice(),
+            [RtcpPacket::PictureLossIndication(_)]
+        ));
+    }
+
+    #[tokio::test]
+    async fn rtp_mode_forwards_unknown_rtcp_when_enabled() {
+        use crate::TransportMode;
+        use tokio::net::UdpSocket;
+
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        config.bind_ip = Some("127.0.0.1".to_string());
+        config.forward_unknown_rtcp = true;
+        let pc = PeerConnection::new(config);
+        let mut unknown_rx = pc.subscribe_unknown_rtcp();
+
+        let remote_sdp = format!(
+            "v=0\r\no=- 1 1 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\nc=IN IP4 127.0.0.1\r\n\
+             m=audio {} RTP/AVP 0\r\na=rtpmap:0 PCMU/8000\r\na=rtcp-mux\r\na=sendrecv\r\n",
+            remote.local_addr().unwrap().port()
+        );
+        let desc = SessionDescription::parse(SdpType::Offer, &remote_sdp).unwrap();
+        pc.set_remote_description(desc).await.unwrap();
+        let answer = pc.create_answer().await.unwrap();
+        pc.set_local_description(answer.clone()).unwrap();
+        pc.wait_for_rtp_transport_ready(std::time::Duration::from_secs(2))
+            .await
+            .unwrap();
+        let local_port = answer.media_sections[0].port;
+
+        // An application-defined (APP) packet in a compound with a PLI.
+        let app = crate::rtp::UnknownRtcp {
+            packet_type: crate::rtp::RTCP_APP,
+            fmt: 1,
+            payload: [&0xCAFE_F00Du32.to_be_bytes()[..], b"SFU1", &[0xAB; 8]].concat(),
+        };
+        let raw = crate::rtp::marshal_rtcp_packets(&[
+            RtcpPacket::PictureLossIndication(PictureLossIndication {
+                sender_ssrc: 0xCAFE_F00D,
+                media_ssrc: 1234,
+            }),
+            RtcpPacket::Unknown(app.clone()),
+        ])
+        .unwrap();
+        remote
+            .send_to(&raw, ("127.0.0.1", local_port))
+            .await
+            .unwrap();
+
+        let forwarded = tokio::time::timeout(std::time::Duration::from_secs(2), unknown_rx.recv())
+            .await
+            .expect("unknown RTCP should be forwarded")
+            .unwrap();
+        assert_eq!(forwarded, RtcpPacket::Unknown(app));
+    }
+
+    #[test]
+    fn rtcp_attribute_unspecified_address_uses_media_ip() {
+        let media_ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let parse = |value: &str| {
+            PeerConnection::parse_rtcp_attribute(
+                &Attribute::new("rtcp", Some(value.to_string())),
+                media_ip,
+            )
+        };
+        assert_eq!(parse("9001"), Some("10.0.0.1:9001".parse().unwrap()));
+        assert_eq!(
+            parse("9001 IN IP4 127.0.0.2"),
+            Some("127.0.0.2:9001".parse().unwrap())
         );
         assert_eq!(
-            rtcp_addr.unwrap().port(),
-            9000,
-            "RTCP port should honor explicit a=rtcp"
+            parse("9 IN IP4 0.0.0.0"),
+            Some("10.0.0.1:9".parse().unwrap())
         );
     }
 
@@ -8933,6 +13769,276 @@ a=mid:0
         );
     }
 
+    #[tokio::test]
+    async fn sender_reports_negotiated_opus_fmtp() {
+        use crate::TransportMode;
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        let pc = PeerConnection::new(config);
+
+        let (_, track, _) = sample_track(crate::media::frame::MediaKind::Audio, 48000);
+        let params = RtpCodecParameters {
+            payload_type: 111,
+            clock_rate: 48000,
+            channels: 2,
+        };
+        let sender = pc.add_track(track, params).unwrap();
+        assert!(sender.negotiated_fmtp().is_empty());
+
+        let offer = pc.create_offer().await.unwrap();
+        pc.set_local_description(offer).unwrap();
+
+        let remote_answer = "v=0\r\n\
+            o=- 1 1 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            t=0 0\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            m=audio 8000 RTP/AVP 111\r\n\
+            a=rtpmap:111 opus/48000/2\r\n\
+            a=fmtp:111 maxaveragebitrate=24000;useinbandfec=1\r\n\
+            a=recvonly\r\n";
+        let desc = SessionDescription::parse(SdpType::Answer, remote_answer).unwrap();
+        pc.set_remote_description(desc).await.unwrap();
+
+        let fmtp = sender.negotiated_fmtp();
+        assert_eq!(
+            fmtp.get("maxaveragebitrate").map(String::as_str),
+            Some("24000")
+        );
+        assert_eq!(fmtp.get("useinbandfec").map(String::as_str), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn reinvite_changing_only_fmtp_updates_sender_in_place() {
+        use crate::TransportMode;
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        let pc = PeerConnection::new(config);
+
+        let (_, track, _) = sample_track(crate::media::frame::MediaKind::Audio, 48000);
+        let params = RtpCodecParameters {
+            payload_type: 111,
+            clock_rate: 48000,
+            channels: 2,
+        };
+        let sender = pc.add_track(track, params).unwrap();
+        let offer = pc.create_offer().await.unwrap();
+        pc.set_local_description(offer).unwrap();
+
+        let remote = |version: u32, sdp_type: SdpType, fmtp: &str| {
+            let sdp = format!(
+                "v=0\r\n\
+                o=- 1 {version} IN IP4 10.0.0.1\r\n\
+                s=-\r\n\
+                t=0 0\r\n\
+                c=IN IP4 10.0.0.1\r\n\
+                m=audio 8000 RTP/AVP 111\r\n\
+                a=rtpmap:111 opus/48000/2\r\n\
+                a=fmtp:111 {fmtp}\r\n\
+                a=ssrc:4242 cname:remote\r\n\
+                a=sendrecv\r\n"
+            );
+            SessionDescription::parse(sdp_type, &sdp).unwrap()
+        };
+        pc.set_remote_description(remote(1, SdpType::Answer, "useinbandfec=1"))
+            .await
+            .unwrap();
+        assert_eq!(sender.negotiated_fmtp().get("usedtx"), None);
+
+        let transceiver = pc.get_transceivers()[0].clone();
+        let receiver_track_id = transceiver.receiver().unwrap().track().id().to_string();
+        let mut fmtp_rx = sender.watch_negotiated_fmtp();
+        fmtp_rx.mark_unchanged();
+
+        pc.set_remote_description(remote(2, SdpType::Offer, "useinbandfec=1;usedtx=1"))
+            .await
+            .unwrap();
+        let answer = pc.create_answer().await.unwrap();
+        pc.set_local_description(answer).unwrap();
+
+        assert!(fmtp_rx.has_changed().unwrap(), "sender should be notified");
+        let fmtp = sender.negotiated_fmtp();
+        assert_eq!(fmtp.get("usedtx").map(String::as_str), Some("1"));
+        assert_eq!(fmtp.get("useinbandfec").map(String::as_str), Some("1"));
+
+        // Same sender and receiver track: nothing was recreated.
+        assert!(Arc::ptr_eq(&transceiver.sender().unwrap(), &sender));
+        assert_eq!(
+            transceiver.receiver().unwrap().track().id(),
+            receiver_track_id
+        );
+    }
+
+    #[cfg(feature = "opus")]
+    #[tokio::test]
+    async fn negotiated_opus_dtx_and_fec_configure_encoder() {
+        use crate::TransportMode;
+        use crate::media::{
+            AudioFrame, AudioSampleFormat, ChannelMediaSource, MediaSample, MediaSource,
+            OpusPacketizer,
+        };
+        use bytes::Bytes;
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        let pc = PeerConnection::new(config);
+
+        let (_, track, _) = sample_track(crate::media::frame::MediaKind::Audio, 48000);
+        let params = RtpCodecParameters {
+            payload_type: 111,
+            clock_rate: 48000,
+            channels: 2,
+        };
+        let sender = pc.add_track(track, params).unwrap();
+        let offer = pc.create_offer().await.unwrap();
+        pc.set_local_description(offer).unwrap();
+
+        let remote_answer = "v=0\r\n\
+            o=- 1 1 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            t=0 0\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            m=audio 8000 RTP/AVP 111\r\n\
+            a=rtpmap:111 opus/48000/2\r\n\
+            a=fmtp:111 useinbandfec=1;usedtx=1\r\n\
+            a=recvonly\r\n";
+        let desc = SessionDescription::parse(SdpType::Answer, remote_answer).unwrap();
+        pc.set_remote_description(desc).await.unwrap();
+
+        // 1 s of silence in 20 ms frames, then speech.
+        const FRAME: u32 = 960;
+        let (tx, source) = ChannelMediaSource::channel(crate::media::frame::MediaKind::Audio, 64);
+        let mut encoder =
+            OpusPacketizer::with_negotiated_fmtp(Box::new(source), sender.watch_negotiated_fmtp());
+        let settings = encoder.settings();
+        assert!(settings.inband_fec && settings.dtx);
+
+        for i in 0..51u32 {
+            let level = if i == 50 { 8000.0 } else { 0.0 };
+            let data: Vec<u8> = (0..FRAME)
+                .flat_map(|n| (((n as f32 * 0.05).sin() * level) as i16).to_le_bytes())
+                .collect();
+            tx.send(MediaSample::Audio(AudioFrame {
+                rtp_timestamp: i * FRAME,
+                clock_rate: 48000,
+                data: Bytes::from(data),
+                format: AudioSampleFormat::S16,
+                channels: 1,
+                ..Default::default()
+            }))
+            .unwrap();
+        }
+        drop(tx);
+
+        let mut sent = Vec::new();
+        while let Ok(MediaSample::Audio(frame)) = encoder.next_sample().await {
+            assert_eq!(frame.format, AudioSampleFormat::Encoded);
+            sent.push((frame.rtp_timestamp / FRAME, frame.marker));
+        }
+        // 200 ms hangover, an update every 400 ms, then the talkspurt
+        // resumes with the marker set.
+        let expected: Vec<(u32, bool)> = (0..10)
+            .map(|i| (i, false))
+            .chain([(29, false), (49, false), (50, true)])
+            .collect();
+        assert_eq!(sent, expected);
+    }
+
+    fn audio_video_offer(version: u32, mids: [&str; 2], ssrcs: [u32; 2], swap: bool) -> String {
+        let mid_lines = mids.map(|mid| match mid {
+            "" => String::new(),
+            mid => format!("a=mid:{mid}\r\n"),
+        });
+        let audio = format!(
+            "m=audio 8000 RTP/AVP 0\r\n\
+            {}a=rtpmap:0 PCMU/8000\r\n\
+            a=ssrc:{} cname:remote\r\n\
+            a=sendrecv\r\n",
+            mid_lines[0], ssrcs[0]
+        );
+        let video = format!(
+            "m=video 8002 RTP/AVP 96\r\n\
+            {}a=rtpmap:96 VP8/90000\r\n\
+            a=ssrc:{} cname:remote\r\n\
+            a=sendrecv\r\n",
+            mid_lines[1], ssrcs[1]
+        );
+        let media = if swap { video + &audio } else { audio + &video };
+        format!(
+            "v=0\r\n\
+            o=- 1 {version} IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            t=0 0\r\n\
+            {media}"
+        )
+    }
+
+    async fn negotiate_remote_offer(pc: &PeerConnection, sdp: &str) -> SessionDescription {
+        let offer = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        pc.set_remote_description(offer).await.unwrap();
+        let answer = pc.create_answer().await.unwrap();
+        pc.set_local_description(answer.clone()).unwrap();
+        answer
+    }
+
+    fn receiver_ssrc(pc: &PeerConnection, kind: MediaKind) -> (Option<String>, u32) {
+        let transceivers = pc.get_transceivers();
+        let t = transceivers.iter().find(|t| t.kind() == kind).unwrap();
+        (t.mid(), t.receiver().unwrap().ssrc())
+    }
+
+    #[tokio::test]
+    async fn reinvite_with_reordered_m_lines_matches_transceivers_by_mid() {
+        use crate::TransportMode;
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        let pc = PeerConnection::new(config);
+
+        let initial = audio_video_offer(1, ["a", "v"], [1111, 2222], false);
+        negotiate_remote_offer(&pc, &initial).await;
+        let audio = pc.get_transceivers()[0].clone();
+        let video = pc.get_transceivers()[1].clone();
+
+        // Same mids, m-lines swapped, new SSRCs on each stream.
+        let reordered = audio_video_offer(2, ["a", "v"], [3333, 4444], true);
+        let answer = negotiate_remote_offer(&pc, &reordered).await;
+
+        let transceivers = pc.get_transceivers();
+        assert_eq!(transceivers.len(), 2, "no transceiver was added");
+        assert!(Arc::ptr_eq(&transceivers[0], &audio));
+        assert!(Arc::ptr_eq(&transceivers[1], &video));
+        assert_eq!(
+            receiver_ssrc(&pc, MediaKind::Audio),
+            (Some("a".into()), 3333)
+        );
+        assert_eq!(
+            receiver_ssrc(&pc, MediaKind::Video),
+            (Some("v".into()), 4444)
+        );
+        let answered: Vec<_> = answer.media_sections.iter().map(|s| s.kind).collect();
+        assert_eq!(answered, vec![MediaKind::Video, MediaKind::Audio]);
+    }
+
+    #[tokio::test]
+    async fn mid_less_reinvite_matches_transceivers_by_position() {
+        use crate::TransportMode;
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        let pc = PeerConnection::new(config);
+
+        negotiate_remote_offer(&pc, &audio_video_offer(1, ["", ""], [1111, 2222], false)).await;
+        negotiate_remote_offer(&pc, &audio_video_offer(2, ["", ""], [3333, 4444], false)).await;
+
+        assert_eq!(pc.get_transceivers().len(), 2);
+        assert_eq!(receiver_ssrc(&pc, MediaKind::Audio).1, 3333);
+        assert_eq!(receiver_ssrc(&pc, MediaKind::Video).1, 4444);
+    }
+
     #[tokio::test]
     async fn reinvite_answer_audio_codecs_follow_remote_offer_subset() {
         use crate::TransportMode;
@@ -9008,6 +14114,154 @@ a=mid:0
         );
     }
 
+    #[test]
+    fn answer_audio_excludes_telephone_event_with_mismatched_clock_rate() {
+        let offer = "v=0\r\n\
+            o=- 1 1 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            t=0 0\r\n\
+            m=audio 8000 RTP/AVP 0 101 102\r\n\
+            a=rtpmap:0 PCMU/8000\r\n\
+            a=rtpmap:101 telephone-event/48000\r\n\
+            a=fmtp:101 0-16\r\n\
+            a=rtpmap:102 telephone-event/8000\r\n\
+            a=fmtp:102 0-16\r\n\
+            a=sendrecv\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, offer).unwrap();
+        let local_caps = vec![
+            AudioCapability::pcmu(),
+            AudioCapability::telephone_event(),
+            AudioCapability {
+                payload_type: 110,
+                clock_rate: 48000,
+                ..AudioCapability::telephone_event()
+            },
+        ];
+
+        let caps = PeerConnectionInner::derive_answer_audio_capabilities(
+            &desc.media_sections[0],
+            &local_caps,
+        );
+        let negotiated: Vec<(u8, u32)> = caps
+            .iter()
+            .map(|c| (c.payload_type, c.clock_rate))
+            .collect();
+        assert_eq!(negotiated, vec![(0, 8000), (102, 8000)]);
+    }
+
+    #[test]
+    fn answer_opus_fmtp_is_intersection_of_offer_and_capability() {
+        let offer = "v=0\r\n\
+            o=- 1 1 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            t=0 0\r\n\
+            m=audio 8000 RTP/AVP 109\r\n\
+            a=rtpmap:109 opus/48000/2\r\n\
+            a=fmtp:109 stereo=1;sprop-stereo=1;useinbandfec=1;maxplaybackrate=16000\r\n\
+            a=sendrecv\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, offer).unwrap();
+        let mono = AudioCapability {
+            fmtp: Some("minptime=10;useinbandfec=1;stereo=0;maxplaybackrate=24000".to_string()),
+            ..AudioCapability::opus()
+        };
+
+        let caps =
+            PeerConnectionInner::derive_answer_audio_capabilities(&desc.media_sections[0], &[mono]);
+        assert_eq!(caps.len(), 1);
+        assert_eq!(caps[0].payload_type, 109);
+        assert_eq!(
+            caps[0].fmtp.as_deref(),
+            Some("minptime=10;useinbandfec=1;stereo=0;maxplaybackrate=16000")
+        );
+    }
+
+    #[test]
+    fn answer_opus_fmtp_keeps_own_stereo_and_adds_no_remote_rates() {
+        let offer = "v=0\r\n\
+            o=- 1 1 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            t=0 0\r\n\
+            m=audio 8000 RTP/AVP 111\r\n\
+            a=rtpmap:111 opus/48000/2\r\n\
+            a=fmtp:111 stereo=0;sprop-stereo=0;maxplaybackrate=16000;sprop-maxcapturerate=16000;maxaveragebitrate=20000\r\n\
+            a=sendrecv\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, offer).unwrap();
+        let stereo = AudioCapability {
+            fmtp: Some("stereo=1;sprop-stereo=1;sprop-maxcapturerate=48000".to_string()),
+            ..AudioCapability::opus()
+        };
+
+        let caps = PeerConnectionInner::derive_answer_audio_capabilities(
+            &desc.media_sections[0],
+            &[stereo],
+        );
+        assert_eq!(
+            caps[0].fmtp.as_deref(),
+            Some("stereo=1;sprop-stereo=1;sprop-maxcapturerate=48000")
+        );
+    }
+
+    #[test]
+    fn answer_excludes_opus_with_malformed_fmtp() {
+        let offer = "v=0\r\n\
+            o=- 1 1 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            t=0 0\r\n\
+            m=audio 8000 RTP/AVP 111 0\r\n\
+            a=rtpmap:111 opus/48000/2\r\n\
+            a=fmtp:111 stereo=2;maxplaybackrate=96000\r\n\
+            a=rtpmap:0 PCMU/8000\r\n\
+            a=sendrecv\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, offer).unwrap();
+        let local_caps = vec![AudioCapability::opus(), AudioCapability::pcmu()];
+
+        let caps = PeerConnectionInner::derive_answer_audio_capabilities(
+            &desc.media_sections[0],
+            &local_caps,
+        );
+        let negotiated: Vec<u8> = caps.iter().map(|c| c.payload_type).collect();
+        assert_eq!(negotiated, vec![0]);
+    }
+
+    #[tokio::test]
+    async fn create_answer_negotiates_mono_when_offer_requests_stereo() {
+        use crate::TransportMode;
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        config.media_capabilities = Some(crate::config::MediaCapabilities {
+            audio: vec![AudioCapability {
+                channels: 1,
+                fmtp: Some("minptime=10;useinbandfec=1".to_string()),
+                ..AudioCapability::opus()
+            }],
+            ..Default::default()
+        });
+        let pc = PeerConnection::new(config);
+
+        let offer = "v=0\r\n\
+            o=- 1 1 IN IP4 10.0.0.1\r\n\
+            s=-\r\n\
+            c=IN IP4 10.0.0.1\r\n\
+            t=0 0\r\n\
+            m=audio 8000 RTP/AVP 111\r\n\
+            a=rtpmap:111 opus/48000/2\r\n\
+            a=fmtp:111 minptime=20;useinbandfec=1;stereo=1\r\n\
+            a=sendrecv\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, offer).unwrap();
+        pc.set_remote_description(desc).await.unwrap();
+
+        let answer = pc.create_answer().await.unwrap();
+        let audio = &answer.media_sections[0];
+        assert_eq!(audio.formats, vec!["111".to_string()]);
+        assert_eq!(audio.fmtp(111), Some("minptime=20;useinbandfec=1;stereo=0"));
+        assert!(answer.to_sdp_string().contains("a=rtpmap:111 opus/48000/2"));
+    }
+
     #[tokio::test]
     async fn reinvite_updates_remote_addr_in_rtp_mode() {
         use crate::{SdpType, SessionDescription, TransportMode};
@@ -9266,6 +14520,13 @@ a=mid:0
                 assert_eq!(t.kind(), MediaKind::Audio);
             }
             PeerConnectionEvent::DataChannel(_) => panic!("Expected Track event, got DataChannel"),
+            PeerConnectionEvent::RealtimeText(_) => {
+                panic!("Expected Track event, got RealtimeText")
+            }
+            PeerConnectionEvent::IceCandidate(_) | PeerConnectionEvent::EndOfCandidates => {
+                panic!("Expected Track event, got an ICE candidate event")
+            }
+            PeerConnectionEvent::SctpConnected => panic!("Expected Track event, got SctpConnected"),
         }
     }
 
@@ -9327,13 +14588,64 @@ a=mid:0
     fn sender_report_builder_uses_rtp_counters() {
         let report = RtpSender::build_sender_report(10000, 123456, 42, 4096, UNIX_EPOCH);
 
-        assert_eq!(report.sender_ssrc, 10000);
-        assert_eq!(report.rtp_timestamp, 123456);
-        assert_eq!(report.packet_count, 42);
-        assert_eq!(report.octet_count, 4096);
-        assert_eq!(report.ntp_most, 2_208_988_800);
-        assert_eq!(report.ntp_least, 0);
-        assert!(report.report_blocks.is_empty());
+        assert_eq!(report.sender_ssrc, 10000);
+        assert_eq!(report.rtp_timestamp, 123456);
+        assert_eq!(report.packet_count, 42);
+        assert_eq!(report.octet_count, 4096);
+        assert_eq!(report.ntp_most, 2_208_988_800);
+        assert_eq!(report.ntp_least, 0);
+        assert!(report.report_blocks.is_empty());
+    }
+
+    #[test]
+    fn sender_report_compound_starts_with_sr_and_carries_cname() {
+        let report = RtpSender::build_sender_report(10000, 123456, 42, 4096, UNIX_EPOCH);
+        let compound = RtpSender::build_report_compound(report, "rustrtc-cname-10000");
+        let bytes = crate::rtp::marshal_rtcp_packets(&compound).unwrap();
+
+        let parsed = crate::rtp::parse_rtcp_packets(&bytes, None).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(matches!(&parsed[0], RtcpPacket::SenderReport(sr) if sr.sender_ssrc == 10000));
+        match &parsed[1] {
+            RtcpPacket::SourceDescription(sdes) => {
+                assert_eq!(sdes.chunks.len(), 1);
+                assert_eq!(sdes.chunks[0].ssrc, 10000);
+                assert!(
+                    sdes.chunks[0].items.iter().any(
+                        |item| item.ty == RTCP_SDES_CNAME && item.text == "rustrtc-cname-10000"
+                    )
+                );
+            }
+            other => panic!("expected SDES after SR, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bye_is_sent_in_a_compound_after_a_report() {
+        let (_, track, _) = sample_track(crate::media::frame::MediaKind::Audio, 48000);
+        let sender = |ssrc| {
+            RtpSender::builder(track.clone(), ssrc)
+                .cname(format!("cname-{ssrc}"))
+                .build()
+        };
+        let senders = [sender(10000), sender(20000)];
+        assert!(RtpSender::build_bye_compound(&[], "closed", UNIX_EPOCH).is_none());
+
+        let compound = RtpSender::build_bye_compound(&senders, "closed", UNIX_EPOCH).unwrap();
+        let bytes = crate::rtp::marshal_rtcp_packets(&compound).unwrap();
+        let parsed = crate::rtp::parse_rtcp_packets(&bytes, None).unwrap();
+        assert_eq!(parsed.len(), 3);
+        // Nothing was sent yet, so the report is an RR.
+        assert!(matches!(&parsed[0], RtcpPacket::ReceiverReport(rr) if rr.sender_ssrc == 10000));
+        assert!(matches!(&parsed[1], RtcpPacket::SourceDescription(_)));
+        match &parsed[2] {
+            RtcpPacket::Goodbye(bye) => assert_eq!(bye.sources, vec![10000, 20000]),
+            other => panic!("expected BYE last, got {:?}", other),
+        }
+
+        senders[0].packets_sent.store(42, Ordering::Relaxed);
+        let compound = RtpSender::build_bye_compound(&senders, "closed", UNIX_EPOCH).unwrap();
+        assert!(matches!(&compound[0], RtcpPacket::SenderReport(sr) if sr.packet_count == 42));
     }
 
     // ---------------------------------------------------------------------------
@@ -9353,6 +14665,7 @@ a=mid:0
         let sdp_str = "v=0\r\n\
                        o=- 123 0 IN IP4 127.0.0.1\r\n\
                        s=-\r\n\
+                       c=IN IP4 127.0.0.1\r\n\
                        t=0 0\r\n\
                        m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
                        a=rtpmap:111 opus/48000/2\r\n\
@@ -9373,6 +14686,21 @@ a=mid:0
         );
     }
 
+    /// Type preferences above RFC 8445's 126 are rejected before gathering.
+    #[tokio::test]
+    async fn test_create_offer_rejects_out_of_range_type_preference() {
+        let mut config = RtcConfiguration::default();
+        config.candidate_type_preferences.relay = 127;
+        let pc = PeerConnection::new(config);
+        pc.create_data_channel("probe", None).unwrap();
+        let err = pc.create_offer().await.unwrap_err();
+        assert!(
+            matches!(err, RtcError::InvalidConfiguration(ref msg) if msg.contains("relay")),
+            "expected InvalidConfiguration, got: {:?}",
+            err
+        );
+    }
+
     /// WebRTC mode: SDP with a valid sha-256 a=fingerprint must be accepted.
     #[tokio::test]
     async fn test_set_remote_description_accepts_valid_sha256_fingerprint_webrtc() {
@@ -9388,6 +14716,7 @@ a=mid:0
             "v=0\r\n\
              o=- 123 0 IN IP4 127.0.0.1\r\n\
              s=-\r\n\
+             c=IN IP4 127.0.0.1\r\n\
              t=0 0\r\n\
              m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
              a=rtpmap:111 opus/48000/2\r\n\
@@ -9418,6 +14747,7 @@ a=mid:0
         let sdp_str = "v=0\r\n\
                        o=- 123 0 IN IP4 127.0.0.1\r\n\
                        s=-\r\n\
+                       c=IN IP4 127.0.0.1\r\n\
                        t=0 0\r\n\
                        m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
                        a=rtpmap:111 opus/48000/2\r\n\
@@ -9687,6 +15017,200 @@ a=mid:0
         );
     }
 
+    /// The SipGateway profile offers and answers one codec per m-line, without
+    /// BUNDLE or rtcp-mux, even when the remote offers both.
+    #[tokio::test]
+    async fn sip_gateway_profile_offer_and_answer_conform() {
+        use crate::TransportMode;
+        use crate::config::{AudioCapability, MediaCapabilities, VideoCapability};
+
+        fn assert_conforms(desc: &SessionDescription) {
+            let sdp = desc.to_sdp_string();
+            assert!(!sdp.contains("a=group:BUNDLE"), "no BUNDLE:\n{sdp}");
+            assert!(!sdp.contains("a=rtcp-mux"), "no rtcp-mux:\n{sdp}");
+            for section in &desc.media_sections {
+                assert_eq!(section.formats.len(), 1, "one codec:\n{sdp}");
+                let rtpmaps = section.attributes.iter().filter(|a| a.key == "rtpmap");
+                assert_eq!(rtpmaps.count(), 1, "single rtpmap:\n{sdp}");
+                assert!(
+                    section.attributes.iter().any(|a| a.key == "rtcp"),
+                    "a=rtcp present:\n{sdp}"
+                );
+            }
+        }
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+        config.profile = MediaProfile::SipGateway;
+        config.media_capabilities = Some(MediaCapabilities {
+            audio: vec![
+                AudioCapability::pcma(),
+                AudioCapability::pcmu(),
+                AudioCapability::telephone_event(),
+            ],
+            video: vec![
+                VideoCapability::h264(),
+                VideoCapability {
+                    payload_type: 100,
+                    ..VideoCapability::vp8_with_rtx(101)
+                },
+            ],
+            application: None,
+            image: vec![],
+        });
+
+        let offerer = PeerConnection::new(config.clone());
+        offerer.add_transceiver(MediaKind::Audio, TransceiverDirection::SendRecv);
+        offerer.add_transceiver(MediaKind::Video, TransceiverDirection::SendRecv);
+        let offer = offerer.create_offer().await.unwrap();
+        assert_conforms(&offer);
+        assert_eq!(offer.media_sections[0].formats, vec!["8".to_string()]);
+
+        let remote_offer = "v=0\r\n\
+            o=- 1 1 IN IP4 127.0.0.1\r\n\
+            s=-\r\n\
+            t=0 0\r\n\
+            c=IN IP4 127.0.0.1\r\n\
+            a=group:BUNDLE 0 1\r\n\
+            m=audio 40000 RTP/AVP 0 8 101\r\n\
+            a=mid:0\r\n\
+            a=rtpmap:0 PCMU/8000\r\n\
+            a=rtpmap:8 PCMA/8000\r\n\
+            a=rtpmap:101 telephone-event/8000\r\n\
+            a=fmtp:101 0-16\r\n\
+            a=rtcp-mux\r\n\
+            a=sendrecv\r\n\
+            m=video 40000 RTP/AVP 96 97\r\n\
+            a=mid:1\r\n\
+            a=rtpmap:96 H264/90000\r\n\
+            a=fmtp:96 packetization-mode=1;profile-level-id=42e01f\r\n\
+            a=rtpmap:97 rtx/90000\r\n\
+            a=fmtp:97 apt=96\r\n\
+            a=rtcp-mux\r\n\
+            a=sendrecv\r\n";
+        config.bind_ip = Some("127.0.0.1".to_string());
+        let answerer = PeerConnection::new(config);
+        answerer
+            .set_remote_description(
+                SessionDescription::parse(SdpType::Offer, remote_offer).unwrap(),
+            )
+            .await
+            .unwrap();
+        let answer = answerer.create_answer().await.unwrap();
+        assert_conforms(&answer);
+    }
+
+    #[tokio::test]
+    async fn sip_gateway_profile_offers_passive_dtls_setup() {
+        let config = RtcConfiguration {
+            profile: MediaProfile::SipGateway,
+            ..Default::default()
+        };
+        assert_eq!(config.effective_dtls_role(), Some(DtlsRole::Server));
+        let pc = PeerConnection::new(config);
+        pc.add_transceiver(MediaKind::Audio, TransceiverDirection::SendRecv);
+
+        let sdp = pc.create_offer().await.unwrap().to_sdp_string();
+        assert!(sdp.contains("a=setup:passive"), "got:\n{sdp}");
+        assert_eq!(pc.dtls_role(), Some(DtlsRole::Server));
+    }
+
+    #[tokio::test]
+    async fn rtp_answer_advertises_fixed_media_port() {
+        use crate::TransportMode;
+
+        let offer_sdp = "v=0\r\n\
+            o=- 1 1 IN IP4 127.0.0.1\r\n\
+            s=-\r\n\
+            t=0 0\r\n\
+            c=IN IP4 127.0.0.1\r\n\
+            m=audio 40000 RTP/AVP 0\r\n\
+            a=rtpmap:0 PCMU/8000\r\n\
+            a=sendrecv\r\n";
+        let answer_with = |port: u16| async move {
+            let config = RtcConfiguration {
+                transport_mode: TransportMode::Rtp,
+                bind_ip: Some("127.0.0.1".to_string()),
+                fixed_media_ports: HashMap::from([(MediaKind::Audio, port)]),
+                ..Default::default()
+            };
+            let pc = PeerConnection::new(config);
+            let offer = SessionDescription::parse(SdpType::Offer, offer_sdp).unwrap();
+            pc.set_remote_description(offer).await?;
+            pc.create_answer().await
+        };
+
+        let port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let answer = answer_with(port).await.unwrap();
+        assert_eq!(answer.media_sections[0].port, port);
+
+        // A port that is already taken fails negotiation instead of
+        // silently advertising another one.
+        let taken = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+        assert!(answer_with(port).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rtp_bundled_answer_honours_fixed_video_port() {
+        use crate::TransportMode;
+
+        let offer_sdp = "v=0\r\n\
+            o=- 1 1 IN IP4 127.0.0.1\r\n\
+            s=-\r\n\
+            t=0 0\r\n\
+            c=IN IP4 127.0.0.1\r\n\
+            a=group:BUNDLE 0 1\r\n\
+            m=audio 40000 RTP/AVP 0\r\n\
+            a=mid:0\r\n\
+            a=rtcp-mux\r\n\
+            a=rtpmap:0 PCMU/8000\r\n\
+            a=sendrecv\r\n\
+            m=video 40000 RTP/AVP 96\r\n\
+            a=mid:1\r\n\
+            a=rtcp-mux\r\n\
+            a=rtpmap:96 VP8/90000\r\n\
+            a=sendrecv\r\n";
+        let answer_with = |ports: HashMap<MediaKind, u16>| async move {
+            let config = RtcConfiguration {
+                transport_mode: TransportMode::Rtp,
+                bind_ip: Some("127.0.0.1".to_string()),
+                fixed_media_ports: ports,
+                ..Default::default()
+            };
+            let pc = PeerConnection::new(config);
+            let offer = SessionDescription::parse(SdpType::Offer, offer_sdp).unwrap();
+            pc.set_remote_description(offer).await?;
+            pc.create_answer().await
+        };
+
+        // Only video is pinned, but audio comes first and owns the shared
+        // socket; the pin must still apply to it.
+        let port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let answer = answer_with(HashMap::from([(MediaKind::Video, port)]))
+            .await
+            .unwrap();
+        assert_eq!(answer.media_sections.len(), 2);
+        assert!(answer.media_sections.iter().all(|m| m.port == port));
+
+        // Bundled kinds pinned to different ports cannot share a socket.
+        let err = answer_with(HashMap::from([
+            (MediaKind::Audio, port),
+            (MediaKind::Video, port + 1),
+        ]))
+        .await
+        .unwrap_err();
+        assert!(matches!(err, RtcError::InvalidConfiguration(_)), "{err:?}");
+    }
+
     #[tokio::test]
     async fn rtp_mode_legacy_non_bundle_offer_uses_distinct_media_ports() {
         use crate::TransportMode;
@@ -9988,6 +15512,68 @@ a=rtpmap:8 PCMA/8000\r\n";
         );
     }
 
+    /// Two audio and one video section bundled by the offerer share the one
+    /// ICE transport; a port-0 section outside the group is rejected.
+    #[tokio::test]
+    async fn answer_bundles_accepted_mids_over_single_ice_transport() {
+        use crate::sdp::SessionDescription;
+
+        let section = |kind: &str, port: u16, pt: &str, rtpmap: &str, mid: &str| {
+            format!(
+                "m={kind} {port} UDP/TLS/RTP/SAVPF {pt}\r\n\
+c=IN IP4 0.0.0.0\r\n\
+a=ice-ufrag:IIjZ\r\n\
+a=ice-pwd:h/NG2DkTNsPwhU0swhrzWbLD\r\n\
+a=fingerprint:sha-256 A9:96:C7:D5:20:2D:17:06:CC:7E:94:0D:89:AA:DE:47:8F:21:3F:97:B1:D5:C5:A2:41:48:E1:A5:8A:D5:BB:B1\r\n\
+a=setup:actpass\r\n\
+a=mid:{mid}\r\n\
+a=sendrecv\r\n\
+a=rtcp-mux\r\n\
+a=rtpmap:{pt} {rtpmap}\r\n"
+            )
+        };
+        let remote_sdp = format!(
+            "v=0\r\n\
+o=- 3572571646755393356 2 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+t=0 0\r\n\
+a=group:BUNDLE 0 1 2\r\n\
+{}{}{}{}",
+            section("audio", 9, "111", "opus/48000/2", "0"),
+            section("audio", 9, "111", "opus/48000/2", "1"),
+            section("video", 9, "96", "VP8/90000", "2"),
+            section("video", 0, "96", "VP8/90000", "3"),
+        );
+
+        let pc = PeerConnection::new(RtcConfiguration::default());
+        let remote = SessionDescription::parse(SdpType::Offer, &remote_sdp).unwrap();
+        assert_eq!(remote.bundle_groups(), vec![vec!["0", "1", "2"]]);
+        pc.set_remote_description(remote).await.unwrap();
+        pc.wait_for_gathering_complete().await;
+
+        let answer = pc.create_answer().await.unwrap();
+        assert_eq!(answer.bundle_groups(), vec![vec!["0", "1", "2"]]);
+        assert_eq!(answer.media_sections.len(), 4);
+        assert_eq!(answer.media_sections[3].port, 0);
+
+        let gathered: Vec<String> = pc
+            .ice_transport()
+            .local_candidates()
+            .iter()
+            .map(IceCandidate::to_sdp)
+            .collect();
+        assert!(!gathered.is_empty());
+        for section in &answer.media_sections[..3] {
+            let candidates: Vec<String> = section
+                .attributes
+                .iter()
+                .filter(|a| a.key == "candidate")
+                .filter_map(|a| a.value.clone())
+                .collect();
+            assert_eq!(candidates, gathered, "mid {}", section.mid);
+        }
+    }
+
     /// Reproduce: WebRTC caller ↔ plain-RTP callee bridge scenario.
     ///
     /// The RTP PeerConnection acts as the *offerer* (bridge → callee):
@@ -10924,6 +16510,40 @@ a=rtpmap:8 PCMA/8000\r\n";
         );
     }
 
+    /// Odd spacing and a missing channel count still register the codec; a
+    /// malformed `a=rtpmap` is skipped instead of guessing a clock rate.
+    #[tokio::test]
+    async fn remote_rtpmap_variants_build_payload_map() {
+        use crate::sdp::{SdpType, SessionDescription};
+
+        let mut config = RtcConfiguration::default();
+        config.transport_mode = TransportMode::Rtp;
+
+        let pc = PeerConnection::new(config);
+        pc.add_transceiver(MediaKind::Audio, TransceiverDirection::RecvOnly);
+
+        let remote_sdp = "v=0\r\n\
+                          o=- 1 1 IN IP4 127.0.0.1\r\n\
+                          s=-\r\n\
+                          t=0 0\r\n\
+                          c=IN IP4 127.0.0.1\r\n\
+                          m=audio 9000 RTP/AVP 111 9 96\r\n\
+                          a=rtpmap:111  OPUS/48000/2 \r\n\
+                          a=rtpmap:9 g722/8000\r\n\
+                          a=rtpmap:96 L16/rate\r\n\
+                          a=sendonly\r\n";
+
+        let desc = SessionDescription::parse(SdpType::Offer, remote_sdp).unwrap();
+        pc.set_remote_description(desc).await.unwrap();
+
+        let payload_map = pc.get_transceivers()[0].get_payload_map();
+        assert_eq!(payload_map[&111].clock_rate, 48000);
+        assert_eq!(payload_map[&111].channels, 2);
+        assert_eq!(payload_map[&9].clock_rate, 8000);
+        assert_eq!(payload_map[&9].channels, 0);
+        assert!(!payload_map.contains_key(&96));
+    }
+
     // ── Bug 3 integration: full ICE reconnect cycle ───────────────────────────
 
     /// End-to-end regression test for Bug 3 triggered by the real ICE reconnect
@@ -11190,4 +16810,229 @@ a=mid:0
             "Should create a new transceiver when no offer transceiver exists"
         );
     }
+
+    /// A re-INVITE that reassigns payload types must take effect for the very
+    /// next packet: new-PT packets use the new codec's clock rate and packets
+    /// still carrying the old PT are dropped instead of being depacketized
+    /// with the wrong parameters.
+    #[tokio::test]
+    async fn payload_type_reassignment_does_not_misroute_old_pt() {
+        use crate::media::frame::MediaSample;
+        use crate::rtp::RtpHeader;
+        use crate::transports::ice::conn::IceConn;
+        use crate::transports::rtp::RtpTransport;
+        use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+        let transceiver = Arc::new(RtpTransceiver::new_for_test(
+            MediaKind::Audio,
+            TransceiverDirection::RecvOnly,
+        ));
+        let receiver = RtpReceiverBuilder::new(MediaKind::Audio, 0)
+            .payload_map(transceiver.payload_map.clone())
+            .build();
+        transceiver.set_receiver(Some(receiver.clone()));
+        transceiver
+            .update_payload_map(HashMap::from([(
+                0u8,
+                RtpCodecParameters {
+                    payload_type: 0,
+                    clock_rate: 8000,
+                    channels: 1,
+                },
+            )]))
+            .unwrap();
+
+        let (_, socket_rx) =
+            tokio::sync::watch::channel::<Option<crate::transports::ice::IceSocketWrapper>>(None);
+        let ice_conn = IceConn::new(
+            socket_rx,
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+            None,
+        );
+        receiver.set_transport(Arc::new(RtpTransport::new(ice_conn, false)), None, None);
+        tokio::task::yield_now().await;
+
+        let packet_tx = receiver.packet_tx().unwrap();
+        let track = receiver.track();
+        let addr: SocketAddr = "127.0.0.1:20004".parse().unwrap();
+        let send = |pt: u8, seq: u16| {
+            let packet_tx = packet_tx.clone();
+            async move {
+                let pkt = crate::rtp::RtpPacket::new(
+                    RtpHeader::new(pt, seq, seq as u32 * 160, 0x1234_5678),
+                    vec![pt; 160],
+                );
+                packet_tx.send((pkt, addr)).await.unwrap();
+            }
+        };
+        let next_frame = || async {
+            match tokio::time::timeout(std::time::Duration::from_millis(500), track.recv()).await {
+                Ok(Ok(MediaSample::Audio(frame))) => Some(frame),
+                Ok(Ok(other)) => panic!("unexpected sample {:?}", other),
+                _ => None,
+            }
+        };
+
+        send(0, 1).await;
+        let frame = next_frame().await.expect("PT 0 before the switch");
+        assert_eq!(frame.payload_type, Some(0));
+        assert_eq!(frame.clock_rate, 8000);
+
+        // Re-INVITE moves the stream to PT 96 (opus).
+        transceiver
+            .update_payload_map(HashMap::from([(
+                96u8,
+                RtpCodecParameters {
+                    payload_type: 96,
+                    clock_rate: 48000,
+                    channels: 2,
+                },
+            )]))
+            .unwrap();
+
+        send(96, 2).await;
+        send(0, 3).await;
+        send(96, 4).await;
+        send(0, 5).await;
+
+        for _ in 0..2 {
+            let frame = next_frame().await.expect("new-PT frame");
+            assert_eq!(frame.payload_type, Some(96));
+            assert_eq!(frame.clock_rate, 48000);
+            assert!(frame.data.iter().all(|&b| b == 96));
+        }
+        assert!(
+            next_frame().await.is_none(),
+            "old-PT packets must be dropped after the switch"
+        );
+    }
+
+    #[test]
+    fn remote_extmap_direction_is_reversed_for_sending() {
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
+t=0 0\r\n\
+m=video 9 RTP/AVP 96\r\n\
+a=mid:0\r\n\
+a=rtpmap:96 VP8/90000\r\n\
+a=extmap:1/sendonly urn:ietf:params:rtp-hdrext:sdes:mid\r\n\
+a=extmap:2/recvonly urn:3gpp:video-orientation\r\n\
+a=extmap:3 urn:ietf:params:rtp-hdrext:ssrc-audio-level\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        let section = &desc.media_sections[0];
+
+        let transceiver =
+            RtpTransceiver::new_for_test(MediaKind::Video, TransceiverDirection::SendRecv);
+        *transceiver.mid.lock() = Some("0".to_string());
+        let (_, track, _) = sample_track(crate::media::frame::MediaKind::Video, 8);
+        let sender = RtpSender::builder(track, 42).build();
+        transceiver.set_sender(Some(sender.clone()));
+
+        transceiver
+            .update_extmap_with_directions(
+                PeerConnection::extract_extmap(section),
+                PeerConnection::extract_remote_extmap_directions(section),
+            )
+            .unwrap();
+
+        assert_eq!(
+            transceiver.get_extmap().get(&1).map(String::as_str),
+            Some(crate::sdp::SDES_MID_URI)
+        );
+        // The remote only sends mid and only receives CVO.
+        let directions = transceiver.extmap_directions();
+        assert_eq!(directions.get(&1), Some(&TransceiverDirection::RecvOnly));
+        assert_eq!(directions.get(&2), Some(&TransceiverDirection::SendOnly));
+        assert_eq!(directions.get(&3), Some(&TransceiverDirection::SendRecv));
+
+        assert!(sender.sdes_mid.lock().is_none());
+        assert_eq!(*sender.video_orientation_ext_id.lock(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn sender_drops_frames_over_negotiated_imageattr() {
+        use crate::media::frame::VideoFrame;
+        use tokio::net::UdpSocket;
+
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
+t=0 0\r\n\
+m=video 9 RTP/AVP 96\r\n\
+a=rtpmap:96 VP8/90000\r\n\
+a=imageattr:96 recv [x=1280,y=720]\r\n\
+a=framerate:30\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        let limits = PeerConnection::extract_video_limits(&desc.media_sections[0]);
+        assert_eq!(limits.max_resolution, Some((1280, 720)));
+        assert_eq!(limits.max_framerate, Some(30.0));
+
+        let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_socket_tx, socket_rx) = tokio::sync::watch::channel(Some(
+            crate::transports::ice::IceSocketWrapper::Udp(Arc::new(local)),
+        ));
+        let ice_conn = IceConn::new(socket_rx, remote.local_addr().unwrap(), None);
+        let transport = Arc::new(RtpTransport::new(ice_conn, false));
+
+        let (source, track, _) = sample_track(crate::media::frame::MediaKind::Video, 8);
+        let sender = RtpSender::builder(track, 42)
+            .params(RtpCodecParameters {
+                payload_type: 96,
+                clock_rate: 90000,
+                channels: 0,
+            })
+            .build();
+        sender.set_video_limits(limits);
+        sender.set_transport(transport);
+
+        for (i, (width, height)) in [(1920, 1080), (1280, 720), (1281, 720), (640, 480)]
+            .into_iter()
+            .enumerate()
+        {
+            source
+                .send_video(VideoFrame {
+                    rtp_timestamp: i as u32 * 3000,
+                    width,
+                    height,
+                    data: bytes::Bytes::from(vec![i as u8; 4]),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        let mut buf = [0u8; 1500];
+        let mut payloads = Vec::new();
+        while let Ok(Ok(n)) =
+            tokio::time::timeout(std::time::Duration::from_millis(200), remote.recv(&mut buf)).await
+        {
+            payloads.push(buf[12..n].to_vec());
+        }
+        assert_eq!(payloads, vec![vec![1u8; 4], vec![3u8; 4]]);
+    }
+
+    #[test]
+    fn video_frame_gate_limits_framerate_per_frame() {
+        use crate::media::frame::VideoFrame;
+
+        let limits = VideoSendLimits {
+            max_resolution: None,
+            max_framerate: Some(15.0),
+        };
+        let frame = |rtp_timestamp| VideoFrame {
+            rtp_timestamp,
+            ..Default::default()
+        };
+        let mut gate = VideoFrameGate::default();
+        // 30 fps input: every other frame is dropped, packets of a kept
+        // frame all pass.
+        assert!(gate.admit(&frame(0), limits, 90000));
+        assert!(gate.admit(&frame(0), limits, 90000));
+        assert!(!gate.admit(&frame(3000), limits, 90000));
+        assert!(!gate.admit(&frame(3000), limits, 90000));
+        assert!(gate.admit(&frame(6000), limits, 90000));
+    }
 }
@@ -1,8 +1,9 @@
 use crate::errors::{RtpError, RtpResult};
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 use std::net::SocketAddr;
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 use tracing::debug;
 
 const RTP_VERSION: u8 = 2;
@@ -10,11 +11,15 @@ pub const RTCP_SR: u8 = 200;
 pub const RTCP_RR: u8 = 201;
 pub const RTCP_SDES: u8 = 202;
 pub const RTCP_BYE: u8 = 203;
+pub const RTCP_APP: u8 = 204;
 pub const RTCP_RTPFB: u8 = 205;
 pub const RTCP_PSFB: u8 = 206;
 /// RTCP Extended Report (RFC 3611)
 pub const RTCP_XR: u8 = 207;
 
+/// SDES item types (RFC 3550 §6.5).
+pub const RTCP_SDES_CNAME: u8 = 1;
+
 pub const RTCP_RTPFB_NACK: u8 = 1;
 pub const RTCP_RTPFB_TWCC: u8 = 15;
 
@@ -35,6 +40,134 @@ impl RtpHeaderExtension {
             data: Bytes::from(data),
         }
     }
+
+    /// Look up the element with `id` in a one-byte (0xBEDE) or two-byte
+    /// (0x1000) header extension block.
+    pub fn get(&self, id: u8) -> Option<Bytes> {
+        if self.profile == 0xBEDE {
+            let mut offset = 0;
+            while offset < self.data.len() {
+                let b = self.data[offset];
+                if b == 0 {
+                    offset += 1;
+                    continue;
+                }
+                let ext_id = b >> 4;
+                let len = (b & 0x0F) as usize + 1;
+                offset += 1;
+
+                if ext_id == 15 {
+                    break;
+                }
+
+                if ext_id == id {
+                    if offset + len <= self.data.len() {
+                        return Some(self.data.slice(offset..offset + len));
+                    } else {
+                        return None;
+                    }
+                }
+                offset += len;
+            }
+        } else if self.profile == 0x1000 {
+            let mut offset = 0;
+            while offset < self.data.len() {
+                let ext_id = self.data[offset];
+                if ext_id == 0 {
+                    offset += 1;
+                    continue;
+                }
+                offset += 1;
+
+                if offset >= self.data.len() {
+                    break;
+                }
+                let len = self.data[offset] as usize;
+                offset += 1;
+
+                if ext_id == id {
+                    if offset + len <= self.data.len() {
+                        return Some(self.data.slice(offset..offset + len));
+                    } else {
+                        return None;
+                    }
+                }
+                offset += len;
+            }
+        } else {
+            // Unsupported extension profile
+        }
+        None
+    }
+}
+
+/// Frame marking header extension (draft-ietf-avtext-framemarking), letting a
+/// middlebox see frame boundaries and layers without depacketizing.
+///
+/// The one-byte form carries only the flags; the three-byte form adds the
+/// layer IDs and TL0PICIDX for scalable streams.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameMarking {
+    pub start_of_frame: bool,
+    pub end_of_frame: bool,
+    pub independent: bool,
+    pub discardable: bool,
+    pub base_layer_sync: bool,
+    /// Temporal layer ID (TID, 0-7).
+    pub temporal_id: u8,
+    /// Layer ID (LID); the spatial layer for VP9 and other SVC codecs.
+    pub spatial_id: u8,
+    pub tl0_pic_idx: u8,
+}
+
+impl FrameMarking {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let flags = *data.first()?;
+        let scalable = data.len() >= 3;
+        Some(Self {
+            start_of_frame: flags & 0x80 != 0,
+            end_of_frame: flags & 0x40 != 0,
+            independent: flags & 0x20 != 0,
+            discardable: flags & 0x10 != 0,
+            base_layer_sync: scalable && flags & 0x08 != 0,
+            temporal_id: if scalable { flags & 0x07 } else { 0 },
+            spatial_id: if scalable { data[1] } else { 0 },
+            tl0_pic_idx: if scalable { data[2] } else { 0 },
+        })
+    }
+
+    /// Whether the layer fields are present, i.e. the three-byte form is used.
+    pub fn is_scalable(&self) -> bool {
+        self.base_layer_sync
+            || self.temporal_id != 0
+            || self.spatial_id != 0
+            || self.tl0_pic_idx != 0
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0u8;
+        for (set, bit) in [
+            (self.start_of_frame, 0x80),
+            (self.end_of_frame, 0x40),
+            (self.independent, 0x20),
+            (self.discardable, 0x10),
+        ] {
+            if set {
+                flags |= bit;
+            }
+        }
+        if !self.is_scalable() {
+            return vec![flags];
+        }
+        if self.base_layer_sync {
+            flags |= 0x08;
+        }
+        vec![
+            flags | (self.temporal_id & 0x07),
+            self.spatial_id,
+            self.tl0_pic_idx,
+        ]
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -62,62 +195,7 @@ impl RtpHeader {
     }
 
     pub fn get_extension(&self, id: u8) -> Option<Bytes> {
-        if let Some(ext) = &self.extension {
-            if ext.profile == 0xBEDE {
-                let mut offset = 0;
-                while offset < ext.data.len() {
-                    let b = ext.data[offset];
-                    if b == 0 {
-                        offset += 1;
-                        continue;
-                    }
-                    let ext_id = b >> 4;
-                    let len = (b & 0x0F) as usize + 1;
-                    offset += 1;
-
-                    if ext_id == 15 {
-                        break;
-                    }
-
-                    if ext_id == id {
-                        if offset + len <= ext.data.len() {
-                            return Some(ext.data.slice(offset..offset + len));
-                        } else {
-                            return None;
-                        }
-                    }
-                    offset += len;
-                }
-            } else if ext.profile == 0x1000 {
-                let mut offset = 0;
-                while offset < ext.data.len() {
-                    let ext_id = ext.data[offset];
-                    if ext_id == 0 {
-                        offset += 1;
-                        continue;
-                    }
-                    offset += 1;
-
-                    if offset >= ext.data.len() {
-                        break;
-                    }
-                    let len = ext.data[offset] as usize;
-                    offset += 1;
-
-                    if ext_id == id {
-                        if offset + len <= ext.data.len() {
-                            return Some(ext.data.slice(offset..offset + len));
-                        } else {
-                            return None;
-                        }
-                    }
-                    offset += len;
-                }
-            } else {
-                // Unsupported extension profile
-            }
-        }
-        None
+        self.extension.as_ref()?.get(id)
     }
 
     pub fn set_extension(&mut self, id: u8, data: &[u8]) -> RtpResult<()> {
@@ -324,7 +402,7 @@ impl RtpPacket {
     }
 
     fn marshal_impl(packet: &RtpPacket, buffer: &mut Vec<u8>) {
-        buffer.reserve(12 + packet.header.csrcs.len() * 4 + packet.payload.len() as usize);
+        buffer.reserve(12 + packet.header.csrcs.len() * 4 + packet.payload.len());
         let mut b0 = RTP_VERSION << 6;
         if packet.padding_len > 0 {
             b0 |= 0x20;
@@ -396,6 +474,15 @@ pub struct SenderReport {
     pub report_blocks: Vec<ReportBlock>,
 }
 
+impl SenderReport {
+    /// Sender wallclock time carried in the NTP timestamp fields.
+    pub fn ntp_time(&self) -> SystemTime {
+        let secs = (self.ntp_most as u64).saturating_sub(2208988800);
+        let nanos = (self.ntp_least as u64 * 1_000_000_000) >> 32;
+        std::time::UNIX_EPOCH + std::time::Duration::new(secs, nanos as u32)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReceiverReport {
     pub sender_ssrc: u32,
@@ -468,7 +555,20 @@ pub struct Goodbye {
     pub reason: Option<String>,
 }
 
+/// RTCP packet this crate does not interpret (APP, XR, unknown feedback
+/// formats). The body is kept verbatim, minus padding, so it can be relayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownRtcp {
+    pub packet_type: u8,
+    /// Count / feedback message type field of the header.
+    pub fmt: u8,
+    pub payload: Vec<u8>,
+}
+
+/// Parsed RTCP packet. New kinds may be added, so matches need a wildcard
+/// arm; `Unknown` carries anything not interpreted here.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum RtcpPacket {
     SenderReport(SenderReport),
     ReceiverReport(ReceiverReport),
@@ -479,6 +579,7 @@ pub enum RtcpPacket {
     GenericNack(GenericNack),
     RemoteBitrateEstimate(RemoteBitrateEstimate),
     TransportWideCc(TransportWideCc),
+    Unknown(UnknownRtcp),
 }
 
 pub fn parse_rtcp_packets(raw: &[u8], addr: Option<SocketAddr>) -> RtpResult<Vec<RtcpPacket>> {
@@ -517,14 +618,15 @@ pub fn parse_rtcp_packets(raw: &[u8], addr: Option<SocketAddr>) -> RtpResult<Vec
             RTCP_BYE => packets.push(RtcpPacket::Goodbye(parse_goodbye(fmt, body)?)),
             RTCP_RTPFB => packets.push(parse_rtcp_rtpfb(fmt, body)?),
             RTCP_PSFB => packets.push(parse_rtcp_psfb(fmt, body)?),
-            RTCP_XR => {
-                // XR (Extended Report, RFC 3611) — valid but not parsed; skip silently
-            }
             _ => {
-                debug!(
-                    "unsupported RTCP packet type: {} from {:?}",
-                    packet_type, addr
-                );
+                // APP and XR (RFC 3611) are valid but not interpreted here.
+                if packet_type != RTCP_APP && packet_type != RTCP_XR {
+                    debug!(
+                        "unsupported RTCP packet type: {} from {:?}",
+                        packet_type, addr
+                    );
+                }
+                packets.push(unknown_rtcp(packet_type, fmt, body));
             }
         }
         offset += packet_len;
@@ -581,6 +683,12 @@ pub fn marshal_rtcp_packets(packets: &[RtcpPacket]) -> RtpResult<Vec<u8>> {
             RtcpPacket::TransportWideCc(twcc) => {
                 write_rtcp_packet(&mut out, RTCP_RTPFB_TWCC, RTCP_RTPFB, build_twcc_body(twcc))
             }
+            RtcpPacket::Unknown(unknown) => write_rtcp_packet(
+                &mut out,
+                unknown.fmt,
+                unknown.packet_type,
+                unknown.payload.clone(),
+            ),
         }
     }
     Ok(out)
@@ -750,7 +858,7 @@ fn parse_rtcp_rtpfb(fmt: u8, body: &[u8]) -> RtpResult<RtcpPacket> {
     match fmt {
         RTCP_RTPFB_NACK => Ok(RtcpPacket::GenericNack(parse_nack_body(body)?)),
         RTCP_RTPFB_TWCC => Ok(RtcpPacket::TransportWideCc(parse_twcc_body(body)?)),
-        _ => Err(RtpError::InvalidRtcp("unsupported RTP feedback format")),
+        _ => Ok(unknown_rtcp(RTCP_RTPFB, fmt, body)),
     }
 }
 
@@ -758,11 +866,21 @@ fn parse_rtcp_psfb(fmt: u8, body: &[u8]) -> RtpResult<RtcpPacket> {
     match fmt {
         RTCP_PSFB_PLI => Ok(RtcpPacket::PictureLossIndication(parse_psfb_common(body)?)),
         RTCP_PSFB_FIR => Ok(RtcpPacket::FullIntraRequest(parse_fir_body(body)?)),
-        RTCP_PSFB_APP => Ok(RtcpPacket::RemoteBitrateEstimate(parse_remb_body(body)?)),
-        _ => Err(RtpError::InvalidRtcp("unsupported payload feedback format")),
+        RTCP_PSFB_APP if body.get(8..12) == Some(b"REMB") => {
+            Ok(RtcpPacket::RemoteBitrateEstimate(parse_remb_body(body)?))
+        }
+        _ => Ok(unknown_rtcp(RTCP_PSFB, fmt, body)),
     }
 }
 
+fn unknown_rtcp(packet_type: u8, fmt: u8, body: &[u8]) -> RtcpPacket {
+    RtcpPacket::Unknown(UnknownRtcp {
+        packet_type,
+        fmt,
+        payload: body.to_vec(),
+    })
+}
+
 fn parse_psfb_common(body: &[u8]) -> RtpResult<PictureLossIndication> {
     if body.len() < 8 {
         return Err(RtpError::InvalidRtcp("payload feedback body too short"));
@@ -1046,6 +1164,235 @@ fn pack_nack_pairs(packets: &[u16]) -> Vec<(u16, u16)> {
     pairs
 }
 
+/// Default timestamp jump beyond which a packet starts a new talkspurt
+/// rather than waiting for, or counting as, a late packet.
+pub const DEFAULT_MAX_TIMESTAMP_GAP: Duration = Duration::from_secs(1);
+
+/// Receive-side reorder buffer for one RTP stream.
+///
+/// Packets are released in sequence order. The head is released as soon as
+/// it follows the last released packet; across a gap it is held until it has
+/// waited `target_delay`, after which the missing packets count as lost and
+/// are dropped if they still arrive. A new SSRC resets the buffer.
+///
+/// Once the codec's clock rate is known, a packet more than
+/// [`set_max_timestamp_gap`](Self::set_max_timestamp_gap) of media away from
+/// the last released one (e.g. a sender resuming after DTX silence) starts a
+/// new talkspurt. If it still lies just ahead in sequence, the packets missing
+/// before it are given up on at once; otherwise the sender restarted its
+/// sequence, and whatever is still buffered is released first.
+///
+/// This is separate from [`JitterBuffer`](crate::media::JitterBuffer), which
+/// reorders depacketized [`MediaSample`](crate::media::MediaSample)s. The
+/// receiver has to reorder before depacketization, because depacketizers
+/// reassemble frames (e.g. H.264 FU-A) from packets in sequence order. It
+/// also needs the SSRC, which samples do not carry, and takes `now` from the
+/// caller so the receive loop drives it from a single clock.
+pub struct RtpJitterBuffer {
+    target_delay: Duration,
+    capacity: usize,
+    ssrc: Option<u32>,
+    clock_rate: u32,
+    max_timestamp_gap: Duration,
+    /// `max_timestamp_gap` in clock-rate units; zero disables detection.
+    max_gap_ticks: u64,
+    /// Extended sequence number of the newest packet seen.
+    highest: Option<u64>,
+    /// Extended sequence number of the last released packet.
+    released: Option<u64>,
+    /// RTP timestamp of the last released packet.
+    released_timestamp: Option<u32>,
+    packets: BTreeMap<u64, (Instant, RtpPacket, SocketAddr)>,
+    /// The previous talkspurt's packets, released ahead of `packets`.
+    draining: VecDeque<(Instant, RtpPacket, SocketAddr)>,
+    late_packets: u64,
+}
+
+impl RtpJitterBuffer {
+    pub fn new(target_delay: Duration, capacity: usize) -> Self {
+        Self {
+            target_delay,
+            capacity: capacity.max(1),
+            ssrc: None,
+            clock_rate: 0,
+            max_timestamp_gap: DEFAULT_MAX_TIMESTAMP_GAP,
+            max_gap_ticks: 0,
+            highest: None,
+            released: None,
+            released_timestamp: None,
+            packets: BTreeMap::new(),
+            draining: VecDeque::new(),
+            late_packets: 0,
+        }
+    }
+
+    /// Clock rate of the stream's codec, which scales the timestamp gap
+    /// treated as a discontinuity. Detection is off while it is zero.
+    pub fn set_clock_rate(&mut self, clock_rate: u32) {
+        if clock_rate != self.clock_rate {
+            self.clock_rate = clock_rate;
+            self.update_max_gap_ticks();
+        }
+    }
+
+    /// Media time a packet may jump from the last released one before it
+    /// starts a new talkspurt. Defaults to [`DEFAULT_MAX_TIMESTAMP_GAP`].
+    pub fn set_max_timestamp_gap(&mut self, gap: Duration) {
+        self.max_timestamp_gap = gap;
+        self.update_max_gap_ticks();
+    }
+
+    fn update_max_gap_ticks(&mut self) {
+        self.max_gap_ticks =
+            u64::from(self.clock_rate) * self.max_timestamp_gap.as_millis() as u64 / 1000;
+    }
+
+    /// Buffer a packet. Returns `false` for duplicates and for packets whose
+    /// playout time has already passed.
+    pub fn push(&mut self, packet: RtpPacket, addr: SocketAddr, now: Instant) -> bool {
+        if self.ssrc != Some(packet.header.ssrc) {
+            if self.ssrc.is_some() {
+                debug!(
+                    "RtpJitterBuffer: SSRC changed to {}, resetting",
+                    packet.header.ssrc
+                );
+            }
+            self.reset();
+            self.ssrc = Some(packet.header.ssrc);
+        }
+
+        let mut seq = self.extend(packet.header.sequence_number);
+        if self.is_discontinuity(&packet, seq) {
+            debug!(
+                "RtpJitterBuffer: timestamp discontinuity on SSRC {}, starting a new talkspurt",
+                packet.header.ssrc
+            );
+            if self.is_ahead(seq) {
+                self.skip_to(seq);
+            } else {
+                self.restart();
+                seq = self.extend(packet.header.sequence_number);
+            }
+        }
+        if self.released.is_some_and(|released| seq <= released) {
+            self.late_packets += 1;
+            return false;
+        }
+        if self.packets.contains_key(&seq) {
+            return false;
+        }
+        self.highest = Some(self.highest.map_or(seq, |highest| highest.max(seq)));
+        self.packets.insert(seq, (now, packet, addr));
+        true
+    }
+
+    /// Next packet due for playout at `now`.
+    pub fn pop(&mut self, now: Instant) -> Option<(RtpPacket, SocketAddr)> {
+        if let Some((_, packet, addr)) = self.draining.pop_front() {
+            return Some((packet, addr));
+        }
+        if self.next_release()? > now {
+            return None;
+        }
+        let (seq, (_, packet, addr)) = self.packets.pop_first()?;
+        self.released = Some(seq);
+        self.released_timestamp = Some(packet.header.timestamp);
+        Some((packet, addr))
+    }
+
+    /// When the head packet becomes due, or `None` while empty.
+    pub fn next_release(&self) -> Option<Instant> {
+        if let Some((arrival, _, _)) = self.draining.front() {
+            return Some(*arrival);
+        }
+        let (&seq, (arrival, _, _)) = self.packets.first_key_value()?;
+        let in_order = self.released.is_some_and(|released| seq == released + 1);
+        if in_order || self.packets.len() > self.capacity {
+            Some(*arrival)
+        } else {
+            Some(*arrival + self.target_delay)
+        }
+    }
+
+    /// Drop buffered packets and forget the stream position.
+    pub fn reset(&mut self) {
+        self.ssrc = None;
+        self.highest = None;
+        self.released = None;
+        self.released_timestamp = None;
+        self.packets.clear();
+        self.draining.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.packets.len() + self.draining.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty() && self.draining.is_empty()
+    }
+
+    /// Packets dropped because they arrived after their playout time.
+    pub fn late_packets(&self) -> u64 {
+        self.late_packets
+    }
+
+    /// Whether `packet` (at extended sequence `seq`) jumps too far in time
+    /// from the last released packet. A packet just behind it in sequence
+    /// that also goes back in time is late, not a new talkspurt.
+    fn is_discontinuity(&self, packet: &RtpPacket, seq: u64) -> bool {
+        let (Some(released), Some(timestamp)) = (self.released, self.released_timestamp) else {
+            return false;
+        };
+        if self.max_gap_ticks == 0 {
+            return false;
+        }
+        let gap = packet.header.timestamp.wrapping_sub(timestamp) as i32;
+        let late = seq <= released && released - seq <= self.capacity as u64;
+        if late && gap < 0 {
+            return false;
+        }
+        u64::from(gap.unsigned_abs()) > self.max_gap_ticks
+    }
+
+    /// Whether `seq` lies within the buffer's reach ahead of the last
+    /// released packet.
+    fn is_ahead(&self, seq: u64) -> bool {
+        self.released
+            .is_some_and(|released| seq > released && seq - released <= self.capacity as u64)
+    }
+
+    /// Queue the packets before `seq` for immediate release and stop waiting
+    /// for the ones missing there, so `seq` plays out as soon as it is due.
+    fn skip_to(&mut self, seq: u64) {
+        let newer = self.packets.split_off(&seq);
+        self.draining
+            .extend(std::mem::replace(&mut self.packets, newer).into_values());
+        self.released = Some(seq - 1);
+        self.released_timestamp = None;
+    }
+
+    /// Queue what is buffered for immediate release and forget the stream
+    /// position, so the next packet starts a new talkspurt.
+    fn restart(&mut self) {
+        self.draining
+            .extend(std::mem::take(&mut self.packets).into_values());
+        self.highest = None;
+        self.released = None;
+        self.released_timestamp = None;
+    }
+
+    /// Unwrap a 16-bit sequence number relative to the newest one seen. The
+    /// first packet starts one cycle in, so earlier packets stay positive.
+    fn extend(&self, seq: u16) -> u64 {
+        let Some(highest) = self.highest else {
+            return (1 << 16) | seq as u64;
+        };
+        let delta = seq.wrapping_sub(highest as u16) as i16;
+        highest.saturating_add_signed(delta as i64)
+    }
+}
+
 pub fn is_rtcp(packet: &[u8]) -> bool {
     packet.len() >= 2 && (192..=208).contains(&packet[1])
 }
@@ -1092,6 +1439,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn unknown_rtcp_roundtrip() {
+        let app = RtcpPacket::Unknown(UnknownRtcp {
+            packet_type: RTCP_APP,
+            fmt: 3,
+            payload: [&0x1122_3344u32.to_be_bytes()[..], b"TEST", &[1, 2, 3, 4]].concat(),
+        });
+        // PSFB application layer feedback that is not REMB.
+        let afb = RtcpPacket::Unknown(UnknownRtcp {
+            packet_type: RTCP_PSFB,
+            fmt: RTCP_PSFB_APP,
+            payload: [&[0u8; 8][..], b"GOOG", &[9, 9, 9, 9]].concat(),
+        });
+        let pli = RtcpPacket::PictureLossIndication(PictureLossIndication {
+            sender_ssrc: 1,
+            media_ssrc: 2,
+        });
+        let packets = vec![app, afb, pli];
+        let raw = marshal_rtcp_packets(&packets).unwrap();
+        assert_eq!(parse_rtcp_packets(&raw, None).unwrap(), packets);
+    }
+
+    #[test]
+    fn frame_marking_roundtrip() {
+        let flags_only = FrameMarking {
+            start_of_frame: true,
+            independent: true,
+            ..Default::default()
+        };
+        assert_eq!(flags_only.to_bytes(), vec![0xA0]);
+        assert_eq!(FrameMarking::parse(&[0xA0]), Some(flags_only));
+
+        let layered = FrameMarking {
+            end_of_frame: true,
+            discardable: true,
+            base_layer_sync: true,
+            temporal_id: 2,
+            spatial_id: 1,
+            tl0_pic_idx: 77,
+            ..Default::default()
+        };
+        assert_eq!(layered.to_bytes(), vec![0x5A, 1, 77]);
+        assert_eq!(FrameMarking::parse(&layered.to_bytes()), Some(layered));
+        assert_eq!(FrameMarking::parse(&[]), None);
+    }
+
     #[test]
     fn nack_pair_encoding() {
         let pairs = pack_nack_pairs(&[10, 11, 12, 30]);
@@ -1221,4 +1614,132 @@ mod tests {
         let abs2 = calculate_abs_send_time(t2);
         assert_eq!(abs2, 0x40000); // 1 << 18
     }
+
+    fn jitter_packet(ssrc: u32, seq: u16) -> RtpPacket {
+        RtpPacket::new(RtpHeader::new(0, seq, seq as u32 * 160, ssrc), vec![0; 160])
+    }
+
+    fn drain(jb: &mut RtpJitterBuffer, now: Instant) -> Vec<u16> {
+        std::iter::from_fn(|| jb.pop(now))
+            .map(|(packet, _)| packet.header.sequence_number)
+            .collect()
+    }
+
+    #[test]
+    fn jitter_buffer_reorders_across_wraparound() {
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let start = Instant::now();
+        let delay = Duration::from_millis(60);
+        let mut jb = RtpJitterBuffer::new(delay, 64);
+        for seq in [65534, 0, 65535, 1] {
+            assert!(jb.push(jitter_packet(7, seq), addr, start));
+        }
+        // The first packet waits out the target delay.
+        assert!(drain(&mut jb, start).is_empty());
+        assert_eq!(jb.next_release(), Some(start + delay));
+        assert_eq!(drain(&mut jb, start + delay), vec![65534, 65535, 0, 1]);
+
+        // In-order packets are released immediately; a gap is held.
+        assert!(jb.push(jitter_packet(7, 3), addr, start + delay));
+        assert!(drain(&mut jb, start + delay).is_empty());
+        assert!(jb.push(jitter_packet(7, 2), addr, start + delay));
+        assert_eq!(drain(&mut jb, start + delay), vec![2, 3]);
+    }
+
+    #[test]
+    fn jitter_buffer_drops_late_packets_and_resets_on_ssrc_change() {
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let start = Instant::now();
+        let delay = Duration::from_millis(40);
+        let mut jb = RtpJitterBuffer::new(delay, 64);
+        jb.push(jitter_packet(7, 10), addr, start);
+        jb.push(jitter_packet(7, 12), addr, start);
+        assert_eq!(drain(&mut jb, start + delay), vec![10, 12]);
+
+        // 11 was given up on when 12 played out.
+        assert!(!jb.push(jitter_packet(7, 11), addr, start + delay));
+        assert!(!jb.push(jitter_packet(7, 12), addr, start + delay));
+        assert_eq!(jb.late_packets(), 2);
+
+        // A new source starts over, even with lower sequence numbers.
+        assert!(jb.push(jitter_packet(8, 5), addr, start + delay));
+        assert!(jb.push(jitter_packet(8, 4), addr, start + delay));
+        assert_eq!(drain(&mut jb, start + delay * 2), vec![4, 5]);
+    }
+
+    #[test]
+    fn jitter_buffer_resumes_after_timestamp_discontinuity() {
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let start = Instant::now();
+        let delay = Duration::from_millis(40);
+        let mut jb = RtpJitterBuffer::new(delay, 64);
+        jb.set_clock_rate(8000);
+        for seq in 10..13 {
+            jb.push(jitter_packet(7, seq), addr, start);
+        }
+        assert_eq!(drain(&mut jb, start + delay), vec![10, 11, 12]);
+
+        // After 30 s of DTX silence the sender resumes on a new sequence
+        // base; without resync every packet would count as late.
+        let resume = start + Duration::from_secs(30);
+        let talkspurt_ts = 12 * 160 + 30 * 8000;
+        for (i, seq) in [3u16, 4, 5].into_iter().enumerate() {
+            let header = RtpHeader::new(0, seq, talkspurt_ts + i as u32 * 160, 7);
+            assert!(jb.push(RtpPacket::new(header, vec![0; 160]), addr, resume));
+        }
+        assert_eq!(jb.next_release(), Some(resume + delay));
+        assert_eq!(drain(&mut jb, resume + delay), vec![3, 4, 5]);
+        assert_eq!(jb.late_packets(), 0);
+
+        // An in-sequence packet plays out right away, whatever its timestamp.
+        let header = RtpHeader::new(0, 6, talkspurt_ts + 60 * 8000, 7);
+        assert!(jb.push(RtpPacket::new(header, vec![0; 160]), addr, resume + delay));
+        assert_eq!(drain(&mut jb, resume + delay), vec![6]);
+    }
+
+    #[test]
+    fn jitter_buffer_skips_missing_packets_across_timestamp_jump() {
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let start = Instant::now();
+        let delay = Duration::from_millis(40);
+        let mut jb = RtpJitterBuffer::new(delay, 64);
+        jb.set_clock_rate(8000);
+        jb.push(jitter_packet(7, 10), addr, start);
+        assert_eq!(drain(&mut jb, start + delay), vec![10]);
+        assert!(jb.push(jitter_packet(7, 12), addr, start));
+
+        // 5 s later the sequence continues past a gap: the new talkspurt
+        // plays out at once instead of waiting for 11 and 13.
+        let resume = start + Duration::from_secs(5);
+        let header = RtpHeader::new(0, 14, 14 * 160 + 5 * 8000, 7);
+        assert!(jb.push(RtpPacket::new(header, vec![0; 160]), addr, resume));
+        assert_eq!(drain(&mut jb, resume), vec![12, 14]);
+
+        // The packets skipped over are late, not another restart.
+        assert!(!jb.push(jitter_packet(7, 13), addr, resume));
+        assert_eq!(jb.late_packets(), 1);
+
+        // With a larger threshold the same jump is an ordinary gap.
+        let mut jb = RtpJitterBuffer::new(delay, 64);
+        jb.set_clock_rate(8000);
+        jb.set_max_timestamp_gap(Duration::from_secs(10));
+        jb.push(jitter_packet(7, 10), addr, start);
+        assert_eq!(drain(&mut jb, start + delay), vec![10]);
+        let header = RtpHeader::new(0, 14, 14 * 160 + 5 * 8000, 7);
+        assert!(jb.push(RtpPacket::new(header, vec![0; 160]), addr, resume));
+        assert!(drain(&mut jb, resume).is_empty());
+        assert_eq!(drain(&mut jb, resume + delay), vec![14]);
+    }
+
+    #[test]
+    fn jitter_buffer_releases_early_when_full() {
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let now = Instant::now();
+        let mut jb = RtpJitterBuffer::new(Duration::from_secs(10), 2);
+        for seq in [3, 1, 2] {
+            jb.push(jitter_packet(7, seq), addr, now);
+        }
+        assert_eq!(drain(&mut jb, now), vec![1, 2, 3]);
+        assert!(jb.is_empty());
+    }
 }
@@ -2,12 +2,19 @@ use crate::config::RtcConfiguration;
 use crate::errors::{SdpError, SdpResult};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     fmt::{self, Write},
     str::FromStr,
 };
 
 pub const ABS_SEND_TIME_URI: &str = "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time";
 pub const SDES_MID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:mid";
+pub const VIDEO_ORIENTATION_URI: &str = "urn:3gpp:video-orientation";
+pub const FRAME_MARKING_URI: &str =
+    "http://www.webrtc.org/experiments/rtp-hdrext/video-frame-marking";
+pub const RID_URI: &str = "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id";
+pub const TRANSPORT_CC_URI: &str =
+    "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -74,6 +81,8 @@ impl SessionDescription {
         let mut session = SessionSection::default();
         let mut current_media: Option<MediaSection> = None;
         let mut media_sections = Vec::new();
+        // 1-based line number of each m= line, for error reporting.
+        let mut media_lines = Vec::new();
         let mut saw_version = false;
         let mut saw_origin = false;
         let mut saw_name = false;
@@ -113,10 +122,16 @@ impl SessionDescription {
                     saw_timing = true;
                 }
                 "c" => {
-                    if let Some(media) = current_media.as_mut() {
-                        media.connection = Some(value.to_string());
+                    // Multiple c= lines (e.g. multicast layering) are allowed by
+                    // RFC 8866; only the first one is honoured.
+                    let connection = match current_media.as_mut() {
+                        Some(media) => &mut media.connection,
+                        None => &mut session.connection,
+                    };
+                    if connection.is_some() {
+                        tracing::warn!("ignoring extra c= line {}: {}", line_no + 1, value);
                     } else {
-                        session.connection = Some(value.to_string());
+                        *connection = Some(value.to_string());
                     }
                 }
                 "a" => {
@@ -132,6 +147,7 @@ impl SessionDescription {
                         media_sections.push(media);
                     }
                     current_media = Some(MediaSection::from_m_line(value)?);
+                    media_lines.push(line_no + 1);
                 }
                 _ => {
                     // Unhandled prefixes are preserved as session-level attributes.
@@ -162,6 +178,23 @@ impl SessionDescription {
             return Err(SdpError::MissingLine("t"));
         }
 
+        // RFC 8866 §5.7: a media section without its own c= inherits the
+        // session-level one; having neither is invalid.
+        for (media, line) in media_sections.iter_mut().zip(&media_lines) {
+            if media.connection.is_some() {
+                continue;
+            }
+            match &session.connection {
+                Some(connection) => media.connection = Some(connection.clone()),
+                None => {
+                    return Err(SdpError::Parse(format!(
+                        "missing c= line for m= line {} and at session level",
+                        line
+                    )));
+                }
+            }
+        }
+
         Ok(Self {
             sdp_type,
             session,
@@ -178,6 +211,50 @@ impl SessionDescription {
         out
     }
 
+    /// Compare this description against `other` (typically a later offer or
+    /// answer for the same session) and report what changed.
+    ///
+    /// Media sections are matched by `mid`, or by position when neither side
+    /// carries a mid (plain SIP). Attribute order is ignored.
+    pub fn diff(&self, other: &SessionDescription) -> SdpDiff {
+        let mut diff = SdpDiff::default();
+        let mut matched = vec![false; other.media_sections.len()];
+
+        for (index, old) in self.media_sections.iter().enumerate() {
+            let found = other
+                .media_sections
+                .iter()
+                .enumerate()
+                .position(|(other_index, new)| {
+                    !matched[other_index]
+                        && if old.mid.is_empty() && new.mid.is_empty() {
+                            other_index == index
+                        } else {
+                            old.mid == new.mid
+                        }
+                });
+            match found {
+                Some(other_index) => {
+                    matched[other_index] = true;
+                    let changes = old.diff(&other.media_sections[other_index], other_index);
+                    if !changes.is_empty() {
+                        diff.changed_media.push(changes);
+                    }
+                }
+                None => diff.removed_media.push(old.clone()),
+            }
+        }
+
+        diff.added_media = other
+            .media_sections
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(section, _)| section.clone())
+            .collect();
+        diff
+    }
+
     pub fn dtls_fingerprint(&self) -> SdpResult<Option<SdpFingerprint>> {
         let mut fingerprint = None;
 
@@ -194,6 +271,28 @@ impl SessionDescription {
         Ok(fingerprint)
     }
 
+    /// ICE options (RFC 8839 `a=ice-options`) advertised at session level or
+    /// in any media section, e.g. `trickle`. Other options, such as
+    /// `renomination`, are listed but not acted on.
+    pub fn ice_options(&self) -> Vec<String> {
+        let mut options = self.session.ice_options();
+        for option in self
+            .media_sections
+            .iter()
+            .flat_map(MediaSection::ice_options)
+        {
+            if !options.contains(&option) {
+                options.push(option);
+            }
+        }
+        options
+    }
+
+    /// Whether `option` appears in [`ice_options`](Self::ice_options).
+    pub fn has_ice_option(&self, option: &str) -> bool {
+        self.ice_options().iter().any(|o| o == option)
+    }
+
     /// Returns all video media sections.
     pub fn video_sections(&self) -> impl Iterator<Item = &MediaSection> {
         self.media_sections
@@ -236,6 +335,21 @@ impl SessionDescription {
             .find(|s| s.kind == MediaKind::Image)
     }
 
+    /// Mids of each `a=group:BUNDLE` line (RFC 8843), in SDP order. The first
+    /// mid of a group is its offerer-tagged section.
+    pub fn bundle_groups(&self) -> Vec<Vec<String>> {
+        self.session
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "group")
+            .filter_map(|attr| attr.value.as_deref())
+            .filter_map(|value| {
+                let mut parts = value.split_whitespace();
+                (parts.next() == Some("BUNDLE")).then(|| parts.map(str::to_string).collect())
+            })
+            .collect()
+    }
+
     /// Extracts all video capabilities from all video media sections.
     pub fn to_video_capabilities(&self) -> Vec<crate::config::VideoCapability> {
         self.video_sections()
@@ -258,6 +372,235 @@ impl SessionDescription {
     }
 }
 
+/// Typed construction of a [`SessionDescription`].
+///
+/// Media-level methods apply to the section most recently started with
+/// [`add_media`](Self::add_media). Mistakes such as a duplicate payload type,
+/// an fmtp for an unknown payload type or an out-of-range extmap id are
+/// reported by [`build`](Self::build).
+#[derive(Debug, Clone)]
+pub struct SessionDescriptionBuilder {
+    description: SessionDescription,
+    error: Option<SdpError>,
+}
+
+impl SessionDescriptionBuilder {
+    pub fn new(sdp_type: SdpType) -> Self {
+        Self {
+            description: SessionDescription::new(sdp_type),
+            error: None,
+        }
+    }
+
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.description.session.origin = origin;
+        self
+    }
+
+    /// Session-level `c=` value, e.g. `IN IP4 192.0.2.1`.
+    pub fn connection(mut self, connection: impl Into<String>) -> Self {
+        self.description.session.connection = Some(connection.into());
+        self
+    }
+
+    /// Start a new media section; later media-level calls apply to it.
+    pub fn add_media(mut self, kind: MediaKind, mid: impl Into<String>) -> Self {
+        let mid = mid.into();
+        if !mid.is_empty()
+            && self
+                .description
+                .media_sections
+                .iter()
+                .any(|section| section.mid == mid)
+        {
+            self.fail(format!("duplicate mid {mid}"));
+        }
+        self.description
+            .media_sections
+            .push(MediaSection::new(kind, mid));
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        if let Some(section) = self.media("port") {
+            section.port = port;
+        }
+        self
+    }
+
+    /// Transport protocol of the m= line, e.g. `RTP/AVP`.
+    pub fn protocol(mut self, protocol: impl Into<String>) -> Self {
+        if let Some(section) = self.media("protocol") {
+            section.protocol = protocol.into();
+        }
+        self
+    }
+
+    /// Add an audio format with its `a=rtpmap`.
+    pub fn add_audio_codec(
+        self,
+        payload_type: u8,
+        encoding_name: &str,
+        clock_rate: u32,
+        channels: Option<u8>,
+    ) -> Self {
+        self.add_codec(
+            MediaKind::Audio,
+            RtpMap {
+                payload_type,
+                encoding_name: encoding_name.to_string(),
+                clock_rate,
+                channels,
+            },
+        )
+    }
+
+    /// Add a video format with its `a=rtpmap`.
+    pub fn add_video_codec(self, payload_type: u8, encoding_name: &str, clock_rate: u32) -> Self {
+        self.add_codec(
+            MediaKind::Video,
+            RtpMap {
+                payload_type,
+                encoding_name: encoding_name.to_string(),
+                clock_rate,
+                channels: None,
+            },
+        )
+    }
+
+    fn add_codec(mut self, kind: MediaKind, rtpmap: RtpMap) -> Self {
+        let payload_type = rtpmap.payload_type;
+        let Some(section) = self.media("codec") else {
+            return self;
+        };
+        let error = if section.kind != kind {
+            Some(format!(
+                "{} codec {} in {} section",
+                kind.as_str(),
+                rtpmap.encoding_name,
+                section.kind.as_str()
+            ))
+        } else if payload_type > 127 {
+            Some(format!("payload type {payload_type} out of range"))
+        } else if section.formats.contains(&payload_type.to_string()) {
+            Some(format!("duplicate payload type {payload_type}"))
+        } else if rtpmap.clock_rate == 0 {
+            Some(format!("zero clock rate for payload type {payload_type}"))
+        } else {
+            section.formats.push(payload_type.to_string());
+            section
+                .attributes
+                .push(Attribute::new("rtpmap", Some(rtpmap.to_string())));
+            None
+        };
+        if let Some(error) = error {
+            self.fail(error);
+        }
+        self
+    }
+
+    /// `a=fmtp` for a payload type already added to the section.
+    pub fn add_fmtp(mut self, payload_type: u8, params: &str) -> Self {
+        let Some(section) = self.media("fmtp") else {
+            return self;
+        };
+        if section.formats.contains(&payload_type.to_string()) {
+            section.attributes.push(Attribute::new(
+                "fmtp",
+                Some(format!("{payload_type} {params}")),
+            ));
+        } else {
+            self.fail(format!("fmtp for unknown payload type {payload_type}"));
+        }
+        self
+    }
+
+    pub fn set_direction(mut self, direction: Direction) -> Self {
+        if let Some(section) = self.media("direction") {
+            section.direction = direction;
+        }
+        self
+    }
+
+    /// `a=extmap` with a one-byte (1-14) or two-byte (1-255, not 15) id.
+    pub fn add_extmap(mut self, id: u8, uri: &str) -> Self {
+        let Some(section) = self.media("extmap") else {
+            return self;
+        };
+        let taken = section
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "extmap")
+            .filter_map(|attr| attr.value.as_deref()?.split_whitespace().next())
+            .filter_map(|value| value.split('/').next()?.parse::<u8>().ok())
+            .any(|existing| existing == id);
+        if id == 0 || id == 15 {
+            self.fail(format!("extmap id {id} is reserved"));
+        } else if taken {
+            self.fail(format!("duplicate extmap id {id}"));
+        } else {
+            section
+                .attributes
+                .push(Attribute::new("extmap", Some(format!("{id} {uri}"))));
+        }
+        self
+    }
+
+    /// Announce `ssrc` with its CNAME, replacing any SSRC set before.
+    pub fn set_ssrc(mut self, ssrc: u32, cname: &str) -> Self {
+        if let Some(section) = self.media("ssrc") {
+            section.attributes.retain(|attr| attr.key != "ssrc");
+            section.attributes.push(Attribute::new(
+                "ssrc",
+                Some(format!("{ssrc} cname:{cname}")),
+            ));
+        }
+        self
+    }
+
+    /// Any other media-level attribute, e.g. `rtcp-mux`.
+    pub fn attribute(mut self, key: impl Into<String>, value: Option<String>) -> Self {
+        if let Some(section) = self.media("attribute") {
+            section.attributes.push(Attribute::new(key, value));
+        }
+        self
+    }
+
+    pub fn build(self) -> SdpResult<SessionDescription> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        for section in &self.description.media_sections {
+            if section.formats.is_empty() {
+                return Err(SdpError::Invalid(format!(
+                    "{} section {:?} has no formats",
+                    section.kind.as_str(),
+                    section.mid
+                )));
+            }
+            if section.connection.is_none() && self.description.session.connection.is_none() {
+                return Err(SdpError::Invalid(format!(
+                    "{} section {:?} has no connection address",
+                    section.kind.as_str(),
+                    section.mid
+                )));
+            }
+        }
+        Ok(self.description)
+    }
+
+    fn media(&mut self, what: &str) -> Option<&mut MediaSection> {
+        if self.description.media_sections.is_empty() {
+            self.fail(format!("{what} set before add_media"));
+        }
+        self.description.media_sections.last_mut()
+    }
+
+    fn fail(&mut self, message: String) {
+        self.error.get_or_insert(SdpError::Invalid(message));
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SdpFingerprint {
     pub algorithm: String,
@@ -287,6 +630,21 @@ impl SdpFingerprint {
     }
 }
 
+fn ice_options(attributes: &[Attribute]) -> Vec<String> {
+    let mut options: Vec<String> = Vec::new();
+    for token in attributes
+        .iter()
+        .filter(|a| a.key == "ice-options")
+        .filter_map(|a| a.value.as_deref())
+        .flat_map(str::split_whitespace)
+    {
+        if !options.iter().any(|o| o == token) {
+            options.push(token.to_string());
+        }
+    }
+    options
+}
+
 fn collect_dtls_fingerprint(
     attr: &Attribute,
     current: &mut Option<SdpFingerprint>,
@@ -367,6 +725,11 @@ impl Default for SessionSection {
 }
 
 impl SessionSection {
+    /// Session-level `a=ice-options` tokens.
+    pub fn ice_options(&self) -> Vec<String> {
+        ice_options(&self.attributes)
+    }
+
     fn write_lines(&self, out: &mut String) -> fmt::Result {
         write!(out, "v={}\r\n", self.version)?;
         write!(
@@ -506,42 +869,6 @@ impl Attribute {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct Simulcast {
-    pub send: Vec<String>,
-    pub recv: Vec<String>,
-}
-
-impl Simulcast {
-    pub fn parse(value: &str) -> Option<Self> {
-        let mut send = Vec::new();
-        let mut recv = Vec::new();
-
-        // Example: send ~1;2 recv 3
-        let parts: Vec<&str> = value.split_whitespace().collect();
-        let mut current_dir = "";
-
-        for part in parts {
-            if part == "send" || part == "recv" {
-                current_dir = part;
-                continue;
-            }
-
-            if current_dir == "send" {
-                send.extend(part.split(';').map(|s| s.to_string()));
-            } else if current_dir == "recv" {
-                recv.extend(part.split(';').map(|s| s.to_string()));
-            }
-        }
-
-        if send.is_empty() && recv.is_empty() {
-            None
-        } else {
-            Some(Self { send, recv })
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Rid {
     pub id: String,
@@ -554,8 +881,11 @@ impl Rid {
         // Example: 1 send pt=100;max-width=1280
         let mut parts = value.split_whitespace();
         let id = parts.next()?.to_string();
-        let direction_str = parts.next()?;
-        let direction = Direction::from_attribute(direction_str)?;
+        let direction = match parts.next()? {
+            "send" => Direction::SendOnly,
+            "recv" => Direction::RecvOnly,
+            _ => return None,
+        };
 
         let mut params = Vec::new();
         if let Some(params_str) = parts.next() {
@@ -576,6 +906,98 @@ impl Rid {
     }
 }
 
+/// One simulcast stream (RFC 8853): its RID, whether it is offered paused
+/// (`~`), and the restrictions of its `a=rid` line (RFC 8851).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SimulcastLayer {
+    pub rid: String,
+    pub paused: bool,
+    pub restrictions: Vec<(String, String)>,
+}
+
+/// Simulcast streams of an m-line, from its `a=simulcast` and `a=rid`
+/// lines, as seen by the side that wrote them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SimulcastLayers {
+    pub send: Vec<SimulcastLayer>,
+    pub recv: Vec<SimulcastLayer>,
+}
+
+impl SimulcastLayers {
+    /// Parse an `a=simulcast` value, taking restrictions from `rids`. Only
+    /// the first alternative of each stream (`1,2`) is kept.
+    pub fn parse(value: &str, rids: &[Rid]) -> Option<Self> {
+        let mut layers = Self::default();
+        let mut parts = value.split_whitespace();
+        while let (Some(direction), Some(streams)) = (parts.next(), parts.next()) {
+            let (direction, target) = match direction {
+                "send" => (Direction::SendOnly, &mut layers.send),
+                "recv" => (Direction::RecvOnly, &mut layers.recv),
+                _ => continue,
+            };
+            for stream in streams.split(';') {
+                let first = stream.split(',').next().unwrap_or_default();
+                let (paused, rid) = match first.strip_prefix('~') {
+                    Some(rid) => (true, rid),
+                    None => (false, first),
+                };
+                if rid.is_empty() {
+                    continue;
+                }
+                let restrictions = rids
+                    .iter()
+                    .find(|r| r.id == rid && r.direction == direction)
+                    .map(|r| r.params.clone())
+                    .unwrap_or_default();
+                target.push(SimulcastLayer {
+                    rid: rid.to_string(),
+                    paused,
+                    restrictions,
+                });
+            }
+        }
+        (!layers.send.is_empty() || !layers.recv.is_empty()).then_some(layers)
+    }
+
+    /// The `a=rid` lines and the `a=simulcast` line describing these layers.
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attributes = Vec::new();
+        let mut simulcast = Vec::new();
+        for (direction, layers) in [("send", &self.send), ("recv", &self.recv)] {
+            if layers.is_empty() {
+                continue;
+            }
+            for layer in layers {
+                let mut value = format!("{} {}", layer.rid, direction);
+                if !layer.restrictions.is_empty() {
+                    let restrictions: Vec<String> = layer
+                        .restrictions
+                        .iter()
+                        .map(|(k, v)| {
+                            if v.is_empty() {
+                                k.clone()
+                            } else {
+                                format!("{k}={v}")
+                            }
+                        })
+                        .collect();
+                    value = format!("{} {}", value, restrictions.join(";"));
+                }
+                attributes.push(Attribute::new("rid", Some(value)));
+            }
+            let streams: Vec<String> = layers
+                .iter()
+                .map(|l| format!("{}{}", if l.paused { "~" } else { "" }, l.rid))
+                .collect();
+            simulcast.push(format!("{} {}", direction, streams.join(";")));
+        }
+        if !simulcast.is_empty() {
+            attributes.push(Attribute::new("simulcast", Some(simulcast.join(" "))));
+        }
+        attributes
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum NetworkType {
     Internet,
@@ -627,7 +1049,7 @@ impl FromStr for AddressType {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum MediaKind {
     Audio,
     Video,
@@ -679,7 +1101,7 @@ impl Direction {
         }
     }
 
-    fn from_attribute(key: &str) -> Option<Self> {
+    pub(crate) fn from_attribute(key: &str) -> Option<Self> {
         match key {
             "sendrecv" => Some(Direction::SendRecv),
             "sendonly" => Some(Direction::SendOnly),
@@ -721,6 +1143,183 @@ impl CryptoAttribute {
     }
 }
 
+/// `a=rtpmap` value: `<payload type> <encoding name>/<clock rate>[/<channels>]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RtpMap {
+    pub payload_type: u8,
+    /// As written in the SDP; encoding names are case-insensitive.
+    pub encoding_name: String,
+    pub clock_rate: u32,
+    /// `None` when the line has no channel count (only audio codecs use it).
+    pub channels: Option<u8>,
+}
+
+impl RtpMap {
+    /// Tolerates extra whitespace around every field. Returns `None` for a
+    /// malformed value rather than guessing.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (payload_type, encoding) = value.trim().split_once(char::is_whitespace)?;
+        let mut parts = encoding.split('/').map(str::trim);
+        let encoding_name = parts.next().filter(|name| !name.is_empty())?;
+        let clock_rate = parts.next()?.parse().ok()?;
+        let channels = match parts.next() {
+            Some(channels) => Some(channels.parse().ok()?),
+            None => None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            payload_type: payload_type.parse().ok()?,
+            encoding_name: encoding_name.to_string(),
+            clock_rate,
+            channels,
+        })
+    }
+}
+
+impl fmt::Display for RtpMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}/{}",
+            self.payload_type, self.encoding_name, self.clock_rate
+        )?;
+        if let Some(channels) = self.channels {
+            write!(f, "/{}", channels)?;
+        }
+        Ok(())
+    }
+}
+
+/// `a=imageattr` (RFC 6236), reduced to the largest resolution offered in
+/// each direction. `None` means the direction is absent or `*` (any size).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageAttribute {
+    /// `None` for the `*` wildcard.
+    pub payload_type: Option<u8>,
+    pub send: Option<(u16, u16)>,
+    pub recv: Option<(u16, u16)>,
+}
+
+impl ImageAttribute {
+    pub fn parse(value: &str) -> Option<Self> {
+        // a=imageattr:<pt|*> [send <sets|*>] [recv <sets|*>]
+        let mut parts = value.split_whitespace();
+        let payload_type = match parts.next()? {
+            "*" => None,
+            pt => Some(pt.parse().ok()?),
+        };
+
+        let mut attr = Self {
+            payload_type,
+            send: None,
+            recv: None,
+        };
+        let mut current = None;
+        for part in parts {
+            match part {
+                "send" | "recv" => current = Some(part),
+                set => {
+                    let Some(size) = Self::parse_set(set) else {
+                        continue;
+                    };
+                    let slot = match current? {
+                        "send" => &mut attr.send,
+                        _ => &mut attr.recv,
+                    };
+                    if slot
+                        .is_none_or(|(w, h)| (size.0 as u32 * size.1 as u32) > w as u32 * h as u32)
+                    {
+                        *slot = Some(size);
+                    }
+                }
+            }
+        }
+        Some(attr)
+    }
+
+    /// Largest `(x, y)` a single `[x=...,y=...]` set allows.
+    fn parse_set(set: &str) -> Option<(u16, u16)> {
+        let inner = set.strip_prefix('[')?.strip_suffix(']')?;
+        let (mut x, mut y) = (None, None);
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, c) in inner
+            .char_indices()
+            .chain(std::iter::once((inner.len(), ',')))
+        {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    let field = &inner[start..i];
+                    start = i + 1;
+                    let max = |v: &str| {
+                        v.split([':', ',', '[', ']'])
+                            .filter_map(|n| n.parse::<u16>().ok())
+                            .max()
+                    };
+                    if let Some(v) = field.strip_prefix("x=") {
+                        x = max(v);
+                    } else if let Some(v) = field.strip_prefix("y=") {
+                        y = max(v);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some((x?, y?))
+    }
+}
+
+/// Result of [`SessionDescription::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SdpDiff {
+    pub added_media: Vec<MediaSection>,
+    pub removed_media: Vec<MediaSection>,
+    pub changed_media: Vec<MediaSectionDiff>,
+}
+
+impl SdpDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_media.is_empty()
+            && self.removed_media.is_empty()
+            && self.changed_media.is_empty()
+    }
+}
+
+/// Changes within one media section present in both descriptions. Fields
+/// are in "new" terms: `index` points into the newer description's
+/// `media_sections`, and `(old, new)` pairs are only set when they differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaSectionDiff {
+    pub mid: String,
+    pub index: usize,
+    pub port: Option<(u16, u16)>,
+    pub direction: Option<(Direction, Direction)>,
+    pub added_formats: Vec<String>,
+    pub removed_formats: Vec<String>,
+    pub added_ssrcs: Vec<u32>,
+    pub removed_ssrcs: Vec<u32>,
+    pub added_attributes: Vec<Attribute>,
+    pub removed_attributes: Vec<Attribute>,
+}
+
+impl MediaSectionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.port.is_none()
+            && self.direction.is_none()
+            && self.added_formats.is_empty()
+            && self.removed_formats.is_empty()
+            && self.added_ssrcs.is_empty()
+            && self.removed_ssrcs.is_empty()
+            && self.added_attributes.is_empty()
+            && self.removed_attributes.is_empty()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MediaSection {
     pub kind: MediaKind,
@@ -765,6 +1364,132 @@ impl MediaSection {
             .collect()
     }
 
+    /// `a=framerate` value, the maximum frame rate of the stream.
+    pub fn framerate(&self) -> Option<f32> {
+        self.attributes
+            .iter()
+            .find(|a| a.key == "framerate")
+            .and_then(|a| a.value.as_deref()?.trim().parse().ok())
+            .filter(|fps: &f32| *fps > 0.0)
+    }
+
+    /// `a=content` value (RFC 4796), e.g. `slides` or `main`.
+    pub fn content(&self) -> Option<&str> {
+        self.attribute_value("content")
+    }
+
+    /// Media-level `a=ice-options` tokens.
+    pub fn ice_options(&self) -> Vec<String> {
+        ice_options(&self.attributes)
+    }
+
+    /// `a=label` value (RFC 4574).
+    pub fn label(&self) -> Option<&str> {
+        self.attribute_value("label")
+    }
+
+    /// The first well-formed `a=rtpmap` of `payload_type`.
+    pub fn rtpmap(&self, payload_type: u8) -> Option<RtpMap> {
+        self.attributes
+            .iter()
+            .filter(|a| a.key == "rtpmap")
+            .filter_map(|a| RtpMap::parse(a.value.as_deref()?))
+            .find(|rtpmap| rtpmap.payload_type == payload_type)
+    }
+
+    /// `a=fmtp` parameters of `payload_type`, e.g. `minptime=10;useinbandfec=1`.
+    pub fn fmtp(&self, payload_type: u8) -> Option<&str> {
+        self.attributes
+            .iter()
+            .filter(|a| a.key == "fmtp")
+            .filter_map(|a| a.value.as_deref()?.split_once(' '))
+            .find(|(pt, _)| pt.parse::<u8>().ok() == Some(payload_type))
+            .map(|(_, params)| params.trim())
+    }
+
+    fn attribute_value(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .and_then(|a| a.value.as_deref())
+            .map(str::trim)
+    }
+
+    pub fn get_image_attributes(&self) -> Vec<ImageAttribute> {
+        self.attributes
+            .iter()
+            .filter(|a| a.key == "imageattr")
+            .filter_map(|a| a.value.as_deref().and_then(ImageAttribute::parse))
+            .collect()
+    }
+
+    /// SSRCs announced through `a=ssrc` lines, in order of first appearance.
+    pub fn ssrcs(&self) -> Vec<u32> {
+        let mut ssrcs = Vec::new();
+        for attr in self.attributes.iter().filter(|a| a.key == "ssrc") {
+            if let Some(ssrc) = attr
+                .value
+                .as_deref()
+                .and_then(|v| v.split_whitespace().next())
+                .and_then(|v| v.parse().ok())
+                && !ssrcs.contains(&ssrc)
+            {
+                ssrcs.push(ssrc);
+            }
+        }
+        ssrcs
+    }
+
+    fn diff(&self, other: &MediaSection, index: usize) -> MediaSectionDiff {
+        fn missing_from<T: PartialEq + Clone>(from: &[T], other: &[T]) -> Vec<T> {
+            from.iter()
+                .filter(|v| !other.contains(v))
+                .cloned()
+                .collect()
+        }
+
+        let (old_ssrcs, new_ssrcs) = (self.ssrcs(), other.ssrcs());
+        MediaSectionDiff {
+            mid: other.mid.clone(),
+            index,
+            port: (self.port != other.port).then_some((self.port, other.port)),
+            direction: (self.direction != other.direction)
+                .then_some((self.direction, other.direction)),
+            added_formats: missing_from(&other.formats, &self.formats),
+            removed_formats: missing_from(&self.formats, &other.formats),
+            added_ssrcs: missing_from(&new_ssrcs, &old_ssrcs),
+            removed_ssrcs: missing_from(&old_ssrcs, &new_ssrcs),
+            added_attributes: missing_from(&other.attributes, &self.attributes),
+            removed_attributes: missing_from(&self.attributes, &other.attributes),
+        }
+    }
+
+    /// Whether any payload type (or `*`) lists `a=rtcp-fb` feedback `fb`.
+    pub fn has_rtcp_fb(&self, fb: &str) -> bool {
+        self.attributes
+            .iter()
+            .filter(|attr| attr.key == "rtcp-fb")
+            .filter_map(|attr| attr.value.as_deref()?.split_once(' '))
+            .any(|(_, value)| value.trim() == fb)
+    }
+
+    /// Simulcast layers from `a=simulcast` and `a=rid`, if any.
+    pub fn simulcast_layers(&self) -> Option<SimulcastLayers> {
+        let rids: Vec<Rid> = self
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "rid")
+            .filter_map(|attr| Rid::parse(attr.value.as_deref()?))
+            .collect();
+        let value = self
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "simulcast")?
+            .value
+            .as_deref()?;
+        SimulcastLayers::parse(value, &rids)
+    }
+
     pub fn get_extmap_id(&self, uri: &str) -> Option<u8> {
         for attr in &self.attributes {
             if attr.key == "extmap"
@@ -775,7 +1500,8 @@ impl MediaSection {
                     && let Some(attr_uri) = parts.next()
                     && attr_uri == uri
                 {
-                    return id_str.parse().ok();
+                    // Strip an optional "/direction" suffix (RFC 8285 §7).
+                    return id_str.split('/').next()?.parse().ok();
                 }
             }
         }
@@ -815,24 +1541,9 @@ impl MediaSection {
             let mut codec_name = String::new();
             let mut clock_rate = 90000u32; // Default for video
 
-            for attr in &self.attributes {
-                if attr.key == "rtpmap"
-                    && let Some(value) = &attr.value
-                    && let Some((pt_part, rest)) = value.split_once(' ')
-                    && let Ok(pt) = pt_part.parse::<u8>()
-                    && pt == payload_type
-                {
-                    // Parse "codec_name/clock_rate"
-                    let parts: Vec<&str> = rest.split('/').collect();
-                    if !parts.is_empty() {
-                        codec_name = parts[0].to_string();
-                    }
-                    if parts.len() >= 2
-                        && let Ok(rate) = parts[1].parse()
-                    {
-                        clock_rate = rate;
-                    }
-                }
+            if let Some(rtpmap) = self.rtpmap(payload_type) {
+                codec_name = rtpmap.encoding_name;
+                clock_rate = rtpmap.clock_rate;
             }
 
             // If no rtpmap found, use payload type to guess
@@ -875,8 +1586,10 @@ impl MediaSection {
                 }
             }
 
-            // RTX is not a primary media codec; associate it onto the apt target below.
-            if codec_name.eq_ignore_ascii_case("rtx") {
+            // RTX and FlexFEC are not primary media codecs; they are attached below.
+            if codec_name.eq_ignore_ascii_case("rtx")
+                || codec_name.eq_ignore_ascii_case(crate::flexfec::FLEXFEC_CODEC_NAME)
+            {
                 continue;
             }
 
@@ -887,6 +1600,7 @@ impl MediaSection {
                 fmtp,
                 rtcp_fbs,
                 rtx_payload_type: None,
+                flexfec_payload_type: None,
             });
         }
 
@@ -897,6 +1611,11 @@ impl MediaSection {
                 cap.rtx_payload_type = Some(rtx_pt);
             }
         }
+        // FlexFEC protects the whole m-line rather than one codec.
+        let flexfec_pt = crate::flexfec::flexfec_payload_type(&self.attributes);
+        for cap in &mut capabilities {
+            cap.flexfec_payload_type = flexfec_pt;
+        }
 
         capabilities
     }
@@ -919,29 +1638,10 @@ impl MediaSection {
             let mut clock_rate = 8000u32; // Default for audio
             let mut channels = 1u8;
 
-            for attr in &self.attributes {
-                if attr.key == "rtpmap"
-                    && let Some(value) = &attr.value
-                    && let Some((pt_part, rest)) = value.split_once(' ')
-                    && let Ok(pt) = pt_part.parse::<u8>()
-                    && pt == payload_type
-                {
-                    // Parse "codec_name/clock_rate[/channels]"
-                    let parts: Vec<&str> = rest.split('/').collect();
-                    if !parts.is_empty() {
-                        codec_name = parts[0].to_string();
-                    }
-                    if parts.len() >= 2
-                        && let Ok(rate) = parts[1].parse()
-                    {
-                        clock_rate = rate;
-                    }
-                    if parts.len() >= 3
-                        && let Ok(ch) = parts[2].parse()
-                    {
-                        channels = ch;
-                    }
-                }
+            if let Some(rtpmap) = self.rtpmap(payload_type) {
+                codec_name = rtpmap.encoding_name;
+                clock_rate = rtpmap.clock_rate;
+                channels = rtpmap.channels.unwrap_or(channels);
             }
 
             // If no rtpmap found, use payload type to guess common audio codecs
@@ -1013,24 +1713,24 @@ impl MediaSection {
     }
 
     fn apply_audio_config(&mut self, config: &RtcConfiguration) {
-        let default_caps = crate::config::AudioCapability::default();
-        let caps = if let Some(c) = &config.media_capabilities {
-            if c.audio.is_empty() {
-                vec![default_caps]
-            } else {
-                c.audio.clone()
-            }
-        } else {
-            vec![default_caps]
-        };
+        let caps = config.media_capabilities.as_ref().map_or_else(
+            || vec![crate::config::AudioCapability::default()],
+            |c| c.audio_or_default(),
+        );
 
-        self.formats = caps.iter().map(|c| c.payload_type.to_string()).collect();
         if config.rtcp_mux_policy == crate::config::RtcpMuxPolicy::Require
-            && config.sdp_compatibility != crate::config::SdpCompatibilityMode::LegacySip
+            && config.effective_sdp_compatibility()
+                != crate::config::SdpCompatibilityMode::LegacySip
         {
             self.attributes.push(Attribute::new("rtcp-mux", None));
         }
-        for audio in &caps {
+        self.push_audio_capabilities(&caps);
+    }
+
+    /// Set the payload list and append `rtpmap`/`fmtp`/`rtcp-fb` lines for `caps`.
+    pub(crate) fn push_audio_capabilities(&mut self, caps: &[crate::config::AudioCapability]) {
+        self.formats = caps.iter().map(|c| c.payload_type.to_string()).collect();
+        for audio in caps {
             let rtpmap_value = if audio.channels == 1 {
                 format!(
                     "{} {}/{}",
@@ -1061,24 +1761,25 @@ impl MediaSection {
     }
 
     fn apply_video_config(&mut self, config: &RtcConfiguration) {
-        let default_caps = crate::config::VideoCapability::default();
-        let caps = if let Some(c) = &config.media_capabilities {
-            if c.video.is_empty() {
-                vec![default_caps]
-            } else {
-                c.video.clone()
-            }
-        } else {
-            vec![default_caps]
-        };
+        let caps = config.media_capabilities.as_ref().map_or_else(
+            || vec![crate::config::VideoCapability::default()],
+            |c| c.video_or_default(),
+        );
 
-        self.formats = caps.iter().map(|c| c.payload_type.to_string()).collect();
         if config.rtcp_mux_policy == crate::config::RtcpMuxPolicy::Require
-            && config.sdp_compatibility != crate::config::SdpCompatibilityMode::LegacySip
+            && config.effective_sdp_compatibility()
+                != crate::config::SdpCompatibilityMode::LegacySip
         {
             self.attributes.push(Attribute::new("rtcp-mux", None));
         }
-        for video in &caps {
+        self.push_video_capabilities(&caps);
+    }
+
+    /// Set the payload list and append `rtpmap`/`fmtp`/`rtcp-fb` (plus RTX and
+    /// FlexFEC) lines for `caps`.
+    pub(crate) fn push_video_capabilities(&mut self, caps: &[crate::config::VideoCapability]) {
+        self.formats = caps.iter().map(|c| c.payload_type.to_string()).collect();
+        for video in caps {
             self.attributes.push(Attribute::new(
                 "rtpmap",
                 Some(format!(
@@ -1107,25 +1808,33 @@ impl MediaSection {
                     video.clock_rate,
                 );
             }
+            if let Some(fec_pt) = video.flexfec_payload_type {
+                crate::flexfec::append_flexfec_to_section(
+                    &mut self.formats,
+                    &mut self.attributes,
+                    fec_pt,
+                );
+            }
         }
     }
 
     fn apply_application_config(&mut self, config: &RtcConfiguration) {
-        let default_caps = crate::config::ApplicationCapability::default();
-        let port = if let Some(caps) = &config.media_capabilities {
-            if let Some(app) = &caps.application {
-                app.sctp_port
-            } else {
-                default_caps.sctp_port
-            }
-        } else {
-            default_caps.sctp_port
-        };
-
+        let port = config
+            .media_capabilities
+            .as_ref()
+            .and_then(|caps| caps.application.as_ref())
+            .map_or(
+                crate::config::ApplicationCapability::default().sctp_port,
+                |app| app.sctp_port,
+            );
+        self.push_application_capability(port);
+    }
+
+    pub(crate) fn push_application_capability(&mut self, sctp_port: u16) {
         self.protocol = "UDP/DTLS/SCTP".into();
         self.formats = vec!["webrtc-datachannel".into()];
         self.attributes
-            .push(Attribute::new("sctp-port", Some(port.to_string())));
+            .push(Attribute::new("sctp-port", Some(sctp_port.to_string())));
     }
 
     pub fn to_image_capabilities(&self) -> Vec<crate::config::T38Capability> {
@@ -1201,21 +1910,18 @@ impl MediaSection {
     }
 
     fn apply_image_config(&mut self, config: &RtcConfiguration) {
-        let default_caps = crate::config::T38Capability::default();
-        let caps = if let Some(c) = &config.media_capabilities {
-            if c.image.is_empty() {
-                vec![default_caps]
-            } else {
-                c.image.clone()
-            }
-        } else {
-            vec![default_caps]
-        };
+        let caps = config.media_capabilities.as_ref().map_or_else(
+            || vec![crate::config::T38Capability::default()],
+            |c| c.image_or_default(),
+        );
+        self.push_image_capabilities(&caps);
+    }
 
+    pub(crate) fn push_image_capabilities(&mut self, caps: &[crate::config::T38Capability]) {
         self.protocol = "udptl".into();
         self.formats = caps.iter().map(|c| c.payload_type.to_string()).collect();
 
-        for t38 in &caps {
+        for t38 in caps {
             self.attributes.push(Attribute::new(
                 "T38FaxVersion",
                 Some(t38.version.to_string()),
@@ -1378,6 +2084,20 @@ pub fn modify_sdp_direction(sdp: &str, direction: &str) -> String {
     result.join("\r\n")
 }
 
+/// Split an fmtp value such as `maxaveragebitrate=24000;useinbandfec=1` into
+/// its `key=value` parameters. Parameters without `=` (e.g. the DTMF event
+/// range `0-16`) map to an empty value.
+pub fn parse_fmtp(fmtp: &str) -> HashMap<String, String> {
+    fmtp.split(';')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.split_once('=') {
+            Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+            None => (param.to_string(), String::new()),
+        })
+        .collect()
+}
+
 pub fn parse_bundle_mid_info(sdp: &str) -> Option<(u8, String, String)> {
     let mut extmap_id: Option<u8> = None;
     let mut audio_mid: Option<String> = None;
@@ -1428,6 +2148,7 @@ mod tests {
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 a=fingerprint:sha-256 aa:bb:cc:dd\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
@@ -1445,6 +2166,7 @@ a=mid:0\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 a=fingerprint:sha-256 AA:BB:CC:DD\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
@@ -1484,6 +2206,7 @@ a=fingerprint:sha-256 AA:BB:CC:EE\r\n";
             fmtp: Some("packetization-mode=1;profile-level-id=42e01f".to_string()),
             rtcp_fbs: vec![],
             rtx_payload_type: None,
+            flexfec_payload_type: None,
         };
 
         let caps = MediaCapabilities {
@@ -1573,6 +2296,7 @@ a=fingerprint:sha-256 AA:BB:CC:EE\r\n";
             fmtp: None,
             rtcp_fbs: vec!["nack pli".to_string(), "ccm fir".to_string()],
             rtx_payload_type: None,
+            flexfec_payload_type: None,
         };
         let caps = MediaCapabilities {
             audio: vec![],
@@ -1705,6 +2429,7 @@ a=fingerprint:sha-256 AA:BB:CC:EE\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
 a=mid:0\r\n\
@@ -1724,6 +2449,7 @@ a=mid:1\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
 a=mid:0\r\n";
@@ -1739,6 +2465,7 @@ a=mid:0\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
 a=mid:0\r\n\
@@ -1766,6 +2493,7 @@ a=mid:2\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
 a=mid:0\r\n\
@@ -1803,6 +2531,7 @@ a=rtcp-fb:97 nack pli\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
 a=mid:0\r\n\
@@ -1863,11 +2592,146 @@ a=rtcp-fb:99 ccm fir\r\n";
         assert_eq!(caps[1].rtcp_fbs, vec!["nack pli"]);
     }
 
+    #[test]
+    fn test_parse_media_with_multiple_connection_lines_uses_first() {
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+t=0 0\r\n\
+m=audio 5004 RTP/AVP 0\r\n\
+c=IN IP4 192.0.2.10\r\n\
+c=IN IP4 198.51.100.20\r\n\
+a=rtpmap:0 PCMU/8000\r\n\
+a=ssrc:1234 cname:alice\r\n";
+
+        let desc = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        assert_eq!(desc.media_sections.len(), 1);
+        let media = &desc.media_sections[0];
+        assert_eq!(media.connection.as_deref(), Some("IN IP4 192.0.2.10"));
+        assert_eq!(media.formats, vec!["0"]);
+
+        let out = desc.to_sdp_string();
+        assert!(out.contains("c=IN IP4 192.0.2.10"));
+        assert!(!out.contains("198.51.100.20"));
+    }
+
+    #[test]
+    fn test_parse_media_inherits_session_connection() {
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 192.0.2.10\r\n\
+t=0 0\r\n\
+m=audio 5004 RTP/AVP 0\r\n\
+a=rtpmap:0 PCMU/8000\r\n\
+m=video 5006 RTP/AVP 96\r\n\
+c=IN IP4 198.51.100.20\r\n\
+a=rtpmap:96 VP8/90000\r\n";
+
+        let desc = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        assert_eq!(
+            desc.session.connection.as_deref(),
+            Some("IN IP4 192.0.2.10")
+        );
+        assert_eq!(
+            desc.media_sections[0].connection.as_deref(),
+            Some("IN IP4 192.0.2.10")
+        );
+        assert_eq!(
+            desc.media_sections[1].connection.as_deref(),
+            Some("IN IP4 198.51.100.20")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_media_without_any_connection() {
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+t=0 0\r\n\
+m=audio 5004 RTP/AVP 0\r\n\
+c=IN IP4 192.0.2.10\r\n\
+a=rtpmap:0 PCMU/8000\r\n\
+m=video 5006 RTP/AVP 96\r\n\
+a=rtpmap:96 VP8/90000\r\n";
+
+        let err = SessionDescription::parse(SdpType::Offer, sdp).unwrap_err();
+        assert_eq!(
+            err,
+            SdpError::Parse("missing c= line for m= line 8 and at session level".into())
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_reinvite_payload_type_change() {
+        let initial = "v=0\r\n\
+o=- 1 1 IN IP4 192.0.2.1\r\n\
+s=-\r\n\
+c=IN IP4 192.0.2.1\r\n\
+t=0 0\r\n\
+m=audio 5004 RTP/AVP 111 101\r\n\
+a=rtpmap:111 opus/48000/2\r\n\
+a=rtpmap:101 telephone-event/8000\r\n\
+a=ssrc:1111 cname:a\r\n\
+a=sendrecv\r\n";
+        let reinvite = "v=0\r\n\
+o=- 1 2 IN IP4 192.0.2.1\r\n\
+s=-\r\n\
+c=IN IP4 192.0.2.1\r\n\
+t=0 0\r\n\
+m=audio 5004 RTP/AVP 120 101\r\n\
+a=rtpmap:120 opus/48000/2\r\n\
+a=rtpmap:101 telephone-event/8000\r\n\
+a=ssrc:2222 cname:a\r\n\
+a=sendonly\r\n\
+m=video 5006 RTP/AVP 96\r\n\
+a=rtpmap:96 VP8/90000\r\n";
+
+        let old = SessionDescription::parse(SdpType::Offer, initial).unwrap();
+        let new = SessionDescription::parse(SdpType::Offer, reinvite).unwrap();
+        assert!(old.diff(&old).is_empty());
+
+        let diff = old.diff(&new);
+        assert!(diff.removed_media.is_empty());
+        assert_eq!(diff.added_media.len(), 1);
+        assert_eq!(diff.added_media[0].kind, MediaKind::Video);
+
+        assert_eq!(diff.changed_media.len(), 1);
+        let audio = &diff.changed_media[0];
+        assert_eq!(audio.index, 0);
+        assert_eq!(audio.port, None);
+        assert_eq!(audio.added_formats, vec!["120"]);
+        assert_eq!(audio.removed_formats, vec!["111"]);
+        assert_eq!(audio.added_ssrcs, vec![2222]);
+        assert_eq!(audio.removed_ssrcs, vec![1111]);
+        assert_eq!(
+            audio.direction,
+            Some((Direction::SendRecv, Direction::SendOnly))
+        );
+        assert!(
+            audio
+                .added_attributes
+                .contains(&Attribute::new("rtpmap", Some("120 opus/48000/2".into())))
+        );
+        assert!(
+            audio
+                .removed_attributes
+                .contains(&Attribute::new("rtpmap", Some("111 opus/48000/2".into())))
+        );
+        assert!(
+            !audio
+                .added_attributes
+                .iter()
+                .any(|a| a.value.as_deref() == Some("101 telephone-event/8000"))
+        );
+    }
+
     #[test]
     fn test_parse_audio_capabilities_from_sdp() {
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111 0\r\n\
 a=mid:0\r\n\
@@ -1897,11 +2761,75 @@ a=fmtp:111 minptime=10;useinbandfec=1\r\n";
         assert_eq!(caps[1].channels, 1);
     }
 
+    #[test]
+    fn fmtp_parameters_are_parsed_per_payload_type() {
+        let section = MediaSection::new(MediaKind::Audio, "0")
+            .attribute(
+                "fmtp",
+                Some("111 maxaveragebitrate=24000; useinbandfec=1".into()),
+            )
+            .attribute("fmtp", Some("101 0-16".into()));
+
+        let params = parse_fmtp(section.fmtp(111).unwrap());
+        assert_eq!(params.len(), 2);
+        assert_eq!(params["maxaveragebitrate"], "24000");
+        assert_eq!(params["useinbandfec"], "1");
+        assert_eq!(parse_fmtp(section.fmtp(101).unwrap())["0-16"], "");
+        assert!(section.fmtp(0).is_none());
+    }
+
+    #[test]
+    fn rtpmap_parsing_tolerates_spacing_and_missing_channels() {
+        let opus = RtpMap::parse("111 opus/48000/2 ").unwrap();
+        assert_eq!(opus.payload_type, 111);
+        assert_eq!(opus.encoding_name, "opus");
+        assert_eq!(opus.clock_rate, 48000);
+        assert_eq!(opus.channels, Some(2));
+        assert_eq!(RtpMap::parse("  111\topus / 48000 /2"), Some(opus));
+
+        let pcmu = RtpMap::parse("0 PCMU/8000").unwrap();
+        assert_eq!(pcmu.encoding_name, "PCMU");
+        assert_eq!(pcmu.channels, None);
+        let h264 = RtpMap::parse("96 h264/90000").unwrap();
+        assert_eq!(h264.encoding_name, "h264");
+
+        for malformed in [
+            "",
+            "111",
+            "111 opus",
+            "x opus/48000",
+            "111 /48000",
+            "111 opus/fast",
+            "111 opus/48000/two",
+            "111 opus/48000/2/1",
+        ] {
+            assert_eq!(RtpMap::parse(malformed), None, "{malformed:?}");
+        }
+
+        let mut section = MediaSection::new(MediaKind::Audio, "0")
+            .attribute("rtpmap", Some("111 OPUS/48000/2 ".into()))
+            .attribute("rtpmap", Some("0 opus/broken".into()))
+            .attribute("rtpmap", Some("8  PCMA/8000".into()));
+        section.formats = vec!["111".into(), "0".into(), "8".into()];
+        assert_eq!(section.rtpmap(111).unwrap().clock_rate, 48000);
+        assert!(section.rtpmap(0).is_none());
+
+        let caps = section.to_audio_capabilities();
+        assert_eq!(caps.len(), 3);
+        assert_eq!(caps[0].codec_name, "OPUS");
+        assert_eq!((caps[0].clock_rate, caps[0].channels), (48000, 2));
+        // The malformed line is skipped; PT 0 falls back to its static mapping.
+        assert_eq!(caps[1].codec_name, "PCMU");
+        assert_eq!(caps[2].codec_name, "PCMA");
+        assert_eq!((caps[2].clock_rate, caps[2].channels), (8000, 1));
+    }
+
     #[test]
     fn test_parse_capabilities_empty_for_wrong_kind() {
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
 a=mid:0\r\n\
@@ -1925,6 +2853,7 @@ a=rtpmap:111 opus/48000/2\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
 a=mid:0\r\n";
@@ -1945,6 +2874,7 @@ a=mid:0\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 0 8 9 18 111 101\r\n\
 a=mid:0\r\n";
@@ -2142,6 +3072,7 @@ a=mid:as\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=image 12345 udptl t38\r\n\
 a=mid:0\r\n\
@@ -2227,6 +3158,7 @@ a=T38FaxUdpEC:t38UDPRedundancy\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=image 12345 udptl t38\r\n\
 a=mid:0\r\n\
@@ -2256,6 +3188,7 @@ a=T38FaxUdpEC:t38UDPRedundancy\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
 a=mid:0\r\n";
@@ -2270,6 +3203,7 @@ a=mid:0\r\n";
         let sdp = "v=0\r\n\
 o=- 1 1 IN IP4 127.0.0.1\r\n\
 s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
 t=0 0\r\n\
 m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
 a=mid:0\r\n\
@@ -2280,4 +3214,224 @@ a=mid:1\r\n";
         assert!(desc.first_image_section().is_some());
         assert_eq!(desc.first_image_section().unwrap().mid, "1");
     }
+
+    #[test]
+    fn parse_framerate_and_imageattr() {
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
+t=0 0\r\n\
+m=video 9 RTP/AVP 96 97\r\n\
+a=rtpmap:96 VP8/90000\r\n\
+a=rtpmap:97 H264/90000\r\n\
+a=framerate:15\r\n\
+a=imageattr:96 send [x=1280,y=720] recv [x=[320:640],y=[240:480]] [x=800,y=600]\r\n\
+a=imageattr:* send * recv [x=[320,640,1920],y=[240,480,1080]]\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        let section = &desc.media_sections[0];
+
+        assert_eq!(section.framerate(), Some(15.0));
+        assert_eq!(
+            section.get_image_attributes(),
+            vec![
+                ImageAttribute {
+                    payload_type: Some(96),
+                    send: Some((1280, 720)),
+                    recv: Some((800, 600)),
+                },
+                ImageAttribute {
+                    payload_type: None,
+                    send: None,
+                    recv: Some((1920, 1080)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn builder_offer_parses_and_serializes_cleanly() {
+        let offer = SessionDescriptionBuilder::new(SdpType::Offer)
+            .connection("IN IP4 127.0.0.1")
+            .add_media(MediaKind::Audio, "0")
+            .port(40000)
+            .protocol("RTP/AVP")
+            .add_audio_codec(111, "opus", 48000, Some(2))
+            .add_fmtp(111, "minptime=10;useinbandfec=1")
+            .add_audio_codec(0, "PCMU", 8000, None)
+            .add_extmap(1, SDES_MID_URI)
+            .set_direction(Direction::SendOnly)
+            .set_ssrc(1111, "old")
+            .set_ssrc(4242, "rustrtc")
+            .attribute("rtcp-mux", None)
+            .add_media(MediaKind::Video, "1")
+            .add_video_codec(96, "VP8", 90000)
+            .set_direction(Direction::RecvOnly)
+            .build()
+            .unwrap();
+
+        let sdp = offer.to_sdp_string();
+        let parsed = SessionDescription::parse(SdpType::Offer, &sdp).unwrap();
+        // Parsing copies the session-level c= into each section; after that
+        // the description round-trips unchanged.
+        let reparsed = SessionDescription::parse(SdpType::Offer, &parsed.to_sdp_string()).unwrap();
+        assert_eq!(reparsed, parsed);
+
+        let audio = &parsed.media_sections[0];
+        assert_eq!(audio.port, 40000);
+        assert_eq!(audio.protocol, "RTP/AVP");
+        assert_eq!(audio.formats, vec!["111", "0"]);
+        assert_eq!(audio.rtpmap(111).unwrap().channels, Some(2));
+        assert_eq!(audio.fmtp(111), Some("minptime=10;useinbandfec=1"));
+        assert_eq!(audio.get_extmap_id(SDES_MID_URI), Some(1));
+        assert_eq!(audio.direction, Direction::SendOnly);
+        assert_eq!(audio.ssrcs(), vec![4242]);
+        assert!(sdp.contains("a=ssrc:4242 cname:rustrtc\r\n"));
+        assert!(sdp.contains("a=rtcp-mux\r\n"));
+        assert_eq!(parsed.media_sections[1].mid, "1");
+        assert_eq!(parsed.media_sections[1].direction, Direction::RecvOnly);
+    }
+
+    #[test]
+    fn builder_rejects_inconsistent_descriptions() {
+        let audio = || {
+            SessionDescriptionBuilder::new(SdpType::Offer)
+                .connection("IN IP4 127.0.0.1")
+                .add_media(MediaKind::Audio, "0")
+                .add_audio_codec(0, "PCMU", 8000, None)
+        };
+        let invalid = |result: SdpResult<SessionDescription>| match result {
+            Err(SdpError::Invalid(message)) => message,
+            other => panic!("expected SdpError::Invalid, got {other:?}"),
+        };
+
+        assert!(audio().build().is_ok());
+        assert_eq!(
+            invalid(audio().add_audio_codec(0, "PCMA", 8000, None).build()),
+            "duplicate payload type 0"
+        );
+        assert_eq!(
+            invalid(audio().add_fmtp(101, "0-16").build()),
+            "fmtp for unknown payload type 101"
+        );
+        assert_eq!(
+            invalid(
+                audio()
+                    .add_extmap(3, SDES_MID_URI)
+                    .add_extmap(3, RID_URI)
+                    .build()
+            ),
+            "duplicate extmap id 3"
+        );
+        assert_eq!(
+            invalid(audio().add_extmap(15, RID_URI).build()),
+            "extmap id 15 is reserved"
+        );
+        assert_eq!(
+            invalid(audio().add_video_codec(96, "VP8", 90000).build()),
+            "video codec VP8 in audio section"
+        );
+        assert_eq!(
+            invalid(audio().add_media(MediaKind::Video, "0").build()),
+            "duplicate mid 0"
+        );
+        assert_eq!(
+            invalid(audio().add_media(MediaKind::Video, "1").build()),
+            "video section \"1\" has no formats"
+        );
+        assert_eq!(
+            invalid(
+                SessionDescriptionBuilder::new(SdpType::Offer)
+                    .set_direction(Direction::Inactive)
+                    .build()
+            ),
+            "direction set before add_media"
+        );
+        assert_eq!(
+            invalid(
+                SessionDescriptionBuilder::new(SdpType::Offer)
+                    .add_media(MediaKind::Audio, "0")
+                    .add_audio_codec(0, "PCMU", 8000, None)
+                    .build()
+            ),
+            "audio section \"0\" has no connection address"
+        );
+    }
+
+    #[test]
+    fn parses_ice_options_at_session_and_media_level() {
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 0.0.0.0\r\n\
+t=0 0\r\n\
+a=ice-options:trickle\r\n\
+m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
+a=mid:0\r\n\
+a=ice-options:trickle renomination\r\n\
+m=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+a=mid:1\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        assert_eq!(desc.session.ice_options(), vec!["trickle"]);
+        assert_eq!(
+            desc.media_sections[0].ice_options(),
+            vec!["trickle", "renomination"]
+        );
+        assert!(desc.media_sections[1].ice_options().is_empty());
+        assert_eq!(desc.ice_options(), vec!["trickle", "renomination"]);
+        assert!(desc.has_ice_option("renomination"));
+        assert!(!desc.has_ice_option("ice2"));
+    }
+
+    #[test]
+    fn parses_three_layer_simulcast_with_rid_restrictions() {
+        let sdp = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 0.0.0.0\r\n\
+t=0 0\r\n\
+m=video 9 UDP/TLS/RTP/SAVPF 96 97\r\n\
+a=mid:0\r\n\
+a=rid:low send pt=96;max-width=320;max-height=180\r\n\
+a=rid:mid send max-width=640\r\n\
+a=rid:high send\r\n\
+a=simulcast:send low;mid;~high\r\n";
+        let desc = SessionDescription::parse(SdpType::Offer, sdp).unwrap();
+        let layers = desc.media_sections[0].simulcast_layers().unwrap();
+        assert!(layers.recv.is_empty());
+        let rids: Vec<&str> = layers.send.iter().map(|l| l.rid.as_str()).collect();
+        assert_eq!(rids, vec!["low", "mid", "high"]);
+        assert_eq!(
+            layers.send[0].restrictions,
+            vec![
+                ("pt".to_string(), "96".to_string()),
+                ("max-width".to_string(), "320".to_string()),
+                ("max-height".to_string(), "180".to_string()),
+            ]
+        );
+        assert_eq!(
+            layers.send[1].restrictions,
+            vec![("max-width".to_string(), "640".to_string())]
+        );
+        assert!(layers.send[2].restrictions.is_empty());
+        assert_eq!(
+            layers.send.iter().map(|l| l.paused).collect::<Vec<_>>(),
+            vec![false, false, true]
+        );
+
+        let written: Vec<String> = layers
+            .to_attributes()
+            .iter()
+            .map(|a| format!("{}:{}", a.key, a.value.as_deref().unwrap_or_default()))
+            .collect();
+        assert_eq!(
+            written,
+            vec![
+                "rid:low send pt=96;max-width=320;max-height=180",
+                "rid:mid send max-width=640",
+                "rid:high send",
+                "simulcast:send low;mid;~high",
+            ]
+        );
+    }
 }
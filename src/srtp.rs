@@ -94,6 +94,10 @@ const SSRC_CONTEXT_HIGH_WATERMARK: usize = 32;
 /// eligible for eviction. A real media SSRC silent this long has almost
 /// certainly ended (or rotated), so dropping its ROC state is safe.
 const SSRC_INACTIVITY_EVICT: std::time::Duration = std::time::Duration::from_secs(60);
+/// Packets this far behind the newest authenticated index are rejected as
+/// replays. Sized like libwebrtc's window so late NACK retransmissions of
+/// high-rate video still fit.
+const REPLAY_WINDOW_SIZE: u64 = 1024;
 
 impl SrtpSession {
     pub fn new(
@@ -208,6 +212,62 @@ impl SrtpSession {
     }
 }
 
+/// Sliding replay window over packet indices (RFC 3711 §3.3.2).
+#[derive(Clone)]
+struct ReplayWindow {
+    highest: Option<u64>,
+    // Bit `index % REPLAY_WINDOW_SIZE` is set once that index was accepted
+    seen: [u64; (REPLAY_WINDOW_SIZE / 64) as usize],
+}
+
+impl ReplayWindow {
+    fn new() -> Self {
+        Self {
+            highest: None,
+            seen: [0; (REPLAY_WINDOW_SIZE / 64) as usize],
+        }
+    }
+
+    fn slot(index: u64) -> (usize, u64) {
+        let bit = index % REPLAY_WINDOW_SIZE;
+        ((bit / 64) as usize, 1 << (bit % 64))
+    }
+
+    /// Whether `index` is new: ahead of the window, or inside it and unseen.
+    fn check(&self, index: u64) -> bool {
+        match self.highest {
+            Some(highest) if index <= highest => {
+                if highest - index >= REPLAY_WINDOW_SIZE {
+                    return false;
+                }
+                let (word, mask) = Self::slot(index);
+                self.seen[word] & mask == 0
+            }
+            _ => true,
+        }
+    }
+
+    /// Record an authenticated `index`, sliding the window forward if needed.
+    fn accept(&mut self, index: u64) {
+        match self.highest {
+            Some(highest) if index <= highest => {}
+            Some(highest) if index - highest < REPLAY_WINDOW_SIZE => {
+                for skipped in highest + 1..=index {
+                    let (word, mask) = Self::slot(skipped);
+                    self.seen[word] &= !mask;
+                }
+                self.highest = Some(index);
+            }
+            _ => {
+                self.seen = [0; (REPLAY_WINDOW_SIZE / 64) as usize];
+                self.highest = Some(index);
+            }
+        }
+        let (word, mask) = Self::slot(index);
+        self.seen[word] |= mask;
+    }
+}
+
 #[derive(Debug, Clone)]
 struct SessionKeys {
     cipher_key: Vec<u8>,
@@ -228,6 +288,8 @@ pub struct SrtpContext {
     rollover_counter: u32,
     last_sequence: Option<u16>,
     rtcp_index: u32,
+    rtp_replay: ReplayWindow,
+    rtcp_replay: ReplayWindow,
     /// Wall-clock time of the most recent protect/unprotect call, used to evict
     /// contexts for SSRCs that have gone away (prevents unbounded growth as
     /// SSRCs churn across a long call / relay).
@@ -299,6 +361,8 @@ impl SrtpContext {
             rollover_counter: 0,
             last_sequence: None,
             rtcp_index: 0,
+            rtp_replay: ReplayWindow::new(),
+            rtcp_replay: ReplayWindow::new(),
             last_used: std::time::Instant::now(),
         })
     }
@@ -438,10 +502,8 @@ impl SrtpContext {
                 index_bytes[3],
             ]);
             let index = index_with_e & 0x7FFF_FFFF;
-
-            // Replay check
-            if index > self.rtcp_index {
-                self.rtcp_index = index;
+            if !self.rtcp_replay.check(index as u64) {
+                return Err(SrtpError::ReplayDetected);
             }
 
             let nonce = self.build_gcm_rtcp_nonce(index);
@@ -468,6 +530,7 @@ impl SrtpContext {
             let plaintext = cipher
                 .decrypt(Nonce::from_slice(&nonce), payload)
                 .map_err(|_| SrtpError::AuthenticationFailed)?;
+            self.rtcp_replay.accept(index as u64);
 
             // Reconstruct packet: Header || Plaintext
             packet.truncate(8);
@@ -499,12 +562,10 @@ impl SrtpContext {
 
         let e_bit = (index_with_e & 0x8000_0000) != 0;
         let index = index_with_e & 0x7FFF_FFFF;
-
-        // Replay check (simplified: just check if index is newer than last seen?)
-        // For now, we just update.
-        if index > self.rtcp_index {
-            self.rtcp_index = index;
+        if !self.rtcp_replay.check(index as u64) {
+            return Err(SrtpError::ReplayDetected);
         }
+        self.rtcp_replay.accept(index as u64);
 
         if e_bit && packet.len() > 8 {
             self.cipher_rtcp(packet, index)?;
@@ -596,6 +657,10 @@ impl SrtpContext {
         }
 
         let roc = self.estimate_roc(packet.header.sequence_number);
+        let index = ((roc as u64) << 16) | packet.header.sequence_number as u64;
+        if !self.rtp_replay.check(index) {
+            return Err(SrtpError::ReplayDetected);
+        }
 
         if let SrtpProfile::AeadAes128Gcm = self._profile {
             let nonce = self.build_gcm_nonce(packet.header.sequence_number, roc);
@@ -619,6 +684,7 @@ impl SrtpContext {
                 .map_err(|_| SrtpError::AuthenticationFailed)?;
 
             packet.payload = Bytes::from(plaintext);
+            self.rtp_replay.accept(index);
             self.update(packet.header.sequence_number, roc);
             return Ok(());
         }
@@ -633,6 +699,7 @@ impl SrtpContext {
             return Err(SrtpError::AuthenticationFailed);
         }
         self.cipher_payload(packet, roc)?;
+        self.rtp_replay.accept(index);
         self.update(packet.header.sequence_number, roc);
         Ok(())
     }
@@ -885,6 +952,74 @@ mod tests {
         receiver.unprotect_rtp(&mut p2_recv).unwrap();
         receiver.unprotect_rtp(&mut p1_recv).unwrap();
     }
+
+    #[test]
+    fn replayed_rtp_is_rejected() {
+        for profile in [SrtpProfile::Aes128Sha1_80, SrtpProfile::AeadAes128Gcm] {
+            let mut sender = SrtpSession::new(profile, material(), material()).unwrap();
+            let mut receiver = SrtpSession::new(profile, material(), material()).unwrap();
+            let protected: Vec<_> = [100u16, 101, 102, 2000]
+                .into_iter()
+                .map(|seq| {
+                    let mut packet = sample_packet(seq);
+                    sender.protect_rtp(&mut packet).unwrap();
+                    packet
+                })
+                .collect();
+
+            // 102 arrives before 101: reordering inside the window is fine.
+            for i in [0, 2, 1] {
+                receiver.unprotect_rtp(&mut protected[i].clone()).unwrap();
+            }
+            let err = receiver
+                .unprotect_rtp(&mut protected[1].clone())
+                .unwrap_err();
+            assert!(matches!(err, SrtpError::ReplayDetected));
+
+            // Once the window moves past 100, a first delivery of it is too old.
+            receiver.unprotect_rtp(&mut protected[3].clone()).unwrap();
+            let mut late = sample_packet(100);
+            SrtpSession::new(profile, material(), material())
+                .unwrap()
+                .protect_rtp(&mut late)
+                .unwrap();
+            let err = receiver.unprotect_rtp(&mut late).unwrap_err();
+            assert!(matches!(err, SrtpError::ReplayDetected));
+        }
+    }
+
+    #[test]
+    fn replayed_rtcp_is_rejected() {
+        let mut sender =
+            SrtpSession::new(SrtpProfile::Aes128Sha1_80, material(), material()).unwrap();
+        let mut receiver =
+            SrtpSession::new(SrtpProfile::Aes128Sha1_80, material(), material()).unwrap();
+        // Minimal receiver report: V=2, RC=0, PT=201, length=1, sender SSRC.
+        let mut rtcp = vec![0x80, 201, 0, 1, 0xde, 0xad, 0xbe, 0xef];
+        sender.protect_rtcp(&mut rtcp).unwrap();
+
+        let mut first = rtcp.clone();
+        receiver.unprotect_rtcp(&mut first).unwrap();
+        assert_eq!(first, [0x80, 201, 0, 1, 0xde, 0xad, 0xbe, 0xef]);
+        let err = receiver.unprotect_rtcp(&mut rtcp).unwrap_err();
+        assert!(matches!(err, SrtpError::ReplayDetected));
+    }
+
+    #[test]
+    fn forged_packet_does_not_advance_replay_window() {
+        let mut sender =
+            SrtpSession::new(SrtpProfile::Aes128Sha1_80, material(), material()).unwrap();
+        let mut receiver =
+            SrtpSession::new(SrtpProfile::Aes128Sha1_80, material(), material()).unwrap();
+        let mut packet = sample_packet(5000);
+        sender.protect_rtp(&mut packet).unwrap();
+
+        let mut forged = sample_packet(5000);
+        forged.payload = Bytes::from(vec![0u8; 13]);
+        let err = receiver.unprotect_rtp(&mut forged).unwrap_err();
+        assert!(matches!(err, SrtpError::AuthenticationFailed));
+        receiver.unprotect_rtp(&mut packet).unwrap();
+    }
 }
 
 fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
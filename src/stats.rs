@@ -27,6 +27,8 @@ pub enum StatsKind {
     DataChannel,
     MediaSource,
     MediaSink,
+    /// Send-side bandwidth estimate from transport-cc feedback.
+    SenderBwe,
     Custom(String),
 }
 
@@ -1,11 +1,17 @@
 use crate::errors::RtcResult;
-use crate::peer_connection::{RtpReceiverInterceptor, RtpSenderInterceptor};
-use crate::rtp::{ReceiverReport, RtcpPacket, RtpPacket, SenderReport};
+use crate::peer_connection::{RtpCodecParameters, RtpReceiverInterceptor, RtpSenderInterceptor};
+use crate::rtp::{ReceiverReport, ReportBlock, RtcpPacket, RtpPacket, SenderReport};
 use crate::stats::{StatsEntry, StatsId, StatsKind, StatsProvider};
 use async_trait::async_trait;
 use parking_lot::Mutex;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Sent Sender Reports remembered per SSRC for pairing with the LSR field of
+/// incoming report blocks. Reports go out every few seconds, so this covers
+/// well over a minute of RR latency.
+const SENDER_REPORT_HISTORY: usize = 16;
 
 #[derive(Debug, Clone, Default)]
 struct RemoteInboundStats {
@@ -26,12 +32,20 @@ struct RemoteOutboundStats {
 struct LocalInboundStats {
     packets_received: u64,
     bytes_received: u64,
+    payload_type: u8,
+    /// RFC 3550 §6.4.1 interarrival jitter, in RTP timestamp units.
+    jitter: f64,
+    clock_rate: u32,
+    /// Arrival time and RTP timestamp of the previous packet.
+    last_arrival: Option<(Instant, u32)>,
 }
 
 #[derive(Debug, Clone, Default)]
 struct LocalOutboundStats {
     packets_sent: u64,
     bytes_sent: u64,
+    payload_type: u8,
+    payload_bytes_sent: u64,
 }
 
 #[derive(Default)]
@@ -40,6 +54,12 @@ pub struct StatsCollector {
     remote_outbound: Mutex<HashMap<u32, RemoteOutboundStats>>,
     local_inbound: Mutex<HashMap<u32, LocalInboundStats>>,
     local_outbound: Mutex<HashMap<u32, LocalOutboundStats>>,
+    /// Per sender SSRC: (compact NTP timestamp, send time) of recent SRs.
+    sent_reports: Mutex<HashMap<u32, VecDeque<(u32, Instant)>>>,
+    /// Negotiated clock rate per (media SSRC, payload type), for jitter.
+    /// Keyed by SSRC because receivers on different m-lines may map the same
+    /// payload type to different codecs.
+    clock_rates: Mutex<HashMap<(u32, u8), u32>>,
 }
 
 impl StatsCollector {
@@ -48,14 +68,64 @@ impl StatsCollector {
     }
 
     pub fn process_rtcp(&self, packet: &RtcpPacket) {
+        self.process_rtcp_at(packet, Instant::now());
+    }
+
+    fn process_rtcp_at(&self, packet: &RtcpPacket, received_at: Instant) {
         match packet {
-            RtcpPacket::SenderReport(sr) => self.handle_sr(sr),
-            RtcpPacket::ReceiverReport(rr) => self.handle_rr(rr),
+            RtcpPacket::SenderReport(sr) => self.handle_sr(sr, received_at),
+            RtcpPacket::ReceiverReport(rr) => self.handle_rr(rr, received_at),
             _ => {}
         }
     }
 
-    fn handle_sr(&self, sr: &SenderReport) {
+    /// Remember a Sender Report we sent so a later report block echoing its
+    /// timestamp (LSR) yields a round-trip time.
+    pub fn record_sender_report(&self, sr: &SenderReport, sent_at: Instant) {
+        let mut sent = self.sent_reports.lock();
+        let history = sent.entry(sr.sender_ssrc).or_default();
+        if history.len() == SENDER_REPORT_HISTORY {
+            history.pop_front();
+        }
+        history.push_back((compact_ntp(sr), sent_at));
+    }
+
+    /// RFC 3550 §6.4.1: RTT = arrival - send time of the referenced SR - DLSR.
+    fn round_trip_time(&self, block: &ReportBlock, received_at: Instant) -> Option<f64> {
+        if block.last_sender_report == 0 {
+            return None;
+        }
+        let sent_at = self
+            .sent_reports
+            .lock()
+            .get(&block.ssrc)?
+            .iter()
+            .rev()
+            .find(|(ntp, _)| *ntp == block.last_sender_report)
+            .map(|(_, sent_at)| *sent_at)?;
+        // DLSR is expressed in units of 1/65536 seconds.
+        let delay = Duration::from_nanos(
+            block.delay_since_last_sender_report as u64 * 1_000_000_000 / 65536,
+        );
+        let rtt = received_at
+            .checked_duration_since(sent_at)?
+            .checked_sub(delay)?;
+        Some(rtt.as_secs_f64())
+    }
+
+    fn update_remote_inbound(&self, block: &ReportBlock, received_at: Instant) {
+        let rtt = self.round_trip_time(block, received_at);
+        let mut inbound = self.remote_inbound.lock();
+        let stats = inbound.entry(block.ssrc).or_default();
+        stats.packets_lost = block.packets_lost;
+        stats.fraction_lost = block.fraction_lost;
+        stats.jitter = block.jitter;
+        if rtt.is_some() {
+            stats.round_trip_time = rtt;
+        }
+    }
+
+    fn handle_sr(&self, sr: &SenderReport, received_at: Instant) {
         {
             let mut outbound = self.remote_outbound.lock();
             let stats = outbound.entry(sr.sender_ssrc).or_default();
@@ -66,30 +136,45 @@ impl StatsCollector {
 
         // SR also contains report blocks for our streams
         for block in &sr.report_blocks {
-            let mut inbound = self.remote_inbound.lock();
-            let stats = inbound.entry(block.ssrc).or_default();
-            stats.packets_lost = block.packets_lost;
-            stats.fraction_lost = block.fraction_lost;
-            stats.jitter = block.jitter;
+            self.update_remote_inbound(block, received_at);
         }
     }
 
-    fn handle_rr(&self, rr: &ReceiverReport) {
+    fn handle_rr(&self, rr: &ReceiverReport, received_at: Instant) {
         for block in &rr.report_blocks {
-            let mut inbound = self.remote_inbound.lock();
-            let stats = inbound.entry(block.ssrc).or_default();
-            stats.packets_lost = block.packets_lost;
-            stats.fraction_lost = block.fraction_lost;
-            stats.jitter = block.jitter;
-
-            // Calculate RTT if possible
-            // delay_since_last_sender_report is in units of 1/65536 seconds
-            if block.last_sender_report != 0 {
-                // We need to know when we sent the SR with NTP timestamp `last_sender_report`.
-                // This requires keeping a history of sent SRs.
-                // For now, we skip RTT calculation here or implement a simplified version if we had the send time.
+            self.update_remote_inbound(block, received_at);
+        }
+    }
+
+    fn record_received(&self, packet: &RtpPacket, received_at: Instant) {
+        let size = Self::packet_size(packet);
+        let clock_rate = self
+            .clock_rates
+            .lock()
+            .get(&(packet.header.ssrc, packet.header.payload_type))
+            .copied();
+        let mut inbound = self.local_inbound.lock();
+        let stats = inbound.entry(packet.header.ssrc).or_default();
+        stats.packets_received += 1;
+        stats.bytes_received += size;
+        stats.payload_type = packet.header.payload_type;
+
+        let timestamp = packet.header.timestamp;
+        if let Some(clock_rate) = clock_rate {
+            if let Some((last_received_at, last_timestamp)) = stats.last_arrival
+                && stats.clock_rate == clock_rate
+            {
+                // RFC 3550 §6.4.1: D(i,j) = (Rj - Ri) - (Sj - Si), J += (|D| - J) / 16.
+                let arrival = received_at
+                    .saturating_duration_since(last_received_at)
+                    .as_secs_f64()
+                    * f64::from(clock_rate);
+                let sent = f64::from(timestamp.wrapping_sub(last_timestamp) as i32);
+                stats.jitter += ((arrival - sent).abs() - stats.jitter) / 16.0;
             }
+            stats.clock_rate = clock_rate;
         }
+        stats.last_arrival = Some((received_at, timestamp));
     }
 
     fn packet_size(packet: &RtpPacket) -> u64 {
@@ -103,6 +188,11 @@ impl StatsCollector {
     }
 }
 
+/// Middle 32 bits of the SR's NTP timestamp, as echoed in the LSR field.
+fn compact_ntp(sr: &SenderReport) -> u32 {
+    (sr.ntp_most << 16) | (sr.ntp_least >> 16)
+}
+
 #[async_trait]
 impl RtpSenderInterceptor for StatsCollector {
     async fn on_packet_sent(
@@ -116,6 +206,17 @@ impl RtpSenderInterceptor for StatsCollector {
         let stats = outbound.entry(packet.header.ssrc).or_default();
         stats.packets_sent += 1;
         stats.bytes_sent += size;
+        stats.payload_type = packet.header.payload_type;
+        stats.payload_bytes_sent += packet.payload.len() as u64;
+    }
+
+    async fn on_rtcp_sent(&self, packets: &[RtcpPacket]) {
+        let now = Instant::now();
+        for packet in packets {
+            if let RtcpPacket::SenderReport(sr) = packet {
+                self.record_sender_report(sr, now);
+            }
+        }
     }
 }
 
@@ -127,13 +228,17 @@ impl RtpReceiverInterceptor for StatsCollector {
         _src_addr: std::net::SocketAddr,
         _local_addr: std::net::SocketAddr,
     ) -> Option<RtcpPacket> {
-        let size = Self::packet_size(packet);
-        let mut inbound = self.local_inbound.lock();
-        let stats = inbound.entry(packet.header.ssrc).or_default();
-        stats.packets_received += 1;
-        stats.bytes_received += size;
+        self.record_received(packet, Instant::now());
         None
     }
+
+    fn on_payload_map(&self, ssrc: u32, payload_map: &HashMap<u8, RtpCodecParameters>) {
+        let mut clock_rates = self.clock_rates.lock();
+        clock_rates.retain(|(known, _), _| *known != ssrc);
+        for (pt, params) in payload_map {
+            clock_rates.insert((ssrc, *pt), params.clock_rate);
+        }
+    }
 }
 
 #[async_trait]
@@ -182,7 +287,13 @@ impl StatsProvider for StatsCollector {
                 entry = entry
                     .with_value("ssrc", json!(ssrc))
                     .with_value("packetsReceived", json!(stats.packets_received))
-                    .with_value("bytesReceived", json!(stats.bytes_received));
+                    .with_value("bytesReceived", json!(stats.bytes_received))
+                    .with_value("payloadType", json!(stats.payload_type));
+                // Reported in seconds, like RTCInboundRtpStreamStats.jitter.
+                if stats.clock_rate > 0 {
+                    entry = entry
+                        .with_value("jitter", json!(stats.jitter / f64::from(stats.clock_rate)));
+                }
 
                 entries.push(entry);
             }
@@ -196,7 +307,14 @@ impl StatsProvider for StatsCollector {
                 entry = entry
                     .with_value("ssrc", json!(ssrc))
                     .with_value("packetsSent", json!(stats.packets_sent))
-                    .with_value("bytesSent", json!(stats.bytes_sent));
+                    .with_value("bytesSent", json!(stats.bytes_sent))
+                    .with_value("payloadType", json!(stats.payload_type));
+                if stats.packets_sent > 0 {
+                    entry = entry.with_value(
+                        "averagePayloadSize",
+                        json!(stats.payload_bytes_sent as f64 / stats.packets_sent as f64),
+                    );
+                }
 
                 entries.push(entry);
             }
@@ -209,7 +327,6 @@ impl StatsProvider for StatsCollector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rtp::{ReportBlock, SenderReport};
 
     #[tokio::test]
     async fn test_stats_collector_sr() {
@@ -255,6 +372,99 @@ mod tests {
         assert_eq!(remote_inbound.values["jitter"], 20);
     }
 
+    #[tokio::test]
+    async fn test_stats_collector_rtt_from_sr_rr_cycle() {
+        let collector = StatsCollector::new();
+        let sr = SenderReport {
+            sender_ssrc: 12345,
+            ntp_most: 0xdead_beef,
+            ntp_least: 0x1234_5678,
+            rtp_timestamp: 0,
+            packet_count: 10,
+            octet_count: 1000,
+            report_blocks: Vec::new(),
+        };
+        let sent_at = Instant::now();
+        collector.record_sender_report(&sr, sent_at);
+
+        // The remote held our SR for 250ms before answering; the RR arrives
+        // 350ms after we sent it, leaving 100ms of network round trip.
+        let rr = |last_sender_report| ReceiverReport {
+            sender_ssrc: 67890,
+            report_blocks: vec![ReportBlock {
+                ssrc: 12345,
+                fraction_lost: 0,
+                packets_lost: 0,
+                highest_sequence: 100,
+                jitter: 0,
+                last_sender_report,
+                delay_since_last_sender_report: 65536 / 4,
+            }],
+        };
+        let received_at = sent_at + Duration::from_millis(350);
+
+        // An LSR we never sent yields no RTT.
+        collector.process_rtcp_at(&RtcpPacket::ReceiverReport(rr(0x1111_2222)), received_at);
+        let stats = collector.collect().await.unwrap();
+        let remote_inbound = stats
+            .iter()
+            .find(|s| s.kind == StatsKind::RemoteInboundRtp)
+            .unwrap();
+        assert!(!remote_inbound.values.contains_key("roundTripTime"));
+
+        collector.process_rtcp_at(
+            &RtcpPacket::ReceiverReport(rr(compact_ntp(&sr))),
+            received_at,
+        );
+        let stats = collector.collect().await.unwrap();
+        let remote_inbound = stats
+            .iter()
+            .find(|s| s.kind == StatsKind::RemoteInboundRtp)
+            .unwrap();
+        assert_eq!(remote_inbound.values["ssrc"], 12345);
+        let rtt = remote_inbound.values["roundTripTime"].as_f64().unwrap();
+        assert!((rtt - 0.1).abs() < 1e-6, "rtt = {rtt}");
+    }
+
+    #[tokio::test]
+    async fn test_stats_collector_rtt_from_sent_rtcp_hook() {
+        let collector = StatsCollector::new();
+        let sr = SenderReport {
+            sender_ssrc: 4242,
+            ntp_most: 0x0000_1000,
+            ntp_least: 0x8000_0000,
+            rtp_timestamp: 0,
+            packet_count: 1,
+            octet_count: 100,
+            report_blocks: Vec::new(),
+        };
+        collector
+            .on_rtcp_sent(&[RtcpPacket::SenderReport(sr.clone())])
+            .await;
+
+        let rr = ReceiverReport {
+            sender_ssrc: 1,
+            report_blocks: vec![ReportBlock {
+                ssrc: 4242,
+                fraction_lost: 0,
+                packets_lost: 0,
+                highest_sequence: 1,
+                jitter: 0,
+                last_sender_report: compact_ntp(&sr),
+                delay_since_last_sender_report: 0,
+            }],
+        };
+        collector.process_rtcp(&RtcpPacket::ReceiverReport(rr));
+
+        let stats = collector.collect().await.unwrap();
+        let remote_inbound = stats
+            .iter()
+            .find(|s| s.kind == StatsKind::RemoteInboundRtp)
+            .unwrap();
+        let rtt = remote_inbound.values["roundTripTime"].as_f64().unwrap();
+        assert!((0.0..1.0).contains(&rtt), "rtt = {rtt}");
+    }
+
     #[tokio::test]
     async fn test_stats_collector_interceptor() {
         let collector = StatsCollector::new();
@@ -292,5 +502,49 @@ mod tests {
         assert_eq!(inbound.values["ssrc"], 67890);
         assert_eq!(inbound.values["packetsReceived"], 1);
         assert_eq!(inbound.values["bytesReceived"], 112);
+        assert_eq!(inbound.values["payloadType"], 96);
+        assert_eq!(outbound.values["payloadType"], 96);
+        // No negotiated clock rate for PT 96, so no jitter either.
+        assert!(!inbound.values.contains_key("jitter"));
+    }
+
+    #[tokio::test]
+    async fn test_stats_collector_interarrival_jitter() {
+        let collector = StatsCollector::new();
+        let payload_map = |clock_rate| {
+            HashMap::from([(
+                0,
+                RtpCodecParameters {
+                    payload_type: 0,
+                    clock_rate,
+                    channels: 1,
+                },
+            )])
+        };
+        collector.on_payload_map(777, &payload_map(8000));
+        // Another receiver maps the same payload type to a different rate.
+        collector.on_payload_map(888, &payload_map(48000));
+
+        // 20ms PCMU packets: the second arrives on time, the third 10ms late
+        // and the fourth 10ms early, so |D| = 0, 80, 80 ticks.
+        let start = Instant::now();
+        for (index, arrival_ms) in [0u64, 20, 50, 60].into_iter().enumerate() {
+            let header = crate::rtp::RtpHeader::new(0, index as u16, index as u32 * 160, 777);
+            collector.record_received(
+                &RtpPacket::new(header, vec![0u8; 160]),
+                start + Duration::from_millis(arrival_ms),
+            );
+        }
+
+        let stats = collector.collect().await.unwrap();
+        let inbound = stats
+            .iter()
+            .find(|s| s.kind == StatsKind::InboundRtp && s.values["ssrc"] == 777)
+            .unwrap();
+        assert_eq!(inbound.values["packetsReceived"], 4);
+        assert_eq!(inbound.values["payloadType"], 0);
+        // J = 0 -> 0 -> 80/16 = 5 -> 5 + 75/16 = 9.6875 ticks at 8 kHz.
+        let jitter = inbound.values["jitter"].as_f64().unwrap();
+        assert!((jitter - 9.6875 / 8000.0).abs() < 1e-9, "jitter = {jitter}");
     }
 }
@@ -0,0 +1,38 @@
+//! RFC 8865 T.140 real-time text over a data channel.
+//!
+//! Text is carried on a reliable, ordered data channel negotiated with the
+//! `t140` subprotocol. Each message is plain UTF-8 T.140 text; SCTP
+//! retransmission takes the place of the RTP redundancy of RFC 4103, so no
+//! sequence numbers or redundant blocks are added.
+
+/// Data channel label and subprotocol used for T.140 (RFC 8865 §4.1).
+pub const T140_PROTOCOL: &str = "t140";
+
+/// Zero width no-break space, which T.140 senders may use as a keep-alive or
+/// at the start of a session; it carries no text (ITU-T T.140).
+pub const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+/// Decode one T.140 message into displayable text. Invalid UTF-8 is replaced
+/// by U+FFFD and byte order marks are dropped.
+pub fn decode(message: &[u8]) -> String {
+    String::from_utf8_lossy(message)
+        .chars()
+        .filter(|&c| c != BYTE_ORDER_MARK)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_plain_utf8() {
+        assert_eq!(decode("wörld 👋".as_bytes()), "wörld 👋");
+    }
+
+    #[test]
+    fn decode_drops_byte_order_marks_and_replaces_invalid_bytes() {
+        assert_eq!(decode("\u{FEFF}hi".as_bytes()), "hi");
+        assert_eq!(decode(&[b'a', 0xFF, b'b']), "a\u{FFFD}b");
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicU16, AtomicUsize};
+use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
 use tokio::sync::{Mutex as TokioMutex, mpsc};
 
 // DCEP Constants
@@ -107,6 +107,8 @@ pub enum DataChannelEvent {
     Open,
     Message(Bytes),
     Close,
+    /// `buffered_amount()` dropped to or below `buffered_amount_low_threshold()`.
+    BufferedAmountLow,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -156,6 +158,8 @@ pub struct DataChannel {
     rx: TokioMutex<mpsc::UnboundedReceiver<DataChannelEvent>>,
     pub(crate) reassembly_buffer: Mutex<BytesMut>,
     pub(crate) send_lock: TokioMutex<()>,
+    buffered_amount: AtomicUsize,
+    buffered_amount_low_threshold: AtomicUsize,
 }
 
 impl DataChannel {
@@ -176,6 +180,44 @@ impl DataChannel {
             rx: TokioMutex::new(rx),
             reassembly_buffer: Mutex::new(BytesMut::new()),
             send_lock: TokioMutex::new(()),
+            buffered_amount: AtomicUsize::new(0),
+            buffered_amount_low_threshold: AtomicUsize::new(0),
+        }
+    }
+
+    /// Bytes of application data queued on this channel that have not yet been
+    /// handed to the SCTP association for transmission.
+    pub fn buffered_amount(&self) -> usize {
+        self.buffered_amount.load(Ordering::Acquire)
+    }
+
+    pub fn buffered_amount_low_threshold(&self) -> usize {
+        self.buffered_amount_low_threshold.load(Ordering::Relaxed)
+    }
+
+    /// Set the level at or below which `DataChannelEvent::BufferedAmountLow` fires.
+    pub fn set_buffered_amount_low_threshold(&self, threshold: usize) {
+        self.buffered_amount_low_threshold
+            .store(threshold, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_buffered_amount(&self, len: usize) {
+        self.buffered_amount.fetch_add(len, Ordering::AcqRel);
+    }
+
+    /// Account for `len` bytes leaving the send queue and emit
+    /// `BufferedAmountLow` when this crosses the threshold.
+    pub(crate) fn sub_buffered_amount(&self, len: usize) {
+        let previous = self
+            .buffered_amount
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+                Some(v.saturating_sub(len))
+            })
+            .unwrap_or(0);
+        let current = previous.saturating_sub(len);
+        let threshold = self.buffered_amount_low_threshold();
+        if previous > threshold && current <= threshold {
+            self.send_event(DataChannelEvent::BufferedAmountLow);
         }
     }
 
@@ -493,6 +493,99 @@ impl CertificateMessage {
     }
 }
 
+/// Server request for a client certificate (RFC 5246 §7.4.4).
+#[derive(Debug, Clone)]
+pub struct CertificateRequest {
+    pub certificate_types: Vec<u8>,
+    /// (hash, signature) algorithm pairs.
+    pub signature_algorithms: Vec<(u8, u8)>,
+}
+
+impl CertificateRequest {
+    /// `ecdsa_sign` (64)
+    pub const ECDSA_SIGN: u8 = 64;
+
+    pub fn encode(&self, buf: &mut BytesMut) {
+        buf.put_u8(self.certificate_types.len() as u8);
+        buf.put_slice(&self.certificate_types);
+        buf.put_u16((self.signature_algorithms.len() * 2) as u16);
+        for (hash, signature) in &self.signature_algorithms {
+            buf.put_u8(*hash);
+            buf.put_u8(*signature);
+        }
+        // No certificate_authorities: WebRTC certificates are self-signed.
+        buf.put_u16(0);
+    }
+
+    pub fn decode(buf: &mut Bytes) -> Result<Self> {
+        if buf.is_empty() {
+            bail!("CertificateRequest too short");
+        }
+        let types_len = buf.get_u8() as usize;
+        if buf.len() < types_len + 2 {
+            bail!("CertificateRequest too short for certificate types");
+        }
+        let certificate_types = buf.split_to(types_len).to_vec();
+
+        let algorithms_len = buf.get_u16() as usize;
+        if buf.len() < algorithms_len + 2 || !algorithms_len.is_multiple_of(2) {
+            bail!("CertificateRequest has invalid signature algorithms");
+        }
+        let signature_algorithms = buf
+            .split_to(algorithms_len)
+            .chunks(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+
+        let authorities_len = buf.get_u16() as usize;
+        if buf.len() < authorities_len {
+            bail!("CertificateRequest too short for certificate authorities");
+        }
+        buf.advance(authorities_len);
+
+        Ok(Self {
+            certificate_types,
+            signature_algorithms,
+        })
+    }
+}
+
+/// Client proof of possession of its certificate key (RFC 5246 §7.4.8).
+#[derive(Debug, Clone)]
+pub struct CertificateVerify {
+    pub hash_algorithm: u8,
+    pub signature_algorithm: u8,
+    pub signature: Vec<u8>,
+}
+
+impl CertificateVerify {
+    pub fn encode(&self, buf: &mut BytesMut) {
+        buf.put_u8(self.hash_algorithm);
+        buf.put_u8(self.signature_algorithm);
+        buf.put_u16(self.signature.len() as u16);
+        buf.put_slice(&self.signature);
+    }
+
+    pub fn decode(buf: &mut Bytes) -> Result<Self> {
+        if buf.len() < 4 {
+            bail!("CertificateVerify too short");
+        }
+        let hash_algorithm = buf.get_u8();
+        let signature_algorithm = buf.get_u8();
+        let sig_len = buf.get_u16() as usize;
+        if buf.len() < sig_len {
+            bail!("CertificateVerify too short for signature");
+        }
+        let signature = buf.split_to(sig_len).to_vec();
+
+        Ok(Self {
+            hash_algorithm,
+            signature_algorithm,
+            signature,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ClientKeyExchange {
     pub identity_hint: Vec<u8>,
@@ -644,4 +737,22 @@ mod tests {
         assert_eq!(decoded.certificates, message.certificates);
         assert!(encoded.is_empty());
     }
+
+    #[test]
+    fn test_certificate_request_encode_decode() {
+        let request = CertificateRequest {
+            certificate_types: vec![CertificateRequest::ECDSA_SIGN],
+            signature_algorithms: vec![(4, 3)],
+        };
+
+        let mut buf = BytesMut::new();
+        request.encode(&mut buf);
+
+        let mut encoded = buf.freeze();
+        let decoded = CertificateRequest::decode(&mut encoded).unwrap();
+
+        assert_eq!(decoded.certificate_types, request.certificate_types);
+        assert_eq!(decoded.signature_algorithms, request.signature_algorithms);
+        assert!(encoded.is_empty());
+    }
 }
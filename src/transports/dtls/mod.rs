@@ -34,10 +34,12 @@ use x509_parser::prelude::FromDer;
 use x509_parser::public_key::PublicKey as X509PublicKey;
 
 use self::handshake::{
-    CertificateMessage, ClientHello, ClientKeyExchange, Finished, HandshakeMessage, HandshakeType,
-    HelloVerifyRequest, Random, ServerHello, ServerHelloDone, ServerKeyExchange,
+    CertificateMessage, CertificateRequest, CertificateVerify, ClientHello, ClientKeyExchange,
+    Finished, HandshakeMessage, HandshakeType, HelloVerifyRequest, Random, ServerHello,
+    ServerHelloDone, ServerKeyExchange,
 };
-use self::record::{ContentType, DtlsRecord, ProtocolVersion};
+use self::record::{ContentType, DtlsAlert, DtlsRecord, ProtocolVersion};
+use crate::errors::TransportError;
 use crate::transports::ice::conn::IceConn;
 use tracing::{debug, info, trace, warn};
 
@@ -118,6 +120,41 @@ pub(crate) fn verify_server_key_exchange_signature(
         .map_err(|e| anyhow::anyhow!("ServerKeyExchange signature verification failed: {}", e))
 }
 
+/// Check a client's CertificateVerify: an ECDSA/SHA-256 signature over every
+/// handshake message that preceded it.
+pub(crate) fn verify_certificate_verify_signature(
+    certificate_der: &[u8],
+    transcript: &[u8],
+    certificate_verify: &CertificateVerify,
+) -> Result<()> {
+    if (
+        certificate_verify.hash_algorithm,
+        certificate_verify.signature_algorithm,
+    ) != (4, 3)
+    {
+        return Err(anyhow::anyhow!(
+            "Unsupported CertificateVerify algorithm: hash={} signature={}",
+            certificate_verify.hash_algorithm,
+            certificate_verify.signature_algorithm
+        ));
+    }
+    let verifying_key = certificate_public_key(certificate_der)?;
+    let signature = Signature::from_der(&certificate_verify.signature)
+        .map_err(|e| anyhow::anyhow!("Invalid CertificateVerify signature format: {}", e))?;
+    verifying_key
+        .verify(transcript, &signature)
+        .map_err(|e| anyhow::anyhow!("CertificateVerify signature verification failed: {}", e))
+}
+
+fn signing_key(certificate: &Certificate) -> Result<Arc<SigningKey>> {
+    if let Some(k) = &certificate.dtls_signing_key {
+        return Ok(k.clone());
+    }
+    SigningKey::from_pkcs8_pem(&certificate.private_key)
+        .map(Arc::new)
+        .map_err(|e| anyhow::anyhow!("Failed to parse private key: {}", e))
+}
+
 pub fn get_client_hello_extensions() -> Vec<u8> {
     let mut extensions = Vec::new();
 
@@ -182,6 +219,11 @@ struct DtlsInner {
     write_epoch: AtomicU16,
     is_client: bool,
     expected_remote_fingerprint: Option<String>,
+    /// Fatal alert that terminated the association, if the peer sent one.
+    received_alert: Mutex<Option<DtlsAlert>>,
+    /// Why the peer certificate did not match the SDP fingerprint, if it
+    /// failed the handshake.
+    fingerprint_mismatch: Mutex<Option<String>>,
 }
 
 /// Maximum time to wait for the DTLS handshake to complete before giving up.
@@ -262,6 +304,8 @@ impl DtlsTransport {
             write_epoch: AtomicU16::new(0),
             is_client,
             expected_remote_fingerprint,
+            received_alert: Mutex::new(None),
+            fingerprint_mismatch: Mutex::new(None),
         });
 
         let close_tx = Arc::new(tokio::sync::Notify::new());
@@ -301,6 +345,18 @@ impl DtlsTransport {
         self.inner.state_rx.clone()
     }
 
+    /// Fatal alert received from the peer, set when it moved the transport to
+    /// `Failed`.
+    pub fn received_alert(&self) -> Option<DtlsAlert> {
+        *self.inner.received_alert.lock()
+    }
+
+    /// Set when the handshake failed because the peer's certificate did not
+    /// match the expected `a=fingerprint`, or the peer proved no certificate.
+    pub fn fingerprint_mismatch(&self) -> Option<String> {
+        self.inner.fingerprint_mismatch.lock().clone()
+    }
+
     pub fn close(&self) {
         self.close_tx.notify_one();
     }
@@ -385,14 +441,31 @@ impl DtlsTransport {
     }
 
     pub fn export_keying_material(&self, label: &str, len: usize) -> Result<Vec<u8>> {
+        self.export_keying_material_with_context(label, None, len)
+    }
+
+    /// RFC 5705 exporter. A `Some` context (even empty) is mixed into the seed
+    /// with its length prefix, so it yields different output than `None`.
+    pub fn export_keying_material_with_context(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        len: usize,
+    ) -> Result<Vec<u8>> {
         let state = self.inner.state.lock();
         if let DtlsState::Connected(crypto, _) = &*state {
             // RFC 5764: seed = client_random || server_random (in that order).
-            let seed = [
+            let mut seed = [
                 crypto.keys.client_random.as_slice(),
                 crypto.keys.server_random.as_slice(),
             ]
             .concat();
+            if let Some(context) = context {
+                let context_len = u16::try_from(context.len())
+                    .map_err(|_| anyhow::anyhow!("exporter context too long"))?;
+                seed.extend_from_slice(&context_len.to_be_bytes());
+                seed.extend_from_slice(context);
+            }
             prf_sha256(&crypto.keys.master_secret, label.as_bytes(), &seed, len)
         } else {
             Err(anyhow::anyhow!("DTLS not connected"))
@@ -414,10 +487,9 @@ impl DtlsInner {
         if let Some(records) = &ctx.last_flight_records
             && let Err(e) = self.conn.send_dtls_record_batch(records).await
         {
-            let msg = format!("{:?}", e);
-            if msg.contains("No selected socket") || msg.contains("Remote address not set") {
+            if matches!(e, TransportError::NoSocket | TransportError::RemoteNotSet) {
                 debug!("Retransmission skipped — ICE socket unavailable");
-            } else if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
+            } else if let TransportError::SocketError(io_err) = &e {
                 match io_err.kind() {
                     std::io::ErrorKind::HostUnreachable
                     | std::io::ErrorKind::NetworkUnreachable => {
@@ -581,13 +653,19 @@ impl DtlsInner {
             }
             ContentType::Alert => {
                 trace!("Received Alert: {:?}", payload);
-                if payload.len() >= 2 {
-                    let description = payload[1];
-                    if description == 0 {
-                        // CloseNotify
-                        *self.state.lock() = DtlsState::Closed;
-                        let _ = self.state_tx.send(DtlsState::Closed);
-                    }
+                let Some(alert) = DtlsAlert::decode(&payload) else {
+                    return Ok(());
+                };
+                if alert.description == DtlsAlert::CLOSE_NOTIFY {
+                    *self.state.lock() = DtlsState::Closed;
+                    let _ = self.state_tx.send(DtlsState::Closed);
+                } else if alert.is_fatal() {
+                    *self.received_alert.lock() = Some(alert);
+                    *self.state.lock() = DtlsState::Failed;
+                    let _ = self.state_tx.send(DtlsState::Failed);
+                    return Err(anyhow::anyhow!("received DTLS alert: {}", alert));
+                } else {
+                    debug!("Ignoring DTLS alert: {}", alert);
                 }
             }
             _ => {}
@@ -759,13 +837,22 @@ impl DtlsInner {
                 self.handle_server_hello(msg, ctx, is_client)?;
             }
             HandshakeType::Certificate => {
-                self.handle_certificate(msg, ctx).await?;
+                self.handle_certificate(msg, ctx, is_client).await?;
+            }
+            HandshakeType::CertificateRequest if is_client => {
+                CertificateRequest::decode(&mut msg.body.clone())?;
+                ctx.certificate_requested = true;
+            }
+            HandshakeType::CertificateVerify => {
+                self.handle_certificate_verify(msg, raw_msg, ctx, is_client)
+                    .await?;
             }
             HandshakeType::ServerKeyExchange => {
                 self.handle_server_key_exchange(msg, ctx, is_client)?;
             }
             HandshakeType::ServerHelloDone => {
-                self.handle_server_hello_done(ctx, is_client).await?;
+                self.handle_server_hello_done(ctx, certificate, is_client)
+                    .await?;
             }
             _ => {}
         }
@@ -776,6 +863,7 @@ impl DtlsInner {
         &self,
         msg: HandshakeMessage,
         ctx: &mut HandshakeContext,
+        is_client: bool,
     ) -> Result<()> {
         let mut body = msg.body.clone();
         let certificate = CertificateMessage::decode(&mut body)?;
@@ -789,19 +877,19 @@ impl DtlsInner {
 
         // Compare the certificate hash to SDP before accepting any key material from it.
         let actual_fingerprint = fingerprint_from_der(leaf_certificate);
-        if let Some(expected_fingerprint) = &ctx.expected_remote_fingerprint
-            && &actual_fingerprint != expected_fingerprint
+        if let Some(expected_fingerprint) = ctx.expected_remote_fingerprint.clone()
+            && actual_fingerprint != expected_fingerprint
         {
-            *self.state.lock() = DtlsState::Failed;
-            let _ = self.state_tx.send(DtlsState::Failed);
-            return Err(anyhow::anyhow!(
-                "DTLS fingerprint mismatch: expected {}, got {}",
-                expected_fingerprint,
-                actual_fingerprint
-            ));
+            let reason = format!(
+                "expected {}, got {}",
+                expected_fingerprint, actual_fingerprint
+            );
+            return Err(self.fail_fingerprint_check(reason, ctx, is_client).await);
         }
 
         if let Err(e) = certificate_public_key(leaf_certificate) {
+            self.send_alert(DtlsAlert::fatal(DtlsAlert::BAD_CERTIFICATE), ctx, is_client)
+                .await;
             *self.state.lock() = DtlsState::Failed;
             let _ = self.state_tx.send(DtlsState::Failed);
             return Err(e);
@@ -810,6 +898,59 @@ impl DtlsInner {
 
         Ok(())
     }
+    /// Server side: the client signs the transcript with the key of the
+    /// certificate it sent, proving it owns the fingerprinted certificate.
+    async fn handle_certificate_verify(
+        &self,
+        msg: HandshakeMessage,
+        raw_msg: &[u8],
+        ctx: &mut HandshakeContext,
+        is_client: bool,
+    ) -> Result<()> {
+        if is_client || !ctx.certificate_requested {
+            return Ok(());
+        }
+        // The message itself was already appended to the transcript.
+        let transcript_len = ctx.handshake_messages.len().saturating_sub(raw_msg.len());
+        let result = match ctx.peer_certificate.as_deref() {
+            Some(peer_certificate) => {
+                CertificateVerify::decode(&mut msg.body.clone()).and_then(|verify| {
+                    verify_certificate_verify_signature(
+                        peer_certificate,
+                        &ctx.handshake_messages[..transcript_len],
+                        &verify,
+                    )
+                })
+            }
+            None => Err(anyhow::anyhow!(
+                "Received CertificateVerify without a client certificate"
+            )),
+        };
+        if let Err(e) = result {
+            return Err(self
+                .fail_fingerprint_check(e.to_string(), ctx, is_client)
+                .await);
+        }
+        ctx.client_certificate_verified = true;
+        Ok(())
+    }
+
+    /// Reject the peer certificate: tell the peer with a `bad_certificate`
+    /// alert and fail the transport.
+    async fn fail_fingerprint_check(
+        &self,
+        reason: String,
+        ctx: &mut HandshakeContext,
+        is_client: bool,
+    ) -> anyhow::Error {
+        self.send_alert(DtlsAlert::fatal(DtlsAlert::BAD_CERTIFICATE), ctx, is_client)
+            .await;
+        *self.fingerprint_mismatch.lock() = Some(reason.clone());
+        *self.state.lock() = DtlsState::Failed;
+        let _ = self.state_tx.send(DtlsState::Failed);
+        anyhow::anyhow!("DTLS fingerprint mismatch: {}", reason)
+    }
+
     async fn handle_client_hello(
         &self,
         msg: HandshakeMessage,
@@ -825,7 +966,7 @@ impl DtlsInner {
             if let Some(records) = &ctx.last_flight_records
                 && let Err(e) = self.conn.send_dtls_record_batch(records).await
             {
-                if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
+                if let TransportError::SocketError(io_err) = &e {
                     match io_err.kind() {
                         std::io::ErrorKind::HostUnreachable
                         | std::io::ErrorKind::NetworkUnreachable => {
@@ -1009,14 +1150,7 @@ impl DtlsInner {
         params.push(ctx.local_public_key_bytes.len() as u8);
         params.extend_from_slice(&ctx.local_public_key_bytes);
 
-        let signing_key = if let Some(k) = &certificate.dtls_signing_key {
-            k.clone()
-        } else {
-            Arc::new(
-                SigningKey::from_pkcs8_pem(&certificate.private_key)
-                    .map_err(|e| anyhow::anyhow!("Failed to parse private key: {}", e))?,
-            )
-        };
+        let signing_key = signing_key(certificate)?;
         let signature: p256::ecdsa::Signature = signing_key.sign_with_rng(&mut OsRng, &params);
         let signature_bytes = signature.to_der().as_bytes().to_vec();
         // Self-verification
@@ -1057,6 +1191,41 @@ impl DtlsInner {
         )?);
         ctx.message_seq += 1;
 
+        // Request the client certificate so it can be checked against the SDP
+        // fingerprint (RFC 8827 §6.5).
+        if ctx.expected_remote_fingerprint.is_some() {
+            let certificate_request = CertificateRequest {
+                certificate_types: vec![CertificateRequest::ECDSA_SIGN],
+                signature_algorithms: vec![(4, 3)], // ecdsa_secp256r1_sha256
+            };
+
+            let mut body = BytesMut::new();
+            certificate_request.encode(&mut body);
+
+            let handshake_msg = HandshakeMessage {
+                msg_type: HandshakeType::CertificateRequest,
+                total_length: body.len() as u32,
+                message_seq: ctx.message_seq,
+                fragment_offset: 0,
+                fragment_length: body.len() as u32,
+                body: body.freeze(),
+            };
+
+            let mut buf = BytesMut::new();
+            handshake_msg.encode(&mut buf);
+            ctx.handshake_messages.extend_from_slice(&buf);
+
+            flight_records.push(self.build_handshake_record(
+                handshake_msg,
+                ctx.epoch,
+                &mut ctx.sequence_number,
+                None,
+                is_client,
+            )?);
+            ctx.message_seq += 1;
+            ctx.certificate_requested = true;
+        }
+
         // Send ServerHelloDone
         let done_msg = ServerHelloDone {};
         let mut body = BytesMut::new();
@@ -1200,6 +1369,11 @@ impl DtlsInner {
         };
 
         if !is_client {
+            if ctx.certificate_requested && !ctx.client_certificate_verified {
+                let reason = "client did not prove ownership of a certificate".to_string();
+                return Err(self.fail_fingerprint_check(reason, ctx, is_client).await);
+            }
+
             // Verify Client's Finished
             if let Some(keys) = &ctx.session_keys {
                 let expected_verify_data = calculate_verify_data(
@@ -1500,6 +1674,7 @@ impl DtlsInner {
     async fn handle_server_hello_done(
         &self,
         ctx: &mut HandshakeContext,
+        certificate: &Certificate,
         is_client: bool,
     ) -> Result<()> {
         if ctx.session_keys.is_some() {
@@ -1514,6 +1689,40 @@ impl DtlsInner {
             ));
         }
 
+        let mut flight_records: Vec<Vec<u8>> = Vec::new();
+
+        // Send Certificate when the server asked for one
+        if ctx.certificate_requested {
+            let cert_msg = CertificateMessage {
+                certificates: certificate.certificate.clone(),
+            };
+
+            let mut body = BytesMut::new();
+            cert_msg.encode(&mut body);
+
+            let handshake_msg = HandshakeMessage {
+                msg_type: HandshakeType::Certificate,
+                total_length: body.len() as u32,
+                message_seq: ctx.message_seq,
+                fragment_offset: 0,
+                fragment_length: body.len() as u32,
+                body: body.freeze(),
+            };
+
+            let mut buf = BytesMut::new();
+            handshake_msg.encode(&mut buf);
+            ctx.handshake_messages.extend_from_slice(&buf);
+
+            flight_records.push(self.build_handshake_record(
+                handshake_msg,
+                ctx.epoch,
+                &mut ctx.sequence_number,
+                None,
+                is_client,
+            )?);
+            ctx.message_seq += 1;
+        }
+
         // Send ClientKeyExchange
         let client_key_exchange = ClientKeyExchange {
             identity_hint: vec![],
@@ -1536,14 +1745,13 @@ impl DtlsInner {
         handshake_msg.encode(&mut buf);
         ctx.handshake_messages.extend_from_slice(&buf);
 
-        self.send_handshake_message(
+        flight_records.push(self.build_handshake_record(
             handshake_msg,
             ctx.epoch,
             &mut ctx.sequence_number,
             None,
             is_client,
-        )
-        .await?;
+        )?);
         ctx.message_seq += 1;
 
         // Compute shared secret
@@ -1606,7 +1814,42 @@ impl DtlsInner {
         ctx.session_crypto = Some(create_session_crypto(keys.clone())?);
         ctx.session_keys = Some(keys);
 
-        let mut flight_records: Vec<Vec<u8>> = Vec::new();
+        // Send CertificateVerify, signing every handshake message so far
+        if ctx.certificate_requested {
+            let signing_key = signing_key(certificate)?;
+            let signature: Signature =
+                signing_key.sign_with_rng(&mut OsRng, &ctx.handshake_messages);
+            let certificate_verify = CertificateVerify {
+                hash_algorithm: 4,      // sha256
+                signature_algorithm: 3, // ecdsa
+                signature: signature.to_der().as_bytes().to_vec(),
+            };
+
+            let mut body = BytesMut::new();
+            certificate_verify.encode(&mut body);
+
+            let handshake_msg = HandshakeMessage {
+                msg_type: HandshakeType::CertificateVerify,
+                total_length: body.len() as u32,
+                message_seq: ctx.message_seq,
+                fragment_offset: 0,
+                fragment_length: body.len() as u32,
+                body: body.freeze(),
+            };
+
+            let mut buf = BytesMut::new();
+            handshake_msg.encode(&mut buf);
+            ctx.handshake_messages.extend_from_slice(&buf);
+
+            flight_records.push(self.build_handshake_record(
+                handshake_msg,
+                ctx.epoch,
+                &mut ctx.sequence_number,
+                None,
+                is_client,
+            )?);
+            ctx.message_seq += 1;
+        }
 
         // Send ChangeCipherSpec
         let record = DtlsRecord {
@@ -1762,34 +2005,8 @@ impl DtlsInner {
 
             tokio::select! {
                 _ = close_rx.notified() => {
-                    // Send CloseNotify
-                    if let Some(keys) = &ctx.session_keys {
-                        let alert = vec![1, 0]; // Level: Warning (1), Description: CloseNotify (0)
-                        let (key, iv) = if is_client {
-                            (&keys.client_write_key, &keys.client_write_iv)
-                        } else {
-                            (&keys.server_write_key, &keys.server_write_iv)
-                        };
-                        let full_seq = ((ctx.epoch as u64) << 48) | ctx.sequence_number;
-                        if let Ok(encrypted) = encrypt_record(
-                            ContentType::Alert,
-                            ProtocolVersion::DTLS_1_2,
-                            full_seq,
-                            &alert,
-                            key,
-                            iv
-                        ) {
-                            let record = DtlsRecord {
-                                content_type: ContentType::Alert,
-                                version: ProtocolVersion::DTLS_1_2,
-                                epoch: ctx.epoch,
-                                sequence_number: ctx.sequence_number,
-                                payload: Bytes::from(encrypted),
-                            };
-                            let mut buf = BytesMut::new();
-                            record.encode(&mut buf);
-                            let _ = self.conn.send(&buf).await;
-                        }
+                    if ctx.session_keys.is_some() {
+                        self.send_alert(DtlsAlert::close_notify(), &mut ctx, is_client).await;
                     }
                     return Ok(());
                 }
@@ -1882,6 +2099,51 @@ impl DtlsInner {
         Ok(buf.to_vec())
     }
 
+    /// Send `alert`, encrypted once keys for the current write epoch exist.
+    /// Delivery is best effort: the caller tears the association down anyway.
+    async fn send_alert(&self, alert: DtlsAlert, ctx: &mut HandshakeContext, is_client: bool) {
+        let payload = alert.encode();
+        let payload = match (&ctx.session_keys, ctx.epoch) {
+            (Some(keys), epoch) if epoch > 0 => {
+                let (key, iv) = if is_client {
+                    (&keys.client_write_key, &keys.client_write_iv)
+                } else {
+                    (&keys.server_write_key, &keys.server_write_iv)
+                };
+                let full_seq = ((epoch as u64) << 48) | ctx.sequence_number;
+                match encrypt_record(
+                    ContentType::Alert,
+                    ProtocolVersion::DTLS_1_2,
+                    full_seq,
+                    &payload,
+                    key,
+                    iv,
+                ) {
+                    Ok(encrypted) => Bytes::from(encrypted),
+                    Err(e) => {
+                        debug!("Failed to encrypt DTLS alert: {}", e);
+                        return;
+                    }
+                }
+            }
+            _ => Bytes::copy_from_slice(&payload),
+        };
+
+        let record = DtlsRecord {
+            content_type: ContentType::Alert,
+            version: ProtocolVersion::DTLS_1_2,
+            epoch: ctx.epoch,
+            sequence_number: ctx.sequence_number,
+            payload,
+        };
+        ctx.sequence_number += 1;
+        let mut buf = BytesMut::new();
+        record.encode(&mut buf);
+        if let Err(e) = self.conn.send(&buf).await {
+            debug!("Failed to send DTLS alert {}: {}", alert, e);
+        }
+    }
+
     async fn send_handshake_message(
         &self,
         msg: HandshakeMessage,
@@ -1893,7 +2155,7 @@ impl DtlsInner {
         let buf =
             self.build_handshake_record(msg, epoch, sequence_number, session_keys, is_client)?;
         if let Err(e) = self.conn.send(&buf).await {
-            if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
+            if let TransportError::SocketError(io_err) = &e {
                 match io_err.kind() {
                     std::io::ErrorKind::HostUnreachable
                     | std::io::ErrorKind::NetworkUnreachable => {
@@ -1910,7 +2172,7 @@ impl DtlsInner {
             } else {
                 warn!("Failed to send DTLS record: {}", e);
             }
-            return Err(e);
+            return Err(e.into());
         }
 
         Ok(buf.to_vec())
@@ -1938,6 +2200,35 @@ impl PacketReceiver for DtlsTransport {
     }
 }
 
+impl DtlsState {
+    /// The `RTCDtlsTransportState` name reported in stats.
+    pub fn stats_name(&self) -> &'static str {
+        match self {
+            DtlsState::New => "new",
+            DtlsState::Handshaking => "connecting",
+            DtlsState::Connected(..) => "connected",
+            DtlsState::Failed => "failed",
+            DtlsState::Closed => "closed",
+        }
+    }
+
+    /// The `transport` stats entry for a transport in this state. Byte
+    /// counts and the selected pair belong to the ICE side and are added by
+    /// the peer connection.
+    pub(crate) fn stats_entry(&self) -> crate::stats::StatsEntry {
+        use crate::stats::{StatsEntry, StatsId, StatsKind};
+        StatsEntry::new(StatsId::new("transport"), StatsKind::Transport)
+            .with_value("dtlsState", serde_json::json!(self.stats_name()))
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::stats::StatsProvider for DtlsTransport {
+    async fn collect(&self) -> crate::errors::RtcResult<Vec<crate::stats::StatsEntry>> {
+        Ok(vec![self.get_state().stats_entry()])
+    }
+}
+
 fn prf_sha256(secret: &[u8], label: &[u8], seed: &[u8], output_length: usize) -> Result<Vec<u8>> {
     let mut output = Vec::new();
     let mut real_seed = Vec::new();
@@ -2173,6 +2464,9 @@ struct HandshakeContext {
     srtp_profile: Option<u16>,
     expected_remote_fingerprint: Option<String>,
     server_key_exchange_verified: bool,
+    /// Client: the server sent a CertificateRequest. Server: we sent one.
+    certificate_requested: bool,
+    client_certificate_verified: bool,
 }
 
 impl HandshakeContext {
@@ -2205,6 +2499,8 @@ impl HandshakeContext {
             srtp_profile: None,
             expected_remote_fingerprint,
             server_key_exchange_verified: false,
+            certificate_requested: false,
+            client_certificate_verified: false,
         }
     }
 }
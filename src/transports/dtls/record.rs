@@ -42,6 +42,94 @@ impl ProtocolVersion {
     };
 }
 
+/// Alert message carried in an `Alert` record (RFC 5246 §7.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DtlsAlert {
+    pub level: u8,
+    pub description: u8,
+}
+
+impl DtlsAlert {
+    pub const LEVEL_WARNING: u8 = 1;
+    pub const LEVEL_FATAL: u8 = 2;
+
+    pub const CLOSE_NOTIFY: u8 = 0;
+    pub const HANDSHAKE_FAILURE: u8 = 40;
+    pub const BAD_CERTIFICATE: u8 = 42;
+
+    pub fn fatal(description: u8) -> Self {
+        Self {
+            level: Self::LEVEL_FATAL,
+            description,
+        }
+    }
+
+    pub fn close_notify() -> Self {
+        Self {
+            level: Self::LEVEL_WARNING,
+            description: Self::CLOSE_NOTIFY,
+        }
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        match payload {
+            [level, description, ..] => Some(Self {
+                level: *level,
+                description: *description,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; 2] {
+        [self.level, self.description]
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        self.level == Self::LEVEL_FATAL
+    }
+
+    pub fn description_name(&self) -> &'static str {
+        match self.description {
+            0 => "close_notify",
+            10 => "unexpected_message",
+            20 => "bad_record_mac",
+            22 => "record_overflow",
+            40 => "handshake_failure",
+            42 => "bad_certificate",
+            43 => "unsupported_certificate",
+            44 => "certificate_revoked",
+            45 => "certificate_expired",
+            46 => "certificate_unknown",
+            47 => "illegal_parameter",
+            48 => "unknown_ca",
+            49 => "access_denied",
+            50 => "decode_error",
+            51 => "decrypt_error",
+            70 => "protocol_version",
+            71 => "insufficient_security",
+            80 => "internal_error",
+            90 => "user_canceled",
+            100 => "no_renegotiation",
+            110 => "unsupported_extension",
+            _ => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for DtlsAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = if self.is_fatal() { "fatal" } else { "warning" };
+        write!(
+            f,
+            "{} {} ({})",
+            level,
+            self.description_name(),
+            self.description
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DtlsRecord {
     pub content_type: ContentType,
@@ -133,4 +221,12 @@ mod tests {
         assert_eq!(decoded.sequence_number, record.sequence_number);
         assert_eq!(decoded.payload, record.payload);
     }
+
+    #[test]
+    fn test_dtls_alert_decode_and_display() {
+        let alert = DtlsAlert::decode(&[2, 42]).unwrap();
+        assert_eq!(alert, DtlsAlert::fatal(DtlsAlert::BAD_CERTIFICATE));
+        assert_eq!(alert.to_string(), "fatal bad_certificate (42)");
+        assert!(DtlsAlert::decode(&[2]).is_none());
+    }
 }
@@ -0,0 +1,76 @@
+//! Reusable storage for packets handed from the ICE read loops to the
+//! DTLS/RTP receivers.
+//!
+//! Receivers take ownership of each packet as [`Bytes`], so the read loop
+//! cannot simply reuse its socket buffer. Instead every packet is carved out
+//! of one larger [`BytesMut`] region. Once all packets from a region have been
+//! dropped, `reserve` reclaims the region in place, so at a steady packet rate
+//! the loop allocates about once per region instead of once per packet.
+
+use bytes::{Bytes, BytesMut};
+
+/// Per-read-loop packet buffer pool. A `size` of 0 disables pooling and
+/// copies every packet into its own allocation.
+#[derive(Debug)]
+pub struct ReceiveBufferPool {
+    buf: BytesMut,
+    size: usize,
+}
+
+impl ReceiveBufferPool {
+    pub fn new(size: usize) -> Self {
+        Self {
+            buf: BytesMut::with_capacity(size),
+            size,
+        }
+    }
+
+    /// Copy `packet` into pooled storage.
+    ///
+    /// A packet that is held on to keeps its whole region alive, so
+    /// long-lived packets pin up to `size` bytes each.
+    pub fn copy(&mut self, packet: &[u8]) -> Bytes {
+        if self.size == 0 {
+            return Bytes::copy_from_slice(packet);
+        }
+        if self.buf.capacity() < packet.len() {
+            self.buf.reserve(self.size.max(packet.len()));
+        }
+        self.buf.extend_from_slice(packet);
+        self.buf.split().freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retained_packets_keep_their_contents() {
+        let mut pool = ReceiveBufferPool::new(4096);
+        let kept: Vec<Bytes> = (0..20u8).map(|i| pool.copy(&[i; 1200])).collect();
+        for (i, packet) in kept.iter().enumerate() {
+            assert_eq!(packet.len(), 1200);
+            assert!(packet.iter().all(|&b| b == i as u8));
+        }
+        // Larger than the pool size.
+        assert_eq!(pool.copy(&[7; 5000]).len(), 5000);
+    }
+
+    #[test]
+    fn drained_region_is_reused() {
+        let mut pool = ReceiveBufferPool::new(4096);
+        let first = pool.copy(&[1; 1000]);
+        let region = first.as_ptr();
+        drop(first);
+        for _ in 0..10 {
+            drop(pool.copy(&[2; 1000]));
+        }
+        let again = pool.copy(&[3; 1000]);
+        let offset = (again.as_ptr() as usize).wrapping_sub(region as usize);
+        assert!(
+            offset + again.len() <= 4096,
+            "packet left the original region"
+        );
+    }
+}
@@ -1,8 +1,7 @@
-use super::{IceSocketWrapper, should_drop_packet};
-use crate::errors::RtcResult;
+use super::{IceSocketWrapper, LossInjection, should_drop_packet};
+use crate::errors::{RtcResult, TransportError, TransportResult};
 use crate::stats::{StatsEntry, StatsId, StatsKind, StatsProvider};
 use crate::transports::PacketReceiver;
-use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
 use parking_lot::{Mutex, RwLock};
@@ -61,6 +60,9 @@ struct RtpProbationState {
     max_packets: u8,
 }
 
+/// Invoked with `(old, new)` whenever the RTP remote address changes.
+pub type RemoteAddrChangeHandler = Box<dyn Fn(SocketAddr, SocketAddr) + Send + Sync>;
+
 pub struct IceConn {
     pub socket_rx: watch::Receiver<Option<IceSocketWrapper>>,
     rtcp_socket_rx: watch::Receiver<Option<IceSocketWrapper>>,
@@ -71,6 +73,13 @@ pub struct IceConn {
     pub latch_on_rtp: AtomicBool,
     pub rtp_latched: AtomicBool,
     pub rtcp_latched: AtomicBool,
+    /// When set, packets from a new source no longer move `remote_addr`
+    /// (first-packet fill, inbound TCP latching, RTP latching). Signaling and
+    /// ICE pair updates still apply. Engaged once the selected pair is
+    /// nominated; cleared with [`unlock_remote`](Self::unlock_remote) to
+    /// allow mobility.
+    pub lock_remote: AtomicBool,
+    remote_addr_change_handler: RwLock<Option<RemoteAddrChangeHandler>>,
     pub expected_ssrc: AtomicU32,
     pub rtp_rx_count: AtomicU64,
     pub label: Option<String>,
@@ -85,6 +94,7 @@ pub struct IceConn {
     /// Maximum packets to observe during probation.  `0` means "no probation"
     /// — first SSRC-matching RTP latches immediately (legacy behaviour).
     probation_max_packets: AtomicU8,
+    loss_injection: LossInjection,
 }
 
 impl IceConn {
@@ -113,6 +123,8 @@ impl IceConn {
             latch_on_rtp: AtomicBool::new(false),
             rtp_latched: AtomicBool::new(false),
             rtcp_latched: AtomicBool::new(false),
+            lock_remote: AtomicBool::new(false),
+            remote_addr_change_handler: RwLock::new(None),
             expected_ssrc: AtomicU32::new(0),
             rtp_rx_count: AtomicU64::new(0),
             label,
@@ -122,6 +134,7 @@ impl IceConn {
             tx_bytes: AtomicU64::new(0),
             probation: Mutex::new(None),
             probation_max_packets: AtomicU8::new(probation_max_packets.unwrap_or(0)),
+            loss_injection: LossInjection::default(),
         })
     }
 
@@ -186,7 +199,32 @@ impl IceConn {
             return;
         }
 
-        *self.remote_addr.write() = addr;
+        self.update_remote_addr(addr);
+    }
+
+    /// Stop packets from a new source changing the remote address.
+    pub fn lock_remote(&self) {
+        self.lock_remote.store(true, Ordering::Relaxed);
+    }
+
+    /// Allow packets from a new source to change the remote address again
+    /// (e.g. for mobility across a NAT rebinding).
+    pub fn unlock_remote(&self) {
+        self.lock_remote.store(false, Ordering::Relaxed);
+    }
+
+    /// Register a handler called whenever the remote address changes.
+    pub fn on_remote_addr_change(&self, handler: RemoteAddrChangeHandler) {
+        *self.remote_addr_change_handler.write() = Some(handler);
+    }
+
+    fn update_remote_addr(&self, addr: SocketAddr) {
+        let old = std::mem::replace(&mut *self.remote_addr.write(), addr);
+        if old != addr
+            && let Some(handler) = self.remote_addr_change_handler.read().as_ref()
+        {
+            handler(old, addr);
+        }
     }
 
     /// Reset latching state (called on re-INVITE so a new source can be
@@ -214,11 +252,22 @@ impl IceConn {
         *self.rtp_receiver.write() = Some(Arc::downgrade(&receiver));
     }
 
+    /// Randomly drop `ratio` (0.0–1.0) of the packets sent on this
+    /// connection, as if lost on the network.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn set_loss_injection(&self, ratio: f64) {
+        self.loss_injection.set_ratio(ratio);
+    }
+
+    fn should_drop(&self) -> bool {
+        should_drop_packet() || self.loss_injection.should_drop()
+    }
+
     /// Non-blocking variant of `send`. Skips the `writable().await` parking
     /// and simply returns `Err` when the kernel socket buffer is full. Used by
     /// the RTP bridge fast-path so the receive loop never suspends on send.
-    pub fn try_send(&self, buf: &[u8]) -> Result<usize> {
-        if should_drop_packet() {
+    pub fn try_send(&self, buf: &[u8]) -> TransportResult<usize> {
+        if self.should_drop() {
             return Ok(buf.len());
         }
         let socket = self.socket_rx.borrow().clone();
@@ -228,31 +277,28 @@ impl IceConn {
             let socket = socket_rx.borrow_and_update().clone();
             let Some(socket) = socket else {
                 tracing::debug!("IceConn: try_send failed - no selected socket");
-                return Err(anyhow::anyhow!("No selected socket"));
+                return Err(TransportError::NoSocket);
             };
-            return Self::do_try_send(socket, buf, &self.remote_addr);
+            return self.do_try_send(socket, buf);
         };
-        Self::do_try_send(socket, buf, &self.remote_addr)
+        self.do_try_send(socket, buf)
     }
 
-    fn do_try_send(
-        socket: IceSocketWrapper,
-        buf: &[u8],
-        remote_addr: &parking_lot::RwLock<SocketAddr>,
-    ) -> Result<usize> {
-        let remote = *remote_addr.read();
+    fn do_try_send(&self, socket: IceSocketWrapper, buf: &[u8]) -> TransportResult<usize> {
+        let remote = *self.remote_addr.read();
         if remote.port() == 0 {
-            return Err(anyhow::anyhow!("Remote address not set"));
+            return Err(TransportError::RemoteNotSet);
         }
         let n = socket.try_send_to(buf, remote)?;
-        // Note: tx_packets/tx_bytes not updated here (fast-path opt).
-        // These counters are informational and the write-order atomic
-        // would add unnecessary cost on the hot path.
+        // Relaxed adds keep the bridge fast-path visible in the
+        // connection-level totals at negligible cost.
+        self.tx_packets.fetch_add(1, Ordering::Relaxed);
+        self.tx_bytes.fetch_add(n as u64, Ordering::Relaxed);
         Ok(n)
     }
 
-    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
-        if should_drop_packet() {
+    pub async fn send(&self, buf: &[u8]) -> TransportResult<usize> {
+        if self.should_drop() {
             return Ok(buf.len());
         }
         let socket_rx = self.socket_rx.clone();
@@ -261,9 +307,9 @@ impl IceConn {
         if let Some(socket) = socket_opt {
             let remote = *self.remote_addr.read();
             if remote.port() == 0 {
-                return Err(anyhow::anyhow!("Remote address not set"));
+                return Err(TransportError::RemoteNotSet);
             }
-            let n = socket.send_to(buf, remote).await?;
+            let n = socket.send_to(buf, remote).await.map_err(socket_error)?;
             self.tx_packets.fetch_add(1, Ordering::Relaxed);
             self.tx_bytes.fetch_add(n as u64, Ordering::Relaxed);
             Ok(n)
@@ -274,32 +320,32 @@ impl IceConn {
             if let Some(socket) = socket_opt {
                 let remote = *self.remote_addr.read();
                 if remote.port() == 0 {
-                    return Err(anyhow::anyhow!("Remote address not set"));
+                    return Err(TransportError::RemoteNotSet);
                 }
-                let n = socket.send_to(buf, remote).await?;
+                let n = socket.send_to(buf, remote).await.map_err(socket_error)?;
                 self.tx_packets.fetch_add(1, Ordering::Relaxed);
                 self.tx_bytes.fetch_add(n as u64, Ordering::Relaxed);
                 Ok(n)
             } else {
                 tracing::debug!("IceConn: send failed - no selected socket");
-                Err(anyhow::anyhow!("No selected socket"))
+                Err(TransportError::NoSocket)
             }
         }
     }
 
     /// Send multiple DTLS records. On TCP, each record is RFC 4571-framed and all
     /// frames are written in one syscall (avoids Chrome seeing a partial flight).
-    pub async fn send_dtls_record_batch(&self, records: &[Vec<u8>]) -> Result<usize> {
+    pub async fn send_dtls_record_batch(&self, records: &[Vec<u8>]) -> TransportResult<usize> {
         if records.is_empty() {
             return Ok(0);
         }
-        if should_drop_packet() {
+        if self.should_drop() {
             return Ok(records.iter().map(|r| r.len()).sum());
         }
 
         let remote = *self.remote_addr.read();
         if remote.port() == 0 {
-            return Err(anyhow::anyhow!("Remote address not set"));
+            return Err(TransportError::RemoteNotSet);
         }
 
         let socket_rx = self.socket_rx.clone();
@@ -311,7 +357,7 @@ impl IceConn {
 
         let Some(socket) = socket_opt else {
             tracing::debug!("IceConn: send_dtls_record_batch failed - no selected socket");
-            return Err(anyhow::anyhow!("No selected socket"));
+            return Err(TransportError::NoSocket);
         };
 
         let total_payload: usize = records.iter().map(|r| r.len()).sum();
@@ -323,12 +369,17 @@ impl IceConn {
                 let mut framed = Vec::new();
                 for record in records {
                     if record.len() > 0xFFFF {
-                        return Err(anyhow::anyhow!("DTLS record too large for TCP framing"));
+                        return Err(TransportError::SocketError(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "DTLS record too large for TCP framing",
+                        )));
                     }
                     framed.extend_from_slice(&(record.len() as u16).to_be_bytes());
                     framed.extend_from_slice(record);
                 }
-                super::tcp_write_all(write, &framed).await?;
+                super::tcp_write_all(write, &framed)
+                    .await
+                    .map_err(socket_error)?;
                 self.tx_bytes
                     .fetch_add(framed.len() as u64, Ordering::Relaxed);
                 Ok(total_payload)
@@ -343,7 +394,7 @@ impl IceConn {
         }
     }
 
-    pub async fn send_rtcp(&self, buf: &[u8]) -> Result<usize> {
+    pub async fn send_rtcp(&self, buf: &[u8]) -> TransportResult<usize> {
         let rtcp_addr = *self.remote_rtcp_addr.read();
         let remote = if let Some(rtcp_addr) = rtcp_addr {
             rtcp_addr
@@ -352,7 +403,7 @@ impl IceConn {
         };
 
         if remote.port() == 0 {
-            return Err(anyhow::anyhow!("Remote address not set"));
+            return Err(TransportError::RemoteNotSet);
         }
 
         let mut socket_rx = if rtcp_addr.is_some() {
@@ -374,17 +425,26 @@ impl IceConn {
         }
 
         if let Some(socket) = socket_opt {
-            let n = socket.send_to(buf, remote).await?;
+            let n = socket.send_to(buf, remote).await.map_err(socket_error)?;
             self.tx_packets.fetch_add(1, Ordering::Relaxed);
             self.tx_bytes.fetch_add(n as u64, Ordering::Relaxed);
             Ok(n)
         } else {
             tracing::debug!("IceConn: send_rtcp failed - no selected socket");
-            Err(anyhow::anyhow!("No selected socket"))
+            Err(TransportError::NoSocket)
         }
     }
 }
 
+/// Socket-level failures surface from [`IceSocketWrapper`] as `anyhow`;
+/// recover the underlying `io::Error` when there is one.
+fn socket_error(err: anyhow::Error) -> TransportError {
+    match err.downcast::<std::io::Error>() {
+        Ok(io) => io.into(),
+        Err(err) => TransportError::SocketError(std::io::Error::other(err)),
+    }
+}
+
 #[async_trait]
 impl PacketReceiver for IceConn {
     async fn receive(&self, packet: Bytes, addr: SocketAddr, marshal_buf: &mut Vec<u8>) {
@@ -397,6 +457,14 @@ impl PacketReceiver for IceConn {
             .fetch_add(packet.len() as u64, Ordering::Relaxed);
 
         let first_byte = packet[0];
+        // RFC 7983: 0..=3 is STUN. Consent checks are answered by the ICE
+        // agent before packets are handed to this demuxer; anything that slips
+        // through must neither move the remote address nor reach DTLS/RTP.
+        if first_byte < 4 {
+            trace!("IceConn: ignoring STUN packet from {}", addr);
+            return;
+        }
+
         // Scope for read lock
         let current_remote = *self.remote_addr.read();
 
@@ -410,8 +478,11 @@ impl PacketReceiver for IceConn {
                 Some(IceSocketWrapper::TcpStream(_, _, _))
             )
         };
-        if current_remote.port() == 0 || (socket_is_inbound_tcp && current_remote != addr) {
-            *self.remote_addr.write() = addr;
+        let locked = self.lock_remote.load(Ordering::Relaxed);
+        if !locked
+            && (current_remote.port() == 0 || (socket_is_inbound_tcp && current_remote != addr))
+        {
+            self.update_remote_addr(addr);
         } else if addr != current_remote {
             // Note: We no longer automatically switch the remote address just by receiving
             // a packet from a new source (e.g. DTLS). This prevents "path flapping"
@@ -445,7 +516,7 @@ impl PacketReceiver for IceConn {
             // RTP / RTCP
             let is_rtcp = packet.len() >= 2 && (200..=211).contains(&packet[1]);
 
-            if self.latch_on_rtp.load(Ordering::Relaxed) {
+            if self.latch_on_rtp.load(Ordering::Relaxed) && !locked {
                 if is_rtcp {
                     // RTCP may teach the RTCP destination in non-mux mode, but it must
                     // never override the RTP remote address.
@@ -507,7 +578,7 @@ impl PacketReceiver for IceConn {
                             }
 
                             if addr != current_remote {
-                                *self.remote_addr.write() = addr;
+                                self.update_remote_addr(addr);
                             }
 
                             let total = prob.total_packets;
@@ -556,7 +627,7 @@ impl PacketReceiver for IceConn {
                                 drop(probation_guard);
 
                                 if win_addr != current_remote {
-                                    *self.remote_addr.write() = win_addr;
+                                    self.update_remote_addr(win_addr);
                                 }
                                 self.rtp_latched.store(true, Ordering::Relaxed);
                                 trace!(
@@ -570,7 +641,7 @@ impl PacketReceiver for IceConn {
                             // (legacy path for callers that never called
                             // `enable_latch_on_rtp`).
                             if addr != current_remote {
-                                *self.remote_addr.write() = addr;
+                                self.update_remote_addr(addr);
                             }
                             self.rtp_latched.store(true, Ordering::Relaxed);
                             trace!(
@@ -663,6 +734,45 @@ mod tests {
         assert_eq!(&buf[..len], b"hello");
     }
 
+    #[tokio::test]
+    async fn test_ice_conn_send_without_remote_returns_remote_not_set() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_tx, rx) = watch::channel(Some(IceSocketWrapper::Udp(Arc::new(socket))));
+        let conn = IceConn::new(rx, SocketAddr::from(([0, 0, 0, 0], 0)), None);
+
+        assert!(matches!(
+            conn.send(b"rtp").await,
+            Err(TransportError::RemoteNotSet)
+        ));
+        assert!(matches!(
+            conn.try_send(b"rtp"),
+            Err(TransportError::RemoteNotSet)
+        ));
+        assert!(matches!(
+            conn.send_rtcp(b"rtcp").await,
+            Err(TransportError::RemoteNotSet)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ice_conn_send_keeps_socket_error_kind() {
+        // Broadcast without SO_BROADCAST is refused by the OS (EACCES).
+        let socket = UdpSocket::bind("0.0.0.0:0").await.unwrap();
+        let (_tx, rx) = watch::channel(Some(IceSocketWrapper::Udp(Arc::new(socket))));
+        let conn = IceConn::new(rx, SocketAddr::from(([255, 255, 255, 255], 9)), None);
+
+        for result in [conn.send(b"rtp").await, conn.try_send(b"rtp")] {
+            match result {
+                Err(TransportError::SocketError(err)) => {
+                    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+                    assert!(err.to_string().contains("255.255.255.255:9"), "{err}");
+                    assert!(std::error::Error::source(&err).is_some());
+                }
+                other => panic!("expected SocketError, got {other:?}"),
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_ice_conn_send_rtcp_no_mux() {
         let rtp_socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
@@ -705,6 +815,24 @@ mod tests {
         async fn receive(&self, _packet: Bytes, _addr: SocketAddr, _buf: &mut Vec<u8>) {}
     }
 
+    #[tokio::test]
+    async fn test_ice_conn_ignores_stun_packets() {
+        let (_tx, rx) = watch::channel(None);
+        let conn = IceConn::new(rx, SocketAddr::from(([0, 0, 0, 0], 0)), None);
+        conn.enable_latch_on_rtp();
+
+        let stun_src = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5000);
+        let request = crate::transports::ice::stun::StunMessage::binding_request([7; 12], None)
+            .encode(None, true)
+            .unwrap();
+        let mut marshal_buf = Vec::new();
+        conn.receive(Bytes::from(request), stun_src, &mut marshal_buf)
+            .await;
+
+        assert_eq!(conn.remote_addr.read().port(), 0);
+        assert_eq!(conn.rtp_rx_count.load(Ordering::Relaxed), 0);
+    }
+
     #[tokio::test]
     async fn test_ice_conn_latches_remote_addr_on_rtp() {
         let (_tx, rx) = watch::channel(None);
@@ -727,6 +855,40 @@ mod tests {
         assert_eq!(*conn.remote_addr.read(), latched_addr);
     }
 
+    #[tokio::test]
+    async fn test_lock_remote_blocks_address_change_until_unlocked() {
+        let (_tx, rx) = watch::channel(None);
+        let initial_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 4000);
+        let new_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5000);
+        let conn = IceConn::new(rx, initial_addr, None);
+        conn.enable_latch_on_rtp();
+        conn.set_rtp_receiver(Arc::new(NoopReceiver));
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let changes_clone = changes.clone();
+        conn.on_remote_addr_change(Box::new(move |old, new| {
+            changes_clone.lock().push((old, new));
+        }));
+
+        let pkt = Bytes::from_static(&[
+            0x80, 0x80, // V=2, M=1 (marker set)
+            0x00, 0x01, // seq=1
+            0x00, 0x00, 0x00, 0x01, // ts=1
+            0x00, 0x00, 0x00, 0x01, // ssrc=1
+        ]);
+        let mut marshal_buf = Vec::new();
+
+        conn.lock_remote();
+        conn.receive(pkt.clone(), new_addr, &mut marshal_buf).await;
+        assert_eq!(*conn.remote_addr.read(), initial_addr);
+        assert!(changes.lock().is_empty());
+
+        conn.unlock_remote();
+        conn.receive(pkt, new_addr, &mut marshal_buf).await;
+        assert_eq!(*conn.remote_addr.read(), new_addr);
+        assert_eq!(*changes.lock(), vec![(initial_addr, new_addr)]);
+    }
+
     #[tokio::test]
     async fn test_rtcp_does_not_override_rtp_remote_addr() {
         let (_tx, rx) = watch::channel(None);
@@ -1,3 +1,4 @@
+pub mod buffer_pool;
 pub mod conn;
 pub mod shared_tcp;
 pub mod shared_udp;
@@ -13,8 +14,10 @@ pub use upnp::{
     UpnpPortMapper,
 };
 
-use crate::config::{BufferDropStrategy, IceServer, IceTransportPolicy, RtcConfiguration};
-use crate::transports::ice::turn::{TurnClient, TurnCredentials};
+use crate::config::{
+    BufferDropStrategy, CandidateTypePreferences, IceServer, IceTransportPolicy, RtcConfiguration,
+};
+use crate::transports::ice::turn::{TURN_REFRESH_INTERVAL, TurnClient, TurnCredentials};
 use crate::transports::{PacketReceiver, get_local_ip};
 use bytes::Bytes;
 use futures::future::BoxFuture;
@@ -33,7 +36,8 @@ use tokio::sync::{Mutex, broadcast, mpsc, oneshot, watch};
 use tokio::time::timeout;
 use tracing::{debug, info, instrument, trace};
 
-#[cfg(any(test, feature = "simulator"))]
+use self::buffer_pool::ReceiveBufferPool;
+#[cfg(any(test, feature = "simulator", feature = "testing"))]
 use self::stun::random_u32;
 use self::stun::{
     StunAttribute, StunClass, StunDecoded, StunMessage, StunMethod, random_bytes, random_u64,
@@ -72,6 +76,35 @@ pub(crate) fn should_drop_packet() -> bool {
     }
 }
 
+/// Random packet loss injected by a single sender or connection, for
+/// exercising jitter buffers and NACK under controlled loss. Only settable
+/// with the `testing` feature; otherwise it never drops.
+#[derive(Debug, Default)]
+pub(crate) struct LossInjection {
+    /// Drop probability in units of 0.01%.
+    #[cfg(any(test, feature = "testing"))]
+    rate: AtomicU32,
+}
+
+impl LossInjection {
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn set_ratio(&self, ratio: f64) {
+        let rate = (ratio.clamp(0.0, 1.0) * 10000.0) as u32;
+        self.rate.store(rate, Ordering::Relaxed);
+    }
+
+    pub(crate) fn should_drop(&self) -> bool {
+        #[cfg(not(any(test, feature = "testing")))]
+        return false;
+
+        #[cfg(any(test, feature = "testing"))]
+        {
+            let rate = self.rate.load(Ordering::Relaxed);
+            rate != 0 && random_u32() % 10000 < rate
+        }
+    }
+}
+
 /// Statistics for monitoring buffer behavior
 #[derive(Debug)]
 struct BufferStats {
@@ -97,7 +130,9 @@ impl Default for BufferStats {
 #[derive(Debug)]
 enum IceCommand {
     StartGathering,
+    CancelGathering,
     RunChecks,
+    Restart,
 }
 
 #[derive(Debug, Clone)]
@@ -113,6 +148,8 @@ pub(crate) struct IceTransportInner {
     selected_pair: parking_lot::Mutex<Option<IceCandidatePair>>,
     local_candidates: Mutex<Vec<IceCandidate>>,
     remote_candidates: parking_lot::Mutex<Vec<IceCandidate>>,
+    /// The peer signalled end-of-candidates for the current generation.
+    remote_candidates_complete: std::sync::atomic::AtomicBool,
     gather_state: parking_lot::Mutex<IceGathererState>,
     config: RtcConfiguration,
     gatherer: IceGatherer,
@@ -132,6 +169,9 @@ pub(crate) struct IceTransportInner {
     selected_pair_notifier: watch::Sender<Option<IceCandidatePair>>,
     _selected_pair_rx_keeper: watch::Receiver<Option<IceCandidatePair>>,
     last_received: parking_lot::Mutex<Instant>,
+    checking_started: parking_lot::Mutex<Option<Instant>>,
+    /// Outcome of every connectivity check, keyed by (local, remote) address.
+    pair_checks: parking_lot::Mutex<HashMap<(SocketAddr, SocketAddr), PairCheck>>,
     candidate_tx: broadcast::Sender<IceCandidate>,
     cmd_tx: mpsc::UnboundedSender<IceCommand>,
     checking_pairs: Mutex<std::collections::HashSet<(SocketAddr, SocketAddr)>>,
@@ -144,6 +184,16 @@ pub(crate) struct IceTransportInner {
     /// timer tick skips when a previous refresh is still in flight instead of
     /// cancelling it (which used to orphan pending transactions).
     turn_refresh_in_progress: std::sync::atomic::AtomicBool,
+    /// Set by an ICE restart until a pair is selected under the new
+    /// credentials. The previously selected pair keeps carrying traffic
+    /// meanwhile, so checks must run even though `selected_pair` is set.
+    restarting: std::sync::atomic::AtomicBool,
+    /// Bumped by every ICE restart so a gathering run of an earlier generation
+    /// does not report completion for the current one.
+    gather_generation: std::sync::atomic::AtomicU64,
+    /// Serializes RTP-mode offer socket setup so a call never races one that
+    /// is still binding on behalf of a cancelled caller.
+    direct_rtp_setup: Mutex<()>,
 }
 
 impl std::fmt::Debug for IceTransportInner {
@@ -169,6 +219,7 @@ impl std::fmt::Debug for IceTransportInner {
             .field("candidate_tx", &self.candidate_tx)
             .field("cmd_tx", &self.cmd_tx)
             .field("nomination_complete", &self.nomination_complete)
+            .field("restarting", &self.restarting)
             .finish()
     }
 }
@@ -194,9 +245,11 @@ impl IceTransportRunner {
         // mapping warm for relays that are gathered but not selected (and
         // therefore carry no media ChannelData). 25 s is safely under all
         // those budgets while staying cheap (tiny authenticated packets).
+        // Allocations granted a short lifetime shorten the period further;
+        // see `IceGatherer::turn_refresh_interval`.
         let mut turn_refresh_interval = tokio::time::interval_at(
-            tokio::time::Instant::now() + Duration::from_secs(25),
-            Duration::from_secs(25),
+            tokio::time::Instant::now() + TURN_REFRESH_INTERVAL,
+            TURN_REFRESH_INTERVAL,
         );
         let mut read_futures: FuturesUnordered<BoxFuture<'static, ()>> = FuturesUnordered::new();
         let mut gathering_future: BoxFuture<'static, ()> = Box::pin(futures::future::pending());
@@ -253,18 +306,41 @@ impl IceTransportRunner {
                     match cmd {
                         IceCommand::StartGathering => {
                             let inner = self.inner.clone();
+                            let generation = inner
+                                .gather_generation
+                                .load(std::sync::atomic::Ordering::SeqCst);
                             gathering_future = Box::pin(async move {
                                 if let Err(e) = inner.gatherer.gather().await {
                                     debug!("Gathering failed: {}", e);
                                 }
+                                if inner.gather_generation.load(std::sync::atomic::Ordering::SeqCst)
+                                    == generation
                                 {
-                                    let mut buffer = inner.local_candidates.lock().await;
-                                    *buffer = inner.gatherer.local_candidates();
+                                    Self::finish_gathering(&inner).await;
                                 }
-                                *inner.gather_state.lock() = IceGathererState::Complete;
-                                let _ = inner.gathering_state.send(IceGathererState::Complete);
                             });
                         }
+                        IceCommand::Restart => {
+                            // Drop a gathering run of the previous generation,
+                            // along with whatever it added since `restart`, so
+                            // the next StartGathering collects afresh.
+                            gathering_future = Box::pin(futures::future::pending());
+                            self.inner.gatherer.retire_sockets();
+                            self.inner.gatherer.reset_candidates();
+                        }
+                        IceCommand::CancelGathering => {
+                            if *self.inner.gather_state.lock() == IceGathererState::Gathering {
+                                debug!(
+                                    "ICE gathering cancelled, abandoned probes: {:?}",
+                                    self.inner.gatherer.pending_probes()
+                                );
+                                // Dropping the future drops the outstanding STUN/TURN
+                                // probes together with their sockets.
+                                gathering_future = Box::pin(futures::future::pending());
+                                *self.inner.gatherer.state.lock() = IceGathererState::Complete;
+                                Self::finish_gathering(&self.inner).await;
+                            }
+                        }
                         IceCommand::RunChecks => {
                             let inner = self.inner.clone();
                             // Spawn connectivity checks in a separate task so they don't
@@ -282,6 +358,14 @@ impl IceTransportRunner {
                     if let Some(f) = Self::run_keepalive_tick(&self.inner).await {
                         read_futures.push(f);
                     }
+                    let period = self.inner.gatherer.turn_refresh_interval();
+                    if period != turn_refresh_interval.period() {
+                        debug!("TURN refresh interval now {:?}", period);
+                        turn_refresh_interval = tokio::time::interval_at(
+                            tokio::time::Instant::now() + period,
+                            period,
+                        );
+                    }
                 }
                 _ = turn_refresh_interval.tick() => {
                     // Only start a new refresh if the previous one has
@@ -314,14 +398,32 @@ impl IceTransportRunner {
         }
     }
 
+    /// Publish the gathered candidates and mark gathering complete.
+    async fn finish_gathering(inner: &IceTransportInner) {
+        {
+            let mut buffer = inner.local_candidates.lock().await;
+            *buffer = inner.gatherer.local_candidates();
+        }
+        *inner.gather_state.lock() = IceGathererState::Complete;
+        let _ = inner.gathering_state.send(IceGathererState::Complete);
+    }
+
     async fn run_udp_read_loop(socket: Arc<UdpSocket>, inner: Arc<IceTransportInner>) {
         let mut buf = [0u8; 1500];
         let mut marshal_buf = Vec::with_capacity(200);
+        let mut rx_pool = ReceiveBufferPool::new(inner.config.receive_buffer_pool_size);
         let mut state_rx = inner.state.subscribe();
+        let mut released_rx = inner.gatherer.sockets_released.subscribe();
         let sender = IceSocketWrapper::Udp(socket.clone());
         trace!("Read loop started for {:?}", socket.local_addr());
         loop {
             tokio::select! {
+                res = released_rx.changed() => {
+                    if res.is_err() || !inner.gatherer.holds_socket(&socket) {
+                        debug!("Read loop stopping (socket released)");
+                        break;
+                    }
+                }
                 res = socket.readable() => {
                     if let Err(e) = res {
                         debug!("Socket readable wait error: {}", e);
@@ -348,6 +450,7 @@ impl IceTransportRunner {
                                 inner.clone(),
                                 sender.clone(),
                                 &mut marshal_buf,
+                                &mut rx_pool,
                             )
                             .await;
                         }
@@ -372,6 +475,7 @@ impl IceTransportRunner {
     ) {
         let mut state_rx = inner.state.subscribe();
         let mut marshal_buf = Vec::with_capacity(200);
+        let mut rx_pool = ReceiveBufferPool::new(inner.config.receive_buffer_pool_size);
         let sender = IceSocketWrapper::SharedUdp(handle.clone());
         trace!("Shared UDP read loop started");
         loop {
@@ -399,6 +503,7 @@ impl IceTransportRunner {
                         inner.clone(),
                         sender.clone(),
                         &mut marshal_buf,
+                        &mut rx_pool,
                     )
                     .await;
                 }
@@ -414,6 +519,7 @@ impl IceTransportRunner {
     ) {
         let mut buf = [0u8; 1500];
         let mut marshal_buf = Vec::with_capacity(200);
+        let mut rx_pool = ReceiveBufferPool::new(inner.config.receive_buffer_pool_size);
         let mut state_rx = inner.state.subscribe();
         trace!("Read loop started for TURN client {}", relayed_addr);
         loop {
@@ -424,7 +530,7 @@ impl IceTransportRunner {
                     match result {
                         Ok(len) => {
                             if len > 0 {
-                                IceTransport::handle_turn_packet(&buf[..len], &inner, &client, relayed_addr, &mut marshal_buf).await;
+                                IceTransport::handle_turn_packet(&buf[..len], &inner, &client, relayed_addr, &mut marshal_buf, &mut rx_pool).await;
                             }
                         }
                         Err(e) => {
@@ -490,6 +596,7 @@ impl IceTransportRunner {
     ) {
         let mut buf = [0u8; 65_535];
         let mut marshal_buf = Vec::with_capacity(200);
+        let mut rx_pool = ReceiveBufferPool::new(inner.config.receive_buffer_pool_size);
         let mut state_rx = inner.state.subscribe();
         let sender = IceSocketWrapper::TcpStream(read, write, peer_addr);
         trace!("TCP read loop started for peer {}", peer_addr);
@@ -505,6 +612,7 @@ impl IceTransportRunner {
                                     inner.clone(),
                                     sender.clone(),
                                     &mut marshal_buf,
+                                    &mut rx_pool,
                                 )
                                 .await;
                             }
@@ -530,6 +638,20 @@ impl IceTransportRunner {
     /// removes the transaction from `pending_transactions`.
     async fn run_keepalive_tick(inner: &Arc<IceTransportInner>) -> Option<BoxFuture<'static, ()>> {
         let state = *inner.state.borrow();
+        if state == IceTransportState::Checking && inner.selected_pair.lock().is_none() {
+            let expired = inner
+                .checking_started
+                .lock()
+                .is_some_and(|started| started.elapsed() > inner.config.ice_checking_timeout);
+            if expired {
+                debug!("ICE checking timed out without a succeeded pair");
+                let _ = inner.state.send(IceTransportState::Failed);
+            } else if check_list_exhausted(inner).await {
+                debug!("ICE check list exhausted, every pair failed");
+                let _ = inner.state.send(IceTransportState::Failed);
+            }
+            return None;
+        }
         if state == IceTransportState::Connected || state == IceTransportState::Disconnected {
             if inner.config.transport_mode == crate::TransportMode::WebRtc {
                 let elapsed = inner.last_received.lock().elapsed();
@@ -590,11 +712,23 @@ impl IceTransportRunner {
                             }
 
                             let inner_weak = Arc::downgrade(inner);
+                            let sent_at = Instant::now();
+                            let checked_pair = pair.clone();
                             let cleanup: BoxFuture<'static, ()> = Box::pin(async move {
-                                let _ = timeout(Duration::from_secs(5), rx).await;
+                                let answered =
+                                    matches!(timeout(Duration::from_secs(5), rx).await, Ok(Ok(_)));
                                 if let Some(inner) = inner_weak.upgrade() {
                                     let mut map = inner.pending_transactions.lock();
                                     map.remove(&tx_id);
+                                    drop(map);
+                                    if answered {
+                                        record_pair_check(
+                                            &inner,
+                                            &checked_pair,
+                                            PairCheckState::Succeeded,
+                                            Some(sent_at.elapsed()),
+                                        );
+                                    }
                                 }
                             });
 
@@ -707,6 +841,9 @@ impl IceTransportRunner {
                     match send_and_await_inner(client, inner, bytes, tx_id).await {
                         Some(msg) if msg.class == StunClass::SuccessResponse => {
                             trace!("TURN allocation refreshed successfully");
+                            if let Some(lifetime) = msg.lifetime.filter(|&l| l > 0) {
+                                client.set_lifetime(lifetime);
+                            }
                             break 'alloc;
                         }
                         Some(msg)
@@ -861,6 +998,7 @@ impl IceTransport {
             selected_pair: parking_lot::Mutex::new(None),
             local_candidates: Mutex::new(Vec::new()),
             remote_candidates: parking_lot::Mutex::new(Vec::new()),
+            remote_candidates_complete: std::sync::atomic::AtomicBool::new(false),
             gather_state: parking_lot::Mutex::new(IceGathererState::New),
             config: config.clone(),
             gatherer,
@@ -876,12 +1014,17 @@ impl IceTransport {
             selected_pair_notifier: selected_pair_tx,
             _selected_pair_rx_keeper: selected_pair_rx,
             last_received: parking_lot::Mutex::new(Instant::now()),
+            checking_started: parking_lot::Mutex::new(None),
+            pair_checks: parking_lot::Mutex::new(HashMap::new()),
             candidate_tx: candidate_tx.clone(),
             cmd_tx,
             checking_pairs: Mutex::new(std::collections::HashSet::new()),
             nomination_complete: nomination_complete_tx,
             _nomination_complete_rx: nomination_complete_rx,
             turn_refresh_in_progress: std::sync::atomic::AtomicBool::new(false),
+            restarting: std::sync::atomic::AtomicBool::new(false),
+            gather_generation: std::sync::atomic::AtomicU64::new(0),
+            direct_rtp_setup: Mutex::new(()),
             buffer_stats: Arc::new(BufferStats::default()),
         };
         let inner = Arc::new(inner);
@@ -992,10 +1135,60 @@ impl IceTransport {
         self.inner.local_parameters.lock().clone()
     }
 
+    pub fn remote_parameters(&self) -> Option<IceParameters> {
+        self.inner.remote_parameters.lock().clone()
+    }
+
     pub fn set_remote_parameters(&self, params: IceParameters) {
         *self.inner.remote_parameters.lock() = Some(params);
     }
 
+    /// Begin an ICE restart (RFC 8445 §9): generate fresh local credentials,
+    /// forget the candidates of the previous generation and re-arm gathering
+    /// so the next [`start_gathering`](Self::start_gathering) collects new
+    /// ones, abandoning a gathering run still in progress. The selected pair
+    /// and its socket are kept, so traffic (and anything layered on top, such
+    /// as DTLS) continues on the old path until the next
+    /// [`start`](Self::start) with the peer's new credentials nominates a
+    /// pair; the previous generation's sockets are closed then. Returns the
+    /// new local parameters.
+    pub fn restart(&self) -> IceParameters {
+        let params = {
+            let mut local = self.inner.local_parameters.lock();
+            let fresh = IceParameters::generate();
+            local.username_fragment = fresh.username_fragment;
+            local.password = fresh.password;
+            local.clone()
+        };
+        self.inner.remote_candidates.lock().clear();
+        self.inner.pair_checks.lock().clear();
+        self.inner
+            .remote_candidates_complete
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+        {
+            let mut state = self.inner.gather_state.lock();
+            self.inner
+                .gather_generation
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.gatherer.retire_sockets();
+            self.inner.gatherer.reset_candidates();
+            *state = IceGathererState::New;
+            let _ = self.inner.gathering_state.send(IceGathererState::New);
+        }
+        let _ = self.inner.cmd_tx.send(IceCommand::Restart);
+        self.inner
+            .restarting
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        params
+    }
+
+    /// Whether an ICE restart is waiting for a pair under the new credentials.
+    pub fn is_restarting(&self) -> bool {
+        self.inner
+            .restarting
+            .load(std::sync::atomic::Ordering::SeqCst)
+    }
+
     fn start_keepalive(&self) {
         // Handled by runner
     }
@@ -1014,13 +1207,45 @@ impl IceTransport {
         Ok(())
     }
 
+    /// Abort candidate gathering in progress. Outstanding STUN/TURN probes
+    /// are dropped and their sockets released; gathering then completes with
+    /// the candidates found so far. Does nothing unless gathering is running.
+    pub fn cancel_gathering(&self) {
+        if *self.inner.gather_state.lock() != IceGathererState::Gathering {
+            return;
+        }
+        let _ = self.inner.cmd_tx.send(IceCommand::CancelGathering);
+    }
+
+    /// ICE server URLs whose STUN/TURN probe is still in flight.
+    pub fn pending_gathering_probes(&self) -> Vec<String> {
+        self.inner.gatherer.pending_probes()
+    }
+
     pub fn start(&self, remote: IceParameters) -> Result<()> {
         self.start_gathering()?;
         self.start_keepalive();
-        {
+        let credentials_changed = {
             let mut params = self.inner.remote_parameters.lock();
+            let changed = params
+                .as_ref()
+                .is_some_and(|current| remote.is_restart_of(current));
             *params = Some(remote);
+            changed
+        };
+        if self.inner.selected_pair.lock().is_some() {
+            if credentials_changed {
+                // The peer restarted ICE: re-run checks while the current pair
+                // keeps carrying traffic.
+                self.inner
+                    .restarting
+                    .store(true, std::sync::atomic::Ordering::SeqCst);
+            } else if !self.is_restarting() {
+                // Renegotiation with unchanged credentials keeps the current pair.
+                return Ok(());
+            }
         }
+        *self.inner.checking_started.lock() = Some(Instant::now());
         if let Err(e) = self.inner.state.send(IceTransportState::Checking) {
             debug!("start: failed to set state to Checking: {}", e);
         }
@@ -1098,6 +1323,9 @@ impl IceTransport {
             let _ = self.inner.selected_socket.send(Some(socket.clone()));
             publish_selected_rtcp_socket(&self.inner, Some(socket));
         }
+        // A direct pair is selected by signaling, there is no USE-CANDIDATE
+        // exchange to wait for.
+        let _ = self.inner.nomination_complete.send(Some(true));
         let _ = self.inner.state.send(IceTransportState::Connected);
         Ok(())
     }
@@ -1106,13 +1334,17 @@ impl IceTransport {
     /// STUN lookups, or connectivity checks.
     /// Binds a single socket, registers it, and marks the transport as connected.
     pub async fn setup_direct_rtp(&self, remote_addr: SocketAddr) -> Result<SocketAddr> {
-        self.setup_direct_rtp_with_rtcp(remote_addr, false).await
+        self.setup_direct_rtp_with_rtcp(remote_addr, false, None)
+            .await
     }
 
+    /// `port` pins the RTP socket to that port instead of the configured
+    /// range.
     pub(crate) async fn setup_direct_rtp_with_rtcp(
         &self,
         remote_addr: SocketAddr,
         bind_rtcp: bool,
+        port: Option<u16>,
     ) -> Result<SocketAddr> {
         let bind_ip = if let Some(bind_ip_str) = &self.inner.config.bind_ip {
             bind_ip_str.parse::<IpAddr>().unwrap_or_else(|_| {
@@ -1124,7 +1356,7 @@ impl IceTransport {
             IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED)
         };
 
-        let socket = self.inner.gatherer.bind_socket(bind_ip).await?;
+        let socket = self.inner.gatherer.bind_media_socket(bind_ip, port).await?;
         let local_addr = socket.local_addr()?;
         let socket = Arc::new(socket);
 
@@ -1232,12 +1464,38 @@ impl IceTransport {
     /// Binds a socket and registers the local candidate, but does NOT set the
     /// selected pair or transition to Connected.
     pub async fn setup_direct_rtp_offer(&self) -> Result<SocketAddr> {
-        self.setup_direct_rtp_offer_with_rtcp(false).await
+        self.setup_direct_rtp_offer_with_rtcp(false, None).await
     }
 
+    /// Runs on its own task, so dropping the caller (e.g. a cancelled
+    /// `create_offer`) never leaves a bound socket without its candidate. A
+    /// later call waits for that setup and returns its candidate address
+    /// instead of binding a second socket.
     pub(crate) async fn setup_direct_rtp_offer_with_rtcp(
         &self,
         bind_rtcp: bool,
+        port: Option<u16>,
+    ) -> Result<SocketAddr> {
+        let this = self.clone();
+        tokio::spawn(async move {
+            let _setup = this.inner.direct_rtp_setup.lock().await;
+            if let Some(candidate) = this
+                .local_candidates()
+                .into_iter()
+                .find(|c| c.component == 1)
+            {
+                return Ok(candidate.address);
+            }
+            this.bind_direct_rtp_offer(bind_rtcp, port).await
+        })
+        .await
+        .map_err(|err| anyhow!("RTP offer setup task failed: {err}"))?
+    }
+
+    async fn bind_direct_rtp_offer(
+        &self,
+        bind_rtcp: bool,
+        port: Option<u16>,
     ) -> Result<SocketAddr> {
         let bind_ip = if let Some(bind_ip_str) = &self.inner.config.bind_ip {
             bind_ip_str.parse::<IpAddr>().unwrap_or_else(|_| {
@@ -1249,7 +1507,7 @@ impl IceTransport {
             IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED)
         };
 
-        let socket = self.inner.gatherer.bind_socket(bind_ip).await?;
+        let socket = self.inner.gatherer.bind_media_socket(bind_ip, port).await?;
         let local_addr = socket.local_addr()?;
         let socket = Arc::new(socket);
 
@@ -1454,7 +1712,9 @@ impl IceTransport {
         let _ = self.inner.selected_rtcp_socket.send(None);
         let _ = self.inner.selected_pair_notifier.send(None);
         *self.inner.selected_pair.lock() = None;
+        self.inner.pair_checks.lock().clear();
         self.inner.gatherer.sockets.lock().clear();
+        self.inner.gatherer.retired_sockets.lock().clear();
         self.inner.gatherer.tcp_listeners.lock().clear();
         self.inner.gatherer.tcp_streams.lock().clear();
         self.inner.gatherer.shared_tcp_regs.lock().clear();
@@ -1482,6 +1742,15 @@ impl IceTransport {
         self.try_connectivity_checks();
     }
 
+    /// The peer has sent all its candidates (`a=end-of-candidates` or its
+    /// trickle equivalent). Until then a check list whose pairs all failed
+    /// may still grow, so only `ice_checking_timeout` fails the transport.
+    pub fn add_remote_end_of_candidates(&self) {
+        self.inner
+            .remote_candidates_complete
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
     pub fn select_pair(&self, pair: IceCandidatePair) {
         *self.inner.selected_pair.lock() = Some(pair.clone());
         let _ = self.inner.selected_pair_notifier.send(Some(pair.clone()));
@@ -1508,6 +1777,14 @@ impl IceTransport {
         self.inner.selected_pair.lock().clone()
     }
 
+    /// `(local, remote)` candidates of the pair media currently flows on.
+    /// Follows nomination and any later switch, e.g. when the peer nominates
+    /// another pair or RTP latching moves the remote address.
+    pub fn selected_pair(&self) -> Option<(IceCandidate, IceCandidate)> {
+        self.get_selected_pair()
+            .map(|pair| (pair.local, pair.remote))
+    }
+
     pub async fn set_data_receiver(&self, receiver: Arc<dyn PacketReceiver>) {
         {
             let mut rx_lock = self.inner.data_receiver.lock();
@@ -1544,6 +1821,7 @@ impl IceTransport {
         client: &Arc<TurnClient>,
         relayed_addr: SocketAddr,
         marshal_buf: &mut Vec<u8>,
+        rx_pool: &mut ReceiveBufferPool,
     ) {
         // Check for ChannelData (0x4000 - 0x7FFF)
         if packet.len() >= 4 {
@@ -1559,6 +1837,7 @@ impl IceTransport {
                             inner.clone(),
                             IceSocketWrapper::Turn(client.clone(), relayed_addr),
                             marshal_buf,
+                            rx_pool,
                         )
                         .await;
                     }
@@ -1578,6 +1857,7 @@ impl IceTransport {
                         inner.clone(),
                         IceSocketWrapper::Turn(client.clone(), relayed_addr),
                         marshal_buf,
+                        rx_pool,
                     )
                     .await;
                 }
@@ -1589,6 +1869,7 @@ impl IceTransport {
                     inner.clone(),
                     IceSocketWrapper::Turn(client.clone(), relayed_addr),
                     marshal_buf,
+                    rx_pool,
                 )
                 .await;
             }
@@ -1596,14 +1877,167 @@ impl IceTransport {
     }
 }
 
+/// Connectivity-check state of a candidate pair (RFC 8445 §6.1.2.6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PairCheckState {
+    InProgress,
+    Succeeded,
+    Failed,
+}
+
+impl PairCheckState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PairCheckState::InProgress => "in-progress",
+            PairCheckState::Succeeded => "succeeded",
+            PairCheckState::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PairCheck {
+    pair: IceCandidatePair,
+    state: PairCheckState,
+    /// Latest binding request/response round trip on this pair.
+    round_trip_time: Option<Duration>,
+}
+
+fn record_pair_check(
+    inner: &IceTransportInner,
+    pair: &IceCandidatePair,
+    state: PairCheckState,
+    round_trip_time: Option<Duration>,
+) {
+    let mut checks = inner.pair_checks.lock();
+    let key = (pair.local.address, pair.remote.address);
+    if state != PairCheckState::InProgress && !checks.contains_key(&key) {
+        // Dropped by a restart or stop while the check was in flight.
+        return;
+    }
+    let check = checks.entry(key).or_insert_with(|| PairCheck {
+        pair: pair.clone(),
+        state,
+        round_trip_time: None,
+    });
+    check.state = state;
+    if round_trip_time.is_some() {
+        check.round_trip_time = round_trip_time;
+    }
+}
+
+/// Every pair we formed has failed its check and no more can come from
+/// either side. Early error responses (a role conflict, a peer that does not know
+/// our credentials yet) fail a pair before it had its full retransmission
+/// window, so nothing counts as exhausted within `stun_timeout` of starting.
+async fn check_list_exhausted(inner: &IceTransportInner) -> bool {
+    if *inner.gatherer.state.lock() != IceGathererState::Complete
+        || !inner
+            .remote_candidates_complete
+            .load(std::sync::atomic::Ordering::SeqCst)
+        || !inner.checking_pairs.lock().await.is_empty()
+    {
+        return false;
+    }
+    let waited = inner
+        .checking_started
+        .lock()
+        .is_some_and(|started| started.elapsed() >= inner.config.stun_timeout);
+    let checks = inner.pair_checks.lock();
+    waited
+        && !checks.is_empty()
+        && checks
+            .values()
+            .all(|check| check.state == PairCheckState::Failed)
+}
+
+fn candidate_pair_stats_id(pair: &IceCandidatePair) -> String {
+    format!(
+        "candidate-pair-{}-{}",
+        pair.local.address, pair.remote.address
+    )
+}
+
+/// One `IceCandidatePair` entry per checked pair plus the selected one. Byte
+/// counts live on the connection carrying the selected pair and are added to
+/// that pair's entry by the peer connection; other pairs carry none.
+#[async_trait::async_trait]
+impl crate::stats::StatsProvider for IceTransport {
+    async fn collect(&self) -> crate::errors::RtcResult<Vec<crate::stats::StatsEntry>> {
+        use crate::stats::{StatsEntry, StatsId, StatsKind};
+        use serde_json::json;
+
+        let selected = self.inner.selected_pair.lock().clone();
+        let nominated = *self.inner.nomination_complete.borrow() == Some(true);
+        let mut checks: Vec<PairCheck> = self.inner.pair_checks.lock().values().cloned().collect();
+        if let Some(pair) = &selected
+            && !checks.iter().any(|c| {
+                c.pair.local.address == pair.local.address
+                    && c.pair.remote.address == pair.remote.address
+            })
+        {
+            // Selected without a check of ours: direct RTP, inbound TCP or a
+            // controlled-side USE-CANDIDATE.
+            checks.push(PairCheck {
+                pair: pair.clone(),
+                state: PairCheckState::Succeeded,
+                round_trip_time: None,
+            });
+        }
+        checks.sort_by_key(|c| candidate_pair_stats_id(&c.pair));
+
+        Ok(checks
+            .into_iter()
+            .map(|check| {
+                let is_selected = selected.as_ref().is_some_and(|pair| {
+                    pair.local.address == check.pair.local.address
+                        && pair.remote.address == check.pair.remote.address
+                });
+                let mut entry = StatsEntry::new(
+                    StatsId::new(candidate_pair_stats_id(&check.pair)),
+                    StatsKind::IceCandidatePair,
+                )
+                .with_value(
+                    "localCandidate",
+                    json!(format!(
+                        "{} {} {}",
+                        check.pair.local.typ.as_str(),
+                        check.pair.local.transport,
+                        check.pair.local.address
+                    )),
+                )
+                .with_value(
+                    "remoteCandidate",
+                    json!(format!(
+                        "{} {} {}",
+                        check.pair.remote.typ.as_str(),
+                        check.pair.remote.transport,
+                        check.pair.remote.address
+                    )),
+                )
+                .with_value("state", json!(check.state.as_str()))
+                .with_value("nominated", json!(is_selected && nominated))
+                .with_value("selected", json!(is_selected));
+                if let Some(rtt) = check.round_trip_time {
+                    entry = entry.with_value("currentRoundTripTime", json!(rtt.as_secs_f64()));
+                }
+                entry
+            })
+            .collect())
+    }
+}
+
 async fn perform_connectivity_checks_async(inner: Arc<IceTransportInner>) {
     let state = *inner.state.borrow();
     if state != IceTransportState::Checking {
         return;
     }
 
-    // If we already have a selected pair, don't run more checks
-    if inner.selected_pair.lock().is_some() {
+    // If we already have a selected pair, don't run more checks unless an
+    // ICE restart is looking for a new one.
+    if inner.selected_pair.lock().is_some()
+        && !inner.restarting.load(std::sync::atomic::Ordering::SeqCst)
+    {
         return;
     }
 
@@ -1722,7 +2156,10 @@ async fn perform_connectivity_checks_async(inner: Arc<IceTransportInner>) {
 
         checks.push(async move {
             let key = (local.address, remote.address);
-            let res = perform_binding_check(&local, &remote, &inner, role, false).await;
+            let pair = IceCandidatePair::new(local, remote);
+            record_pair_check(&inner, &pair, PairCheckState::InProgress, None);
+            let started = Instant::now();
+            let res = perform_binding_check(&pair.local, &pair.remote, &inner, role, false).await;
 
             {
                 let mut checking = inner.checking_pairs.lock().await;
@@ -1730,8 +2167,19 @@ async fn perform_connectivity_checks_async(inner: Arc<IceTransportInner>) {
             }
 
             match res {
-                Ok(_) => Some(IceCandidatePair::new(local, remote)),
-                Err(_) => None,
+                Ok(_) => {
+                    record_pair_check(
+                        &inner,
+                        &pair,
+                        PairCheckState::Succeeded,
+                        Some(started.elapsed()),
+                    );
+                    Some(pair)
+                }
+                Err(_) => {
+                    record_pair_check(&inner, &pair, PairCheckState::Failed, None);
+                    None
+                }
             }
         });
     }
@@ -1779,9 +2227,14 @@ async fn perform_connectivity_checks_async(inner: Arc<IceTransportInner>) {
     }
 
     if successful_pairs.is_empty() {
+        // The peer may still trickle candidates; otherwise the keepalive tick
+        // fails the transport once checking times out.
         let state = *inner.state.borrow();
         let has_selected_pair = inner.selected_pair.lock().is_some();
-        if state != IceTransportState::Connected && !has_selected_pair {
+        if state != IceTransportState::Connected
+            && !has_selected_pair
+            && check_list_exhausted(&inner).await
+        {
             let _ = inner.state.send(IceTransportState::Failed);
         }
         return;
@@ -1819,6 +2272,7 @@ async fn perform_connectivity_checks_async(inner: Arc<IceTransportInner>) {
                         pair.local.address, pair.remote.address
                     );
                     let _ = inner.nomination_complete.send(Some(true));
+                    finish_restart(&inner);
                     nominated = true;
                     break;
                 }
@@ -1850,6 +2304,7 @@ async fn perform_connectivity_checks_async(inner: Arc<IceTransportInner>) {
             let _ = inner.selected_socket.send(Some(socket.clone()));
             publish_selected_rtcp_socket(&inner, Some(socket));
         }
+        finish_restart(&inner);
         let _ = inner.state.send(IceTransportState::Connected);
         if pair.local.transport == "tcp" {
             let _ = inner.nomination_complete.send(Some(true));
@@ -1861,6 +2316,25 @@ async fn perform_connectivity_checks_async(inner: Arc<IceTransportInner>) {
     }
 }
 
+/// Leave restart mode once a pair is selected under the new credentials and
+/// close the previous generation's sockets the selection no longer uses.
+fn finish_restart(inner: &IceTransportInner) {
+    inner
+        .restarting
+        .store(false, std::sync::atomic::Ordering::SeqCst);
+    let in_use: Vec<Arc<UdpSocket>> = [
+        inner.selected_socket.borrow().clone(),
+        inner.selected_rtcp_socket.borrow().clone(),
+    ]
+    .into_iter()
+    .filter_map(|socket| match socket {
+        Some(IceSocketWrapper::Udp(s)) => Some(s),
+        _ => None,
+    })
+    .collect();
+    inner.gatherer.release_retired_sockets(&in_use);
+}
+
 fn resolve_socket(inner: &IceTransportInner, pair: &IceCandidatePair) -> Option<IceSocketWrapper> {
     if pair.local.typ == IceCandidateType::Relay {
         let clients = inner.gatherer.turn_clients.lock();
@@ -2094,6 +2568,7 @@ async fn handle_packet(
     inner: Arc<IceTransportInner>,
     sender: IceSocketWrapper,
     marshal_buf: &mut Vec<u8>,
+    rx_pool: &mut ReceiveBufferPool,
 ) {
     if should_drop_packet() {
         return;
@@ -2102,8 +2577,9 @@ async fn handle_packet(
         *inner.last_received.lock() = Instant::now();
     }
     let b = packet[0];
-    if b < 2 {
-        // STUN
+    if b < 4 {
+        // STUN (RFC 7983 demux range), including consent checks that arrive
+        // interleaved with DTLS/RTP after the connection is up.
         match StunMessage::decode(packet) {
             Ok(msg) => {
                 if msg.class == StunClass::Request {
@@ -2161,8 +2637,7 @@ async fn handle_packet(
         // DTLS or RTP
         let receiver = inner.data_receiver.lock().clone();
         if let Some(rx) = receiver {
-            rx.receive(Bytes::copy_from_slice(packet), addr, marshal_buf)
-                .await;
+            rx.receive(rx_pool.copy(packet), addr, marshal_buf).await;
         } else {
             let mut buffer = inner.buffered_packets.lock();
             let stats = inner.buffer_stats.clone();
@@ -2327,7 +2802,10 @@ async fn handle_stun_request(
             // RFC 8445 §7.3.1.5: once a pair is already nominated, subsequent
             // USE-CANDIDATE (e.g. keepalives from other candidates) must not
             // trigger re-nomination.  Guard here to prevent pair_monitor churn.
-            if inner.selected_pair.lock().is_some() {
+            // An ICE restart re-opens nomination for the new generation.
+            if inner.selected_pair.lock().is_some()
+                && !inner.restarting.load(std::sync::atomic::Ordering::SeqCst)
+            {
                 if inner.nomination_complete.borrow().is_none() {
                     trace!(
                         "Controlled agent: pair already selected, signalling nomination_complete via UseCandidate from {}",
@@ -2382,6 +2860,7 @@ async fn handle_stun_request(
                     publish_selected_socket(&inner, &pair, Some(sender));
                     let _ = inner.state.send(IceTransportState::Connected);
                     let _ = inner.nomination_complete.send(Some(true));
+                    finish_restart(&inner);
                 } else {
                     debug!(
                         "Received UseCandidate but could not find UDP pair for {} -> {}",
@@ -2709,7 +3188,16 @@ pub(crate) async fn attach_demuxed_tcp_stream(
         .store_tcp_stream(listen_addr, wrapper.clone());
     let _ = inner.gatherer.socket_tx.send(wrapper.clone());
     let mut marshal_buf = Vec::new();
-    handle_packet(&first_packet, peer_addr, inner, wrapper, &mut marshal_buf).await;
+    let mut rx_pool = ReceiveBufferPool::new(0);
+    handle_packet(
+        &first_packet,
+        peer_addr,
+        inner,
+        wrapper,
+        &mut marshal_buf,
+        &mut rx_pool,
+    )
+    .await;
 }
 
 pub(crate) async fn tcp_write_all(write: &Arc<Mutex<TcpWriteHalf>>, data: &[u8]) -> Result<()> {
@@ -2912,6 +3400,17 @@ pub struct IceCandidate {
     pub tcp_type: Option<TcpType>,
     pub related_address: Option<SocketAddr>,
     pub component: u16,
+    /// `generation` extension; browsers bump it on ICE restart.
+    pub generation: Option<u32>,
+    /// `ufrag` extension naming the credentials this candidate belongs to.
+    pub ufrag: Option<String>,
+    /// `network-id` extension (Chrome).
+    pub network_id: Option<u32>,
+    /// `network-cost` extension (Chrome).
+    pub network_cost: Option<u32>,
+    /// Unrecognized `name value` extensions, kept in order so they are
+    /// re-serialized unchanged.
+    pub extensions: Vec<(String, String)>,
 }
 
 impl IceCandidate {
@@ -2943,6 +3442,11 @@ impl IceCandidate {
             tcp_type: None,
             related_address: None,
             component,
+            generation: None,
+            ufrag: None,
+            network_id: None,
+            network_cost: None,
+            extensions: Vec::new(),
         }
     }
 
@@ -2956,6 +3460,11 @@ impl IceCandidate {
             tcp_type: Some(tcp_type),
             related_address: None,
             component,
+            generation: None,
+            ufrag: None,
+            network_id: None,
+            network_cost: None,
+            extensions: Vec::new(),
         }
     }
 
@@ -2971,6 +3480,11 @@ impl IceCandidate {
             tcp_type: Some(tcp_type),
             related_address: None,
             component,
+            generation: None,
+            ufrag: None,
+            network_id: None,
+            network_cost: None,
+            extensions: Vec::new(),
         }
     }
 
@@ -2992,6 +3506,11 @@ impl IceCandidate {
             tcp_type: None,
             related_address: Some(base),
             component,
+            generation: None,
+            ufrag: None,
+            network_id: None,
+            network_cost: None,
+            extensions: Vec::new(),
         }
     }
 
@@ -3005,16 +3524,16 @@ impl IceCandidate {
             tcp_type: None,
             related_address: None,
             component,
+            generation: None,
+            ufrag: None,
+            network_id: None,
+            network_cost: None,
+            extensions: Vec::new(),
         }
     }
 
     fn priority_for(typ: IceCandidateType, component: u16) -> u32 {
-        let type_pref = match typ {
-            IceCandidateType::Host => 126u32,
-            IceCandidateType::PeerReflexive => 110u32,
-            IceCandidateType::ServerReflexive => 100u32,
-            IceCandidateType::Relay => 0u32,
-        };
+        let type_pref = typ.preference(&CandidateTypePreferences::default()) as u32;
         let local_pref = 65_535u32;
         let component = component.min(256) as u32;
         (type_pref << 24) | (local_pref << 8) | (256 - component)
@@ -3025,12 +3544,7 @@ impl IceCandidate {
     /// between active, passive, and SO types, while UDP candidates always
     /// use the full 65535 local preference.
     fn priority_for_tcp(typ: IceCandidateType, component: u16, tcp_type: TcpType) -> u32 {
-        let type_pref = match typ {
-            IceCandidateType::Host => 126u32,
-            IceCandidateType::PeerReflexive => 110u32,
-            IceCandidateType::ServerReflexive => 100u32,
-            IceCandidateType::Relay => 0u32,
-        };
+        let type_pref = typ.preference(&CandidateTypePreferences::default()) as u32;
         // RFC 6544 § 4.1: local preference for TCP candidates
         let local_pref = match tcp_type {
             TcpType::Passive => 65535u32,
@@ -3041,6 +3555,13 @@ impl IceCandidate {
         (type_pref << 24) | (local_pref << 8) | (256 - component)
     }
 
+    /// Replace the type preference byte of `priority`, keeping the local
+    /// preference and component bits.
+    fn apply_type_preference(&mut self, preferences: &CandidateTypePreferences) {
+        let type_pref = self.typ.preference(preferences) as u32;
+        self.priority = (type_pref << 24) | (self.priority & 0x00ff_ffff);
+    }
+
     pub fn to_sdp(&self) -> String {
         let mut parts = vec![
             self.foundation.clone(),
@@ -3052,10 +3573,7 @@ impl IceCandidate {
             "typ".into(),
             self.typ.as_str().into(),
         ];
-        if let Some(tcp_type) = self.tcp_type {
-            parts.push("tcptype".into());
-            parts.push(tcp_type.as_str().into());
-        }
+        // Extensions follow the order browsers emit them in.
         if let Some(addr) = self.related_address
             && self.typ != IceCandidateType::Host
         {
@@ -3064,6 +3582,36 @@ impl IceCandidate {
             parts.push("rport".into());
             parts.push(addr.port().to_string());
         }
+        // An unparsed related address keeps its place.
+        let is_related = |name: &str| name == "raddr" || name == "rport";
+        for (name, value) in self.extensions.iter().filter(|(n, _)| is_related(n)) {
+            parts.push(name.clone());
+            parts.push(value.clone());
+        }
+        if let Some(tcp_type) = self.tcp_type {
+            parts.push("tcptype".into());
+            parts.push(tcp_type.as_str().into());
+        }
+        if let Some(generation) = self.generation {
+            parts.push("generation".into());
+            parts.push(generation.to_string());
+        }
+        if let Some(ufrag) = &self.ufrag {
+            parts.push("ufrag".into());
+            parts.push(ufrag.clone());
+        }
+        if let Some(network_id) = self.network_id {
+            parts.push("network-id".into());
+            parts.push(network_id.to_string());
+        }
+        if let Some(network_cost) = self.network_cost {
+            parts.push("network-cost".into());
+            parts.push(network_cost.to_string());
+        }
+        for (name, value) in self.extensions.iter().filter(|(n, _)| !is_related(n)) {
+            parts.push(name.clone());
+            parts.push(value.clone());
+        }
         parts.join(" ")
     }
 
@@ -3100,37 +3648,75 @@ impl IceCandidate {
             _ => bail!("unknown type"),
         };
 
-        // Parse optional tcptype attribute (RFC 6544)
-        let tcp_type = if transport == "tcp" {
-            // Search for "tcptype" keyword at even indices after position 8
-            let mut i = 8;
-            loop {
-                if i + 1 >= parts.len() {
-                    break None;
-                }
-                match parts[i] {
-                    "tcptype" => {
-                        break TcpType::from_str(parts[i + 1]);
-                    }
-                    _ => {
-                        i += 2;
-                    }
-                }
-            }
-        } else {
-            None
-        };
-
-        Ok(Self {
+        // Extensions are `name value` pairs after the type (RFC 8839 §5.1).
+        // Anything not understood here is kept verbatim.
+        let mut candidate = Self {
             foundation,
             priority,
             address,
             typ,
             transport,
-            tcp_type,
+            tcp_type: None,
             related_address: None,
             component,
-        })
+            generation: None,
+            ufrag: None,
+            network_id: None,
+            network_cost: None,
+            extensions: Vec::new(),
+        };
+        for pair in parts[start_idx + 8..].chunks_exact(2) {
+            let (name, value) = (pair[0], pair[1]);
+            let known = match name {
+                // Resolved below, since they only make sense together.
+                "raddr" | "rport" => false,
+                // RFC 6544
+                "tcptype" if candidate.transport == "tcp" => TcpType::from_str(value)
+                    .map(|t| candidate.tcp_type = Some(t))
+                    .is_some(),
+                "generation" => value
+                    .parse()
+                    .map(|g| candidate.generation = Some(g))
+                    .is_ok(),
+                "ufrag" => {
+                    candidate.ufrag = Some(value.to_string());
+                    true
+                }
+                "network-id" => value
+                    .parse()
+                    .map(|id| candidate.network_id = Some(id))
+                    .is_ok(),
+                "network-cost" => value
+                    .parse()
+                    .map(|cost| candidate.network_cost = Some(cost))
+                    .is_ok(),
+                _ => false,
+            };
+            if !known {
+                candidate
+                    .extensions
+                    .push((name.to_string(), value.to_string()));
+            }
+        }
+        // A related address needs both halves; if either is missing or
+        // malformed, both stay verbatim in `extensions`.
+        let related = |key: &str| {
+            candidate
+                .extensions
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+        };
+        let raddr = related("raddr").and_then(|v| v.parse::<IpAddr>().ok());
+        let rport = related("rport").and_then(|v| v.parse::<u16>().ok());
+        if let (Some(ip), Some(port)) = (raddr, rport) {
+            candidate.related_address = Some(SocketAddr::new(ip, port));
+            candidate
+                .extensions
+                .retain(|(name, _)| name != "raddr" && name != "rport");
+        }
+
+        Ok(candidate)
     }
 }
 
@@ -3143,6 +3729,15 @@ pub enum IceCandidateType {
 }
 
 impl IceCandidateType {
+    fn preference(&self, preferences: &CandidateTypePreferences) -> u8 {
+        match self {
+            IceCandidateType::Host => preferences.host,
+            IceCandidateType::ServerReflexive => preferences.server_reflexive,
+            IceCandidateType::PeerReflexive => preferences.peer_reflexive,
+            IceCandidateType::Relay => preferences.relay,
+        }
+    }
+
     fn as_str(&self) -> &'static str {
         match self {
             IceCandidateType::Host => "host",
@@ -3198,6 +3793,12 @@ impl IceParameters {
         }
     }
 
+    /// Whether these remote credentials replace `previous`, meaning the peer
+    /// restarted ICE (RFC 8445 §9): either the ufrag or the password changed.
+    pub fn is_restart_of(&self, previous: &IceParameters) -> bool {
+        self.username_fragment != previous.username_fragment || self.password != previous.password
+    }
+
     fn generate() -> Self {
         let ufrag = hex_encode(&random_bytes::<8>());
         let pwd = hex_encode(&random_bytes::<16>());
@@ -3253,16 +3854,23 @@ struct IceGatherer {
     state: Arc<parking_lot::Mutex<IceGathererState>>,
     local_candidates: Arc<parking_lot::Mutex<Vec<IceCandidate>>>,
     sockets: Arc<parking_lot::Mutex<Vec<Arc<UdpSocket>>>>,
+    /// Sockets of the generation before an ICE restart, open until a pair is
+    /// selected under the new credentials.
+    retired_sockets: Arc<parking_lot::Mutex<Vec<Arc<UdpSocket>>>>,
+    /// Bumped when retired sockets are released, so their read loops stop.
+    sockets_released: Arc<watch::Sender<u64>>,
     tcp_listeners: Arc<parking_lot::Mutex<Vec<Arc<TcpListener>>>>,
     tcp_streams: Arc<parking_lot::Mutex<HashMap<SocketAddr, IceSocketWrapper>>>,
     shared_tcp_regs: Arc<parking_lot::Mutex<Vec<shared_tcp::SharedTcpRegistration>>>,
     shared_udp_regs: Arc<parking_lot::Mutex<Vec<shared_udp::SharedUdpRegistration>>>,
-    /// The shared UDP mux socket wrapper (when `ice_udp_mux` is enabled).
+    /// The shared UDP mux socket wrapper (when `ice_udp_mux` or `udp_mux` is set).
     /// Stored so `resolve_socket` can return it for sending.
     shared_udp_socket: Arc<parking_lot::Mutex<Option<IceSocketWrapper>>>,
     transport_inner: Arc<parking_lot::Mutex<Option<std::sync::Weak<IceTransportInner>>>>,
     turn_clients: Arc<parking_lot::Mutex<HashMap<SocketAddr, Arc<TurnClient>>>>,
     upnp_mappers: Arc<parking_lot::Mutex<Vec<UpnpPortMapper>>>,
+    /// ICE server URLs currently being probed by `gather_servers`.
+    pending_probes: Arc<parking_lot::Mutex<Vec<String>>>,
     config: RtcConfiguration,
     candidate_tx: broadcast::Sender<IceCandidate>,
     socket_tx: tokio::sync::mpsc::UnboundedSender<IceSocketWrapper>,
@@ -3278,6 +3886,8 @@ impl IceGatherer {
             state: Arc::new(parking_lot::Mutex::new(IceGathererState::New)),
             local_candidates: Arc::new(parking_lot::Mutex::new(Vec::new())),
             sockets: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            retired_sockets: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            sockets_released: Arc::new(watch::channel(0).0),
             tcp_listeners: Arc::new(parking_lot::Mutex::new(Vec::new())),
             tcp_streams: Arc::new(parking_lot::Mutex::new(HashMap::new())),
             shared_tcp_regs: Arc::new(parking_lot::Mutex::new(Vec::new())),
@@ -3286,12 +3896,23 @@ impl IceGatherer {
             transport_inner: Arc::new(parking_lot::Mutex::new(None)),
             turn_clients: Arc::new(parking_lot::Mutex::new(HashMap::new())),
             upnp_mappers: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            pending_probes: Arc::new(parking_lot::Mutex::new(Vec::new())),
             config,
             candidate_tx,
             socket_tx,
         }
     }
 
+    /// Refresh period that keeps every TURN allocation alive.
+    pub(crate) fn turn_refresh_interval(&self) -> Duration {
+        self.turn_clients
+            .lock()
+            .values()
+            .map(|client| client.refresh_interval())
+            .min()
+            .unwrap_or(TURN_REFRESH_INTERVAL)
+    }
+
     fn set_transport(&self, inner: std::sync::Weak<IceTransportInner>) {
         *self.transport_inner.lock() = Some(inner);
     }
@@ -3348,6 +3969,54 @@ impl IceGatherer {
         self.local_candidates.lock().clone()
     }
 
+    /// Forget the gathered candidates so the next `gather` starts afresh.
+    fn reset_candidates(&self) {
+        self.local_candidates.lock().clear();
+        *self.state.lock() = IceGathererState::New;
+    }
+
+    /// Set the current sockets aside for an ICE restart. They stay open, as
+    /// the selected pair may still be using one of them, until
+    /// [`release_retired_sockets`](Self::release_retired_sockets).
+    fn retire_sockets(&self) {
+        let current = std::mem::take(&mut *self.sockets.lock());
+        self.retired_sockets.lock().extend(current);
+    }
+
+    /// Close the retired sockets, except those in `in_use`, which rejoin the
+    /// current generation.
+    fn release_retired_sockets(&self, in_use: &[Arc<UdpSocket>]) {
+        let retired = std::mem::take(&mut *self.retired_sockets.lock());
+        if retired.is_empty() {
+            return;
+        }
+        let (kept, released): (Vec<_>, Vec<_>) = retired
+            .into_iter()
+            .partition(|socket| in_use.iter().any(|s| Arc::ptr_eq(s, socket)));
+        self.sockets.lock().extend(kept);
+        if !released.is_empty() {
+            debug!(
+                "Closing {} sockets of the previous ICE generation",
+                released.len()
+            );
+            self.sockets_released.send_modify(|count| *count += 1);
+        }
+    }
+
+    /// Whether `socket` belongs to the current or the retired generation.
+    fn holds_socket(&self, socket: &Arc<UdpSocket>) -> bool {
+        self.sockets.lock().iter().any(|s| Arc::ptr_eq(s, socket))
+            || self
+                .retired_sockets
+                .lock()
+                .iter()
+                .any(|s| Arc::ptr_eq(s, socket))
+    }
+
+    fn pending_probes(&self) -> Vec<String> {
+        self.pending_probes.lock().clone()
+    }
+
     async fn bind_socket(&self, ip: IpAddr) -> Result<UdpSocket> {
         if let (Some(start), Some(end)) = (self.config.rtp_start_port, self.config.rtp_end_port) {
             let start = start.saturating_add(start % 2);
@@ -3380,9 +4049,19 @@ impl IceGatherer {
         }
     }
 
+    /// Bind a direct RTP socket: exactly `port` when given, otherwise as
+    /// [`bind_socket`](Self::bind_socket).
+    async fn bind_media_socket(&self, ip: IpAddr, port: Option<u16>) -> Result<UdpSocket> {
+        match port {
+            Some(port) => UdpSocket::bind(SocketAddr::new(ip, port))
+                .await
+                .map_err(|e| anyhow!("Failed to bind fixed media port {}: {}", port, e)),
+            None => self.bind_socket(ip).await,
+        }
+    }
+
     fn get_socket(&self, addr: SocketAddr) -> Option<Arc<UdpSocket>> {
-        let found = {
-            let sockets = self.sockets.lock();
+        let find = |sockets: &[Arc<UdpSocket>]| {
             sockets.iter().find_map(|socket| {
                 let local = socket.local_addr().ok()?;
                 let matches =
@@ -3390,6 +4069,7 @@ impl IceGatherer {
                 matches.then(|| socket.clone())
             })
         };
+        let found = find(&self.sockets.lock()).or_else(|| find(&self.retired_sockets.lock()));
         if let Some(s) = found {
             return Some(s);
         }
@@ -3512,32 +4192,12 @@ impl IceGatherer {
         Ok(())
     }
 
-    /// Gather a host candidate backed by the process-wide shared UDP socket
-    /// (single-port multiplexing). All PeerConnections sharing the same
-    /// `ice_udp_mux_port` register their ufrag on the same socket; incoming
-    /// packets are demuxed by ufrag / source address in `shared_udp`.
+    /// Gather a host candidate backed by a shared UDP socket (single-port
+    /// multiplexing): either the caller-owned `udp_mux`, or the process-wide
+    /// socket for `ice_udp_mux_port`. All PeerConnections on the socket
+    /// register their ufrag on it; incoming packets are demuxed by ufrag /
+    /// source address in `shared_udp`.
     async fn gather_shared_udp_host_candidate(&self) -> Result<()> {
-        let port = self
-            .config
-            .ice_udp_mux_port
-            .ok_or_else(|| anyhow!("ice_udp_mux is enabled but ice_udp_mux_port is not set"))?;
-
-        let bind_ip = if let Some(bind_ip_str) = &self.config.bind_ip {
-            bind_ip_str
-                .parse::<IpAddr>()
-                .with_context(|| format!("invalid bind_ip {}", bind_ip_str))?
-        } else {
-            // Bind on the wildcard so the shared socket accepts on every
-            // interface; the advertised candidate IP is rewritten below.
-            IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED)
-        };
-
-        if self.config.disable_ipv6 && bind_ip.is_ipv6() {
-            bail!("disable_ipv6 is set but bind_ip is IPv6");
-        }
-
-        let bind_addr = SocketAddr::new(bind_ip, port);
-
         let inner = self
             .transport_inner
             .lock()
@@ -3546,7 +4206,34 @@ impl IceGatherer {
             .context("ICE transport unavailable during shared UDP gather")?;
         let ufrag = inner.local_parameters.lock().username_fragment.clone();
 
-        let (local_addr, handle, registration) = shared_udp::acquire(bind_addr, ufrag).await?;
+        let (local_addr, handle, registration) = if let Some(mux) = &self.config.udp_mux {
+            if self.config.disable_ipv6 && mux.local_addr().is_ipv6() {
+                bail!("disable_ipv6 is set but the UDP mux is bound to IPv6");
+            }
+            mux.register(ufrag)?
+        } else {
+            let port = self
+                .config
+                .ice_udp_mux_port
+                .ok_or_else(|| anyhow!("ice_udp_mux is enabled but ice_udp_mux_port is not set"))?;
+
+            let bind_ip = if let Some(bind_ip_str) = &self.config.bind_ip {
+                bind_ip_str
+                    .parse::<IpAddr>()
+                    .with_context(|| format!("invalid bind_ip {}", bind_ip_str))?
+            } else {
+                // Bind on the wildcard so the shared socket accepts on every
+                // interface; the advertised candidate IP is rewritten below.
+                IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED)
+            };
+
+            if self.config.disable_ipv6 && bind_ip.is_ipv6() {
+                bail!("disable_ipv6 is set but bind_ip is IPv6");
+            }
+
+            shared_udp::acquire(SocketAddr::new(bind_ip, port), ufrag).await?
+        };
+        let bind_ip = local_addr.ip();
 
         self.shared_udp_regs.lock().push(registration);
 
@@ -3618,9 +4305,8 @@ impl IceGatherer {
             }
         }
 
-        if self.config.ice_udp_mux
-            && let Err(e) = self.gather_shared_udp_host_candidate().await
-        {
+        let udp_mux = self.config.ice_udp_mux || self.config.udp_mux.is_some();
+        if udp_mux && let Err(e) = self.gather_shared_udp_host_candidate().await {
             debug!("Shared UDP mux host candidate failed: {}", e);
         }
 
@@ -3628,7 +4314,7 @@ impl IceGatherer {
             let ip = *ip;
             // When UDP mux is enabled, the shared socket already provides the
             // host candidate; skip per-IP UDP socket binding.
-            if self.config.ice_udp_mux {
+            if udp_mux {
                 continue;
             }
             match self.bind_socket(ip).await {
@@ -3929,6 +4615,7 @@ impl IceGatherer {
                 let this = self.clone();
 
                 tasks.push(async move {
+                    let _probe = PendingProbe::new(&this.pending_probes, &url);
                     let uri = match IceServerUri::parse(&url) {
                         Ok(uri) => uri,
                         Err(err) => {
@@ -3976,6 +4663,7 @@ impl IceGatherer {
                 let public_ip_clone = public_ip.clone();
 
                 tasks.push(async move {
+                    let _probe = PendingProbe::new(&this.pending_probes, &url);
                     let uri = match IceServerUri::parse(&url) {
                         Ok(uri) => uri,
                         Err(err) => {
@@ -4108,7 +4796,7 @@ impl IceGatherer {
         )))
     }
 
-    fn push_candidate(&self, candidate: IceCandidate) {
+    fn push_candidate(&self, mut candidate: IceCandidate) {
         if self.config.disable_ipv6 && candidate.address.is_ipv6() {
             return;
         }
@@ -4116,17 +4804,47 @@ impl IceGatherer {
         if candidates.iter().any(|c| c.address == candidate.address) {
             return;
         }
+        candidate.apply_type_preference(&self.config.candidate_type_preferences);
         tracing::debug!(
             "Gathered local candidate: {} type={:?}",
             candidate.address,
             candidate.typ
         );
-        candidates.push(candidate.clone());
+        // Keep the list ordered by priority; equal priorities stay in
+        // gathering order.
+        let index = candidates.partition_point(|c| c.priority >= candidate.priority);
+        candidates.insert(index, candidate.clone());
         drop(candidates);
         let _ = self.candidate_tx.send(candidate);
     }
 }
 
+/// Entry in the gatherer's in-flight probe list, removed when the probe
+/// finishes or its future is dropped by `cancel_gathering`.
+struct PendingProbe {
+    probes: Arc<parking_lot::Mutex<Vec<String>>>,
+    url: String,
+}
+
+impl PendingProbe {
+    fn new(probes: &Arc<parking_lot::Mutex<Vec<String>>>, url: &str) -> Self {
+        probes.lock().push(url.to_string());
+        Self {
+            probes: probes.clone(),
+            url: url.to_string(),
+        }
+    }
+}
+
+impl Drop for PendingProbe {
+    fn drop(&mut self) {
+        let mut probes = self.probes.lock();
+        if let Some(pos) = probes.iter().position(|u| *u == self.url) {
+            probes.remove(pos);
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct IceServerUri {
     kind: IceUriKind,
@@ -4272,6 +4990,48 @@ fn hex_encode(bytes: &[u8]) -> String {
     out
 }
 
+/// A failed UDP send, naming both ends. Wrapped in an `io::Error` of the
+/// original kind, with the OS error as its source.
+#[derive(Debug)]
+struct UdpSendError {
+    local: Option<SocketAddr>,
+    remote: SocketAddr,
+    source: std::io::Error,
+}
+
+impl std::fmt::Display for UdpSendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.local {
+            Some(local) => write!(
+                f,
+                "UDP {} -> {} failed: {}",
+                local, self.remote, self.source
+            ),
+            None => write!(f, "UDP -> {} failed: {}", self.remote, self.source),
+        }
+    }
+}
+
+impl std::error::Error for UdpSendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn udp_send_error(
+    socket: &UdpSocket,
+    remote: SocketAddr,
+    source: std::io::Error,
+) -> std::io::Error {
+    let kind = source.kind();
+    let error = UdpSendError {
+        local: socket.local_addr().ok(),
+        remote,
+        source,
+    };
+    std::io::Error::new(kind, error)
+}
+
 #[derive(Debug, Clone)]
 pub enum IceSocketWrapper {
     Udp(Arc<UdpSocket>),
@@ -4318,26 +5078,22 @@ impl IceSocketWrapper {
     /// Non-blocking variant of `send_to`: calls `try_send_to` once and returns
     /// immediately on `WouldBlock` / `ENOBUFS` instead of parking on
     /// `writable()`. Used by the RTP bridge fast-path.
-    pub fn try_send_to(&self, data: &[u8], addr: SocketAddr) -> Result<usize> {
+    pub fn try_send_to(&self, data: &[u8], addr: SocketAddr) -> std::io::Result<usize> {
         match self {
-            IceSocketWrapper::Udp(s) => match s.try_send_to(data, addr) {
-                Ok(len) => Ok(len),
-                Err(e) => {
-                    let reason = match s.local_addr() {
-                        Ok(local) => format!("UDP {} -> {} failed: {}", local, addr, e),
-                        Err(_) => format!("UDP -> {} failed: {}", addr, e),
-                    };
-                    Err(anyhow!(reason))
-                }
-            },
+            IceSocketWrapper::Udp(s) => s
+                .try_send_to(data, addr)
+                .map_err(|e| udp_send_error(s, addr, e)),
             // Non-UDP transports (TCP/TLS/TURN) are not used by the bridge
             // fast-path; fall back to the async variant.
-            _ => Err(anyhow::anyhow!(
-                "IceSocketWrapper::try_send_to not supported for this transport variant"
+            _ => Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "IceSocketWrapper::try_send_to not supported for this transport variant",
             )),
         }
     }
 
+    /// Send `data` to `addr`. UDP failures are returned as an `io::Error`
+    /// (keeping its kind) inside the `anyhow::Error`.
     pub async fn send_to(&self, data: &[u8], addr: SocketAddr) -> Result<usize> {
         match self {
             IceSocketWrapper::Udp(s) => loop {
@@ -4354,8 +5110,7 @@ impl IceSocketWrapper {
                             s.writable().await?;
                             continue;
                         }
-                        let reason = anyhow!("UDP {} -> {} failed: {}", s.local_addr()?, addr, e);
-                        return Err(reason);
+                        return Err(udp_send_error(s, addr, e).into());
                     }
                 }
             },
@@ -13,6 +13,10 @@
 //!    locally-initiated checks (e.g. a controlled agent's STUN binding request)
 //!    route back correctly.
 //!
+//! Instead of the process-wide registry keyed by `ice_udp_mux_port`, callers
+//! can also bind a [`UdpMux`] themselves and hand it to each PeerConnection
+//! through `RtcConfiguration::udp_mux`; the same demultiplexing applies.
+//!
 //! This mirrors [`super::shared_tcp`] for the UDP case.
 
 use super::shared_tcp::peer_ufrag_from_binding_request;
@@ -73,6 +77,65 @@ impl SharedUdpPort {
         }
     }
 
+    /// Register `local_ufrag` on this port. `listen_key` is the registry entry
+    /// to remove once the last user is gone (`None` for a caller-owned
+    /// [`UdpMux`]).
+    fn register(
+        self: &Arc<Self>,
+        listen_key: Option<SocketAddr>,
+        local_ufrag: String,
+    ) -> Result<(SocketAddr, SharedUdpHandle, SharedUdpRegistration)> {
+        let local_addr = self
+            .socket
+            .local_addr()
+            .context("shared UDP socket local_addr")?;
+
+        // Reject a duplicate ufrag registration on the same shared socket — each
+        // PeerConnection must own a unique ufrag so demuxing is unambiguous.
+        if self.sessions.lock().contains_key(&local_ufrag) {
+            bail!("ufrag {local_ufrag} already registered on shared UDP socket {local_addr}");
+        }
+
+        let (tx, rx) = mpsc::channel(SHARED_UDP_CHANNEL_CAPACITY);
+        self.ref_count.fetch_add(1, Ordering::SeqCst);
+        self.sessions
+            .lock()
+            .insert(local_ufrag.clone(), Session { tx });
+
+        let handle = SharedUdpHandle {
+            socket: self.socket.clone(),
+            rx: Arc::new(tokio::sync::Mutex::new(rx)),
+            peers: self.peers.clone(),
+            ufrag: local_ufrag.clone(),
+        };
+
+        if local_addr.ip().is_unspecified() {
+            // Sanity log; the gatherer will rewrite the advertised candidate IP.
+            trace!("shared UDP socket bound on wildcard: {}", local_addr);
+        }
+
+        Ok((
+            local_addr,
+            handle,
+            SharedUdpRegistration {
+                port: self.clone(),
+                listen_key,
+                ufrag: local_ufrag,
+            },
+        ))
+    }
+
+    /// Drop one reference; the last one stops the receive loop.
+    fn release(&self, listen_key: Option<SocketAddr>) {
+        let prev = self.ref_count.fetch_sub(1, Ordering::SeqCst);
+        if prev == 1 {
+            self.shutting_down.store(true, Ordering::SeqCst);
+            if let Some(key) = listen_key {
+                registry().lock().remove(&key);
+            }
+        }
+    }
+
     fn spawn_recv_loop(self: &Arc<Self>) {
         let port = Arc::clone(self);
         tokio::spawn(async move {
@@ -122,7 +185,7 @@ impl SharedUdpPort {
     }
 
     fn dispatch(&self, packet: &[u8], peer_addr: SocketAddr) {
-        let target_ufrag = if packet[0] < 2 {
+        let target_ufrag = if packet[0] < 4 {
             peer_ufrag_from_binding_request(packet)
         } else {
             None
@@ -238,7 +301,7 @@ impl SharedUdpHandle {
 /// Keeps a PeerConnection registered on a shared UDP socket until dropped.
 pub(crate) struct SharedUdpRegistration {
     port: Arc<SharedUdpPort>,
-    listen_key: SocketAddr,
+    listen_key: Option<SocketAddr>,
     ufrag: String,
 }
 
@@ -259,11 +322,7 @@ impl Drop for SharedUdpRegistration {
             .peers
             .lock()
             .retain(|_, ufrag| ufrag != &self.ufrag);
-        let prev = self.port.ref_count.fetch_sub(1, Ordering::SeqCst);
-        if prev == 1 {
-            self.port.shutting_down.store(true, Ordering::SeqCst);
-            registry().lock().remove(&self.listen_key);
-        }
+        self.port.release(self.listen_key);
     }
 }
 
@@ -294,46 +353,84 @@ pub(crate) async fn acquire(
         }
     };
 
-    let local_addr = port
-        .socket
-        .local_addr()
-        .context("shared UDP socket local_addr")?;
+    port.register(Some(bind_addr), local_ufrag)
+}
 
-    // Reject a duplicate ufrag registration on the same shared socket — each
-    // PeerConnection must own a unique ufrag so demuxing is unambiguous.
-    if port.sessions.lock().contains_key(&local_ufrag) {
-        bail!("ufrag {local_ufrag} already registered on shared UDP socket {bind_addr}");
+/// A UDP socket shared by several PeerConnections (single-port multiplexing).
+///
+/// Bind it once and pass clones to each PeerConnection through
+/// `RtcConfiguration::udp_mux`. Every PeerConnection then gathers a single host
+/// candidate on this socket; incoming packets are demultiplexed by the ICE
+/// ufrag of STUN Binding Requests and afterwards by the remote address. The
+/// socket is closed once the last clone and the last PeerConnection using it
+/// are dropped.
+#[derive(Clone)]
+pub struct UdpMux {
+    owner: Arc<UdpMuxOwner>,
+}
+
+/// Holds one reference on the port for as long as any [`UdpMux`] clone lives.
+struct UdpMuxOwner {
+    port: Arc<SharedUdpPort>,
+    local_addr: SocketAddr,
+}
+
+impl Drop for UdpMuxOwner {
+    fn drop(&mut self) {
+        self.port.release(None);
     }
+}
 
-    let (tx, rx) = mpsc::channel(SHARED_UDP_CHANNEL_CAPACITY);
-    port.ref_count.fetch_add(1, Ordering::SeqCst);
-    port.sessions
-        .lock()
-        .insert(local_ufrag.clone(), Session { tx });
+impl UdpMux {
+    /// Bind the shared socket at `addr` and start demultiplexing.
+    pub async fn bind(addr: SocketAddr) -> Result<Self> {
+        let socket = UdpSocket::bind(addr)
+            .await
+            .with_context(|| format!("bind UDP mux socket {addr}"))?;
+        let local_addr = socket.local_addr().context("UDP mux local_addr")?;
+        let port = Arc::new(SharedUdpPort::new(Arc::new(socket)));
+        port.ref_count.fetch_add(1, Ordering::SeqCst);
+        port.spawn_recv_loop();
+        Ok(Self {
+            owner: Arc::new(UdpMuxOwner { port, local_addr }),
+        })
+    }
 
-    let handle = SharedUdpHandle {
-        socket: port.socket.clone(),
-        rx: Arc::new(tokio::sync::Mutex::new(rx)),
-        peers: port.peers.clone(),
-        ufrag: local_ufrag.clone(),
-    };
+    /// The address the shared socket is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.owner.local_addr
+    }
+
+    /// Number of PeerConnections currently registered on this socket.
+    pub fn session_count(&self) -> usize {
+        self.owner.port.sessions.lock().len()
+    }
+
+    pub(crate) fn register(
+        &self,
+        local_ufrag: String,
+    ) -> Result<(SocketAddr, SharedUdpHandle, SharedUdpRegistration)> {
+        self.owner.port.register(None, local_ufrag)
+    }
+}
 
-    if local_addr.ip().is_unspecified() {
-        // Sanity log; the gatherer will rewrite the advertised candidate IP.
-        trace!("shared UDP socket bound on wildcard: {}", local_addr);
+impl std::fmt::Debug for UdpMux {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UdpMux")
+            .field("local_addr", &self.owner.local_addr)
+            .field("sessions", &self.session_count())
+            .finish()
     }
+}
 
-    Ok((
-        local_addr,
-        handle,
-        SharedUdpRegistration {
-            port,
-            listen_key: bind_addr,
-            ufrag: local_ufrag,
-        },
-    ))
+impl PartialEq for UdpMux {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.owner, &other.owner)
+    }
 }
 
+impl Eq for UdpMux {}
+
 /// Test helper: return the number of sessions currently registered on a shared
 /// socket bound at `bind_addr` (0 if none).
 #[cfg(test)]
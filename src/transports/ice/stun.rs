@@ -352,11 +352,9 @@ fn decode_stun_message(bytes: &[u8]) -> Result<StunDecoded> {
                     xor_peer_address = Some(addr);
                 }
             }
-            0x0009 => {
-                if value.len() >= 4 {
-                    let code = (value[2] as u16) * 100 + value[3] as u16;
-                    error_code = Some(code);
-                }
+            0x0009 if value.len() >= 4 => {
+                let code = (value[2] as u16) * 100 + value[3] as u16;
+                error_code = Some(code);
             }
             0x0014 => {
                 if let Ok(text) = std::str::from_utf8(value) {
@@ -371,11 +369,9 @@ fn decode_stun_message(bytes: &[u8]) -> Result<StunDecoded> {
             0x0013 => {
                 data = Some(value.to_vec());
             }
-            0x000D => {
+            0x000D if value.len() >= 4 => {
                 // LIFETIME (RFC 5766): 4-byte big-endian seconds.
-                if value.len() >= 4 {
-                    lifetime = Some(u32::from_be_bytes([value[0], value[1], value[2], value[3]]));
-                }
+                lifetime = Some(u32::from_be_bytes([value[0], value[1], value[2], value[3]]));
             }
             0x0025 => {
                 use_candidate = true;
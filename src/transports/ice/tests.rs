@@ -43,6 +43,51 @@ async fn builder_starts_gathering() {
     ));
 }
 
+#[tokio::test]
+async fn cancel_gathering_releases_stun_probe_socket() -> Result<()> {
+    // A STUN server that never answers keeps the probe waiting for the whole
+    // stun_timeout unless gathering is cancelled.
+    let stun_server = tokio::net::UdpSocket::bind("127.0.0.1:0").await?;
+    let stun_url = format!("stun:{}", stun_server.local_addr()?);
+    let mut config = RtcConfiguration::default();
+    config.stun_timeout = Duration::from_secs(30);
+    config
+        .ice_servers
+        .push(IceServer::new(vec![stun_url.clone()]));
+
+    let (transport, runner) = IceTransport::new(config);
+    tokio::spawn(runner);
+    let mut gathering = transport.subscribe_gathering_state();
+    transport.start_gathering()?;
+
+    let mut buf = [0u8; 1500];
+    let (_, probe_addr) =
+        timeout(Duration::from_secs(5), stun_server.recv_from(&mut buf)).await??;
+    assert_eq!(transport.pending_gathering_probes(), vec![stun_url]);
+
+    transport.cancel_gathering();
+    timeout(
+        Duration::from_secs(1),
+        gathering.wait_for(|s| *s == IceGathererState::Complete),
+    )
+    .await??;
+    assert!(transport.pending_gathering_probes().is_empty());
+    assert_eq!(transport.gather_state(), IceGathererState::Complete);
+
+    // The probe socket is closed, so its address can be bound again.
+    let rebound = timeout(Duration::from_secs(1), async {
+        loop {
+            if let Ok(socket) = tokio::net::UdpSocket::bind(probe_addr).await {
+                return socket;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    assert!(rebound.is_ok(), "probe socket {probe_addr} still bound");
+    Ok(())
+}
+
 #[tokio::test]
 async fn stun_probe_yields_server_reflexive_candidate() -> Result<()> {
     let mut turn_server = TestTurnServer::start().await?;
@@ -137,6 +182,122 @@ async fn turn_probe_yields_relay_candidate() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[serial]
+async fn turn_refresh_interval_follows_granted_lifetime() -> Result<()> {
+    let mut turn_server = TestTurnServer::start().await?;
+    let mut config = RtcConfiguration::default();
+    config.ice_servers.push(
+        IceServer::new(vec![turn_server.turn_url()]).with_credential(TEST_USERNAME, TEST_PASSWORD),
+    );
+    let (tx, _) = broadcast::channel(100);
+    let (socket_tx, _) = tokio::sync::mpsc::unbounded_channel();
+    let gatherer = IceGatherer::new(config, tx, socket_tx);
+    gatherer.gather().await?;
+    let client = gatherer
+        .turn_clients
+        .lock()
+        .values()
+        .next()
+        .cloned()
+        .expect("should have TurnClient for relay");
+
+    // A 600 s allocation is refreshed on the regular period.
+    assert!(client.lifetime_secs() >= 2 * TURN_REFRESH_INTERVAL.as_secs() as u32);
+    assert_eq!(gatherer.turn_refresh_interval(), TURN_REFRESH_INTERVAL);
+
+    // A short grant is refreshed at half its lifetime.
+    client.set_lifetime(20);
+    assert_eq!(gatherer.turn_refresh_interval(), Duration::from_secs(10));
+    client.set_lifetime(1);
+    assert_eq!(gatherer.turn_refresh_interval(), Duration::from_secs(1));
+
+    turn_server.stop().await?;
+    Ok(())
+}
+
+/// A Refresh round trip records the lifetime the server grants, restoring
+/// the regular refresh period after a short grant.
+#[tokio::test]
+#[serial]
+async fn turn_refresh_records_granted_lifetime() -> Result<()> {
+    let mut turn_server = TestTurnServer::start().await?;
+    let uri = IceServerUri::parse(&turn_server.turn_url())?;
+    let server =
+        IceServer::new(vec![turn_server.turn_url()]).with_credential(TEST_USERNAME, TEST_PASSWORD);
+    let client = Arc::new(TurnClient::connect(&uri, false).await?);
+    let alloc = client
+        .allocate(TurnCredentials::from_server(&server)?)
+        .await?;
+
+    let mut config = RtcConfiguration::default();
+    config.ice_servers.push(server);
+    let (transport, runner) = IceTransport::new(config);
+    tokio::spawn(runner);
+    transport
+        .inner
+        .gatherer
+        .turn_clients
+        .lock()
+        .insert(alloc.relayed_address, client.clone());
+    let inner = transport.inner.clone();
+    let reader = client.clone();
+    tokio::spawn(async move {
+        IceTransportRunner::run_turn_read_loop(reader, alloc.relayed_address, inner).await;
+    });
+    let _ = transport.inner.state.send(IceTransportState::Connected);
+
+    client.set_lifetime(20);
+    assert_eq!(
+        transport.inner.gatherer.turn_refresh_interval(),
+        Duration::from_secs(10)
+    );
+
+    timeout(
+        Duration::from_secs(4),
+        IceTransportRunner::run_turn_refresh(&transport.inner),
+    )
+    .await?;
+    assert_eq!(client.lifetime_secs(), turn::DEFAULT_TURN_LIFETIME);
+    assert_eq!(
+        transport.inner.gatherer.turn_refresh_interval(),
+        TURN_REFRESH_INTERVAL
+    );
+
+    turn_server.stop().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn candidate_type_preferences_sort_relay_first() -> Result<()> {
+    let mut turn_server = TestTurnServer::start().await?;
+    let mut config = RtcConfiguration::default();
+    config.candidate_type_preferences = CandidateTypePreferences {
+        host: 0,
+        relay: 126,
+        ..Default::default()
+    };
+    config.ice_servers.push(
+        IceServer::new(vec![turn_server.turn_url()]).with_credential(TEST_USERNAME, TEST_PASSWORD),
+    );
+    let (tx, _) = broadcast::channel(100);
+    let (socket_tx, _) = tokio::sync::mpsc::unbounded_channel();
+    let gatherer = IceGatherer::new(config, tx, socket_tx);
+    gatherer.gather().await?;
+    let candidates = gatherer.local_candidates();
+    assert!(candidates.iter().any(|c| c.typ == IceCandidateType::Host));
+    assert_eq!(candidates[0].typ, IceCandidateType::Relay);
+    assert_eq!(candidates[0].priority >> 24, 126);
+    assert!(
+        candidates
+            .windows(2)
+            .all(|w| w[0].priority >= w[1].priority)
+    );
+    turn_server.stop().await?;
+    Ok(())
+}
+
 #[tokio::test]
 async fn policy_relay_only_gathers_relay_candidates() -> Result<()> {
     let mut turn_server = TestTurnServer::start().await?;
@@ -386,6 +547,147 @@ async fn test_ice_connection_timeout() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_ice_checking_timeout_on_unreachable_candidates() -> Result<()> {
+    let config = RtcConfigurationBuilder::new()
+        .ice_checking_timeout(Duration::from_millis(200))
+        .build();
+
+    let (transport, runner) = IceTransportBuilder::new(config).build();
+    tokio::spawn(runner);
+
+    // Host candidates on a TEST-NET range that no local interface can reach.
+    transport.add_remote_candidate(IceCandidate::host("192.0.2.1:9".parse().unwrap(), 1));
+    transport.add_remote_candidate(IceCandidate::host("198.51.100.1:9".parse().unwrap(), 1));
+    transport.start(IceParameters::new("remote_ufrag", "remote_pwd_12345"))?;
+
+    let mut state_rx = transport.subscribe_state();
+    timeout(Duration::from_secs(5), async {
+        while *state_rx.borrow_and_update() != IceTransportState::Failed {
+            state_rx.changed().await.unwrap();
+        }
+    })
+    .await?;
+    assert!(transport.selected_pair().is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ice_fails_once_check_list_is_exhausted() -> Result<()> {
+    // The checking timeout alone would keep the agent waiting for a minute.
+    let config = RtcConfigurationBuilder::new()
+        .stun_timeout(Duration::from_millis(300))
+        .ice_checking_timeout(Duration::from_secs(60))
+        .build();
+
+    let (transport, runner) = IceTransportBuilder::new(config).build();
+    tokio::spawn(runner);
+
+    transport.add_remote_candidate(IceCandidate::host("192.0.2.1:9".parse().unwrap(), 1));
+    transport.add_remote_end_of_candidates();
+    transport.start(IceParameters::new("remote_ufrag", "remote_pwd_12345"))?;
+
+    let mut state_rx = transport.subscribe_state();
+    timeout(Duration::from_secs(10), async {
+        while *state_rx.borrow_and_update() != IceTransportState::Failed {
+            state_rx.changed().await.unwrap();
+        }
+    })
+    .await?;
+    assert!(transport.selected_pair().is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_ice_keeps_checking_for_trickled_candidates_after_pairs_fail() -> Result<()> {
+    let config = RtcConfigurationBuilder::new()
+        .bind_ip("127.0.0.1".to_string())
+        .stun_timeout(Duration::from_millis(300))
+        .ice_checking_timeout(Duration::from_secs(60))
+        .build();
+    let (controlling, runner_c) = IceTransportBuilder::new(config.clone())
+        .role(IceRole::Controlling)
+        .build();
+    tokio::spawn(runner_c);
+    let (controlled, runner_d) = IceTransportBuilder::new(config)
+        .role(IceRole::Controlled)
+        .build();
+    tokio::spawn(runner_d);
+
+    // The only pair known at first has nobody listening and fails; without
+    // end-of-candidates the agent must keep waiting for more.
+    controlling.add_remote_candidate(IceCandidate::host("127.0.0.1:9".parse().unwrap(), 1));
+    controlling.start(controlled.local_parameters())?;
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(controlling.state(), IceTransportState::Checking);
+
+    for c in controlling.local_candidates() {
+        controlled.add_remote_candidate(c);
+    }
+    controlled.start(controlling.local_parameters())?;
+    for c in controlled.local_candidates() {
+        controlling.add_remote_candidate(c);
+    }
+
+    assert!(wait_ice_connected(controlling.subscribe_state(), Duration::from_secs(10)).await);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pair_checks_are_dropped_on_restart_and_stop() -> Result<()> {
+    use crate::stats::StatsProvider;
+
+    let config = RtcConfigurationBuilder::new()
+        .bind_ip("127.0.0.1".to_string())
+        .stun_timeout(Duration::from_millis(300))
+        .build();
+    let (transport, runner) = IceTransportBuilder::new(config).build();
+    tokio::spawn(runner);
+
+    let remote = IceCandidate::host("127.0.0.1:9".parse().unwrap(), 1);
+    transport.add_remote_candidate(remote.clone());
+    transport.start(IceParameters::new("remote_ufrag", "remote_pwd_12345"))?;
+    timeout(Duration::from_secs(5), async {
+        while transport.collect().await.unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+
+    // The check still in flight must not bring its pair back when it fails.
+    transport.restart();
+    tokio::time::sleep(Duration::from_millis(800)).await;
+    assert!(transport.collect().await?.is_empty());
+
+    transport.add_remote_candidate(remote);
+    transport.start(IceParameters::new("remote_ufrag2", "remote_pwd_67890"))?;
+    timeout(Duration::from_secs(5), async {
+        while transport.collect().await.unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+
+    transport.stop();
+    assert!(transport.collect().await?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_ice_restart_detected_from_ufrag_or_password() {
+    let current = IceParameters::new("ufrag", "password_1234567890");
+    let same = IceParameters::new("ufrag", "password_1234567890");
+    let new_ufrag = IceParameters::new("ufrag2", "password_1234567890");
+    let new_password = IceParameters::new("ufrag", "password_0987654321");
+    assert!(!same.is_restart_of(&current));
+    assert!(new_ufrag.is_restart_of(&current));
+    assert!(new_password.is_restart_of(&current));
+}
+
 const TEST_USERNAME: &str = "test";
 const TEST_PASSWORD: &str = "test";
 const TEST_REALM: &str = ".turn";
@@ -1386,6 +1688,43 @@ async fn test_ice_connection_without_external_ip() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[serial]
+async fn selected_pair_reports_loopback_host_candidates() -> Result<()> {
+    let config = RtcConfiguration {
+        bind_ip: Some("127.0.0.1".to_string()),
+        ..Default::default()
+    };
+    let (controlling, controlled) = setup_host_pair(config.clone(), config).await;
+    assert!(controlling.selected_pair().is_none());
+
+    let (ok1, ok2) = tokio::join!(
+        wait_ice_connected(controlling.subscribe_state(), Duration::from_secs(10)),
+        wait_ice_connected(controlled.subscribe_state(), Duration::from_secs(10)),
+    );
+    assert!(ok1 && ok2, "loopback agents should connect");
+
+    let host = |transport: &IceTransport| {
+        let candidates = transport.local_candidates();
+        assert_eq!(candidates.len(), 1, "{candidates:?}");
+        assert_eq!(candidates[0].typ, IceCandidateType::Host);
+        candidates[0].address
+    };
+    let (controlling_host, controlled_host) = (host(&controlling), host(&controlled));
+    assert!(controlling_host.ip().is_loopback());
+
+    let (local, remote) = controlling.selected_pair().unwrap();
+    assert_eq!(local.address, controlling_host);
+    assert_eq!(remote.address, controlled_host);
+    assert_eq!(remote.typ, IceCandidateType::Host);
+
+    let (local, remote) = controlled.selected_pair().unwrap();
+    assert_eq!(local.address, controlled_host);
+    assert_eq!(remote.address, controlling_host);
+
+    Ok(())
+}
+
 #[tokio::test]
 #[serial]
 async fn test_nomination_delayed_by_dtls_socket_contention() -> Result<()> {
@@ -2957,6 +3296,55 @@ fn test_ice_tcp_candidate_sdp_roundtrip() {
     assert_eq!(parsed_so.tcp_type, Some(TcpType::So));
 }
 
+/// Chrome-style candidate lines keep every extension through a parse and
+/// re-serialize cycle.
+#[test]
+fn test_ice_candidate_extensions_roundtrip() {
+    let srflx = "candidate:842163049 1 udp 1677729535 203.0.113.7 54400 typ srflx \
+                 raddr 192.168.1.2 rport 54401 generation 0 ufrag EsAw network-id 1 network-cost 10";
+    let parsed = IceCandidate::from_sdp(srflx).unwrap();
+    assert_eq!(parsed.typ, IceCandidateType::ServerReflexive);
+    assert_eq!(
+        parsed.related_address,
+        Some("192.168.1.2:54401".parse().unwrap())
+    );
+    assert_eq!(parsed.tcp_type, None);
+    assert_eq!(parsed.generation, Some(0));
+    assert_eq!(parsed.ufrag.as_deref(), Some("EsAw"));
+    assert_eq!(parsed.network_id, Some(1));
+    assert_eq!(parsed.network_cost, Some(10));
+    assert!(parsed.extensions.is_empty());
+    assert_eq!(format!("candidate:{}", parsed.to_sdp()), srflx);
+
+    let tcp = "candidate:1052210311 1 tcp 1518280447 192.168.1.2 9 typ host \
+               tcptype active generation 3 ufrag EsAw network-id 2 network-cost 50 x-custom keep";
+    let parsed = IceCandidate::from_sdp(tcp).unwrap();
+    assert_eq!(parsed.tcp_type, Some(TcpType::Active));
+    assert_eq!(parsed.related_address, None);
+    assert_eq!(parsed.generation, Some(3));
+    assert_eq!(parsed.network_id, Some(2));
+    assert_eq!(parsed.network_cost, Some(50));
+    assert_eq!(
+        parsed.extensions,
+        vec![("x-custom".to_string(), "keep".to_string())]
+    );
+    assert_eq!(format!("candidate:{}", parsed.to_sdp()), tcp);
+
+    // A related address that is not an IP (e.g. mDNS) leaves rport intact.
+    let mdns = "candidate:842163049 1 udp 1677729535 203.0.113.7 54400 typ srflx \
+                raddr 4f3c.local rport 54401 generation 0";
+    let parsed = IceCandidate::from_sdp(mdns).unwrap();
+    assert_eq!(parsed.related_address, None);
+    assert_eq!(
+        parsed.extensions,
+        vec![
+            ("raddr".to_string(), "4f3c.local".to_string()),
+            ("rport".to_string(), "54401".to_string()),
+        ]
+    );
+    assert_eq!(format!("candidate:{}", parsed.to_sdp()), mdns);
+}
+
 /// Test that TCP candidates get higher local preference for passive type.
 #[test]
 fn test_ice_tcp_priority_ordering() {
@@ -3807,6 +4195,7 @@ async fn test_handle_packet_dispatches_error_response_to_pending_transactions()
         transport.inner.clone(),
         sender,
         &mut marshal_buf,
+        &mut ReceiveBufferPool::new(0),
     )
     .await;
 
@@ -3856,6 +4245,7 @@ async fn test_handle_packet_dispatches_401_to_pending_transactions() {
         transport.inner.clone(),
         sender,
         &mut marshal_buf,
+        &mut ReceiveBufferPool::new(0),
     )
     .await;
 
@@ -3900,6 +4290,7 @@ async fn test_handle_packet_ignores_unmatched_error_response() {
         transport.inner.clone(),
         sender,
         &mut marshal_buf,
+        &mut ReceiveBufferPool::new(0),
     )
     .await;
 
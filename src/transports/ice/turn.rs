@@ -3,6 +3,7 @@ use md5::{Digest as Md5Digest, Md5};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::{TcpStream, UdpSocket};
@@ -14,6 +15,9 @@ use super::{IceServerUri, IceTransportProtocol, MAX_STUN_MESSAGE};
 use crate::{IceCredentialType, IceServer};
 
 pub const DEFAULT_TURN_LIFETIME: u32 = 600;
+/// Refresh period for TURN allocations, permissions and channel bindings,
+/// shortened for allocations granted a lifetime under twice this.
+pub const TURN_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(25);
 pub const DEFAULT_STUN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
 
 #[derive(Debug, Clone)]
@@ -46,6 +50,8 @@ pub struct TurnClient {
     channels: Mutex<HashMap<SocketAddr, u16>>,
     channel_map: Mutex<HashMap<u16, SocketAddr>>,
     next_channel: Mutex<u16>,
+    /// Allocation lifetime last granted by the server, in seconds.
+    lifetime_secs: AtomicU32,
 }
 
 #[derive(Clone, Debug)]
@@ -110,6 +116,7 @@ impl TurnClient {
             channels: Mutex::new(HashMap::new()),
             channel_map: Mutex::new(HashMap::new()),
             next_channel: Mutex::new(0x4000),
+            lifetime_secs: AtomicU32::new(DEFAULT_TURN_LIFETIME),
         })
     }
 
@@ -166,6 +173,7 @@ impl TurnClient {
                             .lifetime
                             .filter(|&l| l > 0)
                             .unwrap_or(DEFAULT_TURN_LIFETIME);
+                        self.set_lifetime(granted_lifetime);
                         return Ok(TurnAllocation {
                             relayed_address: relayed,
                             transport: self.transport.protocol(),
@@ -314,6 +322,23 @@ impl TurnClient {
         }
     }
 
+    /// Record the allocation lifetime granted by an Allocate or Refresh
+    /// response.
+    pub(crate) fn set_lifetime(&self, lifetime_secs: u32) {
+        self.lifetime_secs.store(lifetime_secs, Ordering::Relaxed);
+    }
+
+    pub(crate) fn lifetime_secs(&self) -> u32 {
+        self.lifetime_secs.load(Ordering::Relaxed)
+    }
+
+    /// How often the allocation must be refreshed: [`TURN_REFRESH_INTERVAL`],
+    /// or half the granted lifetime when that is shorter.
+    pub(crate) fn refresh_interval(&self) -> std::time::Duration {
+        let half_lifetime = (self.lifetime_secs() / 2).max(1);
+        TURN_REFRESH_INTERVAL.min(std::time::Duration::from_secs(half_lifetime.into()))
+    }
+
     pub(crate) async fn bound_peers(&self) -> Vec<SocketAddr> {
         self.channels.lock().await.keys().cloned().collect()
     }
@@ -1,22 +1,44 @@
-use crate::rtp::{RtcpPacket, RtpPacket, is_rtcp, marshal_rtcp_packets, parse_rtcp_packets};
+use crate::config::UnsignaledSsrcPolicy;
+use crate::errors::RtcResult;
+use crate::media::pipeline::TimestampNormalizer;
+use crate::rtp::{
+    RtcpPacket, RtpHeader, RtpPacket, TransportWideCc, is_rtcp, marshal_rtcp_packets,
+    parse_rtcp_packets,
+};
 use crate::srtp::SrtpSession;
+use crate::stats::{StatsEntry, StatsId, StatsKind, StatsProvider};
 use crate::transports::PacketReceiver;
 use crate::transports::ice::conn::IceConn;
 use crate::transports::ice::stun::random_u32;
+use crate::twcc::{SendSideBandwidthEstimator, TransportCcRecorder};
 use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
 use parking_lot::Mutex;
+use serde_json::json;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU16, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{info, trace, warn};
 
 const EXT_ID_NONE: u8 = 0;
 
+/// Target of the per-packet trace events emitted at the RTP send/receive
+/// boundaries; enable with `RUST_LOG=rustrtc::rtp=trace`. Fields are plain
+/// integers so disabled events cost a level check and nothing else.
+pub const PACKET_TRACE_TARGET: &str = "rustrtc::rtp";
+
+/// How long RTP that matched no listener is kept for a late listener.
+const EARLY_RTP_WINDOW: Duration = Duration::from_millis(500);
+/// Early RTP packets kept per source address.
+const EARLY_RTP_MAX_PER_SOURCE: usize = 64;
+/// Distinct source addresses with early RTP kept at once.
+const EARLY_RTP_MAX_SOURCES: usize = 8;
+
 #[inline]
 fn encode_ext_id(id: Option<u8>) -> u8 {
     id.unwrap_or(EXT_ID_NONE)
@@ -226,6 +248,21 @@ impl ListenerRegistry {
         selected.cloned()
     }
 
+    /// First PT-matching or provisional route, in registration order, whose
+    /// listener has not been bound to any SSRC yet.
+    fn first_unbound(&self, pt: u8) -> Option<mpsc::Sender<(RtpPacket, SocketAddr)>> {
+        self.routes
+            .iter()
+            .filter(|route| route.provisional || route.payload_types.contains(&pt))
+            .find(|route| {
+                !self
+                    .by_ssrc
+                    .values()
+                    .any(|existing| existing.same_channel(&route.tx))
+            })
+            .map(|route| route.tx.clone())
+    }
+
     fn bind_ssrc_route(&mut self, ssrc: u32, tx: mpsc::Sender<(RtpPacket, SocketAddr)>) {
         self.by_ssrc.insert(ssrc, tx);
     }
@@ -239,6 +276,48 @@ impl ListenerRegistry {
     }
 }
 
+/// RTP that arrived before any listener could take it, keyed by source
+/// address so one noisy sender cannot evict another's packets.
+#[derive(Default)]
+struct EarlyRtpBuffer {
+    by_source: HashMap<SocketAddr, VecDeque<(Instant, RtpPacket)>>,
+}
+
+impl EarlyRtpBuffer {
+    fn expire(&mut self, now: Instant) {
+        self.by_source.retain(|_, queue| {
+            while queue
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > EARLY_RTP_WINDOW)
+            {
+                queue.pop_front();
+            }
+            !queue.is_empty()
+        });
+    }
+
+    fn push(&mut self, packet: RtpPacket, addr: SocketAddr, at: Instant) {
+        self.expire(Instant::now());
+        if !self.by_source.contains_key(&addr) && self.by_source.len() >= EARLY_RTP_MAX_SOURCES {
+            trace!("Early RTP buffer full, dropping packet from {}", addr);
+            return;
+        }
+        let queue = self.by_source.entry(addr).or_default();
+        if queue.len() >= EARLY_RTP_MAX_PER_SOURCE {
+            queue.pop_front();
+        }
+        queue.push_back((at, packet));
+    }
+
+    fn drain(&mut self) -> Vec<(Instant, RtpPacket, SocketAddr)> {
+        self.expire(Instant::now());
+        self.by_source
+            .drain()
+            .flat_map(|(addr, queue)| queue.into_iter().map(move |(at, p)| (at, p, addr)))
+            .collect()
+    }
+}
+
 pub struct RtpTransport {
     transport: Arc<IceConn>,
     srtp_session: Mutex<Option<Arc<Mutex<SrtpSession>>>>,
@@ -247,6 +326,15 @@ pub struct RtpTransport {
     rid_extension_id: AtomicU8,
     sdes_mid_extension_id: AtomicU8,
     abs_send_time_extension_id: AtomicU8,
+    transport_cc_extension_id: AtomicU8,
+    /// Next transport-wide sequence number stamped on outgoing RTP.
+    transport_cc_sequence: AtomicU16,
+    transport_cc_recorder: Mutex<TransportCcRecorder>,
+    /// Sender SSRC of transport-wide RTCP feedback: the first local sender
+    /// attached, or a random one until then.
+    rtcp_sender_ssrc: AtomicU32,
+    has_local_sender: AtomicBool,
+    bandwidth_estimator: Mutex<SendSideBandwidthEstimator>,
     rewrite_bridge: Mutex<Option<Box<RewriteBridge>>>,
     has_bridge: AtomicBool,
     srtp_required: bool,
@@ -257,6 +345,15 @@ pub struct RtpTransport {
     /// fast-path, listener/track chain) share, so it can be polled to detect
     /// RTP inactivity regardless of the active forwarding mode.
     received_rtp_packets: AtomicU64,
+    /// When set, RTP with no matching listener is held briefly and replayed
+    /// once a listener registers (see `set_early_rtp_buffering`).
+    buffer_early_rtp: AtomicBool,
+    early_rtp: Mutex<EarlyRtpBuffer>,
+    /// `UnsignaledSsrcPolicy::Latch`: PT/provisional fallback routes adopt
+    /// one SSRC each instead of accepting any.
+    latch_unsignaled_ssrc: AtomicBool,
+    /// Deliver `RtcpPacket::Unknown` (APP, XR, ...) to the RTCP listener.
+    forward_unknown_rtcp: AtomicBool,
 }
 
 impl RtpTransport {
@@ -277,14 +374,46 @@ impl RtpTransport {
             rid_extension_id: AtomicU8::new(EXT_ID_NONE),
             sdes_mid_extension_id: AtomicU8::new(EXT_ID_NONE),
             abs_send_time_extension_id: AtomicU8::new(EXT_ID_NONE),
+            transport_cc_extension_id: AtomicU8::new(EXT_ID_NONE),
+            transport_cc_sequence: AtomicU16::new(0),
+            transport_cc_recorder: Mutex::new(TransportCcRecorder::new()),
+            rtcp_sender_ssrc: AtomicU32::new(random_u32()),
+            has_local_sender: AtomicBool::new(false),
+            bandwidth_estimator: Mutex::new(SendSideBandwidthEstimator::new()),
             rewrite_bridge: Mutex::new(None),
             has_bridge: AtomicBool::new(false),
             srtp_required,
             has_sent_first_packet: AtomicBool::new(false),
             received_rtp_packets: AtomicU64::new(0),
+            buffer_early_rtp: AtomicBool::new(false),
+            early_rtp: Mutex::new(EarlyRtpBuffer::default()),
+            latch_unsignaled_ssrc: AtomicBool::new(false),
+            forward_unknown_rtcp: AtomicBool::new(false),
         }
     }
 
+    pub fn set_unsignaled_ssrc_policy(&self, policy: UnsignaledSsrcPolicy) {
+        self.latch_unsignaled_ssrc
+            .store(policy == UnsignaledSsrcPolicy::Latch, Ordering::Relaxed);
+    }
+
+    /// Hold RTP that arrives before any listener matches it (bounded per
+    /// source and to a short window) and deliver it when a listener
+    /// registers. Plain RTP peers may start sending before our receivers
+    /// are wired up.
+    pub fn set_early_rtp_buffering(&self, enabled: bool) {
+        self.buffer_early_rtp.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.early_rtp.lock().by_source.clear();
+        }
+    }
+
+    /// Pass RTCP this crate does not interpret to the RTCP listener as
+    /// `RtcpPacket::Unknown`. Off by default, in which case it is dropped.
+    pub fn set_forward_unknown_rtcp(&self, enabled: bool) {
+        self.forward_unknown_rtcp.store(enabled, Ordering::Relaxed);
+    }
+
     /// Cumulative count of inbound RTP packets accepted at the transport
     /// layer. Monotonically increasing; safe to poll concurrently.
     pub fn received_rtp_packets(&self) -> u64 {
@@ -301,8 +430,8 @@ impl RtpTransport {
     }
 
     pub fn register_listener_sync(&self, ssrc: u32, tx: mpsc::Sender<(RtpPacket, SocketAddr)>) {
-        let mut listeners = self.listeners.lock();
-        listeners.by_ssrc.insert(ssrc, tx);
+        self.listeners.lock().by_ssrc.insert(ssrc, tx);
+        self.flush_early_rtp();
     }
 
     pub fn has_listener(&self, ssrc: u32) -> bool {
@@ -311,18 +440,18 @@ impl RtpTransport {
     }
 
     pub fn register_rid_listener(&self, rid: String, tx: mpsc::Sender<(RtpPacket, SocketAddr)>) {
-        let mut listeners = self.listeners.lock();
-        listeners.by_rid.insert(rid, tx);
+        self.listeners.lock().by_rid.insert(rid, tx);
+        self.flush_early_rtp();
     }
 
     pub fn register_mid_listener(&self, mid: String, tx: mpsc::Sender<(RtpPacket, SocketAddr)>) {
-        let mut listeners = self.listeners.lock();
-        listeners.register_mid(mid, tx);
+        self.listeners.lock().register_mid(mid, tx);
+        self.flush_early_rtp();
     }
 
     pub fn register_pt_listener(&self, pt: u8, tx: mpsc::Sender<(RtpPacket, SocketAddr)>) {
-        let mut listeners = self.listeners.lock();
-        listeners.register_payload_type(pt, tx);
+        self.listeners.lock().register_payload_type(pt, tx);
+        self.flush_early_rtp();
     }
 
     pub fn register_payload_list_listener(
@@ -330,13 +459,15 @@ impl RtpTransport {
         payload_types: Vec<u8>,
         tx: mpsc::Sender<(RtpPacket, SocketAddr)>,
     ) {
-        let mut listeners = self.listeners.lock();
-        listeners.register_payload_types(payload_types, tx);
+        self.listeners
+            .lock()
+            .register_payload_types(payload_types, tx);
+        self.flush_early_rtp();
     }
 
     pub fn register_provisional_listener(&self, tx: mpsc::Sender<(RtpPacket, SocketAddr)>) {
-        let mut listeners = self.listeners.lock();
-        listeners.register_provisional(tx);
+        self.listeners.lock().register_provisional(tx);
+        self.flush_early_rtp();
     }
 
     pub fn set_rid_extension_id(&self, id: Option<u8>) {
@@ -354,6 +485,50 @@ impl RtpTransport {
             .store(encode_ext_id(id), Ordering::Relaxed);
     }
 
+    pub fn set_transport_cc_extension_id(&self, id: Option<u8>) {
+        self.transport_cc_extension_id
+            .store(encode_ext_id(id), Ordering::Relaxed);
+    }
+
+    /// Transport-cc feedback for everything received since the last call, or
+    /// `None` when transport-cc is not negotiated or nothing arrived.
+    pub fn take_transport_cc_feedback(&self) -> Option<TransportWideCc> {
+        decode_ext_id(self.transport_cc_extension_id.load(Ordering::Relaxed))?;
+        self.transport_cc_recorder
+            .lock()
+            .build_feedback(self.rtcp_sender_ssrc())
+    }
+
+    /// Report transport-wide feedback from `ssrc` unless a local sender was
+    /// attached before. Called for every sender using this transport.
+    pub fn adopt_rtcp_sender_ssrc(&self, ssrc: u32) {
+        if !self.has_local_sender.swap(true, Ordering::Relaxed) {
+            self.rtcp_sender_ssrc.store(ssrc, Ordering::Relaxed);
+        }
+    }
+
+    pub fn rtcp_sender_ssrc(&self) -> u32 {
+        self.rtcp_sender_ssrc.load(Ordering::Relaxed)
+    }
+
+    fn next_transport_cc_sequence(&self) -> Option<(u8, u16)> {
+        let id = decode_ext_id(self.transport_cc_extension_id.load(Ordering::Relaxed))?;
+        let sequence = self.transport_cc_sequence.fetch_add(1, Ordering::Relaxed);
+        Some((id, sequence))
+    }
+
+    /// Feed transport-cc feedback from the remote to the send-side
+    /// bandwidth estimator.
+    pub fn on_transport_cc_feedback(&self, feedback: &TransportWideCc) {
+        self.bandwidth_estimator.lock().on_feedback(feedback);
+    }
+
+    /// Send-side bandwidth estimate in bits per second, once transport-cc
+    /// feedback made one possible.
+    pub fn bandwidth_estimate_bps(&self) -> Option<u64> {
+        self.bandwidth_estimator.lock().estimate_bps()
+    }
+
     /// Returns the remote peer's socket address (the nominated ICE candidate
     /// or the configured RTP destination).
     pub fn remote_addr(&self) -> std::net::SocketAddr {
@@ -397,9 +572,19 @@ impl RtpTransport {
                     let data = abs_send_time.to_be_bytes();
                     packet.header.set_extension(id, &data[1..4])?;
                 }
+                let transport_cc_sequence = self.next_transport_cc_sequence();
+                if let Some((id, sequence)) = transport_cc_sequence {
+                    packet.header.set_extension(id, &sequence.to_be_bytes())?;
+                }
 
                 srtp.protect_rtp(&mut packet)?;
-                packet.marshal()?
+                let protected = packet.marshal()?;
+                if let Some((_, sequence)) = transport_cc_sequence {
+                    self.bandwidth_estimator
+                        .lock()
+                        .on_packet_sent(sequence, protected.len());
+                }
+                protected
             } else {
                 if self.srtp_required {
                     return Err(anyhow::anyhow!("SRTP required but session not ready"));
@@ -407,7 +592,7 @@ impl RtpTransport {
                 buf.to_vec()
             }
         };
-        self.transport.send(&protected).await
+        Ok(self.transport.send(&protected).await?)
     }
 
     pub async fn send_rtp(&self, mut packet: RtpPacket) -> Result<usize> {
@@ -425,6 +610,18 @@ impl RtpTransport {
                 trace!("RtpTransport: abs-send-time extension skipped: {}", e);
             }
         }
+        let (ssrc, payload_type, sequence_number) = (
+            packet.header.ssrc,
+            packet.header.payload_type,
+            packet.header.sequence_number,
+        );
+        let mut transport_cc_sequence = None;
+        if let Some((id, sequence)) = self.next_transport_cc_sequence() {
+            match packet.header.set_extension(id, &sequence.to_be_bytes()) {
+                Ok(()) => transport_cc_sequence = Some(sequence),
+                Err(e) => trace!("RtpTransport: transport-cc extension skipped: {}", e),
+            }
+        }
 
         let protected = {
             let session_guard = self.srtp_session.lock();
@@ -440,8 +637,23 @@ impl RtpTransport {
                 packet.marshal()?
             }
         };
+        if let Some(sequence) = transport_cc_sequence {
+            self.bandwidth_estimator
+                .lock()
+                .on_packet_sent(sequence, protected.len());
+        }
         match self.transport.send(&protected).await {
             Ok(n) => {
+                trace!(
+                    target: PACKET_TRACE_TARGET,
+                    direction = "send",
+                    class = "rtp",
+                    ssrc,
+                    pt = payload_type,
+                    seq = sequence_number,
+                    size = n,
+                    "rtp packet"
+                );
                 if is_first {
                     info!(
                         "RtpTransport: first SRTP packet sent ({} bytes)",
@@ -456,7 +668,7 @@ impl RtpTransport {
                     protected.len(),
                     e
                 );
-                Err(e)
+                Err(e.into())
             }
         }
     }
@@ -477,7 +689,15 @@ impl RtpTransport {
                 raw
             }
         };
-        self.transport.send_rtcp(&protected).await
+        let n = self.transport.send_rtcp(&protected).await?;
+        trace!(
+            target: PACKET_TRACE_TARGET,
+            direction = "send",
+            class = "rtcp",
+            size = n,
+            "rtcp packet"
+        );
+        Ok(n)
     }
 
     fn try_bridge_rewrite_rtp(
@@ -554,6 +774,13 @@ impl PacketReceiver for RtpTransport {
                     packet.to_vec()
                 }
             };
+            trace!(
+                target: PACKET_TRACE_TARGET,
+                direction = "recv",
+                class = "rtcp",
+                size = packet.len(),
+                "rtcp packet"
+            );
 
             let listener = {
                 let guard = self.rtcp_listener.lock();
@@ -561,7 +788,13 @@ impl PacketReceiver for RtpTransport {
             };
             if let Some(tx) = listener {
                 match parse_rtcp_packets(&unprotected, Some(addr)) {
-                    Ok(packets) => {
+                    Ok(mut packets) => {
+                        if !self.forward_unknown_rtcp.load(Ordering::Relaxed) {
+                            packets.retain(|p| !matches!(p, RtcpPacket::Unknown(_)));
+                            if packets.is_empty() {
+                                return;
+                            }
+                        }
                         if try_send_with_fallback(&tx, packets).await.is_err() {
                             let mut guard = self.rtcp_listener.lock();
                             *guard = None;
@@ -613,11 +846,33 @@ impl PacketReceiver for RtpTransport {
                 }
             };
 
+            trace!(
+                target: PACKET_TRACE_TARGET,
+                direction = "recv",
+                class = "rtp",
+                ssrc = rtp_packet.header.ssrc,
+                pt = rtp_packet.header.payload_type,
+                seq = rtp_packet.header.sequence_number,
+                size = packet.len(),
+                "rtp packet"
+            );
+
             // Count every accepted inbound RTP packet at the transport layer.
             // This runs before the rewrite-bridge fast-path early-return, so
             // the counter advances for both relayed and depacketized packets.
             self.received_rtp_packets.fetch_add(1, Ordering::Relaxed);
 
+            if let Some(id) = decode_ext_id(self.transport_cc_extension_id.load(Ordering::Relaxed))
+                && let Some(data) = rtp_packet.header.get_extension(id)
+                && let [hi, lo, ..] = data[..]
+            {
+                self.transport_cc_recorder.lock().record(
+                    u16::from_be_bytes([hi, lo]),
+                    rtp_packet.header.ssrc,
+                    Instant::now(),
+                );
+            }
+
             let Some(rtp_packet) = self.try_bridge_rewrite_rtp(rtp_packet, marshal_buf) else {
                 return;
             };
@@ -625,72 +880,273 @@ impl PacketReceiver for RtpTransport {
             let ssrc = rtp_packet.header.ssrc;
             let pt = rtp_packet.header.payload_type;
 
-            let listener = {
-                let rid_id = decode_ext_id(self.rid_extension_id.load(Ordering::Relaxed));
-                let mid_id = decode_ext_id(self.sdes_mid_extension_id.load(Ordering::Relaxed));
-                let mut listeners = self.listeners.lock();
-                let mut selected = None;
-                let mut bind_ssrc = false;
-
-                if let Some(id) = rid_id
-                    && let Some(rid) = rtp_packet.header.get_extension(id)
-                    && let Ok(rid_str) = std::str::from_utf8(&rid)
-                {
-                    selected = listeners.by_rid.get(rid_str).cloned();
-                    bind_ssrc = selected.is_some();
-                }
+            if let Some(tx) = self.select_listener(&rtp_packet) {
+                self.deliver(&tx, rtp_packet, addr);
+            } else if self.buffer_early_rtp.load(Ordering::Relaxed) {
+                trace!(
+                    "No listener yet for packet SSRC: {} PT: {} from {}, buffering",
+                    ssrc, pt, addr
+                );
+                self.early_rtp.lock().push(rtp_packet, addr, Instant::now());
+            } else {
+                trace!(
+                    "No listener found for packet SSRC: {} PT: {} from {}",
+                    ssrc, pt, addr
+                );
+            }
+        }
+    }
+}
 
-                if selected.is_none()
-                    && let Some(id) = mid_id
-                    && let Some(mid) = rtp_packet.header.get_extension(id)
-                    && let Ok(mid_str) = std::str::from_utf8(&mid)
-                {
-                    selected = listeners.by_mid(mid_str);
-                    bind_ssrc = selected.is_some();
-                }
+#[async_trait]
+impl StatsProvider for RtpTransport {
+    /// One `SenderBwe` entry once transport-cc is negotiated.
+    async fn collect(&self) -> RtcResult<Vec<StatsEntry>> {
+        if decode_ext_id(self.transport_cc_extension_id.load(Ordering::Relaxed)).is_none() {
+            return Ok(Vec::new());
+        }
+        let estimator = self.bandwidth_estimator.lock();
+        let label = self.transport.label.as_deref().unwrap_or("unknown");
+        let mut entry = StatsEntry::new(
+            StatsId::new(format!("sender-bwe-{}", label)),
+            StatsKind::SenderBwe,
+        )
+        .with_value("feedbackPacketsReceived", json!(estimator.feedback_count()))
+        .with_value("lossFraction", json!(estimator.loss_fraction()));
+        if let Some(estimate) = estimator.estimate_bps() {
+            entry = entry.with_value("availableOutgoingBitrate", json!(estimate));
+        }
+        Ok(vec![entry])
+    }
+}
 
-                if selected.is_none() {
-                    selected = listeners.by_ssrc.get(&ssrc).cloned();
-                    bind_ssrc = false;
-                }
+impl RtpTransport {
+    fn select_listener(
+        &self,
+        rtp_packet: &RtpPacket,
+    ) -> Option<mpsc::Sender<(RtpPacket, SocketAddr)>> {
+        let ssrc = rtp_packet.header.ssrc;
+        let pt = rtp_packet.header.payload_type;
+        let rid_id = decode_ext_id(self.rid_extension_id.load(Ordering::Relaxed));
+        let mid_id = decode_ext_id(self.sdes_mid_extension_id.load(Ordering::Relaxed));
+        let mut listeners = self.listeners.lock();
+        let mut selected = None;
+        let mut bind_ssrc = false;
 
-                if selected.is_none() {
-                    selected = listeners.unique_by_pt(pt);
-                    bind_ssrc = selected.is_some();
-                }
+        if let Some(id) = rid_id
+            && let Some(rid) = rtp_packet.header.get_extension(id)
+            && let Ok(rid_str) = std::str::from_utf8(&rid)
+        {
+            selected = listeners.by_rid.get(rid_str).cloned();
+            bind_ssrc = selected.is_some();
+        }
 
-                if selected.is_none() {
-                    selected = listeners.single_provisional();
-                    bind_ssrc = false;
-                }
+        if selected.is_none()
+            && let Some(id) = mid_id
+            && let Some(mid) = rtp_packet.header.get_extension(id)
+            && let Ok(mid_str) = std::str::from_utf8(&mid)
+        {
+            selected = listeners.by_mid(mid_str);
+            bind_ssrc = selected.is_some();
+        }
 
-                if let Some(tx) = selected.as_ref()
-                    && bind_ssrc
-                {
-                    listeners.bind_ssrc_route(ssrc, tx.clone());
-                }
+        if selected.is_none() {
+            selected = listeners.by_ssrc.get(&ssrc).cloned();
+            bind_ssrc = false;
+        }
 
-                selected
+        // An unknown SSRC may only claim a listener that has none yet, so a
+        // listener keeps the first SSRC it adopted.
+        if selected.is_none() && self.latch_unsignaled_ssrc.load(Ordering::Relaxed) {
+            let Some(tx) = listeners.first_unbound(pt) else {
+                trace!("No unbound listener for unsignaled SSRC {}", ssrc);
+                return None;
             };
+            listeners.bind_ssrc_route(ssrc, tx.clone());
+            return Some(tx);
+        }
 
-            if let Some(tx) = listener {
-                match try_send_dropping(&tx, (rtp_packet, addr)) {
-                    Ok(()) => {}
-                    Err(mpsc::error::TrySendError::Full(_)) => {}
-                    Err(mpsc::error::TrySendError::Closed(_)) => {
-                        let mut listeners = self.listeners.lock();
-                        listeners.by_ssrc.remove(&ssrc);
-                        listeners.remove_sender(&tx);
-                    }
-                }
-            } else {
-                trace!(
-                    "No listener found for packet SSRC: {} PT: {} from {}",
-                    ssrc, pt, addr
-                );
+        if selected.is_none() {
+            selected = listeners.unique_by_pt(pt);
+            bind_ssrc = selected.is_some();
+        }
+
+        if selected.is_none() {
+            selected = listeners.single_provisional();
+            bind_ssrc = false;
+        }
+
+        if let Some(tx) = selected.as_ref()
+            && bind_ssrc
+        {
+            listeners.bind_ssrc_route(ssrc, tx.clone());
+        }
+
+        selected
+    }
+
+    fn deliver(
+        &self,
+        tx: &mpsc::Sender<(RtpPacket, SocketAddr)>,
+        rtp_packet: RtpPacket,
+        addr: SocketAddr,
+    ) {
+        let ssrc = rtp_packet.header.ssrc;
+        match try_send_dropping(tx, (rtp_packet, addr)) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {}
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                let mut listeners = self.listeners.lock();
+                listeners.by_ssrc.remove(&ssrc);
+                listeners.remove_sender(tx);
             }
         }
     }
+
+    /// Replay buffered early RTP now that a listener was registered. Packets
+    /// that still match nothing stay buffered until they age out.
+    fn flush_early_rtp(&self) {
+        if !self.buffer_early_rtp.load(Ordering::Relaxed) {
+            return;
+        }
+        let buffered = self.early_rtp.lock().drain();
+        if buffered.is_empty() {
+            return;
+        }
+        trace!("Replaying {} early RTP packets", buffered.len());
+        let mut unmatched = Vec::new();
+        for (at, rtp_packet, addr) in buffered {
+            match self.select_listener(&rtp_packet) {
+                Some(tx) => self.deliver(&tx, rtp_packet, addr),
+                None => unmatched.push((at, rtp_packet, addr)),
+            }
+        }
+        if !unmatched.is_empty() {
+            let mut early = self.early_rtp.lock();
+            for (at, rtp_packet, addr) in unmatched {
+                early.push(rtp_packet, addr, at);
+            }
+        }
+    }
+}
+
+/// Maps sequence numbers and timestamps from a series of upstream sources
+/// onto one contiguous output stream.
+///
+/// Within a source, gaps and reordering are preserved so receivers can still
+/// NACK. When the source SSRC changes, the next packet continues one sequence
+/// number after the highest one sent and one frame interval after the last
+/// timestamp. Callers should only feed packets of the currently selected
+/// source; a stray packet from the previous one counts as a switch back.
+pub struct RtpContinuity {
+    source: Option<u32>,
+    sequence_offset: u16,
+    next_sequence_number: u16,
+    timestamps: TimestampNormalizer,
+}
+
+impl Default for RtpContinuity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RtpContinuity {
+    pub fn new() -> Self {
+        Self {
+            source: None,
+            sequence_offset: 0,
+            next_sequence_number: random_u32() as u16,
+            timestamps: TimestampNormalizer::new(),
+        }
+    }
+
+    /// Rewrite a packet of `source_ssrc`; returns the output sequence number
+    /// and timestamp.
+    pub fn rewrite(
+        &mut self,
+        source_ssrc: u32,
+        sequence_number: u16,
+        timestamp: u32,
+    ) -> (u16, u32) {
+        if self.source != Some(source_ssrc) {
+            self.sequence_offset = self.next_sequence_number.wrapping_sub(sequence_number);
+            if self.source.is_some() {
+                self.timestamps.restart();
+            }
+            self.source = Some(source_ssrc);
+        }
+        let sequence_number = sequence_number.wrapping_add(self.sequence_offset);
+        if sequence_number.wrapping_sub(self.next_sequence_number) < 0x8000 {
+            self.next_sequence_number = sequence_number.wrapping_add(1);
+        }
+        (sequence_number, self.timestamps.normalize(timestamp))
+    }
+}
+
+/// Sends application-built RTP on a transport with caller-chosen sequence
+/// numbers and timestamps, for SFU-style forwarding.
+pub struct RtpForwardSender {
+    transport: Arc<RtpTransport>,
+    ssrc: u32,
+    payload_type: u8,
+    continuity: Mutex<RtpContinuity>,
+}
+
+impl RtpForwardSender {
+    pub fn new(transport: Arc<RtpTransport>, ssrc: u32, payload_type: u8) -> Self {
+        Self {
+            transport,
+            ssrc,
+            payload_type,
+            continuity: Mutex::new(RtpContinuity::new()),
+        }
+    }
+
+    pub fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+
+    pub fn payload_type(&self) -> u8 {
+        self.payload_type
+    }
+
+    /// Send one packet with exactly these header fields; nothing is rewritten.
+    pub async fn send_with(
+        &self,
+        sequence_number: u16,
+        timestamp: u32,
+        marker: bool,
+        payload: impl Into<Bytes>,
+    ) -> Result<usize> {
+        let mut header = RtpHeader::new(self.payload_type, sequence_number, timestamp, self.ssrc);
+        header.marker = marker;
+        self.transport
+            .send_rtp(RtpPacket {
+                header,
+                payload: payload.into(),
+                padding_len: 0,
+            })
+            .await
+    }
+
+    /// Send an upstream packet, keeping the output sequence numbers and
+    /// timestamps contiguous when the upstream source changes (see
+    /// [`RtpContinuity`]). Upstream header extensions are not carried over.
+    pub async fn forward(&self, packet: &RtpPacket) -> Result<usize> {
+        let (sequence_number, timestamp) = self.continuity.lock().rewrite(
+            packet.header.ssrc,
+            packet.header.sequence_number,
+            packet.header.timestamp,
+        );
+        self.send_with(
+            sequence_number,
+            timestamp,
+            packet.header.marker,
+            packet.payload.clone(),
+        )
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -819,6 +1275,59 @@ mod tests {
         assert_eq!(received3.0.header.payload_type, 8);
     }
 
+    #[tokio::test]
+    async fn test_latch_policy_keeps_first_unsignaled_ssrc() {
+        use crate::transports::ice::IceSocketWrapper;
+        use bytes::Bytes;
+        use tokio::sync::watch;
+
+        let (_ice_tx, ice_rx) = watch::channel(None::<IceSocketWrapper>);
+        let ice_conn = IceConn::new(ice_rx, "127.0.0.1:1234".parse().unwrap(), None);
+        let transport = RtpTransport::new(ice_conn, false);
+        transport.set_unsignaled_ssrc_policy(UnsignaledSsrcPolicy::Latch);
+
+        let (first_tx, mut first_rx) = mpsc::channel(10);
+        transport.register_provisional_listener(first_tx.clone());
+        transport.register_payload_list_listener(vec![0], first_tx);
+
+        let addr = "127.0.0.1:5000".parse().unwrap();
+        let mut marshal_buf = Vec::new();
+        let mut send = async |seq: u16, ssrc: u32| {
+            let packet = crate::rtp::RtpPacket::new(
+                crate::rtp::RtpHeader::new(0, seq, seq as u32 * 160, ssrc),
+                vec![0u8; 160],
+            );
+            transport
+                .receive(
+                    Bytes::from(packet.marshal().unwrap()),
+                    addr,
+                    &mut marshal_buf,
+                )
+                .await;
+        };
+
+        send(1, 1111).await;
+        assert_eq!(first_rx.recv().await.unwrap().0.header.ssrc, 1111);
+        assert!(transport.has_listener(1111));
+
+        // No other listener is free: the second SSRC is dropped.
+        send(2, 2222).await;
+        tokio::time::timeout(tokio::time::Duration::from_millis(50), first_rx.recv())
+            .await
+            .expect_err("second SSRC must not reach the latched listener");
+        assert!(!transport.has_listener(2222));
+
+        // A new listener (e.g. another recvonly transceiver) takes the next
+        // new SSRC while the first keeps its own.
+        let (second_tx, mut second_rx) = mpsc::channel(10);
+        transport.register_provisional_listener(second_tx);
+
+        send(3, 2222).await;
+        send(4, 1111).await;
+        assert_eq!(second_rx.recv().await.unwrap().0.header.ssrc, 2222);
+        assert_eq!(first_rx.recv().await.unwrap().0.header.ssrc, 1111);
+    }
+
     #[tokio::test]
     async fn test_ambiguous_payload_type_without_mid_or_ssrc_is_dropped() {
         use crate::transports::ice::IceSocketWrapper;
@@ -1010,6 +1519,257 @@ mod tests {
         assert_eq!(packet.header.timestamp, 1111 + 12345);
     }
 
+    #[tokio::test]
+    async fn forward_sender_keeps_sequence_contiguous_across_source_switch() {
+        use crate::transports::ice::IceSocketWrapper;
+        use tokio::net::UdpSocket;
+        use tokio::sync::watch;
+
+        let sink = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_tx, rx) = watch::channel(Some(IceSocketWrapper::Udp(Arc::new(socket))));
+        let conn = IceConn::new(rx, sink.local_addr().unwrap(), None);
+        let forwarder = RtpForwardSender::new(Arc::new(RtpTransport::new(conn, false)), 555, 96);
+
+        // Source A, then source B with an unrelated sequence/timestamp base,
+        // then back to A where it left off.
+        let upstream = [
+            (0xA, 1000, 90_000),
+            (0xA, 1001, 93_000),
+            (0xA, 1002, 96_000),
+            (0xB, 40_000, 7_000_000),
+            (0xB, 40_001, 7_003_000),
+            (0xA, 1003, 99_000),
+        ];
+        for (ssrc, seq, ts) in upstream {
+            let packet = RtpPacket::new(RtpHeader::new(100, seq, ts, ssrc), vec![0; 20]);
+            forwarder.forward(&packet).await.unwrap();
+        }
+        let payload = vec![1u8, 2, 3];
+        forwarder.send_with(7, 1234, true, payload).await.unwrap();
+
+        let mut buf = [0u8; 1500];
+        let mut received = Vec::new();
+        for _ in 0..upstream.len() + 1 {
+            let (n, _) = tokio::time::timeout(Duration::from_secs(1), sink.recv_from(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            received.push(RtpPacket::parse(&buf[..n]).unwrap());
+        }
+
+        assert!(
+            received
+                .iter()
+                .all(|p| p.header.ssrc == 555 && p.header.payload_type == 96)
+        );
+        let forwarded = &received[..upstream.len()];
+        for pair in forwarded.windows(2) {
+            let (prev, next) = (&pair[0].header, &pair[1].header);
+            assert_eq!(next.sequence_number, prev.sequence_number.wrapping_add(1));
+            assert_eq!(next.timestamp.wrapping_sub(prev.timestamp), 3000);
+        }
+
+        let explicit = &received[upstream.len()];
+        assert_eq!(explicit.header.sequence_number, 7);
+        assert_eq!(explicit.header.timestamp, 1234);
+        assert!(explicit.header.marker);
+        assert_eq!(&explicit.payload[..], &[1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn transport_cc_feedback_is_sent_from_first_local_sender() {
+        use crate::transports::ice::IceSocketWrapper;
+        use tokio::sync::watch;
+
+        let (_ice_tx, ice_rx) = watch::channel(None::<IceSocketWrapper>);
+        let ice_conn = IceConn::new(ice_rx, "127.0.0.1:1234".parse().unwrap(), None);
+        let transport = RtpTransport::new(ice_conn, false);
+        transport.set_transport_cc_extension_id(Some(3));
+
+        let mut header = RtpHeader::new(96, 1, 0, 0x5678);
+        header.set_extension(3, &7u16.to_be_bytes()).unwrap();
+        let packet = RtpPacket::new(header, vec![0; 100]);
+        transport
+            .receive(
+                Bytes::from(packet.marshal().unwrap()),
+                "127.0.0.1:5000".parse().unwrap(),
+                &mut Vec::new(),
+            )
+            .await;
+
+        transport.adopt_rtcp_sender_ssrc(0xABCD);
+        transport.adopt_rtcp_sender_ssrc(0x9999);
+        let feedback = transport.take_transport_cc_feedback().unwrap();
+        assert_eq!(feedback.sender_ssrc, 0xABCD);
+        assert_eq!(feedback.media_ssrc, 0x5678);
+    }
+
+    #[tokio::test]
+    async fn sender_bwe_stats_update_on_transport_cc_feedback() {
+        use crate::transports::ice::IceSocketWrapper;
+        use tokio::net::UdpSocket;
+        use tokio::sync::watch;
+
+        let sink = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_tx, rx) = watch::channel(Some(IceSocketWrapper::Udp(Arc::new(socket))));
+        let conn = IceConn::new(rx, sink.local_addr().unwrap(), None);
+        let transport = RtpTransport::new(conn, false);
+        assert!(transport.collect().await.unwrap().is_empty());
+
+        transport.set_transport_cc_extension_id(Some(3));
+        let start = Instant::now();
+        let mut recorder = TransportCcRecorder::new();
+        let mut buf = [0u8; 1500];
+        for i in 0..10u16 {
+            let packet = RtpPacket::new(RtpHeader::new(96, i, 0, 0x1234), vec![0; 1000]);
+            transport.send_rtp(packet).await.unwrap();
+            let (n, _) = tokio::time::timeout(Duration::from_secs(1), sink.recv_from(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            let packet = RtpPacket::parse(&buf[..n]).unwrap();
+            let ext = packet.header.get_extension(3).unwrap();
+            let sequence = u16::from_be_bytes([ext[0], ext[1]]);
+            let arrival = start + Duration::from_millis(10 * i as u64);
+            recorder.record(sequence, 0x1234, arrival);
+        }
+
+        let entries = transport.collect().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind, StatsKind::SenderBwe);
+        assert_eq!(entries[0].values["feedbackPacketsReceived"], json!(0));
+        assert!(!entries[0].values.contains_key("availableOutgoingBitrate"));
+
+        transport.on_transport_cc_feedback(&recorder.build_feedback(1).unwrap());
+        let entries = transport.collect().await.unwrap();
+        assert_eq!(entries[0].values["feedbackPacketsReceived"], json!(1));
+        let bitrate = transport.bandwidth_estimate_bps().unwrap();
+        assert!(bitrate > 0);
+        assert_eq!(
+            entries[0].values["availableOutgoingBitrate"],
+            json!(bitrate)
+        );
+    }
+
+    #[tokio::test]
+    async fn packet_trace_events_cover_send_and_receive() {
+        use crate::transports::ice::IceSocketWrapper;
+        use std::collections::BTreeMap;
+        use tokio::net::UdpSocket;
+        use tokio::sync::watch;
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        type Fields = BTreeMap<String, String>;
+
+        struct FieldVisitor<'a>(&'a mut Fields);
+        impl Visit for FieldVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{:?}", value));
+            }
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+        }
+
+        struct Collect(Arc<Mutex<Vec<Fields>>>);
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for Collect {
+            fn on_event(&self, event: &tracing::Event<'_>, _: Context<'_, S>) {
+                if event.metadata().target() == PACKET_TRACE_TARGET {
+                    let mut fields = Fields::new();
+                    event.record(&mut FieldVisitor(&mut fields));
+                    self.0.lock().push(fields);
+                }
+            }
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(Collect(events.clone()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let sink = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_tx, rx) = watch::channel(Some(IceSocketWrapper::Udp(Arc::new(socket))));
+        let conn = IceConn::new(rx, sink.local_addr().unwrap(), None);
+        let transport = RtpTransport::new(conn, false);
+
+        let sent = RtpPacket::new(RtpHeader::new(96, 7, 0, 0x1234), vec![0; 100]);
+        transport.send_rtp(sent).await.unwrap();
+        let received = RtpPacket::new(RtpHeader::new(111, 42, 0, 0x5678), vec![0; 60]);
+        let mut marshal_buf = Vec::new();
+        transport
+            .receive(
+                Bytes::from(received.marshal().unwrap()),
+                sink.local_addr().unwrap(),
+                &mut marshal_buf,
+            )
+            .await;
+
+        let events = events.lock().clone();
+        assert_eq!(events.len(), 2, "{events:?}");
+        let expect = |fields: &Fields, key: &str, value: &str| {
+            assert_eq!(fields.get(key).map(String::as_str), Some(value), "{key}");
+        };
+        expect(&events[0], "direction", "send");
+        expect(&events[0], "class", "rtp");
+        expect(&events[0], "ssrc", "4660");
+        expect(&events[0], "pt", "96");
+        expect(&events[0], "seq", "7");
+        expect(&events[0], "size", "112");
+        expect(&events[1], "direction", "recv");
+        expect(&events[1], "class", "rtp");
+        expect(&events[1], "ssrc", "22136");
+        expect(&events[1], "pt", "111");
+        expect(&events[1], "seq", "42");
+        expect(&events[1], "size", "72");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_rtcp_reaches_listener_only_when_enabled() {
+        use crate::rtp::{PictureLossIndication, UnknownRtcp, marshal_rtcp_packets};
+        use crate::transports::ice::IceSocketWrapper;
+        use tokio::net::UdpSocket;
+        use tokio::sync::watch;
+
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (_tx, rx) = watch::channel(Some(IceSocketWrapper::Udp(Arc::new(socket))));
+        let conn = IceConn::new(rx, "127.0.0.1:9".parse().unwrap(), None);
+        let transport = RtpTransport::new(conn, false);
+        let (rtcp_tx, mut rtcp_rx) = mpsc::channel(8);
+        transport.register_rtcp_listener(rtcp_tx);
+
+        let pli = RtcpPacket::PictureLossIndication(PictureLossIndication {
+            sender_ssrc: 1,
+            media_ssrc: 2,
+        });
+        let app = RtcpPacket::Unknown(UnknownRtcp {
+            packet_type: 204,
+            fmt: 0,
+            payload: [&1u32.to_be_bytes()[..], b"TEST"].concat(),
+        });
+        let compound = Bytes::from(marshal_rtcp_packets(&[pli.clone(), app.clone()]).unwrap());
+        let app_only = Bytes::from(marshal_rtcp_packets(std::slice::from_ref(&app)).unwrap());
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let mut marshal_buf = Vec::new();
+
+        transport
+            .receive(compound.clone(), addr, &mut marshal_buf)
+            .await;
+        transport.receive(app_only, addr, &mut marshal_buf).await;
+        assert_eq!(rtcp_rx.try_recv().unwrap(), vec![pli.clone()]);
+        assert!(
+            rtcp_rx.try_recv().is_err(),
+            "APP-only compound must be dropped"
+        );
+
+        transport.set_forward_unknown_rtcp(true);
+        transport.receive(compound, addr, &mut marshal_buf).await;
+        assert_eq!(rtcp_rx.try_recv().unwrap(), vec![pli, app]);
+    }
+
     #[tokio::test]
     async fn test_received_rtp_packets_counter_advances_on_slow_path() {
         use crate::transports::ice::IceSocketWrapper;
@@ -1134,4 +1894,93 @@ mod tests {
             "listener must NOT receive on the fast-path relay (interceptor path is bypassed)"
         );
     }
+
+    #[tokio::test]
+    async fn early_rtp_is_replayed_when_listener_registers() {
+        use crate::transports::ice::IceSocketWrapper;
+        use tokio::sync::watch;
+
+        let (_ice_tx, ice_rx) = watch::channel(None::<IceSocketWrapper>);
+        let ice_conn = IceConn::new(ice_rx, "127.0.0.1:1234".parse().unwrap(), None);
+        let transport = RtpTransport::new(ice_conn, false);
+        transport.set_early_rtp_buffering(true);
+
+        let mut marshal_buf = Vec::new();
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        for seq in 1..=3u16 {
+            let header = crate::rtp::RtpHeader::new(0, seq, seq as u32 * 160, 100);
+            let packet = RtpPacket::new(header, vec![seq as u8; 160]);
+            transport
+                .receive(
+                    Bytes::from(packet.marshal().unwrap()),
+                    addr,
+                    &mut marshal_buf,
+                )
+                .await;
+        }
+
+        // The receiver only becomes ready after the packets arrived.
+        let (tx, mut rx) = mpsc::channel(10);
+        transport.register_listener_sync(100, tx);
+
+        for seq in 1..=3u16 {
+            let (packet, from) = rx.try_recv().expect("buffered packet must be replayed");
+            assert_eq!(packet.header.sequence_number, seq);
+            assert_eq!(from, addr);
+        }
+        assert!(rx.try_recv().is_err());
+
+        // Packets for an SSRC nobody listens to expire instead of piling up.
+        let header = crate::rtp::RtpHeader::new(0, 10, 0, 999);
+        let packet = RtpPacket::new(header, vec![0u8; 160]);
+        transport
+            .receive(
+                Bytes::from(packet.marshal().unwrap()),
+                addr,
+                &mut marshal_buf,
+            )
+            .await;
+        tokio::time::sleep(EARLY_RTP_WINDOW + Duration::from_millis(50)).await;
+        let (tx, mut rx) = mpsc::channel(10);
+        transport.register_listener_sync(999, tx);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn early_rtp_buffer_is_bounded_per_source() {
+        let mut buffer = EarlyRtpBuffer::default();
+        let now = Instant::now();
+        for i in 0..(EARLY_RTP_MAX_PER_SOURCE as u16 + 10) {
+            let header = crate::rtp::RtpHeader::new(0, i, 0, 1);
+            buffer.push(
+                RtpPacket::new(header, vec![]),
+                "127.0.0.1:5000".parse().unwrap(),
+                now,
+            );
+        }
+        for port in 0..(EARLY_RTP_MAX_SOURCES as u16 + 2) {
+            let header = crate::rtp::RtpHeader::new(0, 0, 0, 2);
+            buffer.push(
+                RtpPacket::new(header, vec![]),
+                SocketAddr::from(([127, 0, 0, 1], 6000 + port)),
+                now,
+            );
+        }
+
+        let drained = buffer.drain();
+        assert_eq!(
+            drained.len(),
+            EARLY_RTP_MAX_PER_SOURCE + EARLY_RTP_MAX_SOURCES - 1
+        );
+        let first_source: Vec<u16> = drained
+            .iter()
+            .filter(|(_, packet, _)| packet.header.ssrc == 1)
+            .map(|(_, packet, _)| packet.header.sequence_number)
+            .collect();
+        assert_eq!(
+            first_source.first(),
+            Some(&10),
+            "oldest packets are evicted first"
+        );
+    }
 }
@@ -39,6 +39,7 @@ const MAX_BUFFERED_AMOUNT: usize = 256 * 1024; // 256KB - reduced for lower memo
 const MAX_INBOUND_STREAM_PENDING: usize = 128; // max pending ordered messages per stream
 const MAX_DUPS_BUFFER_SIZE: usize = 32; // max duplicate TSNs to track (increased for lossy networks)
 const MAX_RECEIVED_QUEUE_SIZE: usize = 512; // max out-of-order packets (increased for lossy networks)
+const MAX_SACK_DELAY: Duration = Duration::from_millis(500); // RFC 4960 §6.2 upper bound
 
 // Fast Recovery re-entry cooldown: prevent rapid exit-then-re-enter cycles that
 // keep cwnd pinned at SSTHRESH_MIN on lossy links (e.g. rate-limited TURN relays).
@@ -47,7 +48,8 @@ const FAST_RECOVERY_REENTRY_COOLDOWN: Duration = Duration::from_millis(500);
 const SCTP_MAX_INIT_RETRANS: u32 = 8;
 const COOKIE_HMAC_LEN: usize = 20; // SHA1 output
 const COOKIE_TIMESTAMP_LEN: usize = 8; // u64 millis
-const COOKIE_TOTAL_LEN: usize = COOKIE_TIMESTAMP_LEN + COOKIE_HMAC_LEN;
+const COOKIE_TAGS_LEN: usize = 16; // local, peer and the two tie-tags
+const COOKIE_TOTAL_LEN: usize = COOKIE_TIMESTAMP_LEN + COOKIE_TAGS_LEN + COOKIE_HMAC_LEN;
 const COOKIE_LIFETIME_MS: u64 = 60_000;
 
 #[derive(Debug, Clone)]
@@ -136,6 +138,48 @@ fn ssn_gt(a: u16, b: u16) -> bool {
     (a.wrapping_sub(b) as i16) > 0
 }
 
+/// RE-CONFIG chunk carrying one Outgoing SSN Reset Request (RFC 6525 §4.1).
+fn reconfig_request_chunk(request: &SsnResetRequest) -> Bytes {
+    let param_len = 16 + request.streams.len() * 2;
+    let mut param = BytesMut::with_capacity(param_len);
+    param.put_u16(RECONFIG_PARAM_OUTGOING_SSN_RESET);
+    param.put_u16(param_len as u16);
+    param.put_u32(request.request_sn);
+    param.put_u32(0); // response SN (not used for outgoing reset)
+    param.put_u32(request.last_tsn);
+
+    for &stream in &request.streams {
+        param.put_u16(stream);
+    }
+
+    // Padding for parameter
+    let padding = (4 - (param_len % 4)) % 4;
+    for _ in 0..padding {
+        param.put_u8(0);
+    }
+
+    let chunk_len = 4 + param.len();
+    let mut chunk = BytesMut::with_capacity(chunk_len);
+    chunk.put_u8(CT_RECONFIG);
+    chunk.put_u8(0);
+    chunk.put_u16(chunk_len as u16);
+    chunk.put(param);
+    chunk.freeze()
+}
+
+/// RE-CONFIG chunk carrying a Re-configuration Response (RFC 6525 §4.4).
+fn reconfig_response_chunk(response_sn: u32, result: u32) -> Bytes {
+    let mut chunk = BytesMut::with_capacity(16);
+    chunk.put_u8(CT_RECONFIG);
+    chunk.put_u8(0);
+    chunk.put_u16(16);
+    chunk.put_u16(RECONFIG_PARAM_RESPONSE);
+    chunk.put_u16(12);
+    chunk.put_u32(response_sn);
+    chunk.put_u32(result);
+    chunk.freeze()
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct OutboundChunk {
     pub(crate) stream_id: u16,
@@ -147,6 +191,123 @@ pub(crate) struct OutboundChunk {
     pub(crate) expiry: Option<Instant>,
 }
 
+/// Per-stream send queues served round-robin, one user message at a time, so a
+/// channel with a deep backlog cannot starve the others sharing the association.
+/// All fragments of a message are dequeued back to back because their TSNs
+/// must be consecutive (RFC 4960 §6.9).
+#[derive(Debug, Default)]
+pub(crate) struct OutboundQueue {
+    streams: HashMap<u16, VecDeque<OutboundChunk>>,
+    /// Streams with queued chunks; the front one is being served.
+    order: VecDeque<u16>,
+    len: usize,
+}
+
+impl OutboundQueue {
+    pub(crate) fn push_back(&mut self, chunk: OutboundChunk) {
+        let stream_id = chunk.stream_id;
+        let queue = self.streams.entry(stream_id).or_default();
+        if queue.is_empty() {
+            self.order.push_back(stream_id);
+        }
+        queue.push_back(chunk);
+        self.len += 1;
+    }
+
+    pub(crate) fn pop_front(&mut self) -> Option<OutboundChunk> {
+        let stream_id = *self.order.front()?;
+        let queue = self.streams.get_mut(&stream_id)?;
+        let chunk = queue.pop_front()?;
+        self.len -= 1;
+
+        // Stay on this stream until the message ends, then rotate.
+        if chunk.flags & 0x01 != 0 {
+            self.order.pop_front();
+            if queue.is_empty() {
+                self.streams.remove(&stream_id);
+            } else {
+                self.order.push_back(stream_id);
+            }
+        }
+        Some(chunk)
+    }
+
+    pub(crate) fn has_stream(&self, stream_id: u16) -> bool {
+        self.streams.contains_key(&stream_id)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// An Outgoing SSN Reset Request (RFC 6525 §4.1), ours or the peer's.
+#[derive(Debug, Clone)]
+struct SsnResetRequest {
+    request_sn: u32,
+    /// Sender's Last Assigned TSN: the reset takes effect after this TSN.
+    last_tsn: u32,
+    streams: Vec<u16>,
+}
+
+/// Stream reset state used to close data channels (RFC 8831 §6.7).
+#[derive(Debug, Default)]
+struct StreamResets {
+    /// Streams closed locally whose queued DATA has no TSN yet.
+    pending: Vec<u16>,
+    /// Our one outstanding request and when it was last sent.
+    outstanding: Option<(SsnResetRequest, Instant)>,
+    /// Peer requests held until their last TSN arrives (RFC 6525 §5.2.2).
+    deferred: Vec<SsnResetRequest>,
+    /// The latest peer request answered, to repeat for a retransmission.
+    last_response: Option<(u32, u32)>,
+}
+
+impl StreamResets {
+    fn answer_to(&self, request_sn: u32) -> u32 {
+        if self.deferred.iter().any(|r| r.request_sn == request_sn) {
+            return RECONFIG_RESPONSE_IN_PROGRESS;
+        }
+        match self.last_response {
+            Some((sn, result)) if sn == request_sn => result,
+            _ => RECONFIG_RESPONSE_SUCCESS_NOTHING_TO_DO,
+        }
+    }
+}
+
+/// Verification tags carried in the state cookie (RFC 4960 §5.2.4). The
+/// tie-tags are the tags of the association that was established when the
+/// INIT-ACK went out, or zero if there was none.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct CookieTags {
+    local: u32,
+    peer: u32,
+    local_tie: u32,
+    peer_tie: u32,
+}
+
+impl CookieTags {
+    fn to_bytes(self) -> [u8; COOKIE_TAGS_LEN] {
+        let mut out = [0u8; COOKIE_TAGS_LEN];
+        out[0..4].copy_from_slice(&self.local.to_be_bytes());
+        out[4..8].copy_from_slice(&self.peer.to_be_bytes());
+        out[8..12].copy_from_slice(&self.local_tie.to_be_bytes());
+        out[12..16].copy_from_slice(&self.peer_tie.to_be_bytes());
+        out
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let word = |i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap());
+        Self {
+            local: word(0),
+            peer: word(4),
+            local_tie: word(8),
+            peer_tie: word(12),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SctpState {
     New,
@@ -155,6 +316,10 @@ pub enum SctpState {
     Closed,
 }
 
+/// Invoked once when the association handshake completes, before any
+/// channel is marked open.
+pub type SctpConnectedHandler = Box<dyn FnOnce() + Send + Sync>;
+
 // Chunk Types
 const CT_DATA: u8 = 0;
 const CT_INIT: u8 = 1;
@@ -192,7 +357,6 @@ const RECONFIG_RESPONSE_ERROR_WRONG_SSN: u32 = 3;
 const RECONFIG_RESPONSE_ERROR_REQUEST_ALREADY_IN_PROGRESS: u32 = 4;
 #[allow(unused)]
 const RECONFIG_RESPONSE_ERROR_BAD_SEQUENCE_NUMBER: u32 = 5;
-#[allow(unused)]
 const RECONFIG_RESPONSE_IN_PROGRESS: u32 = 6;
 
 #[derive(Debug)]
@@ -242,6 +406,7 @@ struct SctpInner {
     next_tsn: AtomicU32,
     cumulative_tsn_ack: AtomicU32,
     new_data_channel_tx: Option<mpsc::UnboundedSender<Arc<DataChannel>>>,
+    connected_handler: Mutex<Option<SctpConnectedHandler>>,
     is_client: bool,
     sent_queue: Mutex<BTreeMap<u32, ChunkRecord>>,
     received_queue: Mutex<BTreeMap<u32, (u8, Bytes)>>,
@@ -262,9 +427,17 @@ struct SctpInner {
     last_sack_sig: AtomicU64,
     dups_buffer: Mutex<Vec<u32>>, // duplicate TSNs to include in next SACK
 
+    // Delayed SACK (RFC 4960 §6.2)
+    sack_delay: Duration, // zero = SACK every DATA packet immediately
+    sack_max_unacked: u32,
+    unacked_data_packets: AtomicU32,
+    sack_immediate: AtomicBool, // gap or duplicate seen, do not delay
+    sack_deadline: Mutex<Option<Instant>>,
+
     // Reconfig State
     reconfig_request_sn: AtomicU32,
     peer_reconfig_request_sn: AtomicU32,
+    stream_resets: Mutex<StreamResets>,
     local_rwnd: usize,
 
     // Fast Recovery
@@ -301,7 +474,7 @@ struct SctpInner {
     last_suspicious_rto_log: Mutex<Option<(Instant, u32)>>,
 
     // Outqueue for non-blocking sends
-    outbound_queue: Mutex<VecDeque<OutboundChunk>>,
+    outbound_queue: Mutex<OutboundQueue>,
     queued_bytes: AtomicUsize,
 
     // Outgoing Packet Queue to prevent deadlocks
@@ -335,6 +508,7 @@ struct SctpInner {
     stats_packets_received: AtomicU64,
     stats_retransmissions: AtomicU64,
     stats_heartbeats_sent: AtomicU64,
+    stats_sacks_sent: AtomicU64,
     stats_created_time: Instant,
     close_reason: Mutex<Option<String>>,
 }
@@ -612,19 +786,7 @@ fn apply_sack_to_sent_queue(
 impl<'a> Drop for SctpCleanupGuard<'a> {
     fn drop(&mut self) {
         *self.inner.state.lock() = SctpState::Closed;
-
-        let channels = self.inner.data_channels.lock();
-        for weak_dc in channels.iter() {
-            if let Some(dc) = weak_dc.upgrade() {
-                let old_state = dc
-                    .state
-                    .swap(DataChannelState::Closed as usize, Ordering::SeqCst);
-                if old_state != DataChannelState::Closed as usize {
-                    dc.send_event(DataChannelEvent::Close);
-                    dc.close_channel();
-                }
-            }
-        }
+        self.inner.close_data_channels();
     }
 }
 
@@ -660,6 +822,7 @@ impl SctpTransport {
             next_tsn: AtomicU32::new(0),
             cumulative_tsn_ack: AtomicU32::new(0),
             new_data_channel_tx,
+            connected_handler: Mutex::new(None),
             is_client,
             sent_queue: Mutex::new(BTreeMap::new()),
             received_queue: Mutex::new(BTreeMap::new()),
@@ -679,8 +842,14 @@ impl SctpTransport {
             sack_needed: AtomicBool::new(false),
             last_sack_sig: AtomicU64::new(0),
             dups_buffer: Mutex::new(Vec::new()),
+            sack_delay: config.sctp_sack_delay.min(MAX_SACK_DELAY),
+            sack_max_unacked: config.sctp_sack_max_unacked.max(1),
+            unacked_data_packets: AtomicU32::new(0),
+            sack_immediate: AtomicBool::new(false),
+            sack_deadline: Mutex::new(None),
             reconfig_request_sn: AtomicU32::new(0),
             peer_reconfig_request_sn: AtomicU32::new(u32::MAX), // Initial value to allow 0
+            stream_resets: Mutex::new(StreamResets::default()),
             local_rwnd: config.sctp_receive_window,
             fast_recovery_exit_tsn: AtomicU32::new(0),
             fast_recovery_active: AtomicBool::new(false),
@@ -699,7 +868,7 @@ impl SctpTransport {
             last_t3_fire_time: Mutex::new(None),
             cached_rto_timeout: Mutex::new(None),
             last_suspicious_rto_log: Mutex::new(None),
-            outbound_queue: Mutex::new(VecDeque::new()),
+            outbound_queue: Mutex::new(OutboundQueue::default()),
             queued_bytes: AtomicUsize::new(0),
             last_sack_time: Mutex::new(None),
             t1_chunk: Mutex::new(None),
@@ -723,6 +892,7 @@ impl SctpTransport {
             stats_packets_received: AtomicU64::new(0),
             stats_retransmissions: AtomicU64::new(0),
             stats_heartbeats_sent: AtomicU64::new(0),
+            stats_sacks_sent: AtomicU64::new(0),
             stats_created_time: Instant::now(),
             close_reason: Mutex::new(None),
             outgoing_packet_tx,
@@ -759,6 +929,11 @@ impl SctpTransport {
         (transport, runner)
     }
 
+    /// Register the handler run once the association is established.
+    pub fn on_connected(&self, handler: SctpConnectedHandler) {
+        *self.inner.connected_handler.lock() = Some(handler);
+    }
+
     pub async fn send_data(&self, channel_id: u16, data: &[u8]) -> Result<()> {
         self.inner.send_data(channel_id, data).await
     }
@@ -844,6 +1019,22 @@ impl Drop for SctpTransport {
 }
 
 impl SctpInner {
+    /// Move every channel to `Closed` and emit its final `Close` event.
+    fn close_data_channels(&self) {
+        let channels = self.data_channels.lock();
+        for weak_dc in channels.iter() {
+            if let Some(dc) = weak_dc.upgrade() {
+                let old_state = dc
+                    .state
+                    .swap(DataChannelState::Closed as usize, Ordering::SeqCst);
+                if old_state != DataChannelState::Closed as usize {
+                    dc.send_event(DataChannelEvent::Close);
+                    dc.close_channel();
+                }
+            }
+        }
+    }
+
     async fn run_loop(
         &self,
         close_rx: Arc<tokio::sync::Notify>,
@@ -994,7 +1185,22 @@ impl SctpInner {
                 Duration::from_secs(3600)
             };
 
-            let sleep_duration = rto_timeout.min(heartbeat_timeout).min(t1_timeout);
+            // 4. Calculate delayed SACK timeout
+            let sack_timeout = match *self.sack_deadline.lock() {
+                Some(deadline) if self.sack_needed.load(Ordering::Relaxed) => {
+                    deadline.saturating_duration_since(now)
+                }
+                _ => Duration::from_secs(3600),
+            };
+
+            // 5. Calculate stream reset retransmission timeout
+            let reset_timeout = self.stream_reset_timeout(now, rto_snapshot);
+
+            let sleep_duration = rto_timeout
+                .min(heartbeat_timeout)
+                .min(t1_timeout)
+                .min(sack_timeout)
+                .min(reset_timeout);
 
             tokio::select! {
                 _ = close_rx.notified() => {
@@ -1049,6 +1255,21 @@ impl SctpInner {
                         }
                         last_heartbeat = Instant::now();
                     }
+
+                    // Flush a delayed SACK whose timer expired
+                    if self.sack_needed.load(Ordering::Relaxed)
+                        && let Err(e) = self.transmit().await
+                    {
+                        trace!("SCTP transmit error after SACK delay: {}", e);
+                    }
+
+                    // Retransmit an unanswered stream reset request
+                    let rto = self.rto_state.lock().rto;
+                    if self.stream_reset_timeout(Instant::now(), rto).is_zero()
+                        && let Err(e) = self.transmit().await
+                    {
+                        trace!("SCTP transmit error after stream reset timeout: {}", e);
+                    }
                 },
                 res = incoming_data_rx.recv() => {
                     match res {
@@ -1140,35 +1361,40 @@ impl SctpInner {
         self.t1_failures.store(0, Ordering::SeqCst);
     }
 
-    fn generate_cookie(&self) -> Vec<u8> {
+    fn generate_cookie(&self, tags: CookieTags) -> Vec<u8> {
         use std::time::{SystemTime, UNIX_EPOCH};
         let now_ms = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_millis() as u64;
         let timestamp = now_ms.to_be_bytes();
+        let tags = tags.to_bytes();
         let mut mac = <HmacSha1 as hmac::digest::KeyInit>::new_from_slice(&self.cookie_hmac_key)
             .expect("HMAC key length is valid");
         mac.update(&timestamp);
+        mac.update(&tags);
         let result = mac.finalize();
         let mut cookie = Vec::with_capacity(COOKIE_TOTAL_LEN);
         cookie.extend_from_slice(&timestamp);
+        cookie.extend_from_slice(&tags);
         cookie.extend_from_slice(&result.into_bytes());
         cookie
     }
 
-    fn validate_cookie(&self, cookie: &[u8]) -> bool {
+    /// Returns the tags of a cookie we issued that is intact and not expired.
+    fn validate_cookie(&self, cookie: &[u8]) -> Option<CookieTags> {
         use std::time::{SystemTime, UNIX_EPOCH};
         if cookie.len() != COOKIE_TOTAL_LEN {
-            return false;
+            return None;
         }
-        let timestamp = &cookie[..COOKIE_TIMESTAMP_LEN];
-        let received_mac = &cookie[COOKIE_TIMESTAMP_LEN..];
+        let (timestamp, rest) = cookie.split_at(COOKIE_TIMESTAMP_LEN);
+        let (tags, received_mac) = rest.split_at(COOKIE_TAGS_LEN);
         let mut mac = <HmacSha1 as hmac::digest::KeyInit>::new_from_slice(&self.cookie_hmac_key)
             .expect("HMAC key length is valid");
         mac.update(timestamp);
+        mac.update(tags);
         if mac.verify_slice(received_mac).is_err() {
-            return false;
+            return None;
         }
         let stamp_ms = u64::from_be_bytes(
             timestamp
@@ -1180,9 +1406,9 @@ impl SctpInner {
             .unwrap_or_default()
             .as_millis() as u64;
         if now_ms < stamp_ms || now_ms - stamp_ms > COOKIE_LIFETIME_MS {
-            return false;
+            return None;
         }
-        true
+        Some(CookieTags::from_bytes(tags))
     }
 
     // aiortc-style T3 expiry logic
@@ -1346,6 +1572,12 @@ impl SctpInner {
         Ok(())
     }
 
+    fn notify_connected(&self) {
+        if let Some(handler) = self.connected_handler.lock().take() {
+            handler();
+        }
+    }
+
     fn set_state(&self, new_state: SctpState) {
         let mut state = self.state.lock();
         if *state != new_state {
@@ -1356,7 +1588,7 @@ impl SctpInner {
 
     async fn handle_packet(&self, packet: Bytes) -> Result<()> {
         let now = Instant::now();
-        if packet.len() < SCTP_COMMON_HEADER_SIZE {
+        if packet.len() < SCTP_COMMON_HEADER_SIZE || *self.state.lock() == SctpState::Closed {
             return Ok(());
         }
 
@@ -1391,6 +1623,7 @@ impl SctpInner {
             }
         }
 
+        let mut has_data = false;
         while buf.has_remaining() {
             if buf.remaining() < CHUNK_HEADER_SIZE {
                 break;
@@ -1418,7 +1651,10 @@ impl SctpInner {
                 CT_INIT_ACK => self.handle_init_ack(chunk_value).await?,
                 CT_COOKIE_ECHO => self.handle_cookie_echo(chunk_value).await?,
                 CT_COOKIE_ACK => self.handle_cookie_ack(chunk_value).await?,
-                CT_DATA => self.handle_data(chunk_flags, chunk_value).await?,
+                CT_DATA => {
+                    has_data = true;
+                    self.handle_data(chunk_flags, chunk_value).await?
+                }
                 CT_SACK => self.handle_sack(chunk_value).await?,
                 CT_HEARTBEAT => self.handle_heartbeat(chunk_value).await?,
                 CT_HEARTBEAT_ACK => {
@@ -1465,6 +1701,9 @@ impl SctpInner {
         }
 
         // SACK will be handled in transmit() to allow bundling
+        if has_data {
+            self.schedule_sack(now);
+        }
 
         Ok(())
     }
@@ -1481,6 +1720,22 @@ impl SctpInner {
         let _inbound_streams = buf.get_u16();
         let initial_tsn = buf.get_u32();
 
+        if *self.state.lock() == SctpState::Connected {
+            // Unexpected INIT (RFC 4960 §5.2.2): it may be a stale duplicate or
+            // a peer restart, which only the COOKIE-ECHO can tell apart. Answer
+            // with a fresh tag and our current tags as tie-tags, and leave the
+            // association untouched.
+            debug!("SCTP INIT received on an established association");
+            let tags = CookieTags {
+                local: random_u32(),
+                peer: initiate_tag,
+                local_tie: self.verification_tag.load(Ordering::SeqCst),
+                peer_tie: self.remote_verification_tag.load(Ordering::SeqCst),
+            };
+            let initial_tsn = self.next_tsn.load(Ordering::SeqCst);
+            return self.send_init_ack(tags, initial_tsn).await;
+        }
+
         self.peer_rwnd.store(a_rwnd, Ordering::SeqCst);
         let init_ssthresh = (a_rwnd as usize).max(SSTHRESH_MIN);
         self.ssthresh.store(init_ssthresh, Ordering::SeqCst);
@@ -1493,12 +1748,23 @@ impl SctpInner {
         let local_tag = random_u32();
         self.verification_tag.store(local_tag, Ordering::SeqCst);
 
+        let initial_tsn = random_u32();
+        self.next_tsn.store(initial_tsn, Ordering::SeqCst);
+        let tags = CookieTags {
+            local: local_tag,
+            peer: initiate_tag,
+            ..Default::default()
+        };
+        self.send_init_ack(tags, initial_tsn).await
+    }
+
+    async fn send_init_ack(&self, tags: CookieTags, initial_tsn: u32) -> Result<()> {
         // Generate HMAC-protected state cookie
-        let cookie = self.generate_cookie();
+        let cookie = self.generate_cookie(tags);
 
         let mut init_ack_params = BytesMut::new();
         // Initiate Tag
-        init_ack_params.put_u32(local_tag);
+        init_ack_params.put_u32(tags.local);
         // a_rwnd
         init_ack_params.put_u32(self.local_rwnd as u32);
         // Outbound streams
@@ -1506,8 +1772,6 @@ impl SctpInner {
         // Inbound streams
         init_ack_params.put_u16(10);
         // Initial TSN
-        let initial_tsn = random_u32();
-        self.next_tsn.store(initial_tsn, Ordering::SeqCst);
         init_ack_params.put_u32(initial_tsn);
 
         // Forward TSN (Type 0xC000)
@@ -1531,7 +1795,7 @@ impl SctpInner {
             init_ack_params.put_u8(0);
         }
 
-        self.send_chunk(CT_INIT_ACK, 0, init_ack_params.freeze(), initiate_tag)
+        self.send_chunk(CT_INIT_ACK, 0, init_ack_params.freeze(), tags.peer)
             .await?;
         Ok(())
     }
@@ -1592,6 +1856,7 @@ impl SctpInner {
     async fn handle_cookie_ack(&self, _chunk: Bytes) -> Result<()> {
         self.t1_cancel();
         *self.state.lock() = SctpState::Connected;
+        self.notify_connected();
         self.advanced_peer_ack_tsn.store(
             self.next_tsn.load(Ordering::SeqCst).wrapping_sub(1),
             Ordering::SeqCst,
@@ -1947,9 +2212,40 @@ impl SctpInner {
     }
 
     async fn handle_cookie_echo(&self, chunk: Bytes) -> Result<()> {
-        if !self.validate_cookie(&chunk) {
+        let Some(tags) = self.validate_cookie(&chunk) else {
             debug!("SCTP: Invalid or expired cookie, ignoring COOKIE-ECHO");
             return Ok(());
+        };
+
+        if *self.state.lock() == SctpState::Connected {
+            let local_tag = self.verification_tag.load(Ordering::SeqCst);
+            let peer_tag = self.remote_verification_tag.load(Ordering::SeqCst);
+            if tags.local == local_tag && tags.peer == peer_tag {
+                // Retransmitted COOKIE-ECHO after our COOKIE-ACK was lost: the
+                // association and its stream state stay as they are.
+                self.send_chunk(CT_COOKIE_ACK, 0, Bytes::new(), peer_tag)
+                    .await?;
+            } else if tags.peer != peer_tag
+                && tags.local_tie == local_tag
+                && tags.peer_tie == peer_tag
+            {
+                // The peer restarted its association (RFC 4960 §5.2.4 action A).
+                // Its TSNs and per-stream SSNs start over, which ordered
+                // channels cannot splice onto what was already delivered
+                // without gaps or duplicates, so fail the association instead
+                // of adopting the new parameters.
+                debug!("SCTP peer restarted an established association, aborting");
+                self.send_chunk(CT_ABORT, 0, Bytes::new(), tags.peer)
+                    .await?;
+                self.print_stats("ASSOCIATION_RESTART");
+                *self.close_reason.lock() = Some("ASSOCIATION_RESTART".into());
+                self.set_state(SctpState::Closed);
+                self.close_data_channels();
+            } else {
+                // A cookie from a duplicate INIT or another stale exchange.
+                debug!("SCTP: COOKIE-ECHO does not match the association, discarding");
+            }
+            return Ok(());
         }
 
         // Send COOKIE ACK
@@ -1957,6 +2253,7 @@ impl SctpInner {
         self.send_chunk(CT_COOKIE_ACK, 0, Bytes::new(), tag).await?;
 
         *self.state.lock() = SctpState::Connected;
+        self.notify_connected();
         self.advanced_peer_ack_tsn.store(
             self.next_tsn.load(Ordering::SeqCst).wrapping_sub(1),
             Ordering::SeqCst,
@@ -2091,52 +2388,87 @@ impl SctpInner {
         }
         let request_sn = buf.get_u32();
         let _response_sn = buf.get_u32();
-        let _send_next_tsn = buf.get_u32();
+        let last_tsn = buf.get_u32();
+        let mut streams = Vec::new();
+        while buf.remaining() >= 2 {
+            streams.push(buf.get_u16());
+        }
 
         let last_peer_sn = self.peer_reconfig_request_sn.load(Ordering::SeqCst);
         if request_sn <= last_peer_sn && last_peer_sn != u32::MAX {
-            // Duplicate request, just ack it again
-            self.send_reconfig_response(request_sn, RECONFIG_RESPONSE_SUCCESS_NOTHING_TO_DO)
-                .await?;
+            // Retransmitted request: our answer may have been lost, repeat it
+            let result = self.stream_resets.lock().answer_to(request_sn);
+            self.send_reconfig_response(request_sn, result).await?;
             return Ok(());
         }
 
         self.peer_reconfig_request_sn
             .store(request_sn, Ordering::SeqCst);
 
-        // Reset SSNs for specified streams
-        let mut streams = Vec::new();
-        while buf.remaining() >= 2 {
-            streams.push(buf.get_u16());
+        let request = SsnResetRequest {
+            request_sn,
+            last_tsn,
+            streams,
+        };
+        // DATA sent before the reset is still missing, so the reset has to
+        // wait for it (RFC 6525 §5.2.2).
+        if tsn_gt(last_tsn, self.cumulative_tsn_ack.load(Ordering::SeqCst)) {
+            debug!(
+                "Deferring stream reset {} until TSN {} arrives",
+                request_sn, last_tsn
+            );
+            self.stream_resets.lock().deferred.push(request);
+            self.send_reconfig_response(request_sn, RECONFIG_RESPONSE_IN_PROGRESS)
+                .await?;
+            return Ok(());
         }
 
-        {
-            let channels = self.data_channels.lock();
-            for weak_dc in channels.iter() {
-                if let Some(dc) = weak_dc.upgrade()
-                    && (streams.is_empty() || streams.contains(&dc.id))
-                {
-                    dc.next_ssn.store(0, Ordering::SeqCst);
-                    debug!("Reset SSN for stream {}", dc.id);
-                }
-            }
-        }
+        let result = self.reset_incoming_streams(&request);
+        self.send_reconfig_response(request_sn, result).await
+    }
 
-        // Reset inbound stream state for affected streams
+    /// The peer reset its outgoing streams, so the matching channels close.
+    /// Channels still open reset their own side in return (RFC 8831 §6.7).
+    fn reset_incoming_streams(&self, request: &SsnResetRequest) -> u32 {
         {
             let mut inbound = self.inbound_streams.lock();
-            if streams.is_empty() {
+            if request.streams.is_empty() {
                 inbound.clear();
             } else {
-                for &sid in &streams {
-                    inbound.remove(&sid);
+                for sid in &request.streams {
+                    inbound.remove(sid);
                 }
             }
         }
 
-        self.send_reconfig_response(request_sn, RECONFIG_RESPONSE_SUCCESS_PERFORMED)
-            .await?;
-        Ok(())
+        let channels: Vec<Arc<DataChannel>> = self
+            .data_channels
+            .lock()
+            .iter()
+            .filter_map(|weak_dc| weak_dc.upgrade())
+            .filter(|dc| request.streams.is_empty() || request.streams.contains(&dc.id))
+            .collect();
+        let mut resets = self.stream_resets.lock();
+        for dc in channels {
+            let previous = dc
+                .state
+                .swap(DataChannelState::Closed as usize, Ordering::SeqCst);
+            match DataChannelState::from(previous) {
+                DataChannelState::Connecting | DataChannelState::Open => {
+                    debug!("Peer closed stream {}, resetting ours", dc.id);
+                    resets.pending.push(dc.id);
+                }
+                // We reset first, or both sides reset at once
+                DataChannelState::Closing => {}
+                DataChannelState::Closed => continue,
+            }
+            dc.send_event(DataChannelEvent::Close);
+            dc.close_channel();
+        }
+        resets.last_response = Some((request.request_sn, RECONFIG_RESPONSE_SUCCESS_PERFORMED));
+        drop(resets);
+        self.timer_notify.notify_one();
+        RECONFIG_RESPONSE_SUCCESS_PERFORMED
     }
 
     fn handle_reconfig_response(&self, mut buf: Bytes) -> Result<()> {
@@ -2149,94 +2481,138 @@ impl SctpInner {
             "Received RE-CONFIG response for SN {}, result: {}",
             response_sn, result
         );
+
+        let mut resets = self.stream_resets.lock();
+        let Some((request, _)) = resets
+            .outstanding
+            .take_if(|(request, _)| request.request_sn == response_sn)
+        else {
+            return Ok(());
+        };
+        match result {
+            RECONFIG_RESPONSE_SUCCESS_PERFORMED | RECONFIG_RESPONSE_SUCCESS_NOTHING_TO_DO => {
+                let channels = self.data_channels.lock();
+                for dc in channels.iter().filter_map(|weak_dc| weak_dc.upgrade()) {
+                    if request.streams.contains(&dc.id) {
+                        dc.next_ssn.store(0, Ordering::SeqCst);
+                    }
+                }
+            }
+            // The peer still waits for our DATA; ask again after an RTO
+            RECONFIG_RESPONSE_IN_PROGRESS => {
+                resets.outstanding = Some((request, Instant::now()));
+                return Ok(());
+            }
+            _ => {
+                debug!(
+                    "Stream reset {} for {:?} refused: {}",
+                    response_sn, request.streams, result
+                );
+            }
+        }
+        drop(resets);
+        // The next request may go out now
+        self.timer_notify.notify_one();
         Ok(())
     }
 
-    async fn send_reconfig_response(&self, response_sn: u32, result: u32) -> Result<()> {
-        let mut param = BytesMut::with_capacity(12);
-        param.put_u16(RECONFIG_PARAM_RESPONSE);
-        param.put_u16(12);
-        param.put_u32(response_sn);
-        param.put_u32(result);
-
-        let mut chunk = BytesMut::with_capacity(16);
-        chunk.put_u8(CT_RECONFIG);
-        chunk.put_u8(0);
-        chunk.put_u16(16);
-        chunk.put(param);
+    /// RE-CONFIG chunks due now: answers to deferred peer requests whose
+    /// DATA has arrived, and our next or retransmitted reset request.
+    fn poll_stream_resets(&self, now: Instant) -> Vec<Bytes> {
+        let mut chunks = Vec::new();
 
-        let tag = self.remote_verification_tag.load(Ordering::SeqCst);
-        self.transmit_chunks_with_tag(vec![chunk.freeze()], tag)
-            .await
-    }
-
-    pub async fn send_reconfig_ssn_reset(&self, streams: &[u16]) -> Result<()> {
-        let request_sn = self.reconfig_request_sn.fetch_add(1, Ordering::SeqCst);
-        let param_len = 16 + streams.len() * 2;
-        let mut param = BytesMut::with_capacity(param_len);
-        param.put_u16(RECONFIG_PARAM_OUTGOING_SSN_RESET);
-        param.put_u16(param_len as u16);
-        param.put_u32(request_sn);
-        param.put_u32(0); // response SN (not used for outgoing reset)
-        param.put_u32(self.next_tsn.load(Ordering::SeqCst));
+        let cumulative_tsn_ack = self.cumulative_tsn_ack.load(Ordering::SeqCst);
+        let ready: Vec<SsnResetRequest> = {
+            let mut resets = self.stream_resets.lock();
+            let (ready, waiting) = std::mem::take(&mut resets.deferred)
+                .into_iter()
+                .partition(|r| !tsn_gt(r.last_tsn, cumulative_tsn_ack));
+            resets.deferred = waiting;
+            ready
+        };
+        for request in ready {
+            let result = self.reset_incoming_streams(&request);
+            chunks.push(reconfig_response_chunk(request.request_sn, result));
+        }
 
-        for &stream in streams {
-            param.put_u16(stream);
+        let rto = Duration::from_secs_f64(self.rto_state.lock().rto);
+        let mut resets = self.stream_resets.lock();
+        if let Some((request, sent_at)) = &mut resets.outstanding {
+            if now >= *sent_at + rto {
+                *sent_at = now;
+                chunks.push(reconfig_request_chunk(request));
+            }
+            return chunks;
         }
 
-        // Padding for parameter
-        let padding = (4 - (param_len % 4)) % 4;
-        for _ in 0..padding {
-            param.put_u8(0);
+        // A stream resets only once its queued DATA has TSNs, so the last
+        // assigned TSN covers everything sent on it.
+        let (ready, waiting): (Vec<u16>, Vec<u16>) = {
+            let outbound = self.outbound_queue.lock();
+            std::mem::take(&mut resets.pending)
+                .into_iter()
+                .partition(|&sid| !outbound.has_stream(sid))
+        };
+        resets.pending = waiting;
+        if !ready.is_empty() {
+            let request = SsnResetRequest {
+                request_sn: self.reconfig_request_sn.fetch_add(1, Ordering::SeqCst),
+                last_tsn: self.next_tsn.load(Ordering::SeqCst).wrapping_sub(1),
+                streams: ready,
+            };
+            debug!("Resetting outgoing streams {:?}", request.streams);
+            chunks.push(reconfig_request_chunk(&request));
+            resets.outstanding = Some((request, now));
         }
+        chunks
+    }
 
-        let chunk_len = 4 + param.len();
-        let mut chunk = BytesMut::with_capacity(chunk_len);
-        chunk.put_u8(CT_RECONFIG);
-        chunk.put_u8(0);
-        chunk.put_u16(chunk_len as u16);
-        chunk.put(param);
+    /// Time until our outstanding reset request should be retransmitted.
+    fn stream_reset_timeout(&self, now: Instant, rto: f64) -> Duration {
+        match &self.stream_resets.lock().outstanding {
+            Some((_, sent_at)) => {
+                (*sent_at + Duration::from_secs_f64(rto)).saturating_duration_since(now)
+            }
+            None => Duration::from_secs(3600),
+        }
+    }
 
+    async fn send_reconfig_response(&self, response_sn: u32, result: u32) -> Result<()> {
         let tag = self.remote_verification_tag.load(Ordering::SeqCst);
-        self.transmit_chunks_with_tag(vec![chunk.freeze()], tag)
+        self.transmit_chunks_with_tag(vec![reconfig_response_chunk(response_sn, result)], tag)
             .await
     }
 
+    /// Close a channel by resetting its outgoing stream (RFC 8831 §6.7).
+    ///
+    /// The reset goes out after any DATA already queued on the stream, and
+    /// the channel emits `Close` once the peer has reset its side as well.
     pub async fn close_data_channel(&self, channel_id: u16) -> Result<()> {
-        // 1. Find the channel and set state to Closing
-        {
-            let channels = self.data_channels.lock();
-            if let Some(dc) = channels
-                .iter()
-                .find_map(|w| w.upgrade().filter(|d| d.id == channel_id))
-            {
-                dc.state
-                    .store(DataChannelState::Closing as usize, Ordering::SeqCst);
-            }
-        }
-
-        // 2. Send RE-CONFIG SSN Reset
-        self.send_reconfig_ssn_reset(&[channel_id]).await?;
-
-        // 3. Clean up inbound stream state
-        {
-            let mut streams = self.inbound_streams.lock();
-            streams.remove(&channel_id);
-        }
+        let Some(dc) = self
+            .data_channels
+            .lock()
+            .iter()
+            .find_map(|w| w.upgrade().filter(|d| d.id == channel_id))
+        else {
+            return Ok(());
+        };
 
-        // 4. Set state to Closed
-        {
-            let channels = self.data_channels.lock();
-            if let Some(dc) = channels
-                .iter()
-                .find_map(|w| w.upgrade().filter(|d| d.id == channel_id))
-            {
-                dc.state
-                    .store(DataChannelState::Closed as usize, Ordering::SeqCst);
-                dc.send_event(DataChannelEvent::Close);
-            }
+        // A send holding the lock finishes queueing before the reset.
+        let _guard = dc.send_lock.lock().await;
+        let closing = dc
+            .state
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |state| {
+                let open = matches!(
+                    DataChannelState::from(state),
+                    DataChannelState::Connecting | DataChannelState::Open
+                );
+                open.then_some(DataChannelState::Closing as usize)
+            })
+            .is_ok();
+        if closing {
+            self.stream_resets.lock().pending.push(channel_id);
+            self.timer_notify.notify_one();
         }
-
         Ok(())
     }
 
@@ -2376,6 +2752,7 @@ impl SctpInner {
                     dups.push(tsn);
                 }
             }
+            self.sack_immediate.store(true, Ordering::Relaxed);
             self.sack_needed.store(true, Ordering::Relaxed);
             return Ok(());
         }
@@ -2393,7 +2770,19 @@ impl SctpInner {
         }
 
         // Slow path: out of order or need to drain queue
-        // Store in received_queue and process in order under one lock
+        // Store in received_queue and process in order under one lock.
+        // Gaps being opened or filled are reported without delay (RFC 4960 §6.7).
+        self.sack_immediate.store(true, Ordering::Relaxed);
+
+        // A complete unordered message does not wait for earlier TSNs
+        // (RFC 4960 §6.6). Only an empty placeholder is queued so the
+        // cumulative ack still advances past it without redelivery.
+        let mut chunk = chunk;
+        if flags & 0x07 == 0x07 && !self.received_queue.lock().contains_key(&tsn) {
+            self.process_data_payload(flags, chunk).await?;
+            chunk = Bytes::new();
+        }
+
         let mut to_process = Vec::new();
         {
             let mut received_queue = self.received_queue.lock();
@@ -2436,7 +2825,9 @@ impl SctpInner {
                     .load(Ordering::Relaxed)
                     .wrapping_add(1);
 
-                self.process_data_payload(p_flags, p_chunk).await?;
+                if !p_chunk.is_empty() {
+                    self.process_data_payload(p_flags, p_chunk).await?;
+                }
                 self.cumulative_tsn_ack.store(next_tsn, Ordering::Relaxed);
                 self.used_rwnd.fetch_sub(chunk_len, Ordering::Relaxed);
             }
@@ -2769,6 +3160,14 @@ impl SctpInner {
 
         let (_guard, ssn) = if let Some(dc) = &dc_opt {
             let guard = dc.send_lock.lock().await;
+            if !is_dcep
+                && matches!(
+                    DataChannelState::from(dc.state.load(Ordering::SeqCst)),
+                    DataChannelState::Closing | DataChannelState::Closed
+                )
+            {
+                return Err(anyhow::anyhow!("data channel {} is closed", channel_id));
+            }
             ordered = if is_dcep { false } else { dc.ordered };
             let ssn = if ordered {
                 dc.next_ssn.fetch_add(1, Ordering::SeqCst)
@@ -2822,10 +3221,23 @@ impl SctpInner {
             if flight + queued <= MAX_BUFFERED_AMOUNT {
                 break;
             }
+            // A channel with nothing queued may always enqueue one message, so a
+            // saturating channel cannot lock the others out of the association.
+            if let Some(dc) = &dc_opt
+                && !is_dcep
+                && dc.buffered_amount() == 0
+            {
+                break;
+            }
             self.flow_control_notify.notified().await;
         }
 
         self.queued_bytes.fetch_add(total_len, Ordering::Relaxed);
+        if let Some(dc) = &dc_opt
+            && !is_dcep
+        {
+            dc.add_buffered_amount(total_len);
+        }
 
         if total_len == 0 {
             // Handle empty message
@@ -2885,8 +3297,13 @@ impl SctpInner {
     async fn transmit(&self) -> Result<()> {
         let mut chunks_to_send = Vec::new();
 
-        if self.sack_needed.swap(false, Ordering::Acquire) {
+        let mut sack_sent = false;
+        if self.sack_needed.load(Ordering::Acquire)
+            && self.sack_is_due(Instant::now())
+            && self.sack_needed.swap(false, Ordering::Acquire)
+        {
             chunks_to_send.push(self.create_sack_chunk());
+            sack_sent = true;
         }
 
         // 1. Calculate Effective Window
@@ -2946,6 +3363,7 @@ impl SctpInner {
             let mut budget = available;
             let mut batch: Vec<OutboundChunk> = Vec::new();
             let mut dequeued_bytes = 0usize;
+            let mut dequeued_per_stream: Vec<(u16, usize)> = Vec::new();
             {
                 let mut outbound = self.outbound_queue.lock();
                 while budget > 0 && batch.len() < 1000 {
@@ -2953,6 +3371,16 @@ impl SctpInner {
                         let chunk_wire_size = CHUNK_HEADER_SIZE + 12 + chunk_info.payload.len();
                         let padded = chunk_wire_size + (4 - (chunk_wire_size % 4)) % 4;
                         dequeued_bytes += chunk_info.payload.len();
+                        if chunk_info.ppid != DATA_CHANNEL_PPID_DCEP {
+                            match dequeued_per_stream
+                                .iter_mut()
+                                .find(|(sid, _)| *sid == chunk_info.stream_id)
+                            {
+                                Some((_, n)) => *n += chunk_info.payload.len(),
+                                None => dequeued_per_stream
+                                    .push((chunk_info.stream_id, chunk_info.payload.len())),
+                            }
+                        }
                         budget = budget.saturating_sub(padded);
                         batch.push(chunk_info);
                     } else {
@@ -2964,6 +3392,19 @@ impl SctpInner {
                 self.queued_bytes
                     .fetch_sub(dequeued_bytes, Ordering::Relaxed);
             }
+            if !dequeued_per_stream.is_empty() {
+                let channels: Vec<Arc<DataChannel>> = self
+                    .data_channels
+                    .lock()
+                    .iter()
+                    .filter_map(|weak_dc| weak_dc.upgrade())
+                    .collect();
+                for (stream_id, len) in dequeued_per_stream {
+                    if let Some(dc) = channels.iter().find(|dc| dc.id == stream_id) {
+                        dc.sub_buffered_amount(len);
+                    }
+                }
+            }
 
             let now = Instant::now();
             let mut sent = self.sent_queue.lock();
@@ -3003,6 +3444,9 @@ impl SctpInner {
             }
         }
 
+        // Stream resets follow the DATA assigned TSNs above
+        chunks_to_send.extend(self.poll_stream_resets(Instant::now()));
+
         // PR-SCTP: check for abandoned chunks and send FORWARD-TSN
         // Only scan when PR-SCTP channels exist (max_retransmits or expiry set)
         if self.has_pr_sctp.load(Ordering::Relaxed) {
@@ -3014,6 +3458,21 @@ impl SctpInner {
             }
         }
 
+        // A delayed SACK rides along with any outgoing chunks
+        if !sack_sent
+            && !chunks_to_send.is_empty()
+            && self.sack_needed.swap(false, Ordering::Acquire)
+        {
+            chunks_to_send.insert(0, self.create_sack_chunk());
+            sack_sent = true;
+        }
+        if sack_sent {
+            self.unacked_data_packets.store(0, Ordering::Relaxed);
+            self.sack_immediate.store(false, Ordering::Relaxed);
+            *self.sack_deadline.lock() = None;
+            self.stats_sacks_sent.fetch_add(1, Ordering::Relaxed);
+        }
+
         if !chunks_to_send.is_empty() {
             self.transmit_chunks(chunks_to_send).await?;
         }
@@ -3021,6 +3480,29 @@ impl SctpInner {
         Ok(())
     }
 
+    /// Count a received packet carrying DATA and arm the delayed SACK timer.
+    fn schedule_sack(&self, now: Instant) {
+        let unacked = self.unacked_data_packets.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.sack_delay.is_zero() || unacked >= self.sack_max_unacked {
+            self.sack_immediate.store(true, Ordering::Relaxed);
+        } else {
+            self.sack_deadline
+                .lock()
+                .get_or_insert(now + self.sack_delay);
+        }
+    }
+
+    /// Whether a pending SACK must go out now rather than wait for the
+    /// delayed SACK timer or for outgoing DATA to bundle with.
+    fn sack_is_due(&self, now: Instant) -> bool {
+        if self.sack_delay.is_zero() || self.sack_immediate.load(Ordering::Relaxed) {
+            return true;
+        }
+        self.sack_deadline
+            .lock()
+            .is_none_or(|deadline| now >= deadline)
+    }
+
     fn should_abandon(record: &ChunkRecord) -> bool {
         if record.abandoned {
             return true;
@@ -3306,6 +3788,7 @@ impl SctpInner {
         let packets_received = self.stats_packets_received.load(Ordering::SeqCst);
         let retransmissions = self.stats_retransmissions.load(Ordering::SeqCst);
         let heartbeats_sent = self.stats_heartbeats_sent.load(Ordering::SeqCst);
+        let sacks_sent = self.stats_sacks_sent.load(Ordering::SeqCst);
         let error_count = self.association_error_count.load(Ordering::SeqCst);
         let cwnd_tx = self.cwnd_tx.load(Ordering::SeqCst);
         let cwnd_rx = self.cwnd_rx.load(Ordering::SeqCst);
@@ -3326,6 +3809,7 @@ impl SctpInner {
              Packets Received: {}\n\
              Retransmissions: {} ({:.1}% of sent)\n\
              Heartbeats Sent: {}\n\
+             SACKs Sent: {}\n\
              Error Count: {}/{} (consecutive hb failures: {}/{})\n\
              Final RTO: {:.1}s\n\
              Final CWND_TX: {} bytes\n\
@@ -3350,6 +3834,7 @@ impl SctpInner {
                 0.0
             },
             heartbeats_sent,
+            sacks_sent,
             error_count,
             self.max_association_retransmits,
             consecutive_hb,
@@ -6037,11 +6522,20 @@ mod tests {
             .unwrap()
             .as_millis() as u64;
 
+        let tags = CookieTags {
+            local: 1,
+            peer: 2,
+            local_tie: 3,
+            peer_tie: 4,
+        };
+        let mac_offset = COOKIE_TIMESTAMP_LEN + COOKIE_TAGS_LEN;
+
         // Build cookie
         let mut cookie = Vec::with_capacity(COOKIE_TOTAL_LEN);
         cookie.extend_from_slice(&now_ms.to_be_bytes());
+        cookie.extend_from_slice(&tags.to_bytes());
         let mut mac = TestHmac::new_from_slice(&key).expect("HMAC key");
-        mac.update(&now_ms.to_be_bytes());
+        mac.update(&cookie);
         let hmac_result = mac.finalize().into_bytes();
         cookie.extend_from_slice(&hmac_result);
         assert_eq!(cookie.len(), COOKIE_TOTAL_LEN);
@@ -6050,25 +6544,21 @@ mod tests {
         let ts_bytes: [u8; 8] = cookie[..COOKIE_TIMESTAMP_LEN].try_into().unwrap();
         let ts = u64::from_be_bytes(ts_bytes);
         assert_eq!(ts, now_ms);
+        assert_eq!(
+            CookieTags::from_bytes(&cookie[COOKIE_TIMESTAMP_LEN..mac_offset]),
+            tags
+        );
 
         let mut verifier = TestHmac::new_from_slice(&key).expect("HMAC key");
-        verifier.update(&ts.to_be_bytes());
-        assert!(
-            verifier
-                .verify_slice(&cookie[COOKIE_TIMESTAMP_LEN..])
-                .is_ok()
-        );
+        verifier.update(&cookie[..mac_offset]);
+        assert!(verifier.verify_slice(&cookie[mac_offset..]).is_ok());
 
-        // Tampered cookie fails
+        // Tampered tags fail
         let mut bad_cookie = cookie.clone();
-        bad_cookie[COOKIE_TOTAL_LEN - 1] ^= 0xFF;
+        bad_cookie[COOKIE_TIMESTAMP_LEN] ^= 0xFF;
         let mut verifier2 = TestHmac::new_from_slice(&key).expect("HMAC key");
-        verifier2.update(&ts.to_be_bytes());
-        assert!(
-            verifier2
-                .verify_slice(&bad_cookie[COOKIE_TIMESTAMP_LEN..])
-                .is_err()
-        );
+        verifier2.update(&bad_cookie[..mac_offset]);
+        assert!(verifier2.verify_slice(&bad_cookie[mac_offset..]).is_err());
     }
 
     // ===== PR-SCTP Tests =====
@@ -6539,6 +7029,78 @@ mod tests {
         println!("✅ SACK generation with out-of-order packets works correctly!");
     }
 
+    #[tokio::test]
+    async fn test_unordered_channel_delivers_out_of_send_order() {
+        let (socket_tx, _) = tokio::sync::watch::channel(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_tx.subscribe(),
+            "127.0.0.1:5000".parse().unwrap(),
+            None,
+        );
+        let cert = crate::transports::dtls::generate_certificate().unwrap();
+        let (dtls, _, _) = DtlsTransport::new(ice_conn, cert, true, 100, None)
+            .await
+            .unwrap();
+
+        let config = RtcConfiguration::default();
+        let (_incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let channels = Arc::new(Mutex::new(Vec::new()));
+        let (sctp, runner) = SctpTransport::new(
+            dtls,
+            incoming_rx,
+            channels.clone(),
+            5000,
+            5000,
+            None,
+            true,
+            &config,
+        );
+        tokio::spawn(runner);
+
+        let dc = Arc::new(DataChannel::new(
+            0,
+            DataChannelConfig {
+                ordered: false,
+                max_retransmits: Some(0),
+                ..Default::default()
+            },
+        ));
+        channels.lock().push(Arc::downgrade(&dc));
+        *sctp.inner.state.lock() = SctpState::Connecting;
+        sctp.inner.cumulative_tsn_ack.store(99, Ordering::SeqCst);
+
+        let unordered_chunk = |tsn: u32, payload: &'static [u8]| {
+            let mut buf = BytesMut::new();
+            buf.put_u32(tsn);
+            buf.put_u16(0); // Stream ID
+            buf.put_u16(0); // SSN (ignored when unordered)
+            buf.put_u32(DATA_CHANNEL_PPID_BINARY);
+            buf.put_slice(payload);
+            buf.freeze()
+        };
+
+        // "second" overtakes "first" on the wire: U=1, B=1, E=1.
+        sctp.inner
+            .handle_data(0x07, unordered_chunk(101, b"second"))
+            .await
+            .unwrap();
+        sctp.inner
+            .handle_data(0x07, unordered_chunk(100, b"first"))
+            .await
+            .unwrap();
+
+        let mut delivered = Vec::new();
+        for _ in 0..2 {
+            match dc.recv().await {
+                Some(DataChannelEvent::Message(msg)) => delivered.push(msg),
+                other => panic!("expected message, got {other:?}"),
+            }
+        }
+        assert_eq!(delivered, vec![&b"second"[..], &b"first"[..]]);
+        assert_eq!(sctp.inner.cumulative_tsn_ack.load(Ordering::SeqCst), 101);
+        assert!(sctp.inner.received_queue.lock().is_empty());
+    }
+
     /// Test: Simulate the exact scenario from the bug report
     /// A TSN is retransmitted multiple times but the peer never receives it
     /// This tests that the connection correctly handles repeated retransmissions
@@ -8863,4 +9425,560 @@ mod tests {
             "Duplicate SACK should not trigger fast retransmit"
         );
     }
+
+    #[test]
+    fn outbound_queue_round_robins_whole_messages() {
+        let chunk = |stream_id: u16, ssn: u16, flags: u8| OutboundChunk {
+            stream_id,
+            ppid: DATA_CHANNEL_PPID_BINARY,
+            payload: Bytes::from_static(b"x"),
+            flags,
+            ssn,
+            max_retransmits: None,
+            expiry: None,
+        };
+
+        let mut queue = OutboundQueue::default();
+        // Stream 0: a three-fragment message then a single-chunk message.
+        queue.push_back(chunk(0, 0, 0x02));
+        queue.push_back(chunk(0, 0, 0x00));
+        queue.push_back(chunk(0, 0, 0x01));
+        queue.push_back(chunk(0, 1, 0x03));
+        // Stream 2: two single-chunk messages.
+        queue.push_back(chunk(2, 0, 0x03));
+        queue.push_back(chunk(2, 1, 0x03));
+        assert_eq!(queue.len(), 6);
+
+        let order: Vec<(u16, u16)> = std::iter::from_fn(|| queue.pop_front())
+            .map(|c| (c.stream_id, c.ssn))
+            .collect();
+        assert_eq!(
+            order,
+            vec![(0, 0), (0, 0), (0, 0), (2, 0), (0, 1), (2, 1)],
+            "fragments stay contiguous and streams alternate per message"
+        );
+        assert_eq!(queue.len(), 0);
+    }
+
+    /// Build an SCTP packet carrying one chunk, as the remote end would.
+    fn remote_packet(tag: u32, chunk_type: u8, flags: u8, value: &[u8]) -> Bytes {
+        let mut buf = BytesMut::new();
+        buf.put_u16(5000);
+        buf.put_u16(5000);
+        buf.put_u32(tag);
+        buf.put_u32(0);
+        buf.put_u8(chunk_type);
+        buf.put_u8(flags);
+        buf.put_u16((CHUNK_HEADER_SIZE + value.len()) as u16);
+        buf.put_slice(value);
+        while !buf.len().is_multiple_of(4) {
+            buf.put_u8(0);
+        }
+        let checksum = crc32c::crc32c(&buf).to_le_bytes();
+        buf[8..12].copy_from_slice(&checksum);
+        buf.freeze()
+    }
+
+    fn data_chunk(tsn: u32, ssn: u16, payload: &[u8]) -> Vec<u8> {
+        let mut value = Vec::new();
+        value.extend_from_slice(&tsn.to_be_bytes());
+        value.extend_from_slice(&0u16.to_be_bytes());
+        value.extend_from_slice(&ssn.to_be_bytes());
+        value.extend_from_slice(&DATA_CHANNEL_PPID_BINARY.to_be_bytes());
+        value.extend_from_slice(payload);
+        value
+    }
+
+    /// A COOKIE-ECHO completing an INIT that arrived mid-transfer means the
+    /// peer restarted and its TSN/SSN numbering starts over. The association
+    /// must fail with a defined reason and close its channels rather than
+    /// deliver the restarted stream.
+    #[tokio::test]
+    async fn test_init_on_established_association_fails_channels() {
+        let (socket_tx, _) = tokio::sync::watch::channel(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_tx.subscribe(),
+            "127.0.0.1:5000".parse().unwrap(),
+            None,
+        );
+        let cert = crate::transports::dtls::generate_certificate().unwrap();
+        let (dtls, _, _) = DtlsTransport::new(ice_conn, cert, true, 100, None)
+            .await
+            .unwrap();
+
+        let dc = Arc::new(DataChannel::new(
+            0,
+            DataChannelConfig {
+                label: "ordered".into(),
+                ordered: true,
+                negotiated: Some(0),
+                ..Default::default()
+            },
+        ));
+        dc.state
+            .store(DataChannelState::Open as usize, Ordering::SeqCst);
+        let (_incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let (sctp, _runner) = SctpTransport::new(
+            dtls,
+            incoming_rx,
+            Arc::new(Mutex::new(vec![Arc::downgrade(&dc)])),
+            5000,
+            5000,
+            None,
+            false,
+            &RtcConfiguration::default(),
+        );
+
+        let local_tag = 0x1111_1111;
+        *sctp.inner.state.lock() = SctpState::Connected;
+        sctp.inner
+            .verification_tag
+            .store(local_tag, Ordering::SeqCst);
+        sctp.inner
+            .remote_verification_tag
+            .store(0x2222_2222, Ordering::SeqCst);
+        sctp.inner.cumulative_tsn_ack.store(99, Ordering::SeqCst);
+
+        let inner = &sctp.inner;
+        for (tsn, ssn, payload) in [(100, 0, &b"first"[..]), (101, 1, &b"second"[..])] {
+            let packet = remote_packet(local_tag, CT_DATA, 0x03, &data_chunk(tsn, ssn, payload));
+            inner.handle_packet(packet).await.unwrap();
+        }
+
+        let mut init = Vec::new();
+        init.extend_from_slice(&0x3333_3333u32.to_be_bytes()); // new initiate tag
+        init.extend_from_slice(&131072u32.to_be_bytes());
+        init.extend_from_slice(&10u16.to_be_bytes());
+        init.extend_from_slice(&10u16.to_be_bytes());
+        init.extend_from_slice(&0u32.to_be_bytes()); // restarted TSN space
+        inner
+            .handle_packet(remote_packet(0, CT_INIT, 0, &init))
+            .await
+            .unwrap();
+        assert_eq!(*inner.state.lock(), SctpState::Connected);
+
+        // The cookie our INIT-ACK carried: a fresh local tag, the new peer tag
+        // and the established tags as tie-tags.
+        let cookie = inner.generate_cookie(CookieTags {
+            local: 0x4444_4444,
+            peer: 0x3333_3333,
+            local_tie: local_tag,
+            peer_tie: 0x2222_2222,
+        });
+        inner
+            .handle_packet(remote_packet(0x4444_4444, CT_COOKIE_ECHO, 0, &cookie))
+            .await
+            .unwrap();
+        // The restarted peer reuses SSN 0, which would duplicate "first".
+        let again = remote_packet(local_tag, CT_DATA, 0x03, &data_chunk(0, 0, b"again"));
+        inner.handle_packet(again).await.unwrap();
+
+        let mut delivered = Vec::new();
+        let closed = tokio::time::timeout(Duration::from_secs(2), async {
+            while let Some(event) = dc.recv().await {
+                match event {
+                    DataChannelEvent::Message(data) => delivered.push(data),
+                    DataChannelEvent::Close => return true,
+                    _ => {}
+                }
+            }
+            false
+        })
+        .await
+        .expect("channel should be closed after the restart");
+
+        assert!(closed, "channel must report Close");
+        assert_eq!(
+            delivered,
+            vec![Bytes::from_static(b"first"), Bytes::from_static(b"second")]
+        );
+        assert_eq!(
+            dc.state.load(Ordering::SeqCst),
+            DataChannelState::Closed as usize
+        );
+        assert_eq!(*inner.state.lock(), SctpState::Closed);
+        assert_eq!(
+            inner.close_reason.lock().as_deref(),
+            Some("ASSOCIATION_RESTART")
+        );
+    }
+
+    /// A retransmitted COOKIE-ECHO on an established association is only
+    /// re-acknowledged; it must not reopen channels.
+    #[tokio::test]
+    async fn test_duplicate_cookie_echo_keeps_association() {
+        let (socket_tx, _) = tokio::sync::watch::channel(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_tx.subscribe(),
+            "127.0.0.1:5000".parse().unwrap(),
+            None,
+        );
+        let cert = crate::transports::dtls::generate_certificate().unwrap();
+        let (dtls, _, _) = DtlsTransport::new(ice_conn, cert, true, 100, None)
+            .await
+            .unwrap();
+
+        let dc = Arc::new(DataChannel::new(
+            0,
+            DataChannelConfig {
+                label: "ordered".into(),
+                ordered: true,
+                negotiated: Some(0),
+                ..Default::default()
+            },
+        ));
+        let (_incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let (sctp, _runner) = SctpTransport::new(
+            dtls,
+            incoming_rx,
+            Arc::new(Mutex::new(vec![Arc::downgrade(&dc)])),
+            5000,
+            5000,
+            None,
+            false,
+            &RtcConfiguration::default(),
+        );
+
+        let cookie = Bytes::from(sctp.inner.generate_cookie(CookieTags {
+            local: sctp.inner.verification_tag.load(Ordering::SeqCst),
+            peer: sctp.inner.remote_verification_tag.load(Ordering::SeqCst),
+            ..Default::default()
+        }));
+        sctp.inner.handle_cookie_echo(cookie.clone()).await.unwrap();
+        assert!(matches!(dc.recv().await, Some(DataChannelEvent::Open)));
+
+        sctp.inner.handle_cookie_echo(cookie).await.unwrap();
+        assert_eq!(*sctp.inner.state.lock(), SctpState::Connected);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), dc.recv())
+                .await
+                .is_err(),
+            "no second Open event"
+        );
+    }
+
+    /// A duplicate INIT from the established peer is answered with an INIT-ACK
+    /// only; neither it nor the cookie echoed back disturbs the association.
+    #[tokio::test]
+    async fn test_duplicate_init_keeps_association() {
+        let (socket_tx, _) = tokio::sync::watch::channel(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_tx.subscribe(),
+            "127.0.0.1:5000".parse().unwrap(),
+            None,
+        );
+        let cert = crate::transports::dtls::generate_certificate().unwrap();
+        let (dtls, _, _) = DtlsTransport::new(ice_conn, cert, true, 100, None)
+            .await
+            .unwrap();
+
+        let dc = Arc::new(DataChannel::new(
+            0,
+            DataChannelConfig {
+                label: "ordered".into(),
+                ordered: true,
+                negotiated: Some(0),
+                ..Default::default()
+            },
+        ));
+        dc.state
+            .store(DataChannelState::Open as usize, Ordering::SeqCst);
+        let (_incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let (sctp, _runner) = SctpTransport::new(
+            dtls,
+            incoming_rx,
+            Arc::new(Mutex::new(vec![Arc::downgrade(&dc)])),
+            5000,
+            5000,
+            None,
+            false,
+            &RtcConfiguration::default(),
+        );
+
+        let local_tag = 0x1111_1111;
+        let peer_tag = 0x2222_2222;
+        let inner = &sctp.inner;
+        *inner.state.lock() = SctpState::Connected;
+        inner.verification_tag.store(local_tag, Ordering::SeqCst);
+        inner
+            .remote_verification_tag
+            .store(peer_tag, Ordering::SeqCst);
+        inner.cumulative_tsn_ack.store(99, Ordering::SeqCst);
+        inner.next_tsn.store(500, Ordering::SeqCst);
+
+        let mut init = Vec::new();
+        init.extend_from_slice(&peer_tag.to_be_bytes()); // same initiate tag
+        init.extend_from_slice(&131072u32.to_be_bytes());
+        init.extend_from_slice(&10u16.to_be_bytes());
+        init.extend_from_slice(&10u16.to_be_bytes());
+        init.extend_from_slice(&0u32.to_be_bytes());
+        inner
+            .handle_packet(remote_packet(0, CT_INIT, 0, &init))
+            .await
+            .unwrap();
+
+        // The cookie from our INIT-ACK matches neither a restart nor a
+        // duplicate COOKIE-ECHO, so it is discarded.
+        let cookie = inner.generate_cookie(CookieTags {
+            local: 0x4444_4444,
+            peer: peer_tag,
+            local_tie: local_tag,
+            peer_tie: peer_tag,
+        });
+        inner
+            .handle_packet(remote_packet(0x4444_4444, CT_COOKIE_ECHO, 0, &cookie))
+            .await
+            .unwrap();
+
+        assert_eq!(*inner.state.lock(), SctpState::Connected);
+        assert_eq!(inner.verification_tag.load(Ordering::SeqCst), local_tag);
+        assert_eq!(
+            inner.remote_verification_tag.load(Ordering::SeqCst),
+            peer_tag
+        );
+        assert_eq!(inner.cumulative_tsn_ack.load(Ordering::SeqCst), 99);
+        assert_eq!(inner.next_tsn.load(Ordering::SeqCst), 500);
+        assert!(inner.close_reason.lock().is_none());
+
+        let packet = remote_packet(local_tag, CT_DATA, 0x03, &data_chunk(100, 0, b"still up"));
+        inner.handle_packet(packet).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(1), dc.recv())
+            .await
+            .expect("message delivered");
+        assert!(
+            matches!(event, Some(DataChannelEvent::Message(ref data)) if data.as_ref() == b"still up")
+        );
+    }
+
+    /// Feed `count` single-DATA packets through `handle_packet`, transmitting
+    /// after each like the run loop does. Returns the SACKs sent and the
+    /// messages delivered.
+    async fn receive_data_packets(config: &RtcConfiguration, count: u32) -> (u64, Vec<Bytes>) {
+        let (socket_tx, _) = tokio::sync::watch::channel(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_tx.subscribe(),
+            "127.0.0.1:5000".parse().unwrap(),
+            None,
+        );
+        let cert = crate::transports::dtls::generate_certificate().unwrap();
+        let (dtls, _, _) = DtlsTransport::new(ice_conn, cert, true, 100, None)
+            .await
+            .unwrap();
+
+        let dc = Arc::new(DataChannel::new(
+            0,
+            DataChannelConfig {
+                label: "ordered".into(),
+                ordered: true,
+                negotiated: Some(0),
+                ..Default::default()
+            },
+        ));
+        dc.state
+            .store(DataChannelState::Open as usize, Ordering::SeqCst);
+        let (_incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let (sctp, _runner) = SctpTransport::new(
+            dtls,
+            incoming_rx,
+            Arc::new(Mutex::new(vec![Arc::downgrade(&dc)])),
+            5000,
+            5000,
+            None,
+            false,
+            config,
+        );
+
+        let local_tag = 0x1111_1111;
+        let inner = &sctp.inner;
+        *inner.state.lock() = SctpState::Connected;
+        inner.verification_tag.store(local_tag, Ordering::SeqCst);
+        inner
+            .remote_verification_tag
+            .store(0x2222_2222, Ordering::SeqCst);
+        inner.cumulative_tsn_ack.store(99, Ordering::SeqCst);
+
+        for i in 0..count {
+            let payload = format!("msg-{i}");
+            let chunk = data_chunk(100 + i, i as u16, payload.as_bytes());
+            let packet = remote_packet(local_tag, CT_DATA, 0x03, &chunk);
+            inner.handle_packet(packet).await.unwrap();
+            let _ = inner.transmit().await;
+        }
+        // Let a partially filled delayed SACK expire.
+        if inner.sack_needed.load(Ordering::SeqCst) {
+            tokio::time::sleep(inner.sack_delay).await;
+            let _ = inner.transmit().await;
+        }
+        assert!(!inner.sack_needed.load(Ordering::SeqCst));
+        assert_eq!(inner.cumulative_tsn_ack.load(Ordering::SeqCst), 99 + count);
+
+        let mut delivered = Vec::new();
+        while let Ok(Some(event)) = tokio::time::timeout(Duration::from_millis(50), dc.recv()).await
+        {
+            if let DataChannelEvent::Message(data) = event {
+                delivered.push(data);
+            }
+        }
+        (inner.stats_sacks_sent.load(Ordering::SeqCst), delivered)
+    }
+
+    #[tokio::test]
+    async fn test_delayed_sack_coalesces_acknowledgements() {
+        const PACKETS: u32 = 22;
+        let expected: Vec<Bytes> = (0..PACKETS)
+            .map(|i| Bytes::from(format!("msg-{i}")))
+            .collect();
+
+        let (immediate_sacks, immediate_data) =
+            receive_data_packets(&RtcConfiguration::default(), PACKETS).await;
+        assert_eq!(immediate_sacks, PACKETS as u64);
+        assert_eq!(immediate_data, expected);
+
+        let delayed = RtcConfiguration {
+            sctp_sack_delay: Duration::from_millis(20),
+            sctp_sack_max_unacked: 4,
+            ..Default::default()
+        };
+        let (delayed_sacks, delayed_data) = receive_data_packets(&delayed, PACKETS).await;
+        // One SACK per 4 packets, plus one for the 2 left when the timer fires.
+        assert_eq!(delayed_sacks, 6);
+        assert!(delayed_sacks * 3 < immediate_sacks);
+        assert_eq!(delayed_data, expected);
+    }
+
+    /// Out-of-order DATA is acknowledged at once even with delayed SACK, so
+    /// the sender learns about the gap without waiting for the timer.
+    #[tokio::test]
+    async fn test_delayed_sack_reports_gap_immediately() {
+        let config = RtcConfiguration {
+            sctp_sack_delay: Duration::from_millis(200),
+            sctp_sack_max_unacked: 4,
+            ..Default::default()
+        };
+        let (socket_tx, _) = tokio::sync::watch::channel(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_tx.subscribe(),
+            "127.0.0.1:5000".parse().unwrap(),
+            None,
+        );
+        let cert = crate::transports::dtls::generate_certificate().unwrap();
+        let (dtls, _, _) = DtlsTransport::new(ice_conn, cert, true, 100, None)
+            .await
+            .unwrap();
+        let (_incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let (sctp, _runner) = SctpTransport::new(
+            dtls,
+            incoming_rx,
+            Arc::new(Mutex::new(Vec::new())),
+            5000,
+            5000,
+            None,
+            false,
+            &config,
+        );
+        let local_tag = 0x1111_1111;
+        let inner = &sctp.inner;
+        *inner.state.lock() = SctpState::Connected;
+        inner.verification_tag.store(local_tag, Ordering::SeqCst);
+        inner.cumulative_tsn_ack.store(99, Ordering::SeqCst);
+
+        let in_order = remote_packet(local_tag, CT_DATA, 0x03, &data_chunk(100, 0, b"a"));
+        inner.handle_packet(in_order).await.unwrap();
+        let _ = inner.transmit().await;
+        assert!(inner.sack_needed.load(Ordering::SeqCst), "SACK is delayed");
+        assert_eq!(inner.stats_sacks_sent.load(Ordering::SeqCst), 0);
+
+        let gap = remote_packet(local_tag, CT_DATA, 0x03, &data_chunk(102, 2, b"c"));
+        inner.handle_packet(gap).await.unwrap();
+        let _ = inner.transmit().await;
+        assert!(!inner.sack_needed.load(Ordering::SeqCst));
+        assert_eq!(inner.stats_sacks_sent.load(Ordering::SeqCst), 1);
+    }
+
+    /// A peer reset whose last TSN has not arrived is answered "in progress"
+    /// and applied once that DATA is in; the channel then closes and resets
+    /// its own side (RFC 6525 §5.2.2, RFC 8831 §6.7).
+    #[tokio::test]
+    async fn test_stream_reset_waits_for_missing_data() {
+        let (socket_tx, _) = tokio::sync::watch::channel(None);
+        let ice_conn = crate::transports::ice::conn::IceConn::new(
+            socket_tx.subscribe(),
+            "127.0.0.1:5000".parse().unwrap(),
+            None,
+        );
+        let cert = crate::transports::dtls::generate_certificate().unwrap();
+        let (dtls, _, _) = DtlsTransport::new(ice_conn, cert, true, 100, None)
+            .await
+            .unwrap();
+
+        let dc = Arc::new(DataChannel::new(
+            0,
+            DataChannelConfig {
+                label: "ordered".into(),
+                ordered: true,
+                negotiated: Some(0),
+                ..Default::default()
+            },
+        ));
+        dc.state
+            .store(DataChannelState::Open as usize, Ordering::SeqCst);
+        let (_incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let (sctp, _runner) = SctpTransport::new(
+            dtls,
+            incoming_rx,
+            Arc::new(Mutex::new(vec![Arc::downgrade(&dc)])),
+            5000,
+            5000,
+            None,
+            false,
+            &RtcConfiguration::default(),
+        );
+        let local_tag = 0x1111_1111;
+        let inner = &sctp.inner;
+        *inner.state.lock() = SctpState::Connected;
+        inner.verification_tag.store(local_tag, Ordering::SeqCst);
+        inner.cumulative_tsn_ack.store(99, Ordering::SeqCst);
+
+        // The peer reset stream 0 after TSN 100, which is still in flight.
+        let mut request = BytesMut::new();
+        request.put_u16(RECONFIG_PARAM_OUTGOING_SSN_RESET);
+        request.put_u16(18);
+        request.put_u32(7);
+        request.put_u32(0);
+        request.put_u32(100);
+        request.put_u16(0);
+        request.put_u16(0);
+        let reconfig = remote_packet(local_tag, CT_RECONFIG, 0, &request);
+        inner.handle_packet(reconfig).await.unwrap();
+        assert_eq!(
+            inner.stream_resets.lock().answer_to(7),
+            RECONFIG_RESPONSE_IN_PROGRESS
+        );
+        assert_eq!(
+            DataChannelState::from(dc.state.load(Ordering::SeqCst)),
+            DataChannelState::Open
+        );
+
+        let data = remote_packet(local_tag, CT_DATA, 0x03, &data_chunk(100, 0, b"last"));
+        inner.handle_packet(data).await.unwrap();
+        let chunks = inner.poll_stream_resets(Instant::now());
+        assert_eq!(
+            chunks[0],
+            reconfig_response_chunk(7, RECONFIG_RESPONSE_SUCCESS_PERFORMED)
+        );
+        // Our own reset of stream 0 follows.
+        assert_eq!(chunks.len(), 2);
+        let (request, _) = inner.stream_resets.lock().outstanding.clone().unwrap();
+        assert_eq!(request.streams, vec![0]);
+
+        assert!(matches!(dc.recv().await, Some(DataChannelEvent::Message(m)) if m == "last"));
+        assert!(matches!(dc.recv().await, Some(DataChannelEvent::Close)));
+        assert!(dc.recv().await.is_none());
+
+        // A retransmitted request gets the same answer again.
+        assert_eq!(
+            inner.stream_resets.lock().answer_to(7),
+            RECONFIG_RESPONSE_SUCCESS_PERFORMED
+        );
+    }
 }
@@ -0,0 +1,446 @@
+//! Transport-wide congestion control feedback (draft-holmer-rmcat-transport-wide-cc-extensions-01).
+//!
+//! Every outgoing RTP packet carries a 16-bit transport-wide sequence number in
+//! a header extension. The receiver records when each one arrived and
+//! periodically reports the arrivals back in an RTCP `RTPFB` (FMT 15) packet:
+//!
+//! ```text
+//! packet status chunks (u16 each) | receive deltas (u8 small / i16 large)
+//! ```
+//!
+//! Deltas are in 250 µs ticks, the first one relative to the 24-bit reference
+//! time (64 ms ticks) in the feedback header.
+
+use crate::errors::{RtpError, RtpResult};
+use crate::rtp::TransportWideCc;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// How often a receiver reports arrivals back to the sender.
+pub const FEEDBACK_INTERVAL: Duration = Duration::from_millis(100);
+
+const DELTA_TICK_US: i64 = 250;
+const REFERENCE_TICK_US: i64 = 64_000;
+const MAX_RUN_LENGTH: usize = 0x1FFF;
+const SYMBOLS_PER_VECTOR: usize = 7;
+
+const STATUS_NOT_RECEIVED: u8 = 0;
+const STATUS_SMALL_DELTA: u8 = 1;
+const STATUS_LARGE_DELTA: u8 = 2;
+
+/// Arrival of one transport-wide sequence number as reported by feedback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransportCcPacketResult {
+    pub sequence: u16,
+    /// Receiver clock in microseconds, `None` when the packet was lost.
+    pub arrival_time_us: Option<i64>,
+}
+
+/// Receiver side: records arrivals and builds feedback packets from them.
+#[derive(Debug, Default)]
+pub struct TransportCcRecorder {
+    epoch: Option<Instant>,
+    media_ssrc: u32,
+    last_sequence: Option<i64>,
+    /// First sequence number not yet covered by a sent feedback packet.
+    next_base: Option<i64>,
+    arrivals: BTreeMap<i64, Instant>,
+    feedback_count: u8,
+}
+
+impl TransportCcRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that the packet carrying transport sequence number `sequence`
+    /// (from `media_ssrc`) arrived at `arrival`.
+    pub fn record(&mut self, sequence: u16, media_ssrc: u32, arrival: Instant) {
+        let unwrapped = match self.last_sequence {
+            Some(last) => last + (sequence.wrapping_sub(last as u16) as i16) as i64,
+            None => sequence as i64,
+        };
+        if self.next_base.is_some_and(|base| unwrapped < base) {
+            return;
+        }
+        self.epoch.get_or_insert(arrival);
+        self.media_ssrc = media_ssrc;
+        self.last_sequence = Some(self.last_sequence.map_or(unwrapped, |l| l.max(unwrapped)));
+        self.arrivals.entry(unwrapped).or_insert(arrival);
+    }
+
+    /// Build a feedback packet covering every sequence number since the
+    /// previous one, or `None` when nothing arrived in between.
+    pub fn build_feedback(&mut self, sender_ssrc: u32) -> Option<TransportWideCc> {
+        let (&first, _) = self.arrivals.first_key_value()?;
+        let (&last, _) = self.arrivals.last_key_value()?;
+        let epoch = self.epoch?;
+        let base = self
+            .next_base
+            .unwrap_or(first)
+            .max(last - u16::MAX as i64 + 1);
+
+        let first_arrival_us = self
+            .arrivals
+            .range(base..)
+            .next()
+            .map(|(_, at)| at.duration_since(epoch).as_micros() as i64)?;
+        let reference_time = first_arrival_us / REFERENCE_TICK_US;
+        let mut previous_us = reference_time * REFERENCE_TICK_US;
+
+        let mut symbols = Vec::with_capacity((last - base + 1) as usize);
+        let mut deltas = Vec::new();
+        for sequence in base..=last {
+            let Some(at) = self.arrivals.get(&sequence) else {
+                symbols.push(STATUS_NOT_RECEIVED);
+                continue;
+            };
+            let arrival_us = at.duration_since(epoch).as_micros() as i64;
+            let ticks = ((arrival_us - previous_us) / DELTA_TICK_US)
+                .clamp(i16::MIN as i64, i16::MAX as i64);
+            previous_us += ticks * DELTA_TICK_US;
+            if (0..=u8::MAX as i64).contains(&ticks) {
+                symbols.push(STATUS_SMALL_DELTA);
+                deltas.push(ticks as u8);
+            } else {
+                symbols.push(STATUS_LARGE_DELTA);
+                deltas.extend_from_slice(&(ticks as i16).to_be_bytes());
+            }
+        }
+
+        let mut payload = encode_chunks(&symbols);
+        payload.extend_from_slice(&deltas);
+
+        let feedback = TransportWideCc {
+            sender_ssrc,
+            media_ssrc: self.media_ssrc,
+            base_sequence: base as u16,
+            packet_status_count: symbols.len() as u16,
+            reference_time_64ms: (reference_time as u32) & 0x00FF_FFFF,
+            feedback_packet_count: self.feedback_count,
+            payload,
+        };
+        self.feedback_count = self.feedback_count.wrapping_add(1);
+        self.next_base = Some(last + 1);
+        self.arrivals = self.arrivals.split_off(&(last + 1));
+        Some(feedback)
+    }
+}
+
+fn encode_chunks(symbols: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < symbols.len() {
+        let run = symbols[i..]
+            .iter()
+            .take(MAX_RUN_LENGTH)
+            .take_while(|&&s| s == symbols[i])
+            .count();
+        let chunk = if run >= SYMBOLS_PER_VECTOR {
+            i += run;
+            ((symbols[i - run] as u16) << 13) | run as u16
+        } else {
+            let mut chunk = 0xC000u16;
+            for (slot, &symbol) in symbols[i..].iter().take(SYMBOLS_PER_VECTOR).enumerate() {
+                chunk |= (symbol as u16) << (12 - 2 * slot);
+            }
+            i += SYMBOLS_PER_VECTOR;
+            chunk
+        };
+        out.extend_from_slice(&chunk.to_be_bytes());
+    }
+    out
+}
+
+impl TransportWideCc {
+    /// Decode the per-packet statuses and arrival times for a sender-side
+    /// bandwidth estimator.
+    pub fn packet_results(&self) -> RtpResult<Vec<TransportCcPacketResult>> {
+        let count = self.packet_status_count as usize;
+        let mut symbols = Vec::with_capacity(count);
+        let mut rest = self.payload.as_slice();
+        while symbols.len() < count {
+            let [hi, lo, tail @ ..] = rest else {
+                return Err(RtpError::InvalidRtcp("TWCC status chunks truncated"));
+            };
+            rest = tail;
+            let chunk = u16::from_be_bytes([*hi, *lo]);
+            let remaining = count - symbols.len();
+            if chunk & 0x8000 == 0 {
+                let symbol = ((chunk >> 13) & 0x3) as u8;
+                let run = (chunk & 0x1FFF) as usize;
+                symbols.extend(std::iter::repeat_n(symbol, run.min(remaining)));
+            } else if chunk & 0x4000 == 0 {
+                symbols.extend(
+                    (0..14)
+                        .rev()
+                        .map(|bit| ((chunk >> bit) & 0x1) as u8)
+                        .take(remaining),
+                );
+            } else {
+                symbols.extend(
+                    (0..SYMBOLS_PER_VECTOR)
+                        .map(|slot| ((chunk >> (12 - 2 * slot)) & 0x3) as u8)
+                        .take(remaining),
+                );
+            }
+        }
+
+        let mut arrival_us = self.reference_time_64ms as i64 * REFERENCE_TICK_US;
+        let mut results = Vec::with_capacity(count);
+        for (i, symbol) in symbols.into_iter().enumerate() {
+            let ticks = match symbol {
+                STATUS_NOT_RECEIVED => None,
+                STATUS_SMALL_DELTA => {
+                    let [delta, tail @ ..] = rest else {
+                        return Err(RtpError::InvalidRtcp("TWCC receive deltas truncated"));
+                    };
+                    rest = tail;
+                    Some(*delta as i64)
+                }
+                STATUS_LARGE_DELTA => {
+                    let [hi, lo, tail @ ..] = rest else {
+                        return Err(RtpError::InvalidRtcp("TWCC receive deltas truncated"));
+                    };
+                    rest = tail;
+                    Some(i16::from_be_bytes([*hi, *lo]) as i64)
+                }
+                _ => return Err(RtpError::InvalidRtcp("TWCC reserved packet status")),
+            };
+            let arrival_time_us = ticks.map(|ticks| {
+                arrival_us += ticks * DELTA_TICK_US;
+                arrival_us
+            });
+            results.push(TransportCcPacketResult {
+                sequence: self.base_sequence.wrapping_add(i as u16),
+                arrival_time_us,
+            });
+        }
+        Ok(results)
+    }
+}
+
+/// Sent packets remembered for matching against feedback.
+const SEND_HISTORY: usize = 4096;
+/// Loss fraction above which the estimate is cut (GCC loss-based control).
+const HIGH_LOSS: f64 = 0.1;
+
+/// Sender side: a throughput-based bandwidth estimate from the bytes the
+/// remote acknowledged per feedback window, cut back on heavy loss.
+#[derive(Debug, Default)]
+pub struct SendSideBandwidthEstimator {
+    sent: HashMap<u16, usize>,
+    order: VecDeque<u16>,
+    estimate_bps: Option<u64>,
+    feedback_count: u64,
+    loss_fraction: f64,
+}
+
+impl SendSideBandwidthEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember that `size` bytes went out with transport sequence `sequence`.
+    pub fn on_packet_sent(&mut self, sequence: u16, size: usize) {
+        if self.sent.insert(sequence, size).is_none() {
+            self.order.push_back(sequence);
+        }
+        while self.order.len() > SEND_HISTORY {
+            if let Some(old) = self.order.pop_front() {
+                self.sent.remove(&old);
+            }
+        }
+    }
+
+    /// Update the estimate from one feedback packet.
+    pub fn on_feedback(&mut self, feedback: &TransportWideCc) {
+        self.feedback_count += 1;
+        let Ok(results) = feedback.packet_results() else {
+            return;
+        };
+        let mut acked_bytes = 0usize;
+        let mut lost = 0usize;
+        let mut span: Option<(i64, i64)> = None;
+        for result in &results {
+            let Some(arrival) = result.arrival_time_us else {
+                lost += 1;
+                continue;
+            };
+            acked_bytes += self.sent.get(&result.sequence).copied().unwrap_or(0);
+            span = Some(span.map_or((arrival, arrival), |(first, last)| {
+                (first.min(arrival), last.max(arrival))
+            }));
+        }
+        if !results.is_empty() {
+            self.loss_fraction = lost as f64 / results.len() as f64;
+        }
+
+        if let Some((first, last)) = span
+            && last > first
+        {
+            let rate = acked_bytes as u64 * 8 * 1_000_000 / (last - first) as u64;
+            self.estimate_bps = Some(match self.estimate_bps {
+                Some(previous) => (previous * 7 + rate * 3) / 10,
+                None => rate,
+            });
+        }
+        if self.loss_fraction > HIGH_LOSS
+            && let Some(estimate) = self.estimate_bps.as_mut()
+        {
+            *estimate = (*estimate as f64 * (1.0 - 0.5 * self.loss_fraction)) as u64;
+        }
+    }
+
+    /// Current estimate in bits per second, once feedback spanned a
+    /// measurable interval.
+    pub fn estimate_bps(&self) -> Option<u64> {
+        self.estimate_bps
+    }
+
+    /// Transport-cc feedback packets received so far.
+    pub fn feedback_count(&self) -> u64 {
+        self.feedback_count
+    }
+
+    /// Fraction of packets reported lost by the latest feedback.
+    pub fn loss_fraction(&self) -> f64 {
+        self.loss_fraction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtp::{RtcpPacket, marshal_rtcp_packets, parse_rtcp_packets};
+
+    fn round_trip(feedback: TransportWideCc) -> TransportWideCc {
+        let raw = marshal_rtcp_packets(&[RtcpPacket::TransportWideCc(feedback)]).unwrap();
+        assert_eq!(raw.len() % 4, 0);
+        match parse_rtcp_packets(&raw, None).unwrap().as_slice() {
+            [RtcpPacket::TransportWideCc(parsed)] => parsed.clone(),
+            other => panic!("expected one transport-cc packet, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn feedback_covers_run_of_received_packets() {
+        let start = Instant::now();
+        let mut recorder = TransportCcRecorder::new();
+        // Sequence numbers wrap; 3 is lost and 5 arrives late, out of order.
+        let arrivals = [
+            (65534u16, 0u64),
+            (65535, 5),
+            (0, 10),
+            (1, 15),
+            (2, 20),
+            (4, 30),
+            (5, 100),
+        ];
+        for &(sequence, ms) in &arrivals {
+            recorder.record(sequence, 0xCAFE, start + Duration::from_millis(ms));
+        }
+
+        let feedback = round_trip(recorder.build_feedback(1).unwrap());
+        assert_eq!(feedback.sender_ssrc, 1);
+        assert_eq!(feedback.media_ssrc, 0xCAFE);
+        assert_eq!(feedback.base_sequence, 65534);
+        assert_eq!(feedback.packet_status_count, 8);
+        assert_eq!(feedback.feedback_packet_count, 0);
+
+        let results = feedback.packet_results().unwrap();
+        let sequences: Vec<u16> = results.iter().map(|r| r.sequence).collect();
+        assert_eq!(sequences, [65534, 65535, 0, 1, 2, 3, 4, 5]);
+        assert_eq!(results[5].arrival_time_us, None);
+
+        let first = results[0].arrival_time_us.unwrap();
+        let offsets: Vec<_> = results
+            .iter()
+            .filter_map(|r| r.arrival_time_us)
+            .map(|us| (us - first) / 1000)
+            .collect();
+        assert_eq!(offsets, [0, 5, 10, 15, 20, 30, 100]);
+
+        // The next feedback starts where this one ended.
+        assert!(recorder.build_feedback(1).is_none());
+        recorder.record(6, 0xCAFE, start + Duration::from_millis(110));
+        let next = round_trip(recorder.build_feedback(1).unwrap());
+        assert_eq!(next.base_sequence, 6);
+        assert_eq!(next.packet_status_count, 1);
+        assert_eq!(next.feedback_packet_count, 1);
+    }
+
+    #[test]
+    fn long_runs_use_run_length_chunks_and_large_deltas() {
+        let start = Instant::now();
+        let mut recorder = TransportCcRecorder::new();
+        for sequence in 0..20u16 {
+            recorder.record(sequence, 1, start + Duration::from_millis(sequence as u64));
+        }
+        // A gap of 100 lost packets, then one arriving 2 s later.
+        recorder.record(120, 1, start + Duration::from_millis(2000));
+
+        let feedback = round_trip(recorder.build_feedback(0).unwrap());
+        assert_eq!(feedback.packet_status_count, 121);
+        // Run of 20 small deltas, run of 100 losses, one vector chunk.
+        assert_eq!(&feedback.payload[..2], &(0x2000u16 | 20).to_be_bytes());
+        assert_eq!(&feedback.payload[2..4], &100u16.to_be_bytes());
+
+        let results = feedback.packet_results().unwrap();
+        assert_eq!(
+            results
+                .iter()
+                .filter(|r| r.arrival_time_us.is_none())
+                .count(),
+            100
+        );
+        let last = results[120].arrival_time_us.unwrap();
+        let first = results[0].arrival_time_us.unwrap();
+        assert_eq!((last - first) / 1000, 2000);
+    }
+
+    #[test]
+    fn estimator_tracks_acknowledged_rate_and_cuts_on_loss() {
+        let start = Instant::now();
+        let mut recorder = TransportCcRecorder::new();
+        let mut estimator = SendSideBandwidthEstimator::new();
+        // 1250-byte packets every 10 ms: 1 Mbps.
+        for sequence in 0..11u16 {
+            estimator.on_packet_sent(sequence, 1250);
+            let at = start + Duration::from_millis(10 * sequence as u64);
+            recorder.record(sequence, 1, at);
+        }
+        estimator.on_feedback(&recorder.build_feedback(0).unwrap());
+        let estimate = estimator.estimate_bps().unwrap();
+        assert!((1_000_000..=1_200_000).contains(&estimate), "{estimate}");
+        assert_eq!(estimator.feedback_count(), 1);
+        assert_eq!(estimator.loss_fraction(), 0.0);
+
+        // Half of the next window is lost.
+        for sequence in 11..21u16 {
+            estimator.on_packet_sent(sequence, 1250);
+            if sequence.is_multiple_of(2) {
+                let at = start + Duration::from_millis(10 * sequence as u64);
+                recorder.record(sequence, 1, at);
+            }
+        }
+        estimator.on_feedback(&recorder.build_feedback(0).unwrap());
+        assert_eq!(estimator.feedback_count(), 2);
+        assert!(estimator.loss_fraction() > 0.4);
+        assert!(estimator.estimate_bps().unwrap() < estimate);
+    }
+
+    #[test]
+    fn truncated_feedback_is_rejected() {
+        let feedback = TransportWideCc {
+            sender_ssrc: 0,
+            media_ssrc: 0,
+            base_sequence: 0,
+            packet_status_count: 2,
+            reference_time_64ms: 0,
+            feedback_packet_count: 0,
+            payload: vec![0x20, 0x02, 0x01],
+        };
+        assert!(feedback.packet_results().is_err());
+    }
+}
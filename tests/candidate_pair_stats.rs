@@ -0,0 +1,76 @@
+use anyhow::Result;
+use rustrtc::transports::sctp::DataChannelConfig;
+use rustrtc::{DataChannelEvent, PeerConnection, RtcConfiguration, StatsKind};
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[tokio::test]
+async fn get_stats_reports_selected_candidate_pair_and_transport() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let dc1 = pc1.create_data_channel("stats", negotiated.clone())?;
+    let _dc2 = pc2.create_data_channel("stats", negotiated)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    timeout(Duration::from_secs(10), async {
+        while !matches!(dc1.recv().await, Some(DataChannelEvent::Open) | None) {}
+    })
+    .await?;
+
+    let report = pc1.get_stats().await?;
+    let pairs: Vec<_> = report
+        .entries
+        .iter()
+        .filter(|e| e.kind == StatsKind::IceCandidatePair)
+        .collect();
+    let selected = pairs
+        .iter()
+        .find(|e| e.values["selected"] == true)
+        .expect("selected candidate pair");
+    assert_eq!(selected.values["state"], "succeeded");
+    assert_eq!(selected.values["nominated"], true);
+    assert!(selected.values["localCandidate"].is_string());
+    assert!(selected.values["remoteCandidate"].is_string());
+    assert!(selected.values["bytesSent"].as_u64().unwrap() > 0);
+    assert!(selected.values["bytesReceived"].as_u64().unwrap() > 0);
+    // Bytes are only counted on the pair that carries them.
+    assert!(
+        pairs
+            .iter()
+            .filter(|e| e.values["selected"] != true)
+            .all(|e| !e.values.contains_key("bytesSent"))
+    );
+    let rtt = selected.values["currentRoundTripTime"].as_f64().unwrap();
+    assert!((0.0..1.0).contains(&rtt), "rtt = {rtt}");
+
+    let transport = report
+        .entries
+        .iter()
+        .find(|e| e.kind == StatsKind::Transport)
+        .expect("transport entry");
+    assert_eq!(
+        transport.values["selectedCandidatePairId"],
+        serde_json::to_value(&selected.id)?
+    );
+    assert_eq!(transport.values["dtlsState"], "connected");
+    assert_eq!(transport.values["bytesSent"], selected.values["bytesSent"]);
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
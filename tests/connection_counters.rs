@@ -0,0 +1,169 @@
+use anyhow::Result;
+use bytes::Bytes;
+use rustrtc::media::frame::{AudioFrame, MediaKind};
+use rustrtc::transports::sctp::DataChannelConfig;
+use rustrtc::{
+    DataChannelEvent, PeerConnection, RtcConfiguration, RtpCodecParameters, SdpType,
+    SessionDescription, TransportMode,
+};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+async fn wait_for(mut condition: impl FnMut() -> bool) -> Result<()> {
+    timeout(Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    Ok(())
+}
+
+/// Plain RTP adds nothing beyond the 12-byte RTP header, so the totals are exact.
+#[tokio::test]
+async fn rtp_totals_match_wire_bytes() -> Result<()> {
+    const PACKETS: u64 = 20;
+    const PAYLOAD: usize = 160;
+    const WIRE: u64 = (12 + PAYLOAD) as u64;
+
+    let remote = UdpSocket::bind("127.0.0.1:0").await?;
+    let offer = format!(
+        "v=0\r\n\
+         o=- 1 1 IN IP4 127.0.0.1\r\n\
+         s=-\r\n\
+         t=0 0\r\n\
+         c=IN IP4 127.0.0.1\r\n\
+         m=audio {} RTP/AVP 0\r\n\
+         a=rtpmap:0 PCMU/8000\r\n\
+         a=sendrecv\r\n",
+        remote.local_addr()?.port()
+    );
+
+    let pc = PeerConnection::new(RtcConfiguration {
+        transport_mode: TransportMode::Rtp,
+        bind_ip: Some("127.0.0.1".to_string()),
+        ..Default::default()
+    });
+    let (source, track, _) = rustrtc::media::track::sample_track(MediaKind::Audio, 64);
+    pc.add_track(
+        track,
+        RtpCodecParameters {
+            payload_type: 0,
+            clock_rate: 8000,
+            channels: 1,
+        },
+    )?;
+    pc.set_remote_description(SessionDescription::parse(SdpType::Offer, &offer)?)
+        .await?;
+    let answer = pc.create_answer().await?;
+    pc.set_local_description(answer.clone())?;
+    pc.wait_for_rtp_transport_ready(Duration::from_secs(5))
+        .await?;
+    let local_port = answer.media_sections[0].port;
+
+    assert_eq!(pc.total_bytes_sent(), 0);
+    assert_eq!(pc.total_bytes_received(), 0);
+
+    let mut buf = [0u8; 1500];
+    for _ in 0..PACKETS {
+        source.send_audio(AudioFrame {
+            data: Bytes::from(vec![0xff; PAYLOAD]),
+            ..AudioFrame::default()
+        })?;
+        let n = timeout(Duration::from_secs(2), remote.recv(&mut buf)).await??;
+        assert_eq!(n as u64, WIRE);
+    }
+    assert_eq!(pc.total_bytes_sent(), PACKETS * WIRE);
+    assert_eq!(pc.total_packets_sent(), PACKETS);
+
+    for seq in 0..PACKETS as u16 {
+        let mut packet = vec![0x80, 0x00];
+        packet.extend_from_slice(&seq.to_be_bytes());
+        packet.extend_from_slice(&(seq as u32 * 160).to_be_bytes());
+        packet.extend_from_slice(&0x1234_5678u32.to_be_bytes());
+        packet.extend_from_slice(&[0xff; PAYLOAD]);
+        remote.send_to(&packet, ("127.0.0.1", local_port)).await?;
+    }
+    wait_for(|| pc.total_packets_received() == PACKETS).await?;
+    assert_eq!(pc.total_bytes_received(), PACKETS * WIRE);
+
+    pc.close();
+    Ok(())
+}
+
+/// Data channel bytes ride DTLS/SCTP through the same counters; the totals
+/// must cover the payload plus a bounded per-message framing overhead.
+#[tokio::test]
+async fn data_channel_totals_include_payload() -> Result<()> {
+    const MESSAGES: usize = 20;
+    const SIZE: usize = 1000;
+    // SCTP common header + DATA chunk + DTLS record header/AEAD tag.
+    const MAX_OVERHEAD_PER_MESSAGE: u64 = 128;
+    const SLACK: u64 = 2048;
+
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let tx = pc1.create_data_channel("counters", negotiated.clone())?;
+    let rx = pc2.create_data_channel("counters", negotiated)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    for dc in [&tx, &rx] {
+        timeout(Duration::from_secs(10), async {
+            while !matches!(dc.recv().await, Some(DataChannelEvent::Open) | None) {}
+        })
+        .await?;
+    }
+
+    let sent_before = pc1.total_bytes_sent();
+    let received_before = pc2.total_bytes_received();
+
+    let payload = vec![0x5a; SIZE];
+    for _ in 0..MESSAGES {
+        pc1.send_data(0, &payload).await?;
+    }
+    let mut received = 0;
+    timeout(Duration::from_secs(5), async {
+        while received < MESSAGES * SIZE {
+            match rx.recv().await {
+                Some(DataChannelEvent::Message(data)) => received += data.len(),
+                Some(_) => continue,
+                None => break,
+            }
+        }
+    })
+    .await?;
+    assert_eq!(received, MESSAGES * SIZE);
+
+    let app_bytes = (MESSAGES * SIZE) as u64;
+    let upper = app_bytes + MESSAGES as u64 * MAX_OVERHEAD_PER_MESSAGE + SLACK;
+    let sent = pc1.total_bytes_sent() - sent_before;
+    let got = pc2.total_bytes_received() - received_before;
+    assert!(
+        (app_bytes..=upper).contains(&sent),
+        "sent {sent} bytes for {app_bytes} bytes of data"
+    );
+    assert!(
+        (app_bytes..=upper).contains(&got),
+        "received {got} bytes for {app_bytes} bytes of data"
+    );
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use anyhow::Result;
+use rustrtc::transports::sctp::DataChannelConfig;
+use rustrtc::{DataChannelEvent, PeerConnection, RtcConfiguration};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::time::timeout;
+
+async fn wait_open(dc: &rustrtc::transports::sctp::DataChannel) -> Result<()> {
+    timeout(Duration::from_secs(10), async {
+        loop {
+            match dc.recv().await {
+                Some(DataChannelEvent::Open) => return Ok(()),
+                Some(_) => continue,
+                None => anyhow::bail!("channel {} closed before open", dc.id),
+            }
+        }
+    })
+    .await?
+}
+
+/// Two channels share one association: saturating the first must not stall
+/// the second.
+#[tokio::test]
+async fn saturated_channel_does_not_block_sibling() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+
+    let negotiated = |id| {
+        Some(DataChannelConfig {
+            negotiated: Some(id),
+            ordered: true,
+            ..Default::default()
+        })
+    };
+    let bulk_tx = pc1.create_data_channel("bulk", negotiated(0))?;
+    let chat_tx = pc1.create_data_channel("chat", negotiated(2))?;
+    let bulk_rx = pc2.create_data_channel("bulk", negotiated(0))?;
+    let chat_rx = pc2.create_data_channel("chat", negotiated(2))?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    for dc in [&bulk_tx, &chat_tx, &bulk_rx, &chat_rx] {
+        wait_open(dc).await?;
+    }
+
+    // Drain the bulk receiver so the association keeps moving.
+    tokio::spawn(async move { while bulk_rx.recv().await.is_some() {} });
+
+    let pc1 = Arc::new(pc1);
+    let stop = Arc::new(AtomicBool::new(false));
+    let bulk_sent = Arc::new(AtomicUsize::new(0));
+    let bulk_task = {
+        let pc1 = pc1.clone();
+        let stop = stop.clone();
+        let bulk_sent = bulk_sent.clone();
+        let payload = vec![0xAB; 16 * 1024];
+        tokio::spawn(async move {
+            while !stop.load(Ordering::Relaxed) {
+                if pc1.send_data(0, &payload).await.is_err() {
+                    break;
+                }
+                bulk_sent.fetch_add(payload.len(), Ordering::Relaxed);
+            }
+        })
+    };
+
+    // Wait until the bulk channel has queued more than the association's
+    // 256 KiB send buffer, so further sends block on flow control.
+    timeout(Duration::from_secs(5), async {
+        while bulk_sent.load(Ordering::Relaxed) < 512 * 1024 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await?;
+
+    let chat_msg = b"hello";
+    for _ in 0..10 {
+        timeout(Duration::from_secs(2), pc1.send_data(2, chat_msg)).await??;
+        let event = timeout(Duration::from_secs(2), async {
+            loop {
+                match chat_rx.recv().await {
+                    Some(DataChannelEvent::Message(data)) => return Some(data),
+                    Some(_) => continue,
+                    None => return None,
+                }
+            }
+        })
+        .await?;
+        assert_eq!(event.as_deref(), Some(&chat_msg[..]));
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    bulk_task.await?;
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+/// Filling a channel raises its buffered amount; draining it back below the
+/// low threshold fires `BufferedAmountLow` once.
+#[tokio::test]
+async fn buffered_amount_low_fires_after_drain() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+
+    let config = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let tx = pc1.create_data_channel("bulk", config.clone())?;
+    let rx = pc2.create_data_channel("bulk", config)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    wait_open(&tx).await?;
+    wait_open(&rx).await?;
+    tokio::spawn(async move { while rx.recv().await.is_some() {} });
+
+    const THRESHOLD: usize = 1024;
+    tx.set_buffered_amount_low_threshold(THRESHOLD);
+    assert_eq!(tx.buffered_amount_low_threshold(), THRESHOLD);
+    assert_eq!(tx.buffered_amount(), 0);
+
+    // Queue more than the association can take in one go, without waiting.
+    let payload = vec![0xCD; 16 * 1024];
+    for _ in 0..8 {
+        pc1.send_data(0, &payload).await?;
+    }
+    assert!(
+        tx.buffered_amount() > THRESHOLD,
+        "buffered {} bytes after filling",
+        tx.buffered_amount()
+    );
+
+    timeout(Duration::from_secs(10), async {
+        loop {
+            match tx.recv().await {
+                Some(DataChannelEvent::BufferedAmountLow) => break,
+                Some(_) => continue,
+                None => panic!("channel closed"),
+            }
+        }
+    })
+    .await?;
+    assert!(tx.buffered_amount() <= THRESHOLD);
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
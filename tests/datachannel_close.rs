@@ -0,0 +1,106 @@
+use anyhow::Result;
+use rustrtc::transports::sctp::{DataChannel, DataChannelConfig};
+use rustrtc::{DataChannelEvent, DataChannelState, PeerConnection, RtcConfiguration};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+const CHANNEL_ID: u16 = 0;
+
+async fn connected_pair() -> Result<(
+    PeerConnection,
+    PeerConnection,
+    Arc<DataChannel>,
+    Arc<DataChannel>,
+)> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(CHANNEL_ID),
+        ordered: true,
+        ..Default::default()
+    });
+    let dc1 = pc1.create_data_channel("close", negotiated.clone())?;
+    let dc2 = pc2.create_data_channel("close", negotiated)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    for dc in [&dc1, &dc2] {
+        timeout(Duration::from_secs(10), async {
+            while !matches!(dc.recv().await, Some(DataChannelEvent::Open) | None) {}
+        })
+        .await?;
+    }
+    Ok((pc1, pc2, dc1, dc2))
+}
+
+/// Messages received before `Close`; fails if the channel ends without one.
+async fn until_close(dc: &DataChannel) -> Result<Vec<Vec<u8>>> {
+    let mut messages = Vec::new();
+    timeout(Duration::from_secs(10), async {
+        loop {
+            match dc.recv().await {
+                Some(DataChannelEvent::Message(data)) => messages.push(data.to_vec()),
+                Some(DataChannelEvent::Close) => return Ok(()),
+                Some(_) => {}
+                None => anyhow::bail!("channel ended without Close"),
+            }
+        }
+    })
+    .await??;
+    Ok(messages)
+}
+
+#[tokio::test]
+async fn remote_sees_close_after_pending_messages() -> Result<()> {
+    let (pc1, pc2, dc1, dc2) = connected_pair().await?;
+
+    // Close straight after a burst, so the reset races the DATA.
+    let sent: Vec<Vec<u8>> = (0..50u8).map(|i| vec![i; 1000]).collect();
+    for message in &sent {
+        pc1.send_data(CHANNEL_ID, message).await?;
+    }
+    pc1.close_data_channel(CHANNEL_ID).await?;
+    assert!(pc1.send_data(CHANNEL_ID, b"late").await.is_err());
+
+    assert_eq!(until_close(&dc2).await?, sent);
+    assert!(until_close(&dc1).await?.is_empty());
+    for dc in [&dc1, &dc2] {
+        assert_eq!(
+            DataChannelState::from(dc.state.load(std::sync::atomic::Ordering::SeqCst)),
+            DataChannelState::Closed
+        );
+    }
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+#[tokio::test]
+async fn simultaneous_close_closes_both_sides() -> Result<()> {
+    let (pc1, pc2, dc1, dc2) = connected_pair().await?;
+
+    tokio::try_join!(
+        pc1.close_data_channel(CHANNEL_ID),
+        pc2.close_data_channel(CHANNEL_ID)
+    )?;
+
+    until_close(&dc1).await?;
+    until_close(&dc2).await?;
+    // Closing again is a no-op.
+    pc1.close_data_channel(CHANNEL_ID).await?;
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
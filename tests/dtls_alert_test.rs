@@ -0,0 +1,105 @@
+use anyhow::Result;
+use rustrtc::transports::dtls::{fingerprint, generate_certificate};
+use rustrtc::{DisconnectReason, PeerConnection, RtcConfiguration, RtcError, SessionDescription};
+use std::time::Duration;
+
+/// Replace every `a=fingerprint` with the hash of an unrelated certificate.
+fn tamper_fingerprint(desc: &mut SessionDescription) -> Result<()> {
+    let other = format!("sha-256 {}", fingerprint(&generate_certificate()?));
+    let attributes = desc.session.attributes.iter_mut().chain(
+        desc.media_sections
+            .iter_mut()
+            .flat_map(|m| m.attributes.iter_mut()),
+    );
+    for attr in attributes.filter(|a| a.key == "fingerprint") {
+        attr.value = Some(other.clone());
+    }
+    Ok(())
+}
+
+/// The offerer sees a fingerprint in the answer that does not match the
+/// answerer's certificate, rejects it with a `bad_certificate` alert, and the
+/// answerer reports that alert instead of a generic failure.
+#[tokio::test]
+async fn rejected_certificate_surfaces_dtls_alert() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    pc1.create_data_channel("probe", None)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    let mut tampered = answer;
+    tamper_fingerprint(&mut tampered)?;
+    pc1.set_remote_description(tampered).await?;
+
+    let err = tokio::time::timeout(Duration::from_secs(10), pc2.wait_for_connected())
+        .await?
+        .expect_err("answerer must not connect with a rejected certificate");
+    match err {
+        RtcError::DtlsAlert(alert) => assert!(alert.contains("bad_certificate"), "{alert}"),
+        other => panic!("expected DtlsAlert, got {other:?}"),
+    }
+    assert!(matches!(
+        pc2.disconnect_reason(),
+        Some(DisconnectReason::DtlsAlert(_))
+    ));
+
+    let err = tokio::time::timeout(Duration::from_secs(10), pc1.wait_for_connected())
+        .await?
+        .expect_err("offerer must reject the mismatched fingerprint");
+    assert!(matches!(err, RtcError::FingerprintMismatch(_)), "{err:?}");
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+/// The DTLS server (the answerer) is given a tampered offer. The handshake
+/// itself would succeed, but the client certificate does not match the
+/// fingerprint, so the server fails the connection.
+#[tokio::test]
+async fn tampered_offer_fingerprint_fails_connection() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    pc1.create_data_channel("probe", None)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    let mut tampered = offer;
+    tamper_fingerprint(&mut tampered)?;
+    pc2.set_remote_description(tampered).await?;
+
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    let err = tokio::time::timeout(Duration::from_secs(10), pc2.wait_for_connected())
+        .await?
+        .expect_err("answerer must reject the tampered fingerprint");
+    assert!(matches!(err, RtcError::FingerprintMismatch(_)), "{err:?}");
+    assert!(matches!(
+        pc2.disconnect_reason(),
+        Some(DisconnectReason::FingerprintMismatch(_))
+    ));
+
+    let err = tokio::time::timeout(Duration::from_secs(10), pc1.wait_for_connected())
+        .await?
+        .expect_err("offerer must learn its certificate was rejected");
+    assert!(matches!(err, RtcError::DtlsAlert(_)), "{err:?}");
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
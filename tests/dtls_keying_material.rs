@@ -0,0 +1,60 @@
+use anyhow::Result;
+use rustrtc::{PeerConnection, RtcConfiguration};
+use std::time::Duration;
+
+#[tokio::test]
+async fn exported_keying_material_matches_on_both_peers() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    pc1.create_data_channel("probe", None)?;
+
+    assert!(
+        pc1.export_keying_material("EXTRACTOR-test", None, 32)
+            .is_err(),
+        "exporter must fail before the DTLS handshake"
+    );
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())
+    })
+    .await??;
+
+    let context = Some(&b"app-context"[..]);
+    let a = pc1.export_keying_material("EXTRACTOR-test", context, 48)?;
+    let b = pc2.export_keying_material("EXTRACTOR-test", context, 48)?;
+    assert_eq!(a.len(), 48);
+    assert_eq!(a, b);
+
+    // Label and context both feed the PRF, and an empty context differs from none.
+    let other_label = pc1.export_keying_material("EXTRACTOR-other", context, 48)?;
+    assert_ne!(a, other_label);
+    let no_context = pc1.export_keying_material("EXTRACTOR-test", None, 48)?;
+    let empty_context = pc1.export_keying_material("EXTRACTOR-test", Some(&[]), 48)?;
+    assert_ne!(a, no_context);
+    assert_ne!(no_context, empty_context);
+    assert_eq!(
+        no_context,
+        pc2.export_keying_material("EXTRACTOR-test", None, 48)?
+    );
+
+    assert!(
+        pc1.export_keying_material("master secret", None, 48)
+            .is_err()
+    );
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
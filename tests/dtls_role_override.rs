@@ -0,0 +1,80 @@
+use anyhow::Result;
+use rustrtc::{
+    DtlsRole, PeerConnection, RtcConfiguration, RtcConfigurationBuilder, SessionDescription,
+};
+use std::time::Duration;
+
+fn setup_values(desc: &SessionDescription) -> Vec<String> {
+    desc.media_sections
+        .iter()
+        .flat_map(|m| m.attributes.iter())
+        .filter(|a| a.key == "setup")
+        .filter_map(|a| a.value.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn forced_server_role_advertises_passive_and_accepts_handshake() -> Result<()> {
+    let config = RtcConfigurationBuilder::new()
+        .dtls_role_override(DtlsRole::Server)
+        .build();
+    let pc1 = PeerConnection::new(config);
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    pc1.create_data_channel("probe", None)?;
+    assert_eq!(pc1.dtls_role(), Some(DtlsRole::Server));
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    assert_eq!(setup_values(&offer), vec!["passive"]);
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    assert_eq!(setup_values(&answer), vec!["active"]);
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())
+    })
+    .await??;
+
+    assert_eq!(pc1.dtls_role(), Some(DtlsRole::Server));
+    assert_eq!(pc2.dtls_role(), Some(DtlsRole::Client));
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+/// Two peers both forced into the client role would both send ClientHello;
+/// the remote `a=setup:active` is rejected instead of stalling the handshake.
+#[tokio::test]
+async fn forced_role_rejects_conflicting_remote_setup() -> Result<()> {
+    let client = || {
+        RtcConfigurationBuilder::new()
+            .dtls_role_override(DtlsRole::Client)
+            .build()
+    };
+    let pc1 = PeerConnection::new(client());
+    let pc2 = PeerConnection::new(client());
+    pc1.create_data_channel("probe", None)?;
+
+    let offer = pc1.create_offer().await?;
+    assert_eq!(setup_values(&offer), vec!["active"]);
+    let err = pc2
+        .set_remote_description(offer)
+        .await
+        .expect_err("active/active must be rejected");
+    assert!(
+        matches!(err, rustrtc::RtcError::InvalidState(ref msg) if msg.contains("a=setup:active")),
+        "got {err:?}"
+    );
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
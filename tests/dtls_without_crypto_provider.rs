@@ -0,0 +1,51 @@
+use anyhow::Result;
+use rustrtc::{PeerConnection, PeerConnectionState, RtcConfiguration, RtcError};
+use std::time::Duration;
+
+/// rustrtc implements DTLS/SRTP itself, so a WebRTC session must come up
+/// without the application installing a process-wide rustls `CryptoProvider`.
+/// This file must not install one anywhere, otherwise the check is moot.
+#[tokio::test]
+async fn dtls_handshake_without_rustls_crypto_provider() -> Result<()> {
+    assert!(
+        rustls::crypto::CryptoProvider::get_default().is_none(),
+        "no default rustls provider may be installed in this test binary"
+    );
+
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    pc1.create_data_channel("probe", None)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())
+    })
+    .await??;
+
+    assert_eq!(
+        *pc1.subscribe_peer_state().borrow(),
+        PeerConnectionState::Connected
+    );
+    assert!(rustls::crypto::CryptoProvider::get_default().is_none());
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+#[test]
+fn crypto_provider_missing_error_names_the_fix() {
+    let message = RtcError::CryptoProviderMissing.to_string();
+    assert!(message.contains("CryptoProvider::install_default"));
+}
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use rustrtc::config::{AudioCapability, MediaCapabilities};
+use rustrtc::media::MediaStreamTrack;
+use rustrtc::media::frame::{AudioFrame, MediaKind, MediaSample, TelephoneEvent};
+use rustrtc::{
+    PeerConnection, RtcConfiguration, RtpCodecParameters, TransceiverDirection, TransportMode,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn pcmu_dtmf_config() -> RtcConfiguration {
+    RtcConfiguration {
+        transport_mode: TransportMode::Rtp,
+        bind_ip: Some("127.0.0.1".to_string()),
+        media_capabilities: Some(MediaCapabilities {
+            audio: vec![AudioCapability::pcmu(), AudioCapability::telephone_event()],
+            video: vec![],
+            application: None,
+            image: vec![],
+        }),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn pcmu_with_telephone_event_surfaces_inbound_dtmf() -> Result<()> {
+    let pc1 = PeerConnection::new(pcmu_dtmf_config());
+    let pc2 = PeerConnection::new(pcmu_dtmf_config());
+
+    let (source, track, _) = rustrtc::media::track::sample_track(MediaKind::Audio, 100);
+    let source = Arc::new(source);
+    pc1.add_track(
+        track,
+        RtpCodecParameters {
+            payload_type: 0,
+            clock_rate: 8000,
+            channels: 1,
+        },
+    )?;
+    pc2.add_transceiver(rustrtc::MediaKind::Audio, TransceiverDirection::RecvOnly);
+
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let answer = pc2.create_answer().await?;
+    let sdp = answer.to_sdp_string();
+    assert!(sdp.contains("a=rtpmap:0 PCMU/8000"), "answer:\n{sdp}");
+    assert!(
+        sdp.contains("a=rtpmap:101 telephone-event/8000"),
+        "answer:\n{sdp}"
+    );
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+    tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())?;
+
+    let digit = TelephoneEvent {
+        event: 5,
+        end: true,
+        volume: 10,
+        duration: 800,
+    };
+    let sender = tokio::spawn(async move {
+        for i in 0..50u32 {
+            let _ = source.send(MediaSample::Audio(AudioFrame {
+                rtp_timestamp: i * 160,
+                clock_rate: 8000,
+                data: bytes::Bytes::from_static(&[0xff; 160]),
+                ..Default::default()
+            }));
+            let _ = source.send(MediaSample::Audio(AudioFrame {
+                rtp_timestamp: i * 160,
+                clock_rate: 8000,
+                data: bytes::Bytes::copy_from_slice(&digit.to_bytes()),
+                payload_type: Some(101),
+                ..Default::default()
+            }));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+
+    let receiver = pc2.get_transceivers()[0]
+        .receiver()
+        .expect("receiver")
+        .clone();
+    let track = receiver.track();
+    let event = timeout(Duration::from_secs(5), async {
+        loop {
+            if let MediaSample::Audio(frame) = track.recv().await?
+                && frame.payload_type == Some(101)
+            {
+                return anyhow::Ok(TelephoneEvent::parse(&frame.data));
+            }
+        }
+    })
+    .await??;
+    sender.abort();
+
+    assert_eq!(event, Some(digit));
+    assert_eq!(event.and_then(|e| e.digit()), Some('5'));
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use anyhow::Result;
+use rustrtc::transports::ice::{IceCandidateType, IceTransportState};
+use rustrtc::transports::sctp::{DataChannel, DataChannelConfig};
+use rustrtc::{
+    DataChannelEvent, PeerConnection, PeerConnectionState, RtcConfiguration, SessionDescription,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Runs one offer/answer round and returns the offer that was sent.
+async fn negotiate(
+    offerer: &PeerConnection,
+    answerer: &PeerConnection,
+) -> Result<SessionDescription> {
+    let _ = offerer.create_offer().await?;
+    offerer.wait_for_gathering_complete().await;
+    let offer = offerer.create_offer().await?;
+    offerer.set_local_description(offer.clone())?;
+    answerer.set_remote_description(offer.clone()).await?;
+    let _ = answerer.create_answer().await?;
+    answerer.wait_for_gathering_complete().await;
+    let answer = answerer.create_answer().await?;
+    answerer.set_local_description(answer.clone())?;
+    offerer.set_remote_description(answer).await?;
+    Ok(offer)
+}
+
+fn ice_ufrag(desc: &SessionDescription) -> Option<String> {
+    desc.session
+        .attributes
+        .iter()
+        .chain(desc.media_sections.iter().flat_map(|m| &m.attributes))
+        .find(|attr| attr.key == "ice-ufrag")
+        .and_then(|attr| attr.value.clone())
+}
+
+/// Two peers with an open negotiated data channel (id 0) between them.
+async fn connected_peers() -> Result<(
+    PeerConnection,
+    PeerConnection,
+    Arc<DataChannel>,
+    Arc<DataChannel>,
+)> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let tx = pc1.create_data_channel("restart", negotiated.clone())?;
+    let rx = pc2.create_data_channel("restart", negotiated)?;
+
+    negotiate(&pc1, &pc2).await?;
+    for dc in [&tx, &rx] {
+        timeout(Duration::from_secs(10), async {
+            while !matches!(dc.recv().await, Some(DataChannelEvent::Open) | None) {}
+        })
+        .await?;
+    }
+    Ok((pc1, pc2, tx, rx))
+}
+
+async fn wait_for_restart_complete(pc: &PeerConnection) -> Result<()> {
+    let ice = pc.ice_transport();
+    timeout(Duration::from_secs(10), async {
+        while ice.is_restarting() || ice.state() != IceTransportState::Connected {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    Ok(())
+}
+
+fn host_udp_addrs(pc: &PeerConnection) -> Vec<SocketAddr> {
+    pc.ice_transport()
+        .local_candidates()
+        .into_iter()
+        .filter(|c| c.typ == IceCandidateType::Host && c.transport.eq_ignore_ascii_case("udp"))
+        .map(|c| c.address)
+        .collect()
+}
+
+/// How many of `addrs` still have a socket bound to them.
+fn open_sockets(addrs: &[SocketAddr]) -> usize {
+    addrs
+        .iter()
+        .filter(|addr| std::net::UdpSocket::bind(addr).is_err())
+        .count()
+}
+
+async fn expect_message(dc: &DataChannel, expected: &[u8]) -> Result<()> {
+    let data = timeout(Duration::from_secs(5), async {
+        loop {
+            match dc.recv().await {
+                Some(DataChannelEvent::Message(data)) => return Some(data),
+                Some(_) => continue,
+                None => return None,
+            }
+        }
+    })
+    .await?;
+    assert_eq!(data.as_deref(), Some(expected));
+    Ok(())
+}
+
+/// An ICE restart picks a new candidate pair under fresh credentials, but the
+/// DTLS association (and therefore its keys) carries over untouched.
+#[tokio::test]
+async fn ice_restart_keeps_dtls_session_and_data_flowing() -> Result<()> {
+    let (pc1, pc2, tx, rx) = connected_peers().await?;
+    pc1.send_data(0, b"before").await?;
+    expect_message(&rx, b"before").await?;
+
+    let keys_before = pc1.export_keying_material("EXTRACTOR-test", None, 32)?;
+    let ufrag1 = pc1.ice_transport().local_parameters().username_fragment;
+    let ufrag2 = pc2.ice_transport().local_parameters().username_fragment;
+
+    pc1.restart_ice()?;
+    let _ = negotiate(&pc1, &pc2).await?;
+
+    // Both sides moved to new credentials and re-ran connectivity checks.
+    assert_ne!(
+        pc1.ice_transport().local_parameters().username_fragment,
+        ufrag1
+    );
+    assert_ne!(
+        pc2.ice_transport().local_parameters().username_fragment,
+        ufrag2
+    );
+    wait_for_restart_complete(&pc1).await?;
+    wait_for_restart_complete(&pc2).await?;
+
+    // No new handshake: the exporter still derives from the original master secret.
+    assert_eq!(
+        pc1.export_keying_material("EXTRACTOR-test", None, 32)?,
+        keys_before
+    );
+    assert_eq!(
+        pc2.export_keying_material("EXTRACTOR-test", None, 32)?,
+        keys_before
+    );
+    assert_eq!(
+        *pc1.subscribe_peer_state().borrow(),
+        PeerConnectionState::Connected
+    );
+
+    pc1.send_data(0, b"after").await?;
+    expect_message(&rx, b"after").await?;
+    pc2.send_data(0, b"reply").await?;
+    expect_message(&tx, b"reply").await?;
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+/// `restart_ice` re-gathers, so the offer carries new credentials and the
+/// peers nominate a pair built from the new candidates.
+#[tokio::test]
+async fn restart_ice_offers_new_credentials_and_reforms_pairs() -> Result<()> {
+    let (pc1, pc2, tx, rx) = connected_peers().await?;
+    let initial_offer = pc1.local_description().expect("initial offer applied");
+    let pair_before = pc1.ice_transport().get_selected_pair().expect("pair");
+
+    pc1.restart_ice()?;
+    let offer = negotiate(&pc1, &pc2).await?;
+    assert!(ice_ufrag(&initial_offer).is_some());
+    assert_ne!(ice_ufrag(&offer), ice_ufrag(&initial_offer));
+    assert_eq!(
+        ice_ufrag(&offer),
+        Some(pc1.ice_transport().local_parameters().username_fragment)
+    );
+
+    wait_for_restart_complete(&pc1).await?;
+    wait_for_restart_complete(&pc2).await?;
+    let pair_after = pc1.ice_transport().get_selected_pair().expect("pair");
+    assert!(
+        pc1.ice_transport()
+            .local_candidates()
+            .iter()
+            .any(|c| c.address == pair_after.local.address),
+        "new pair uses a freshly gathered local candidate"
+    );
+    assert_ne!(pair_after.local.address, pair_before.local.address);
+    assert_ne!(pair_after.remote.address, pair_before.remote.address);
+
+    pc2.send_data(0, b"over the new pair").await?;
+    expect_message(&tx, b"over the new pair").await?;
+    drop(rx);
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+/// Each restart closes the previous generation's sockets once the new pair
+/// is selected, so repeated restarts do not accumulate open sockets.
+#[tokio::test]
+async fn ice_restart_closes_previous_generation_sockets() -> Result<()> {
+    let (pc1, pc2, tx, _rx) = connected_peers().await?;
+    let mut previous = host_udp_addrs(&pc1);
+    assert!(!previous.is_empty());
+    assert_eq!(open_sockets(&previous), previous.len());
+
+    for round in 1..=2 {
+        pc1.restart_ice()?;
+        let _ = negotiate(&pc1, &pc2).await?;
+        wait_for_restart_complete(&pc1).await?;
+        wait_for_restart_complete(&pc2).await?;
+
+        let current = host_udp_addrs(&pc1);
+        assert!(current.iter().all(|addr| !previous.contains(addr)));
+        timeout(Duration::from_secs(5), async {
+            while open_sockets(&previous) > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("restart {round} left previous sockets open"));
+        assert_eq!(open_sockets(&current), current.len());
+
+        pc2.send_data(0, b"still connected").await?;
+        expect_message(&tx, b"still connected").await?;
+        previous = current;
+    }
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
@@ -365,6 +365,10 @@ async fn interop_datachannel_incoming_test() -> Result<()> {
             rustrtc::PeerConnectionEvent::Track(_) => {
                 println!("Received Track event, waiting for DataChannel...");
             }
+            rustrtc::PeerConnectionEvent::RealtimeText(_)
+            | rustrtc::PeerConnectionEvent::IceCandidate(_)
+            | rustrtc::PeerConnectionEvent::EndOfCandidates
+            | rustrtc::PeerConnectionEvent::SctpConnected => {}
         }
     }
 
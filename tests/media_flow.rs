@@ -1,8 +1,10 @@
 use anyhow::Result;
 use rustrtc::media::MediaStreamTrack;
 use rustrtc::media::frame::{MediaSample, VideoFrame};
+use rustrtc::media::track::FeedbackEvent;
+use rustrtc::rtp::RtcpPacket;
 use rustrtc::{
-    MediaKind, PeerConnection, RtcConfiguration, RtpCodecParameters, TransceiverDirection,
+    MediaKind, PeerConnection, RtcConfiguration, RtcError, RtpCodecParameters, TransceiverDirection,
 };
 use std::sync::Arc;
 use std::time::Duration;
@@ -119,3 +121,129 @@ async fn test_media_flow_and_pli() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn wait_for_media_resolves_after_first_packet_not_dtls() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+
+    let (source, track, _) =
+        rustrtc::media::track::sample_track(rustrtc::media::frame::MediaKind::Video, 100);
+    let params = RtpCodecParameters {
+        payload_type: 96,
+        clock_rate: 90000,
+        channels: 0,
+    };
+    pc1.add_track(track, params)?;
+    pc2.add_transceiver(MediaKind::Video, TransceiverDirection::RecvOnly);
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+    tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())?;
+
+    // DTLS is up but nothing has been sent yet.
+    let sending = pc1.get_transceivers()[0].clone();
+    let receiving = pc2.get_transceivers()[0].clone();
+    let short = Duration::from_millis(200);
+    assert!(matches!(
+        pc1.wait_for_media(&sending, short).await,
+        Err(RtcError::Timeout(_))
+    ));
+    assert!(matches!(
+        pc2.wait_for_media(&receiving, short).await,
+        Err(RtcError::Timeout(_))
+    ));
+
+    source.send(MediaSample::Video(VideoFrame {
+        data: bytes::Bytes::from(vec![0u8; 100]),
+        is_last_packet: true,
+        ..Default::default()
+    }))?;
+    pc1.wait_for_media(&sending, Duration::from_secs(5)).await?;
+    pc2.wait_for_media(&receiving, Duration::from_secs(5))
+        .await?;
+    assert_eq!(sending.sender().unwrap().packets_sent(), 1);
+    assert_eq!(receiving.receiver().unwrap().packets_received(), 1);
+
+    Ok(())
+}
+
+/// The receiving side lost the key frame and asks for a new one: the PLI
+/// reaches the remote sender, which passes the request on to the producer of
+/// its track.
+#[tokio::test]
+async fn key_frame_request_reaches_remote_track_producer() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+
+    let (source, track, mut feedback_rx) =
+        rustrtc::media::track::sample_track(rustrtc::media::frame::MediaKind::Video, 100);
+    let params = RtpCodecParameters {
+        payload_type: 96,
+        clock_rate: 90000,
+        channels: 0,
+    };
+    let sender = pc1.add_track(track, params)?;
+    let mut rtcp_rx = sender.subscribe_rtcp();
+    pc2.add_transceiver(MediaKind::Video, TransceiverDirection::RecvOnly);
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+    tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())?;
+
+    // Only delta frames go out: the key frame that should open the stream
+    // never made it.
+    let send_task = tokio::spawn(async move {
+        for seq in 1.. {
+            let frame = VideoFrame {
+                rtp_timestamp: seq * 3000,
+                data: bytes::Bytes::from(vec![0u8; 100]),
+                is_last_packet: true,
+                ..Default::default()
+            };
+            if source.send(MediaSample::Video(frame)).is_err() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(33)).await;
+        }
+    });
+
+    let receiver = pc2.get_transceivers()[0].receiver().unwrap();
+    let remote_track = receiver.track();
+    tokio::time::timeout(Duration::from_secs(5), remote_track.recv()).await??;
+    remote_track.request_key_frame().await?;
+
+    let pli = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let RtcpPacket::PictureLossIndication(pli) = rtcp_rx.recv().await? {
+                return Ok::<_, anyhow::Error>(pli);
+            }
+        }
+    })
+    .await??;
+    assert_eq!(pli.media_ssrc, sender.ssrc());
+
+    let event = tokio::time::timeout(Duration::from_secs(5), feedback_rx.recv()).await?;
+    assert!(matches!(event, Some(FeedbackEvent::RequestKeyFrame)));
+
+    send_task.abort();
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
-use rustrtc::{DataChannelEvent, PeerConnection, RtcConfiguration};
+use rustrtc::transports::sctp::DataChannel;
+use rustrtc::{DataChannelEvent, PeerConnection, PeerConnectionEvent, RtcConfiguration};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
@@ -14,6 +15,16 @@ use webrtc::interceptor::registry::Registry;
 use webrtc::peer_connection::configuration::RTCConfiguration as WebrtcConfiguration;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
 
+/// Next remotely opened data channel, skipping trickled ICE candidate events.
+async fn next_data_channel(pc: &PeerConnection) -> Option<Arc<DataChannel>> {
+    loop {
+        match pc.recv().await? {
+            PeerConnectionEvent::DataChannel(dc) => return Some(dc),
+            _ => continue,
+        }
+    }
+}
+
 /// Test: ordered channels with negotiated mode
 /// This mimics the browser scenario where channels are ordered.
 /// Sends data from RustRTC to webrtc-rs via ordered channels.
@@ -356,17 +367,14 @@ async fn dcep_ordered_channel_test() -> Result<()> {
 
     // Wait for DCEP channel to arrive from WebRTC side
     println!("RustRTC: Waiting for DCEP channel...");
-    let dc = match timeout(Duration::from_secs(10), rust_pc.recv()).await {
-        Ok(Some(rustrtc::PeerConnectionEvent::DataChannel(dc))) => {
+    let dc = match timeout(Duration::from_secs(10), next_data_channel(&rust_pc)).await {
+        Ok(Some(dc)) => {
             println!(
                 "RustRTC: Got DCEP channel: id={} label={} ordered={}",
                 dc.id, dc.label, dc.ordered
             );
             dc
         }
-        Ok(Some(_other)) => {
-            return Err(anyhow::anyhow!("Unexpected event received"));
-        }
         Ok(None) => {
             return Err(anyhow::anyhow!("PC closed before channel arrived"));
         }
@@ -547,8 +555,8 @@ async fn dcep_ordered_bidirectional_test() -> Result<()> {
 
     // Wait for DCEP channel
     println!("RustRTC: Waiting for DCEP channel...");
-    let dc = match timeout(Duration::from_secs(10), rust_pc.recv()).await {
-        Ok(Some(rustrtc::PeerConnectionEvent::DataChannel(dc))) => {
+    let dc = match timeout(Duration::from_secs(10), next_data_channel(&rust_pc)).await {
+        Ok(Some(dc)) => {
             println!(
                 "RustRTC: Got DCEP channel: id={} label={} ordered={}",
                 dc.id, dc.label, dc.ordered
@@ -577,6 +585,7 @@ async fn dcep_ordered_bidirectional_test() -> Result<()> {
             Ok(Some(DataChannelEvent::Close)) => {
                 return Err(anyhow::anyhow!("Channel closed before ping"));
             }
+            Ok(Some(DataChannelEvent::BufferedAmountLow)) => continue,
             Ok(None) => {
                 return Err(anyhow::anyhow!("Channel recv returned None"));
             }
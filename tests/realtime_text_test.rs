@@ -0,0 +1,77 @@
+use anyhow::Result;
+use rustrtc::transports::sctp::DataChannelConfig;
+use rustrtc::{DataChannelEvent, PeerConnection, PeerConnectionEvent, RtcConfiguration};
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Collect `expected_len` characters of real-time text, along with the
+/// protocols of any data channels announced on the way.
+async fn recv_text(pc: &PeerConnection, expected_len: usize) -> Result<(String, Vec<String>)> {
+    let mut text = String::new();
+    let mut channels = Vec::new();
+    timeout(Duration::from_secs(5), async {
+        while text.chars().count() < expected_len {
+            match pc.recv().await {
+                Some(PeerConnectionEvent::RealtimeText(chunk)) => text.push_str(&chunk),
+                Some(PeerConnectionEvent::DataChannel(dc)) => channels.push(dc.protocol.clone()),
+                Some(_) => continue,
+                None => break,
+            }
+        }
+    })
+    .await?;
+    Ok((text, channels))
+}
+
+/// Text typed on one side is reconstructed on the other in order, and the
+/// peer answers on the same T.140 channel.
+#[tokio::test]
+async fn realtime_text_is_reconstructed_in_order() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let dc1 = pc1.create_data_channel("control", negotiated.clone())?;
+    let dc2 = pc2.create_data_channel("control", negotiated)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    for dc in [&dc1, &dc2] {
+        timeout(Duration::from_secs(10), async {
+            while !matches!(dc.recv().await, Some(DataChannelEvent::Open) | None) {}
+        })
+        .await?;
+    }
+
+    let message = "Hello, wörld! 👋";
+    for ch in message.chars() {
+        pc1.send_realtime_text(&ch.to_string()).await?;
+    }
+    let (text, channels) = recv_text(&pc2, message.chars().count()).await?;
+    assert_eq!(text, message);
+    assert_eq!(channels, ["t140"], "the remote t140 channel is announced");
+
+    pc2.send_realtime_text("ok").await?;
+    let (text, channels) = recv_text(&pc1, 2).await?;
+    assert_eq!(text, "ok");
+    assert!(
+        channels.is_empty(),
+        "the answer reuses the existing channel"
+    );
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
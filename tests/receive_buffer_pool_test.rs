@@ -0,0 +1,61 @@
+use bytes::Bytes;
+use rustrtc::transports::ice::buffer_pool::ReceiveBufferPool;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const PACKETS: usize = 10_000;
+/// Packets the consumer holds before releasing them, like a short jitter buffer.
+const IN_FLIGHT: usize = 8;
+
+/// Feed `PACKETS` MTU-sized packets through `copy` while keeping a window of
+/// them alive, and return the allocations made.
+fn allocations_for(mut copy: impl FnMut(&[u8]) -> Bytes) -> usize {
+    let packet = [0xAB; 1200];
+    let mut in_flight = VecDeque::with_capacity(IN_FLIGHT + 1);
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..PACKETS {
+        in_flight.push_back(copy(&packet));
+        if in_flight.len() > IN_FLIGHT {
+            in_flight.pop_front();
+        }
+    }
+    let after = ALLOCATIONS.load(Ordering::Relaxed);
+    drop(in_flight);
+    after - before
+}
+
+#[test]
+fn pooled_receive_allocates_less_per_packet() {
+    let baseline = allocations_for(Bytes::copy_from_slice);
+    let mut pool = ReceiveBufferPool::new(64 * 1024);
+    let pooled = allocations_for(|packet| pool.copy(packet));
+
+    assert!(baseline >= PACKETS, "baseline made {baseline} allocations");
+    assert!(
+        pooled * 10 < baseline,
+        "pooled made {pooled} allocations, baseline {baseline}"
+    );
+
+    // Pooling disabled behaves like the baseline.
+    let mut unpooled = ReceiveBufferPool::new(0);
+    assert!(allocations_for(|packet| unpooled.copy(packet)) >= PACKETS);
+}
@@ -10,7 +10,7 @@
 use anyhow::Result;
 use rustrtc::{
     MediaKind, PeerConnection, PeerConnectionEvent, RtcConfiguration, RtpCodecParameters, SdpType,
-    SessionDescription, TransceiverDirection, TransportMode,
+    SessionDescription, TransceiverDirection, TransportMode, UnsignaledSsrcPolicy,
 };
 use std::time::Duration;
 use tokio::net::UdpSocket;
@@ -247,6 +247,94 @@ async fn test_rtp_mode_callee_no_ssrc_signaled_stun_first() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_rtp_mode_callee_no_ssrc_signaled_latches_first_ssrc() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut config = RtcConfiguration::default();
+    config.transport_mode = TransportMode::Rtp;
+    config.unsignaled_ssrc_policy = UnsignaledSsrcPolicy::Latch;
+    let pc = PeerConnection::new(config);
+
+    pc.add_transceiver(MediaKind::Video, TransceiverDirection::RecvOnly);
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let pc_clone = pc.clone();
+    tokio::spawn(async move {
+        while let Some(event) = pc_clone.recv().await {
+            if let PeerConnectionEvent::Track(transceiver) = event {
+                let _ = tx.send(transceiver);
+            }
+        }
+    });
+
+    let mut config_fake = RtcConfiguration::default();
+    config_fake.transport_mode = TransportMode::Rtp;
+    let pc_fake = PeerConnection::new(config_fake);
+    let (_source, track, _) =
+        rustrtc::media::track::sample_track(rustrtc::media::frame::MediaKind::Video, 100);
+    let params = RtpCodecParameters {
+        payload_type: 96,
+        clock_rate: 90000,
+        channels: 0,
+    };
+    pc_fake.add_track(track, params)?;
+
+    let _ = pc_fake.create_offer().await?;
+    pc_fake.wait_for_gathering_complete().await;
+    let offer = pc_fake.create_offer().await?;
+    let offer_desc =
+        SessionDescription::parse(SdpType::Offer, &strip_ssrc(&offer.to_sdp_string()))?;
+
+    pc.set_remote_description(offer_desc).await?;
+    let _ = pc.create_answer().await?;
+    pc.wait_for_gathering_complete().await;
+    let answer = pc.create_answer().await?;
+    pc.set_local_description(answer)?;
+
+    let pc_addr = pc.ice_transport().local_candidates()[0].address;
+    let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    socket.connect(pc_addr).await?;
+
+    let first_ssrc = 111111;
+    let second_ssrc = 222222;
+    let payload_type = 96;
+
+    for i in 0..20 {
+        socket
+            .send(&create_rtp_packet(i, first_ssrc, payload_type))
+            .await?;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let transceiver = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await?
+        .expect("Track event for the first SSRC");
+    let receiver = transceiver.receiver().expect("receiver");
+    assert_eq!(receiver.ssrc(), first_ssrc);
+
+    // Only one transceiver exists, so the second SSRC has nowhere to go.
+    for i in 20..40 {
+        socket
+            .send(&create_rtp_packet(i, second_ssrc, payload_type))
+            .await?;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(
+        receiver.ssrc(),
+        first_ssrc,
+        "the first SSRC stays latched under UnsignaledSsrcPolicy::Latch"
+    );
+    assert!(
+        rx.try_recv().is_err(),
+        "the dropped SSRC must not produce another Track event"
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_rtp_mode_caller_no_ssrc_signaled() -> Result<()> {
     let _ = env_logger::builder().is_test(true).try_init();
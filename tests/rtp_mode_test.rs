@@ -9,13 +9,14 @@
 #![allow(clippy::zombie_processes)]
 use anyhow::Result;
 use rustrtc::media::MediaStreamTrack;
-use rustrtc::media::frame::{MediaSample, VideoFrame};
+use rustrtc::media::frame::{AudioFrame, MediaSample, VideoFrame};
 use rustrtc::{
-    MediaKind, PeerConnection, RtcConfiguration, RtpCodecParameters, TransceiverDirection,
-    TransportMode,
+    AudioCapability, MediaCapabilities, MediaKind, PeerConnection, RtcConfiguration,
+    RtpCodecParameters, SdpType, SessionDescription, TransceiverDirection, TransportMode,
 };
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::net::UdpSocket;
 
 #[tokio::test]
 async fn test_rtp_mode_peer_connection() -> Result<()> {
@@ -147,3 +148,83 @@ async fn test_rtp_mode_peer_connection() -> Result<()> {
 
     Ok(())
 }
+
+/// A SIP-style offer carries no ICE attributes, only `c=`/`m=`. The answer
+/// must stay ICE-less and media must go to the offer's connection address,
+/// with a media-level `c=` taking precedence over the session-level one.
+#[tokio::test]
+async fn answer_bare_sip_offer_sends_media_to_connection_address() -> Result<()> {
+    let remote = UdpSocket::bind("127.0.0.1:0").await?;
+    let offer = format!(
+        "v=0\r\n\
+         o=- 1 1 IN IP4 192.0.2.1\r\n\
+         s=-\r\n\
+         c=IN IP4 192.0.2.1\r\n\
+         t=0 0\r\n\
+         m=audio {} RTP/AVP 0 8\r\n\
+         c=IN IP4 127.0.0.1\r\n\
+         a=rtpmap:0 PCMU/8000\r\n\
+         a=rtpmap:8 PCMA/8000\r\n\
+         a=sendrecv\r\n",
+        remote.local_addr()?.port()
+    );
+
+    let mut config = RtcConfiguration::default();
+    config.transport_mode = TransportMode::Rtp;
+    config.bind_ip = Some("127.0.0.1".to_string());
+    config.media_capabilities = Some(MediaCapabilities {
+        audio: vec![AudioCapability::pcmu(), AudioCapability::pcma()],
+        ..Default::default()
+    });
+    let pc = PeerConnection::new(config);
+    let (source, track, _) =
+        rustrtc::media::track::sample_track(rustrtc::media::frame::MediaKind::Audio, 64);
+    pc.add_track(
+        track,
+        RtpCodecParameters {
+            payload_type: 0,
+            clock_rate: 8000,
+            channels: 1,
+        },
+    )?;
+
+    pc.set_remote_description(SessionDescription::parse(SdpType::Offer, &offer)?)
+        .await?;
+    let answer = pc.create_answer().await?;
+    pc.set_local_description(answer.clone())?;
+
+    let sdp = answer.to_sdp_string();
+    for ice_attr in ["a=ice-ufrag", "a=ice-pwd", "a=candidate", "a=ice-options"] {
+        assert!(
+            !sdp.contains(ice_attr),
+            "unexpected {ice_attr} in answer:\n{sdp}"
+        );
+    }
+    assert!(sdp.contains("c=IN IP4 127.0.0.1"), "answer:\n{sdp}");
+    let audio = &answer.media_sections[0];
+    assert_eq!(audio.protocol, "RTP/AVP");
+    assert_ne!(audio.port, 0);
+    assert_eq!(audio.formats, vec!["0".to_string(), "8".to_string()]);
+
+    pc.wait_for_rtp_transport_ready(Duration::from_secs(5))
+        .await?;
+    let pair = pc
+        .ice_transport()
+        .get_selected_pair()
+        .expect("selected pair");
+    assert_eq!(pair.remote.address, remote.local_addr()?);
+
+    source.send_audio(AudioFrame {
+        data: bytes::Bytes::from(vec![0xff; 160]),
+        ..AudioFrame::default()
+    })?;
+    let mut buf = [0u8; 1500];
+    let (n, from) =
+        tokio::time::timeout(Duration::from_secs(2), remote.recv_from(&mut buf)).await??;
+    assert_eq!(n, 12 + 160);
+    assert_eq!(buf[1] & 0x7f, 0, "PCMU payload type");
+    assert_eq!(from.port(), audio.port);
+
+    pc.close();
+    Ok(())
+}
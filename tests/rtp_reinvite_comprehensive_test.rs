@@ -106,6 +106,39 @@ async fn test_reinvite_offerer_timing() {
     );
 }
 
+/// Test 1b: Reinvite offers keep the o= session id and bump its version
+#[tokio::test]
+async fn test_reinvite_offer_increments_session_version() {
+    let config = RtcConfigurationBuilder::new()
+        .transport_mode(TransportMode::Rtp)
+        .sdp_origin_username("rustrtc-ua".to_string())
+        .build();
+    let pc = PeerConnection::new(config);
+    pc.add_transceiver(
+        MediaKind::Audio,
+        peer_connection::TransceiverDirection::SendRecv,
+    );
+
+    let offer = pc.create_offer().await.unwrap();
+    pc.set_local_description(offer.clone()).unwrap();
+    let mid = offer.media_sections[0].mid.clone();
+    let answer = create_minimal_sdp(SdpType::Answer, &mid, Direction::SendRecv);
+    pc.set_remote_description(answer).await.unwrap();
+
+    let reinvite = pc.create_offer().await.unwrap();
+    let first = &offer.session.origin;
+    let second = &reinvite.session.origin;
+    assert_eq!(first.username, "rustrtc-ua");
+    assert_eq!(second.username, "rustrtc-ua");
+    assert_eq!(second.session_id, first.session_id);
+    assert_eq!(second.session_version, first.session_version + 1);
+    assert!(reinvite.to_sdp_string().contains(&format!(
+        "o=rustrtc-ua {} {} IN IP4",
+        first.session_id,
+        first.session_version + 1
+    )));
+}
+
 /// Test 2: Answerer timing - parameters should apply when offer is received
 #[tokio::test]
 async fn test_reinvite_answerer_timing() {
@@ -241,6 +274,89 @@ async fn test_direction_change_unhold() {
     );
 }
 
+/// Test 5b: Unhold of a transceiver created recvonly starts a live sender
+#[tokio::test]
+async fn test_direction_change_unhold_starts_sender() {
+    use rustrtc::media::frame::AudioFrame;
+    use tokio::net::UdpSocket;
+
+    let remote = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let remote_port = remote.local_addr().unwrap().port();
+    let remote_offer = |direction: &str| {
+        let sdp = format!(
+            "v=0\r\n\
+             o=- 1 1 IN IP4 127.0.0.1\r\n\
+             s=-\r\n\
+             t=0 0\r\n\
+             c=IN IP4 127.0.0.1\r\n\
+             m=audio {} RTP/AVP 0\r\n\
+             a=rtpmap:0 PCMU/8000\r\n\
+             a=ssrc:12345 cname:test\r\n\
+             a={}\r\n",
+            remote_port, direction
+        );
+        SessionDescription::parse(SdpType::Offer, &sdp).unwrap()
+    };
+
+    let mut config = RtcConfiguration::default();
+    config.transport_mode = TransportMode::Rtp;
+    let pc = PeerConnection::new(config);
+
+    // Remote is sendonly (hold), so the transceiver is created without a send stream.
+    pc.set_remote_description(remote_offer("sendonly"))
+        .await
+        .unwrap();
+    let answer = pc.create_answer().await.unwrap();
+    pc.set_local_description(answer).unwrap();
+    let transceiver = pc.get_transceivers()[0].clone();
+    assert!(transceiver.sender().is_none());
+    assert_eq!(transceiver.sender_ssrc(), None);
+
+    // Unhold: the answer must now advertise a freshly allocated send SSRC.
+    pc.set_remote_description(remote_offer("sendrecv"))
+        .await
+        .unwrap();
+    let ssrc = transceiver
+        .sender_ssrc()
+        .expect("unhold should allocate a sender SSRC");
+    let answer = pc.create_answer().await.unwrap();
+    assert_eq!(answer.media_sections[0].direction, Direction::SendRecv);
+    assert!(
+        answer.media_sections[0]
+            .attributes
+            .iter()
+            .any(|a| a.key == "ssrc" && a.value.as_deref().unwrap().starts_with(&ssrc.to_string())),
+        "answer should signal the new send SSRC"
+    );
+    pc.set_local_description(answer).unwrap();
+
+    let (source, track, _) =
+        rustrtc::media::track::sample_track(rustrtc::media::frame::MediaKind::Audio, 16);
+    let params = RtpCodecParameters {
+        payload_type: 0,
+        clock_rate: 8000,
+        channels: 1,
+    };
+    let sender = pc.add_track(track, params).unwrap();
+    assert_eq!(pc.get_transceivers().len(), 1);
+    assert_eq!(sender.ssrc(), ssrc);
+
+    source
+        .send_audio(AudioFrame {
+            data: bytes::Bytes::from_static(&[0xff; 160]),
+            ..AudioFrame::default()
+        })
+        .unwrap();
+
+    let mut buf = [0u8; 1500];
+    let n = tokio::time::timeout(std::time::Duration::from_secs(2), remote.recv(&mut buf))
+        .await
+        .expect("sender should emit RTP after unhold")
+        .unwrap();
+    assert!(n > 12);
+    assert_eq!(u32::from_be_bytes([buf[8], buf[9], buf[10], buf[11]]), ssrc);
+}
+
 /// Test 6: Direction change - SendRecv to Inactive
 #[tokio::test]
 async fn test_direction_change_inactive() {
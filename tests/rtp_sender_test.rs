@@ -157,4 +157,78 @@ mod tests {
         // So ts3 should be approx ts2 + 3000.
         assert_eq!(ts3.wrapping_sub(ts2), 3000);
     }
+
+    #[tokio::test]
+    async fn rtp_sender_emits_padding_probes() {
+        use rustrtc::peer_connection::PaddingProbe;
+        use std::time::Duration;
+
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket_wrapper = IceSocketWrapper::Udp(Arc::new(socket));
+        let (_tx, rx) = watch::channel(Some(socket_wrapper));
+
+        let receiver_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let receiver_addr = receiver_socket.local_addr().unwrap();
+
+        let ice_conn = IceConn::new(rx, receiver_addr, None);
+        let rtp_transport = Arc::new(RtpTransport::new(ice_conn, false));
+
+        // No samples are ever sent on the track: every packet is a probe.
+        let (_source, track, _) = sample_track(MediaKind::Audio, 10);
+        let params = RtpCodecParameters {
+            payload_type: 111,
+            clock_rate: 48000,
+            channels: 2,
+        };
+        let sender = RtpSender::builder(track, 12345)
+            .params(params)
+            .padding_probe(PaddingProbe {
+                packets_per_second: 50,
+                padding_size: 32,
+            })
+            .build();
+        sender.set_transport(rtp_transport);
+
+        let mut buf = [0u8; 1500];
+        let mut packets = Vec::new();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(1);
+        while let Ok(Ok((len, _))) =
+            tokio::time::timeout_at(deadline, receiver_socket.recv_from(&mut buf)).await
+        {
+            assert_eq!(len, 12 + 32);
+            assert_eq!(buf[0] & 0x20, 0x20, "P bit must be set");
+            packets.push(rustrtc::rtp::RtpPacket::parse(&buf[..len]).unwrap());
+        }
+
+        assert!(
+            (35..=65).contains(&packets.len()),
+            "expected ~50 probes in 1s, got {}",
+            packets.len()
+        );
+        for pair in packets.windows(2) {
+            assert_eq!(
+                pair[1].header.sequence_number,
+                pair[0].header.sequence_number.wrapping_add(1)
+            );
+        }
+        for packet in &packets {
+            assert_eq!(packet.header.ssrc, 12345);
+            assert_eq!(packet.header.payload_type, 111);
+            assert!(packet.payload.is_empty());
+            assert_eq!(packet.padding_len, 32);
+        }
+
+        // Disabling stops the probes.
+        sender.set_padding_probe(None);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        while receiver_socket.try_recv_from(&mut buf).is_ok() {}
+        assert!(
+            tokio::time::timeout(
+                Duration::from_millis(200),
+                receiver_socket.recv_from(&mut buf)
+            )
+            .await
+            .is_err()
+        );
+    }
 }
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use rustrtc::transports::sctp::DataChannelConfig;
+use rustrtc::{DataChannelEvent, PeerConnection, PeerConnectionEvent, RtcConfiguration};
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Both sides must see `SctpConnected` queued by the time their negotiated
+/// channel reports `Open`, whichever end completed the handshake.
+#[tokio::test]
+async fn sctp_connected_precedes_negotiated_channel_open() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let dc1 = pc1.create_data_channel("events", negotiated.clone())?;
+    let dc2 = pc2.create_data_channel("events", negotiated)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    for (pc, dc) in [(&pc1, &dc1), (&pc2, &dc2)] {
+        timeout(Duration::from_secs(10), async {
+            while !matches!(dc.recv().await, Some(DataChannelEvent::Open) | None) {}
+        })
+        .await?;
+
+        // Only events already queued when `Open` was observed count.
+        let mut connected = 0;
+        while let Ok(Some(event)) = timeout(Duration::ZERO, pc.recv()).await {
+            if matches!(event, PeerConnectionEvent::SctpConnected) {
+                connected += 1;
+            }
+        }
+        assert_eq!(connected, 1, "SctpConnected must be emitted exactly once");
+    }
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
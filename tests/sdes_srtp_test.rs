@@ -0,0 +1,117 @@
+// Test/example crate: relax pedantic style lints that are noisy in fixtures.
+#![allow(clippy::field_reassign_with_default)]
+use anyhow::Result;
+use rustrtc::media::MediaStreamTrack;
+use rustrtc::media::frame::{AudioFrame, MediaSample};
+use rustrtc::{
+    AudioCapability, MediaCapabilities, MediaKind, PeerConnection, RtcConfiguration, RtcError,
+    RtpCodecParameters, SdpType, SessionDescription, TransceiverDirection, TransportMode,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn sdes_config() -> RtcConfiguration {
+    let mut config = RtcConfiguration::default();
+    config.transport_mode = TransportMode::Srtp;
+    // Negotiate the PCMU stream the media test sends.
+    config.media_capabilities = Some(MediaCapabilities {
+        audio: vec![AudioCapability::pcmu()],
+        ..Default::default()
+    });
+    config
+}
+
+#[tokio::test]
+async fn sdes_mode_exchanges_encrypted_media() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pc1 = PeerConnection::new(sdes_config());
+    let pc2 = PeerConnection::new(sdes_config());
+
+    let (source, track, _) =
+        rustrtc::media::track::sample_track(rustrtc::media::frame::MediaKind::Audio, 100);
+    let source = Arc::new(source);
+    let params = RtpCodecParameters {
+        payload_type: 0,
+        clock_rate: 8000,
+        channels: 1,
+    };
+    pc1.add_track(track, params)?;
+    pc2.add_transceiver(MediaKind::Audio, TransceiverDirection::RecvOnly);
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    for section in &offer.media_sections {
+        assert_eq!(section.protocol, "RTP/SAVP");
+        assert!(section.attributes.iter().any(|a| a.key == "crypto"));
+        assert!(!section.attributes.iter().any(|a| a.key == "fingerprint"));
+    }
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    assert!(
+        answer.media_sections[0]
+            .attributes
+            .iter()
+            .any(|a| a.key == "crypto")
+    );
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())
+    })
+    .await??;
+
+    let payload = vec![0x5A_u8; 160];
+    let send_payload = payload.clone();
+    let sender = tokio::spawn(async move {
+        for i in 0..100u32 {
+            let frame = AudioFrame {
+                rtp_timestamp: i * 160,
+                data: bytes::Bytes::from(send_payload.clone()),
+                ..Default::default()
+            };
+            if source.send(MediaSample::Audio(frame)).is_err() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+
+    let track_remote = pc2.get_transceivers()[0].receiver().unwrap().track();
+    let sample = tokio::time::timeout(Duration::from_secs(5), track_remote.recv()).await??;
+    match sample {
+        MediaSample::Audio(frame) => assert_eq!(frame.data.as_ref(), &payload[..]),
+        other => panic!("unexpected sample {:?}", other),
+    }
+
+    sender.abort();
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
+
+#[tokio::test]
+async fn sdes_mode_rejects_dtls_only_remote() {
+    let remote_offer = "v=0\r\n\
+o=- 1 1 IN IP4 127.0.0.1\r\n\
+s=-\r\n\
+c=IN IP4 127.0.0.1\r\n\
+t=0 0\r\n\
+m=audio 9 UDP/TLS/RTP/SAVPF 0\r\n\
+a=mid:0\r\n\
+a=rtpmap:0 PCMU/8000\r\n\
+a=fingerprint:sha-256 AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99\r\n\
+a=setup:actpass\r\n\
+a=sendrecv\r\n";
+
+    let pc = PeerConnection::new(sdes_config());
+    let offer = SessionDescription::parse(SdpType::Offer, remote_offer).unwrap();
+    let err = pc.set_remote_description(offer).await.unwrap_err();
+    assert!(matches!(err, RtcError::InvalidConfiguration(_)));
+}
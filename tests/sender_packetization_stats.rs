@@ -0,0 +1,86 @@
+use anyhow::Result;
+use rustrtc::media::frame::{MediaKind, MediaSample, VideoFrame};
+use rustrtc::media::{ChannelMediaSource, Packetizer, Vp8Payloader, track_from_source};
+use rustrtc::{
+    PeerConnection, RtcConfiguration, RtpCodecParameters, StatsKind, TransceiverDirection,
+    TransportMode,
+};
+use std::time::Duration;
+
+fn rtp_config() -> RtcConfiguration {
+    RtcConfiguration {
+        transport_mode: TransportMode::Rtp,
+        bind_ip: Some("127.0.0.1".to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn outbound_stats_report_packets_per_frame() -> Result<()> {
+    let pc1 = PeerConnection::new(rtp_config());
+    let pc2 = PeerConnection::new(rtp_config());
+
+    // Whole frames go in; the packetizer splits them at a 1000-byte MTU.
+    let (frames_tx, source) = ChannelMediaSource::channel(MediaKind::Video, 16);
+    let packetizer = Packetizer::new(Box::new(source), 1000, Box::new(Vp8Payloader));
+    let (track, _pump) = track_from_source(Box::new(packetizer), 100)?;
+    pc1.add_track(
+        track,
+        RtpCodecParameters {
+            payload_type: 96,
+            clock_rate: 90000,
+            channels: 0,
+        },
+    )?;
+    pc2.add_transceiver(rustrtc::MediaKind::Video, TransceiverDirection::RecvOnly);
+
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+    tokio::try_join!(pc1.wait_for_connected(), pc2.wait_for_connected())?;
+
+    // 4000 bytes take five packets of at most 999 bytes plus the one-byte
+    // VP8 payload descriptor.
+    let frames = 3u64;
+    let packets_per_frame = 5u64;
+    for i in 0..frames {
+        frames_tx
+            .send(MediaSample::Video(VideoFrame {
+                rtp_timestamp: i as u32 * 3000,
+                data: bytes::Bytes::from(vec![0x42; 4000]),
+                ..Default::default()
+            }))
+            .expect("packetizer input open");
+    }
+
+    let packets = frames * packets_per_frame;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    let outbound = loop {
+        let report = pc1.get_stats().await?;
+        if let Some(entry) = report
+            .entries
+            .into_iter()
+            .find(|e| e.kind == StatsKind::OutboundRtp && e.values["packetsSent"] == packets)
+        {
+            break entry;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "never saw {packets} packets sent"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+
+    assert_eq!(outbound.values["framesSent"], frames);
+    assert_eq!(outbound.values["packetsPerFrame"], packets_per_frame as f64);
+    assert_eq!(
+        outbound.values["averagePayloadSize"],
+        (4000 + packets_per_frame) as f64 / packets_per_frame as f64
+    );
+    assert_eq!(outbound.values["retransmittedPacketsSent"], 0);
+    assert_eq!(outbound.values["paddingBytesSent"], 0);
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Smoke test for `examples/sfu.rs`: three participants each receive the
+//! other two's media on rewritten SSRCs, and key frame requests reach the
+//! publisher.
+#[allow(dead_code)]
+#[path = "../examples/sfu.rs"]
+mod sfu;
+
+use rustrtc::media::MediaStreamTrack;
+use rustrtc::media::frame::MediaSample;
+use rustrtc::rtp::RtcpPacket;
+use sfu::{DemoPeer, Sfu};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+#[tokio::test]
+async fn three_peers_receive_each_others_media() {
+    let sfu = Sfu::new();
+    let mut peers = Vec::new();
+    for i in 0..3u32 {
+        peers.push(DemoPeer::join(&sfu, 1000 * (i + 1)).await.unwrap());
+    }
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while peers.iter().any(|p| p.forwarded_receivers().len() < 2) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("every peer should be offered the other two publications");
+
+    let publisher_ssrcs: HashSet<u32> = peers.iter().map(|p| p.sender.ssrc()).collect();
+    let send = async {
+        for index in 0..100 {
+            for peer in &peers {
+                peer.send_frame(peer.id as u8, index).unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    let receive = async {
+        let mut received = HashMap::new();
+        for peer in &peers {
+            // Source tag → SSRC it arrived on at this peer.
+            let mut sources = HashMap::new();
+            for receiver in peer.forwarded_receivers() {
+                let sample = tokio::time::timeout(Duration::from_secs(5), receiver.track().recv())
+                    .await
+                    .expect("forwarded media should arrive")
+                    .unwrap();
+                let MediaSample::Video(frame) = sample else {
+                    panic!("expected video");
+                };
+                let ssrc = frame.raw_packet.as_ref().unwrap().header.ssrc;
+                sources.insert(frame.data[0], (ssrc, receiver));
+            }
+            received.insert(peer.id, sources);
+        }
+        received
+    };
+    let (_, received) = tokio::join!(send, receive);
+
+    for peer in &peers {
+        let sources = &received[&peer.id];
+        let tags: HashSet<u8> = sources.keys().copied().collect();
+        let expected: HashSet<u8> = peers
+            .iter()
+            .filter(|p| p.id != peer.id)
+            .map(|p| p.id as u8)
+            .collect();
+        assert_eq!(tags, expected, "peer {} sources", peer.id);
+        let ssrcs: HashSet<u32> = sources.values().map(|(ssrc, _)| *ssrc).collect();
+        assert_eq!(ssrcs.len(), 2, "one SSRC per forwarded participant");
+        assert!(
+            ssrcs.is_disjoint(&publisher_ssrcs),
+            "forwarded SSRCs are rewritten"
+        );
+    }
+
+    // A key frame request from peer 0 for peer 1's media reaches peer 1.
+    let mut rtcp_rx = peers[1].sender.subscribe_rtcp();
+    let (_, receiver) = &received[&peers[0].id][&(peers[1].id as u8)];
+    receiver.request_key_frame().await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let RtcpPacket::PictureLossIndication(pli) = rtcp_rx.recv().await.unwrap() {
+                assert_eq!(pli.media_ssrc, peers[1].sender.ssrc());
+                break;
+            }
+        }
+    })
+    .await
+    .expect("PLI should be relayed to the publisher");
+}
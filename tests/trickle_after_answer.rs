@@ -0,0 +1,74 @@
+use anyhow::Result;
+use rustrtc::transports::sctp::DataChannelConfig;
+use rustrtc::{DataChannelEvent, PeerConnection, RtcConfiguration, SessionDescription};
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn without_candidates(mut desc: SessionDescription) -> SessionDescription {
+    for section in &mut desc.media_sections {
+        section
+            .attributes
+            .retain(|a| a.key != "candidate" && a.key != "end-of-candidates");
+    }
+    desc
+}
+
+#[tokio::test]
+async fn candidates_trickled_after_answer_establish_connectivity() -> Result<()> {
+    let pc1 = PeerConnection::new(RtcConfiguration::default());
+    let pc2 = PeerConnection::new(RtcConfiguration::default());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let dc1 = pc1.create_data_channel("trickle", negotiated.clone())?;
+    let _dc2 = pc2.create_data_channel("trickle", negotiated)?;
+
+    // Signal both descriptions without any candidates.
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(without_candidates(offer))
+        .await?;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(without_candidates(answer))
+        .await?;
+
+    pc1.wait_for_gathering_complete().await;
+    pc2.wait_for_gathering_complete().await;
+
+    // The regenerated answer carries everything gathered after create_answer.
+    let full_answer = pc2.current_local_description().expect("local description");
+    let attrs = &full_answer.media_sections[0].attributes;
+    assert_eq!(
+        attrs.iter().filter(|a| a.key == "candidate").count(),
+        pc2.ice_transport().local_candidates().len()
+    );
+    assert!(attrs.iter().any(|a| a.key == "end-of-candidates"));
+
+    let trickled = pc1.ice_transport().local_candidates();
+    assert!(!trickled.is_empty());
+    for candidate in &trickled {
+        pc2.add_ice_candidate(candidate.clone())?;
+    }
+    for candidate in pc2.ice_transport().local_candidates() {
+        pc1.add_ice_candidate(candidate)?;
+    }
+
+    timeout(Duration::from_secs(10), async {
+        while !matches!(dc1.recv().await, Some(DataChannelEvent::Open) | None) {}
+    })
+    .await?;
+
+    let (_, remote) = pc2.ice_transport().selected_pair().expect("selected pair");
+    assert!(
+        trickled.iter().any(|c| c.address == remote.address),
+        "selected remote {} was not trickled",
+        remote.address
+    );
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}
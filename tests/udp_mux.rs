@@ -0,0 +1,98 @@
+use anyhow::Result;
+use rustrtc::media::MediaStreamTrack;
+use rustrtc::media::frame::{MediaSample, VideoFrame};
+use rustrtc::{
+    IceCandidateType, MediaKind, PeerConnection, RtcConfigurationBuilder, RtpCodecParameters,
+    TransceiverDirection, UdpMux,
+};
+use std::time::Duration;
+
+async fn connect(offerer: &PeerConnection, answerer: &PeerConnection) -> Result<()> {
+    let _ = offerer.create_offer().await?;
+    offerer.wait_for_gathering_complete().await;
+    let offer = offerer.create_offer().await?;
+    offerer.set_local_description(offer.clone())?;
+    answerer.set_remote_description(offer).await?;
+    let _ = answerer.create_answer().await?;
+    answerer.wait_for_gathering_complete().await;
+    let answer = answerer.create_answer().await?;
+    answerer.set_local_description(answer.clone())?;
+    offerer.set_remote_description(answer).await?;
+    tokio::try_join!(offerer.wait_for_connected(), answerer.wait_for_connected())?;
+    Ok(())
+}
+
+/// Two PeerConnections gather on one muxed port, each connects to its own
+/// peer, and each peer receives only the media of its own session.
+#[tokio::test]
+async fn two_peer_connections_share_one_udp_port() -> Result<()> {
+    let mux = UdpMux::bind("127.0.0.1:0".parse()?).await?;
+    let loopback = || RtcConfigurationBuilder::new().bind_ip("127.0.0.1".into());
+
+    let mut senders = Vec::new();
+    let mut peers = Vec::new();
+    for marker in [1u8, 2] {
+        let muxed = PeerConnection::new(loopback().udp_mux(mux.clone()).build());
+        let remote = PeerConnection::new(loopback().build());
+
+        let (source, track, _) =
+            rustrtc::media::track::sample_track(rustrtc::media::frame::MediaKind::Video, 100);
+        let params = RtpCodecParameters {
+            payload_type: 96,
+            clock_rate: 90000,
+            channels: 0,
+        };
+        muxed.add_track(track, params)?;
+        remote.add_transceiver(MediaKind::Video, TransceiverDirection::RecvOnly);
+        connect(&muxed, &remote).await?;
+
+        let host = muxed
+            .ice_transport()
+            .local_candidates()
+            .into_iter()
+            .filter(|c| c.typ == IceCandidateType::Host && c.transport == "udp")
+            .collect::<Vec<_>>();
+        assert_eq!(host.len(), 1, "a muxed PC gathers a single host candidate");
+        assert_eq!(host[0].address, mux.local_addr());
+
+        senders.push(tokio::spawn(async move {
+            for seq in 0.. {
+                let frame = VideoFrame {
+                    rtp_timestamp: seq * 3000,
+                    data: bytes::Bytes::from(vec![marker; 100]),
+                    is_last_packet: true,
+                    ..Default::default()
+                };
+                if source.send(MediaSample::Video(frame)).is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        }));
+        peers.push((marker, muxed, remote));
+    }
+    assert_eq!(mux.session_count(), 2);
+
+    for (marker, _, remote) in &peers {
+        let track = remote.get_transceivers()[0].receiver().unwrap().track();
+        for _ in 0..10 {
+            let sample = tokio::time::timeout(Duration::from_secs(5), track.recv()).await??;
+            let MediaSample::Video(frame) = sample else {
+                panic!("expected a video sample");
+            };
+            assert!(
+                frame.data.iter().all(|b| b == marker),
+                "peer {marker} received media from the other session"
+            );
+        }
+    }
+
+    for task in senders {
+        task.abort();
+    }
+    for (_, muxed, remote) in peers {
+        muxed.close();
+        remote.close();
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use rustrtc::transports::ice::stun::{StunClass, StunMessage};
+use rustrtc::transports::sctp::{DataChannel, DataChannelConfig};
+use rustrtc::{DataChannelEvent, PeerConnection, RtcConfiguration};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+async fn recv_message(dc: &DataChannel) -> Result<Vec<u8>> {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            match dc.recv().await {
+                Some(DataChannelEvent::Message(data)) => return Ok(data.to_vec()),
+                Some(_) => continue,
+                None => anyhow::bail!("channel {} closed", dc.id),
+            }
+        }
+    })
+    .await?
+}
+
+/// A consent-freshness binding request arriving on the connected 5-tuple's
+/// socket is answered, and the DTLS/SCTP traffic sharing that socket keeps
+/// flowing in both directions.
+#[tokio::test]
+async fn binding_request_after_connect_is_answered_while_data_flows() -> Result<()> {
+    let config = || RtcConfiguration {
+        bind_ip: Some("127.0.0.1".to_string()),
+        ..Default::default()
+    };
+    let pc1 = PeerConnection::new(config());
+    let pc2 = PeerConnection::new(config());
+    let negotiated = Some(DataChannelConfig {
+        negotiated: Some(0),
+        ordered: true,
+        ..Default::default()
+    });
+    let dc1 = pc1.create_data_channel("consent", negotiated.clone())?;
+    let dc2 = pc2.create_data_channel("consent", negotiated)?;
+
+    let _ = pc1.create_offer().await?;
+    pc1.wait_for_gathering_complete().await;
+    let offer = pc1.create_offer().await?;
+    pc1.set_local_description(offer.clone())?;
+    pc2.set_remote_description(offer).await?;
+    let _ = pc2.create_answer().await?;
+    pc2.wait_for_gathering_complete().await;
+    let answer = pc2.create_answer().await?;
+    pc2.set_local_description(answer.clone())?;
+    pc1.set_remote_description(answer).await?;
+
+    for dc in [&dc1, &dc2] {
+        timeout(Duration::from_secs(10), async {
+            while !matches!(dc.recv().await, Some(DataChannelEvent::Open) | None) {}
+        })
+        .await?;
+    }
+
+    let target = pc2
+        .ice_transport()
+        .get_selected_pair()
+        .expect("connected pair")
+        .local
+        .address;
+    let prober = UdpSocket::bind("127.0.0.1:0").await?;
+    let mut buf = [0u8; 1500];
+
+    for round in 0..3u8 {
+        pc1.send_data(0, &[round; 64]).await?;
+
+        let transaction_id = [round; 12];
+        let request =
+            StunMessage::binding_request(transaction_id, Some("consent")).encode(None, true)?;
+        prober.send_to(&request, target).await?;
+
+        pc2.send_data(0, &[round; 32]).await?;
+
+        // The unknown source is learned as a peer-reflexive candidate, so
+        // pc2 may probe it back; skip those requests.
+        let response = timeout(Duration::from_secs(2), async {
+            loop {
+                let n = prober.recv(&mut buf).await?;
+                let msg = StunMessage::decode(&buf[..n])?;
+                if msg.class != StunClass::Request {
+                    return anyhow::Ok(msg);
+                }
+            }
+        })
+        .await??;
+        assert_eq!(response.class, StunClass::SuccessResponse);
+        assert_eq!(response.transaction_id, transaction_id);
+        assert_eq!(response.xor_mapped_address, Some(prober.local_addr()?));
+
+        assert_eq!(recv_message(&dc2).await?, vec![round; 64]);
+        assert_eq!(recv_message(&dc1).await?, vec![round; 32]);
+    }
+
+    pc1.close();
+    pc2.close();
+    Ok(())
+}